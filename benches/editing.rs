@@ -79,6 +79,25 @@ fn insert_char_with_clone_around(c: &mut Criterion) {
     });
 }
 
+fn append_line(c: &mut Criterion) {
+    let mut group = c.benchmark_group("append_line");
+
+    group.bench_function("large", |bench| {
+        let mut r = Rope::from(LARGE);
+        let orig_len = r.byte_len();
+        bench.iter(|| {
+            r.insert(
+                r.byte_len(),
+                "Lorem ipsum dolor sit amet, consectetur adipiscing elit.\n",
+            );
+
+            if r.byte_len() >= 2 * orig_len {
+                r = Rope::from(LARGE);
+            }
+        })
+    });
+}
+
 fn bench_delete(group: &mut BenchmarkGroup<WallTime>, delete_bytes: usize) {
     #[inline(always)]
     fn bench(bench: &mut Bencher, s: &str, delete_bytes: usize) {
@@ -194,6 +213,7 @@ criterion_group!(
     insert_sentence,
     insert_large,
     insert_char_with_clone_around,
+    append_line,
     delete_char,
     delete_sentence,
     delete_large,
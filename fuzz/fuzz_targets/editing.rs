@@ -70,5 +70,8 @@ fuzz_target!(|data: (StartingText, Vec<EditOp>)| {
         }
     }
 
+    let violations = rope.validate();
+    assert!(violations.is_empty(), "{violations:#?}");
+
     rope.assert_invariants();
 });
@@ -0,0 +1,337 @@
+use core::ops::{Add, AddAssign, Bound, Range, RangeBounds, Sub, SubAssign};
+
+use crate::rope::CHUNK_MAX_BYTES;
+use crate::tree::{
+    AsSlice,
+    BalancedLeaf,
+    BaseMeasured,
+    Metric,
+    ReplaceableLeaf,
+    SlicingMetric,
+    Summarize,
+};
+
+pub(super) const CHUNK_MIN_BYTES: usize = CHUNK_MAX_BYTES / 2;
+
+/// The leaves of the `Tree` backing a [`BytesRope`](super::BytesRope).
+///
+/// Unlike the chunks of a `Rope`, a `ByteChunk` can be split at any byte
+/// offset, and its summary only tracks its length.
+#[derive(Clone, Default)]
+pub(super) struct ByteChunk {
+    bytes: Vec<u8>,
+}
+
+impl core::fmt::Debug for ByteChunk {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl ByteChunk {
+    /// Splits the bytes into as many chunks as needed for none of them to be
+    /// too big, keeping the first one in `self` and returning the others.
+    #[inline]
+    fn split_overflowing(&mut self) -> Vec<Self> {
+        let total = self.bytes.len();
+
+        let chunks = (total + CHUNK_MAX_BYTES - 1) / CHUNK_MAX_BYTES;
+
+        let mut extras = Vec::with_capacity(chunks - 1);
+
+        // Split from the back so that every chunk gets roughly the same
+        // number of bytes.
+        for idx in (1..chunks).rev() {
+            let at = total * idx / chunks;
+            extras.push(Self { bytes: self.bytes.split_off(at) });
+        }
+
+        extras.reverse();
+
+        self.bytes.shrink_to(CHUNK_MAX_BYTES);
+
+        extras
+    }
+}
+
+/// A borrowed [`ByteChunk`].
+#[derive(Copy, Clone, Default)]
+pub(super) struct ByteChunkSlice<'a> {
+    pub(super) bytes: &'a [u8],
+}
+
+impl core::fmt::Debug for ByteChunkSlice<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.bytes.fmt(f)
+    }
+}
+
+impl<'a> From<ByteChunkSlice<'a>> for ByteChunk {
+    #[inline]
+    fn from(slice: ByteChunkSlice<'a>) -> Self {
+        let mut bytes = Vec::with_capacity(CHUNK_MAX_BYTES);
+        bytes.extend_from_slice(slice.bytes);
+        Self { bytes }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(super) struct ByteChunkSummary {
+    pub(super) bytes: usize,
+}
+
+impl Add<&Self> for ByteChunkSummary {
+    type Output = Self;
+
+    #[inline]
+    fn add(mut self, rhs: &Self) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl Sub<&Self> for ByteChunkSummary {
+    type Output = Self;
+
+    #[inline]
+    fn sub(mut self, rhs: &Self) -> Self {
+        self -= rhs;
+        self
+    }
+}
+
+impl AddAssign<&Self> for ByteChunkSummary {
+    #[inline]
+    fn add_assign(&mut self, rhs: &Self) {
+        self.bytes += rhs.bytes;
+    }
+}
+
+impl SubAssign<&Self> for ByteChunkSummary {
+    #[inline]
+    fn sub_assign(&mut self, rhs: &Self) {
+        self.bytes -= rhs.bytes;
+    }
+}
+
+impl Summarize for ByteChunk {
+    type Summary = ByteChunkSummary;
+
+    #[inline]
+    fn summarize(&self) -> ByteChunkSummary {
+        self.as_slice().summarize()
+    }
+}
+
+impl Summarize for ByteChunkSlice<'_> {
+    type Summary = ByteChunkSummary;
+
+    #[inline]
+    fn summarize(&self) -> ByteChunkSummary {
+        ByteChunkSummary { bytes: self.bytes.len() }
+    }
+}
+
+impl AsSlice for ByteChunk {
+    type Slice<'a> = ByteChunkSlice<'a>;
+
+    #[inline]
+    fn as_slice(&self) -> ByteChunkSlice<'_> {
+        ByteChunkSlice { bytes: &self.bytes }
+    }
+}
+
+impl BaseMeasured for ByteChunk {
+    type BaseMetric = ByteMetric;
+}
+
+impl BalancedLeaf for ByteChunk {
+    #[inline]
+    fn is_underfilled(&self, summary: &ByteChunkSummary) -> bool {
+        summary.bytes < CHUNK_MIN_BYTES
+    }
+
+    #[inline]
+    fn balance_leaves(
+        (left, left_summary): (&mut Self, &mut ByteChunkSummary),
+        (right, right_summary): (&mut Self, &mut ByteChunkSummary),
+    ) {
+        // The two leaves can be combined in a single chunk.
+        if left.bytes.len() + right.bytes.len() <= CHUNK_MAX_BYTES {
+            left.bytes.append(&mut right.bytes);
+        }
+        // Split the bytes evenly between the two chunks. Since there are
+        // more than `CHUNK_MAX_BYTES` of them neither ends up underfilled.
+        else {
+            let total = left.bytes.len() + right.bytes.len();
+
+            let mid = total / 2;
+
+            if left.bytes.len() > mid {
+                let moved = left.bytes.split_off(mid);
+                right.bytes.splice(0..0, moved);
+            } else {
+                let moved = mid - left.bytes.len();
+                left.bytes.extend(right.bytes.drain(..moved));
+            }
+        }
+
+        *left_summary = left.summarize();
+        *right_summary = right.summarize();
+    }
+}
+
+impl ReplaceableLeaf<ByteMetric> for ByteChunk {
+    type Replacement<'a> = &'a [u8];
+
+    type ExtraLeaves = alloc::vec::IntoIter<Self>;
+
+    #[inline]
+    fn replace<R>(
+        &mut self,
+        summary: &mut ByteChunkSummary,
+        range: R,
+        replace_with: &[u8],
+    ) -> Option<Self::ExtraLeaves>
+    where
+        R: RangeBounds<ByteMetric>,
+    {
+        let Range { start, end } = range_to_bytes(range, self.bytes.len());
+
+        self.bytes.splice(start..end, replace_with.iter().copied());
+
+        let extras = if self.bytes.len() > CHUNK_MAX_BYTES {
+            Some(self.split_overflowing().into_iter())
+        } else {
+            None
+        };
+
+        *summary = self.summarize();
+
+        extras
+    }
+
+    #[inline]
+    fn remove_up_to(
+        &mut self,
+        summary: &mut ByteChunkSummary,
+        ByteMetric(up_to): ByteMetric,
+    ) {
+        self.bytes.drain(..up_to);
+        *summary = self.summarize();
+    }
+
+    #[inline]
+    fn replace_fits(
+        &self,
+        summary: &ByteChunkSummary,
+        Range { start, end }: Range<ByteMetric>,
+        replace_with: &&[u8],
+    ) -> bool {
+        let new_len = summary.bytes - (end.0 - start.0) + replace_with.len();
+        (CHUNK_MIN_BYTES..=CHUNK_MAX_BYTES).contains(&new_len)
+    }
+}
+
+#[inline]
+fn range_to_bytes<R>(range: R, len: usize) -> Range<usize>
+where
+    R: RangeBounds<ByteMetric>,
+{
+    let start = match range.start_bound() {
+        Bound::Included(&ByteMetric(n)) => n,
+        Bound::Excluded(&ByteMetric(n)) => n + 1,
+        Bound::Unbounded => 0,
+    };
+
+    let end = match range.end_bound() {
+        Bound::Included(&ByteMetric(n)) => n + 1,
+        Bound::Excluded(&ByteMetric(n)) => n,
+        Bound::Unbounded => len,
+    };
+
+    start..end
+}
+
+/// A metric measuring the number of bytes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct ByteMetric(pub(super) usize);
+
+impl Add for ByteMetric {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl Sub for ByteMetric {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0)
+    }
+}
+
+impl AddAssign for ByteMetric {
+    #[inline]
+    fn add_assign(&mut self, other: Self) {
+        self.0 += other.0
+    }
+}
+
+impl SubAssign for ByteMetric {
+    #[inline]
+    fn sub_assign(&mut self, other: Self) {
+        self.0 -= other.0
+    }
+}
+
+impl Metric<ByteChunkSummary> for ByteMetric {
+    #[inline]
+    fn zero() -> Self {
+        Self(0)
+    }
+
+    #[inline]
+    fn one() -> Self {
+        Self(1)
+    }
+
+    #[inline]
+    fn measure(summary: &ByteChunkSummary) -> Self {
+        Self(summary.bytes)
+    }
+}
+
+impl SlicingMetric<ByteChunk> for ByteMetric {
+    #[inline]
+    fn slice_up_to<'a>(
+        slice: ByteChunkSlice<'a>,
+        ByteMetric(up_to): Self,
+        _: &ByteChunkSummary,
+    ) -> (ByteChunkSlice<'a>, ByteChunkSummary)
+    where
+        'a: 'a,
+    {
+        let left = ByteChunkSlice { bytes: &slice.bytes[..up_to] };
+        (left, left.summarize())
+    }
+
+    #[inline]
+    fn slice_from<'a>(
+        slice: ByteChunkSlice<'a>,
+        ByteMetric(from): Self,
+        _: &ByteChunkSummary,
+    ) -> (ByteChunkSlice<'a>, ByteChunkSummary)
+    where
+        'a: 'a,
+    {
+        let right = ByteChunkSlice { bytes: &slice.bytes[from..] };
+        (right, right.summarize())
+    }
+}
@@ -0,0 +1,423 @@
+use core::ops::RangeBounds;
+
+use super::byte_chunk::{ByteChunk, ByteChunkSlice, ByteMetric};
+use super::iterators::{ByteChunks, ByteIter};
+use super::BytesRopeSlice;
+use crate::range_bounds_to_start_end;
+use crate::rope::utils::panic_messages as panic;
+use crate::rope::CHUNK_MAX_BYTES;
+use crate::tree::Tree;
+
+#[cfg(any(test, feature = "arity_4"))]
+pub(super) const ARITY: usize = 4;
+
+#[cfg(not(any(test, feature = "arity_4")))]
+pub(super) const ARITY: usize = 16;
+
+/// A rope over arbitrary bytes, meant for binary data like the buffers of hex
+/// editors or the inputs of binary patch tools.
+///
+/// A `BytesRope` is backed by the same B-tree as [`Rope`](crate::Rope), so
+/// cloning it is cheap and inserting, deleting or replacing a range of bytes
+/// runs in logarithmic time. Unlike a `Rope` its contents don't have to be
+/// valid UTF-8 and it doesn't track lines, so it can be edited and sliced at
+/// any byte offset.
+///
+/// # Examples
+///
+/// ```
+/// # use crop::BytesRope;
+/// #
+/// let mut r = BytesRope::from(&[0x7f, b'E', b'L', b'F'][..]);
+///
+/// r.replace(1..4, [0xde, 0xad, 0xbe, 0xef]);
+/// assert_eq!(r, [0x7f, 0xde, 0xad, 0xbe, 0xef][..]);
+///
+/// r.delete(0..1);
+/// assert_eq!(r.byte(0), 0xde);
+/// assert_eq!(r.slice(2..), [0xbe, 0xef][..]);
+/// ```
+#[derive(Clone, Default)]
+pub struct BytesRope {
+    pub(super) tree: Tree<ARITY, ByteChunk>,
+}
+
+impl BytesRope {
+    /// Appends the bytes of `other` at the end of the `BytesRope`.
+    ///
+    /// This reuses the chunks of `other` and runs in time logarithmic in the
+    /// length of the two `BytesRope`s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::BytesRope;
+    /// #
+    /// let mut r = BytesRope::from(&[1, 2][..]);
+    ///
+    /// r.append(BytesRope::from(&[3, 4][..]));
+    /// assert_eq!(r, [1, 2, 3, 4][..]);
+    /// ```
+    #[inline]
+    pub fn append(&mut self, other: Self) {
+        self.tree.append(other.tree);
+    }
+
+    #[doc(hidden)]
+    pub fn assert_invariants(&self) {
+        self.tree.assert_invariants();
+    }
+
+    /// Returns the byte at `byte_index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte index is out of bounds (i.e. greater than or equal
+    /// to [`len()`](Self::len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::BytesRope;
+    /// #
+    /// let r = BytesRope::from(&[0xca, 0xfe][..]);
+    ///
+    /// assert_eq!(r.byte(0), 0xca);
+    /// assert_eq!(r.byte(1), 0xfe);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn byte(&self, byte_index: usize) -> u8 {
+        if byte_index >= self.len() {
+            panic::byte_index_out_of_bounds(byte_index, self.len());
+        }
+
+        let (chunk, ByteMetric(chunk_byte_offset)) =
+            self.tree.leaf_at_measure(ByteMetric(byte_index + 1));
+
+        chunk.bytes[byte_index - chunk_byte_offset]
+    }
+
+    /// Returns an iterator over the bytes of this `BytesRope`.
+    #[inline]
+    pub fn bytes(&self) -> ByteIter<'_> {
+        ByteIter::from(self.chunks())
+    }
+
+    /// Returns an iterator over the chunks of this `BytesRope`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::BytesRope;
+    /// #
+    /// let r = BytesRope::from(vec![0; 10_000]);
+    ///
+    /// assert_eq!(r.chunks().map(|chunk| chunk.len()).sum::<usize>(), 10_000);
+    /// ```
+    #[inline]
+    pub fn chunks(&self) -> ByteChunks<'_> {
+        ByteChunks::from(self)
+    }
+
+    /// Deletes the bytes in the given byte range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if its
+    /// end is out of bounds (i.e. greater than [`len()`](Self::len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::BytesRope;
+    /// #
+    /// let mut r = BytesRope::from(&[1, 2, 3, 4][..]);
+    ///
+    /// r.delete(1..3);
+    /// assert_eq!(r, [1, 4][..]);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn delete<R>(&mut self, byte_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        self.replace(byte_range, []);
+    }
+
+    /// Inserts `bytes` at the given byte offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`len()`](Self::len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::BytesRope;
+    /// #
+    /// let mut r = BytesRope::from(&[1, 4][..]);
+    ///
+    /// r.insert(1, [2, 3]);
+    /// assert_eq!(r, [1, 2, 3, 4][..]);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn insert<B>(&mut self, byte_offset: usize, bytes: B)
+    where
+        B: AsRef<[u8]>,
+    {
+        self.replace(byte_offset..byte_offset, bytes);
+    }
+
+    /// Returns `true` if the `BytesRope` is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the length of the `BytesRope` in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tree.summary().bytes
+    }
+
+    /// Creates a new, empty `BytesRope`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the bytes in the given byte range with `bytes`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if its
+    /// end is out of bounds (i.e. greater than [`len()`](Self::len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::BytesRope;
+    /// #
+    /// let mut r = BytesRope::from(&[1, 2, 3][..]);
+    ///
+    /// r.replace(1..2, [0xff, 0xff]);
+    /// assert_eq!(r, [1, 0xff, 0xff, 3][..]);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn replace<R, B>(&mut self, byte_range: R, bytes: B)
+    where
+        R: RangeBounds<usize>,
+        B: AsRef<[u8]>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.len());
+
+        if start > end {
+            panic::byte_start_after_end(start, end);
+        }
+
+        if end > self.len() {
+            panic::byte_offset_out_of_bounds(end, self.len());
+        }
+
+        self.tree.replace(ByteMetric(start)..ByteMetric(end), bytes.as_ref());
+    }
+
+    /// Returns an immutable slice of the `BytesRope` in the specified byte
+    /// range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if its
+    /// end is out of bounds (i.e. greater than [`len()`](Self::len())).
+    #[track_caller]
+    #[inline]
+    pub fn slice<R>(&self, byte_range: R) -> BytesRopeSlice<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.len());
+
+        if start > end {
+            panic::byte_start_after_end(start, end);
+        }
+
+        if end > self.len() {
+            panic::byte_offset_out_of_bounds(end, self.len());
+        }
+
+        BytesRopeSlice::from(
+            self.tree.slice(ByteMetric(start)..ByteMetric(end)),
+        )
+    }
+
+    /// Splits the `BytesRope` at the given byte offset, returning the bytes
+    /// after it and keeping the bytes before it.
+    ///
+    /// The two halves share the chunks that aren't cut by the split, so this
+    /// runs in time logarithmic in the length of the `BytesRope`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`len()`](Self::len())).
+    #[track_caller]
+    #[inline]
+    pub fn split_off(&mut self, byte_offset: usize) -> Self {
+        if byte_offset > self.len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.len());
+        }
+
+        Self { tree: self.tree.split_off(ByteMetric(byte_offset)) }
+    }
+
+    /// Copies the bytes of the `BytesRope` into a new `Vec`.
+    #[inline]
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut vec = Vec::with_capacity(self.len());
+        for chunk in self.chunks() {
+            vec.extend_from_slice(chunk);
+        }
+        vec
+    }
+}
+
+impl core::fmt::Debug for BytesRope {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_list().entries(self.bytes()).finish()
+    }
+}
+
+impl From<&[u8]> for BytesRope {
+    #[inline]
+    fn from(bytes: &[u8]) -> Self {
+        Self {
+            tree: Tree::from_leaves(bytes.chunks(CHUNK_MAX_BYTES).map(
+                |chunk| ByteChunk::from(ByteChunkSlice { bytes: chunk }),
+            )),
+        }
+    }
+}
+
+impl From<Vec<u8>> for BytesRope {
+    #[inline]
+    fn from(bytes: Vec<u8>) -> Self {
+        Self::from(&*bytes)
+    }
+}
+
+impl From<BytesRopeSlice<'_>> for BytesRope {
+    #[inline]
+    fn from(slice: BytesRopeSlice<'_>) -> Self {
+        Self { tree: Tree::from(slice.tree_slice) }
+    }
+}
+
+impl From<BytesRope> for Vec<u8> {
+    #[inline]
+    fn from(rope: BytesRope) -> Self {
+        rope.to_vec()
+    }
+}
+
+impl FromIterator<u8> for BytesRope {
+    #[inline]
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = u8>,
+    {
+        Self::from(iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+impl core::cmp::PartialEq<BytesRope> for BytesRope {
+    #[inline]
+    fn eq(&self, rhs: &BytesRope) -> bool {
+        self.len() == rhs.len()
+            && chunks_eq_chunks(self.chunks(), rhs.chunks())
+    }
+}
+
+impl core::cmp::Eq for BytesRope {}
+
+impl core::cmp::PartialEq<BytesRopeSlice<'_>> for BytesRope {
+    #[inline]
+    fn eq(&self, rhs: &BytesRopeSlice<'_>) -> bool {
+        self.len() == rhs.len()
+            && chunks_eq_chunks(self.chunks(), rhs.chunks())
+    }
+}
+
+impl core::cmp::PartialEq<[u8]> for BytesRope {
+    #[inline]
+    fn eq(&self, rhs: &[u8]) -> bool {
+        self.len() == rhs.len() && chunks_eq_bytes(self.chunks(), rhs)
+    }
+}
+
+impl core::cmp::PartialEq<Vec<u8>> for BytesRope {
+    #[inline]
+    fn eq(&self, rhs: &Vec<u8>) -> bool {
+        self == &**rhs
+    }
+}
+
+/// Returns `true` if the concatenation of the two sequences of chunks is
+/// the same, assuming they have the same total length.
+#[inline]
+pub(super) fn chunks_eq_chunks<'a, 'b>(
+    mut lhs: impl Iterator<Item = &'a [u8]>,
+    mut rhs: impl Iterator<Item = &'b [u8]>,
+) -> bool {
+    let mut left: &[u8] = &[];
+    let mut right: &[u8] = &[];
+
+    loop {
+        if left.is_empty() {
+            match lhs.next() {
+                Some(chunk) => left = chunk,
+                None => return true,
+            }
+        }
+
+        if right.is_empty() {
+            match rhs.next() {
+                Some(chunk) => right = chunk,
+                None => return true,
+            }
+        }
+
+        let len = left.len().min(right.len());
+
+        if left[..len] != right[..len] {
+            return false;
+        }
+
+        left = &left[len..];
+        right = &right[len..];
+    }
+}
+
+/// Returns `true` if the concatenation of the chunks is equal to `bytes`,
+/// assuming they have the same total length.
+#[inline]
+pub(super) fn chunks_eq_bytes<'a>(
+    chunks: impl Iterator<Item = &'a [u8]>,
+    mut bytes: &[u8],
+) -> bool {
+    for chunk in chunks {
+        if chunk != &bytes[..chunk.len()] {
+            return false;
+        }
+        bytes = &bytes[chunk.len()..];
+    }
+
+    true
+}
@@ -0,0 +1,154 @@
+use core::ops::RangeBounds;
+
+use super::byte_chunk::{ByteChunk, ByteMetric};
+use super::bytes_rope::{chunks_eq_bytes, chunks_eq_chunks, ARITY};
+use super::iterators::{ByteChunks, ByteIter};
+use super::BytesRope;
+use crate::range_bounds_to_start_end;
+use crate::rope::utils::panic_messages as panic;
+use crate::tree::TreeSlice;
+
+/// An immutable slice of a [`BytesRope`].
+#[derive(Copy, Clone)]
+pub struct BytesRopeSlice<'a> {
+    pub(super) tree_slice: TreeSlice<'a, ARITY, ByteChunk>,
+}
+
+impl<'a> BytesRopeSlice<'a> {
+    #[doc(hidden)]
+    pub fn assert_invariants(&self) {
+        self.tree_slice.assert_invariants();
+    }
+
+    /// Returns the byte at `byte_index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte index is out of bounds (i.e. greater than or equal
+    /// to [`len()`](Self::len())).
+    #[track_caller]
+    #[inline]
+    pub fn byte(&self, byte_index: usize) -> u8 {
+        if byte_index >= self.len() {
+            panic::byte_index_out_of_bounds(byte_index, self.len());
+        }
+
+        let (chunk, ByteMetric(chunk_byte_offset)) =
+            self.tree_slice.leaf_at_measure(ByteMetric(byte_index + 1));
+
+        chunk.bytes[byte_index - chunk_byte_offset]
+    }
+
+    /// Returns an iterator over the bytes of this `BytesRopeSlice`.
+    #[inline]
+    pub fn bytes(&self) -> ByteIter<'a> {
+        ByteIter::from(self.chunks())
+    }
+
+    /// Returns an iterator over the chunks of this `BytesRopeSlice`.
+    #[inline]
+    pub fn chunks(&self) -> ByteChunks<'a> {
+        ByteChunks::from(self)
+    }
+
+    /// Returns `true` if the `BytesRopeSlice` is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the length of the `BytesRopeSlice` in bytes.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tree_slice.summary().bytes
+    }
+
+    /// Returns an immutable slice of the `BytesRopeSlice` in the specified
+    /// byte range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if its
+    /// end is out of bounds (i.e. greater than [`len()`](Self::len())).
+    #[track_caller]
+    #[inline]
+    pub fn slice<R>(&self, byte_range: R) -> BytesRopeSlice<'a>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.len());
+
+        if start > end {
+            panic::byte_start_after_end(start, end);
+        }
+
+        if end > self.len() {
+            panic::byte_offset_out_of_bounds(end, self.len());
+        }
+
+        Self::from(self.tree_slice.slice(ByteMetric(start)..ByteMetric(end)))
+    }
+
+    /// Copies the bytes of the `BytesRopeSlice` into a new `Vec`.
+    #[inline]
+    pub fn to_vec(&self) -> Vec<u8> {
+        let mut vec = Vec::with_capacity(self.len());
+        for chunk in self.chunks() {
+            vec.extend_from_slice(chunk);
+        }
+        vec
+    }
+}
+
+impl<'a> From<TreeSlice<'a, ARITY, ByteChunk>> for BytesRopeSlice<'a> {
+    #[inline]
+    fn from(tree_slice: TreeSlice<'a, ARITY, ByteChunk>) -> Self {
+        Self { tree_slice }
+    }
+}
+
+impl From<BytesRopeSlice<'_>> for Vec<u8> {
+    #[inline]
+    fn from(slice: BytesRopeSlice<'_>) -> Self {
+        slice.to_vec()
+    }
+}
+
+impl core::fmt::Debug for BytesRopeSlice<'_> {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_list().entries(self.bytes()).finish()
+    }
+}
+
+impl core::cmp::PartialEq<BytesRopeSlice<'_>> for BytesRopeSlice<'_> {
+    #[inline]
+    fn eq(&self, rhs: &BytesRopeSlice<'_>) -> bool {
+        self.len() == rhs.len()
+            && chunks_eq_chunks(self.chunks(), rhs.chunks())
+    }
+}
+
+impl core::cmp::Eq for BytesRopeSlice<'_> {}
+
+impl core::cmp::PartialEq<BytesRope> for BytesRopeSlice<'_> {
+    #[inline]
+    fn eq(&self, rhs: &BytesRope) -> bool {
+        rhs == self
+    }
+}
+
+impl core::cmp::PartialEq<[u8]> for BytesRopeSlice<'_> {
+    #[inline]
+    fn eq(&self, rhs: &[u8]) -> bool {
+        self.len() == rhs.len() && chunks_eq_bytes(self.chunks(), rhs)
+    }
+}
+
+impl core::cmp::PartialEq<Vec<u8>> for BytesRopeSlice<'_> {
+    #[inline]
+    fn eq(&self, rhs: &Vec<u8>) -> bool {
+        self == &**rhs
+    }
+}
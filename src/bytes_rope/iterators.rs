@@ -0,0 +1,118 @@
+use super::byte_chunk::ByteChunk;
+use super::bytes_rope::ARITY;
+use super::{BytesRope, BytesRopeSlice};
+use crate::tree::Leaves;
+
+/// An iterator over the chunks of a [`BytesRope`] or [`BytesRopeSlice`].
+///
+/// This struct is created by the `chunks` method on
+/// [`BytesRope`](BytesRope::chunks()) and
+/// [`BytesRopeSlice`](BytesRopeSlice::chunks()). See their documentation for
+/// more.
+#[derive(Clone)]
+pub struct ByteChunks<'a> {
+    leaves: Leaves<'a, ARITY, ByteChunk>,
+}
+
+impl<'a> From<&'a BytesRope> for ByteChunks<'a> {
+    #[inline]
+    fn from(rope: &'a BytesRope) -> Self {
+        let mut leaves = rope.tree.leaves();
+        if rope.is_empty() {
+            let _ = leaves.next();
+        }
+        Self { leaves }
+    }
+}
+
+impl<'a> From<&BytesRopeSlice<'a>> for ByteChunks<'a> {
+    #[inline]
+    fn from(slice: &BytesRopeSlice<'a>) -> Self {
+        let mut leaves = slice.tree_slice.leaves();
+        if slice.is_empty() {
+            let _ = leaves.next();
+        }
+        Self { leaves }
+    }
+}
+
+impl<'a> Iterator for ByteChunks<'a> {
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.leaves.next().map(|chunk| chunk.bytes)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.leaves.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for ByteChunks<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.leaves.next_back().map(|chunk| chunk.bytes)
+    }
+}
+
+impl ExactSizeIterator for ByteChunks<'_> {}
+
+impl core::iter::FusedIterator for ByteChunks<'_> {}
+
+/// An iterator over the bytes of a [`BytesRope`] or [`BytesRopeSlice`].
+///
+/// This struct is created by the `bytes` method on
+/// [`BytesRope`](BytesRope::bytes()) and
+/// [`BytesRopeSlice`](BytesRopeSlice::bytes()). See their documentation for
+/// more.
+#[derive(Clone)]
+pub struct ByteIter<'a> {
+    chunks: ByteChunks<'a>,
+    forward: core::slice::Iter<'a, u8>,
+    backward: core::slice::Iter<'a, u8>,
+}
+
+impl<'a> From<ByteChunks<'a>> for ByteIter<'a> {
+    #[inline]
+    fn from(chunks: ByteChunks<'a>) -> Self {
+        Self { chunks, forward: [].iter(), backward: [].iter() }
+    }
+}
+
+impl Iterator for ByteIter<'_> {
+    type Item = u8;
+
+    #[inline]
+    fn next(&mut self) -> Option<u8> {
+        loop {
+            if let Some(&byte) = self.forward.next() {
+                return Some(byte);
+            }
+
+            match self.chunks.next() {
+                Some(chunk) => self.forward = chunk.iter(),
+                None => return self.backward.next().copied(),
+            }
+        }
+    }
+}
+
+impl DoubleEndedIterator for ByteIter<'_> {
+    #[inline]
+    fn next_back(&mut self) -> Option<u8> {
+        loop {
+            if let Some(&byte) = self.backward.next_back() {
+                return Some(byte);
+            }
+
+            match self.chunks.next_back() {
+                Some(chunk) => self.backward = chunk.iter(),
+                None => return self.forward.next_back().copied(),
+            }
+        }
+    }
+}
+
+impl core::iter::FusedIterator for ByteIter<'_> {}
@@ -0,0 +1,8 @@
+mod byte_chunk;
+mod bytes_rope;
+mod bytes_rope_slice;
+mod iterators;
+
+pub use bytes_rope::BytesRope;
+pub use bytes_rope_slice::BytesRopeSlice;
+pub use iterators::{ByteChunks, ByteIter};
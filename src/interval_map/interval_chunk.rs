@@ -0,0 +1,392 @@
+use core::ops::{Add, AddAssign, Bound, RangeBounds, Sub, SubAssign};
+
+use crate::tree::{
+    AsSlice,
+    BalancedLeaf,
+    BaseMeasured,
+    Metric,
+    ReplaceableLeaf,
+    Summarize,
+};
+
+#[cfg(any(test, feature = "small_chunks"))]
+pub(super) const CHUNK_MAX_ENTRIES: usize = 4;
+
+#[cfg(not(any(test, feature = "small_chunks")))]
+pub(super) const CHUNK_MAX_ENTRIES: usize = 32;
+
+pub(super) const CHUNK_MIN_ENTRIES: usize = CHUNK_MAX_ENTRIES / 2;
+
+/// A single interval of an [`IntervalMap`](super::IntervalMap).
+///
+/// The start of the interval is stored as the distance from the start of the
+/// previous interval in the map (or from zero if it's the first one), so that
+/// shifting all the intervals after an edit only requires updating the first
+/// one.
+#[derive(Clone)]
+pub(super) struct Entry<T> {
+    pub(super) gap: usize,
+    pub(super) len: usize,
+    pub(super) value: T,
+}
+
+/// The leaves of the `Tree` backing an [`IntervalMap`](super::IntervalMap),
+/// each one holding a run of consecutive intervals sorted by their start.
+#[derive(Clone)]
+pub(super) struct IntervalChunk<T> {
+    entries: Vec<Entry<T>>,
+}
+
+impl<T> Default for IntervalChunk<T> {
+    #[inline]
+    fn default() -> Self {
+        Self { entries: Vec::new() }
+    }
+}
+
+impl<T> core::fmt::Debug for IntervalChunk<T> {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        self.as_slice().fmt(f)
+    }
+}
+
+impl<T> IntervalChunk<T> {
+    /// Splits the entries into as many chunks as needed for none of them to
+    /// be too big, keeping the first one in `self` and returning the others.
+    #[inline]
+    fn split_overflowing(&mut self) -> Vec<Self> {
+        let total = self.entries.len();
+
+        let chunks = (total + CHUNK_MAX_ENTRIES - 1) / CHUNK_MAX_ENTRIES;
+
+        let mut extras = Vec::with_capacity(chunks - 1);
+
+        // Split from the back so that every chunk gets roughly the same
+        // number of entries.
+        for idx in (1..chunks).rev() {
+            let at = total * idx / chunks;
+            extras.push(Self { entries: self.entries.split_off(at) });
+        }
+
+        extras.reverse();
+
+        extras
+    }
+}
+
+/// A borrowed [`IntervalChunk`].
+pub(super) struct ChunkSlice<'a, T> {
+    pub(super) entries: &'a [Entry<T>],
+}
+
+impl<T> Copy for ChunkSlice<'_, T> {}
+
+impl<T> Clone for ChunkSlice<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Default for ChunkSlice<'_, T> {
+    #[inline]
+    fn default() -> Self {
+        Self { entries: &[] }
+    }
+}
+
+impl<T> core::fmt::Debug for ChunkSlice<'_, T> {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_list()
+            .entries(self.entries.iter().map(|entry| (entry.gap, entry.len)))
+            .finish()
+    }
+}
+
+impl<'a, T> From<ChunkSlice<'a, T>> for IntervalChunk<T>
+where
+    T: Clone,
+{
+    #[inline]
+    fn from(slice: ChunkSlice<'a, T>) -> Self {
+        Self { entries: slice.entries.to_vec() }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub(super) struct IntervalSummary {
+    /// The number of intervals.
+    entries: usize,
+
+    /// The sum of the gaps of the intervals, i.e. the start of the last
+    /// interval relative to the start of the interval before the first one.
+    span: usize,
+}
+
+impl Add<&Self> for IntervalSummary {
+    type Output = Self;
+
+    #[inline]
+    fn add(mut self, rhs: &Self) -> Self {
+        self += rhs;
+        self
+    }
+}
+
+impl Sub<&Self> for IntervalSummary {
+    type Output = Self;
+
+    #[inline]
+    fn sub(mut self, rhs: &Self) -> Self {
+        self -= rhs;
+        self
+    }
+}
+
+impl AddAssign<&Self> for IntervalSummary {
+    #[inline]
+    fn add_assign(&mut self, rhs: &Self) {
+        self.entries += rhs.entries;
+        self.span += rhs.span;
+    }
+}
+
+impl SubAssign<&Self> for IntervalSummary {
+    #[inline]
+    fn sub_assign(&mut self, rhs: &Self) {
+        self.entries -= rhs.entries;
+        self.span -= rhs.span;
+    }
+}
+
+impl<T> Summarize for IntervalChunk<T> {
+    type Summary = IntervalSummary;
+
+    #[inline]
+    fn summarize(&self) -> IntervalSummary {
+        self.as_slice().summarize()
+    }
+}
+
+impl<T> Summarize for ChunkSlice<'_, T> {
+    type Summary = IntervalSummary;
+
+    #[inline]
+    fn summarize(&self) -> IntervalSummary {
+        IntervalSummary {
+            entries: self.entries.len(),
+            span: self.entries.iter().map(|entry| entry.gap).sum(),
+        }
+    }
+}
+
+impl<T> AsSlice for IntervalChunk<T> {
+    type Slice<'a>
+        = ChunkSlice<'a, T>
+    where
+        T: 'a;
+
+    #[inline]
+    fn as_slice(&self) -> ChunkSlice<'_, T> {
+        ChunkSlice { entries: &self.entries }
+    }
+}
+
+impl<T> BaseMeasured for IntervalChunk<T> {
+    type BaseMetric = EntryMetric;
+}
+
+impl<T: Clone + 'static> BalancedLeaf for IntervalChunk<T> {
+    #[inline]
+    fn is_underfilled(&self, summary: &IntervalSummary) -> bool {
+        summary.entries < CHUNK_MIN_ENTRIES
+    }
+
+    #[inline]
+    fn balance_leaves(
+        (left, left_summary): (&mut Self, &mut IntervalSummary),
+        (right, right_summary): (&mut Self, &mut IntervalSummary),
+    ) {
+        // The two leaves can be combined in a single chunk.
+        if left.entries.len() + right.entries.len() <= CHUNK_MAX_ENTRIES {
+            left.entries.append(&mut right.entries);
+        }
+        // Split the entries evenly between the two chunks. Since there are
+        // more than `CHUNK_MAX_ENTRIES` of them neither ends up underfilled.
+        else {
+            let total = left.entries.len() + right.entries.len();
+
+            let mid = total / 2;
+
+            if left.entries.len() > mid {
+                let moved = left.entries.split_off(mid);
+                right.entries.splice(0..0, moved);
+            } else {
+                let moved = mid - left.entries.len();
+                left.entries.extend(right.entries.drain(..moved));
+            }
+        }
+
+        *left_summary = left.summarize();
+        *right_summary = right.summarize();
+    }
+}
+
+impl<T: Clone + 'static> ReplaceableLeaf<EntryMetric> for IntervalChunk<T> {
+    type Replacement<'a> = Vec<Entry<T>>;
+
+    type ExtraLeaves = alloc::vec::IntoIter<Self>;
+
+    #[inline]
+    fn replace<R>(
+        &mut self,
+        summary: &mut IntervalSummary,
+        range: R,
+        replace_with: Vec<Entry<T>>,
+    ) -> Option<Self::ExtraLeaves>
+    where
+        R: RangeBounds<EntryMetric>,
+    {
+        let start = match range.start_bound() {
+            Bound::Included(&EntryMetric(n)) => n,
+            Bound::Excluded(&EntryMetric(n)) => n + 1,
+            Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(&EntryMetric(n)) => n + 1,
+            Bound::Excluded(&EntryMetric(n)) => n,
+            Bound::Unbounded => self.entries.len(),
+        };
+
+        self.entries.splice(start..end, replace_with);
+
+        let extras = if self.entries.len() > CHUNK_MAX_ENTRIES {
+            Some(self.split_overflowing().into_iter())
+        } else {
+            None
+        };
+
+        *summary = self.summarize();
+
+        extras
+    }
+
+    #[inline]
+    fn remove_up_to(
+        &mut self,
+        summary: &mut IntervalSummary,
+        up_to: EntryMetric,
+    ) {
+        self.replace(summary, ..up_to, Vec::new());
+    }
+}
+
+/// A metric measuring the number of intervals.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct EntryMetric(pub(super) usize);
+
+/// A metric measuring the sum of the gaps between the starts of the
+/// intervals, which is used to find intervals by their byte offset.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(super) struct SpanMetric(pub(super) usize);
+
+impl Add for EntryMetric {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl Sub for EntryMetric {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0)
+    }
+}
+
+impl AddAssign for EntryMetric {
+    #[inline]
+    fn add_assign(&mut self, other: Self) {
+        self.0 += other.0
+    }
+}
+
+impl SubAssign for EntryMetric {
+    #[inline]
+    fn sub_assign(&mut self, other: Self) {
+        self.0 -= other.0
+    }
+}
+
+impl Metric<IntervalSummary> for EntryMetric {
+    #[inline]
+    fn zero() -> Self {
+        Self(0)
+    }
+
+    #[inline]
+    fn one() -> Self {
+        Self(1)
+    }
+
+    #[inline]
+    fn measure(summary: &IntervalSummary) -> Self {
+        Self(summary.entries)
+    }
+}
+
+impl Add for SpanMetric {
+    type Output = Self;
+
+    #[inline]
+    fn add(self, other: Self) -> Self {
+        Self(self.0 + other.0)
+    }
+}
+
+impl Sub for SpanMetric {
+    type Output = Self;
+
+    #[inline]
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 - other.0)
+    }
+}
+
+impl AddAssign for SpanMetric {
+    #[inline]
+    fn add_assign(&mut self, other: Self) {
+        self.0 += other.0
+    }
+}
+
+impl SubAssign for SpanMetric {
+    #[inline]
+    fn sub_assign(&mut self, other: Self) {
+        self.0 -= other.0
+    }
+}
+
+impl Metric<IntervalSummary> for SpanMetric {
+    #[inline]
+    fn zero() -> Self {
+        Self(0)
+    }
+
+    #[inline]
+    fn one() -> Self {
+        Self(1)
+    }
+
+    #[inline]
+    fn measure(summary: &IntervalSummary) -> Self {
+        Self(summary.span)
+    }
+}
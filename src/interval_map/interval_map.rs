@@ -0,0 +1,465 @@
+use core::ops::Range;
+
+use super::interval_chunk::{
+    Entry,
+    EntryMetric,
+    IntervalChunk,
+    SpanMetric,
+    CHUNK_MAX_ENTRIES,
+    CHUNK_MIN_ENTRIES,
+};
+use super::Intervals;
+use crate::tree::Tree;
+
+#[cfg(any(test, feature = "arity_4"))]
+pub(super) const ARITY: usize = 4;
+
+#[cfg(not(any(test, feature = "arity_4")))]
+pub(super) const ARITY: usize = 16;
+
+/// A map from byte ranges to values, meant to be kept next to a
+/// [`Rope`](crate::Rope) to track things like diagnostics, folds or
+/// highlighted spans as the text is edited.
+///
+/// The intervals are stored sorted by their start in the same B-tree that
+/// powers `Rope`, each one relative to the start of the previous one. This
+/// means that after an edit only the intervals touching the edited range
+/// have to be updated, while all the ones after it are shifted by updating a
+/// single interval, so both [`insert()`](Self::insert()) and
+/// [`apply_delta()`](Self::apply_delta()) run in logarithmic time in the
+/// number of intervals plus the number of intervals close to the edit.
+///
+/// Intervals can overlap and the same range can be inserted multiple times.
+///
+/// # Examples
+///
+/// ```
+/// # use crop::{IntervalMap, Rope};
+/// let mut rope = Rope::from("fn main() { let x = 1; }");
+///
+/// let mut diagnostics = IntervalMap::new();
+/// diagnostics.insert(16..17, "unused variable");
+///
+/// // Keep the map in sync with the rope.
+/// rope.insert(12, "let y = 0; ");
+/// diagnostics.apply_delta(12..12, "let y = 0; ".len());
+///
+/// let (range, message) = diagnostics.iter().next().unwrap();
+/// assert_eq!(rope.byte_slice(range), "x");
+/// assert_eq!(*message, "unused variable");
+/// ```
+pub struct IntervalMap<T> {
+    tree: Tree<ARITY, IntervalChunk<T>>,
+
+    /// An upper bound on the length of the intervals in the map.
+    ///
+    /// Every interval overlapping an offset has to start at most `max_len`
+    /// bytes before it, which bounds how far back we need to look when
+    /// querying or editing the map. It's only recomputed from scratch by
+    /// [`retain()`](Self::retain()).
+    max_len: usize,
+}
+
+impl<T> Clone for IntervalMap<T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self { tree: self.tree.clone(), max_len: self.max_len }
+    }
+}
+
+impl<T: core::fmt::Debug> core::fmt::Debug for IntervalMap<T> {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+impl<T> Default for IntervalMap<T> {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Clone + 'static> FromIterator<(Range<usize>, T)> for IntervalMap<T> {
+    #[inline]
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = (Range<usize>, T)>,
+    {
+        let mut intervals = iter.into_iter().collect::<Vec<_>>();
+
+        intervals.sort_by_key(|(range, _)| range.start);
+
+        let mut map = Self::new();
+        map.rebuild(intervals);
+        map
+    }
+}
+
+impl<T> IntervalMap<T> {
+    /// Updates the intervals of the map after the text in the `replaced`
+    /// byte range of the companion `Rope` was replaced by `inserted_len`
+    /// bytes, i.e. after a call to
+    /// [`Rope::replace(replaced, text)`](crate::Rope::replace()) where
+    /// `text.len() == inserted_len`.
+    ///
+    /// The intervals after the replaced range are shifted, the ones
+    /// containing it grow or shrink to include the inserted text, and the
+    /// ones partially overlapping it are cut to the part that's left of the
+    /// original text. Text inserted right at the start of an interval ends
+    /// up before it, while text inserted right at its end is not included
+    /// in it. An interval contained in the replaced range collapses to an
+    /// empty interval right after the inserted text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::IntervalMap;
+    /// let mut map = IntervalMap::new();
+    /// map.insert(0..4, 'a');
+    /// map.insert(2..8, 'b');
+    /// map.insert(10..12, 'c');
+    ///
+    /// // Replace the bytes in 3..6 with 1 byte.
+    /// map.apply_delta(3..6, 1);
+    ///
+    /// assert_eq!(
+    ///     map.iter().collect::<Vec<_>>(),
+    ///     [(0..3, &'a'), (2..6, &'b'), (8..10, &'c')]
+    /// );
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn apply_delta(&mut self, replaced: Range<usize>, inserted_len: usize)
+    where
+        T: Clone + 'static,
+    {
+        let Range { start, end } = replaced;
+
+        if start > end {
+            start_after_end(start, end);
+        }
+
+        let deleted_len = end - start;
+
+        let map_start = |offset: usize| {
+            if offset < start {
+                offset
+            } else if offset <= end {
+                start + inserted_len
+            } else {
+                offset - deleted_len + inserted_len
+            }
+        };
+
+        let map_end = |offset: usize| {
+            if offset <= start {
+                offset
+            } else if offset <= end {
+                start
+            } else {
+                offset - deleted_len + inserted_len
+            }
+        };
+
+        // The intervals starting before this offset can't reach the
+        // replaced range.
+        let from = start.saturating_sub(self.max_len);
+
+        let (first_idx, intervals) = self.intervals_from(from, None);
+
+        let mut last_start = intervals.start;
+
+        let mut updated = Vec::new();
+
+        let mut max_len = self.max_len;
+
+        for (range, value) in intervals {
+            let new_start = map_start(range.start);
+
+            let new_end = map_end(range.end).max(new_start);
+
+            updated.push(Entry {
+                gap: new_start - last_start,
+                len: new_end - new_start,
+                value: value.clone(),
+            });
+
+            last_start = new_start;
+
+            max_len = max_len.max(new_end - new_start);
+
+            // The intervals after this one are stored relative to it, so
+            // they're shifted together with it.
+            if range.start > end {
+                break;
+            }
+        }
+
+        if updated.is_empty() {
+            return;
+        }
+
+        let last_idx = first_idx + updated.len();
+
+        self.max_len = max_len;
+
+        self.tree
+            .replace(EntryMetric(first_idx)..EntryMetric(last_idx), updated);
+    }
+
+    #[doc(hidden)]
+    pub fn assert_invariants(&self) {
+        self.tree.assert_invariants();
+
+        if self.tree.leaf_count() > 1 {
+            for leaf in self.tree.leaves() {
+                assert!(leaf.entries.len() >= CHUNK_MIN_ENTRIES);
+                assert!(leaf.entries.len() <= CHUNK_MAX_ENTRIES);
+            }
+        }
+
+        for (range, _) in self.iter() {
+            assert!(range.len() <= self.max_len);
+        }
+    }
+
+    /// Removes all the intervals from the map.
+    #[inline]
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Inserts an interval in the map.
+    ///
+    /// The interval is placed after all the intervals starting at or before
+    /// its start, so intervals with the same start are iterated over in the
+    /// order in which they were inserted.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::IntervalMap;
+    /// let mut map = IntervalMap::new();
+    /// map.insert(4..8, "foo");
+    /// map.insert(0..2, "bar");
+    /// map.insert(4..6, "baz");
+    ///
+    /// assert_eq!(
+    ///     map.iter().collect::<Vec<_>>(),
+    ///     [(0..2, &"bar"), (4..8, &"foo"), (4..6, &"baz")]
+    /// );
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn insert(&mut self, range: Range<usize>, value: T)
+    where
+        T: Clone + 'static,
+    {
+        if range.start > range.end {
+            start_after_end(range.start, range.end);
+        }
+
+        let (idx, mut intervals) = self.intervals_from(range.start + 1, None);
+
+        let mut inserted = vec![Entry {
+            gap: range.start - intervals.start,
+            len: range.len(),
+            value,
+        }];
+
+        // The interval after the new one is now stored relative to it.
+        if let Some((next, next_value)) = intervals.next() {
+            inserted.push(Entry {
+                gap: next.start - range.start,
+                len: next.len(),
+                value: next_value.clone(),
+            });
+        }
+
+        self.max_len = self.max_len.max(range.len());
+
+        let end = idx + inserted.len() - 1;
+
+        self.tree.replace(EntryMetric(idx)..EntryMetric(end), inserted);
+    }
+
+    /// Returns an iterator over the first interval starting at or after the
+    /// given byte offset and all the ones after it, together with the index
+    /// of that interval.
+    #[inline]
+    fn intervals_from(
+        &self,
+        offset: usize,
+        overlapping: Option<Range<usize>>,
+    ) -> (usize, Intervals<'_, T>) {
+        let mut cursor = self.tree.cursor();
+
+        let last_start = self.tree.measure::<SpanMetric>().0;
+
+        // All the intervals start before the offset, so we position the
+        // iterator right after the last one.
+        if offset > last_start {
+            let len = self.len();
+            let leaf = cursor.seek(EntryMetric(len));
+            let entries = &leaf.entries[leaf.entries.len()..];
+            return (
+                len,
+                Intervals::new(cursor, entries, last_start, overlapping),
+            );
+        }
+
+        let mut entries = cursor.seek(SpanMetric(offset)).entries;
+
+        let mut start = cursor.offset::<SpanMetric>().0;
+
+        let mut idx = cursor.offset::<EntryMetric>().0;
+
+        // The leaf we landed on is the first one whose last interval starts
+        // at or after the offset, so the interval we're looking for is in
+        // it.
+        while let Some((entry, rest)) = entries.split_first() {
+            if start + entry.gap >= offset {
+                break;
+            }
+
+            start += entry.gap;
+            idx += 1;
+            entries = rest;
+        }
+
+        (idx, Intervals::new(cursor, entries, start, overlapping))
+    }
+
+    /// Returns `true` if the map doesn't contain any intervals.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns an iterator over the intervals of the map and their values,
+    /// sorted by the start of the intervals.
+    #[inline]
+    pub fn iter(&self) -> Intervals<'_, T> {
+        self.intervals_from(0, None).1
+    }
+
+    /// Returns the number of intervals in the map.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.tree.measure::<EntryMetric>().0
+    }
+
+    /// Creates a new, empty `IntervalMap`.
+    #[inline]
+    pub fn new() -> Self {
+        Self { tree: Tree::default(), max_len: 0 }
+    }
+
+    /// Returns an iterator over the intervals overlapping the given byte
+    /// range and their values, sorted by the start of the intervals.
+    ///
+    /// Like with `Range`s, an interval overlaps the range if it contains at
+    /// least one of its bytes, so empty intervals and empty ranges never
+    /// overlap anything.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::IntervalMap;
+    /// let mut map = IntervalMap::new();
+    /// map.insert(0..10, 'a');
+    /// map.insert(3..5, 'b');
+    /// map.insert(5..7, 'c');
+    ///
+    /// assert_eq!(
+    ///     map.overlapping(4..5).collect::<Vec<_>>(),
+    ///     [(0..10, &'a'), (3..5, &'b')]
+    /// );
+    /// ```
+    #[inline]
+    pub fn overlapping(&self, range: Range<usize>) -> Intervals<'_, T> {
+        let from = range.start.saturating_sub(self.max_len);
+        self.intervals_from(from, Some(range)).1
+    }
+
+    /// Replaces the contents of the map with the given intervals, which
+    /// have to be sorted by their start.
+    #[inline]
+    fn rebuild<I>(&mut self, intervals: I)
+    where
+        I: IntoIterator<Item = (Range<usize>, T)>,
+        T: Clone + 'static,
+    {
+        let mut last_start = 0;
+
+        let mut max_len = 0;
+
+        let entries = intervals
+            .into_iter()
+            .map(|(range, value)| {
+                if range.start > range.end {
+                    start_after_end(range.start, range.end);
+                }
+
+                let gap = range.start - last_start;
+                last_start = range.start;
+                max_len = max_len.max(range.len());
+                Entry { gap, len: range.len(), value }
+            })
+            .collect::<Vec<_>>();
+
+        self.tree = Tree::default();
+        self.tree.replace(EntryMetric(0)..EntryMetric(0), entries);
+        self.max_len = max_len;
+    }
+
+    /// Retains only the intervals for which the predicate returns `true`.
+    ///
+    /// This rebuilds the whole map, so it runs in linear time in the number
+    /// of intervals.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::IntervalMap;
+    /// let mut map = IntervalMap::new();
+    /// map.insert(0..10, 'a');
+    /// map.insert(3..5, 'b');
+    /// map.insert(5..5, 'c');
+    ///
+    /// map.retain(|range, _| !range.is_empty());
+    ///
+    /// assert_eq!(map.iter().collect::<Vec<_>>(), [(0..10, &'a'), (3..5, &'b')]);
+    /// ```
+    #[inline]
+    pub fn retain<F>(&mut self, mut f: F)
+    where
+        F: FnMut(Range<usize>, &T) -> bool,
+        T: Clone + 'static,
+    {
+        let kept = self
+            .iter()
+            .filter(|(range, value)| f(range.clone(), value))
+            .map(|(range, value)| (range, value.clone()))
+            .collect::<Vec<_>>();
+
+        self.rebuild(kept);
+    }
+}
+
+#[track_caller]
+#[cold]
+#[inline(never)]
+fn start_after_end(start: usize, end: usize) -> ! {
+    panic!("byte start after end: the start is {start} but the end is {end}");
+}
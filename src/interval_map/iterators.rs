@@ -0,0 +1,99 @@
+use core::ops::Range;
+
+use super::interval_chunk::{Entry, IntervalChunk};
+use super::interval_map::ARITY;
+use crate::tree::Cursor;
+
+/// An iterator over the intervals of an [`IntervalMap`](crate::IntervalMap)
+/// and their values, sorted by the start of the intervals.
+///
+/// This struct is created by the [`iter()`](crate::IntervalMap::iter()) and
+/// [`overlapping()`](crate::IntervalMap::overlapping()) methods on
+/// [`IntervalMap`](crate::IntervalMap). See their documentation for more.
+pub struct Intervals<'a, T> {
+    /// The cursor over the leaves of the map's tree, sitting on the leaf
+    /// containing the next interval.
+    cursor: Cursor<'a, ARITY, IntervalChunk<T>>,
+
+    /// The intervals of the current leaf which haven't been yielded yet.
+    entries: core::slice::Iter<'a, Entry<T>>,
+
+    /// The start of the last interval that was yielded (or skipped).
+    pub(super) start: usize,
+
+    /// If set, only the intervals overlapping this range are yielded.
+    overlapping: Option<Range<usize>>,
+
+    /// Whether we've gone past the end of the `overlapping` range.
+    done: bool,
+}
+
+impl<T> Clone for Intervals<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            cursor: self.cursor.clone(),
+            entries: self.entries.clone(),
+            start: self.start,
+            overlapping: self.overlapping.clone(),
+            done: self.done,
+        }
+    }
+}
+
+impl<'a, T> Intervals<'a, T> {
+    #[inline]
+    pub(super) fn new(
+        cursor: Cursor<'a, ARITY, IntervalChunk<T>>,
+        entries: &'a [Entry<T>],
+        start: usize,
+        overlapping: Option<Range<usize>>,
+    ) -> Self {
+        Self {
+            cursor,
+            entries: entries.iter(),
+            start,
+            overlapping,
+            done: false,
+        }
+    }
+}
+
+impl<'a, T> Iterator for Intervals<'a, T> {
+    type Item = (Range<usize>, &'a T);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+
+        loop {
+            let Some(entry) = self.entries.next() else {
+                self.entries = self.cursor.next_leaf()?.entries.iter();
+                continue;
+            };
+
+            self.start += entry.gap;
+
+            let range = self.start..self.start + entry.len;
+
+            if let Some(overlapping) = &self.overlapping {
+                // The intervals are sorted by their start, so none of the
+                // next ones can overlap the range either.
+                if range.start >= overlapping.end {
+                    self.done = true;
+                    return None;
+                }
+
+                if range.end <= overlapping.start {
+                    continue;
+                }
+            }
+
+            return Some((range, &entry.value));
+        }
+    }
+}
+
+impl<T> core::iter::FusedIterator for Intervals<'_, T> {}
@@ -0,0 +1,6 @@
+mod interval_chunk;
+mod interval_map;
+mod iterators;
+
+pub use interval_map::IntervalMap;
+pub use iterators::Intervals;
@@ -7,19 +7,27 @@
 //! complexity of inserting, deleting or replacing a piece of text is always
 //! logarithmic in the size of the `Rope`.
 //!
-//! The crate has a relatively straightforward API. There are 3 structs to be
+//! The crate has a relatively straightforward API. There are 6 structs to be
 //! aware of:
 //!
 //! - [`Rope`]: the star of the crate;
 //! - [`RopeSlice`]: an immutable slice of a `Rope`;
-//! - [`RopeBuilder`]: an incremental `Rope` builder.
-//!
-//! plus the [`iter`] module which contains iterators over `Rope`s and
-//! `RopeSlice`s. That's it.
+//! - [`RopeBuilder`]: an incremental `Rope` builder;
+//! - [`IntervalMap`]: a map from byte ranges to values which can be kept in
+//!   sync with a `Rope` as it's edited;
+//! - [`BytesRope`]: a rope over arbitrary bytes instead of UTF-8 text, with
+//!   its [`BytesRopeSlice`];
+//! - [`GapBuffer`]: the fixed-capacity buffer the text of a `Rope` is stored
+//!   in, which can also be used on its own to edit short texts like
+//!   single-line inputs, with its [`GapSlice`].
+//!
+//! plus the [`iter`] module which contains iterators over `Rope`s,
+//! `RopeSlice`s, `IntervalMap`s and `BytesRope`s. That's it.
 //!
 //! # Example usage
 //!
 //! ```no_run
+//! # #[cfg(feature = "lines")] {
 //! # use std::fs::{File};
 //! # use std::io::{BufWriter, Write};
 //! # use std::thread;
@@ -98,6 +106,7 @@
 //! })
 //! .join()
 //! .unwrap();
+//! # }
 //! ```
 //!
 //! # On offsets and indexes
@@ -130,14 +139,200 @@
 //!
 //! - `simd` (enabled by default): enables SIMD on supported platforms;
 //!
+//! - `lines` (enabled by default): tracks the number of line breaks of every
+//!   chunk and internal node of the B-tree, which all the line-oriented APIs
+//!   (e.g. [`Rope::line()`], [`Rope::lines()`] or the [`patch`] module) are
+//!   built on. Disabling it compiles those APIs out and makes building and
+//!   editing `Rope`s that are only ever addressed by byte offsets, like
+//!   binary-ish logs or network buffers, a bit faster and leaner;
+//!
+//! - `arbitrary` and `proptest` (disabled by default): implement the
+//!   `Arbitrary` traits of the [arbitrary](https://docs.rs/arbitrary) and
+//!   [proptest](https://docs.rs/proptest) crates for `Rope`s. The generated
+//!   `Rope`s are built in a variety of ways (e.g. by editing or by slicing
+//!   larger `Rope`s) to cover many different tree shapes;
+//!
+//! - `char-metric` (disabled by default): makes the `Rope` and `RopeSlice`
+//!   track the number of `char`s of their text, which enables the
+//!   `char`-indexed APIs like [`Rope::char()`] and [`Rope::char_len()`] and
+//!   lets [`Rope::is_ascii()`] answer in constant time. Checking whether an
+//!   offset is a `char` or grapheme boundary also gets a shortcut on ASCII
+//!   text;
+//!
+//! - `compression` (disabled by default): adds a [`Rope::compress()`]
+//!   method which compresses the chunks of a `Rope` with
+//!   [LZ4](https://docs.rs/lz4_flex). Compressed chunks are decompressed
+//!   lazily when they're read, which can dramatically lower the memory usage
+//!   of huge `Rope`s of which only a small part is ever looked at, like logs.
+//!   Its `lz4_flex` dependency needs Rust 1.81 or later;
+//!
+//! - `custom-metric` (disabled by default): lets a `Rope` measure its text
+//!   by the weight its [`CharWeight`] type parameter gives every `char`,
+//!   e.g. to count curly braces or non-ASCII bytes. The sum of the weights
+//!   is stored in the B-tree next to the byte and line counts, and `Rope`s
+//!   and `RopeSlice`s get methods to measure it, convert it to and from byte
+//!   offsets and slice by it in logarithmic time;
+//!
+//! - `fingerprint` (disabled by default): stores a polynomial hash of the
+//!   text of every chunk and internal node of the B-tree, so that
+//!   [`Rope::fingerprint()`] runs in constant time and comparing two `Rope`s
+//!   with different texts almost never has to look at them. Like with
+//!   `max-line-len` the summaries along an edited path are added back up
+//!   from their children;
+//!
+//! - `grapheme-chunks` (disabled by default): keeps the boundaries between
+//!   the chunks of a `Rope` from splitting extended grapheme clusters, so
+//!   that the text of every chunk (i.e. every pair of segments yielded by
+//!   [`Rope::chunk_segments()`]) can be segmented on its own without looking
+//!   at its neighbors. Editing gets slightly slower, and clusters longer than
+//!   an eighth of a chunk can still be split. Implies `graphemes`;
+//!
+//! - `grapheme-count` (disabled by default): stores the number of extended
+//!   grapheme clusters of every chunk and internal node of the B-tree, so
+//!   that [`Rope::grapheme_len()`] doesn't have to segment the whole text.
+//!   The few boundaries between chunks around which the clusters can't be
+//!   counted from the two sides alone (e.g. inside emoji sequences) are
+//!   counted too, and only the text after those is segmented again. Like
+//!   with `max-line-len` the summaries along an edited path are added back
+//!   up from their children. Implies `graphemes`;
+//!
 //! - `graphemes` (disabled by default): enables a few grapheme-oriented APIs
-//! on `Rope`s and `RopeSlice`s such as the
-//! [`Graphemes`](crate::iter::Graphemes) iterator and others;
+//!   on `Rope`s and `RopeSlice`s such as the
+//!   [`Graphemes`](crate::iter::Graphemes) iterator and others;
+//!
+//! - `max-line-len` (disabled by default): stores the lengths of the first,
+//!   last and longest lines of every chunk and internal node of the B-tree,
+//!   so that [`Rope::max_line_len()`] (and `max_line_width()` with
+//!   `width-metric`) runs in constant time. These can't be subtracted from
+//!   one another, so the summaries of the nodes along an edited path are
+//!   added back up from their children instead, which makes editing a bit
+//!   slower. Implies `lines`;
+//!
+//! - `node-pool` (disabled by default): recycles the memory of the nodes of
+//!   the B-tree through a small thread-local pool instead of freeing it right
+//!   away, which reduces the number of calls to the global allocator when
+//!   making many small edits in a row, e.g. while typing or replaying a
+//!   macro;
+//!
+//! - `paragraph-count` (disabled by default): stores the number of paragraphs
+//!   (i.e. runs of non-blank lines) of every chunk and internal node of the
+//!   B-tree, together with whether their first and last lines are blank, so
+//!   that [`Rope::paragraph_len()`] runs in constant time and
+//!   [`Rope::byte_of_paragraph()`] and [`Rope::paragraph_slice()`] in
+//!   logarithmic time. Like with `max-line-len` the summaries along an edited
+//!   path are added back up from their children. Implies `lines`;
+//!
+//! - `ropey` (disabled by default): implements `From` conversions between
+//!   crop's `Rope`s and `RopeSlice`s and [ropey](https://docs.rs/ropey)'s
+//!   `Rope`s and `RopeSlice`s, which copy the text chunk by chunk;
+//!
+//! - `serde` (disabled by default): implements `Serialize` for `Rope`s and
+//!   `RopeSlice`s and `Deserialize` for `Rope`s. Both are (de)serialized as
+//!   plain strings;
+//!
+//! - `tree-sitter` (disabled by default): implements tree-sitter's
+//!   `TextProvider` trait for `&Rope` and `RopeSlice`, and adds a
+//!   `tree_sitter_callback()` method to both which can be used to parse them
+//!   with `Parser::parse_with_options()`;
+//!
+//! - `u32-summaries` (disabled by default): stores the number of bytes, line
+//!   breaks and UTF-16 code units of every chunk and internal node as `u32`s
+//!   instead of `usize`s, which roughly halves the metadata of the B-tree on
+//!   64-bit targets. Editing a `Rope` so that it would contain more than
+//!   `u32::MAX` bytes panics;
+//!
+//! - `unicode-collation` (disabled by default): adds `collate()` methods to
+//!   `Rope`s and `RopeSlice`s which compare their text according to the
+//!   sorting rules of a language, using the collators of the
+//!   [icu_collator](https://docs.rs/icu_collator) crate;
+//!
+//! - `unicode-normalization` (disabled by default): adds `is_normalized()`
+//!   and `normalize()` methods to `Rope`s and `RopeSlice`s which check and
+//!   convert their text to one of the Unicode normalization forms, using the
+//!   [unicode-normalization](https://docs.rs/unicode-normalization) crate;
 //!
 //! - `utf16-metric` (disabled by default): makes the `Rope` and `RopeSlice`
-//! track the UTF-16 code units they'd have if their content was stored as
-//! UTF-16 instead of UTF-8, allowing them to efficiently convert UTF-16
-//! code unit offsets to and from byte offsets in logarithmic time.
+//!   track the UTF-16 code units they'd have if their content was stored as
+//!   UTF-16 instead of UTF-8, allowing them to efficiently convert UTF-16
+//!   code unit offsets to and from byte offsets in logarithmic time;
+//!
+//! - `validate-utf8` (disabled by default): re-checks the whole `Rope` after
+//!   every edit, panicking if any of the invariants reported by
+//!   [`Rope::validate()`] doesn't hold, e.g. if a chunk isn't valid UTF-8,
+//!   is split in the middle of a code point or has a byte or line count that
+//!   doesn't match a recount of its text. Whether a `Rope` ends with a line
+//!   break is always derived from its last chunk, so it can't go stale. This
+//!   makes every edit linear in the length of the `Rope`, so it's only meant
+//!   to be enabled while testing or fuzzing code built on crop;
+//!
+//! - `width-metric` (disabled by default): makes the `Rope` and `RopeSlice`
+//!   track the display width of their text as given by the
+//!   [unicode-width](https://docs.rs/unicode-width) crate, so that the width
+//!   of a line and the byte offset of a visual column can be computed in
+//!   logarithmic time;
+//!
+//! - `word-count` (disabled by default): stores the number of words of every
+//!   chunk and internal node of the B-tree, together with whether their text
+//!   starts and ends in the middle of a word, so that [`Rope::word_len()`]
+//!   runs in constant time. Like with `max-line-len` the summaries along an
+//!   edited path are added back up from their children.
+//!
+//! # Choosing the metrics
+//!
+//! Every chunk and internal node of the B-tree stores a summary of its text,
+//! which is what makes converting between offsets and slicing by them
+//! logarithmic. The components of the summary are chosen at compile time,
+//! so a `Rope` only pays for the metrics it can be queried for:
+//!
+//! | Metric            | Stored when                              |
+//! |-------------------|------------------------------------------|
+//! | bytes             | always                                   |
+//! | `char`s           | the `char-metric` feature is enabled     |
+//! | line breaks       | the `lines` feature is enabled           |
+//! | UTF-16 code units | the `utf16-metric` feature is enabled    |
+//! | display width     | the `width-metric` feature is enabled    |
+//! | custom weights    | the `custom-metric` feature is enabled   |
+//! | longest line      | the `max-line-len` feature is enabled    |
+//! | words             | the `word-count` feature is enabled      |
+//! | paragraphs        | the `paragraph-count` feature is enabled |
+//! | grapheme clusters | the `grapheme-count` feature is enabled  |
+//! | text fingerprint  | the `fingerprint` feature is enabled     |
+//!
+//! The features decide which components the summaries can have. Which of
+//! the line break, `char`, UTF-16 and width components a rope actually
+//! keeps is chosen by the [`MetricSet`] parameter of [`RopeWith`], which
+//! defaults to [`AllMetrics`]: each of its associated types is either
+//! [`Maintained`] or [`Omitted`]. An omitted component takes no room in the
+//! summaries and is never counted when the rope is edited, and the methods
+//! measuring the rope in its units don't exist for that rope type, so
+//! calling one of them is a compile error. A metric needed by only some of
+//! the ropes of a program thus doesn't slow down or grow the others. The set
+//! comes after the chunk size parameters, which have to be spelled out to
+//! use it.
+//!
+//! The type also decides how the custom metric weighs the text and where
+//! the lines end: those are the [`CharWeight`] and [`LineBreaks`] parameters
+//! of [`RopeWith`], which default to [`ZeroWeight`] and [`LfBreaks`] for
+//! `Rope`. A rope built with [`UnicodeBreaks`] also breaks
+//! its lines on VT, FF, NEL, LS and PS. Ropes with different weights or line
+//! breaks can be used in the same program, each one counting its own in its
+//! summaries.
+//!
+//! # Chunk size
+//!
+//! The text of a `Rope` is stored in chunks of up to 2KB each. A different
+//! size can be chosen with the `MAX_BYTES` parameter of [`RopeWith`], e.g.
+//! `RopeWith<AtomicCounter, ZeroWeight, LfBreaks, 512>`, to any number of
+//! bytes between 4 and 65535. Smaller chunks make editing cheaper, while
+//! larger chunks make the rope more compact and are faster to build and
+//! iterate over.
+//!
+//! Edits can leave chunks only partially filled. A chunk holding less than
+//! 25% of its capacity is merged with or refilled from one of its neighbors,
+//! and this threshold can be changed with the `MIN_FILL` parameter of
+//! `RopeWith` to a percentage between 1 and 50. Higher values keep the
+//! chunks fuller, which favors read-heavy workloads, while lower values move
+//! text between chunks less often, which favors write-heavy ones.
 
 #![allow(clippy::explicit_auto_deref)]
 #![allow(clippy::module_inception)]
@@ -152,23 +347,73 @@ pub mod iter {
     //! Iterators over [`Rope`](crate::Rope)s and
     //! [`RopeSlice`](crate::RopeSlice)s.
 
+    pub use crate::bytes_rope::{ByteChunks, ByteIter};
+    pub use crate::interval_map::Intervals;
     pub use crate::rope::iterators::*;
+    #[cfg(feature = "tree-sitter")]
+    pub use crate::rope::tree_sitter::NodeChunks;
 }
 
+#[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+#[cfg(feature = "lines")]
+pub mod patch {
+    //! Parsing unified diffs and applying them to [`Rope`](crate::Rope)s.
+
+    pub use crate::rope::patch::{Patch, PatchError};
+}
+
+mod bytes_rope;
+mod interval_map;
 mod rope;
 
-#[doc(hidden)]
 pub mod tree;
 
-// These are not part of the public API, we only export them to be able to run
+pub use bytes_rope::{BytesRope, BytesRopeSlice};
+pub use interval_map::IntervalMap;
+// This is not part of the public API, we only export it to be able to run
 // doctests.
 #[doc(hidden)]
+pub use rope::metrics::ChunkSummary;
+pub use rope::metrics::{
+    AllMetrics,
+    CharWeight,
+    Component,
+    Maintained,
+    MetricSet,
+    Omitted,
+    ZeroWeight,
+};
+#[cfg(feature = "unicode-normalization")]
+pub use rope::NormalizationForm;
+pub use rope::{gap_buffer::GapBuffer, gap_slice::GapSlice};
 pub use rope::{
-    gap_buffer::GapBuffer,
-    gap_slice::GapSlice,
-    metrics::ChunkSummary,
+    Anchor,
+    AsciiCaseText,
+    BlockSignature,
+    CheckReport,
+    Delta,
+    DeltaOp,
+    Error,
+    Gravity,
+    JoinItem,
+    LfBreaks,
+    LineBreaks,
+    LineEnding,
+    LocalRope,
+    Rope,
+    RopeBuilder,
+    RopeReader,
+    RopeSlice,
+    RopeStats,
+    RopeWith,
+    RopeWriter,
+    SearchPattern,
+    Signature,
+    Snap,
+    Tracked,
+    UnicodeBreaks,
+    Versioned,
 };
-pub use rope::{Rope, RopeBuilder, RopeSlice};
 
 #[inline]
 pub(crate) fn range_bounds_to_start_end<T, B>(
@@ -178,18 +423,21 @@ pub(crate) fn range_bounds_to_start_end<T, B>(
 ) -> (usize, usize)
 where
     B: core::ops::RangeBounds<T>,
-    T: core::ops::Add<usize, Output = usize> + Into<usize> + Copy,
+    T: Into<usize> + Copy,
 {
     use core::ops::Bound;
 
+    // Turning a bound of `usize::MAX` into an exclusive one would overflow,
+    // but since no text can be that long saturating it still gets it
+    // rejected as out of bounds (or the start as being after the end).
     let start = match range.start_bound() {
         Bound::Included(&n) => n.into(),
-        Bound::Excluded(&n) => n + 1,
+        Bound::Excluded(&n) => n.into().saturating_add(1),
         Bound::Unbounded => lo,
     };
 
     let end = match range.end_bound() {
-        Bound::Included(&n) => n + 1,
+        Bound::Included(&n) => n.into().saturating_add(1),
         Bound::Excluded(&n) => n.into(),
         Bound::Unbounded => hi,
     };
@@ -0,0 +1,179 @@
+use alloc::sync::Arc;
+use core::ops::Range;
+
+/// A handle to a byte offset in a [`Tracked`](crate::Tracked) rope which
+/// follows the edits made to it.
+///
+/// Anchors are created with [`Tracked::anchor()`](crate::Tracked::anchor())
+/// and resolved with
+/// [`Tracked::anchor_offset()`](crate::Tracked::anchor_offset()). Every edit
+/// made through the `Tracked` updates the offsets of its anchors so that
+/// they keep pointing to the same place in the text, which makes them
+/// suited to represent cursors, bookmarks, diagnostics and the like.
+///
+/// An `Anchor` is only meaningful for the `Tracked` that created it (and for
+/// its clones, which share its anchors as they were at the time of the
+/// clone). Any other `Tracked` doesn't resolve it.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Anchor {
+    /// The owner id of the [`Anchors`] that created it.
+    owner: usize,
+
+    /// Its id among the anchors created by its owner.
+    id: u64,
+}
+
+/// The side an [`Anchor`] sticks to when text is inserted at its offset.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Gravity {
+    /// The anchor stays before the inserted text, i.e. it sticks to the
+    /// character on its left.
+    Left,
+
+    /// The anchor moves after the inserted text, i.e. it sticks to the
+    /// character on its right.
+    Right,
+}
+
+#[derive(Copy, Clone, Debug)]
+struct Entry {
+    anchor: Anchor,
+    byte_offset: usize,
+    gravity: Gravity,
+}
+
+/// The anchors of a [`Tracked`](crate::Tracked) rope.
+///
+/// The entries are shared between clones of the same `Tracked` and only
+/// copied when one of them is edited, so that cloning it stays cheap.
+#[derive(Debug)]
+pub(super) struct Anchors {
+    /// The anchors, sorted by their owner and then by their id.
+    entries: Arc<Vec<Entry>>,
+
+    /// An id different from the ones of all the other `Anchors`, including
+    /// clones, with which the anchors created here are tagged so that the
+    /// ones created elsewhere aren't mistaken for them.
+    ///
+    /// Owner ids only ever grow, so the anchors created here sort after all
+    /// the ones inherited from the `Anchors` this was cloned from.
+    owner: usize,
+
+    /// The id of the next anchor to be created. Ids are never reused, so an
+    /// `Anchor` that's been removed can't end up resolving to a different
+    /// one.
+    next_id: u64,
+}
+
+impl Clone for Anchors {
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            entries: Arc::clone(&self.entries),
+            owner: next_owner(),
+            next_id: 0,
+        }
+    }
+}
+
+impl Default for Anchors {
+    #[inline]
+    fn default() -> Self {
+        Self { entries: Arc::default(), owner: next_owner(), next_id: 0 }
+    }
+}
+
+impl Anchors {
+    /// Updates the offsets of the anchors after the bytes in `replaced`
+    /// have been replaced by `inserted_len` new bytes.
+    #[inline]
+    pub(super) fn apply_edit(
+        &mut self,
+        replaced: Range<usize>,
+        inserted_len: usize,
+    ) {
+        if self.entries.is_empty() {
+            return;
+        }
+
+        for entry in Arc::make_mut(&mut self.entries) {
+            entry.byte_offset = map_offset(
+                entry.byte_offset,
+                entry.gravity,
+                replaced.clone(),
+                inserted_len,
+            );
+        }
+    }
+
+    #[inline]
+    pub(super) fn byte_offset(&self, anchor: Anchor) -> Option<usize> {
+        self.position(anchor).map(|idx| self.entries[idx].byte_offset)
+    }
+
+    #[inline]
+    pub(super) fn insert(
+        &mut self,
+        byte_offset: usize,
+        gravity: Gravity,
+    ) -> Anchor {
+        let anchor = Anchor { owner: self.owner, id: self.next_id };
+        self.next_id += 1;
+        Arc::make_mut(&mut self.entries).push(Entry {
+            anchor,
+            byte_offset,
+            gravity,
+        });
+        anchor
+    }
+
+    #[inline]
+    fn position(&self, anchor: Anchor) -> Option<usize> {
+        self.entries.binary_search_by_key(&anchor, |entry| entry.anchor).ok()
+    }
+
+    #[inline]
+    pub(super) fn remove(&mut self, anchor: Anchor) -> Option<usize> {
+        let idx = self.position(anchor)?;
+        Some(Arc::make_mut(&mut self.entries).remove(idx).byte_offset)
+    }
+}
+
+/// Returns a new owner id, greater than the ones of all the `Anchors` created
+/// before.
+#[inline]
+fn next_owner() -> usize {
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    static NEXT_OWNER: AtomicUsize = AtomicUsize::new(0);
+
+    NEXT_OWNER.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Returns where a byte offset with the given gravity ends up after the bytes
+/// in `replaced` have been replaced by `inserted_len` new bytes.
+///
+/// The offsets before the start of the edit and after its end are simply
+/// shifted, while the ones within it are moved to the start of the inserted
+/// text if they have [`Gravity::Left`] or to its end if they have
+/// [`Gravity::Right`].
+#[inline]
+pub(super) fn map_offset(
+    byte_offset: usize,
+    gravity: Gravity,
+    replaced: Range<usize>,
+    inserted_len: usize,
+) -> usize {
+    let Range { start, end } = replaced;
+
+    if byte_offset < start {
+        byte_offset
+    } else if byte_offset > end {
+        byte_offset - (end - start) + inserted_len
+    } else {
+        match gravity {
+            Gravity::Left => start,
+            Gravity::Right => start + inserted_len,
+        }
+    }
+}
@@ -0,0 +1,214 @@
+//! `Arbitrary` implementations for `Rope`s, enabled by the `arbitrary` and
+//! `proptest` features.
+//!
+//! The text of a generated `Rope` is arbitrary, but so is the way the `Rope`
+//! is put together: the same text can be passed to `Rope::from()`, fed to a
+//! `RopeBuilder` in pieces, inserted piece by piece at either end, carved out
+//! of a larger `Rope` by deleting the text in between the pieces, or
+//! converted from a slice of a slice of a larger `Rope`. This results in a
+//! wide range of tree shapes, including underfilled leaves and the ragged
+//! edges left behind by slicing.
+
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use super::utils::adjust_split_point;
+use super::{RopeBuilder, RopeWith};
+use crate::tree::RefCounter;
+
+/// The text surrounding the pieces that are deleted or sliced away.
+const PADDING: &str = "Lorem ipsum dolor sit amet, consectetur adipiscing \
+                       elit, sed do eiusmod tempor incididunt ut labore et \
+                       dolore magna aliqua.\nUt enim ad minim veniam, quis \
+                       nostrud exercitation ullamco laboris nisi ut aliquip \
+                       ex ea commodo consequat.\r\n";
+
+/// The different ways a `Rope` can be built from its text.
+#[derive(Copy, Clone)]
+enum Shape {
+    FromStr,
+    Builder,
+    Appends,
+    Prepends,
+    Deletions,
+    SliceOfSlice,
+}
+
+impl Shape {
+    #[inline]
+    fn from_u8(n: u8) -> Self {
+        match n % 6 {
+            0 => Self::FromStr,
+            1 => Self::Builder,
+            2 => Self::Appends,
+            3 => Self::Prepends,
+            4 => Self::Deletions,
+            _ => Self::SliceOfSlice,
+        }
+    }
+}
+
+/// Splits the text at the given offsets, which are clamped to the length of
+/// the text and moved to the previous char boundary.
+#[inline]
+fn split_pieces<'a>(text: &'a str, offsets: &[u16]) -> Vec<&'a str> {
+    let mut offsets = offsets
+        .iter()
+        .map(|&offset| adjust_split_point::<false>(text, offset as usize))
+        .collect::<Vec<_>>();
+
+    offsets.sort_unstable();
+    offsets.dedup();
+
+    let mut pieces = Vec::with_capacity(offsets.len() + 1);
+    let mut start = 0;
+
+    for offset in offsets {
+        pieces.push(&text[start..offset]);
+        start = offset;
+    }
+
+    pieces.push(&text[start..]);
+
+    pieces
+}
+
+/// Builds a `Rope` containing `text`, with the tree shape determined by the
+/// other arguments.
+#[inline]
+fn build_rope<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    text: &str,
+    offsets: &[u16],
+    shape: u8,
+) -> RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+    let pieces = split_pieces(text, offsets);
+
+    match Shape::from_u8(shape) {
+        Shape::FromStr => RopeWith::from(text),
+
+        Shape::Builder => {
+            let mut builder = RopeBuilder::default();
+            for piece in pieces {
+                builder.append(piece);
+            }
+            builder.build()
+        },
+
+        Shape::Appends => {
+            let mut rope = RopeWith::new();
+            for piece in pieces {
+                rope.insert(rope.byte_len(), piece);
+            }
+            rope
+        },
+
+        Shape::Prepends => {
+            let mut rope = RopeWith::new();
+            for piece in pieces.into_iter().rev() {
+                rope.insert(0, piece);
+            }
+            rope
+        },
+
+        Shape::Deletions => {
+            let mut padded = String::new();
+            for piece in &pieces {
+                padded.push_str(piece);
+                padded.push_str(PADDING);
+            }
+
+            let mut rope = RopeWith::from(padded);
+            let mut end = rope.byte_len();
+
+            for piece in pieces.into_iter().rev() {
+                rope.delete(end - PADDING.len()..end);
+                end -= PADDING.len() + piece.len();
+            }
+
+            rope
+        },
+
+        Shape::SliceOfSlice => {
+            let rope = RopeWith::<C, W, B, MAX_BYTES, MIN_FILL, S>::from(
+                format!("{PADDING}{text}{PADDING}"),
+            );
+            let half = PADDING.len() / 2;
+            let slice = rope.byte_slice(half..);
+            RopeWith::from(
+                slice
+                    .byte_slice(PADDING.len() - half..)
+                    .byte_slice(..text.len()),
+            )
+        },
+    }
+}
+
+#[cfg(feature = "arbitrary")]
+#[cfg_attr(docsrs, doc(cfg(feature = "arbitrary")))]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > ::arbitrary::Arbitrary<'a>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn arbitrary(
+        u: &mut ::arbitrary::Unstructured<'a>,
+    ) -> ::arbitrary::Result<Self> {
+        let shape = u.arbitrary::<u8>()?;
+        let offsets = u.arbitrary::<Vec<u16>>()?;
+        let text = u.arbitrary::<&str>()?;
+        Ok(build_rope(text, &offsets, shape))
+    }
+}
+
+#[cfg(feature = "proptest")]
+#[cfg_attr(docsrs, doc(cfg(feature = "proptest")))]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > ::proptest::arbitrary::Arbitrary
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type Parameters = ();
+    type Strategy = ::proptest::strategy::BoxedStrategy<Self>;
+
+    #[inline]
+    fn arbitrary_with(_: Self::Parameters) -> Self::Strategy {
+        use proptest::prelude::*;
+
+        // Line breaks are over-represented so that the line metrics are
+        // exercised too.
+        let ch = prop_oneof![
+            8 => any::<char>(),
+            2 => Just('\n'),
+            1 => Just('\r'),
+        ];
+
+        let text = ::proptest::collection::vec(ch, 0..4096)
+            .prop_map(|chars| chars.into_iter().collect::<String>());
+
+        let offsets = ::proptest::collection::vec(any::<u16>(), 0..32);
+
+        (text, offsets, any::<u8>())
+            .prop_map(|(text, offsets, shape)| {
+                build_rope(&text, &offsets, shape)
+            })
+            .boxed()
+    }
+}
@@ -0,0 +1,171 @@
+//! ASCII case-insensitive comparisons between `Rope`s, `RopeSlice`s and
+//! string slices.
+
+use super::iterators::Chunks;
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use super::{RopeSlice, RopeWith};
+use crate::tree::RefCounter;
+
+/// A text that a `Rope` or a `RopeSlice` can be compared with by
+/// [`Rope::eq_ignore_ascii_case()`](super::Rope::eq_ignore_ascii_case()) and
+/// [`RopeSlice::eq_ignore_ascii_case()`].
+///
+/// This trait is implemented for `&str`, `&String`, `&Rope` and `RopeSlice`,
+/// and can't be implemented outside of this crate.
+pub trait AsciiCaseText<'a>: sealed::Sealed {
+    #[doc(hidden)]
+    type Chunks: Iterator<Item = &'a str>;
+
+    #[doc(hidden)]
+    fn byte_len(&self) -> usize;
+
+    #[doc(hidden)]
+    fn text_chunks(self) -> Self::Chunks;
+}
+
+mod sealed {
+    use crate::rope::metrics::{CharWeight, MetricSet};
+    use crate::rope::LineBreaks;
+    use crate::tree::RefCounter;
+
+    pub trait Sealed {}
+
+    impl Sealed for &str {}
+    impl Sealed for &String {}
+    impl<
+            C: RefCounter,
+            W: CharWeight,
+            B: LineBreaks,
+            const MAX_BYTES: usize,
+            const MIN_FILL: usize,
+            S: MetricSet,
+        > Sealed for &super::RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+    {
+    }
+    impl<
+            C: RefCounter,
+            W: CharWeight,
+            B: LineBreaks,
+            const MAX_BYTES: usize,
+            const MIN_FILL: usize,
+            S: MetricSet,
+        > Sealed for super::RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    {
+    }
+}
+
+impl<'a> AsciiCaseText<'a> for &'a str {
+    type Chunks = core::iter::Once<&'a str>;
+
+    #[inline]
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn text_chunks(self) -> Self::Chunks {
+        core::iter::once(self)
+    }
+}
+
+impl<'a> AsciiCaseText<'a> for &'a String {
+    type Chunks = core::iter::Once<&'a str>;
+
+    #[inline]
+    fn byte_len(&self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn text_chunks(self) -> Self::Chunks {
+        core::iter::once(self.as_str())
+    }
+}
+
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > AsciiCaseText<'a> for &'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type Chunks = Chunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>;
+
+    #[inline]
+    fn byte_len(&self) -> usize {
+        RopeWith::byte_len(self)
+    }
+
+    #[inline]
+    fn text_chunks(self) -> Self::Chunks {
+        self.chunks()
+    }
+}
+
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > AsciiCaseText<'a> for RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type Chunks = Chunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>;
+
+    #[inline]
+    fn byte_len(&self) -> usize {
+        RopeSlice::byte_len(self)
+    }
+
+    #[inline]
+    fn text_chunks(self) -> Self::Chunks {
+        self.chunks()
+    }
+}
+
+/// Checks whether the texts yielded by two iterators of chunks are equal
+/// when ignoring the ASCII case, like `str::eq_ignore_ascii_case()` would.
+///
+/// Changing the ASCII case of a character doesn't change its length, so
+/// it's assumed that both iterators yield the same number of bytes. The
+/// chunks can split the text at different points, so we compare the common
+/// prefix of the current chunks and advance whichever runs out first.
+#[inline]
+pub(super) fn chunks_eq_ignore_ascii_case<'a, 'b>(
+    mut lhs: impl Iterator<Item = &'a str>,
+    mut rhs: impl Iterator<Item = &'b str>,
+) -> bool {
+    let mut left_chunk: &[u8] = &[];
+    let mut right_chunk: &[u8] = &[];
+
+    loop {
+        if left_chunk.is_empty() {
+            match lhs.next() {
+                Some(chunk) => left_chunk = chunk.as_bytes(),
+                None => return true,
+            }
+        }
+
+        if right_chunk.is_empty() {
+            match rhs.next() {
+                Some(chunk) => right_chunk = chunk.as_bytes(),
+                None => return true,
+            }
+        }
+
+        let len = left_chunk.len().min(right_chunk.len());
+
+        if !left_chunk[..len].eq_ignore_ascii_case(&right_chunk[..len]) {
+            return false;
+        }
+
+        left_chunk = &left_chunk[len..];
+        right_chunk = &right_chunk[len..];
+    }
+}
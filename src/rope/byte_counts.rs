@@ -0,0 +1,299 @@
+//! This module exports the [`ByteCounts`] struct, which gathers the line
+//! feeds, `char`s and UTF-16 code units of a string in a single pass over its
+//! bytes.
+//!
+//! All three can be told apart by looking at one byte at a time:
+//!
+//! - a line feed is the `0x0A` byte;
+//!
+//! - every `char` starts with a byte that's not a continuation byte, i.e. one
+//!   outside of `0x80..=0xBF`;
+//!
+//! - every `char` takes one UTF-16 code unit, except the ones encoded with
+//!   four bytes which take two, and whose first byte is `0xF0` or above.
+//!
+//! On x86_64 with the `simd` feature the bytes are compared 16 at a time
+//! using SSE2 instructions, which every x86_64 CPU supports, and the matches
+//! are added up in vector registers. Everywhere else they're compared one
+//! machine word at a time using bitwise tricks. Either way the few bytes
+//! left at the end are looked at one by one.
+
+use core::marker::PhantomData;
+
+use super::metrics::sealed::Sealed as _;
+use super::metrics::MetricSet;
+
+/// The counts gathered by a single pass over the bytes of a string, for the
+/// components of the summaries maintained by the [`MetricSet`] `S`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub(super) struct ByteCounts<S> {
+    /// The number of `\n` bytes.
+    pub(super) line_feeds: usize,
+
+    /// The number of `char`s.
+    pub(super) chars: usize,
+
+    /// The number of `char`s encoded with four bytes.
+    pub(super) four_byte_chars: usize,
+
+    marker: PhantomData<S>,
+}
+
+impl<S: MetricSet> ByteCounts<S> {
+    /// Whether the line feeds have to be counted.
+    const COUNT_LINE_FEEDS: bool =
+        cfg!(feature = "lines") && S::Lines::IS_MAINTAINED;
+
+    /// Whether the `char`s have to be counted.
+    const COUNT_CHARS: bool = (cfg!(feature = "char-metric")
+        && S::Chars::IS_MAINTAINED)
+        || Self::COUNT_FOUR_BYTE_CHARS;
+
+    /// Whether the four byte `char`s have to be counted.
+    const COUNT_FOUR_BYTE_CHARS: bool =
+        cfg!(feature = "utf16-metric") && S::Utf16CodeUnits::IS_MAINTAINED;
+
+    /// Counts the line feeds, `char`s and four byte `char`s of the string.
+    ///
+    /// The line feeds are only counted if the `lines` feature is enabled,
+    /// the `char`s if the `char-metric` or `utf16-metric` features are, and
+    /// the four byte ones if `utf16-metric` is, each of them only if `S`
+    /// maintains a component of the summaries they're needed for. Otherwise
+    /// they're always zero.
+    #[inline]
+    pub(super) fn of(s: &str) -> Self {
+        let mut counts = Self::default();
+
+        for &byte in counts.add_blocks(s.as_bytes()) {
+            counts.add_byte(byte);
+        }
+
+        counts
+    }
+
+    /// Returns the number of UTF-16 code units of the string.
+    #[cfg(feature = "utf16-metric")]
+    #[inline]
+    pub(super) fn utf16_code_units(&self) -> usize {
+        self.chars + self.four_byte_chars
+    }
+
+    #[inline(always)]
+    fn add_byte(&mut self, byte: u8) {
+        if Self::COUNT_LINE_FEEDS {
+            self.line_feeds += (byte == b'\n') as usize;
+        }
+
+        if Self::COUNT_CHARS {
+            self.chars += !is_continuation(byte) as usize;
+        }
+
+        if Self::COUNT_FOUR_BYTE_CHARS {
+            self.four_byte_chars += (byte >= 0xF0) as usize;
+        }
+    }
+
+    /// Adds the counts of all the 16 byte blocks at the start of `bytes`,
+    /// returning the bytes after them.
+    #[cfg(all(feature = "simd", target_arch = "x86_64", not(miri)))]
+    #[inline]
+    fn add_blocks<'a>(&mut self, bytes: &'a [u8]) -> &'a [u8] {
+        use core::arch::x86_64::*;
+
+        /// The number of blocks whose matches can be added up in the 8-bit
+        /// lanes of a vector before they could overflow.
+        const MAX_BLOCKS_PER_BATCH: usize = u8::MAX as usize;
+
+        /// Adds up the 16 lanes of the vector.
+        #[inline(always)]
+        unsafe fn sum_lanes(lanes: __m128i) -> usize {
+            // Sums each half of the lanes into the low 16 bits of its half.
+            let sums = _mm_sad_epu8(lanes, _mm_setzero_si128());
+            (_mm_extract_epi16(sums, 0) + _mm_extract_epi16(sums, 4)) as usize
+        }
+
+        let mut blocks = bytes.chunks_exact(16);
+
+        // SAFETY: SSE2 is supported by every x86_64 CPU, and every load reads
+        // the 16 bytes of a block.
+        unsafe {
+            let line_feed = _mm_set1_epi8(b'\n' as i8);
+
+            // Interpreted as `i8`s, the continuation bytes are the ones
+            // below -64 (`0xC0`), and the first bytes of a four byte `char`
+            // the negative ones above -17 (`0xEF`).
+            let continuation_end = _mm_set1_epi8(0xC0_u8 as i8);
+            let four_byte_start = _mm_set1_epi8(0xEF_u8 as i8);
+            let zero = _mm_setzero_si128();
+
+            while blocks.len() > 0 {
+                let num_blocks = blocks.len().min(MAX_BLOCKS_PER_BATCH);
+
+                let mut line_feeds = zero;
+                let mut continuations = zero;
+                let mut four_byte_chars = zero;
+
+                for block in (&mut blocks).take(num_blocks) {
+                    let block = _mm_loadu_si128(block.as_ptr().cast());
+
+                    // Every match is `-1`, so subtracting it adds one.
+                    if Self::COUNT_LINE_FEEDS {
+                        line_feeds = _mm_sub_epi8(
+                            line_feeds,
+                            _mm_cmpeq_epi8(block, line_feed),
+                        );
+                    }
+
+                    if Self::COUNT_CHARS {
+                        continuations = _mm_sub_epi8(
+                            continuations,
+                            _mm_cmplt_epi8(block, continuation_end),
+                        );
+                    }
+
+                    if Self::COUNT_FOUR_BYTE_CHARS {
+                        four_byte_chars = _mm_sub_epi8(
+                            four_byte_chars,
+                            _mm_and_si128(
+                                _mm_cmpgt_epi8(block, four_byte_start),
+                                _mm_cmplt_epi8(block, zero),
+                            ),
+                        );
+                    }
+                }
+
+                if Self::COUNT_LINE_FEEDS {
+                    self.line_feeds += sum_lanes(line_feeds);
+                }
+
+                if Self::COUNT_CHARS {
+                    self.chars += num_blocks * 16 - sum_lanes(continuations);
+                }
+
+                if Self::COUNT_FOUR_BYTE_CHARS {
+                    self.four_byte_chars += sum_lanes(four_byte_chars);
+                }
+            }
+        }
+
+        blocks.remainder()
+    }
+
+    /// Adds the counts of all the machine words at the start of `bytes`,
+    /// returning the bytes after them.
+    #[cfg(not(all(feature = "simd", target_arch = "x86_64", not(miri))))]
+    #[inline]
+    fn add_blocks<'a>(&mut self, bytes: &'a [u8]) -> &'a [u8] {
+        const WORD_BYTES: usize = core::mem::size_of::<usize>();
+
+        /// A word with every byte set to `0x01`.
+        const LO: usize = usize::MAX / 0xFF;
+
+        /// A word with every byte set to `0x80`.
+        const HI: usize = LO * 0x80;
+
+        let mut words = bytes.chunks_exact(WORD_BYTES);
+
+        for word in &mut words {
+            let word = usize::from_ne_bytes(word.try_into().unwrap());
+
+            // The high bit of every byte of `not_zero` is set if the byte of
+            // `line_feeds` is not zero, i.e. if it's not a line feed.
+            if Self::COUNT_LINE_FEEDS {
+                let line_feeds = word ^ (LO * b'\n' as usize);
+                let not_zero = ((line_feeds & !HI) + !HI) | line_feeds;
+                self.line_feeds += (!not_zero & HI).count_ones() as usize;
+            }
+
+            // Shifting the word moves the lower bits of every byte into its
+            // high bit, which is all we need to look at. The continuation
+            // bytes start with `0b10`, the first byte of a four byte `char`
+            // with `0b11110`.
+            if Self::COUNT_CHARS {
+                let continuations = word & !(word << 1) & HI;
+                self.chars += WORD_BYTES - continuations.count_ones() as usize;
+            }
+
+            if Self::COUNT_FOUR_BYTE_CHARS {
+                let four_byte_chars =
+                    word & (word << 1) & (word << 2) & (word << 3) & HI;
+                self.four_byte_chars += four_byte_chars.count_ones() as usize;
+            }
+        }
+
+        words.remainder()
+    }
+}
+
+#[inline(always)]
+fn is_continuation(byte: u8) -> bool {
+    byte & 0xC0 == 0x80
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rope::metrics::{AllMetrics, Maintained, Omitted};
+
+    /// Only counts the `char`s.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct CharsOnly;
+
+    impl MetricSet for CharsOnly {
+        type Lines = Omitted;
+        type Chars = Maintained;
+        type Utf16CodeUnits = Omitted;
+        type Width = Maintained;
+    }
+
+    fn naive_counts<S: MetricSet>(s: &str) -> ByteCounts<S> {
+        ByteCounts {
+            line_feeds: if ByteCounts::<S>::COUNT_LINE_FEEDS {
+                s.matches('\n').count()
+            } else {
+                0
+            },
+            chars: if ByteCounts::<S>::COUNT_CHARS {
+                s.chars().count()
+            } else {
+                0
+            },
+            four_byte_chars: if ByteCounts::<S>::COUNT_FOUR_BYTE_CHARS {
+                s.chars().filter(|ch| ch.len_utf8() == 4).count()
+            } else {
+                0
+            },
+            marker: PhantomData,
+        }
+    }
+
+    fn check(s: &str) {
+        assert_eq!(ByteCounts::<AllMetrics>::of(s), naive_counts(s), "{s:?}");
+        assert_eq!(ByteCounts::<CharsOnly>::of(s), naive_counts(s), "{s:?}");
+    }
+
+    /// Tests every substring of a string mixing line feeds and `char`s of
+    /// every length, so that they land at every position of a block.
+    #[test]
+    fn byte_counts_every_substring() {
+        let s = "a\nbé\n🐸\r\nc€\n\n🦀dé".repeat(3);
+
+        for start in (0..=s.len()).filter(|&idx| s.is_char_boundary(idx)) {
+            for end in (start..=s.len()).filter(|&idx| s.is_char_boundary(idx))
+            {
+                check(&s[start..end]);
+            }
+        }
+    }
+
+    /// Tests strings long enough for the counts to be flushed out of the
+    /// vector registers more than once.
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn byte_counts_long() {
+        for s in ["\n".repeat(10_000), "🐸".repeat(3000), "é\n".repeat(5000)]
+        {
+            check(&s);
+        }
+    }
+}
@@ -0,0 +1,180 @@
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, ChunkSummary, MetricSet};
+use super::RopeWith;
+use crate::tree::InvariantViolation;
+use crate::tree::RefCounter;
+
+/// The result of checking the invariants of a [`Rope`](super::Rope).
+///
+/// This is returned by the [`check()`](super::Rope::check()) method on `Rope`. On
+/// top of the [`InvariantViolation`]s reported by
+/// [`validate()`](super::Rope::validate()), it holds the totals stored at the root
+/// of the `Rope`'s tree next to the ones recounted from its text, so that
+/// test suites built on crop can assert on them and print a useful message
+/// when they don't match. The totals which the
+/// [`MetricSet`](crate::MetricSet) of the `Rope` doesn't maintain are zero.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckReport {
+    stored: Totals,
+    actual: Totals,
+    violations: Vec<InvariantViolation>,
+}
+
+/// The totals of a `Rope` that are checked against a recount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Totals {
+    byte_len: usize,
+    #[cfg(feature = "char-metric")]
+    char_len: usize,
+    #[cfg(feature = "lines")]
+    line_breaks: usize,
+}
+
+impl CheckReport {
+    /// Returns the number of bytes in the `Rope`, recounted from its text.
+    #[inline]
+    pub fn actual_byte_len(&self) -> usize {
+        self.actual.byte_len
+    }
+
+    /// Returns the number of `char`s in the `Rope`, recounted from its text.
+    #[cfg_attr(docsrs, doc(cfg(feature = "char-metric")))]
+    #[cfg(feature = "char-metric")]
+    #[inline]
+    pub fn actual_char_len(&self) -> usize {
+        self.actual.char_len
+    }
+
+    /// Returns the number of line breaks in the `Rope`, recounted from its
+    /// text.
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn actual_line_breaks(&self) -> usize {
+        self.actual.line_breaks
+    }
+
+    /// Returns `true` if all the invariants of the `Rope` hold.
+    #[inline]
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+
+    /// Returns the number of bytes in the `Rope` as stored in its tree.
+    #[inline]
+    pub fn stored_byte_len(&self) -> usize {
+        self.stored.byte_len
+    }
+
+    /// Returns the number of `char`s in the `Rope` as stored in its tree.
+    #[cfg_attr(docsrs, doc(cfg(feature = "char-metric")))]
+    #[cfg(feature = "char-metric")]
+    #[inline]
+    pub fn stored_char_len(&self) -> usize {
+        self.stored.char_len
+    }
+
+    /// Returns the number of line breaks in the `Rope` as stored in its
+    /// tree.
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn stored_line_breaks(&self) -> usize {
+        self.stored.line_breaks
+    }
+
+    /// Returns the invariants that don't hold, empty if the `Rope` is valid.
+    #[inline]
+    pub fn violations(&self) -> &[InvariantViolation] {
+        &self.violations
+    }
+}
+
+impl Totals {
+    #[inline]
+    fn new<W: CharWeight, B: LineBreaks, S: MetricSet>(
+        summary: &ChunkSummary<W, B, S>,
+    ) -> Self {
+        Self {
+            byte_len: summary.bytes(),
+            #[cfg(feature = "char-metric")]
+            char_len: summary.maintained_chars().unwrap_or(0),
+            #[cfg(feature = "lines")]
+            line_breaks: summary.maintained_line_breaks().unwrap_or(0),
+        }
+    }
+
+    /// Pushes a [`InvariantViolation::StaleTotal`] to `violations` for every
+    /// total of `self` that doesn't match the one in `actual`.
+    #[inline]
+    fn compare(
+        &self,
+        actual: &Self,
+        violations: &mut Vec<InvariantViolation>,
+    ) {
+        let mut compare = |metric, stored, actual| {
+            if stored != actual {
+                violations.push(InvariantViolation::StaleTotal {
+                    metric,
+                    stored,
+                    actual,
+                });
+            }
+        };
+
+        compare("byte length", self.byte_len, actual.byte_len);
+        #[cfg(feature = "char-metric")]
+        compare("char length", self.char_len, actual.char_len);
+        #[cfg(feature = "lines")]
+        compare("line break count", self.line_breaks, actual.line_breaks);
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> for CheckReport
+{
+    #[inline]
+    fn from(rope: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        let stored = Totals::new(rope.tree.summary());
+
+        let mut recount = ChunkSummary::<W, B, S>::default();
+
+        for chunk in rope.chunks() {
+            recount += ChunkSummary::from(chunk);
+        }
+
+        let actual = Totals::new(&recount);
+
+        let mut violations = rope.validate();
+
+        stored.compare(&actual, &mut violations);
+
+        Self { stored, actual, violations }
+    }
+}
+
+impl core::fmt::Display for CheckReport {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        if self.is_ok() {
+            return f.write_str("all the invariants hold");
+        }
+
+        match self.violations.len() {
+            1 => f.write_str("1 invariant doesn't hold:")?,
+            n => write!(f, "{n} invariants don't hold:")?,
+        }
+
+        for violation in &self.violations {
+            write!(f, "\n  - {violation}")?;
+        }
+
+        Ok(())
+    }
+}
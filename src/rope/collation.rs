@@ -0,0 +1,146 @@
+//! This module contains the logic used to implement
+//! [`Rope::collate()`](crate::Rope::collate()) and
+//! [`RopeSlice::collate()`](crate::RopeSlice::collate()).
+
+use alloc::borrow::Cow;
+use core::cmp::Ordering;
+
+use icu_collator::Collator;
+
+use super::iterators::Chunks;
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use super::RopeSlice;
+use crate::tree::RefCounter;
+
+/// Compares the texts of the two [`RopeSlice`]s according to the rules of
+/// the given collator.
+///
+/// The chunks of the two slices are first walked side by side to find the
+/// first byte where the texts differ, without copying anything. If there's
+/// none the texts are equal, otherwise the collator is given what's left of
+/// them starting from the last ASCII whitespace before that byte, since no
+/// collation element can span one. The collator needs contiguous strings,
+/// so the rest of a text is only copied if it spans more than one chunk.
+#[inline]
+pub(super) fn collate<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    lhs: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    rhs: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    collator: &Collator,
+) -> Ordering {
+    let Some(resume_at) = resume_offset(lhs.chunks(), rhs.chunks()) else {
+        return Ordering::Equal;
+    };
+
+    let lhs = lhs.byte_slice(resume_at..);
+    let rhs = rhs.byte_slice(resume_at..);
+
+    collator.compare(
+        &contiguous(lhs.chunks(), lhs.byte_len()),
+        &contiguous(rhs.chunks(), rhs.byte_len()),
+    )
+}
+
+/// Walks the chunks of two texts side by side and returns the byte offset
+/// of the last ASCII whitespace before the first byte where they differ, or
+/// zero if there's none, or `None` if the two texts are the same.
+///
+/// The chunks of the two texts don't end at the same offsets, so each of
+/// them is compared a piece at a time against the chunk of the other text it
+/// overlaps with.
+#[inline]
+fn resume_offset<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    mut lhs: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    mut rhs: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+) -> Option<usize> {
+    let mut lhs_chunk: &[u8] = &[];
+    let mut rhs_chunk: &[u8] = &[];
+
+    // The number of bytes the two texts have in common so far.
+    let mut compared = 0;
+
+    let mut resume_at = 0;
+
+    loop {
+        while lhs_chunk.is_empty() {
+            let Some(chunk) = lhs.next() else { break };
+            lhs_chunk = chunk.as_bytes();
+        }
+
+        while rhs_chunk.is_empty() {
+            let Some(chunk) = rhs.next() else { break };
+            rhs_chunk = chunk.as_bytes();
+        }
+
+        if lhs_chunk.is_empty() || rhs_chunk.is_empty() {
+            // At least one of the texts is over, so they're only the same if
+            // they both are.
+            return (lhs_chunk.len() != rhs_chunk.len()).then_some(resume_at);
+        }
+
+        let len = lhs_chunk.len().min(rhs_chunk.len());
+
+        let (lhs_piece, rest) = lhs_chunk.split_at(len);
+        lhs_chunk = rest;
+
+        let (rhs_piece, rest) = rhs_chunk.split_at(len);
+        rhs_chunk = rest;
+
+        let diff_at =
+            lhs_piece.iter().zip(rhs_piece).position(|(lhs, rhs)| lhs != rhs);
+
+        let common = &lhs_piece[..diff_at.unwrap_or(len)];
+
+        if let Some(idx) = common.iter().rposition(u8::is_ascii_whitespace) {
+            resume_at = compared + idx;
+        }
+
+        if diff_at.is_some() {
+            return Some(resume_at);
+        }
+
+        compared += len;
+    }
+}
+
+/// Returns the text yielded by the [`Chunks`] as a single string, borrowing
+/// it if it's all in one chunk.
+#[inline]
+fn contiguous<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    mut chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    byte_len: usize,
+) -> Cow<'_, str> {
+    match chunks.next() {
+        Some(first) if first.len() == byte_len => Cow::Borrowed(first),
+
+        Some(first) => {
+            let mut s = String::with_capacity(byte_len);
+            s.push_str(first);
+            chunks.for_each(|chunk| s.push_str(chunk));
+            Cow::Owned(s)
+        },
+
+        None => Cow::Borrowed(""),
+    }
+}
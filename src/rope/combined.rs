@@ -0,0 +1,1477 @@
+//! This module exports the [`Combined`] struct, which holds the parts of a
+//! [`ChunkSummary`](super::metrics::ChunkSummary) that can't be added up
+//! like the number of bytes or line breaks are.
+//!
+//! The summary of two pieces of text put one after the other is still
+//! computed from the summaries of the two pieces, but it also depends on
+//! the text around their join (e.g. the longest line of the whole text could
+//! be made of the last line of the first piece and the first line of the
+//! second one). Each part keeps just enough of the two ends of the text to
+//! combine them, and because of that the summary of a piece can't be
+//! subtracted from the summary of the whole text to get the rest of it.
+//!
+//! Every part is behind its own feature flag, so without any of them enabled
+//! this is a zero-sized type and ropes keep subtracting their summaries.
+
+use super::line_breaks::LineBreaks;
+#[cfg(any(
+    feature = "max-line-len",
+    feature = "word-count",
+    feature = "grapheme-count"
+))]
+use super::metrics::count_ops::to_count;
+#[cfg(any(
+    feature = "max-line-len",
+    feature = "word-count",
+    feature = "grapheme-count",
+    feature = "paragraph-count"
+))]
+use super::metrics::count_ops::{add, from_count};
+#[cfg(any(
+    feature = "max-line-len",
+    feature = "word-count",
+    feature = "grapheme-count",
+    feature = "paragraph-count"
+))]
+use super::metrics::Count;
+
+/// Whether any part of a [`Combined`] is enabled, in which case the
+/// summaries of a rope can't be subtracted from one another.
+pub(super) const IS_ENABLED: bool = cfg!(any(
+    feature = "max-line-len",
+    feature = "word-count",
+    feature = "grapheme-count",
+    feature = "fingerprint",
+    feature = "paragraph-count"
+));
+
+/// The parts of a [`ChunkSummary`](super::metrics::ChunkSummary) that are
+/// combined instead of being added up.
+///
+/// The parts of a summary obtained by a subtraction, or by adding up the
+/// summaries of pieces that weren't next to each other, are meaningless.
+/// So that summaries can still be compared by their metrics, `==` always
+/// considers two `Combined`s to be equal.
+#[derive(Copy, Clone, Default, Debug)]
+pub(super) struct Combined {
+    #[cfg(feature = "max-line-len")]
+    line_lens: LineLens,
+    #[cfg(feature = "word-count")]
+    words: Words,
+    #[cfg(feature = "grapheme-count")]
+    graphemes: Graphemes,
+    #[cfg(feature = "fingerprint")]
+    fingerprint: Fingerprint,
+    #[cfg(feature = "paragraph-count")]
+    paragraphs: Paragraphs,
+}
+
+impl Combined {
+    /// The number of fields returned by [`fields()`](Self::fields()).
+    pub(super) const FIELDS: usize = LineLens::FIELDS
+        + Words::FIELDS
+        + Graphemes::FIELDS
+        + Fingerprint::FIELDS
+        + Paragraphs::FIELDS;
+
+    /// Computes the combined parts of the summary of `s`.
+    #[cfg_attr(
+        not(any(feature = "max-line-len", feature = "paragraph-count")),
+        allow(clippy::extra_unused_type_parameters)
+    )]
+    #[inline]
+    pub(super) fn of<B: LineBreaks>(_s: &str) -> Self {
+        Self {
+            #[cfg(feature = "max-line-len")]
+            line_lens: LineLens::of::<B>(_s),
+            #[cfg(feature = "word-count")]
+            words: Words::of(_s),
+            #[cfg(feature = "grapheme-count")]
+            graphemes: Graphemes::of(_s),
+            #[cfg(feature = "fingerprint")]
+            fingerprint: Fingerprint::of(_s),
+            #[cfg(feature = "paragraph-count")]
+            paragraphs: Paragraphs::of::<B>(_s),
+        }
+    }
+
+    /// Combines `self`, the parts of a piece of text, with the parts of the
+    /// text right after it.
+    #[inline]
+    pub(super) fn append(&mut self, _next: &Self) {
+        #[cfg(feature = "max-line-len")]
+        {
+            self.line_lens = self.line_lens.concat(&_next.line_lens);
+        }
+        #[cfg(feature = "word-count")]
+        {
+            self.words = self.words.concat(&_next.words);
+        }
+        #[cfg(feature = "grapheme-count")]
+        {
+            self.graphemes = self.graphemes.concat(&_next.graphemes);
+        }
+        #[cfg(feature = "fingerprint")]
+        {
+            self.fingerprint = self.fingerprint.concat(&_next.fingerprint);
+        }
+        #[cfg(feature = "paragraph-count")]
+        {
+            self.paragraphs = self.paragraphs.concat(&_next.paragraphs);
+        }
+    }
+
+    /// Turns `self`, the parts of `left` combined with the parts of `right`,
+    /// into the parts of the two of them as a single piece of text.
+    #[inline]
+    pub(super) fn join_segments(&mut self, _left: &str, _right: &str) {
+        #[cfg(feature = "grapheme-count")]
+        self.graphemes.join_segments(_left, _right);
+    }
+
+    /// Returns whether `self` and `other` are the same, unlike `==` which
+    /// always returns `true`.
+    #[inline]
+    pub(super) fn is_identical_to(&self, _other: &Self) -> bool {
+        #[allow(unused_mut)]
+        let mut is_identical = true;
+        #[cfg(feature = "max-line-len")]
+        {
+            is_identical &= self.line_lens == _other.line_lens;
+        }
+        #[cfg(feature = "word-count")]
+        {
+            is_identical &= self.words == _other.words;
+        }
+        #[cfg(feature = "grapheme-count")]
+        {
+            is_identical &= self.graphemes == _other.graphemes;
+        }
+        #[cfg(feature = "fingerprint")]
+        {
+            is_identical &= self.fingerprint == _other.fingerprint;
+        }
+        #[cfg(feature = "paragraph-count")]
+        {
+            is_identical &= self.paragraphs == _other.paragraphs;
+        }
+        is_identical
+    }
+
+    /// Returns the fields of the combined parts, to be stored in a snapshot.
+    #[inline]
+    pub(super) fn fields(&self) -> impl Iterator<Item = usize> {
+        #[cfg(feature = "max-line-len")]
+        let line_lens = self.line_lens.fields();
+
+        #[cfg(not(feature = "max-line-len"))]
+        let line_lens = [0; 0];
+
+        #[cfg(feature = "word-count")]
+        let words = self.words.fields();
+
+        #[cfg(not(feature = "word-count"))]
+        let words = [0; 0];
+
+        #[cfg(feature = "grapheme-count")]
+        let graphemes = self.graphemes.fields();
+
+        #[cfg(not(feature = "grapheme-count"))]
+        let graphemes = [0; 0];
+
+        #[cfg(feature = "fingerprint")]
+        let fingerprint = self.fingerprint.fields();
+
+        #[cfg(not(feature = "fingerprint"))]
+        let fingerprint = [0; 0];
+
+        #[cfg(feature = "paragraph-count")]
+        let paragraphs = self.paragraphs.fields();
+
+        #[cfg(not(feature = "paragraph-count"))]
+        let paragraphs = [0; 0];
+
+        line_lens
+            .into_iter()
+            .chain(words)
+            .chain(graphemes)
+            .chain(fingerprint)
+            .chain(paragraphs)
+    }
+
+    /// Creates the combined parts from the fields returned by
+    /// [`fields()`](Self::fields()), or returns `None` if one of them is
+    /// missing or out of range.
+    #[inline]
+    pub(super) fn from_fields(
+        _fields: &mut impl Iterator<Item = usize>,
+    ) -> Option<Self> {
+        Some(Self {
+            #[cfg(feature = "max-line-len")]
+            line_lens: LineLens::from_fields(_fields)?,
+            #[cfg(feature = "word-count")]
+            words: Words::from_fields(_fields)?,
+            #[cfg(feature = "grapheme-count")]
+            graphemes: Graphemes::from_fields(_fields)?,
+            #[cfg(feature = "fingerprint")]
+            fingerprint: Fingerprint::from_fields(_fields)?,
+            #[cfg(feature = "paragraph-count")]
+            paragraphs: Paragraphs::from_fields(_fields)?,
+        })
+    }
+
+    /// Returns the length in bytes of the longest line of the text, not
+    /// including its line terminator.
+    #[cfg(feature = "max-line-len")]
+    #[inline]
+    pub(super) fn max_line_len(&self) -> usize {
+        self.line_lens.max_len()
+    }
+
+    /// Returns the display width of the widest line of the text.
+    #[cfg(all(feature = "max-line-len", feature = "width-metric"))]
+    #[inline]
+    pub(super) fn max_line_width(&self) -> usize {
+        self.line_lens.max_width()
+    }
+
+    /// Returns the number of words of the text.
+    #[cfg(feature = "word-count")]
+    #[inline]
+    pub(super) fn words(&self) -> usize {
+        from_count(self.words.words)
+    }
+
+    /// Returns the number of extended grapheme clusters of the text, which
+    /// is exact if [`unsure_grapheme_joins()`](Self::unsure_grapheme_joins())
+    /// is zero.
+    #[cfg(feature = "grapheme-count")]
+    #[inline]
+    pub(super) fn graphemes(&self) -> usize {
+        from_count(self.graphemes.graphemes)
+    }
+
+    /// Returns the number of joins between the pieces the text was built
+    /// from around which its grapheme clusters could have been counted
+    /// wrong.
+    #[cfg(feature = "grapheme-count")]
+    #[inline]
+    pub(super) fn unsure_grapheme_joins(&self) -> usize {
+        from_count(self.graphemes.unsure_joins)
+    }
+
+    /// Returns whether the join between the text of `self` and the text of
+    /// `next` right after it is one of the joins counted by
+    /// [`unsure_grapheme_joins()`](Self::unsure_grapheme_joins()).
+    #[cfg(feature = "grapheme-count")]
+    #[inline]
+    pub(super) fn has_unsure_grapheme_join(&self, next: &Self) -> bool {
+        self.graphemes.has_unsure_join(&next.graphemes)
+    }
+
+    /// Returns the fingerprint of the text.
+    #[cfg(feature = "fingerprint")]
+    #[inline]
+    pub(super) fn fingerprint(&self) -> u64 {
+        self.fingerprint.hash
+    }
+
+    /// Returns the number of paragraphs of the text, i.e. of its runs of
+    /// lines which aren't blank.
+    #[cfg(feature = "paragraph-count")]
+    #[inline]
+    pub(super) fn paragraphs(&self) -> usize {
+        self.paragraphs.all_lines().runs()
+    }
+
+    /// Returns the number of paragraphs of the text which are followed by a
+    /// whole blank line, i.e. a blank line ending with a line break.
+    ///
+    /// Unlike the number of paragraphs, this can't change if more text is
+    /// appended.
+    #[cfg(feature = "paragraph-count")]
+    #[inline]
+    pub(super) fn ended_paragraphs(&self) -> usize {
+        self.paragraphs.whole_lines().ended_runs()
+    }
+
+    /// Returns whether the last line of the text, i.e. the one after its last
+    /// line break, is blank.
+    #[cfg(feature = "paragraph-count")]
+    #[inline]
+    pub(super) fn ends_in_blank_line(&self) -> bool {
+        !self.paragraphs.last_not_blank
+    }
+}
+
+impl PartialEq for Combined {
+    #[inline]
+    fn eq(&self, _other: &Self) -> bool {
+        true
+    }
+}
+
+#[cfg(not(feature = "max-line-len"))]
+struct LineLens;
+
+#[cfg(not(feature = "max-line-len"))]
+impl LineLens {
+    const FIELDS: usize = 0;
+}
+
+/// The lengths of the first, last and longest lines of a piece of text.
+///
+/// The first and last lines are the ones before the first line break and
+/// after the last one, so they can be extended by the text around the piece.
+/// If the piece doesn't contain any line break they're both the whole text.
+/// The longest line is the longest one in between, which can't change
+/// anymore.
+///
+/// Like for the lines yielded by [`Rope::lines()`](crate::Rope::lines()) the
+/// lengths don't include the line breaks, nor the `'\r'` of a CRLF pair.
+/// The width of a `'\r'` is zero, so it doesn't matter for the widths.
+#[cfg(feature = "max-line-len")]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+struct LineLens {
+    first: Count,
+    last: Count,
+    longest: Count,
+    #[cfg(feature = "width-metric")]
+    first_width: Count,
+    #[cfg(feature = "width-metric")]
+    last_width: Count,
+    #[cfg(feature = "width-metric")]
+    longest_width: Count,
+    has_line_break: bool,
+    starts_with_lf: bool,
+    ends_with_cr: bool,
+}
+
+#[cfg(feature = "max-line-len")]
+impl LineLens {
+    const FIELDS: usize = if cfg!(feature = "width-metric") { 7 } else { 4 };
+
+    #[inline]
+    fn of<B: LineBreaks>(s: &str) -> Self {
+        let mut lens = Self {
+            starts_with_lf: s.starts_with('\n'),
+            ends_with_cr: s.ends_with('\r'),
+            ..Self::default()
+        };
+
+        let mut line_start = 0;
+
+        // Searching for a single char is a lot faster than calling a
+        // predicate on every char.
+        let (mut any_breaks, mut line_feeds);
+
+        let line_breaks: &mut dyn Iterator<Item = (usize, &str)> =
+            if B::is_line_break('\u{2028}') {
+                any_breaks = s.match_indices(|ch| B::is_line_break(ch));
+                &mut any_breaks
+            } else {
+                line_feeds = s.match_indices('\n');
+                &mut line_feeds
+            };
+
+        for (break_start, line_break) in line_breaks {
+            let mut line = &s[line_start..break_start];
+
+            if line_break == "\n" {
+                line = line.strip_suffix('\r').unwrap_or(line);
+            }
+
+            let len = to_count(line.len());
+
+            #[cfg(feature = "width-metric")]
+            let width = to_count(super::metrics::count::width(line));
+
+            if lens.has_line_break {
+                lens.longest = lens.longest.max(len);
+                #[cfg(feature = "width-metric")]
+                {
+                    lens.longest_width = lens.longest_width.max(width);
+                }
+            } else {
+                lens.first = len;
+                #[cfg(feature = "width-metric")]
+                {
+                    lens.first_width = width;
+                }
+                lens.has_line_break = true;
+            }
+
+            line_start = break_start + line_break.len();
+        }
+
+        let last = &s[line_start..];
+
+        lens.last = to_count(last.len());
+
+        #[cfg(feature = "width-metric")]
+        {
+            lens.last_width = to_count(super::metrics::count::width(last));
+        }
+
+        if !lens.has_line_break {
+            lens.first = lens.last;
+            #[cfg(feature = "width-metric")]
+            {
+                lens.first_width = lens.last_width;
+            }
+        }
+
+        lens
+    }
+
+    /// Returns the lengths of the text of `self` followed by the text of
+    /// `next`.
+    #[inline]
+    fn concat(&self, next: &Self) -> Self {
+        if self.is_empty() {
+            return *next;
+        } else if next.is_empty() {
+            return *self;
+        }
+
+        // The line made of the last line of `self` and the first line of
+        // `next`, which loses the '\r' of a CRLF pair split between them.
+        let joined = add(self.last, next.first)
+            - (self.ends_with_cr && next.starts_with_lf) as Count;
+
+        #[cfg(feature = "width-metric")]
+        let joined_width = add(self.last_width, next.first_width);
+
+        let mut lens = Self {
+            starts_with_lf: self.starts_with_lf,
+            ends_with_cr: next.ends_with_cr,
+            has_line_break: self.has_line_break || next.has_line_break,
+            ..Self::default()
+        };
+
+        match (self.has_line_break, next.has_line_break) {
+            (false, false) => {
+                lens.first = add(self.first, next.first);
+                lens.last = lens.first;
+                #[cfg(feature = "width-metric")]
+                {
+                    lens.first_width = joined_width;
+                    lens.last_width = joined_width;
+                }
+            },
+
+            (false, true) => {
+                lens.first = joined;
+                lens.last = next.last;
+                lens.longest = next.longest;
+                #[cfg(feature = "width-metric")]
+                {
+                    lens.first_width = joined_width;
+                    lens.last_width = next.last_width;
+                    lens.longest_width = next.longest_width;
+                }
+            },
+
+            (true, false) => {
+                lens.first = self.first;
+                lens.last = add(self.last, next.last);
+                lens.longest = self.longest;
+                #[cfg(feature = "width-metric")]
+                {
+                    lens.first_width = self.first_width;
+                    lens.last_width = joined_width;
+                    lens.longest_width = self.longest_width;
+                }
+            },
+
+            (true, true) => {
+                lens.first = self.first;
+                lens.last = next.last;
+                lens.longest = self.longest.max(next.longest).max(joined);
+                #[cfg(feature = "width-metric")]
+                {
+                    lens.first_width = self.first_width;
+                    lens.last_width = next.last_width;
+                    lens.longest_width = self
+                        .longest_width
+                        .max(next.longest_width)
+                        .max(joined_width);
+                }
+            },
+        }
+
+        lens
+    }
+
+    /// Returns whether these are the lengths of the empty string, which is
+    /// the only text without line breaks whose first line is empty.
+    #[inline]
+    fn is_empty(&self) -> bool {
+        !self.has_line_break && self.first == 0
+    }
+
+    #[inline]
+    fn max_len(&self) -> usize {
+        from_count(self.first.max(self.last).max(self.longest))
+    }
+
+    #[cfg(feature = "width-metric")]
+    #[inline]
+    fn max_width(&self) -> usize {
+        from_count(
+            self.first_width.max(self.last_width).max(self.longest_width),
+        )
+    }
+
+    #[inline]
+    fn fields(&self) -> [usize; Self::FIELDS] {
+        let flags = self.has_line_break as usize
+            | (self.starts_with_lf as usize) << 1
+            | (self.ends_with_cr as usize) << 2;
+
+        [
+            from_count(self.first),
+            from_count(self.last),
+            from_count(self.longest),
+            #[cfg(feature = "width-metric")]
+            from_count(self.first_width),
+            #[cfg(feature = "width-metric")]
+            from_count(self.last_width),
+            #[cfg(feature = "width-metric")]
+            from_count(self.longest_width),
+            flags,
+        ]
+    }
+
+    #[inline]
+    fn from_fields(fields: &mut impl Iterator<Item = usize>) -> Option<Self> {
+        let mut count = || Count::try_from(fields.next()?).ok();
+
+        let first = count()?;
+        let last = count()?;
+        let longest = count()?;
+        #[cfg(feature = "width-metric")]
+        let (first_width, last_width, longest_width) =
+            (count()?, count()?, count()?);
+
+        let flags = fields.next()?;
+
+        if flags > 0b111 {
+            return None;
+        }
+
+        Some(Self {
+            first,
+            last,
+            longest,
+            #[cfg(feature = "width-metric")]
+            first_width,
+            #[cfg(feature = "width-metric")]
+            last_width,
+            #[cfg(feature = "width-metric")]
+            longest_width,
+            has_line_break: flags & 1 != 0,
+            starts_with_lf: flags & 0b10 != 0,
+            ends_with_cr: flags & 0b100 != 0,
+        })
+    }
+}
+
+#[cfg(not(feature = "word-count"))]
+struct Words;
+
+#[cfg(not(feature = "word-count"))]
+impl Words {
+    const FIELDS: usize = 0;
+}
+
+/// The number of words of a piece of text, counted like `wc -w` does, i.e.
+/// as the number of maximal runs of non-whitespace `char`s.
+///
+/// A word at the end of a piece and one at the start of the next piece are
+/// the same word, so whether the piece starts and ends in the middle of a
+/// word is kept too.
+#[cfg(feature = "word-count")]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+struct Words {
+    words: Count,
+    has_chars: bool,
+    starts_in_word: bool,
+    ends_in_word: bool,
+}
+
+#[cfg(feature = "word-count")]
+impl Words {
+    const FIELDS: usize = 2;
+
+    #[inline]
+    fn of(s: &str) -> Self {
+        let mut words = 0;
+        let mut in_word = false;
+
+        for ch in s.chars() {
+            let is_word = !ch.is_whitespace();
+            words += (is_word && !in_word) as usize;
+            in_word = is_word;
+        }
+
+        Self {
+            words: to_count(words),
+            has_chars: !s.is_empty(),
+            starts_in_word: s
+                .chars()
+                .next()
+                .map_or(false, |ch| !ch.is_whitespace()),
+            ends_in_word: in_word,
+        }
+    }
+
+    /// Returns the words of the text of `self` followed by the text of
+    /// `next`.
+    #[inline]
+    fn concat(&self, next: &Self) -> Self {
+        if !self.has_chars {
+            return *next;
+        } else if !next.has_chars {
+            return *self;
+        }
+
+        Self {
+            words: add(self.words, next.words)
+                - (self.ends_in_word && next.starts_in_word) as Count,
+            has_chars: true,
+            starts_in_word: self.starts_in_word,
+            ends_in_word: next.ends_in_word,
+        }
+    }
+
+    #[inline]
+    fn fields(&self) -> [usize; Self::FIELDS] {
+        let flags = self.has_chars as usize
+            | (self.starts_in_word as usize) << 1
+            | (self.ends_in_word as usize) << 2;
+
+        [from_count(self.words), flags]
+    }
+
+    #[inline]
+    fn from_fields(fields: &mut impl Iterator<Item = usize>) -> Option<Self> {
+        let words = Count::try_from(fields.next()?).ok()?;
+
+        let flags = fields.next()?;
+
+        if flags > 0b111 {
+            return None;
+        }
+
+        Some(Self {
+            words,
+            has_chars: flags & 1 != 0,
+            starts_in_word: flags & 0b10 != 0,
+            ends_in_word: flags & 0b100 != 0,
+        })
+    }
+}
+
+#[cfg(not(feature = "grapheme-count"))]
+struct Graphemes;
+
+#[cfg(not(feature = "grapheme-count"))]
+impl Graphemes {
+    const FIELDS: usize = 0;
+}
+
+/// The number of extended grapheme clusters of a piece of text, together
+/// with its first and last `char`s.
+///
+/// Most clusters are split from the text around them by rules that only
+/// look at the two `char`s on either side of a boundary, so the clusters of
+/// two pieces put one after the other can be counted from the clusters of
+/// each piece and the `char`s around their join. A few rules look further
+/// back though (e.g. flags are made of pairs of regional indicators, so
+/// where a run of them is split depends on where it starts). The joins
+/// where one of those could apply are counted instead of being resolved,
+/// and the text after them has to be segmented again to get an exact
+/// count.
+#[cfg(feature = "grapheme-count")]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+struct Graphemes {
+    graphemes: Count,
+    unsure_joins: Count,
+    first: Option<char>,
+    last: Option<char>,
+}
+
+#[cfg(feature = "grapheme-count")]
+impl Graphemes {
+    const FIELDS: usize = 4;
+
+    #[inline]
+    fn of(s: &str) -> Self {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        // The only grapheme cluster made of more than one ASCII character
+        // is "\r\n".
+        let graphemes = if s.is_ascii() {
+            s.len() - s.matches("\r\n").count()
+        } else {
+            s.graphemes(true).count()
+        };
+
+        Self {
+            graphemes: to_count(graphemes),
+            unsure_joins: 0,
+            first: s.chars().next(),
+            last: s.chars().next_back(),
+        }
+    }
+
+    /// Returns the clusters of the text of `self` followed by the text of
+    /// `next`.
+    #[inline]
+    fn concat(&self, next: &Self) -> Self {
+        let (Some(last), Some(first)) = (self.last, next.first) else {
+            return if self.first.is_none() { *next } else { *self };
+        };
+
+        // A "\r\n" pair is always a cluster of its own, whatever comes
+        // before it.
+        let is_crlf = (last, first) == ('\r', '\n');
+
+        let is_unsure = !is_crlf && is_unsure_join(last, first);
+
+        Self {
+            graphemes: add(self.graphemes, next.graphemes) - is_crlf as Count,
+            unsure_joins: add(
+                add(self.unsure_joins, next.unsure_joins),
+                is_unsure as Count,
+            ),
+            first: self.first,
+            last: next.last,
+        }
+    }
+
+    /// Turns `self`, the clusters of `left` combined with the clusters of
+    /// `right`, into the clusters of the two of them segmented together, so
+    /// that there are no unsure joins left.
+    #[inline]
+    fn join_segments(&mut self, left: &str, right: &str) {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        // The two segments were summarized on their own, so the only unsure
+        // join can be the one between them.
+        if self.unsure_joins == 0 {
+            return;
+        }
+
+        // A `GraphemeCursor` fed the two segments as separate chunks can
+        // split the regional indicators around the join wrong, so they're
+        // segmented as a single string instead. This is only done when the
+        // gap falls inside such a sequence.
+        let graphemes = [left, right].concat().graphemes(true).count();
+
+        self.graphemes = to_count(graphemes);
+        self.unsure_joins = 0;
+    }
+
+    /// Returns whether concatenating `self` and `next` adds an unsure join.
+    #[inline]
+    fn has_unsure_join(&self, next: &Self) -> bool {
+        match (self.last, next.first) {
+            (Some(last), Some(first)) => {
+                (last, first) != ('\r', '\n') && is_unsure_join(last, first)
+            },
+            _ => false,
+        }
+    }
+
+    #[inline]
+    fn fields(&self) -> [usize; Self::FIELDS] {
+        // Zero is used for a missing char, so the chars are shifted by one.
+        let char_field = |ch: Option<char>| ch.map_or(0, |ch| ch as usize + 1);
+
+        [
+            from_count(self.graphemes),
+            from_count(self.unsure_joins),
+            char_field(self.first),
+            char_field(self.last),
+        ]
+    }
+
+    #[inline]
+    fn from_fields(fields: &mut impl Iterator<Item = usize>) -> Option<Self> {
+        let graphemes = Count::try_from(fields.next()?).ok()?;
+        let unsure_joins = Count::try_from(fields.next()?).ok()?;
+
+        let mut char_field = || -> Option<Option<char>> {
+            match fields.next()? {
+                0 => Some(None),
+                field => u32::try_from(field - 1)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .map(Some),
+            }
+        };
+
+        let first = char_field()?;
+        let last = char_field()?;
+
+        if first.is_some() != last.is_some() {
+            return None;
+        }
+
+        Some(Self { graphemes, unsure_joins, first, last })
+    }
+}
+
+/// Returns whether the boundary between `prev` and `next` could be decided
+/// by the text before `prev`, or doesn't exist, in which case the clusters
+/// after it could change too.
+///
+/// Those are the joins between two regional indicators, or after a zero
+/// width joiner (which could continue an emoji sequence), or between a
+/// virama and a consonant (which could continue an Indic conjunct).
+#[cfg(feature = "grapheme-count")]
+#[inline]
+fn is_unsure_join(prev: char, next: char) -> bool {
+    use unicode_segmentation::GraphemeCursor;
+
+    if prev.is_ascii() && next.is_ascii() {
+        return false;
+    }
+
+    if prev == '\u{200D}' {
+        return true;
+    }
+
+    let is_boundary = |s: &str, offset: usize| {
+        GraphemeCursor::new(offset, s.len(), true).is_boundary(s, 0)
+            == Ok(true)
+    };
+
+    // The text is the pair, preceded by a consonant and a virama which
+    // would make it part of a conjunct if `prev` can continue one.
+    const CONJUNCT_START: &str = "\u{915}\u{94D}";
+
+    let mut bytes = [0; CONJUNCT_START.len() + 8];
+    bytes[..CONJUNCT_START.len()].copy_from_slice(CONJUNCT_START.as_bytes());
+    let prev_len = prev.encode_utf8(&mut bytes[CONJUNCT_START.len()..]).len();
+    let pair_len = prev_len
+        + next
+            .encode_utf8(&mut bytes[CONJUNCT_START.len() + prev_len..])
+            .len();
+
+    // SAFETY: the bytes are all from valid `str`s.
+    let text = unsafe {
+        core::str::from_utf8_unchecked(
+            &bytes[..CONJUNCT_START.len() + pair_len],
+        )
+    };
+
+    let pair = &text[CONJUNCT_START.len()..];
+
+    !is_boundary(pair, prev_len)
+        || !is_boundary(text, CONJUNCT_START.len() + prev_len)
+}
+
+#[cfg(not(feature = "fingerprint"))]
+struct Fingerprint;
+
+#[cfg(not(feature = "fingerprint"))]
+impl Fingerprint {
+    const FIELDS: usize = 0;
+}
+
+/// A polynomial hash of the bytes of a piece of text modulo the Mersenne
+/// prime `2^61 - 1`, together with the base raised to the number of bytes.
+///
+/// The hash of two pieces put one after the other is the hash of the first
+/// one shifted by the length of the second one, plus the hash of the second
+/// one, so it doesn't depend on how the text is split into chunks.
+#[cfg(feature = "fingerprint")]
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct Fingerprint {
+    hash: u64,
+    power: u64,
+}
+
+#[cfg(feature = "fingerprint")]
+impl Default for Fingerprint {
+    #[inline]
+    fn default() -> Self {
+        Self { hash: 0, power: 1 }
+    }
+}
+
+#[cfg(feature = "fingerprint")]
+impl Fingerprint {
+    /// The hash and the power are split in two 32-bit halves, so that they
+    /// fit in a `usize` on every platform.
+    const FIELDS: usize = 4;
+
+    const MODULUS: u64 = (1 << 61) - 1;
+
+    /// An arbitrary base, which should just be large.
+    const BASE: u64 = 0x0a3e_7f12_c9b4_5d87;
+
+    #[inline]
+    fn of(s: &str) -> Self {
+        let hash = s.bytes().fold(0, |hash, byte| {
+            Self::add(Self::mul(hash, Self::BASE), byte.into())
+        });
+
+        Self { hash, power: Self::pow(Self::BASE, s.len()) }
+    }
+
+    /// Returns the fingerprint of the text of `self` followed by the text of
+    /// `next`.
+    #[inline]
+    fn concat(&self, next: &Self) -> Self {
+        Self {
+            hash: Self::add(Self::mul(self.hash, next.power), next.hash),
+            power: Self::mul(self.power, next.power),
+        }
+    }
+
+    #[inline]
+    fn add(a: u64, b: u64) -> u64 {
+        let sum = a + b;
+        if sum >= Self::MODULUS {
+            sum - Self::MODULUS
+        } else {
+            sum
+        }
+    }
+
+    #[inline]
+    fn mul(a: u64, b: u64) -> u64 {
+        // 2^61 is 1 modulo 2^61 - 1, so the bits above the 61st can just be
+        // added to the ones below it. Doing it twice brings the product down
+        // to at most 2^61.
+        let fold = |n: u128| (n as u64 & Self::MODULUS) as u128 + (n >> 61);
+        let folded = fold(fold(a as u128 * b as u128)) as u64;
+
+        if folded >= Self::MODULUS {
+            folded - Self::MODULUS
+        } else {
+            folded
+        }
+    }
+
+    #[inline]
+    fn pow(mut base: u64, mut exp: usize) -> u64 {
+        let mut power = 1;
+
+        while exp > 0 {
+            if exp & 1 == 1 {
+                power = Self::mul(power, base);
+            }
+            base = Self::mul(base, base);
+            exp >>= 1;
+        }
+
+        power
+    }
+
+    #[inline]
+    fn fields(&self) -> [usize; Self::FIELDS] {
+        let halves = |n: u64| [(n as u32) as usize, (n >> 32) as usize];
+        let [hash_lo, hash_hi] = halves(self.hash);
+        let [power_lo, power_hi] = halves(self.power);
+        [hash_lo, hash_hi, power_lo, power_hi]
+    }
+
+    #[inline]
+    fn from_fields(fields: &mut impl Iterator<Item = usize>) -> Option<Self> {
+        let mut half = || u32::try_from(fields.next()?).ok().map(u64::from);
+        let mut field = || Some(half()? | half()? << 32);
+
+        let hash = field()?;
+        let power = field()?;
+
+        if hash >= Self::MODULUS || power >= Self::MODULUS {
+            return None;
+        }
+
+        Some(Self { hash, power })
+    }
+}
+
+#[cfg(not(feature = "paragraph-count"))]
+struct Paragraphs;
+
+#[cfg(not(feature = "paragraph-count"))]
+impl Paragraphs {
+    const FIELDS: usize = 0;
+}
+
+/// The paragraphs of a piece of text, i.e. its runs of consecutive lines
+/// which aren't blank, like the ones yielded by
+/// [`Rope::paragraphs()`](crate::Rope::paragraphs()).
+///
+/// Like for the lengths of the lines, the first and last lines can still be
+/// extended by the text around the piece, so only whether they're blank is
+/// kept, while the runs of the lines in between are counted. If the piece
+/// doesn't contain any line break its first and last lines are both the
+/// whole text.
+///
+/// Every line break is whitespace, so whether a line is blank doesn't depend
+/// on its line terminator being part of it.
+#[cfg(feature = "paragraph-count")]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+struct Paragraphs {
+    inner: LineRuns,
+    first_not_blank: bool,
+    last_not_blank: bool,
+    has_line_break: bool,
+}
+
+#[cfg(feature = "paragraph-count")]
+impl Paragraphs {
+    const FIELDS: usize = 3;
+
+    #[inline]
+    fn of<B: LineBreaks>(s: &str) -> Self {
+        let mut paragraphs = Self::default();
+
+        let mut line_not_blank = false;
+
+        for ch in s.chars() {
+            if B::is_line_break(ch) {
+                paragraphs.push_line(line_not_blank);
+                line_not_blank = false;
+            } else {
+                line_not_blank |= !ch.is_whitespace();
+            }
+        }
+
+        paragraphs.last_not_blank = line_not_blank;
+
+        if !paragraphs.has_line_break {
+            paragraphs.first_not_blank = line_not_blank;
+        }
+
+        paragraphs
+    }
+
+    /// Adds a line ending with a line break after the ones seen so far.
+    #[inline]
+    fn push_line(&mut self, not_blank: bool) {
+        if self.has_line_break {
+            self.inner = self.inner.concat(&LineRuns::line(not_blank));
+        } else {
+            self.first_not_blank = not_blank;
+            self.has_line_break = true;
+        }
+    }
+
+    /// Returns the paragraphs of the text of `self` followed by the text of
+    /// `next`.
+    #[inline]
+    fn concat(&self, next: &Self) -> Self {
+        // The line made of the last line of `self` and the first line of
+        // `next`.
+        let joined = self.last_not_blank || next.first_not_blank;
+
+        match (self.has_line_break, next.has_line_break) {
+            (false, false) => Self {
+                first_not_blank: joined,
+                last_not_blank: joined,
+                ..Self::default()
+            },
+
+            (false, true) => Self { first_not_blank: joined, ..*next },
+
+            (true, false) => Self { last_not_blank: joined, ..*self },
+
+            (true, true) => Self {
+                inner: self
+                    .inner
+                    .concat(&LineRuns::line(joined))
+                    .concat(&next.inner),
+                first_not_blank: self.first_not_blank,
+                last_not_blank: next.last_not_blank,
+                has_line_break: true,
+            },
+        }
+    }
+
+    /// Returns the runs of the lines ending with a line break, i.e. of all
+    /// the lines but the last one.
+    #[inline]
+    fn whole_lines(&self) -> LineRuns {
+        if self.has_line_break {
+            LineRuns::line(self.first_not_blank).concat(&self.inner)
+        } else {
+            LineRuns::default()
+        }
+    }
+
+    /// Returns the runs of all the lines.
+    #[inline]
+    fn all_lines(&self) -> LineRuns {
+        if self.has_line_break {
+            self.whole_lines().concat(&LineRuns::line(self.last_not_blank))
+        } else {
+            LineRuns::line(self.first_not_blank)
+        }
+    }
+
+    #[inline]
+    fn fields(&self) -> [usize; Self::FIELDS] {
+        let flags = self.inner.has_lines as usize
+            | (self.inner.first_not_blank as usize) << 1
+            | (self.inner.last_not_blank as usize) << 2
+            | (self.first_not_blank as usize) << 3
+            | (self.last_not_blank as usize) << 4
+            | (self.has_line_break as usize) << 5;
+
+        [from_count(self.inner.runs), from_count(self.inner.ended_runs), flags]
+    }
+
+    #[inline]
+    fn from_fields(fields: &mut impl Iterator<Item = usize>) -> Option<Self> {
+        let runs = Count::try_from(fields.next()?).ok()?;
+        let ended_runs = Count::try_from(fields.next()?).ok()?;
+
+        let flags = fields.next()?;
+
+        if flags > 0b11_1111 {
+            return None;
+        }
+
+        Some(Self {
+            inner: LineRuns {
+                runs,
+                ended_runs,
+                has_lines: flags & 1 != 0,
+                first_not_blank: flags & 0b10 != 0,
+                last_not_blank: flags & 0b100 != 0,
+            },
+            first_not_blank: flags & 0b1000 != 0,
+            last_not_blank: flags & 0b1_0000 != 0,
+            has_line_break: flags & 0b10_0000 != 0,
+        })
+    }
+}
+
+/// The runs of lines which aren't blank in a sequence of lines, together with
+/// whether the first and last lines of the sequence are blank.
+#[cfg(feature = "paragraph-count")]
+#[derive(Copy, Clone, Default, Debug, PartialEq)]
+struct LineRuns {
+    runs: Count,
+
+    /// The number of runs followed by a blank line.
+    ended_runs: Count,
+
+    has_lines: bool,
+    first_not_blank: bool,
+    last_not_blank: bool,
+}
+
+#[cfg(feature = "paragraph-count")]
+impl LineRuns {
+    /// Returns the runs of a single line.
+    #[inline]
+    fn line(not_blank: bool) -> Self {
+        Self {
+            runs: not_blank as Count,
+            ended_runs: 0,
+            has_lines: true,
+            first_not_blank: not_blank,
+            last_not_blank: not_blank,
+        }
+    }
+
+    /// Returns the runs of the lines of `self` followed by the lines of
+    /// `next`.
+    #[inline]
+    fn concat(&self, next: &Self) -> Self {
+        if !self.has_lines {
+            return *next;
+        } else if !next.has_lines {
+            return *self;
+        }
+
+        Self {
+            runs: add(self.runs, next.runs)
+                - (self.last_not_blank && next.first_not_blank) as Count,
+            ended_runs: add(
+                add(self.ended_runs, next.ended_runs),
+                (self.last_not_blank && !next.first_not_blank) as Count,
+            ),
+            has_lines: true,
+            first_not_blank: self.first_not_blank,
+            last_not_blank: next.last_not_blank,
+        }
+    }
+
+    #[inline]
+    fn runs(&self) -> usize {
+        from_count(self.runs)
+    }
+
+    #[inline]
+    fn ended_runs(&self) -> usize {
+        from_count(self.ended_runs)
+    }
+}
+
+#[cfg(all(
+    test,
+    any(
+        feature = "max-line-len",
+        feature = "word-count",
+        feature = "grapheme-count",
+        feature = "fingerprint",
+        feature = "paragraph-count"
+    )
+))]
+mod tests {
+    use super::*;
+    #[cfg(any(feature = "max-line-len", feature = "paragraph-count"))]
+    use crate::rope::line_breaks::{LfBreaks, UnicodeBreaks};
+
+    /// Checks that the lengths of `s` are the same when it's split at every
+    /// char boundary and the pieces are concatenated.
+    #[cfg(feature = "max-line-len")]
+    fn check<B: LineBreaks>(s: &str) {
+        let whole = LineLens::of::<B>(s);
+
+        for (idx, _) in s.char_indices().skip(1) {
+            let (left, right) = s.split_at(idx);
+            let concat =
+                LineLens::of::<B>(left).concat(&LineLens::of::<B>(right));
+            assert_eq!(whole, concat, "{s:?} split at {idx}");
+        }
+
+        assert_eq!(whole, whole.concat(&LineLens::default()));
+        assert_eq!(whole, LineLens::default().concat(&whole));
+
+        let fields = whole.fields();
+        assert_eq!(
+            Some(whole),
+            LineLens::from_fields(&mut fields.into_iter())
+        );
+    }
+
+    #[cfg(feature = "max-line-len")]
+    #[test]
+    fn line_lens_concat() {
+        for s in [
+            "",
+            "a",
+            "\n",
+            "\r\n",
+            "\r",
+            "foo\r\nbar\r\n",
+            "foo\nbarbaz\r\nqux",
+            "\n\n\nabc\r",
+            "中文\r\n\r\nab\u{2028}cd\r\u{000B}e",
+        ] {
+            check::<LfBreaks>(s);
+            check::<UnicodeBreaks>(s);
+        }
+    }
+
+    #[cfg(feature = "max-line-len")]
+    #[test]
+    fn line_lens_max_len() {
+        let s = "foo\nbarbaz\r\nqux\n";
+        assert_eq!(LineLens::of::<LfBreaks>(s).max_len(), 6);
+
+        let s = "ab\r\u{2028}cd";
+        assert_eq!(LineLens::of::<UnicodeBreaks>(s).max_len(), 3);
+    }
+
+    #[cfg(feature = "word-count")]
+    #[test]
+    fn words_concat() {
+        for s in ["", " ", "a", "foo bar", " foo\tbar \n", "a\u{3000}b c  "] {
+            let whole = Words::of(s);
+
+            assert_eq!(from_count(whole.words), s.split_whitespace().count());
+
+            for (idx, _) in s.char_indices().skip(1) {
+                let (left, right) = s.split_at(idx);
+                let concat = Words::of(left).concat(&Words::of(right));
+                assert_eq!(whole, concat, "{s:?} split at {idx}");
+            }
+
+            assert_eq!(whole, whole.concat(&Words::default()));
+            assert_eq!(whole, Words::default().concat(&whole));
+
+            let fields = whole.fields();
+            assert_eq!(
+                Some(whole),
+                Words::from_fields(&mut fields.into_iter())
+            );
+        }
+    }
+
+    #[cfg(feature = "grapheme-count")]
+    #[test]
+    fn graphemes_concat() {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        for s in [
+            "",
+            "a",
+            "\r\n",
+            "foo\r\nbar",
+            "e\u{301}\u{302}x",
+            "中文\r",
+            "🇺🇸🇫🇷",
+            "👩\u{200D}👩\u{200D}👧",
+            "\u{915}\u{94D}\u{937}",
+        ] {
+            let whole = Graphemes::of(s);
+
+            assert_eq!(from_count(whole.graphemes), s.graphemes(true).count());
+
+            for (idx, _) in s.char_indices().skip(1) {
+                let (left, right) = s.split_at(idx);
+                let concat = Graphemes::of(left).concat(&Graphemes::of(right));
+
+                assert_eq!(
+                    (whole.first, whole.last),
+                    (concat.first, concat.last)
+                );
+
+                // The count is only exact if there's no unsure join.
+                if concat.unsure_joins == 0 {
+                    assert_eq!(whole, concat, "{s:?} split at {idx}");
+                }
+
+                let mut joined = concat;
+                joined.join_segments(left, right);
+                assert_eq!(whole, joined, "{s:?} joined at {idx}");
+            }
+
+            assert_eq!(whole, whole.concat(&Graphemes::default()));
+            assert_eq!(whole, Graphemes::default().concat(&whole));
+
+            let fields = whole.fields();
+            assert_eq!(
+                Some(whole),
+                Graphemes::from_fields(&mut fields.into_iter())
+            );
+        }
+    }
+
+    #[cfg(feature = "grapheme-count")]
+    #[test]
+    fn graphemes_unsure_joins() {
+        // Regional indicators.
+        assert!(is_unsure_join('🇺', '🇸'));
+        // Emoji ZWJ sequences.
+        assert!(is_unsure_join('\u{200D}', '👩'));
+        // Indic conjuncts.
+        assert!(is_unsure_join('\u{94D}', '\u{937}'));
+        // Combining marks.
+        assert!(is_unsure_join('e', '\u{301}'));
+
+        assert!(!is_unsure_join('a', 'b'));
+        assert!(!is_unsure_join('\r', '\n'));
+        assert!(!is_unsure_join('中', '文'));
+        assert!(!is_unsure_join('👩', 'a'));
+        assert!(!is_unsure_join('\u{915}', '\u{937}'));
+    }
+
+    #[cfg(feature = "fingerprint")]
+    #[test]
+    fn fingerprint_concat() {
+        for s in ["", "a", "foo bar", "中文\r\n🐻‍❄️", "\0\0"] {
+            let whole = Fingerprint::of(s);
+
+            for (idx, _) in s.char_indices().skip(1) {
+                let (left, right) = s.split_at(idx);
+                let concat =
+                    Fingerprint::of(left).concat(&Fingerprint::of(right));
+                assert_eq!(whole, concat, "{s:?} split at {idx}");
+            }
+
+            assert_eq!(whole, whole.concat(&Fingerprint::default()));
+            assert_eq!(whole, Fingerprint::default().concat(&whole));
+
+            let fields = whole.fields();
+            assert_eq!(
+                Some(whole),
+                Fingerprint::from_fields(&mut fields.into_iter())
+            );
+        }
+
+        assert_ne!(Fingerprint::of("ab"), Fingerprint::of("ba"));
+        assert_ne!(Fingerprint::of("\0"), Fingerprint::of("\0\0"));
+    }
+
+    /// Checks that the paragraphs of `s` are the same when it's split at
+    /// every char boundary and the pieces are concatenated, and that they're
+    /// the runs of its non-blank lines.
+    #[cfg(feature = "paragraph-count")]
+    fn check_paragraphs<B: LineBreaks>(s: &str) {
+        let whole = Paragraphs::of::<B>(s);
+
+        let lines = s
+            .split(B::is_line_break)
+            .map(|line| line.trim().is_empty())
+            .collect::<Vec<_>>();
+
+        // Whether every line is blank, paired with whether the line before
+        // it is.
+        let with_prev = |lines: &[bool]| {
+            core::iter::once(true)
+                .chain(lines.iter().copied())
+                .zip(lines.iter().copied())
+                .collect::<Vec<_>>()
+        };
+
+        let runs = with_prev(&lines)
+            .into_iter()
+            .filter(|&(prev_is_blank, is_blank)| prev_is_blank && !is_blank)
+            .count();
+
+        // The last line is the one which doesn't end with a line break.
+        let ended_runs = with_prev(&lines[..lines.len() - 1])
+            .into_iter()
+            .filter(|&(prev_is_blank, is_blank)| !prev_is_blank && is_blank)
+            .count();
+
+        assert_eq!(whole.all_lines().runs(), runs, "{s:?}");
+        assert_eq!(whole.whole_lines().ended_runs(), ended_runs, "{s:?}");
+
+        for (idx, _) in s.char_indices().skip(1) {
+            let (left, right) = s.split_at(idx);
+            let concat =
+                Paragraphs::of::<B>(left).concat(&Paragraphs::of::<B>(right));
+            assert_eq!(whole, concat, "{s:?} split at {idx}");
+        }
+
+        assert_eq!(whole, whole.concat(&Paragraphs::default()));
+        assert_eq!(whole, Paragraphs::default().concat(&whole));
+
+        let fields = whole.fields();
+        assert_eq!(
+            Some(whole),
+            Paragraphs::from_fields(&mut fields.into_iter())
+        );
+    }
+
+    #[cfg(feature = "paragraph-count")]
+    #[test]
+    fn paragraphs_concat() {
+        for s in [
+            "",
+            " ",
+            "a",
+            "\n",
+            "foo\nbar\n\n  \nbaz\n",
+            "\n\n foo\r\n\r\nbar\r\n \t",
+            "a\u{2028}\u{2028}b\u{2029}c\u{3000}\n\u{85}d",
+        ] {
+            check_paragraphs::<LfBreaks>(s);
+            check_paragraphs::<UnicodeBreaks>(s);
+        }
+    }
+}
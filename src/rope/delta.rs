@@ -0,0 +1,651 @@
+use core::ops::Range;
+
+use super::anchors::Gravity;
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use super::RopeWith;
+use crate::tree::RefCounter;
+
+/// A single operation of a [`Delta`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeltaOp {
+    /// Keeps the given number of bytes of the base text.
+    Retain(usize),
+
+    /// Deletes the given number of bytes of the base text.
+    Delete(usize),
+
+    /// Inserts the given text.
+    Insert(String),
+}
+
+/// A description of the edits that turn a text of a given length into
+/// another one.
+///
+/// A `Delta` is a sequence of [`DeltaOp`]s which walk the base text from
+/// start to end, retaining, deleting or inserting text along the way. All
+/// the lengths are in bytes, and every operation has to start and end on a
+/// code point boundary of the text it's applied to.
+///
+/// The operations are always kept in a canonical form: adjacent operations
+/// of the same kind are merged, no operation is empty and a deletion always
+/// comes before an insertion at the same offset. This means that two
+/// `Delta`s which do the same thing always compare equal.
+///
+/// # Examples
+///
+/// ```
+/// # use crop::{Delta, Rope};
+/// #
+/// let mut r = Rope::from("Hello Earth!");
+///
+/// let mut delta = Delta::new();
+/// delta.retain(6).delete(5).insert("Saturn").retain(1);
+///
+/// assert_eq!(delta.base_len(), 12);
+/// assert_eq!(delta.target_len(), 13);
+///
+/// let undo = delta.invert(&r);
+///
+/// delta.apply(&mut r);
+/// assert_eq!(r, "Hello Saturn!");
+///
+/// undo.apply(&mut r);
+/// assert_eq!(r, "Hello Earth!");
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Delta {
+    ops: Vec<DeltaOp>,
+    base_len: usize,
+    target_len: usize,
+}
+
+impl Delta {
+    /// Applies the `Delta` to the given `Rope`, performing each operation
+    /// with [`Rope::replace()`](super::Rope::replace()).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte length of the `Rope` is not equal to
+    /// [`base_len()`](Self::base_len()), or if any of the operations doesn't
+    /// start or end on a code point boundary.
+    #[track_caller]
+    #[inline]
+    pub fn apply<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    >(
+        &self,
+        rope: &mut RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) {
+        self.for_each_edit(rope.byte_len(), |byte_range, text| {
+            rope.replace(byte_range, text)
+        });
+    }
+
+    /// Calls `replace` with the byte range and the text of every deletion
+    /// and insertion of the `Delta`, in the order in which they have to be
+    /// made to apply it to a text of `byte_len` bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `byte_len` is not equal to [`base_len()`](Self::base_len()).
+    #[track_caller]
+    #[inline]
+    pub(super) fn for_each_edit<F>(&self, byte_len: usize, mut replace: F)
+    where
+        F: FnMut(Range<usize>, &str),
+    {
+        assert_eq!(
+            byte_len, self.base_len,
+            "the delta can only be applied to a rope of {} bytes, but the \
+             rope has {}",
+            self.base_len, byte_len
+        );
+
+        let mut offset = 0;
+
+        let mut ops = self.ops.iter().peekable();
+
+        while let Some(op) = ops.next() {
+            match op {
+                DeltaOp::Retain(n) => offset += n,
+
+                DeltaOp::Delete(n) => {
+                    let text = match ops.peek() {
+                        Some(DeltaOp::Insert(text)) => {
+                            ops.next();
+                            text.as_str()
+                        },
+                        _ => "",
+                    };
+                    replace(offset..offset + n, text);
+                    offset += text.len();
+                },
+
+                DeltaOp::Insert(text) => {
+                    replace(offset..offset, text);
+                    offset += text.len();
+                },
+            }
+        }
+    }
+
+    /// Returns the length of the text the `Delta` can be applied to.
+    #[inline]
+    pub fn base_len(&self) -> usize {
+        self.base_len
+    }
+
+    /// Returns a `Delta` which has the same effect as applying this `Delta`
+    /// followed by `other`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the [`target_len()`](Self::target_len()) of this `Delta` is
+    /// not equal to the [`base_len()`](Self::base_len()) of `other`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Delta, Rope};
+    /// #
+    /// let mut first = Delta::new();
+    /// first.retain(6).insert("big ").retain(6);
+    ///
+    /// let mut second = Delta::new();
+    /// second.retain(10).delete(5).insert("world").retain(1);
+    ///
+    /// let mut r = Rope::from("Hello Earth!");
+    /// first.compose(&second).apply(&mut r);
+    /// assert_eq!(r, "Hello big world!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn compose(&self, other: &Self) -> Self {
+        assert_eq!(
+            self.target_len, other.base_len,
+            "can't compose a delta producing {} bytes with one applying to \
+             {} bytes",
+            self.target_len, other.base_len
+        );
+
+        let mut composed = Self::new();
+
+        let mut ops1 = self.ops.iter().cloned();
+        let mut ops2 = other.ops.iter().cloned();
+
+        let mut op1 = ops1.next();
+        let mut op2 = ops2.next();
+
+        loop {
+            match (op1, op2) {
+                (None, None) => break,
+
+                (Some(DeltaOp::Delete(n)), op) => {
+                    composed.delete(n);
+                    op1 = ops1.next();
+                    op2 = op;
+                },
+
+                (op, Some(DeltaOp::Insert(text))) => {
+                    composed.insert(text);
+                    op1 = op;
+                    op2 = ops2.next();
+                },
+
+                (Some(first), Some(second)) => {
+                    let len = op_len(&first).min(op_len(&second));
+
+                    match (&first, &second) {
+                        (DeltaOp::Retain(_), DeltaOp::Retain(_)) => {
+                            composed.retain(len);
+                        },
+
+                        (DeltaOp::Retain(_), DeltaOp::Delete(_)) => {
+                            composed.delete(len);
+                        },
+
+                        (DeltaOp::Insert(text), DeltaOp::Retain(_)) => {
+                            composed.insert(&text[..len]);
+                        },
+
+                        // The text inserted by the first delta is deleted by
+                        // the second one.
+                        (DeltaOp::Insert(_), DeltaOp::Delete(_)) => {},
+
+                        _ => unreachable!(),
+                    }
+
+                    op1 = split_op(first, len).or_else(|| ops1.next());
+                    op2 = split_op(second, len).or_else(|| ops2.next());
+                },
+
+                _ => unreachable!(
+                    "the lengths of the deltas were checked to match"
+                ),
+            }
+        }
+
+        composed
+    }
+
+    /// Deletes the next `byte_len` bytes of the base text.
+    #[inline]
+    pub fn delete(&mut self, byte_len: usize) -> &mut Self {
+        if byte_len == 0 {
+            return self;
+        }
+
+        self.base_len += byte_len;
+
+        // Keep deletions before insertions at the same offset.
+        let insert = match self.ops.last() {
+            Some(DeltaOp::Insert(_)) => self.ops.pop(),
+            _ => None,
+        };
+
+        match self.ops.last_mut() {
+            Some(DeltaOp::Delete(n)) => *n += byte_len,
+            _ => self.ops.push(DeltaOp::Delete(byte_len)),
+        }
+
+        self.ops.extend(insert);
+
+        self
+    }
+
+    /// Inserts `text` at the current offset.
+    #[inline]
+    pub fn insert<T>(&mut self, text: T) -> &mut Self
+    where
+        T: AsRef<str>,
+    {
+        let text = text.as_ref();
+
+        if text.is_empty() {
+            return self;
+        }
+
+        self.target_len += text.len();
+
+        match self.ops.last_mut() {
+            Some(DeltaOp::Insert(s)) => s.push_str(text),
+            _ => self.ops.push(DeltaOp::Insert(text.to_owned())),
+        }
+
+        self
+    }
+
+    /// Returns the `Delta` which undoes this one, i.e. the one which turns
+    /// the result of applying this `Delta` to `base` back into `base`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte length of `base` is not equal to
+    /// [`base_len()`](Self::base_len()).
+    #[track_caller]
+    #[inline]
+    pub fn invert<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    >(
+        &self,
+        base: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> Self {
+        assert_eq!(
+            base.byte_len(),
+            self.base_len,
+            "the delta can only be inverted against a rope of {} bytes, but \
+             the rope has {}",
+            self.base_len,
+            base.byte_len()
+        );
+
+        let mut inverted = Self::new();
+
+        let mut offset = 0;
+
+        for op in &self.ops {
+            match op {
+                DeltaOp::Retain(n) => {
+                    inverted.retain(*n);
+                    offset += n;
+                },
+
+                DeltaOp::Delete(n) => {
+                    for chunk in base.byte_slice(offset..offset + n).chunks() {
+                        inverted.insert(chunk);
+                    }
+                    offset += n;
+                },
+
+                DeltaOp::Insert(text) => {
+                    inverted.delete(text.len());
+                },
+            }
+        }
+
+        inverted
+    }
+
+    /// Returns `true` if applying the `Delta` leaves the text unchanged.
+    #[inline]
+    pub fn is_noop(&self) -> bool {
+        self.ops.iter().all(|op| matches!(op, DeltaOp::Retain(_)))
+    }
+
+    /// Maps a byte offset in the base text to the corresponding byte offset
+    /// in the target text.
+    ///
+    /// Offsets which are deleted or at which some text is inserted are
+    /// resolved using the given [`Gravity`], in the same way the
+    /// [`Anchor`](crate::Anchor)s of a [`Tracked`](crate::Tracked) rope are.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is greater than
+    /// [`base_len()`](Self::base_len()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Delta, Gravity};
+    /// #
+    /// let mut delta = Delta::new();
+    /// delta.retain(3).insert("abc").retain(3);
+    ///
+    /// assert_eq!(delta.map(2, Gravity::Left), 2);
+    /// assert_eq!(delta.map(3, Gravity::Left), 3);
+    /// assert_eq!(delta.map(3, Gravity::Right), 6);
+    /// assert_eq!(delta.map(4, Gravity::Left), 7);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn map(&self, byte_offset: usize, gravity: Gravity) -> usize {
+        assert!(
+            byte_offset <= self.base_len,
+            "byte offset out of bounds: the offset is {} but the delta's \
+             base length is {}",
+            byte_offset,
+            self.base_len
+        );
+
+        let mut offset = byte_offset;
+
+        // The offsets in the base and target texts up to which the
+        // operations have been walked.
+        let mut old = 0;
+        let mut new = 0;
+
+        for op in &self.ops {
+            match op {
+                DeltaOp::Retain(n) => {
+                    if offset < old + n {
+                        return new + offset - old;
+                    }
+                    old += n;
+                    new += n;
+                },
+
+                DeltaOp::Delete(n) => {
+                    if offset <= old + n {
+                        if gravity == Gravity::Left {
+                            return new;
+                        }
+                        offset = old + n;
+                    }
+                    old += n;
+                },
+
+                DeltaOp::Insert(text) => {
+                    if offset == old && gravity == Gravity::Left {
+                        return new;
+                    }
+                    new += text.len();
+                },
+            }
+        }
+
+        new
+    }
+
+    /// Creates a new `Delta` with no operations, which can only be applied
+    /// to an empty text.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the operations of the `Delta`.
+    #[inline]
+    pub fn ops(&self) -> &[DeltaOp] {
+        &self.ops
+    }
+
+    /// Records that the bytes in `replaced` of the target text have been
+    /// replaced by the given chunks, so that the `Delta` produces the edited
+    /// text.
+    ///
+    /// Edits which only touch the text retained at the end of the `Delta`
+    /// are appended to its operations. The other ones are composed with it,
+    /// which walks all its operations.
+    #[inline]
+    pub(super) fn push_edit<'a, I>(
+        &mut self,
+        replaced: Range<usize>,
+        inserted: I,
+    ) where
+        I: IntoIterator<Item = &'a str>,
+    {
+        let trailing = match self.ops.last() {
+            Some(DeltaOp::Retain(n)) => *n,
+            _ => 0,
+        };
+
+        let tail_start = self.target_len - trailing;
+
+        if replaced.start < tail_start {
+            let mut edit = Self::new();
+
+            edit.retain(replaced.start).delete(replaced.len());
+
+            for chunk in inserted {
+                edit.insert(chunk);
+            }
+
+            edit.retain(self.target_len - replaced.end);
+
+            *self = self.compose(&edit);
+
+            return;
+        }
+
+        if trailing > 0 {
+            self.ops.pop();
+            self.base_len -= trailing;
+            self.target_len -= trailing;
+        }
+
+        self.retain(replaced.start - tail_start).delete(replaced.len());
+
+        for chunk in inserted {
+            self.insert(chunk);
+        }
+
+        self.retain(tail_start + trailing - replaced.end);
+    }
+
+    /// Keeps the next `byte_len` bytes of the base text.
+    #[inline]
+    pub fn retain(&mut self, byte_len: usize) -> &mut Self {
+        if byte_len == 0 {
+            return self;
+        }
+
+        self.base_len += byte_len;
+        self.target_len += byte_len;
+
+        match self.ops.last_mut() {
+            Some(DeltaOp::Retain(n)) => *n += byte_len,
+            _ => self.ops.push(DeltaOp::Retain(byte_len)),
+        }
+
+        self
+    }
+
+    /// Returns the length of the text produced by applying the `Delta`.
+    #[inline]
+    pub fn target_len(&self) -> usize {
+        self.target_len
+    }
+
+    /// Transforms two `Delta`s made concurrently against the same text so
+    /// that each one can be applied after the other.
+    ///
+    /// Returns a pair `(self', other')` where `self'` has the same effect as
+    /// `self` but applies to the result of `other`, and vice versa. Applying
+    /// `self` followed by `other'` gives the same text as applying `other`
+    /// followed by `self'`, which is the building block of collaborative
+    /// editing.
+    ///
+    /// When both `Delta`s insert some text at the same offset, the text
+    /// inserted by `self` comes first. Cursors and other offsets can be
+    /// moved across the concurrent edits with [`map()`](Self::map()).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the two `Delta`s don't have the same
+    /// [`base_len()`](Self::base_len()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Delta, Rope};
+    /// #
+    /// let base = Rope::from("Hello Earth!");
+    ///
+    /// let mut alice = Delta::new();
+    /// alice.retain(6).delete(5).insert("Saturn").retain(1);
+    ///
+    /// let mut bob = Delta::new();
+    /// bob.insert("Oh, ").retain(11).insert("!!").retain(1);
+    ///
+    /// let (alice_after_bob, bob_after_alice) = alice.transform(&bob);
+    ///
+    /// let mut r1 = base.clone();
+    /// alice.apply(&mut r1);
+    /// bob_after_alice.apply(&mut r1);
+    ///
+    /// let mut r2 = base.clone();
+    /// bob.apply(&mut r2);
+    /// alice_after_bob.apply(&mut r2);
+    ///
+    /// assert_eq!(r1, "Oh, Hello Saturn!!!");
+    /// assert_eq!(r1, r2);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn transform(&self, other: &Self) -> (Self, Self) {
+        assert_eq!(
+            self.base_len, other.base_len,
+            "can't transform deltas applying to texts of different lengths: \
+             {} and {} bytes",
+            self.base_len, other.base_len
+        );
+
+        let mut first = Self::new();
+        let mut second = Self::new();
+
+        let mut ops1 = self.ops.iter().cloned();
+        let mut ops2 = other.ops.iter().cloned();
+
+        let mut op1 = ops1.next();
+        let mut op2 = ops2.next();
+
+        loop {
+            match (op1, op2) {
+                (None, None) => break,
+
+                (Some(DeltaOp::Insert(text)), op) => {
+                    second.retain(text.len());
+                    first.insert(text);
+                    op1 = ops1.next();
+                    op2 = op;
+                },
+
+                (op, Some(DeltaOp::Insert(text))) => {
+                    first.retain(text.len());
+                    second.insert(text);
+                    op1 = op;
+                    op2 = ops2.next();
+                },
+
+                (Some(op), Some(other_op)) => {
+                    let len = op_len(&op).min(op_len(&other_op));
+
+                    match (&op, &other_op) {
+                        (DeltaOp::Retain(_), DeltaOp::Retain(_)) => {
+                            first.retain(len);
+                            second.retain(len);
+                        },
+
+                        (DeltaOp::Delete(_), DeltaOp::Retain(_)) => {
+                            first.delete(len);
+                        },
+
+                        (DeltaOp::Retain(_), DeltaOp::Delete(_)) => {
+                            second.delete(len);
+                        },
+
+                        // Both deltas delete the same text.
+                        (DeltaOp::Delete(_), DeltaOp::Delete(_)) => {},
+
+                        _ => unreachable!(),
+                    }
+
+                    op1 = split_op(op, len).or_else(|| ops1.next());
+                    op2 = split_op(other_op, len).or_else(|| ops2.next());
+                },
+
+                _ => unreachable!(
+                    "the lengths of the deltas were checked to match"
+                ),
+            }
+        }
+
+        (first, second)
+    }
+}
+
+/// Returns the number of bytes the operation retains, deletes or inserts.
+#[inline]
+fn op_len(op: &DeltaOp) -> usize {
+    match op {
+        DeltaOp::Retain(n) | DeltaOp::Delete(n) => *n,
+        DeltaOp::Insert(text) => text.len(),
+    }
+}
+
+/// Returns what's left of the operation after its first `len` bytes, or
+/// `None` if it's fully consumed.
+#[inline]
+fn split_op(op: DeltaOp, len: usize) -> Option<DeltaOp> {
+    if op_len(&op) == len {
+        return None;
+    }
+
+    Some(match op {
+        DeltaOp::Retain(n) => DeltaOp::Retain(n - len),
+        DeltaOp::Delete(n) => DeltaOp::Delete(n - len),
+        DeltaOp::Insert(text) => DeltaOp::Insert(text[len..].to_owned()),
+    })
+}
@@ -0,0 +1,635 @@
+//! This module contains the logic used to implement [`Rope::diff()`] and
+//! [`Rope::changed_ranges()`].
+//!
+//! The common prefix and suffix of the two ropes are found by first
+//! skipping the subtrees they share, and then by walking their chunks and
+//! comparing them byte by byte, again skipping the chunks they share.
+//! What's left in the middle is diffed line by line using Myers' algorithm,
+//! and every group of changed lines is then diffed again character by
+//! character.
+//!
+//! Myers' algorithm is quadratic in the number of edits, so to keep
+//! unrelated texts from taking forever both passes give up once they've
+//! explored `myers::MAX_COST` edits, replacing the whole group with a
+//! single deletion and insertion, and groups longer than
+//! `MAX_CHAR_DIFF_LEN` aren't diffed by characters at all.
+
+use core::ops::Range;
+
+use super::delta::Delta;
+use super::line_breaks::LineBreaks;
+use super::metrics::{ByteMetric, CharWeight, MetricSet};
+use super::RopeWith;
+use crate::tree::RefCounter;
+
+/// The maximum combined byte length of a group of changed lines which is
+/// diffed character by character. Longer groups are replaced as a whole.
+const MAX_CHAR_DIFF_LEN: usize = 16 * 1024;
+
+/// Returns the [`Delta`] which turns `old` into `new`.
+#[inline]
+pub(super) fn diff<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    old: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    new: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+) -> Delta {
+    let prefix = common_prefix(old, new);
+
+    let suffix = common_suffix(old, new, prefix);
+
+    let mut delta = Delta::new();
+
+    delta.retain(prefix);
+
+    if prefix + suffix < old.byte_len().max(new.byte_len()) {
+        let old_middle =
+            String::from(old.byte_slice(prefix..old.byte_len() - suffix));
+
+        let new_middle =
+            String::from(new.byte_slice(prefix..new.byte_len() - suffix));
+
+        diff_lines(&old_middle, &new_middle, &mut delta);
+    }
+
+    delta.retain(suffix);
+
+    delta
+}
+
+/// Returns the byte ranges of `old` and `new` which differ.
+///
+/// The ranges of the leaves which aren't shared by the two ropes are found
+/// by walking their trees, and are then shrunk by skipping the bytes at their
+/// start and at their end which are equal in the two ropes.
+#[inline]
+pub(super) fn changed_ranges<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    old: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    new: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+) -> Vec<(Range<usize>, Range<usize>)> {
+    old.tree
+        .changed_ranges::<ByteMetric>(&new.tree)
+        .into_iter()
+        .filter_map(|(old_range, new_range)| {
+            let old_range = old_range.start.0..old_range.end.0;
+            let new_range = new_range.start.0..new_range.end.0;
+            shrink_changed_range(old, new, old_range, new_range)
+        })
+        .collect()
+}
+
+/// Shrinks the two byte ranges by removing the common prefix and suffix of
+/// the text they contain, keeping them on code point boundaries. Returns
+/// `None` if the text is the same.
+#[inline]
+fn shrink_changed_range<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    old: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    new: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    old_range: Range<usize>,
+    new_range: Range<usize>,
+) -> Option<(Range<usize>, Range<usize>)> {
+    let old_bytes = old.byte_slice(old_range.clone()).bytes();
+    let new_bytes = new.byte_slice(new_range.clone()).bytes();
+
+    let mut prefix = old_bytes
+        .clone()
+        .zip(new_bytes.clone())
+        .take_while(|(o, n)| o == n)
+        .count();
+
+    if prefix == old_range.len() && prefix == new_range.len() {
+        return None;
+    }
+
+    while !old.is_char_boundary(old_range.start + prefix) {
+        prefix -= 1;
+    }
+
+    let max_suffix = old_range.len().min(new_range.len()) - prefix;
+
+    let mut suffix = old_bytes
+        .rev()
+        .zip(new_bytes.rev())
+        .take(max_suffix)
+        .take_while(|(o, n)| o == n)
+        .count();
+
+    while !old.is_char_boundary(old_range.end - suffix) {
+        suffix -= 1;
+    }
+
+    Some((
+        old_range.start + prefix..old_range.end - suffix,
+        new_range.start + prefix..new_range.end - suffix,
+    ))
+}
+
+/// Returns the byte length of the longest common prefix of the two ropes,
+/// rounded down to a code point boundary.
+#[inline]
+pub(super) fn common_prefix<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    old: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    new: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+) -> usize {
+    // Start by skipping the subtrees shared by the two ropes.
+    let mut prefix = old.tree.shared_prefix(&new.tree).bytes();
+
+    let mut old_chunks = old.byte_slice(prefix..).chunks();
+    let mut new_chunks = new.byte_slice(prefix..).chunks();
+
+    let mut old_chunk: &[u8] = &[];
+    let mut new_chunk: &[u8] = &[];
+
+    loop {
+        if old_chunk.is_empty() {
+            let Some(chunk) = old_chunks.next() else { break };
+            old_chunk = chunk.as_bytes();
+        }
+
+        if new_chunk.is_empty() {
+            let Some(chunk) = new_chunks.next() else { break };
+            new_chunk = chunk.as_bytes();
+        }
+
+        // The chunk is shared by the two ropes.
+        if core::ptr::eq(old_chunk, new_chunk) {
+            prefix += old_chunk.len();
+            old_chunk = &[];
+            new_chunk = &[];
+            continue;
+        }
+
+        let len = old_chunk.len().min(new_chunk.len());
+
+        let equal = old_chunk[..len]
+            .iter()
+            .zip(&new_chunk[..len])
+            .take_while(|(old, new)| old == new)
+            .count();
+
+        prefix += equal;
+
+        if equal < len {
+            break;
+        }
+
+        old_chunk = &old_chunk[len..];
+        new_chunk = &new_chunk[len..];
+    }
+
+    while !old.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+
+    prefix
+}
+
+/// Returns the byte length of the longest common suffix of the two ropes
+/// which doesn't overlap with their common prefix, rounded down to a code
+/// point boundary.
+#[inline]
+pub(super) fn common_suffix<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    old: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    new: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    prefix: usize,
+) -> usize {
+    let max_suffix = old.byte_len().min(new.byte_len()) - prefix;
+
+    // Start by skipping the subtrees shared by the two ropes. The shared
+    // suffix can overlap with the common prefix, in which case there's
+    // nothing left to compare.
+    let shared = old.tree.shared_suffix(&new.tree).bytes();
+
+    let mut suffix = if shared >= max_suffix {
+        max_suffix
+    } else {
+        walk_suffix(old, new, shared, max_suffix).min(max_suffix)
+    };
+
+    while !old.is_char_boundary(old.byte_len() - suffix) {
+        suffix -= 1;
+    }
+
+    suffix
+}
+
+/// Returns the byte length of the common suffix of the two ropes, given that
+/// their last `suffix` bytes are known to be equal, stopping once it reaches
+/// `max_suffix` bytes.
+#[inline]
+fn walk_suffix<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    old: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    new: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    mut suffix: usize,
+    max_suffix: usize,
+) -> usize {
+    let mut old_chunks =
+        old.byte_slice(..old.byte_len() - suffix).chunks().rev();
+
+    let mut new_chunks =
+        new.byte_slice(..new.byte_len() - suffix).chunks().rev();
+
+    let mut old_chunk: &[u8] = &[];
+    let mut new_chunk: &[u8] = &[];
+
+    while suffix < max_suffix {
+        if old_chunk.is_empty() {
+            let Some(chunk) = old_chunks.next() else { break };
+            old_chunk = chunk.as_bytes();
+        }
+
+        if new_chunk.is_empty() {
+            let Some(chunk) = new_chunks.next() else { break };
+            new_chunk = chunk.as_bytes();
+        }
+
+        if core::ptr::eq(old_chunk, new_chunk) {
+            suffix += old_chunk.len();
+            old_chunk = &[];
+            new_chunk = &[];
+            continue;
+        }
+
+        let len = old_chunk.len().min(new_chunk.len());
+
+        let equal = old_chunk[old_chunk.len() - len..]
+            .iter()
+            .rev()
+            .zip(new_chunk[new_chunk.len() - len..].iter().rev())
+            .take_while(|(old, new)| old == new)
+            .count();
+
+        suffix += equal;
+
+        if equal < len {
+            break;
+        }
+
+        old_chunk = &old_chunk[..old_chunk.len() - len];
+        new_chunk = &new_chunk[..new_chunk.len() - len];
+    }
+
+    suffix
+}
+
+/// Pushes the operations turning `old` into `new` to `delta`, first
+/// diffing the two strings by lines and then diffing the changed lines by
+/// characters.
+#[inline]
+fn diff_lines(old: &str, new: &str, delta: &mut Delta) {
+    let old_lines = old.split_inclusive('\n').collect::<Vec<_>>();
+    let new_lines = new.split_inclusive('\n').collect::<Vec<_>>();
+
+    let mut edits = Vec::new();
+
+    myers::diff(&old_lines, &new_lines, &mut edits);
+
+    // The byte ranges of the current group of changed lines.
+    let mut old_start = 0;
+    let mut old_end = 0;
+    let mut new_start = 0;
+    let mut new_end = 0;
+
+    let mut old_idx = 0;
+    let mut new_idx = 0;
+
+    for edit in edits {
+        match edit {
+            myers::Edit::Equal(n) => {
+                diff_chars(
+                    &old[old_start..old_end],
+                    &new[new_start..new_end],
+                    delta,
+                );
+
+                let len = byte_len(&old_lines[old_idx..old_idx + n]);
+
+                delta.retain(len);
+
+                old_idx += n;
+                new_idx += n;
+
+                old_start = old_end + len;
+                old_end = old_start;
+                new_start = new_end + len;
+                new_end = new_start;
+            },
+
+            myers::Edit::Delete(n) => {
+                old_end += byte_len(&old_lines[old_idx..old_idx + n]);
+                old_idx += n;
+            },
+
+            myers::Edit::Insert(n) => {
+                new_end += byte_len(&new_lines[new_idx..new_idx + n]);
+                new_idx += n;
+            },
+        }
+    }
+
+    diff_chars(&old[old_start..old_end], &new[new_start..new_end], delta);
+}
+
+/// Pushes the operations turning `old` into `new` to `delta`, diffing the
+/// two strings by characters.
+#[inline]
+fn diff_chars(old: &str, new: &str, delta: &mut Delta) {
+    if old.is_empty()
+        || new.is_empty()
+        || old.len() + new.len() > MAX_CHAR_DIFF_LEN
+    {
+        delta.delete(old.len()).insert(new);
+        return;
+    }
+
+    let old_chars = old.chars().collect::<Vec<_>>();
+    let new_chars = new.chars().collect::<Vec<_>>();
+
+    let mut edits = Vec::new();
+
+    myers::diff(&old_chars, &new_chars, &mut edits);
+
+    let mut old_idx = 0;
+    let mut new_idx = 0;
+
+    for edit in edits {
+        match edit {
+            myers::Edit::Equal(n) => {
+                let chars = &old_chars[old_idx..old_idx + n];
+                delta.retain(chars.iter().map(|ch| ch.len_utf8()).sum());
+                old_idx += n;
+                new_idx += n;
+            },
+
+            myers::Edit::Delete(n) => {
+                let chars = &old_chars[old_idx..old_idx + n];
+                delta.delete(chars.iter().map(|ch| ch.len_utf8()).sum());
+                old_idx += n;
+            },
+
+            myers::Edit::Insert(n) => {
+                let chars = &new_chars[new_idx..new_idx + n];
+                delta.insert(chars.iter().collect::<String>());
+                new_idx += n;
+            },
+        }
+    }
+}
+
+#[inline]
+fn byte_len(lines: &[&str]) -> usize {
+    lines.iter().map(|line| line.len()).sum()
+}
+
+mod myers {
+    //! A linear space implementation of Myers' diff algorithm, described in
+    //! "An O(ND) Difference Algorithm and Its Variations" by Eugene W.
+    //! Myers.
+
+    /// The maximum number of edits explored by a single search for the
+    /// middle snake. Past it the two sequences are considered to have
+    /// nothing in common, which bounds the time taken by
+    /// `diff()` to be linear in the length of the sequences times
+    /// this constant (and a logarithmic factor for the recursion).
+    pub(super) const MAX_COST: usize = 1024;
+
+    /// An edit turning a sequence into another one, counted in number of
+    /// elements.
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub(super) enum Edit {
+        Equal(usize),
+        Delete(usize),
+        Insert(usize),
+    }
+
+    /// Pushes the shortest sequence of edits turning `old` into `new` to
+    /// `edits`, or a longer one if finding the shortest one would exceed
+    /// `MAX_COST`.
+    #[inline]
+    pub(super) fn diff<T: PartialEq>(
+        old: &[T],
+        new: &[T],
+        edits: &mut Vec<Edit>,
+    ) {
+        let prefix =
+            old.iter().zip(new).take_while(|(old, new)| old == new).count();
+
+        let old = &old[prefix..];
+        let new = &new[prefix..];
+
+        let suffix = old
+            .iter()
+            .rev()
+            .zip(new.iter().rev())
+            .take_while(|(old, new)| old == new)
+            .count();
+
+        let old = &old[..old.len() - suffix];
+        let new = &new[..new.len() - suffix];
+
+        push(edits, Edit::Equal(prefix));
+
+        if old.is_empty() || new.is_empty() {
+            push(edits, Edit::Delete(old.len()));
+            push(edits, Edit::Insert(new.len()));
+        } else if let Some((x, y)) = middle_snake(old, new) {
+            diff(&old[..x], &new[..y], edits);
+            diff(&old[x..], &new[y..], edits);
+        } else {
+            push(edits, Edit::Delete(old.len()));
+            push(edits, Edit::Insert(new.len()));
+        }
+
+        push(edits, Edit::Equal(suffix));
+    }
+
+    /// Pushes `edit` to `edits`, merging it with the last edit if they're
+    /// of the same kind.
+    #[inline]
+    fn push(edits: &mut Vec<Edit>, edit: Edit) {
+        match (edits.last_mut(), edit) {
+            (_, Edit::Equal(0) | Edit::Delete(0) | Edit::Insert(0)) => {},
+
+            (Some(Edit::Equal(n)), Edit::Equal(m))
+            | (Some(Edit::Delete(n)), Edit::Delete(m))
+            | (Some(Edit::Insert(n)), Edit::Insert(m)) => *n += m,
+
+            _ => edits.push(edit),
+        }
+    }
+
+    /// Finds the point at which the forward and backward searches for the
+    /// shortest edit script overlap, which splits the problem in two
+    /// smaller ones.
+    ///
+    /// Returns `None` if the two sequences have nothing in common, or if
+    /// the overlap isn't found within `MAX_COST` edits.
+    #[inline]
+    fn middle_snake<T: PartialEq>(
+        old: &[T],
+        new: &[T],
+    ) -> Option<(usize, usize)> {
+        let n = old.len() as isize;
+        let m = new.len() as isize;
+
+        let max_d = ((n + m + 1) / 2).min(MAX_COST as isize);
+        let v_offset = max_d;
+        let v_len = 2 * max_d + 2;
+
+        let mut v1 = vec![-1isize; v_len as usize];
+        let mut v2 = vec![-1isize; v_len as usize];
+
+        v1[(v_offset + 1) as usize] = 0;
+        v2[(v_offset + 1) as usize] = 0;
+
+        let delta = n - m;
+
+        // If the difference in length is odd the forward search will be
+        // the one to find the overlap, otherwise the backward one will.
+        let front = delta % 2 != 0;
+
+        // Offsets for the start and end of the diagonals to explore, used
+        // to prune the ones going off the edit graph.
+        let mut k1_start = 0;
+        let mut k1_end = 0;
+        let mut k2_start = 0;
+        let mut k2_end = 0;
+
+        for d in 0..max_d {
+            let mut k1 = -d + k1_start;
+
+            while k1 <= d - k1_end {
+                let k1_offset = (v_offset + k1) as usize;
+
+                let mut x1 = if k1 == -d
+                    || (k1 != d && v1[k1_offset - 1] < v1[k1_offset + 1])
+                {
+                    v1[k1_offset + 1]
+                } else {
+                    v1[k1_offset - 1] + 1
+                };
+
+                let mut y1 = x1 - k1;
+
+                while x1 < n && y1 < m && old[x1 as usize] == new[y1 as usize]
+                {
+                    x1 += 1;
+                    y1 += 1;
+                }
+
+                v1[k1_offset] = x1;
+
+                if x1 > n {
+                    k1_end += 2;
+                } else if y1 > m {
+                    k1_start += 2;
+                } else if front {
+                    let k2_offset = v_offset + delta - k1;
+
+                    if k2_offset >= 0
+                        && k2_offset < v_len
+                        && v2[k2_offset as usize] != -1
+                    {
+                        let x2 = n - v2[k2_offset as usize];
+
+                        if x1 >= x2 {
+                            return Some((x1 as usize, y1 as usize));
+                        }
+                    }
+                }
+
+                k1 += 2;
+            }
+
+            let mut k2 = -d + k2_start;
+
+            while k2 <= d - k2_end {
+                let k2_offset = (v_offset + k2) as usize;
+
+                let mut x2 = if k2 == -d
+                    || (k2 != d && v2[k2_offset - 1] < v2[k2_offset + 1])
+                {
+                    v2[k2_offset + 1]
+                } else {
+                    v2[k2_offset - 1] + 1
+                };
+
+                let mut y2 = x2 - k2;
+
+                while x2 < n
+                    && y2 < m
+                    && old[(n - x2 - 1) as usize] == new[(m - y2 - 1) as usize]
+                {
+                    x2 += 1;
+                    y2 += 1;
+                }
+
+                v2[k2_offset] = x2;
+
+                if x2 > n {
+                    k2_end += 2;
+                } else if y2 > m {
+                    k2_start += 2;
+                } else if !front {
+                    let k1_offset = v_offset + delta - k2;
+
+                    if k1_offset >= 0
+                        && k1_offset < v_len
+                        && v1[k1_offset as usize] != -1
+                    {
+                        let x1 = v1[k1_offset as usize];
+                        let y1 = v_offset + x1 - k1_offset;
+
+                        if x1 >= n - x2 {
+                            return Some((x1 as usize, y1 as usize));
+                        }
+                    }
+                }
+
+                k2 += 2;
+            }
+        }
+
+        None
+    }
+}
@@ -0,0 +1,55 @@
+//! This module contains the [`DirtyRange`] used by
+//! [`Tracked::mark_clean()`](crate::Tracked::mark_clean()) to track the part
+//! of a `Rope` edited since a checkpoint.
+
+use core::ops::Range;
+
+use super::anchors::map_offset;
+use super::Gravity;
+
+/// The smallest byte range covering all the text edited since the last call
+/// to [`Tracked::mark_clean()`](crate::Tracked::mark_clean()).
+#[derive(Clone, Debug, Default)]
+pub(super) struct DirtyRange {
+    /// `None` if the `Rope` hasn't been edited since the checkpoint.
+    byte_range: Option<Range<usize>>,
+}
+
+impl DirtyRange {
+    /// Extends the range to cover the text inserted by replacing the bytes
+    /// in `replaced` with `inserted_len` new bytes, after shifting it to
+    /// account for the edit.
+    #[inline]
+    pub(super) fn apply_edit(
+        &mut self,
+        replaced: Range<usize>,
+        inserted_len: usize,
+    ) {
+        let inserted = replaced.start..replaced.start + inserted_len;
+
+        let byte_range = match self.byte_range.take() {
+            Some(Range { start, end }) => {
+                let start = map_offset(
+                    start,
+                    Gravity::Left,
+                    replaced.clone(),
+                    inserted_len,
+                );
+
+                let end =
+                    map_offset(end, Gravity::Right, replaced, inserted_len);
+
+                start.min(inserted.start)..end.max(inserted.end)
+            },
+
+            None => inserted,
+        };
+
+        self.byte_range = Some(byte_range);
+    }
+
+    #[inline]
+    pub(super) fn byte_range(&self) -> Option<Range<usize>> {
+        self.byte_range.clone()
+    }
+}
@@ -0,0 +1,194 @@
+/// The error type returned by the fallible `try_*` methods of
+/// [`Rope`](crate::Rope)s and [`RopeSlice`](crate::RopeSlice)s.
+///
+/// Each variant carries the offending index or offset together with the
+/// bound it violated, so that callers can report a meaningful message
+/// without having to catch a panic.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Error {
+    /// A byte index was greater than or equal to the byte length.
+    ByteIndexOutOfBounds {
+        /// The byte index that was out of bounds.
+        index: usize,
+
+        /// The byte length it was checked against.
+        len: usize,
+    },
+
+    /// A byte offset was greater than the byte length.
+    ByteOffsetOutOfBounds {
+        /// The byte offset that was out of bounds.
+        offset: usize,
+
+        /// The byte length it was checked against.
+        len: usize,
+    },
+
+    /// A byte offset didn't lie on a code point boundary.
+    ByteOffsetNotCharBoundary {
+        /// The byte offset that was inside a code point.
+        offset: usize,
+    },
+
+    /// The start of a byte range was greater than its end.
+    ByteStartAfterEnd {
+        /// The start of the byte range.
+        start: usize,
+
+        /// The end of the byte range.
+        end: usize,
+    },
+
+    /// A line index was greater than or equal to the line length.
+    LineIndexOutOfBounds {
+        /// The line index that was out of bounds.
+        index: usize,
+
+        /// The line length it was checked against.
+        len: usize,
+    },
+
+    /// A line offset was greater than the line length.
+    LineOffsetOutOfBounds {
+        /// The line offset that was out of bounds.
+        offset: usize,
+
+        /// The line length it was checked against.
+        len: usize,
+    },
+
+    /// The start of a line range was greater than its end.
+    LineStartAfterEnd {
+        /// The start of the line range.
+        start: usize,
+
+        /// The end of the line range.
+        end: usize,
+    },
+}
+
+impl core::fmt::Display for Error {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            Error::ByteIndexOutOfBounds { index, len } => write!(
+                f,
+                "byte index out of bounds: the index is {index} but the \
+                 length is {len}"
+            ),
+
+            Error::ByteOffsetOutOfBounds { offset, len } => write!(
+                f,
+                "byte offset out of bounds: the offset is {offset} but the \
+                 length is {len}"
+            ),
+
+            Error::ByteOffsetNotCharBoundary { offset } => {
+                write!(f, "byte offset {offset} is not a char boundary")
+            },
+
+            Error::ByteStartAfterEnd { start, end } => write!(
+                f,
+                "byte start after end: the start is {start} but the end is \
+                 {end}"
+            ),
+
+            Error::LineIndexOutOfBounds { index, len } => write!(
+                f,
+                "line index out of bounds: the index is {index} but the \
+                 length is {len}"
+            ),
+
+            Error::LineOffsetOutOfBounds { offset, len } => write!(
+                f,
+                "line offset out of bounds: the offset is {offset} but the \
+                 length is {len}"
+            ),
+
+            Error::LineStartAfterEnd { start, end } => write!(
+                f,
+                "line start after end: the start is {start} but the end is \
+                 {end}"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Checks that `byte_index` is a valid byte index for an object of
+/// `byte_len` bytes.
+#[inline]
+pub(super) fn check_byte_index(
+    byte_index: usize,
+    byte_len: usize,
+) -> Result<(), Error> {
+    if byte_index >= byte_len {
+        Err(Error::ByteIndexOutOfBounds { index: byte_index, len: byte_len })
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that `start..end` is a valid byte range for an object of
+/// `byte_len` bytes whose char boundaries are given by `is_char_boundary`.
+#[inline]
+pub(super) fn check_byte_range(
+    start: usize,
+    end: usize,
+    byte_len: usize,
+    is_char_boundary: impl Fn(usize) -> bool,
+) -> Result<(), Error> {
+    if start > end {
+        return Err(Error::ByteStartAfterEnd { start, end });
+    }
+
+    if end > byte_len {
+        return Err(Error::ByteOffsetOutOfBounds {
+            offset: end,
+            len: byte_len,
+        });
+    }
+
+    for offset in [start, end] {
+        if !is_char_boundary(offset) {
+            return Err(Error::ByteOffsetNotCharBoundary { offset });
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks that `line_index` is a valid line index for an object of
+/// `line_len` lines.
+#[cfg(feature = "lines")]
+#[inline]
+pub(super) fn check_line_index(
+    line_index: usize,
+    line_len: usize,
+) -> Result<(), Error> {
+    if line_index >= line_len {
+        Err(Error::LineIndexOutOfBounds { index: line_index, len: line_len })
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that `start..end` is a valid line range for an object of
+/// `line_len` lines.
+#[cfg(feature = "lines")]
+#[inline]
+pub(super) fn check_line_range(
+    start: usize,
+    end: usize,
+    line_len: usize,
+) -> Result<(), Error> {
+    if start > end {
+        Err(Error::LineStartAfterEnd { start, end })
+    } else if end > line_len {
+        Err(Error::LineOffsetOutOfBounds { offset: end, len: line_len })
+    } else {
+        Ok(())
+    }
+}
@@ -1,12 +1,28 @@
-//! This module exports the [`GapBuffer`] struct and a few of its methods.
+//! This module contains the [`RawGapBuffer`] struct the text of a
+//! [`Rope`](crate::Rope) is stored in, and the [`GapBuffer`] wrapper around
+//! it which is exported to edit short texts on their own.
 //!
 //! It also implements several traits exported by the [tree](crate::tree)
-//! module on it to be able to use it as the leaf of our [`Rope`](crate::Rope).
+//! module on `RawGapBuffer` to be able to use it as the leaf of our `Rope`.
 
+use core::marker::PhantomData;
 use core::ops::{Range, RangeBounds};
 
+use super::combined;
 use super::gap_slice::GapSlice;
-use super::metrics::{ByteMetric, ChunkSummary};
+use super::leaf_bytes::LeafBytes;
+use super::line_breaks::{LfBreaks, LineBreaks};
+#[cfg(not(feature = "char-metric"))]
+use super::metrics::count;
+use super::metrics::{
+    AllMetrics,
+    ByteMetric,
+    CharWeight,
+    ChunkSummary,
+    MetricSet,
+    ZeroWeight,
+};
+use super::rope::CHUNK_MIN_FILL;
 use super::utils::{panic_messages as panic, *};
 use crate::range_bounds_to_start_end;
 use crate::tree::{
@@ -17,30 +33,303 @@ use crate::tree::{
     Summarize,
 };
 
-/// A [gap buffer] with a max capacity of `2^16 - 1` bytes.
+/// A [gap buffer] holding at most `MAX_BYTES` bytes of text, which can be at
+/// most `2^16 - 1`.
 ///
 /// Unlike a regular `String` where the extra capacity is stored at the end of
 /// the string, a `GapBuffer` stores the extra capacity in the middle of the
-/// heap-allocated buffer.
+/// buffer, where the last edit happened.
+///
+/// This allows consecutive insertions and deletions at the same cursor
+/// position to run in O(1) time in the size of the buffer, unlike a regular
+/// `String` where the contents after the cursor would have to be shifted to
+/// the right or left, respectively, resulting in O(n) time.
+///
+/// It's the same buffer the text of a [`Rope`](crate::Rope) is stored in, and
+/// it can be used on its own to edit short texts whose maximum length is
+/// known in advance, like the contents of a single-line input field. Its text
+/// can be read through the [`GapSlice`]s returned by
+/// [`byte_slice()`](Self::byte_slice()).
+///
+/// # Examples
+///
+/// ```
+/// # use crop::GapBuffer;
+/// #
+/// let mut input = GapBuffer::<64>::new();
 ///
-/// This allows us to efficiently perform consecutive insertions and deletions
-/// at the same cursor position in O(1) time in the size of the buffer, unlike
-/// a regular `String` where we would have to shift the contents to the right
-/// or left, respectively, resulting in O(n) time.
+/// input.insert(0, "Hello!");
+/// input.insert(5, " Earth");
+/// input.replace(6..11, "Saturn");
+///
+/// assert_eq!(input, "Hello Saturn!");
+/// assert_eq!(input.byte_slice(6..), "Saturn!");
+/// ```
+///
+/// [gap buffer]: https://en.wikipedia.org/wiki/Gap_buffer
+#[derive(Clone, Default)]
+pub struct GapBuffer<const MAX_BYTES: usize> {
+    raw: RawGapBuffer<MAX_BYTES>,
+}
+
+impl<const MAX_BYTES: usize> core::fmt::Debug for GapBuffer<MAX_BYTES> {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Debug::fmt(&self.raw, f)
+    }
+}
+
+impl<const MAX_BYTES: usize> core::fmt::Display for GapBuffer<MAX_BYTES> {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        core::fmt::Display::fmt(&self.raw.as_slice(), f)
+    }
+}
+
+impl<const MAX_BYTES: usize> From<&str> for GapBuffer<MAX_BYTES> {
+    /// # Panics
+    ///
+    /// Panics if the string's byte length is greater than `MAX_BYTES`.
+    #[track_caller]
+    #[inline]
+    fn from(s: &str) -> Self {
+        if s.len() > MAX_BYTES {
+            panic::gap_buffer_overflow(s.len(), MAX_BYTES);
+        }
+
+        Self { raw: RawGapBuffer::from(s) }
+    }
+}
+
+impl<const N: usize> PartialEq<GapBuffer<N>> for GapBuffer<N> {
+    #[inline]
+    fn eq(&self, rhs: &GapBuffer<N>) -> bool {
+        self.raw.as_slice() == rhs.raw.as_slice()
+    }
+}
+
+impl<const N: usize> Eq for GapBuffer<N> {}
+
+impl<const N: usize> PartialEq<&str> for GapBuffer<N> {
+    #[inline]
+    fn eq(&self, rhs: &&str) -> bool {
+        self.raw.as_slice() == *rhs
+    }
+}
+
+impl<const N: usize> PartialEq<GapBuffer<N>> for &str {
+    #[inline]
+    fn eq(&self, rhs: &GapBuffer<N>) -> bool {
+        rhs == self
+    }
+}
+
+impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
+    /// Returns the slice of the buffer in the given byte range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, if the end
+    /// is out of bounds (i.e. greater than [`len()`](Self::len())) or if
+    /// either of them doesn't lie on a code point boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::GapBuffer;
+    /// let mut buffer = GapBuffer::<16>::from("Hello Earth!");
+    /// buffer.insert(6, "big ");
+    ///
+    /// let slice = buffer.byte_slice(4..13);
+    /// assert_eq!(slice, "o big Ear");
+    /// assert_eq!(slice.left_chunk(), "o big ");
+    /// assert_eq!(slice.right_chunk(), "Ear");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn byte_slice<R>(&self, byte_range: R) -> GapSlice<'_>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.raw.byte_slice(byte_range)
+    }
+
+    /// Returns the maximum number of bytes the buffer can hold, i.e.
+    /// `MAX_BYTES`.
+    #[inline]
+    pub const fn capacity(&self) -> usize {
+        MAX_BYTES
+    }
+
+    /// Returns the number of code points (i.e. [`char`]s) in the buffer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::GapBuffer;
+    /// let buffer = GapBuffer::<16>::from("Hello 🌎!");
+    /// assert_eq!(buffer.char_len(), 8);
+    /// ```
+    #[inline]
+    pub fn char_len(&self) -> usize {
+        #[cfg(feature = "char-metric")]
+        {
+            self.raw.summarize().chars()
+        }
+
+        #[cfg(not(feature = "char-metric"))]
+        {
+            count::chars(self.left_chunk()) + count::chars(self.right_chunk())
+        }
+    }
+
+    /// Deletes the text in the given byte range, moving the gap to where the
+    /// deletion happened.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, if the end
+    /// is out of bounds (i.e. greater than [`len()`](Self::len())) or if
+    /// either of them doesn't lie on a code point boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::GapBuffer;
+    /// let mut buffer = GapBuffer::<16>::from("Hello Earth!");
+    /// buffer.delete(5..11);
+    /// assert_eq!(buffer, "Hello!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn delete<R>(&mut self, byte_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        self.raw.replace(byte_range, "");
+    }
+
+    /// Inserts `text` at the given byte offset, moving the gap to the end of
+    /// the inserted text.
+    ///
+    /// Consecutive insertions at the same cursor position, e.g. while typing,
+    /// only copy the inserted text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`len()`](Self::len())), if it doesn't lie on a code point boundary or
+    /// if the buffer can't fit the text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::GapBuffer;
+    /// let mut buffer = GapBuffer::<16>::new();
+    ///
+    /// buffer.insert(0, "Hello!");
+    /// buffer.insert(5, " Earth");
+    /// assert_eq!(buffer, "Hello Earth!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn insert<T>(&mut self, byte_offset: usize, text: T)
+    where
+        T: AsRef<str>,
+    {
+        self.raw.replace(byte_offset..byte_offset, text);
+    }
+
+    /// Returns `true` if the buffer is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
+    }
+
+    /// Returns the text of the buffer before the gap.
+    #[inline]
+    pub fn left_chunk(&self) -> &str {
+        self.raw.left_chunk()
+    }
+
+    /// Returns the byte length of the text in the buffer.
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.raw.len()
+    }
+
+    /// Creates a new, empty `GapBuffer`.
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replaces the text in the given byte range with `text`, moving the gap
+    /// to the end of the inserted text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, if the end
+    /// is out of bounds (i.e. greater than [`len()`](Self::len())), if either
+    /// of them doesn't lie on a code point boundary or if the buffer can't fit
+    /// the text.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::GapBuffer;
+    /// let mut buffer = GapBuffer::<16>::from("Hello Earth!");
+    /// buffer.replace(6..11, "Saturn");
+    /// assert_eq!(buffer, "Hello Saturn!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn replace<R, T>(&mut self, byte_range: R, text: T)
+    where
+        R: RangeBounds<usize>,
+        T: AsRef<str>,
+    {
+        self.raw.replace(byte_range, text);
+    }
+
+    /// Returns the text of the buffer after the gap.
+    #[inline]
+    pub fn right_chunk(&self) -> &str {
+        self.raw.right_chunk()
+    }
+}
+
+/// The gap buffer the text of a [`Rope`](crate::Rope) is stored in, and
+/// which [`GapBuffer`] wraps.
 ///
 /// Only the first `len_left` and the last `len_right` bytes of the buffer are
 /// valid. The gap is located between the two chunks and has a length of
 /// `len_gap() = MAX_BYTES - len_left - len_right`.
 ///
-/// [gap buffer]: https://en.wikipedia.org/wiki/Gap_buffer
+/// Most of its methods take the current summary of the buffer to avoid
+/// recomputing it, and only check their arguments in debug builds, so it's
+/// not exposed outside of the crate.
 #[derive(Clone)]
-pub struct GapBuffer<const MAX_BYTES: usize> {
-    pub(super) bytes: Box<[u8; MAX_BYTES]>,
-    pub(super) left_summary: ChunkSummary,
+pub(crate) struct RawGapBuffer<
+    const MAX_BYTES: usize,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet = AllMetrics,
+> {
+    pub(super) bytes: LeafBytes<MAX_BYTES>,
+    pub(super) left_summary: ChunkSummary<W, B, S>,
     pub(super) len_right: u16,
 }
 
-impl<const MAX_BYTES: usize> core::fmt::Debug for GapBuffer<MAX_BYTES> {
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::fmt::Debug for RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>
+{
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         f.write_str("\"")?;
@@ -51,38 +340,116 @@ impl<const MAX_BYTES: usize> core::fmt::Debug for GapBuffer<MAX_BYTES> {
     }
 }
 
-impl<const MAX_BYTES: usize> Default for GapBuffer<MAX_BYTES> {
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Default for RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>
+{
     #[inline]
     fn default() -> Self {
+        Self::small("")
+    }
+}
+
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>
+{
+    /// Creates a buffer whose left chunk is made of the first
+    /// `summary.bytes()` bytes of `bytes`, and whose right chunk is empty.
+    ///
+    /// # Safety
+    ///
+    /// The first `summary.bytes()` bytes of `bytes` must be valid UTF-8, and
+    /// there can't be more than `MAX_BYTES` of them.
+    #[inline]
+    pub(super) unsafe fn from_left_chunk(
+        bytes: LeafBytes<MAX_BYTES>,
+        summary: ChunkSummary<W, B, S>,
+    ) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_MIN_FILL;
+
+        Self { bytes, left_summary: summary, len_right: 0 }
+    }
+
+    /// Creates a buffer holding `s` in its left chunk, in an allocation of
+    /// exactly its size instead of a full `MAX_BYTES` one. The buffer is
+    /// grown to `MAX_BYTES` the first time it's edited.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the string's byte length is greater than `MAX_BYTES`.
+    #[inline]
+    pub(super) fn small(s: &str) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_MIN_FILL;
+
         Self {
-            bytes: Box::new([0u8; MAX_BYTES]),
-            left_summary: ChunkSummary::default(),
+            bytes: LeafBytes::small(s.as_bytes()),
+            left_summary: ChunkSummary::from(s),
             len_right: 0,
         }
     }
 }
 
-// We only need this to compare `GapBuffer`s with `&str`s in (doc)tests.
-impl<const N: usize> PartialEq<GapBuffer<N>> for &str {
-    fn eq(&self, rhs: &GapBuffer<N>) -> bool {
+// We only need this to compare `RawGapBuffer`s with `&str`s in tests.
+impl<
+        const N: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > PartialEq<RawGapBuffer<N, W, B, MIN_FILL, S>> for &str
+{
+    fn eq(&self, rhs: &RawGapBuffer<N, W, B, MIN_FILL, S>) -> bool {
         *self == rhs.as_slice()
     }
 }
 
-impl<const N: usize> PartialEq<&str> for GapBuffer<N> {
+impl<
+        const N: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > PartialEq<&str> for RawGapBuffer<N, W, B, MIN_FILL, S>
+{
     fn eq(&self, rhs: &&str) -> bool {
         rhs == self
     }
 }
 
-// We only need this to compare `Option<GapBuffer>` with `None` in (doc)tests.
-impl<const N: usize> PartialEq<GapBuffer<N>> for GapBuffer<N> {
-    fn eq(&self, _rhs: &GapBuffer<N>) -> bool {
+// We only need this to compare `Option<RawGapBuffer>` with `None` in tests.
+impl<
+        const N: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > PartialEq<RawGapBuffer<N, W, B, MIN_FILL, S>>
+    for RawGapBuffer<N, W, B, MIN_FILL, S>
+{
+    fn eq(&self, _rhs: &RawGapBuffer<N, W, B, MIN_FILL, S>) -> bool {
         unimplemented!();
     }
 }
 
-impl<const MAX_BYTES: usize> From<&str> for GapBuffer<MAX_BYTES> {
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&str> for RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>
+{
     /// # Panics
     ///
     /// Panics if the string's byte length is greater than `MAX_BYTES`.
@@ -93,7 +460,14 @@ impl<const MAX_BYTES: usize> From<&str> for GapBuffer<MAX_BYTES> {
     }
 }
 
-impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>
+{
     /// Moves `bytes_to_add` bytes from the start of the right buffer to the
     /// end of this buffer, returning the summary of what's been added to this
     /// buffer.
@@ -105,30 +479,21 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     ///
     /// Panics if `bytes_to_add` is out of bounds in the right buffer or if the
     /// resulting left buffer would have a length greater than `MAX_BYTES`.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use crop::GapBuffer;
-    /// let mut left = GapBuffer::<10>::from("Hello");
-    /// let mut right = GapBuffer::<10>::from(", World!");
-    ///
-    /// left.add_from_right(2, &mut right);
-    /// assert_eq!(left, "Hello, ");
-    /// assert_eq!(right, "World!");
-    /// ```
     #[inline]
-    pub fn add_from_right(
+    pub(super) fn add_from_right(
         &mut self,
         bytes_to_add: usize,
         right: &mut Self,
-    ) -> ChunkSummary {
+    ) -> ChunkSummary<W, B, S> {
         debug_assert!(right.len() >= bytes_to_add);
         debug_assert!(self.len() + bytes_to_add <= MAX_BYTES);
 
         if bytes_to_add <= right.len_left() {
-            let (move_left, _) =
-                split_adjusted::<false>(right.left_chunk(), bytes_to_add);
+            let (move_left, _) = split_chunk_adjusted::<false>(
+                right.left_chunk(),
+                bytes_to_add,
+                Self::max_grapheme_shift(),
+            );
 
             let summary = right.summarize_left_chunk_up_to(move_left.len());
 
@@ -138,9 +503,10 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
 
             summary
         } else {
-            let (move_left, _) = split_adjusted::<false>(
+            let (move_left, _) = split_chunk_adjusted::<false>(
                 right.right_chunk(),
                 bytes_to_add - right.len_left(),
+                Self::max_grapheme_shift(),
             );
 
             let summary = right.left_summary + ChunkSummary::from(move_left);
@@ -160,26 +526,12 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     ///
     /// Panics if the resulting left buffer would have a length greater than
     /// `MAX_BYTES`.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use crop::GapBuffer;
-    /// # use crop::tree::Summarize;
-    /// let mut left = GapBuffer::<15>::from("Hello");
-    ///
-    /// let mut right = GapBuffer::<15>::from(", World!");
-    ///
-    /// let left_summary = left.summarize();
-    ///
-    /// left.append_other(left_summary, &mut right);
-    ///
-    /// assert_eq!(left, "Hello, World!");
-    ///
-    /// assert_eq!(right, "");
-    /// ```
     #[inline]
-    pub fn append_other(&mut self, summary: ChunkSummary, other: &mut Self) {
+    pub(super) fn append_other(
+        &mut self,
+        summary: ChunkSummary<W, B, S>,
+        other: &mut Self,
+    ) {
         debug_assert_eq!(summary, self.summarize());
 
         let len_left = self.len_left();
@@ -212,22 +564,8 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     ///
     /// Panics if the string's byte length is greater that the length of the
     /// gap.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use crop::GapBuffer;
-    ///
-    /// let mut buffer = GapBuffer::<10>::from("aabb");
-    /// assert_eq!(buffer.left_chunk(), "aa");
-    /// assert_eq!(buffer.right_chunk(), "bb");
-    ///
-    /// buffer.append_str("cc");
-    /// assert_eq!(buffer.left_chunk(), "aa");
-    /// assert_eq!(buffer.right_chunk(), "bbcc");
-    /// ```
     #[inline]
-    pub fn append_str(&mut self, s: &str) {
+    pub(super) fn append_str(&mut self, s: &str) {
         debug_assert!(s.len() <= self.len_gap());
 
         let start = MAX_BYTES - self.len_right();
@@ -248,19 +586,8 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     ///
     /// Panics if the combined byte length of the two strings is greater that
     /// the length of the gap.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use crop::GapBuffer;
-    /// let mut buffer = GapBuffer::<10>::from("aabb");
-    ///
-    /// buffer.append_two("cc", "dd");
-    /// assert_eq!(buffer.left_chunk(), "aa");
-    /// assert_eq!(buffer.right_chunk(), "bbccdd");
-    /// ```
     #[inline]
-    pub fn append_two(&mut self, a: &str, b: &str) {
+    pub(super) fn append_two(&mut self, a: &str, b: &str) {
         debug_assert!(a.len() + b.len() <= self.len_gap());
 
         // Shift the second chunk to the left.
@@ -300,30 +627,106 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
         }
     }
 
+    /// Returns the slice of the buffer in the given byte range.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, if the end
+    /// is out of bounds (i.e. greater than [`len()`](Self::len())) or if
+    /// either of them doesn't lie on a code point boundary.
+    #[track_caller]
+    #[inline]
+    fn byte_slice<R>(&self, byte_range: R) -> GapSlice<'_, W, B, S>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) = self.check_byte_range(byte_range);
+
+        let len_left = self.len_left();
+
+        if end <= len_left {
+            let left = &self.left_chunk()[start..end];
+
+            return GapSlice {
+                bytes: left.as_bytes(),
+                left_summary: ChunkSummary::from(left),
+                len_right: 0,
+            };
+        }
+
+        if start >= len_left {
+            let right = &self.right_chunk()[start - len_left..end - len_left];
+
+            return GapSlice {
+                bytes: right.as_bytes(),
+                left_summary: ChunkSummary::from(right),
+                len_right: 0,
+            };
+        }
+
+        let right_start = self.bytes.len() - self.len_right();
+
+        GapSlice {
+            bytes: &self.bytes[start..right_start + end - len_left],
+            left_summary: ChunkSummary::from(&self.left_chunk()[start..]),
+            len_right: (end - len_left) as u16,
+        }
+    }
+
+    /// Checks that the given byte range is valid for this buffer, returning
+    /// its start and end.
+    #[track_caller]
+    #[inline]
+    fn check_byte_range<R>(&self, byte_range: R) -> (usize, usize)
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.len());
+
+        if start > end {
+            panic::byte_start_after_end(start, end);
+        }
+
+        if end > self.len() {
+            panic::byte_offset_out_of_bounds(end, self.len());
+        }
+
+        self.assert_char_boundary(start);
+        self.assert_char_boundary(end);
+
+        (start, end)
+    }
+
     /// The number of bytes `RopeChunk`s must always stay over.
     pub(super) const fn chunk_min() -> usize {
         // The buffer can be underfilled by 3 bytes at most, which can happen
-        // when a byte offset lands inside a 4 byte codepoint.
-        Self::min_bytes().saturating_sub(3)
+        // when a byte offset lands inside a 4 byte codepoint, or by however
+        // much a split point can be moved to not split a grapheme cluster.
+        let max_shift = Self::max_grapheme_shift();
+        Self::min_bytes().saturating_sub(if max_shift > 3 {
+            max_shift
+        } else {
+            3
+        })
     }
 
-    /// Creates a new `GapBuffer` from a slice of `&str`s.
+    /// Compresses the bytes of the buffer, or drops their decompressed copy
+    /// if they're already compressed.
+    #[cfg(feature = "compression")]
+    #[inline]
+    pub(super) fn compress(&mut self) {
+        self.bytes.compress(self.len_left() as u16, self.len_right)
+    }
+
+    /// Creates a new `RawGapBuffer` from a slice of `&str`s.
     ///
     /// # Panics
     ///
     /// Panics if the combined byte length of all the chunks is greater than
     /// `MAX_BYTES`.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use crop::GapBuffer;
-    /// let buffer = GapBuffer::<10>::from_chunks(&["a", "abb", "cc", "dd"]);
-    /// assert_eq!(buffer.left_chunk(), "aabb");
-    /// assert_eq!(buffer.right_chunk(), "ccdd");
-    /// ```
     #[inline]
-    pub fn from_chunks(chunks: &[&str]) -> Self {
+    pub(super) fn from_chunks(chunks: &[&str]) -> Self {
         let total_len = chunks.iter().map(|s| s.len()).sum::<usize>();
 
         if total_len == 0 {
@@ -334,7 +737,7 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
 
         let to_left = total_len / 2;
 
-        let mut bytes = Box::new([0u8; MAX_BYTES]);
+        let mut bytes = LeafBytes::zeroed();
 
         let mut summary_left = ChunkSummary::new();
 
@@ -399,13 +802,67 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
             }
         }
 
-        unreachable!("This can only be reached if the total length is zero");
-    }
+        unreachable!("This can only be reached if the total length is zero");
+    }
+
+    /// Creates a new `RawGapBuffer` from a slice of `&str`s, placing the gap at
+    /// the given byte offset of their concatenation instead of in the middle
+    /// like [`from_chunks`](Self::from_chunks()) does.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the combined byte length of all the chunks is greater than
+    /// `MAX_BYTES`, or if `gap_offset` is not a char boundary of the
+    /// concatenated chunks.
+    #[inline]
+    pub(super) fn from_chunks_with_gap(
+        chunks: &[&str],
+        gap_offset: usize,
+    ) -> Self {
+        let total_len = chunks.iter().map(|s| s.len()).sum::<usize>();
+
+        debug_assert!(total_len <= MAX_BYTES);
+        debug_assert!(gap_offset <= total_len);
+
+        let mut bytes = LeafBytes::zeroed();
+
+        let mut left_summary = ChunkSummary::new();
+
+        let len_right = total_len - gap_offset;
+
+        let mut start_right = MAX_BYTES - len_right;
+
+        let mut copied = 0;
+
+        for &chunk in chunks {
+            let len_to_left =
+                gap_offset.saturating_sub(copied).min(chunk.len());
+
+            let (to_left, to_right) = chunk.split_at(len_to_left);
+
+            let range = {
+                let start = left_summary.bytes();
+                let end = start + to_left.len();
+                start..end
+            };
+
+            bytes[range].copy_from_slice(to_left.as_bytes());
+
+            left_summary += ChunkSummary::from(to_left);
+
+            let range = {
+                let end = start_right + to_right.len();
+                start_right..end
+            };
+
+            bytes[range].copy_from_slice(to_right.as_bytes());
 
-    /// Returns `true` if the buffer ends with a newline ('\n') character.
-    #[inline]
-    pub(super) fn has_trailing_newline(&self) -> bool {
-        self.last_chunk().ends_with('\n')
+            start_right += to_right.len();
+
+            copied += chunk.len();
+        }
+
+        Self { bytes, left_summary, len_right: len_right as u16 }
     }
 
     /// Inserts the string at the given byte offset, moving the gap to the new
@@ -416,12 +873,12 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     /// Panics if the byte offset is not a char boundary of if the byte length
     /// of the string is greater than the length of the gap.
     #[inline]
-    pub(super) fn insert(
+    pub(super) fn insert_with_summary(
         &mut self,
         insert_at: usize,
         s: &str,
-        summary: ChunkSummary,
-    ) -> ChunkSummary {
+        summary: ChunkSummary<W, B, S>,
+    ) -> ChunkSummary<W, B, S> {
         debug_assert!(insert_at <= self.len());
         debug_assert!(self.is_char_boundary(insert_at));
         debug_assert!(s.len() <= self.len_gap());
@@ -459,23 +916,13 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
 
     /// Returns `true` if the buffer is empty.
     #[inline]
-    pub fn is_empty(&self) -> bool {
+    pub(super) fn is_empty(&self) -> bool {
         self.len() == 0
     }
 
-    /// The right chunk if it's not empty, or the left one otherwise.
-    #[inline]
-    pub(super) fn last_chunk(&self) -> &str {
-        if self.len_right() == 0 {
-            self.left_chunk()
-        } else {
-            self.right_chunk()
-        }
-    }
-
     /// Returns the left chunk of this buffer as a string slice.
     #[inline]
-    pub fn left_chunk(&self) -> &str {
+    pub(super) fn left_chunk(&self) -> &str {
         // SAFETY: all the methods are guaranteed to always keep the first
         // `len_left()` bytes valid UTF-8.
         unsafe {
@@ -485,7 +932,7 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
 
     /// Returns the combined byte length of the buffer's left and right chunks.
     #[inline]
-    pub fn len(&self) -> usize {
+    pub(super) fn len(&self) -> usize {
         self.len_left() + self.len_right()
     }
 
@@ -504,10 +951,39 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
         self.len_right as _
     }
 
+    /// The maximum number of bytes a split point between two buffers can be
+    /// moved by to not split a grapheme cluster across them.
+    ///
+    /// This is zero unless the `grapheme-chunks` feature is enabled, and is
+    /// small enough for a buffer to never overflow because of it.
+    pub(super) const fn max_grapheme_shift() -> usize {
+        if cfg!(feature = "grapheme-chunks") {
+            MAX_BYTES / 8
+        } else {
+            0
+        }
+    }
+
+    /// Fails to compile if two underfilled buffers couldn't be merged into a
+    /// single one.
+    const VALID_MIN_FILL: () = assert!(
+        MIN_FILL >= 1 && MIN_FILL <= 50,
+        "the minimum fill of a chunk must be between 1 and 50"
+    );
+
     /// The minimum number of bytes this buffer should have to not be
-    /// considered underfilled.
+    /// considered underfilled, i.e. `MIN_FILL` percent of its capacity, but
+    /// at least one byte.
     pub(super) const fn min_bytes() -> usize {
-        MAX_BYTES / 4
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_MIN_FILL;
+
+        let min_bytes = MAX_BYTES * MIN_FILL / 100;
+        if min_bytes == 0 {
+            1
+        } else {
+            min_bytes
+        }
     }
 
     /// Moves the gap to the given byte offset.
@@ -517,26 +993,12 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     /// Panics if the byte offset is out of bounds, if it's not a char boundary
     /// or if the number of line breaks in the buffer is not equal to
     /// `tot_line_breaks`.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use crop::GapBuffer;
-    /// # use crop::tree::Summarize;
-    /// let mut buffer = GapBuffer::<10>::from("aaaabbbb");
-    ///
-    /// let summary = buffer.summarize();
-    ///
-    /// buffer.move_gap(2, summary);
-    /// assert_eq!(buffer.left_chunk(), "aa");
-    /// assert_eq!(buffer.right_chunk(), "aabbbb");
-    ///
-    /// buffer.move_gap(6, summary);
-    /// assert_eq!(buffer.left_chunk(), "aaaabb");
-    /// assert_eq!(buffer.right_chunk(), "bb");
-    /// ```
     #[inline]
-    pub fn move_gap(&mut self, byte_offset: usize, summary: ChunkSummary) {
+    pub(super) fn move_gap(
+        &mut self,
+        byte_offset: usize,
+        summary: ChunkSummary<W, B, S>,
+    ) {
         debug_assert!(byte_offset <= self.len());
         debug_assert!(self.is_char_boundary(byte_offset));
         debug_assert_eq!(summary, self.summarize());
@@ -599,39 +1061,22 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     ///
     /// Panics if `bytes_to_move` is out of bounds or if the resulting right
     /// buffer would have a length greater than `MAX_BYTES`.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use crop::GapBuffer;
-    /// # use crop::tree::Summarize;
-    /// let mut left = GapBuffer::<10>::from("Hello, ");
-    ///
-    /// let mut right = GapBuffer::<10>::from("World!");
-    ///
-    /// let left_summary = left.summarize();
-    ///
-    /// left.move_to_right(2, &mut right, left_summary);
-    ///
-    /// assert_eq!(left, "Hello");
-    ///
-    /// assert_eq!(right, ", World!");
-    /// ```
     #[inline]
-    pub fn move_to_right(
+    pub(super) fn move_to_right(
         &mut self,
         bytes_to_move: usize,
         right: &mut Self,
-        summary: ChunkSummary,
-    ) -> ChunkSummary {
+        summary: ChunkSummary<W, B, S>,
+    ) -> ChunkSummary<W, B, S> {
         debug_assert!(bytes_to_move <= self.len());
         debug_assert!(right.len() + bytes_to_move <= MAX_BYTES);
         debug_assert_eq!(summary, self.summarize());
 
         if bytes_to_move <= self.len_right() {
-            let (_, move_right) = split_adjusted::<true>(
+            let (_, move_right) = split_chunk_adjusted::<true>(
                 self.right_chunk(),
                 self.len_right() - bytes_to_move,
+                Self::max_grapheme_shift(),
             );
 
             let moved_summary = ChunkSummary::from(move_right);
@@ -642,9 +1087,10 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
 
             moved_summary
         } else {
-            let (_, move_right) = split_adjusted::<true>(
+            let (_, move_right) = split_chunk_adjusted::<true>(
                 self.left_chunk(),
                 self.len_left() - (bytes_to_move - self.len_right()),
+                Self::max_grapheme_shift(),
             );
 
             let move_right_summary = ChunkSummary::from(move_right);
@@ -667,23 +1113,12 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     /// Panics if the resulting buffer would have a length greater than
     /// `MAX_BYTES`, or if the number of line breaks in `s` is not equal to
     /// `prepended_line_breaks`.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use crop::{ChunkSummary, GapBuffer};
-    /// let mut buf = GapBuffer::<15>::from("World!");
-    ///
-    /// let prepend = "Hello, ";
-    ///
-    /// let prepended_summary = ChunkSummary::from(prepend);
-    ///
-    /// buf.prepend(prepend, prepended_summary);
-    ///
-    /// assert_eq!(buf, "Hello, World!");
-    /// ```
     #[inline]
-    pub fn prepend(&mut self, s: &str, prepended_summary: ChunkSummary) {
+    pub(super) fn prepend(
+        &mut self,
+        s: &str,
+        prepended_summary: ChunkSummary<W, B, S>,
+    ) {
         debug_assert!(s.len() <= self.len_gap());
         debug_assert_eq!(prepended_summary, ChunkSummary::from(s));
 
@@ -704,30 +1139,12 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     ///
     /// Panics if the combined byte length of the two strings is greater that
     /// the length of the gap.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use crop::{ChunkSummary, GapBuffer};
-    /// let mut buf = GapBuffer::<15>::from("!");
-    ///
-    /// let hello = "Hello, ";
-    ///
-    /// let world = "World";
-    ///
-    /// let prepended_summary =
-    ///     ChunkSummary::from(hello) + ChunkSummary::from(world);
-    ///
-    /// buf.prepend_two(hello, world, prepended_summary);
-    ///
-    /// assert_eq!(buf, "Hello, World!");
-    /// ```
     #[inline]
-    pub fn prepend_two(
+    pub(super) fn prepend_two(
         &mut self,
         a: &str,
         b: &str,
-        prepended_summary: ChunkSummary,
+        prepended_summary: ChunkSummary<W, B, S>,
     ) {
         debug_assert!(a.len() + b.len() <= self.len_gap());
 
@@ -756,24 +1173,11 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     /// Panics if `byte_offset` is out of bounds, if it's not a char boundary
     /// or if the number of line breaks in the range `0..byte_offset` is not
     /// equal to `removed_line_breaks`.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use crop::{ChunkSummary, GapBuffer};
-    /// let mut buffer = GapBuffer::<10>::from("foo\nbar");
-    ///
-    /// let removed_summary = ChunkSummary::from("foo\n");
-    ///
-    /// buffer.remove_up_to(4, removed_summary);
-    ///
-    /// assert_eq!(buffer, "bar");
-    /// ```
     #[inline]
-    pub fn remove_up_to(
+    pub(super) fn remove_up_to(
         &mut self,
         byte_offset: usize,
-        removed_summary: ChunkSummary,
+        removed_summary: ChunkSummary<W, B, S>,
     ) {
         debug_assert!(byte_offset <= self.len());
         debug_assert!(self.is_char_boundary(byte_offset));
@@ -800,6 +1204,41 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
         }
     }
 
+    /// Replaces the text in the given byte range with `text`, moving the gap
+    /// to the end of the inserted text.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, if the end
+    /// is out of bounds (i.e. greater than [`len()`](Self::len())), if either
+    /// of them doesn't lie on a code point boundary or if the buffer can't fit
+    /// the text.
+    #[track_caller]
+    #[inline]
+    fn replace<R, T>(&mut self, byte_range: R, text: T)
+    where
+        R: RangeBounds<usize>,
+        T: AsRef<str>,
+    {
+        let text = text.as_ref();
+
+        let (start, end) = self.check_byte_range(byte_range);
+
+        let new_len = self.len() - (end - start) + text.len();
+
+        if new_len > MAX_BYTES {
+            panic::gap_buffer_overflow(new_len, MAX_BYTES);
+        }
+
+        let summary = self.summarize();
+
+        if end > start {
+            self.replace_non_overflowing(start..end, text, summary);
+        } else {
+            self.insert_with_summary(start, text, summary);
+        }
+    }
+
     /// Replaces the text in `byte_range` with the string `s`, where the
     /// replaced range is big enough (and the replacement string is small
     /// enough) such that the buffer doesn't go over `MAX_BYTES`.
@@ -810,24 +1249,13 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     /// start or the end of the byte range is not a char boundary, if the
     /// length of the buffer after the replacement would be greater than
     /// `MAX_BYTES` or if `summary` is not equal to this buffer's summary.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use crop::GapBuffer;
-    /// # use crop::tree::Summarize;
-    /// let mut buffer = GapBuffer::<10>::from("foo\nbar");
-    /// let summary = buffer.summarize();
-    /// buffer.replace_non_overflowing(4..7, "baz\r\n", summary);
-    /// assert_eq!(buffer, "foo\nbaz\r\n");
-    /// ```
     #[inline]
-    pub fn replace_non_overflowing(
+    pub(super) fn replace_non_overflowing(
         &mut self,
         Range { start, end }: Range<usize>,
         s: &str,
-        summary: ChunkSummary,
-    ) -> ChunkSummary {
+        summary: ChunkSummary<W, B, S>,
+    ) -> ChunkSummary<W, B, S> {
         debug_assert!(start <= end);
         debug_assert!(end <= self.len());
         debug_assert!(self.is_char_boundary(start));
@@ -863,35 +1291,13 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     /// start or the end of the byte range is not a char boundary, if the
     /// length of the buffer after the replacement would be less than or equal
     /// to `MAX_BYTES` or if `summary` is not equal to this buffer's summary.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use crop::GapBuffer;
-    /// # use crop::tree::Summarize;
-    /// let mut buffer = GapBuffer::<10>::from("foo\nbar");
-    /// let summary = buffer.summarize();
-    ///
-    /// // Replace the newline with a string that's too long to fit in the
-    /// // buffer.
-    /// let (new_summary, extras) =
-    ///     buffer.replace_overflowing(3..4, "foo\nbar\r\nbaz", summary);
-    ///
-    /// assert_eq!(buffer, "foo");
-    /// assert_eq!(new_summary, buffer.summarize());
-    ///
-    /// let mut extras = extras.into_iter();
-    /// assert_eq!("foo\nbar\r\nb", extras.next().unwrap());
-    /// assert_eq!("azbar", extras.next().unwrap());
-    /// assert_eq!(None, extras.next());
-    /// ```
     #[inline]
-    pub fn replace_overflowing(
+    pub(super) fn replace_overflowing(
         &mut self,
         byte_range: Range<usize>,
         s: &str,
-        summary: ChunkSummary,
-    ) -> (ChunkSummary, Vec<Self>) {
+        summary: ChunkSummary<W, B, S>,
+    ) -> (ChunkSummary<W, B, S>, Vec<Self>) {
         let Range { start, end } = byte_range;
 
         debug_assert!(start <= end);
@@ -915,16 +1321,25 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
             let missing = Self::min_bytes() - start;
 
             let extras = if s.len() >= missing {
-                let (left, right) = split_adjusted::<true>(s, missing);
+                let (left, right) = split_chunk_adjusted::<true>(
+                    s,
+                    missing,
+                    Self::max_grapheme_shift(),
+                );
 
                 replacement = left;
 
-                Resegmenter::new([right, extra_left, extra_right]).collect()
+                Resegmenter::new([right, extra_left, extra_right])
+                    .with_gap_at(right.len())
+                    .collect()
             } else if s.len() + extra_left.len() >= missing {
                 let missing = missing - s.len();
 
-                let (left, right) =
-                    split_adjusted::<true>(extra_left, missing);
+                let (left, right) = split_chunk_adjusted::<true>(
+                    extra_left,
+                    missing,
+                    Self::max_grapheme_shift(),
+                );
 
                 truncate_from += left.len();
 
@@ -932,8 +1347,11 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
             } else {
                 let missing = missing - s.len() - extra_left.len();
 
-                let (left, right) =
-                    split_adjusted::<true>(extra_right, missing);
+                let (left, right) = split_chunk_adjusted::<true>(
+                    extra_right,
+                    missing,
+                    Self::max_grapheme_shift(),
+                );
 
                 truncate_from += extra_left.len() + left.len();
 
@@ -961,9 +1379,10 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
             let (add_to_extras_1, add_to_extras_2) = if missing
                 <= new_right.len()
             {
-                let (keep_in_self, add_to_extras) = split_adjusted::<true>(
+                let (keep_in_self, add_to_extras) = split_chunk_adjusted::<true>(
                     new_right,
                     new_right.len() - missing,
+                    Self::max_grapheme_shift(),
                 );
 
                 truncate_from = new_left.len() + keep_in_self.len();
@@ -972,8 +1391,11 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
             } else {
                 let missing = missing - new_right.len();
 
-                let (keep_in_self, add_to_extras) =
-                    split_adjusted::<true>(new_left, new_left.len() - missing);
+                let (keep_in_self, add_to_extras) = split_chunk_adjusted::<true>(
+                    new_left,
+                    new_left.len() - missing,
+                    Self::max_grapheme_shift(),
+                );
 
                 truncate_from = keep_in_self.len();
 
@@ -987,14 +1409,18 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
                 extra_left,
                 extra_right,
             ])
+            .with_gap_at(
+                add_to_extras_1.len() + add_to_extras_2.len() + s.len(),
+            )
             .collect();
 
             let new_summary = self.truncate_from(truncate_from, summary);
 
             (new_summary, extras)
         } else {
-            let extras =
-                Resegmenter::new([s, extra_left, extra_right]).collect();
+            let extras = Resegmenter::new([s, extra_left, extra_right])
+                .with_gap_at(s.len())
+                .collect();
 
             let new_summary = self.truncate_from(start, summary);
 
@@ -1004,12 +1430,12 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
 
     /// Returns the right chunk of this buffer as a string slice.
     #[inline]
-    pub fn right_chunk(&self) -> &str {
+    pub(super) fn right_chunk(&self) -> &str {
         // SAFETY: all the methods are guaranteed to always keep the last
         // `len_right()` bytes valid UTF-8.
         unsafe {
             core::str::from_utf8_unchecked(
-                &self.bytes[MAX_BYTES - self.len_right()..],
+                &self.bytes[self.bytes.len() - self.len_right()..],
             )
         }
     }
@@ -1017,19 +1443,25 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     /// Returns the summary of the right chunk by subtracting the summary of
     /// the left chunk from the total.
     #[inline]
-    fn right_summary(&self, summary: ChunkSummary) -> ChunkSummary {
+    fn right_summary(
+        &self,
+        summary: ChunkSummary<W, B, S>,
+    ) -> ChunkSummary<W, B, S> {
         debug_assert_eq!(summary, self.summarize());
         summary - self.left_summary
     }
 
     #[inline]
-    pub(super) fn segmenter(s: &str) -> Segmenter<'_, MAX_BYTES> {
+    pub(super) fn segmenter(s: &str) -> Segmenter<'_, MAX_BYTES, MIN_FILL> {
         Segmenter { s, yielded: 0 }
     }
 
     /// Returns the summary of the left chunk up to the given byte offset.
     #[inline]
-    fn summarize_left_chunk_up_to(&self, byte_offset: usize) -> ChunkSummary {
+    fn summarize_left_chunk_up_to(
+        &self,
+        byte_offset: usize,
+    ) -> ChunkSummary<W, B, S> {
         debug_assert!(byte_offset <= self.len_left());
         debug_assert!(self.left_chunk().is_char_boundary(byte_offset));
 
@@ -1049,30 +1481,12 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     /// Panics if the summary is different from the output of
     /// `self.summarize()` or if either the start or the end of the byte range
     /// don't lie on a char boundary.
-    ///
-    /// # Examples
-    ///
-    /// ```
-    /// # use crop::GapBuffer;
-    /// # use crop::tree::Summarize;
-    /// let mut buffer = GapBuffer::<10>::from("foo\nbar\r\n");
-    /// let summary = buffer.summarize();
-    /// assert_eq!(buffer.summarize_range(0..buffer.len(), summary), summary);
-    ///
-    /// let s = buffer.summarize_range(0..4, summary);
-    /// assert_eq!(s.bytes(), 4);
-    /// assert_eq!(s.line_breaks(), 1);
-    ///
-    /// let s = buffer.summarize_range(2..buffer.len(), summary);
-    /// assert_eq!(s.bytes(), 7);
-    /// assert_eq!(s.line_breaks(), 2);
-    /// ```
     #[inline]
-    pub fn summarize_range(
+    pub(super) fn summarize_range(
         &self,
         Range { start, end }: Range<usize>,
-        summary: ChunkSummary,
-    ) -> ChunkSummary {
+        summary: ChunkSummary<W, B, S>,
+    ) -> ChunkSummary<W, B, S> {
         debug_assert!(start <= end);
         debug_assert!(end <= self.len());
         debug_assert!(self.is_char_boundary(start));
@@ -1080,12 +1494,18 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
         debug_assert_eq!(summary, self.summarize());
 
         #[inline(always)]
-        fn summarize_range<const MAX_BYTES: usize>(
-            buffer: &GapBuffer<MAX_BYTES>,
+        fn summarize_range<
+            const MAX_BYTES: usize,
+            W: CharWeight,
+            B: LineBreaks,
+            const MIN_FILL: usize,
+            S: MetricSet,
+        >(
+            buffer: &RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>,
             mut start: usize,
             mut end: usize,
-            summary: ChunkSummary,
-        ) -> ChunkSummary {
+            summary: ChunkSummary<W, B, S>,
+        ) -> ChunkSummary<W, B, S> {
             // The whole range is inside the left chunk.
             if end <= buffer.len_left() {
                 let chunk = &buffer.left_chunk()[start..end];
@@ -1125,7 +1545,7 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
 
     /// Computes and returns the summary of the right chunk.
     #[inline]
-    fn summarize_right_chunk(&self) -> ChunkSummary {
+    fn summarize_right_chunk(&self) -> ChunkSummary<W, B, S> {
         ChunkSummary::from(self.right_chunk())
     }
 
@@ -1137,8 +1557,8 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     fn summarize_right_chunk_up_to(
         &self,
         byte_offset: usize,
-        summary: ChunkSummary,
-    ) -> ChunkSummary {
+        summary: ChunkSummary<W, B, S>,
+    ) -> ChunkSummary<W, B, S> {
         debug_assert!(byte_offset <= self.len_right());
         debug_assert!(self.right_chunk().is_char_boundary(byte_offset));
         debug_assert_eq!(summary, self.summarize());
@@ -1161,11 +1581,11 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     /// it doesn't lie on a char boundary or if `summary` is different from the
     /// summary of this buffer.
     #[inline]
-    pub fn truncate_from(
+    pub(super) fn truncate_from(
         &mut self,
         byte_offset: usize,
-        summary: ChunkSummary,
-    ) -> ChunkSummary {
+        summary: ChunkSummary<W, B, S>,
+    ) -> ChunkSummary<W, B, S> {
         debug_assert!(byte_offset <= self.len());
         debug_assert!(self.is_char_boundary(byte_offset));
         debug_assert_eq!(summary, self.summarize());
@@ -1199,23 +1619,57 @@ impl<const MAX_BYTES: usize> GapBuffer<MAX_BYTES> {
     }
 }
 
-impl<const MAX_BYTES: usize> Summarize for GapBuffer<MAX_BYTES> {
-    type Summary = ChunkSummary;
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Summarize for RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>
+{
+    type Summary = ChunkSummary<W, B, S>;
+
+    const SUMMARY_IS_SUBTRACTABLE: bool = !combined::IS_ENABLED;
 
     #[inline]
     fn summarize(&self) -> Self::Summary {
-        self.left_summary + self.summarize_right_chunk()
+        // The left summary is updated by subtracting from it, which leaves
+        // its combined parts stale.
+        let left_summary = if combined::IS_ENABLED {
+            ChunkSummary::from(self.left_chunk())
+        } else {
+            self.left_summary
+        };
+
+        let mut summary = left_summary + self.summarize_right_chunk();
+        summary.join_segments(self.left_chunk(), self.right_chunk());
+        summary
     }
 }
 
-impl<const MAX_BYTES: usize> BaseMeasured for GapBuffer<MAX_BYTES> {
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > BaseMeasured for RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>
+{
     type BaseMetric = ByteMetric;
 }
 
-impl<const MAX_BYTES: usize> From<GapSlice<'_>> for GapBuffer<MAX_BYTES> {
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<GapSlice<'_, W, B, S>>
+    for RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>
+{
     #[inline]
-    fn from(slice: GapSlice<'_>) -> Self {
-        let mut bytes = Box::new([0u8; MAX_BYTES]);
+    fn from(slice: GapSlice<'_, W, B, S>) -> Self {
+        let mut bytes = LeafBytes::zeroed();
 
         bytes[..slice.len_left()]
             .copy_from_slice(slice.left_chunk().as_bytes());
@@ -1231,15 +1685,24 @@ impl<const MAX_BYTES: usize> From<GapSlice<'_>> for GapBuffer<MAX_BYTES> {
     }
 }
 
-impl<const MAX_BYTES: usize> AsSlice for GapBuffer<MAX_BYTES> {
-    type Slice<'a> = GapSlice<'a>;
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > AsSlice for RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>
+{
+    type Slice<'a> = GapSlice<'a, W, B, S>;
 
     #[inline]
-    fn as_slice(&self) -> GapSlice<'_> {
+    fn as_slice(&self) -> GapSlice<'_, W, B, S> {
         let bytes = match (self.len_left() > 0, self.len_right() > 0) {
             (true, true) => &*self.bytes,
             (true, false) => &self.bytes[..self.len_left()],
-            (false, true) => &self.bytes[MAX_BYTES - self.len_right()..],
+            (false, true) => {
+                &self.bytes[self.bytes.len() - self.len_right()..]
+            },
             (false, false) => &[],
         };
 
@@ -1251,16 +1714,23 @@ impl<const MAX_BYTES: usize> AsSlice for GapBuffer<MAX_BYTES> {
     }
 }
 
-impl<const MAX_BYTES: usize> BalancedLeaf for GapBuffer<MAX_BYTES> {
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > BalancedLeaf for RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>
+{
     #[inline]
-    fn is_underfilled(&self, summary: &ChunkSummary) -> bool {
+    fn is_underfilled(&self, summary: &ChunkSummary<W, B, S>) -> bool {
         summary.bytes() < Self::min_bytes()
     }
 
     #[inline]
     fn balance_leaves(
-        (left, left_summary): (&mut Self, &mut ChunkSummary),
-        (right, right_summary): (&mut Self, &mut ChunkSummary),
+        (left, left_summary): (&mut Self, &mut ChunkSummary<W, B, S>),
+        (right, right_summary): (&mut Self, &mut ChunkSummary<W, B, S>),
     ) {
         // The two leaves can be combined in a single chunk.
         if left.len() + right.len() <= MAX_BYTES {
@@ -1301,8 +1771,14 @@ impl<const MAX_BYTES: usize> BalancedLeaf for GapBuffer<MAX_BYTES> {
     }
 }
 
-impl<const MAX_BYTES: usize> ReplaceableLeaf<ByteMetric>
-    for GapBuffer<MAX_BYTES>
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > ReplaceableLeaf<ByteMetric>
+    for RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>
 {
     type Replacement<'a> = &'a str;
 
@@ -1312,7 +1788,7 @@ impl<const MAX_BYTES: usize> ReplaceableLeaf<ByteMetric>
     #[inline]
     fn replace<R>(
         &mut self,
-        summary: &mut ChunkSummary,
+        summary: &mut ChunkSummary<W, B, S>,
         range: R,
         replacement: &str,
     ) -> Option<Self::ExtraLeaves>
@@ -1331,7 +1807,7 @@ impl<const MAX_BYTES: usize> ReplaceableLeaf<ByteMetric>
             let new_summary = if end > start {
                 self.replace_non_overflowing(start..end, replacement, *summary)
             } else {
-                self.insert(start, replacement, *summary)
+                self.insert_with_summary(start, replacement, *summary)
             };
 
             debug_assert_eq!(new_summary, self.summarize());
@@ -1357,23 +1833,41 @@ impl<const MAX_BYTES: usize> ReplaceableLeaf<ByteMetric>
 
     #[track_caller]
     #[inline]
-    fn remove_up_to(&mut self, summary: &mut ChunkSummary, up_to: ByteMetric) {
-        self.replace(summary, ..up_to, "");
+    fn remove_up_to(
+        &mut self,
+        summary: &mut ChunkSummary<W, B, S>,
+        up_to: ByteMetric,
+    ) {
+        ReplaceableLeaf::replace(self, summary, ..up_to, "");
+    }
+
+    #[inline]
+    fn replace_fits(
+        &self,
+        summary: &ChunkSummary<W, B, S>,
+        Range { start, end }: Range<ByteMetric>,
+        replacement: &&str,
+    ) -> bool {
+        let new_len = summary.bytes() - (end - start).0 + replacement.len();
+        (Self::min_bytes()..=MAX_BYTES).contains(&new_len)
     }
 }
 
-/// Segments a string into [`GapBuffer`]s with at least
-/// [`GapBuffer::chunk_min()`] bytes.
+/// Segments a string into [`RawGapBuffer`]s with at least
+/// [`RawGapBuffer::chunk_min()`] bytes.
 ///
 /// The only exception is if the string is shorter than
-/// [`GapBuffer::chunk_min()`], in which case this will only yield a single gap
+/// [`RawGapBuffer::chunk_min()`], in which case this will only yield a single gap
 /// buffer with the entire string.
-pub(super) struct Segmenter<'a, const MAX_BYTES: usize> {
+pub(super) struct Segmenter<'a, const MAX_BYTES: usize, const MIN_FILL: usize>
+{
     s: &'a str,
     yielded: usize,
 }
 
-impl<'a, const MAX_BYTES: usize> Iterator for Segmenter<'a, MAX_BYTES> {
+impl<'a, const MAX_BYTES: usize, const MIN_FILL: usize> Iterator
+    for Segmenter<'a, MAX_BYTES, MIN_FILL>
+{
     type Item = &'a str;
 
     #[inline]
@@ -1383,7 +1877,7 @@ impl<'a, const MAX_BYTES: usize> Iterator for Segmenter<'a, MAX_BYTES> {
         let chunk = if remaining == 0 {
             return None;
         } else if remaining > MAX_BYTES {
-            let min = GapBuffer::<MAX_BYTES>::min_bytes();
+            let min = RawGapBuffer::<MAX_BYTES, ZeroWeight, LfBreaks, MIN_FILL>::min_bytes();
 
             let chunk_len = if remaining - MAX_BYTES >= min {
                 MAX_BYTES
@@ -1392,9 +1886,10 @@ impl<'a, const MAX_BYTES: usize> Iterator for Segmenter<'a, MAX_BYTES> {
                 remaining - min
             };
 
-            let mut adjusted_len = adjust_split_point::<false>(
+            let mut adjusted_len = adjust_chunk_split_point::<false>(
                 &self.s[self.yielded..],
                 chunk_len,
+                RawGapBuffer::<MAX_BYTES, ZeroWeight, LfBreaks, MIN_FILL>::max_grapheme_shift(),
             );
 
             if adjusted_len == 0 {
@@ -1408,7 +1903,13 @@ impl<'a, const MAX_BYTES: usize> Iterator for Segmenter<'a, MAX_BYTES> {
         } else {
             debug_assert!(
                 self.yielded == 0
-                    || remaining >= GapBuffer::<MAX_BYTES>::chunk_min()
+                    || remaining
+                        >= RawGapBuffer::<
+                            MAX_BYTES,
+                            ZeroWeight,
+                            LfBreaks,
+                            MIN_FILL,
+                        >::chunk_min()
             );
 
             &self.s[self.s.len() - remaining..]
@@ -1422,32 +1923,105 @@ impl<'a, const MAX_BYTES: usize> Iterator for Segmenter<'a, MAX_BYTES> {
 
 /// Resegments a bunch of strings.
 ///
-/// The yielded [`GapBuffer`]s should be equal to the ones yielded by the
+/// The yielded [`RawGapBuffer`]s should be equal to the ones yielded by the
 /// [`Segmenter`] iterator initialized with a string that is the concatenation
 /// of the strings passed to this iterator.
-pub(super) struct Resegmenter<'a, const CHUNKS: usize, const MAX_BYTES: usize>
-{
+pub(super) struct Resegmenter<
+    'a,
+    const CHUNKS: usize,
+    const MAX_BYTES: usize,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet = AllMetrics,
+> {
     segments: [&'a str; CHUNKS],
     start: usize,
     yielded: usize,
     total: usize,
+
+    /// The byte offset in the concatenated strings where the gap of the
+    /// buffer containing it should be placed, if any.
+    gap_at: Option<usize>,
+
+    weight: PhantomData<(W, B, S)>,
 }
 
-impl<'a, const CHUNKS: usize, const MAX_BYTES: usize>
-    Resegmenter<'a, CHUNKS, MAX_BYTES>
+impl<
+        'a,
+        const CHUNKS: usize,
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Resegmenter<'a, CHUNKS, MAX_BYTES, W, B, MIN_FILL, S>
 {
     #[inline]
     fn new(segments: [&'a str; CHUNKS]) -> Self {
         let total = segments.iter().map(|s| s.len()).sum::<usize>();
-        debug_assert!(total >= GapBuffer::<MAX_BYTES>::chunk_min());
-        Self { total, segments, yielded: 0, start: 0 }
+        debug_assert!(
+            total >= RawGapBuffer::<MAX_BYTES, W, B, MIN_FILL, S>::chunk_min()
+        );
+        Self {
+            total,
+            segments,
+            yielded: 0,
+            start: 0,
+            gap_at: None,
+            weight: PhantomData,
+        }
+    }
+
+    /// Places the gap of the buffer containing the given byte offset right at
+    /// that offset.
+    ///
+    /// This is used when resegmenting the result of an edit to put the gap
+    /// where the edit ended, so that if the next edit happens at the same
+    /// position (like it does when typing) we don't have to move any bytes
+    /// around.
+    #[inline]
+    fn with_gap_at(mut self, byte_offset: usize) -> Self {
+        debug_assert!(byte_offset <= self.total);
+        self.gap_at = Some(byte_offset);
+        self
+    }
+
+    /// Creates a new buffer from the given segments, which are assumed to
+    /// start at the `yielded` offset.
+    #[inline]
+    fn buffer_from(
+        &self,
+        segments: &[&str],
+    ) -> RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S> {
+        let len = segments.iter().map(|s| s.len()).sum::<usize>();
+
+        match self.gap_at {
+            Some(gap_at)
+                if (self.yielded..=self.yielded + len).contains(&gap_at) =>
+            {
+                RawGapBuffer::from_chunks_with_gap(
+                    segments,
+                    gap_at - self.yielded,
+                )
+            },
+
+            _ => RawGapBuffer::from_chunks(segments),
+        }
     }
 }
 
-impl<'a, const CHUNKS: usize, const MAX_BYTES: usize> Iterator
-    for Resegmenter<'a, CHUNKS, MAX_BYTES>
+impl<
+        'a,
+        const CHUNKS: usize,
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Iterator for Resegmenter<'a, CHUNKS, MAX_BYTES, W, B, MIN_FILL, S>
 {
-    type Item = GapBuffer<MAX_BYTES>;
+    type Item = RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
@@ -1460,7 +2034,8 @@ impl<'a, const CHUNKS: usize, const MAX_BYTES: usize> Iterator
 
             let mut bytes_in_next = 0;
 
-            let min_bytes = GapBuffer::<MAX_BYTES>::min_bytes();
+            let min_bytes =
+                RawGapBuffer::<MAX_BYTES, W, B, MIN_FILL, S>::min_bytes();
 
             for (idx, &segment) in
                 self.segments[self.start..].iter().enumerate()
@@ -1486,9 +2061,11 @@ impl<'a, const CHUNKS: usize, const MAX_BYTES: usize> Iterator
                 last_segment_len = remaining - bytes_in_next - min_bytes
             }
 
-            let (mut left, mut right) = split_adjusted::<false>(
+            let (mut left, mut right) = split_chunk_adjusted::<false>(
                 self.segments[idx_last],
                 last_segment_len,
+                RawGapBuffer::<MAX_BYTES, W, B, MIN_FILL, S>::max_grapheme_shift(
+                ),
             );
 
             // This can happen with e.g. ["🌎", "!"], MAX_BYTES = 4 and
@@ -1510,9 +2087,7 @@ impl<'a, const CHUNKS: usize, const MAX_BYTES: usize> Iterator
                 self.segments[idx_last] = left;
             }
 
-            let next = GapBuffer::<MAX_BYTES>::from_chunks(
-                &self.segments[self.start..=idx_last],
-            );
+            let next = self.buffer_from(&self.segments[self.start..=idx_last]);
 
             self.segments[idx_last] = right;
 
@@ -1520,11 +2095,18 @@ impl<'a, const CHUNKS: usize, const MAX_BYTES: usize> Iterator
 
             next
         } else {
-            debug_assert!(remaining >= GapBuffer::<MAX_BYTES>::chunk_min());
-            GapBuffer::<MAX_BYTES>::from_chunks(&self.segments[self.start..])
+            debug_assert!(
+                remaining
+                    >= RawGapBuffer::<MAX_BYTES, W, B, MIN_FILL, S>::chunk_min(
+                    )
+            );
+            self.buffer_from(&self.segments[self.start..])
         };
 
-        debug_assert!(next.len() >= GapBuffer::<MAX_BYTES>::chunk_min());
+        debug_assert!(
+            next.len()
+                >= RawGapBuffer::<MAX_BYTES, W, B, MIN_FILL, S>::chunk_min()
+        );
 
         self.yielded += next.len();
 
@@ -1536,10 +2118,180 @@ impl<'a, const CHUNKS: usize, const MAX_BYTES: usize> Iterator
 mod tests {
     use super::*;
 
+    #[test]
+    fn add_from_right_0() {
+        let mut left = RawGapBuffer::<10>::from("Hello");
+        let mut right = RawGapBuffer::<10>::from(", World!");
+
+        left.add_from_right(2, &mut right);
+        assert_eq!(left, "Hello, ");
+        assert_eq!(right, "World!");
+    }
+
+    #[test]
+    fn append_other_0() {
+        let mut left = RawGapBuffer::<15>::from("Hello");
+
+        let mut right = RawGapBuffer::<15>::from(", World!");
+
+        let left_summary = left.summarize();
+
+        left.append_other(left_summary, &mut right);
+
+        assert_eq!(left, "Hello, World!");
+
+        assert_eq!(right, "");
+    }
+
+    #[test]
+    fn append_str_0() {
+        let mut buffer = RawGapBuffer::<10>::from("aabb");
+        assert_eq!(buffer.left_chunk(), "aa");
+        assert_eq!(buffer.right_chunk(), "bb");
+
+        buffer.append_str("cc");
+        assert_eq!(buffer.left_chunk(), "aa");
+        assert_eq!(buffer.right_chunk(), "bbcc");
+    }
+
+    #[test]
+    fn append_two_0() {
+        let mut buffer = RawGapBuffer::<10>::from("aabb");
+
+        buffer.append_two("cc", "dd");
+        assert_eq!(buffer.left_chunk(), "aa");
+        assert_eq!(buffer.right_chunk(), "bbccdd");
+    }
+
+    #[test]
+    fn move_gap_0() {
+        let mut buffer = RawGapBuffer::<10>::from("aaaabbbb");
+
+        let summary = buffer.summarize();
+
+        buffer.move_gap(2, summary);
+        assert_eq!(buffer.left_chunk(), "aa");
+        assert_eq!(buffer.right_chunk(), "aabbbb");
+
+        buffer.move_gap(6, summary);
+        assert_eq!(buffer.left_chunk(), "aaaabb");
+        assert_eq!(buffer.right_chunk(), "bb");
+    }
+
+    #[test]
+    fn move_to_right_0() {
+        let mut left = RawGapBuffer::<10>::from("Hello, ");
+
+        let mut right = RawGapBuffer::<10>::from("World!");
+
+        let left_summary = left.summarize();
+
+        left.move_to_right(2, &mut right, left_summary);
+
+        assert_eq!(left, "Hello");
+
+        assert_eq!(right, ", World!");
+    }
+
+    #[test]
+    fn prepend_0() {
+        let mut buf = RawGapBuffer::<15>::from("World!");
+
+        let prepend = "Hello, ";
+
+        let prepended_summary = ChunkSummary::from(prepend);
+
+        buf.prepend(prepend, prepended_summary);
+
+        assert_eq!(buf, "Hello, World!");
+    }
+
+    #[test]
+    fn prepend_two_0() {
+        let mut buf = RawGapBuffer::<15>::from("!");
+
+        let hello = "Hello, ";
+
+        let world = "World";
+
+        let prepended_summary =
+            ChunkSummary::from(hello) + ChunkSummary::from(world);
+
+        buf.prepend_two(hello, world, prepended_summary);
+
+        assert_eq!(buf, "Hello, World!");
+    }
+
+    #[test]
+    fn remove_up_to_1() {
+        let mut buffer = RawGapBuffer::<10>::from("foo\nbar");
+
+        let removed_summary = ChunkSummary::from("foo\n");
+
+        buffer.remove_up_to(4, removed_summary);
+
+        assert_eq!(buffer, "bar");
+    }
+
+    #[test]
+    fn replace_non_overflowing_0() {
+        let mut buffer = RawGapBuffer::<10>::from("foo\nbar");
+        let summary = buffer.summarize();
+        buffer.replace_non_overflowing(4..7, "baz\r\n", summary);
+        assert_eq!(buffer, "foo\nbaz\r\n");
+    }
+
+    #[test]
+    fn replace_overflowing_0() {
+        let mut buffer = RawGapBuffer::<10>::from("foo\nbar");
+        let summary = buffer.summarize();
+
+        // Replace the newline with a string that's too long to fit in the
+        // buffer.
+        let (new_summary, extras) =
+            buffer.replace_overflowing(3..4, "foo\nbar\r\nbaz", summary);
+
+        assert_eq!(new_summary, buffer.summarize());
+
+        // How the text is split between the buffer and the extras depends on
+        // the minimum chunk fill.
+        let mut text = buffer.as_slice().to_string();
+        for extra in extras {
+            assert!(extra.len() <= 10);
+            text.push_str(&extra.as_slice().to_string());
+        }
+        assert_eq!(text, "foofoo\nbar\r\nbazbar");
+    }
+
+    #[test]
+    fn from_chunks_0() {
+        let buffer =
+            RawGapBuffer::<10>::from_chunks(&["a", "abb", "cc", "dd"]);
+        assert_eq!(buffer.left_chunk(), "aabb");
+        assert_eq!(buffer.right_chunk(), "ccdd");
+    }
+
+    #[test]
+    fn summarize_range_0() {
+        let buffer = RawGapBuffer::<10>::from("foo\nbar\r\n");
+        let summary = buffer.summarize();
+        assert_eq!(buffer.summarize_range(0..buffer.len(), summary), summary);
+
+        let s = buffer.summarize_range(0..4, summary);
+        assert_eq!(s.bytes(), 4);
+        #[cfg(feature = "lines")]
+        assert_eq!(s.line_breaks(), 1);
+
+        let s = buffer.summarize_range(2..buffer.len(), summary);
+        assert_eq!(s.bytes(), 7);
+        #[cfg(feature = "lines")]
+        assert_eq!(s.line_breaks(), 2);
+    }
+
     #[test]
     fn remove_up_to_0() {
         let s = "aaabbb";
-        let mut buffer = GapBuffer::<10>::from(s);
+        let mut buffer = RawGapBuffer::<10>::from(s);
 
         let summary = buffer.summarize();
 
@@ -1554,7 +2306,7 @@ mod tests {
     #[test]
     fn segmenter_0() {
         let chunk = "Hello Earth 🌎!";
-        let mut segmenter = GapBuffer::<4>::segmenter(chunk);
+        let mut segmenter = RawGapBuffer::<4>::segmenter(chunk);
 
         assert_eq!("Hell", segmenter.next().unwrap());
         assert_eq!("o Ea", segmenter.next().unwrap());
@@ -1635,4 +2387,36 @@ mod tests {
         assert_eq!("!", resegmenter.next().unwrap());
         assert_eq!(None, resegmenter.next());
     }
+
+    #[test]
+    fn resegmenter_with_gap_0() {
+        let segments = ["a", "abcdefgh", "b"];
+        let mut resegmenter =
+            Resegmenter::<3, 4>::new(segments).with_gap_at(6);
+
+        assert_eq!("aabc", resegmenter.next().unwrap());
+
+        let second = resegmenter.next().unwrap();
+        assert_eq!("de", second.left_chunk());
+        assert_eq!("fg", second.right_chunk());
+
+        assert_eq!("hb", resegmenter.next().unwrap());
+        assert_eq!(None, resegmenter.next());
+    }
+
+    #[test]
+    fn replace_overflowing_gap_at_edit() {
+        let mut buffer = RawGapBuffer::<10>::from("aaaaabbbbb");
+        let summary = buffer.summarize();
+
+        let (_, extras) = buffer.replace_overflowing(7..7, "cc", summary);
+
+        assert_eq!("aaaaabb", buffer);
+
+        let mut extras = extras.into_iter();
+        let extra = extras.next().unwrap();
+        assert_eq!("cc", extra.left_chunk());
+        assert_eq!("bbb", extra.right_chunk());
+        assert_eq!(None, extras.next());
+    }
 }
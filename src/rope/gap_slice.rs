@@ -1,16 +1,39 @@
-use super::metrics::{ChunkSummary, SummaryUpTo, ToByteOffset};
+use super::combined;
+use super::line_breaks::{LfBreaks, LineBreaks};
+use super::metrics::{
+    AllMetrics,
+    CharWeight,
+    ChunkSummary,
+    MetricSet,
+    SummaryUpTo,
+    ToByteOffset,
+    ZeroWeight,
+};
 use super::utils::{debug_no_quotes, panic_messages as panic};
 use crate::tree::{Metric, Summarize};
 
-/// A slice of a [`GapBuffer`](super::gap_buffer::GapBuffer).
+/// An immutable slice of a [`GapBuffer`](crate::GapBuffer).
+///
+/// The text of the slice is made of two string slices, the ones before and
+/// after the gap of the buffer, either of which can be empty.
+///
+/// This is returned by the [`byte_slice()`](crate::GapBuffer::byte_slice())
+/// method on `GapBuffer`.
 #[derive(Copy, Clone, Default)]
-pub struct GapSlice<'a> {
+pub struct GapSlice<
+    'a,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    S: MetricSet = AllMetrics,
+> {
     pub(super) bytes: &'a [u8],
-    pub(super) left_summary: ChunkSummary,
+    pub(super) left_summary: ChunkSummary<W, B, S>,
     pub(super) len_right: u16,
 }
 
-impl core::fmt::Debug for GapSlice<'_> {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> core::fmt::Debug
+    for GapSlice<'_, W, B, S>
+{
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         f.write_str("\"")?;
@@ -21,16 +44,65 @@ impl core::fmt::Debug for GapSlice<'_> {
     }
 }
 
-// We only need this to compare `GapSlice`s with `&str`s in (doc)tests.
-impl PartialEq<GapSlice<'_>> for &str {
-    fn eq(&self, rhs: &GapSlice<'_>) -> bool {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> core::fmt::Display
+    for GapSlice<'_, W, B, S>
+{
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.write_str(self.left_chunk())?;
+        f.write_str(self.right_chunk())
+    }
+}
+
+impl<W: CharWeight, B: LineBreaks, S: MetricSet>
+    PartialEq<GapSlice<'_, W, B, S>> for GapSlice<'_, W, B, S>
+{
+    #[inline]
+    fn eq(&self, rhs: &GapSlice<'_, W, B, S>) -> bool {
         self.len() == rhs.len()
-            && rhs.left_chunk() == &self[..rhs.len_left()]
-            && rhs.right_chunk() == &self[rhs.len_left()..]
+            && self
+                .left_chunk()
+                .bytes()
+                .chain(self.right_chunk().bytes())
+                .eq(rhs.left_chunk().bytes().chain(rhs.right_chunk().bytes()))
+    }
+}
+
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> Eq for GapSlice<'_, W, B, S> {}
+
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> PartialEq<str>
+    for GapSlice<'_, W, B, S>
+{
+    #[inline]
+    fn eq(&self, rhs: &str) -> bool {
+        let (left, right) =
+            rhs.as_bytes().split_at(self.len_left().min(rhs.len()));
+
+        self.len() == rhs.len()
+            && left == self.left_chunk().as_bytes()
+            && right == self.right_chunk().as_bytes()
+    }
+}
+
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> PartialEq<&str>
+    for GapSlice<'_, W, B, S>
+{
+    #[inline]
+    fn eq(&self, rhs: &&str) -> bool {
+        self == *rhs
     }
 }
 
-impl<'a> GapSlice<'a> {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet>
+    PartialEq<GapSlice<'_, W, B, S>> for &str
+{
+    #[inline]
+    fn eq(&self, rhs: &GapSlice<'_, W, B, S>) -> bool {
+        rhs == self
+    }
+}
+
+impl<'a, W: CharWeight, B: LineBreaks, S: MetricSet> GapSlice<'a, W, B, S> {
     /// Panics with a nicely formatted error message if the given byte offset
     /// is not a character boundary.
     #[track_caller]
@@ -83,17 +155,18 @@ impl<'a> GapSlice<'a> {
     #[inline]
     fn left_measure<M>(&self) -> M
     where
-        M: Metric<ChunkSummary>,
+        M: Metric<ChunkSummary<W, B, S>>,
     {
         M::measure(&self.left_summary)
     }
 
+    #[cfg(feature = "lines")]
     #[inline]
     pub(super) fn truncate_last_char(
         &mut self,
-        summary: ChunkSummary,
-    ) -> ChunkSummary {
-        debug_assert!(self.len() > 0);
+        summary: ChunkSummary<W, B, S>,
+    ) -> ChunkSummary<W, B, S> {
+        debug_assert!(!self.is_empty());
         debug_assert_eq!(summary, self.summarize());
 
         use core::cmp::Ordering;
@@ -135,28 +208,7 @@ impl<'a> GapSlice<'a> {
         }
     }
 
-    /// Removes the trailing line break (if it has one), returning the new
-    /// summary.
-    #[inline]
-    pub(super) fn truncate_trailing_line_break(
-        &mut self,
-        summary: ChunkSummary,
-    ) -> ChunkSummary {
-        debug_assert_eq!(summary, self.summarize());
-
-        if !self.has_trailing_newline() {
-            return summary;
-        }
-
-        let mut new_summary = self.truncate_last_char(summary);
-
-        if self.last_chunk().ends_with('\r') {
-            new_summary = self.truncate_last_char(new_summary)
-        }
-
-        new_summary
-    }
-
+    #[cfg(feature = "lines")]
     #[inline]
     pub(super) fn empty() -> Self {
         Self::default()
@@ -165,7 +217,7 @@ impl<'a> GapSlice<'a> {
     /// Returns `true` if it ends with a newline.
     #[inline]
     pub(super) fn has_trailing_newline(&self) -> bool {
-        self.last_chunk().ends_with('\n')
+        B::ends_with_line_break(self.last_chunk())
     }
 
     #[inline]
@@ -179,6 +231,46 @@ impl<'a> GapSlice<'a> {
         }
     }
 
+    /// Returns whether both segments are valid UTF-8, without relying on the
+    /// invariants `left_chunk()` and `right_chunk()` assume.
+    #[inline]
+    pub(super) fn is_valid_utf8(&self) -> bool {
+        let len_left = self.len_left().min(self.bytes.len());
+
+        let len_right = self.len_right().min(self.bytes.len() - len_left);
+
+        core::str::from_utf8(&self.bytes[..len_left]).is_ok()
+            && core::str::from_utf8(
+                &self.bytes[self.bytes.len() - len_right..],
+            )
+            .is_ok()
+    }
+
+    /// Returns the `char` starting at `byte_index`, panicking if it's not a
+    /// char boundary.
+    #[track_caller]
+    #[inline]
+    pub(super) fn char_at_byte(&self, byte_index: usize) -> char {
+        self.assert_char_boundary(byte_index);
+
+        let (segment, segment_offset) = self.chunk_at_byte(byte_index);
+
+        segment[byte_index - segment_offset..].chars().next().unwrap()
+    }
+
+    /// Returns the segment containing the byte at `byte_index`, together with
+    /// its byte offset in the slice.
+    #[inline]
+    pub(super) fn chunk_at_byte(&self, byte_index: usize) -> (&'a str, usize) {
+        debug_assert!(byte_index < self.len());
+
+        if byte_index < self.len_left() {
+            (self.left_chunk(), 0)
+        } else {
+            (self.right_chunk(), self.len_left())
+        }
+    }
+
     /// The second segment if it's not empty, or the first one otherwise.
     #[inline]
     pub(super) fn last_chunk(&self) -> &'a str {
@@ -189,16 +281,24 @@ impl<'a> GapSlice<'a> {
         }
     }
 
+    /// Returns `true` if the slice is empty.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the part of the slice before the gap.
     #[inline]
-    pub(super) fn left_chunk(&self) -> &'a str {
+    pub fn left_chunk(&self) -> &'a str {
         // SAFETY: the first `len_left` bytes are valid UTF-8.
         unsafe {
             core::str::from_utf8_unchecked(&self.bytes[..self.len_left()])
         }
     }
 
+    /// Returns the byte length of the slice.
     #[inline]
-    pub(super) fn len(&self) -> usize {
+    pub fn len(&self) -> usize {
         self.len_left() + self.len_right()
     }
 
@@ -217,8 +317,9 @@ impl<'a> GapSlice<'a> {
         self.len_right as _
     }
 
+    /// Returns the part of the slice after the gap.
     #[inline]
-    pub(super) fn right_chunk(&self) -> &'a str {
+    pub fn right_chunk(&self) -> &'a str {
         // SAFETY: the last `len_right` bytes are valid UTF-8.
         unsafe {
             core::str::from_utf8_unchecked(
@@ -228,7 +329,10 @@ impl<'a> GapSlice<'a> {
     }
 
     #[inline]
-    fn right_summary(&self, summary: ChunkSummary) -> ChunkSummary {
+    fn right_summary(
+        &self,
+        summary: ChunkSummary<W, B, S>,
+    ) -> ChunkSummary<W, B, S> {
         debug_assert_eq!(summary, self.summarize());
         summary - self.left_summary
     }
@@ -243,7 +347,7 @@ impl<'a> GapSlice<'a> {
     /// # Examples
     ///
     /// ```ignore
-    /// let gap_buffer = GapBuffer::<20>::from("foo\nbar\r\nbaz");
+    /// let gap_buffer = RawGapBuffer::<20>::from("foo\nbar\r\nbaz");
     ///
     /// let summary = gap_buffer.summarize();
     ///
@@ -254,15 +358,18 @@ impl<'a> GapSlice<'a> {
     ///
     /// assert_eq!("bar\r\nbaz", right);
     /// ```
+    #[allow(clippy::type_complexity)]
     #[track_caller]
     #[inline]
-    pub fn split_at_offset<M>(
+    pub(super) fn split_at_offset<M>(
         &self,
         mut offset: M,
-        summary: ChunkSummary,
-    ) -> ((Self, ChunkSummary), (Self, ChunkSummary))
+        summary: ChunkSummary<W, B, S>,
+    ) -> ((Self, ChunkSummary<W, B, S>), (Self, ChunkSummary<W, B, S>))
     where
-        M: Metric<ChunkSummary> + ToByteOffset + SummaryUpTo,
+        M: Metric<ChunkSummary<W, B, S>>
+            + ToByteOffset<W, B, S>
+            + SummaryUpTo<W, B, S>,
     {
         debug_assert_eq!(summary, self.summarize());
 
@@ -347,46 +454,42 @@ impl<'a> GapSlice<'a> {
     }
 
     #[inline]
-    fn summarize_right_chunk(&self) -> ChunkSummary {
+    fn summarize_right_chunk(&self) -> ChunkSummary<W, B, S> {
         ChunkSummary::from(self.right_chunk())
     }
 }
 
-impl Summarize for GapSlice<'_> {
-    type Summary = ChunkSummary;
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> Summarize
+    for GapSlice<'_, W, B, S>
+{
+    type Summary = ChunkSummary<W, B, S>;
+
+    const SUMMARY_IS_SUBTRACTABLE: bool = !combined::IS_ENABLED;
 
     #[inline]
     fn summarize(&self) -> Self::Summary {
-        self.left_summary + self.summarize_right_chunk()
+        // The left summary is updated by subtracting from it, which leaves
+        // its combined parts stale.
+        let left_summary = if combined::IS_ENABLED {
+            ChunkSummary::from(self.left_chunk())
+        } else {
+            self.left_summary
+        };
+
+        let mut summary = left_summary + self.summarize_right_chunk();
+        summary.join_segments(self.left_chunk(), self.right_chunk());
+        summary
     }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::rope::gap_buffer::GapBuffer;
-    use crate::tree::{AsSlice, Summarize};
+    use crate::rope::gap_buffer::RawGapBuffer;
+    use crate::tree::AsSlice;
 
     #[test]
     fn debug_slice() {
-        let buffer = GapBuffer::<10>::from("Hello");
+        let buffer = RawGapBuffer::<10>::from("Hello");
         assert_eq!("\"He~~~~~llo\"", format!("{:?}", buffer.as_slice()));
     }
-
-    #[test]
-    fn truncate_trailing_crlf() {
-        let buffer = GapBuffer::<5>::from("bar\r\n");
-        let mut slice = buffer.as_slice();
-        let summary = slice.summarize();
-        slice.truncate_trailing_line_break(summary);
-        assert_eq!("bar", slice);
-    }
-
-    #[test]
-    fn truncate_trailing_lf() {
-        let buffer = GapBuffer::<5>::from("bar\n");
-        let mut slice = buffer.as_slice();
-        let summary = slice.summarize();
-        slice.truncate_trailing_line_break(summary);
-        assert_eq!("bar", slice);
-    }
 }
@@ -0,0 +1,118 @@
+use alloc::sync::Arc;
+use core::ops::Range;
+
+use super::anchors::{map_offset, Gravity};
+
+/// A single edit made to a [`Tracked`](crate::Tracked) rope.
+#[derive(Clone, Debug)]
+struct Edit {
+    replaced: Range<usize>,
+    inserted_len: usize,
+}
+
+/// The revision of a [`Tracked`](crate::Tracked) rope together with the
+/// edits made to it since history tracking was enabled.
+///
+/// The edits are shared between clones of the same `Tracked` and only copied
+/// when one of them is edited.
+#[derive(Clone, Debug, Default)]
+pub(super) struct History {
+    /// The number of edits made to the `Tracked` since it was created.
+    revision: u64,
+
+    /// The edits made since the `first` revision, or `None` if the history
+    /// isn't being tracked.
+    edits: Option<Arc<Vec<Edit>>>,
+
+    /// The revision the first of the `edits` was made on.
+    first: u64,
+}
+
+impl History {
+    /// Drops the edits made before the given revision.
+    #[inline]
+    pub(super) fn forget_before(&mut self, revision: u64) {
+        let Some(edits) = &mut self.edits else { return };
+
+        let revision = revision.clamp(self.first, self.revision);
+
+        let forgotten = (revision - self.first) as usize;
+
+        if forgotten > 0 {
+            Arc::make_mut(edits).drain(..forgotten);
+            self.first = revision;
+        }
+    }
+
+    /// Records that the bytes in `replaced` have been replaced by
+    /// `inserted_len` new bytes.
+    #[inline]
+    pub(super) fn push(
+        &mut self,
+        replaced: Range<usize>,
+        inserted_len: usize,
+    ) {
+        self.revision += 1;
+
+        if let Some(edits) = &mut self.edits {
+            Arc::make_mut(edits).push(Edit { replaced, inserted_len });
+        }
+    }
+
+    /// Maps a byte offset in the text as it was at the given revision to the
+    /// corresponding byte offset in the current text, returning `None` if
+    /// the revision is not in the history.
+    ///
+    /// The second element of the returned tuple is the length of the text
+    /// at that revision.
+    #[inline]
+    pub(super) fn resolve(
+        &self,
+        revision: u64,
+        byte_offset: usize,
+        gravity: Gravity,
+        current_len: usize,
+    ) -> Option<(usize, usize)> {
+        let edits = self.edits.as_ref()?;
+
+        if revision < self.first || revision > self.revision {
+            return None;
+        }
+
+        let edits = &edits[(revision - self.first) as usize..];
+
+        let len = edits.iter().rev().fold(current_len, |len, edit| {
+            len + edit.replaced.len() - edit.inserted_len
+        });
+
+        if byte_offset > len {
+            return Some((byte_offset, len));
+        }
+
+        let offset = edits.iter().fold(byte_offset, |offset, edit| {
+            map_offset(
+                offset,
+                gravity,
+                edit.replaced.clone(),
+                edit.inserted_len,
+            )
+        });
+
+        Some((offset, len))
+    }
+
+    #[inline]
+    pub(super) fn revision(&self) -> u64 {
+        self.revision
+    }
+
+    /// Starts recording the edits made from the current revision onwards,
+    /// unless they're already being recorded.
+    #[inline]
+    pub(super) fn track(&mut self) {
+        if self.edits.is_none() {
+            self.edits = Some(Arc::default());
+            self.first = self.revision;
+        }
+    }
+}
@@ -1,22 +1,70 @@
-use super::metrics::{ByteMetric, LineMetric, RawLineMetric};
-use super::rope::RopeChunk;
-use super::{Rope, RopeSlice};
-use crate::tree::{Leaves, Units};
+use super::line_breaks::{LfBreaks, LineBreaks};
+#[cfg(feature = "char-metric")]
+use super::metrics::sealed::Sealed as _;
+#[cfg(feature = "lines")]
+use super::metrics::ByteMetric;
+#[cfg(any(feature = "lines", feature = "char-metric"))]
+use super::metrics::Maintained;
+#[cfg(feature = "lines")]
+use super::metrics::RawLineMetric;
+use super::metrics::{
+    convert,
+    count,
+    AllMetrics,
+    CharWeight,
+    MetricSet,
+    ZeroWeight,
+};
+use super::rope::{RopeChunk, CHUNK_MAX_BYTES, CHUNK_MIN_FILL};
+use super::{Rope, RopeSlice, RopeWith};
+use crate::tree::{AtomicCounter, Leaves, RefCounter};
+#[cfg(feature = "lines")]
+use crate::tree::{TreeSlice, Units};
 
 /// An iterator over the `&str` chunks of `Rope`s and `RopeSlice`s.
 ///
+/// The text of a `Rope` is stored in gap buffers, each holding two contiguous
+/// segments of text: the one before and the one after the gap. Every segment
+/// is yielded as a separate chunk, so iterating over the chunks never copies
+/// or moves any text, and two consecutive chunks can come from the same
+/// buffer. Use [`ChunkSegments`] to get the two segments of each buffer
+/// together.
+///
 /// This struct is created by the `chunks` method on [`Rope`](Rope::chunks())
 /// and [`RopeSlice`](RopeSlice::chunks()). See their documentation for more.
 #[derive(Clone)]
-pub struct Chunks<'a> {
-    leaves: Leaves<'a, { Rope::arity() }, RopeChunk>,
+pub struct Chunks<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet = AllMetrics,
+> {
+    leaves: Leaves<
+        'a,
+        { Rope::arity() },
+        RopeChunk<W, B, MAX_BYTES, MIN_FILL, S>,
+        C,
+    >,
     forward_extra_right: Option<&'a str>,
     backward_extra_left: Option<&'a str>,
 }
 
-impl<'a> From<&'a Rope> for Chunks<'a> {
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for Chunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn from(rope: &'a Rope) -> Self {
+    fn from(rope: &'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
         let mut leaves = rope.tree.leaves();
         if rope.is_empty() {
             let _ = leaves.next();
@@ -25,9 +73,19 @@ impl<'a> From<&'a Rope> for Chunks<'a> {
     }
 }
 
-impl<'a> From<&RopeSlice<'a>> for Chunks<'a> {
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for Chunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn from(slice: &RopeSlice<'a>) -> Self {
+    fn from(slice: &RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
         let mut leaves = slice.tree_slice.leaves();
         if slice.is_empty() {
             let _ = leaves.next();
@@ -36,7 +94,16 @@ impl<'a> From<&RopeSlice<'a>> for Chunks<'a> {
     }
 }
 
-impl<'a> Iterator for Chunks<'a> {
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Iterator for Chunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     type Item = &'a str;
 
     #[inline]
@@ -68,12 +135,32 @@ impl<'a> Iterator for Chunks<'a> {
 
     #[inline]
     fn size_hint(&self) -> (usize, Option<usize>) {
-        let exact = self.leaves.len();
-        (exact, Some(exact * 2))
+        let extras = usize::from(self.forward_extra_right.is_some())
+            + usize::from(self.backward_extra_left.is_some());
+
+        let leaves = self.leaves.len();
+
+        // Every leaf yields either one or two chunks, except for empty leaves
+        // which can only exist when the `small_chunks` feature is enabled.
+        #[cfg(not(feature = "small_chunks"))]
+        let lo = leaves + extras;
+
+        #[cfg(feature = "small_chunks")]
+        let lo = extras;
+
+        (lo, Some(leaves * 2 + extras))
     }
 }
 
-impl DoubleEndedIterator for Chunks<'_> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > DoubleEndedIterator for Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn next_back(&mut self) -> Option<Self::Item> {
         if let Some(extra) = self.backward_extra_left.take() {
@@ -102,15 +189,178 @@ impl DoubleEndedIterator for Chunks<'_> {
     }
 }
 
-impl core::iter::FusedIterator for Chunks<'_> {}
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::iter::FusedIterator
+    for Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
+
+/// An iterator over the pairs of contiguous text segments of the gap buffers
+/// storing the text of `Rope`s and `RopeSlice`s.
+///
+/// Every item is the text before and the text after the gap of a buffer.
+/// Either one can be empty, but never both.
+///
+/// This struct is created by the `chunk_segments` method on
+/// [`Rope`](Rope::chunk_segments()) and
+/// [`RopeSlice`](RopeSlice::chunk_segments()). See their documentation for
+/// more.
+#[derive(Clone)]
+pub struct ChunkSegments<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet = AllMetrics,
+> {
+    leaves: Leaves<
+        'a,
+        { Rope::arity() },
+        RopeChunk<W, B, MAX_BYTES, MIN_FILL, S>,
+        C,
+    >,
+}
+
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for ChunkSegments<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from(rope: &'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        let mut leaves = rope.tree.leaves();
+        if rope.is_empty() {
+            let _ = leaves.next();
+        }
+        Self { leaves }
+    }
+}
+
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for ChunkSegments<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from(slice: &RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        let mut leaves = slice.tree_slice.leaves();
+        if slice.is_empty() {
+            let _ = leaves.next();
+        }
+        Self { leaves }
+    }
+}
+
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Iterator for ChunkSegments<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type Item = (&'a str, &'a str);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let chunk = self.leaves.next()?;
+
+        #[cfg(feature = "small_chunks")]
+        if chunk.is_empty() {
+            return self.next();
+        }
+
+        Some((chunk.left_chunk(), chunk.right_chunk()))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let leaves = self.leaves.len();
+
+        // Empty leaves can only exist when the `small_chunks` feature is
+        // enabled.
+        #[cfg(not(feature = "small_chunks"))]
+        let lo = leaves;
+
+        #[cfg(feature = "small_chunks")]
+        let lo = 0;
+
+        (lo, Some(leaves))
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > DoubleEndedIterator
+    for ChunkSegments<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let chunk = self.leaves.next_back()?;
+
+        #[cfg(feature = "small_chunks")]
+        if chunk.is_empty() {
+            return self.next_back();
+        }
+
+        Some((chunk.left_chunk(), chunk.right_chunk()))
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::iter::FusedIterator
+    for ChunkSegments<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
 
 /// An iterator over the bytes of `Rope`s and `RopeSlice`s.
 ///
 /// This struct is created by the `bytes` method on [`Rope`](Rope::bytes())
 /// and [`RopeSlice`](RopeSlice::bytes()). See their documentation for more.
 #[derive(Clone)]
-pub struct Bytes<'a> {
-    chunks: Chunks<'a>,
+pub struct Bytes<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet = AllMetrics,
+> {
+    chunks: Chunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
 
     /// The chunk used when calling [`Bytes::next()`].
     forward_chunk: &'a [u8],
@@ -132,9 +382,87 @@ pub struct Bytes<'a> {
     bytes_total: usize,
 }
 
-impl<'a> From<&'a Rope> for Bytes<'a> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Bytes<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    /// Copies the next bytes of the iterator into `buf`, returning how many
+    /// were copied.
+    ///
+    /// This copies whole chunks at a time, so it's a lot faster than calling
+    /// [`next()`](Self::next()) for every byte. The returned value is less
+    /// than the length of `buf` only if the iterator has run out of bytes.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello Earth!");
+    /// let mut bytes = r.bytes();
+    ///
+    /// let mut buf = [0; 8];
+    /// assert_eq!(bytes.read_into(&mut buf), 8);
+    /// assert_eq!(&buf, b"Hello Ea");
+    ///
+    /// assert_eq!(bytes.read_into(&mut buf), 4);
+    /// assert_eq!(&buf[..4], b"rth!");
+    ///
+    /// assert_eq!(bytes.read_into(&mut buf), 0);
+    /// ```
     #[inline]
-    fn from(rope: &'a Rope) -> Self {
+    pub fn read_into(&mut self, buf: &mut [u8]) -> usize {
+        let mut read = 0;
+
+        while read < buf.len() {
+            if self.forward_byte_idx == self.forward_chunk.len() {
+                if let Some(chunk) = self.chunks.next() {
+                    self.forward_chunk = chunk.as_bytes();
+                    self.forward_byte_idx = 0;
+                    continue;
+                }
+
+                // The only bytes left are the ones of the backward chunk.
+                let len = (buf.len() - read).min(self.backward_byte_idx);
+                buf[read..read + len]
+                    .copy_from_slice(&self.backward_chunk[..len]);
+                self.backward_chunk = &self.backward_chunk[len..];
+                self.backward_byte_idx -= len;
+                read += len;
+                break;
+            }
+
+            let rest = &self.forward_chunk[self.forward_byte_idx..];
+            let len = (buf.len() - read).min(rest.len());
+            buf[read..read + len].copy_from_slice(&rest[..len]);
+            self.forward_byte_idx += len;
+            read += len;
+        }
+
+        self.bytes_yielded += read;
+
+        read
+    }
+}
+
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for Bytes<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from(rope: &'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
         Self {
             chunks: rope.chunks(),
             forward_chunk: &[],
@@ -147,9 +475,19 @@ impl<'a> From<&'a Rope> for Bytes<'a> {
     }
 }
 
-impl<'a> From<&RopeSlice<'a>> for Bytes<'a> {
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for Bytes<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn from(slice: &RopeSlice<'a>) -> Self {
+    fn from(slice: &RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
         Self {
             chunks: slice.chunks(),
             forward_chunk: &[],
@@ -162,7 +500,15 @@ impl<'a> From<&RopeSlice<'a>> for Bytes<'a> {
     }
 }
 
-impl Iterator for Bytes<'_> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Iterator for Bytes<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     type Item = u8;
 
     #[inline]
@@ -193,9 +539,60 @@ impl Iterator for Bytes<'_> {
         let exact = self.len();
         (exact, Some(exact))
     }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+
+    /// Skips whole chunks at a time, so it only looks at the chunks it skips
+    /// rather than at every byte in them.
+    #[inline]
+    fn nth(&mut self, mut n: usize) -> Option<Self::Item> {
+        loop {
+            let left_in_chunk =
+                self.forward_chunk.len() - self.forward_byte_idx;
+
+            if n < left_in_chunk {
+                self.forward_byte_idx += n;
+                self.bytes_yielded += n;
+                return self.next();
+            }
+
+            n -= left_in_chunk;
+            self.forward_byte_idx = self.forward_chunk.len();
+            self.bytes_yielded += left_in_chunk;
+
+            if let Some(chunk) = self.chunks.next() {
+                self.forward_chunk = chunk.as_bytes();
+                self.forward_byte_idx = 0;
+                continue;
+            }
+
+            // The only bytes left are the ones of the backward chunk.
+            let skipped = n.min(self.backward_byte_idx);
+            self.backward_chunk = &self.backward_chunk[skipped..];
+            self.backward_byte_idx -= skipped;
+            self.bytes_yielded += skipped;
+            return self.next();
+        }
+    }
 }
 
-impl DoubleEndedIterator for Bytes<'_> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > DoubleEndedIterator for Bytes<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn next_back(&mut self) -> Option<Self::Item> {
         if self.backward_byte_idx == 0 {
@@ -220,14 +617,195 @@ impl DoubleEndedIterator for Bytes<'_> {
     }
 }
 
-impl ExactSizeIterator for Bytes<'_> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > ExactSizeIterator for Bytes<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn len(&self) -> usize {
         self.bytes_total - self.bytes_yielded
     }
 }
 
-impl core::iter::FusedIterator for Bytes<'_> {}
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::iter::FusedIterator
+    for Bytes<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
+
+/// An iterator over the Rabin-Karp rolling hashes of the windows of bytes of
+/// `Rope`s and `RopeSlice`s.
+///
+/// The `n`-th hash is the hash of the `window` bytes starting at byte offset
+/// `n`, computed as `b₀·Bʷ⁻¹ + b₁·Bʷ⁻² + … + bʷ⁻¹` modulo 2⁶⁴, where `b₀..bʷ⁻¹`
+/// are the bytes of the window and `B` is [`RollingHashes::BASE`]. Each hash is
+/// obtained from the previous one in constant time, even when the window
+/// spans several chunks.
+///
+/// This struct is created by the `rolling_hashes` method on
+/// [`Rope`](Rope::rolling_hashes()) and
+/// [`RopeSlice`](RopeSlice::rolling_hashes()). See their documentation for
+/// more.
+#[derive(Clone)]
+pub struct RollingHashes<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet = AllMetrics,
+> {
+    /// Yields the bytes entering the window.
+    incoming: Bytes<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+
+    /// Yields the bytes leaving the window.
+    outgoing: Bytes<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+
+    /// The number of bytes in the window.
+    window: usize,
+
+    /// `BASE` raised to the `window - 1`, i.e. the weight of the byte leaving
+    /// the window.
+    outgoing_weight: u64,
+
+    /// The hash of the current window, or `None` if the first window hasn't
+    /// been hashed yet.
+    hash: Option<u64>,
+}
+
+/// The base of the polynomial the rolling hashes are computed with.
+const ROLLING_HASH_BASE: u64 = 0x0100_0000_01b3;
+
+impl RollingHashes<'_> {
+    /// The base of the polynomial the hashes are computed with.
+    pub const BASE: u64 = ROLLING_HASH_BASE;
+}
+
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > RollingHashes<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[track_caller]
+    #[inline]
+    pub(super) fn new(
+        bytes: Bytes<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+        window: usize,
+    ) -> Self {
+        assert!(window > 0, "the window must be greater than zero");
+
+        let outgoing_weight = (1..window)
+            .fold(1u64, |weight, _| weight.wrapping_mul(ROLLING_HASH_BASE));
+
+        Self {
+            incoming: bytes.clone(),
+            outgoing: bytes,
+            window,
+            outgoing_weight,
+            hash: None,
+        }
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Iterator for RollingHashes<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type Item = u64;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let hash = match self.hash {
+            Some(hash) => {
+                let incoming = self.incoming.next()?;
+                let outgoing = self.outgoing.next()?;
+
+                hash.wrapping_sub(
+                    (outgoing as u64).wrapping_mul(self.outgoing_weight),
+                )
+                .wrapping_mul(ROLLING_HASH_BASE)
+                .wrapping_add(incoming as u64)
+            },
+
+            None => {
+                if self.incoming.len() < self.window {
+                    return None;
+                }
+
+                self.incoming.by_ref().take(self.window).fold(
+                    0u64,
+                    |hash, byte| {
+                        hash.wrapping_mul(ROLLING_HASH_BASE)
+                            .wrapping_add(byte as u64)
+                    },
+                )
+            },
+        };
+
+        self.hash = Some(hash);
+
+        Some(hash)
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let exact = self.len();
+        (exact, Some(exact))
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > ExactSizeIterator
+    for RollingHashes<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn len(&self) -> usize {
+        match self.hash {
+            Some(_) => self.incoming.len(),
+            None => (self.incoming.len() + 1).saturating_sub(self.window),
+        }
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::iter::FusedIterator
+    for RollingHashes<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
 
 /// An iterator over the code points (i.e. [`char`]s) of `Rope`s and
 /// `RopeSlice`s.
@@ -235,8 +813,16 @@ impl core::iter::FusedIterator for Bytes<'_> {}
 /// This struct is created by the `chars` method on [`Rope`](Rope::chars())
 /// and [`RopeSlice`](RopeSlice::chars()). See their documentation for more.
 #[derive(Clone)]
-pub struct Chars<'a> {
-    chunks: Chunks<'a>,
+pub struct Chars<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet = AllMetrics,
+> {
+    chunks: Chunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
 
     /// The chunk used when calling [`Chars::next()`].
     forward_chunk: &'a str,
@@ -250,35 +836,85 @@ pub struct Chars<'a> {
 
     /// The number of bytes of `backward_chunk` which are yet to be yielded.
     backward_byte_idx: usize,
+
+    /// The number of chars that have been yielded so far.
+    #[cfg(feature = "char-metric")]
+    chars_yielded: usize,
+
+    /// The total number of chars this iterator will yield, or zero if the
+    /// `MetricSet` doesn't maintain the chars.
+    #[cfg(feature = "char-metric")]
+    chars_total: usize,
 }
 
-impl<'a> From<&'a Rope> for Chars<'a> {
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for Chars<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn from(rope: &'a Rope) -> Self {
+    fn from(rope: &'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
         Self {
             chunks: rope.chunks(),
             forward_chunk: "",
             forward_byte_idx: 0,
             backward_chunk: "",
             backward_byte_idx: 0,
+            #[cfg(feature = "char-metric")]
+            chars_yielded: 0,
+            #[cfg(feature = "char-metric")]
+            chars_total: rope.tree.summary().maintained_chars().unwrap_or(0),
         }
     }
 }
 
-impl<'a> From<&RopeSlice<'a>> for Chars<'a> {
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for Chars<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn from(slice: &RopeSlice<'a>) -> Self {
+    fn from(slice: &RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
         Self {
             chunks: slice.chunks(),
             forward_chunk: "",
             forward_byte_idx: 0,
             backward_chunk: "",
             backward_byte_idx: 0,
+            #[cfg(feature = "char-metric")]
+            chars_yielded: 0,
+            #[cfg(feature = "char-metric")]
+            chars_total: slice
+                .tree_slice
+                .summary()
+                .maintained_chars()
+                .unwrap_or(0),
         }
     }
 }
 
-impl<'a> Iterator for Chars<'a> {
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Iterator for Chars<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     type Item = char;
 
     #[inline]
@@ -302,6 +938,10 @@ impl<'a> Iterator for Chars<'a> {
                 let len = ch.len_utf8();
                 self.backward_chunk = &self.backward_chunk[len..];
                 self.backward_byte_idx -= len;
+                #[cfg(feature = "char-metric")]
+                {
+                    self.chars_yielded += 1;
+                }
                 return Some(ch);
             }
         }
@@ -317,12 +957,110 @@ impl<'a> Iterator for Chars<'a> {
         };
 
         self.forward_byte_idx += ch.len_utf8();
+        #[cfg(feature = "char-metric")]
+        {
+            self.chars_yielded += 1;
+        }
 
         Some(ch)
     }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        #[cfg(feature = "char-metric")]
+        if S::Chars::IS_MAINTAINED {
+            let exact = self.chars_total - self.chars_yielded;
+            return (exact, Some(exact));
+        }
+
+        // A char is between 1 and 4 bytes long, and none of the chunks left
+        // is longer than `MAX_BYTES`.
+        let bytes_in_hand = self.forward_chunk.len() - self.forward_byte_idx
+            + self.backward_byte_idx;
+
+        let hi = self
+            .chunks
+            .size_hint()
+            .1
+            .and_then(|chunks| chunks.checked_mul(MAX_BYTES))
+            .and_then(|bytes| bytes.checked_add(bytes_in_hand));
+
+        ((bytes_in_hand + 3) / 4, hi)
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        #[cfg(feature = "char-metric")]
+        if S::Chars::IS_MAINTAINED {
+            return self.chars_total - self.chars_yielded;
+        }
+
+        count::chars(&self.forward_chunk[self.forward_byte_idx..])
+            + self.chunks.map(count::chars).sum::<usize>()
+            + count::chars(&self.backward_chunk[..self.backward_byte_idx])
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+
+    /// Skips whole chunks at a time, counting the chars in them in bulk
+    /// rather than decoding them one by one.
+    #[inline]
+    fn nth(&mut self, mut n: usize) -> Option<Self::Item> {
+        loop {
+            let rest = &self.forward_chunk[self.forward_byte_idx..];
+
+            let left_in_chunk = count::chars(rest);
+
+            if n < left_in_chunk {
+                self.forward_byte_idx += convert::byte_of_char(rest, n);
+                #[cfg(feature = "char-metric")]
+                {
+                    self.chars_yielded += n;
+                }
+                return self.next();
+            }
+
+            n -= left_in_chunk;
+            self.forward_byte_idx = self.forward_chunk.len();
+            #[cfg(feature = "char-metric")]
+            {
+                self.chars_yielded += left_in_chunk;
+            }
+
+            if let Some(chunk) = self.chunks.next() {
+                self.forward_chunk = chunk;
+                self.forward_byte_idx = 0;
+                continue;
+            }
+
+            // The only chars left are the ones of the backward chunk.
+            let rest = &self.backward_chunk[..self.backward_byte_idx];
+
+            let skipped = n.min(count::chars(rest));
+            let skipped_bytes = convert::byte_of_char(rest, skipped);
+            self.backward_chunk = &self.backward_chunk[skipped_bytes..];
+            self.backward_byte_idx -= skipped_bytes;
+            #[cfg(feature = "char-metric")]
+            {
+                self.chars_yielded += skipped;
+            }
+            return self.next();
+        }
+    }
 }
 
-impl DoubleEndedIterator for Chars<'_> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > DoubleEndedIterator for Chars<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn next_back(&mut self) -> Option<Self::Item> {
         if self.backward_byte_idx == 0 {
@@ -345,6 +1083,11 @@ impl DoubleEndedIterator for Chars<'_> {
                 self.forward_chunk = &self.forward_chunk
                     [..self.forward_chunk.len() - ch.len_utf8()];
 
+                #[cfg(feature = "char-metric")]
+                {
+                    self.chars_yielded += 1;
+                }
+
                 return Some(ch);
             }
         }
@@ -359,12 +1102,43 @@ impl DoubleEndedIterator for Chars<'_> {
         };
 
         self.backward_byte_idx -= ch.len_utf8();
+        #[cfg(feature = "char-metric")]
+        {
+            self.chars_yielded += 1;
+        }
 
         Some(ch)
     }
 }
 
-impl core::iter::FusedIterator for Chars<'_> {}
+#[cfg_attr(docsrs, doc(cfg(feature = "char-metric")))]
+#[cfg(feature = "char-metric")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Chars = Maintained>,
+    > ExactSizeIterator for Chars<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn len(&self) -> usize {
+        self.chars_total - self.chars_yielded
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::iter::FusedIterator
+    for Chars<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
 
 /// An iterator over the lines of `Rope`s and `RopeSlice`s, including the line
 /// terminators (`\n` or `\r\n`).
@@ -372,9 +1146,25 @@ impl core::iter::FusedIterator for Chars<'_> {}
 /// This struct is created by the `raw_lines` method on
 /// [`Rope`](Rope::raw_lines()) and [`RopeSlice`](RopeSlice::raw_lines()). See
 /// their documentation for more.
+#[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+#[cfg(feature = "lines")]
 #[derive(Clone)]
-pub struct RawLines<'a> {
-    units: Units<'a, { Rope::arity() }, RopeChunk, RawLineMetric>,
+pub struct RawLines<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet<Lines = Maintained> = AllMetrics,
+> {
+    units: Units<
+        'a,
+        { Rope::arity() },
+        RopeChunk<W, B, MAX_BYTES, MIN_FILL, S>,
+        RawLineMetric,
+        C,
+    >,
 
     /// The number of lines that have been yielded so far.
     lines_yielded: usize,
@@ -383,9 +1173,20 @@ pub struct RawLines<'a> {
     lines_total: usize,
 }
 
-impl<'a> From<&'a Rope> for RawLines<'a> {
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > From<&'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RawLines<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn from(rope: &'a Rope) -> Self {
+    fn from(rope: &'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
         Self {
             units: rope.tree.units::<RawLineMetric>(),
             lines_yielded: 0,
@@ -394,9 +1195,20 @@ impl<'a> From<&'a Rope> for RawLines<'a> {
     }
 }
 
-impl<'a> From<&RopeSlice<'a>> for RawLines<'a> {
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > From<&RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RawLines<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn from(slice: &RopeSlice<'a>) -> Self {
+    fn from(slice: &RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
         Self {
             units: slice.tree_slice.units::<RawLineMetric>(),
             lines_yielded: 0,
@@ -405,8 +1217,18 @@ impl<'a> From<&RopeSlice<'a>> for RawLines<'a> {
     }
 }
 
-impl<'a> Iterator for RawLines<'a> {
-    type Item = RopeSlice<'a>;
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > Iterator for RawLines<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type Item = RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
@@ -420,9 +1242,28 @@ impl<'a> Iterator for RawLines<'a> {
         let exact = self.len();
         (exact, Some(exact))
     }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
 }
 
-impl DoubleEndedIterator for RawLines<'_> {
+#[cfg(feature = "lines")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > DoubleEndedIterator for RawLines<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn next_back(&mut self) -> Option<Self::Item> {
         let (tree_slice, _) = self.units.next_back()?;
@@ -431,23 +1272,59 @@ impl DoubleEndedIterator for RawLines<'_> {
     }
 }
 
-impl ExactSizeIterator for RawLines<'_> {
+#[cfg(feature = "lines")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > ExactSizeIterator for RawLines<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn len(&self) -> usize {
         self.lines_total - self.lines_yielded
     }
 }
 
-impl core::iter::FusedIterator for RawLines<'_> {}
+#[cfg(feature = "lines")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > core::iter::FusedIterator
+    for RawLines<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
 
 /// An iterator over the lines of `Rope`s and `RopeSlice`s, not including the
 /// line terminators (`\n` or `\r\n`).
 ///
 /// This struct is created by the `lines` method on [`Rope`](Rope::lines()) and
 /// [`RopeSlice`](RopeSlice::lines()). See their documentation for more.
+#[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+#[cfg(feature = "lines")]
 #[derive(Clone)]
-pub struct Lines<'a> {
-    units: Units<'a, { Rope::arity() }, RopeChunk, LineMetric>,
+pub struct Lines<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet<Lines = Maintained> = AllMetrics,
+> {
+    units: Units<
+        'a,
+        { Rope::arity() },
+        RopeChunk<W, B, MAX_BYTES, MIN_FILL, S>,
+        LinesMetric,
+        C,
+    >,
 
     /// The number of lines that have been yielded so far.
     lines_yielded: usize,
@@ -456,49 +1333,436 @@ pub struct Lines<'a> {
     lines_total: usize,
 }
 
-impl<'a> From<&'a Rope> for Lines<'a> {
+/// The metric used by [`Lines`] to iterate over the lines.
+///
+/// A trailing `'\r'` left in a line could either be the first half of a CRLF
+/// pair split across chunks or, with [`UnicodeBreaks`](super::UnicodeBreaks),
+/// a character preceding a vertical tab or a form feed, and the advance alone
+/// isn't enough to tell them apart. So we iterate over the raw lines and
+/// truncate their line breaks ourselves.
+#[cfg(feature = "lines")]
+type LinesMetric = RawLineMetric;
+
+/// Turns a unit yielded by the [`Lines`]' `units` into the corresponding
+/// line.
+#[cfg(feature = "lines")]
+#[inline]
+fn line_of_unit<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    tree_slice: TreeSlice<
+        '_,
+        { Rope::arity() },
+        RopeChunk<W, B, MAX_BYTES, MIN_FILL, S>,
+        C,
+    >,
+) -> RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S> {
+    let mut slice = RopeSlice::from(tree_slice);
+
+    if slice.has_trailing_newline() {
+        slice.truncate_trailing_line_break();
+    }
+
+    slice
+}
+
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > From<&'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for Lines<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn from(rope: &'a Rope) -> Self {
+    fn from(rope: &'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
         Self {
-            units: rope.tree.units::<LineMetric>(),
+            units: rope.tree.units::<LinesMetric>(),
             lines_yielded: 0,
             lines_total: rope.line_len(),
         }
     }
 }
 
-impl<'a> From<&RopeSlice<'a>> for Lines<'a> {
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > From<&RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for Lines<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn from(slice: &RopeSlice<'a>) -> Self {
+    fn from(slice: &RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
         Self {
-            units: slice.tree_slice.units::<LineMetric>(),
+            units: slice.tree_slice.units::<LinesMetric>(),
             lines_yielded: 0,
             lines_total: slice.line_len(),
         }
     }
 }
 
-impl<'a> Iterator for Lines<'a> {
-    type Item = RopeSlice<'a>;
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > Iterator for Lines<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type Item = RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let (tree_slice, ByteMetric(advance)) = self.units.next()?;
+        let (tree_slice, _) = self.units.next()?;
         self.lines_yielded += 1;
+        Some(line_of_unit(tree_slice))
+    }
 
-        let mut slice = RopeSlice { tree_slice, has_trailing_newline: false };
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let exact = self.len();
+        (exact, Some(exact))
+    }
 
-        // This handles CRLF pairs that have been split across chunks. For
-        // example, if we have "aaa\r" and "\nbbb" we should yield "aaa", but
-        // the tree slice currently contains "aaa\r", so we need to remove
-        // the trailing "\r".
-        if slice.tree_slice.end_slice().last_chunk().ends_with('\r')
-            && advance - slice.byte_len() == 1
-        {
-            slice.truncate_last_char();
+    #[inline]
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+
+#[cfg(feature = "lines")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > DoubleEndedIterator for Lines<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let (tree_slice, _) = self.units.next_back()?;
+        self.lines_yielded += 1;
+        Some(line_of_unit(tree_slice))
+    }
+}
+
+#[cfg(feature = "lines")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > ExactSizeIterator for Lines<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn len(&self) -> usize {
+        self.lines_total - self.lines_yielded
+    }
+}
+
+#[cfg(feature = "lines")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > core::iter::FusedIterator
+    for Lines<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
+
+/// An iterator over the lines intersecting a byte range of `Rope`s and
+/// `RopeSlice`s, together with their line indices.
+///
+/// The first and last lines are clipped to the byte range, and like with
+/// [`Lines`] the line terminators are not included. If the range ends between
+/// the `\r` and the `\n` of a CRLF the `\r` is trimmed from the last line.
+///
+/// This struct is created by the `lines_in_byte_range` method on
+/// [`Rope`](Rope::lines_in_byte_range()) and
+/// [`RopeSlice`](RopeSlice::lines_in_byte_range()). See their documentation
+/// for more.
+#[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+#[cfg(feature = "lines")]
+#[derive(Clone)]
+pub struct LinesInByteRange<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet<Lines = Maintained> = AllMetrics,
+> {
+    lines: Lines<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+
+    /// The index of the line that will be yielded by the next call to
+    /// `next()`.
+    next_line: usize,
+
+    /// If the byte range ends between the `\r` and the `\n` of a CRLF, the
+    /// last line without the `\r`, which is yielded in place of the last line
+    /// of `lines`. It's taken once the last line has been yielded.
+    trimmed_last: Option<RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>,
+}
+
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > LinesInByteRange<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    /// Creates a new iterator over the lines of `slice`, the first of which
+    /// has index `first_line`.
+    ///
+    /// If `slice` ends with the `\r` of a CRLF whose `\n` was cut off,
+    /// `trimmed_last` is its last line without the `\r`.
+    #[inline]
+    pub(super) fn new(
+        slice: RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+        first_line: usize,
+        trimmed_last: Option<RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>,
+    ) -> Self {
+        Self { lines: slice.lines(), next_line: first_line, trimmed_last }
+    }
+
+    /// Replaces the last line with the one without the `\r` of a cut off
+    /// CRLF, if there is one.
+    #[inline]
+    fn trim_last(
+        &mut self,
+        line: RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        self.trimmed_last.take().unwrap_or(line)
+    }
+}
+
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > Iterator for LinesInByteRange<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type Item = (usize, RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut line = self.lines.next()?;
+        if self.lines.len() == 0 {
+            line = self.trim_last(line);
+        }
+        self.next_line += 1;
+        Some((self.next_line - 1, line))
+    }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.lines.size_hint()
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
+}
+
+#[cfg(feature = "lines")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > DoubleEndedIterator
+    for LinesInByteRange<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn next_back(&mut self) -> Option<Self::Item> {
+        let line = self.lines.next_back()?;
+        let line = self.trim_last(line);
+        Some((self.next_line + self.lines.len(), line))
+    }
+}
+
+#[cfg(feature = "lines")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > ExactSizeIterator
+    for LinesInByteRange<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
+
+#[cfg(feature = "lines")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > core::iter::FusedIterator
+    for LinesInByteRange<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
+
+/// An iterator over the lines of `Rope`s and `RopeSlice`s together with the
+/// byte offsets of their starts, not including the line terminators.
+///
+/// This struct is created by the `lines_with_offsets` method on
+/// [`Rope`](Rope::lines_with_offsets()) and
+/// [`RopeSlice`](RopeSlice::lines_with_offsets()). See their documentation
+/// for more.
+#[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+#[cfg(feature = "lines")]
+#[derive(Clone)]
+pub struct LinesWithOffsets<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet<Lines = Maintained> = AllMetrics,
+> {
+    units: Units<
+        'a,
+        { Rope::arity() },
+        RopeChunk<W, B, MAX_BYTES, MIN_FILL, S>,
+        LinesMetric,
+        C,
+    >,
+
+    /// The number of lines that have been yielded so far.
+    lines_yielded: usize,
+
+    /// The total number of lines this iterator will yield.
+    lines_total: usize,
+
+    /// The byte offset of the start of the next line yielded by `next()`.
+    front_offset: usize,
+
+    /// The byte offset of the end of the next line yielded by `next_back()`,
+    /// including its line terminator.
+    back_offset: usize,
+}
+
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > From<&'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for LinesWithOffsets<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from(rope: &'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        Self {
+            units: rope.tree.units::<LinesMetric>(),
+            lines_yielded: 0,
+            lines_total: rope.line_len(),
+            front_offset: 0,
+            back_offset: rope.byte_len(),
         }
+    }
+}
 
-        Some(slice)
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > From<&RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for LinesWithOffsets<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from(slice: &RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        Self {
+            units: slice.tree_slice.units::<LinesMetric>(),
+            lines_yielded: 0,
+            lines_total: slice.line_len(),
+            front_offset: 0,
+            back_offset: slice.byte_len(),
+        }
+    }
+}
+
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > Iterator for LinesWithOffsets<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type Item = (usize, RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let (tree_slice, ByteMetric(advance)) = self.units.next()?;
+        self.lines_yielded += 1;
+        let start = self.front_offset;
+        self.front_offset += advance;
+        Some((start, line_of_unit(tree_slice)))
     }
 
     #[inline]
@@ -506,35 +1770,192 @@ impl<'a> Iterator for Lines<'a> {
         let exact = self.len();
         (exact, Some(exact))
     }
+
+    #[inline]
+    fn count(self) -> usize {
+        self.len()
+    }
+
+    #[inline]
+    fn last(mut self) -> Option<Self::Item> {
+        self.next_back()
+    }
 }
 
-impl DoubleEndedIterator for Lines<'_> {
+#[cfg(feature = "lines")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > DoubleEndedIterator
+    for LinesWithOffsets<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn next_back(&mut self) -> Option<Self::Item> {
         let (tree_slice, ByteMetric(advance)) = self.units.next_back()?;
         self.lines_yielded += 1;
+        self.back_offset -= advance;
+        Some((self.back_offset, line_of_unit(tree_slice)))
+    }
+}
 
-        let mut slice = RopeSlice { tree_slice, has_trailing_newline: false };
+#[cfg(feature = "lines")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > ExactSizeIterator
+    for LinesWithOffsets<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn len(&self) -> usize {
+        self.lines_total - self.lines_yielded
+    }
+}
 
-        // Same as above.
-        if slice.tree_slice.end_slice().last_chunk().ends_with('\r')
-            && advance - slice.byte_len() == 1
-        {
-            slice.truncate_last_char();
-        }
+#[cfg(feature = "lines")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > core::iter::FusedIterator
+    for LinesWithOffsets<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
+
+/// An iterator over the paragraphs of `Rope`s and `RopeSlice`s.
+///
+/// A paragraph is a run of consecutive lines which are not blank, i.e. which
+/// contain something other than whitespace. The yielded slices start at the
+/// beginning of the first line of a paragraph and end at the end of its last
+/// line, not including its line terminator, while the blank lines between
+/// paragraphs are skipped.
+///
+/// This struct is created by the `paragraphs` method on
+/// [`Rope`](Rope::paragraphs()) and [`RopeSlice`](RopeSlice::paragraphs()).
+/// See their documentation for more.
+#[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+#[cfg(feature = "lines")]
+#[derive(Clone)]
+pub struct Paragraphs<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet<Lines = Maintained> = AllMetrics,
+> {
+    slice: RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    lines: Lines<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    raw_lines: RawLines<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+
+    /// The byte offset in `slice` of the next line yielded by `lines` and
+    /// `raw_lines`.
+    byte_offset: usize,
+}
 
-        Some(slice)
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > From<&'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for Paragraphs<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from(rope: &'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        Self::from(&rope.byte_slice(..))
     }
 }
 
-impl ExactSizeIterator for Lines<'_> {
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > From<&RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for Paragraphs<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn len(&self) -> usize {
-        self.lines_total - self.lines_yielded
+    fn from(slice: &RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        Self {
+            slice: *slice,
+            lines: slice.lines(),
+            raw_lines: slice.raw_lines(),
+            byte_offset: 0,
+        }
+    }
+}
+
+#[cfg(feature = "lines")]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > Iterator for Paragraphs<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type Item = RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let mut start = None;
+        let mut end = 0;
+
+        for (line, raw_line) in
+            self.lines.by_ref().zip(self.raw_lines.by_ref())
+        {
+            let line_start = self.byte_offset;
+
+            self.byte_offset += raw_line.byte_len();
+
+            if line.chars().all(char::is_whitespace) {
+                if start.is_some() {
+                    break;
+                }
+            } else {
+                start.get_or_insert(line_start);
+                end = line_start + line.byte_len();
+            }
+        }
+
+        start.map(|start| self.slice.byte_slice(start..end))
     }
 }
 
-impl core::iter::FusedIterator for Lines<'_> {}
+#[cfg(feature = "lines")]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > core::iter::FusedIterator
+    for Paragraphs<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
 
 #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
 #[cfg(feature = "graphemes")]
@@ -555,12 +1976,20 @@ mod graphemes {
     /// [`Rope`](Rope::graphemes()) and [`RopeSlice`](RopeSlice::graphemes()).
     /// See their documentation for more.
     #[derive(Clone)]
-    pub struct Graphemes<'a> {
-        chunks: Chunks<'a>,
+    pub struct Graphemes<
+        'a,
+        C: RefCounter = AtomicCounter,
+        W: CharWeight = ZeroWeight,
+        B: LineBreaks = LfBreaks,
+        const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+        const MIN_FILL: usize = CHUNK_MIN_FILL,
+        S: MetricSet = AllMetrics,
+    > {
+        chunks: Chunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
 
         /// The slice we're iterating over, used to provide precontext to the
         /// `GraphemeCursor`s.
-        slice: RopeSlice<'a>,
+        slice: RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
 
         /// The cursor used when calling [`Graphemes::next()`].
         forward_cursor: GraphemeCursor,
@@ -587,9 +2016,19 @@ mod graphemes {
         backward_offset: usize,
     }
 
-    impl<'a> From<&'a Rope> for Graphemes<'a> {
+    impl<
+            'a,
+            C: RefCounter,
+            W: CharWeight,
+            B: LineBreaks,
+            const MAX_BYTES: usize,
+            const MIN_FILL: usize,
+            S: MetricSet,
+        > From<&'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+        for Graphemes<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    {
         #[inline]
-        fn from(rope: &'a Rope) -> Self {
+        fn from(rope: &'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
             let len = rope.byte_len();
 
             Self {
@@ -605,9 +2044,21 @@ mod graphemes {
         }
     }
 
-    impl<'a> From<&RopeSlice<'a>> for Graphemes<'a> {
+    impl<
+            'a,
+            C: RefCounter,
+            W: CharWeight,
+            B: LineBreaks,
+            const MAX_BYTES: usize,
+            const MIN_FILL: usize,
+            S: MetricSet,
+        > From<&RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>
+        for Graphemes<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    {
         #[inline]
-        fn from(slice: &RopeSlice<'a>) -> Self {
+        fn from(
+            slice: &RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+        ) -> Self {
             let len = slice.byte_len();
 
             Self {
@@ -623,7 +2074,16 @@ mod graphemes {
         }
     }
 
-    impl<'a> Iterator for Graphemes<'a> {
+    impl<
+            'a,
+            C: RefCounter,
+            W: CharWeight,
+            B: LineBreaks,
+            const MAX_BYTES: usize,
+            const MIN_FILL: usize,
+            S: MetricSet,
+        > Iterator for Graphemes<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    {
         type Item = Cow<'a, str>;
 
         #[inline]
@@ -756,7 +2216,16 @@ mod graphemes {
         }
     }
 
-    impl DoubleEndedIterator for Graphemes<'_> {
+    impl<
+            C: RefCounter,
+            W: CharWeight,
+            B: LineBreaks,
+            const MAX_BYTES: usize,
+            const MIN_FILL: usize,
+            S: MetricSet,
+        > DoubleEndedIterator
+        for Graphemes<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    {
         #[inline]
         fn next_back(&mut self) -> Option<Self::Item> {
             debug_assert_eq!(
@@ -907,5 +2376,64 @@ mod graphemes {
         }
     }
 
-    impl core::iter::FusedIterator for Graphemes<'_> {}
+    impl<
+            C: RefCounter,
+            W: CharWeight,
+            B: LineBreaks,
+            const MAX_BYTES: usize,
+            const MIN_FILL: usize,
+            S: MetricSet,
+        > core::iter::FusedIterator
+        for Graphemes<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    {
+    }
+}
+
+#[cfg(all(test, feature = "lines"))]
+mod tests {
+    use super::*;
+
+    /// Tests that counting the units left in a `Units` gives the same result
+    /// as iterating over them, also after some of them were yielded from
+    /// either end.
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn units_count() {
+        let s = "foo\nbar\r\n\nbaz\n".repeat(300) + "qux";
+        let r = Rope::from(s.as_str());
+
+        #[track_caller]
+        fn check<I: Iterator + DoubleEndedIterator + Clone>(units: I) {
+            for (forward, backward) in [(0, 0), (0, 1), (1, 0), (3, 2)] {
+                let mut units = units.clone();
+
+                for _ in 0..forward {
+                    units.next();
+                }
+
+                for _ in 0..backward {
+                    units.next_back();
+                }
+
+                let expected = units.clone().fold(0, |count, _| count + 1);
+                assert_eq!(units.count(), expected);
+            }
+        }
+
+        check(r.tree.units::<RawLineMetric>());
+
+        for (start, end) in
+            [(0, 0), (0, 3), (2, 9), (4, 14), (1, s.len() - 1), (0, s.len())]
+        {
+            for offset in [0, s.len() / 2 - 15, s.len() - 15] {
+                let start = (offset + start).min(s.len());
+                let end = (offset + end).min(s.len());
+                check(
+                    r.byte_slice(start..end)
+                        .tree_slice
+                        .units::<RawLineMetric>(),
+                );
+            }
+        }
+    }
 }
@@ -0,0 +1,197 @@
+//! The [`JoinItem`] trait used by [`Rope::join()`](super::Rope::join()).
+
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use super::{RopeBuilder, RopeSlice, RopeWith};
+use crate::tree::RefCounter;
+
+/// A text that can be joined into a `Rope` by [`Rope::join()`](super::Rope::join()).
+///
+/// This trait is implemented for `&str`, `String`, `&String`, `Rope`,
+/// `&Rope` and `RopeSlice`, and can't be implemented outside of this crate.
+pub trait JoinItem: sealed::Sealed {
+    #[doc(hidden)]
+    fn append_to<
+        D: RefCounter,
+        V: CharWeight,
+        K: LineBreaks,
+        const N: usize,
+        const M: usize,
+        T: MetricSet,
+    >(
+        self,
+        builder: &mut RopeBuilder<D, V, K, N, M, T>,
+    );
+}
+
+mod sealed {
+    use crate::rope::metrics::{CharWeight, MetricSet};
+    use crate::rope::LineBreaks;
+    use crate::tree::RefCounter;
+
+    pub trait Sealed {}
+
+    impl Sealed for &str {}
+    impl Sealed for String {}
+    impl Sealed for &String {}
+    impl<
+            C: RefCounter,
+            W: CharWeight,
+            B: LineBreaks,
+            const MAX_BYTES: usize,
+            const MIN_FILL: usize,
+            S: MetricSet,
+        > Sealed for super::RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+    {
+    }
+    impl<
+            C: RefCounter,
+            W: CharWeight,
+            B: LineBreaks,
+            const MAX_BYTES: usize,
+            const MIN_FILL: usize,
+            S: MetricSet,
+        > Sealed for &super::RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+    {
+    }
+    impl<
+            C: RefCounter,
+            W: CharWeight,
+            B: LineBreaks,
+            const MAX_BYTES: usize,
+            const MIN_FILL: usize,
+            S: MetricSet,
+        > Sealed for super::RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    {
+    }
+}
+
+impl JoinItem for &str {
+    #[inline]
+    fn append_to<
+        D: RefCounter,
+        V: CharWeight,
+        K: LineBreaks,
+        const N: usize,
+        const M: usize,
+        T: MetricSet,
+    >(
+        self,
+        builder: &mut RopeBuilder<D, V, K, N, M, T>,
+    ) {
+        builder.append(self);
+    }
+}
+
+impl JoinItem for String {
+    #[inline]
+    fn append_to<
+        D: RefCounter,
+        V: CharWeight,
+        K: LineBreaks,
+        const N: usize,
+        const M: usize,
+        T: MetricSet,
+    >(
+        self,
+        builder: &mut RopeBuilder<D, V, K, N, M, T>,
+    ) {
+        builder.append(self);
+    }
+}
+
+impl JoinItem for &String {
+    #[inline]
+    fn append_to<
+        D: RefCounter,
+        V: CharWeight,
+        K: LineBreaks,
+        const N: usize,
+        const M: usize,
+        T: MetricSet,
+    >(
+        self,
+        builder: &mut RopeBuilder<D, V, K, N, M, T>,
+    ) {
+        builder.append(self);
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > JoinItem for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn append_to<
+        D: RefCounter,
+        V: CharWeight,
+        K: LineBreaks,
+        const N: usize,
+        const M: usize,
+        T: MetricSet,
+    >(
+        self,
+        builder: &mut RopeBuilder<D, V, K, N, M, T>,
+    ) {
+        (&self).append_to(builder);
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > JoinItem for &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn append_to<
+        D: RefCounter,
+        V: CharWeight,
+        K: LineBreaks,
+        const N: usize,
+        const M: usize,
+        T: MetricSet,
+    >(
+        self,
+        builder: &mut RopeBuilder<D, V, K, N, M, T>,
+    ) {
+        for chunk in self.chunks() {
+            builder.append(chunk);
+        }
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > JoinItem for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn append_to<
+        D: RefCounter,
+        V: CharWeight,
+        K: LineBreaks,
+        const N: usize,
+        const M: usize,
+        T: MetricSet,
+    >(
+        self,
+        builder: &mut RopeBuilder<D, V, K, N, M, T>,
+    ) {
+        for chunk in self.chunks() {
+            builder.append(chunk);
+        }
+    }
+}
@@ -0,0 +1,301 @@
+//! This module exports the [`LeafBytes`] struct, i.e. the buffer backing a
+//! [`RawGapBuffer`](super::gap_buffer::RawGapBuffer).
+//!
+//! The buffer of a leaf read from a shared snapshot by
+//! [`Rope::from_shared_snapshot()`](crate::Rope::from_shared_snapshot())
+//! borrows its bytes from the snapshot instead of owning them, and is copied
+//! into a buffer of its own the first time it's mutably borrowed.
+//!
+//! The buffer of a small leaf, like the only one of a `Rope` created from a
+//! short string, only holds its valid bytes in an allocation of their exact
+//! size, and is grown into a full buffer the first time it's mutably
+//! borrowed.
+//!
+//! With the `compression` feature the buffer of a leaf can be compressed with
+//! LZ4 by [`Rope::compress()`](crate::Rope::compress()). A compressed buffer
+//! is decompressed lazily the first time it's read, and the decompressed copy
+//! is kept around until the next compression pass. Mutably borrowing the
+//! buffer turns it back into a regular, uncompressed one.
+
+use core::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+#[cfg(feature = "compression")]
+use once_cell::sync::OnceCell;
+
+/// The bytes of a snapshot shared by the leaves read from it.
+///
+/// The bound on `RefUnwindSafe` keeps ropes unwind safe, like they were
+/// before they could borrow their text from a snapshot.
+pub(crate) type SharedBytes =
+    Arc<dyn AsRef<[u8]> + Send + Sync + std::panic::RefUnwindSafe>;
+
+/// The bytes of a leaf, only the first `len_left` and the last `len_right` of
+/// which are valid.
+///
+/// It dereferences to `MAX_BYTES` bytes, except for small buffers which only
+/// hold their left chunk.
+pub(crate) struct LeafBytes<const MAX_BYTES: usize>(Repr<MAX_BYTES>);
+
+enum Repr<const MAX_BYTES: usize> {
+    Plain(Box<[u8; MAX_BYTES]>),
+
+    /// The bytes of a buffer whose right chunk is empty, in an allocation of
+    /// exactly their size.
+    Small(Box<[u8]>),
+
+    /// The `MAX_BYTES` bytes of a snapshot starting at `start`.
+    Shared {
+        snapshot: SharedBytes,
+        start: usize,
+    },
+
+    #[cfg(feature = "compression")]
+    Compressed {
+        /// The valid bytes of the buffer, compressed with LZ4.
+        compressed: Box<[u8]>,
+
+        /// The number of bytes before the gap.
+        len_left: u16,
+
+        /// The number of bytes after the gap.
+        len_right: u16,
+
+        /// The buffer, decompressed the first time it's read.
+        decompressed: OnceCell<Box<[u8; MAX_BYTES]>>,
+    },
+}
+
+impl<const MAX_BYTES: usize> LeafBytes<MAX_BYTES> {
+    /// Fails to compile if the buffer couldn't hold every `char`, or if the
+    /// lengths of its chunks wouldn't fit in a `u16`.
+    const VALID_SIZE: () = assert!(
+        MAX_BYTES >= 4 && MAX_BYTES <= u16::MAX as usize,
+        "the maximum number of bytes in a chunk must be between 4 and 65535"
+    );
+
+    /// Returns the number of bytes allocated on the heap by the buffer.
+    ///
+    /// Buffers borrowed from a snapshot don't own any.
+    #[inline]
+    pub(crate) fn heap_size(&self) -> usize {
+        match &self.0 {
+            Repr::Plain(_) => MAX_BYTES,
+
+            Repr::Small(bytes) => bytes.len(),
+
+            Repr::Shared { .. } => 0,
+
+            #[cfg(feature = "compression")]
+            Repr::Compressed { compressed, decompressed, .. } => {
+                compressed.len() + decompressed.get().map_or(0, |_| MAX_BYTES)
+            },
+        }
+    }
+
+    /// Returns a buffer borrowing the `MAX_BYTES` bytes of the snapshot
+    /// starting at `start`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the snapshot is shorter than `start + MAX_BYTES` bytes.
+    #[inline]
+    pub(crate) fn shared(snapshot: SharedBytes, start: usize) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_SIZE;
+
+        assert!((*snapshot).as_ref().len() >= start + MAX_BYTES);
+
+        Self(Repr::Shared { snapshot, start })
+    }
+
+    /// Returns a small buffer holding a copy of `bytes`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bytes` is longer than `MAX_BYTES`.
+    #[inline]
+    pub(crate) fn small(bytes: &[u8]) -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_SIZE;
+
+        assert!(bytes.len() <= MAX_BYTES);
+
+        Self(Repr::Small(bytes.into()))
+    }
+
+    /// Returns a zeroed buffer.
+    #[inline]
+    pub(crate) fn zeroed() -> Self {
+        #[allow(clippy::let_unit_value)]
+        let () = Self::VALID_SIZE;
+
+        Self(Repr::Plain(Box::new([0u8; MAX_BYTES])))
+    }
+}
+
+impl<const MAX_BYTES: usize> Clone for LeafBytes<MAX_BYTES> {
+    #[inline]
+    fn clone(&self) -> Self {
+        match &self.0 {
+            Repr::Plain(bytes) => Self(Repr::Plain(bytes.clone())),
+
+            Repr::Small(bytes) => Self(Repr::Small(bytes.clone())),
+
+            Repr::Shared { snapshot, start } => Self(Repr::Shared {
+                snapshot: Arc::clone(snapshot),
+                start: *start,
+            }),
+
+            #[cfg(feature = "compression")]
+            Repr::Compressed { compressed, len_left, len_right, .. } => {
+                Self(Repr::Compressed {
+                    compressed: compressed.clone(),
+                    len_left: *len_left,
+                    len_right: *len_right,
+                    decompressed: OnceCell::new(),
+                })
+            },
+        }
+    }
+}
+
+impl<const MAX_BYTES: usize> Deref for LeafBytes<MAX_BYTES> {
+    type Target = [u8];
+
+    #[inline]
+    fn deref(&self) -> &Self::Target {
+        match &self.0 {
+            Repr::Plain(bytes) => &**bytes,
+
+            Repr::Small(bytes) => bytes,
+
+            Repr::Shared { snapshot, start } => {
+                shared_bytes::<MAX_BYTES>(snapshot, *start)
+            },
+
+            #[cfg(feature = "compression")]
+            Repr::Compressed {
+                compressed,
+                len_left,
+                len_right,
+                decompressed,
+            } => &**decompressed
+                .get_or_init(|| decompress(compressed, *len_left, *len_right)),
+        }
+    }
+}
+
+impl<const MAX_BYTES: usize> DerefMut for LeafBytes<MAX_BYTES> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        match &mut self.0 {
+            Repr::Plain(_) => {},
+
+            Repr::Small(small) => {
+                let mut bytes = Box::new([0u8; MAX_BYTES]);
+                bytes[..small.len()].copy_from_slice(small);
+                self.0 = Repr::Plain(bytes);
+            },
+
+            Repr::Shared { snapshot, start } => {
+                let bytes = Box::new(*shared_bytes(snapshot, *start));
+                self.0 = Repr::Plain(bytes);
+            },
+
+            #[cfg(feature = "compression")]
+            Repr::Compressed {
+                compressed,
+                len_left,
+                len_right,
+                decompressed,
+            } => {
+                let bytes = decompressed.take().unwrap_or_else(|| {
+                    decompress(compressed, *len_left, *len_right)
+                });
+
+                self.0 = Repr::Plain(bytes);
+            },
+        }
+
+        match &mut self.0 {
+            Repr::Plain(bytes) => &mut **bytes,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[inline]
+fn shared_bytes<const MAX_BYTES: usize>(
+    snapshot: &SharedBytes,
+    start: usize,
+) -> &[u8; MAX_BYTES] {
+    (**snapshot).as_ref()[start..start + MAX_BYTES]
+        .try_into()
+        .expect("the slice is MAX_BYTES long")
+}
+
+#[cfg(feature = "compression")]
+impl<const MAX_BYTES: usize> LeafBytes<MAX_BYTES> {
+    /// Compresses the buffer, whose first `len_left` and last `len_right`
+    /// bytes are valid. If it was already compressed its decompressed copy is
+    /// dropped instead.
+    ///
+    /// Buffers that wouldn't get any smaller are left uncompressed, and so
+    /// are small ones and the ones borrowed from a snapshot, which don't own
+    /// any memory they don't use.
+    #[inline]
+    pub(crate) fn compress(&mut self, len_left: u16, len_right: u16) {
+        let bytes = match &mut self.0 {
+            Repr::Plain(bytes) => bytes,
+
+            Repr::Small(_) | Repr::Shared { .. } => return,
+
+            Repr::Compressed { decompressed, .. } => {
+                decompressed.take();
+                return;
+            },
+        };
+
+        let mut valid =
+            Vec::with_capacity(len_left as usize + len_right as usize);
+
+        valid.extend_from_slice(&bytes[..len_left as usize]);
+        valid.extend_from_slice(&bytes[MAX_BYTES - len_right as usize..]);
+
+        let compressed = lz4_flex::block::compress(&valid);
+
+        if compressed.len() < MAX_BYTES {
+            self.0 = Repr::Compressed {
+                compressed: compressed.into_boxed_slice(),
+                len_left,
+                len_right,
+                decompressed: OnceCell::new(),
+            };
+        }
+    }
+}
+
+#[cfg(feature = "compression")]
+#[inline]
+fn decompress<const MAX_BYTES: usize>(
+    compressed: &[u8],
+    len_left: u16,
+    len_right: u16,
+) -> Box<[u8; MAX_BYTES]> {
+    let (len_left, len_right) = (len_left as usize, len_right as usize);
+
+    let mut bytes = Box::new([0u8; MAX_BYTES]);
+
+    let len = lz4_flex::block::decompress_into(
+        compressed,
+        &mut bytes[..len_left + len_right],
+    )
+    .expect("the buffer was compressed by us");
+
+    debug_assert_eq!(len, len_left + len_right);
+
+    bytes.copy_within(len_left..len_left + len_right, MAX_BYTES - len_right);
+
+    bytes
+}
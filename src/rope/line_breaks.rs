@@ -0,0 +1,344 @@
+//! This module contains the [`LineBreaks`] trait, which decides where the
+//! lines of a [`RopeWith`](crate::RopeWith) end, and its two implementors.
+
+/// The set of characters that break the lines of a
+/// [`RopeWith`](crate::RopeWith).
+///
+/// The set is part of the rope's type, so ropes breaking their lines in
+/// different ways can be used side by side. It's used by every line-oriented
+/// API, from the line breaks stored in the summaries of the B-tree to
+/// [`line()`](crate::RopeWith::line()) and the [`Lines`](crate::iter::Lines)
+/// iterator. A `\r\n` pair always counts as a single line break.
+///
+/// This trait is implemented by [`LfBreaks`], the default of
+/// [`Rope`](crate::Rope), and [`UnicodeBreaks`], and can't be implemented
+/// outside of this crate.
+///
+/// # Examples
+///
+/// ```
+/// # use crop::{tree::AtomicCounter, RopeWith, UnicodeBreaks, ZeroWeight};
+/// #
+/// type UnicodeRope = RopeWith<AtomicCounter, ZeroWeight, UnicodeBreaks>;
+///
+/// let r = UnicodeRope::from("foo\u{2028}bar\u{000C}baz");
+/// # #[cfg(feature = "lines")]
+/// assert_eq!(r.line_len(), 3);
+/// # #[cfg(feature = "lines")]
+/// assert_eq!(r.line(1), "bar");
+/// ```
+pub trait LineBreaks:
+    Copy
+    + Default
+    + core::fmt::Debug
+    + PartialEq
+    + Send
+    + Sync
+    + 'static
+    + sealed::Sealed
+{
+    #[doc(hidden)]
+    fn is_line_break(ch: char) -> bool;
+
+    #[doc(hidden)]
+    fn ends_with_line_break(s: &str) -> bool;
+
+    #[doc(hidden)]
+    fn count_breaks(s: &str) -> usize;
+
+    /// Counts the line breaks of a string whose line feeds were already
+    /// counted.
+    #[doc(hidden)]
+    fn count_breaks_with_line_feeds(s: &str, line_feeds: usize) -> usize;
+
+    #[doc(hidden)]
+    fn byte_of_line(s: &str, line_offset: usize) -> usize;
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for super::LfBreaks {}
+    impl Sealed for super::UnicodeBreaks {}
+}
+
+/// The default [`LineBreaks`] of a [`Rope`](crate::Rope), which only breaks
+/// lines on `\n` (and `\r\n`).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct LfBreaks;
+
+impl LineBreaks for LfBreaks {
+    #[inline]
+    fn is_line_break(ch: char) -> bool {
+        ch == '\n'
+    }
+
+    #[inline]
+    fn ends_with_line_break(s: &str) -> bool {
+        s.ends_with('\n')
+    }
+
+    #[inline]
+    fn count_breaks(s: &str) -> usize {
+        #[cfg(not(miri))]
+        {
+            str_indices::lines_lf::count_breaks(s)
+        }
+
+        #[cfg(miri)]
+        {
+            s.bytes().filter(|&byte| byte == b'\n').count()
+        }
+    }
+
+    #[inline]
+    fn count_breaks_with_line_feeds(_: &str, line_feeds: usize) -> usize {
+        line_feeds
+    }
+
+    #[inline]
+    fn byte_of_line(s: &str, line_offset: usize) -> usize {
+        #[cfg(not(miri))]
+        {
+            str_indices::lines_lf::to_byte_idx(s, line_offset)
+        }
+
+        #[cfg(miri)]
+        {
+            byte_of_line_by_chars::<Self>(s, line_offset)
+        }
+    }
+}
+
+/// A [`LineBreaks`] which, besides `\n` (and `\r\n`), also breaks lines on
+/// the vertical tab (`\u{000B}`), the form feed (`\u{000C}`), the next line
+/// (`\u{0085}`), the line separator (`\u{2028}`) and the paragraph separator
+/// (`\u{2029}`), like the mandatory breaks of
+/// [UAX #14](https://www.unicode.org/reports/tr14/).
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct UnicodeBreaks;
+
+impl LineBreaks for UnicodeBreaks {
+    #[inline]
+    fn is_line_break(ch: char) -> bool {
+        matches!(
+            ch,
+            '\n' | '\u{000B}'
+                | '\u{000C}'
+                | '\u{0085}'
+                | '\u{2028}'
+                | '\u{2029}'
+        )
+    }
+
+    #[inline]
+    fn ends_with_line_break(s: &str) -> bool {
+        s.chars().next_back().map_or(false, Self::is_line_break)
+    }
+
+    #[inline]
+    fn count_breaks(s: &str) -> usize {
+        #[cfg(not(miri))]
+        {
+            unicode_breaks::count_breaks(s)
+        }
+
+        #[cfg(miri)]
+        {
+            s.chars().filter(|&ch| Self::is_line_break(ch)).count()
+        }
+    }
+
+    #[inline]
+    fn count_breaks_with_line_feeds(s: &str, line_feeds: usize) -> usize {
+        #[cfg(not(miri))]
+        {
+            line_feeds + unicode_breaks::count_other_breaks(s)
+        }
+
+        #[cfg(miri)]
+        {
+            line_feeds
+                + s.chars()
+                    .filter(|&ch| ch != '\n' && Self::is_line_break(ch))
+                    .count()
+        }
+    }
+
+    #[inline]
+    fn byte_of_line(s: &str, line_offset: usize) -> usize {
+        #[cfg(not(miri))]
+        {
+            unicode_breaks::to_byte_idx(s, line_offset)
+        }
+
+        #[cfg(miri)]
+        {
+            byte_of_line_by_chars::<Self>(s, line_offset)
+        }
+    }
+}
+
+/// Returns the byte offset of the start of the given line by looking at every
+/// `char` of the string.
+#[cfg(miri)]
+#[inline]
+fn byte_of_line_by_chars<B: LineBreaks>(s: &str, line_offset: usize) -> usize {
+    if line_offset == 0 {
+        return 0;
+    }
+
+    let mut seen = 0;
+
+    for (idx, ch) in s.char_indices() {
+        if B::is_line_break(ch) {
+            seen += 1;
+            if seen == line_offset {
+                return idx + ch.len_utf8();
+            }
+        }
+    }
+
+    s.len()
+}
+
+/// Counting and conversion functions for the line breaks of
+/// [`UnicodeBreaks`], with the same signatures as the ones in
+/// [`str_indices::lines_lf`].
+///
+/// Every line break in the set is a single code point, so we only need to
+/// look for the last byte of its UTF-8 encoding:
+///
+/// - LF, VT and FF are encoded as `0x0A`, `0x0B` and `0x0C`, respectively;
+///
+/// - NEL (U+0085) is encoded as `0xC2 0x85`;
+///
+/// - LS (U+2028) and PS (U+2029) are encoded as `0xE2 0x80 0xA8` and
+///   `0xE2 0x80 0xA9`, respectively.
+///
+/// The text is scanned one machine word at a time, and only the words that
+/// could contain one of those bytes are inspected byte by byte. When
+/// counting, LFs are counted separately, either by [`str_indices::lines_lf`]
+/// or, when summarizing a chunk, together with its `char`s in a single pass
+/// over its bytes.
+#[cfg(not(miri))]
+mod unicode_breaks {
+    const WORD_BYTES: usize = core::mem::size_of::<usize>();
+
+    /// A word with every byte set to `0x01`.
+    const LO: usize = usize::MAX / 0xFF;
+
+    /// A word with every byte set to `0x80`.
+    const HI: usize = LO * 0x80;
+
+    /// Returns `true` if any of the bytes of the word is equal to `byte`.
+    #[inline(always)]
+    fn has_byte(word: usize, byte: u8) -> bool {
+        let xored = word ^ (LO * byte as usize);
+        xored.wrapping_sub(LO) & !xored & HI != 0
+    }
+
+    /// Returns `true` if the word could contain the last byte of a line
+    /// break other than LF. This can have false positives.
+    #[inline(always)]
+    fn may_end_other_break(word: usize) -> bool {
+        // The last bytes of NEL, LS and PS all have their high bit set.
+        word & HI != 0 || has_byte(word, 0x0B) || has_byte(word, 0x0C)
+    }
+
+    /// Returns `true` if the byte at `idx` is the last byte of a line break
+    /// other than LF.
+    #[inline(always)]
+    fn ends_other_break(bytes: &[u8], idx: usize) -> bool {
+        match bytes[idx] {
+            0x0B | 0x0C => true,
+            0x85 => idx >= 1 && bytes[idx - 1] == 0xC2,
+            0xA8 | 0xA9 => {
+                idx >= 2 && bytes[idx - 1] == 0x80 && bytes[idx - 2] == 0xE2
+            },
+            _ => false,
+        }
+    }
+
+    /// Returns `true` if the byte at `idx` is the last byte of a line break.
+    #[inline(always)]
+    fn ends_line_break(bytes: &[u8], idx: usize) -> bool {
+        bytes[idx] == 0x0A || ends_other_break(bytes, idx)
+    }
+
+    #[inline(always)]
+    fn word_at(bytes: &[u8], idx: usize) -> usize {
+        let mut word = [0; WORD_BYTES];
+        word.copy_from_slice(&bytes[idx..idx + WORD_BYTES]);
+        usize::from_ne_bytes(word)
+    }
+
+    #[inline]
+    pub fn count_breaks(s: &str) -> usize {
+        str_indices::lines_lf::count_breaks(s) + count_other_breaks(s)
+    }
+
+    /// Counts the line breaks other than LF.
+    #[inline]
+    pub fn count_other_breaks(s: &str) -> usize {
+        let bytes = s.as_bytes();
+
+        let mut count = 0;
+
+        let mut idx = 0;
+
+        while idx + WORD_BYTES <= bytes.len() {
+            if may_end_other_break(word_at(bytes, idx)) {
+                count += (idx..idx + WORD_BYTES)
+                    .filter(|&idx| ends_other_break(bytes, idx))
+                    .count();
+            }
+            idx += WORD_BYTES;
+        }
+
+        count
+            + (idx..bytes.len())
+                .filter(|&idx| ends_other_break(bytes, idx))
+                .count()
+    }
+
+    #[inline]
+    pub fn to_byte_idx(s: &str, line_idx: usize) -> usize {
+        if line_idx == 0 {
+            return 0;
+        }
+
+        let bytes = s.as_bytes();
+
+        let mut seen = 0;
+
+        let mut idx = 0;
+
+        while idx < bytes.len() {
+            let word_end = idx + WORD_BYTES;
+
+            // Skip the whole word if it can't contain a line break.
+            if word_end <= bytes.len() {
+                let word = word_at(bytes, idx);
+
+                if !has_byte(word, 0x0A) && !may_end_other_break(word) {
+                    idx = word_end;
+                    continue;
+                }
+            }
+
+            for idx in idx..word_end.min(bytes.len()) {
+                if ends_line_break(bytes, idx) {
+                    seen += 1;
+                    if seen == line_idx {
+                        return idx + 1;
+                    }
+                }
+            }
+
+            idx = word_end;
+        }
+
+        s.len()
+    }
+}
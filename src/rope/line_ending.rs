@@ -0,0 +1,104 @@
+//! This module contains the [`LineEnding`] enum and the logic used to write
+//! `Rope`s and `RopeSlice`s with a given line ending.
+
+use std::io::{self, Write};
+
+use super::iterators::Chunks;
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use crate::tree::RefCounter;
+
+/// The sequence of bytes used to terminate a line.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub enum LineEnding {
+    /// A line feed (`\n`), used on Unix-like systems.
+    #[default]
+    Lf,
+
+    /// A carriage return followed by a line feed (`\r\n`), used on Windows.
+    CrLf,
+
+    /// A lone carriage return (`\r`), used on classic Mac OS.
+    Cr,
+}
+
+impl LineEnding {
+    /// Returns the line ending as a string slice.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::LineEnding;
+    /// #
+    /// assert_eq!(LineEnding::Lf.as_str(), "\n");
+    /// assert_eq!(LineEnding::CrLf.as_str(), "\r\n");
+    /// assert_eq!(LineEnding::Cr.as_str(), "\r");
+    /// ```
+    #[inline]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+            LineEnding::Cr => "\r",
+        }
+    }
+}
+
+/// Writes the chunks to `writer`, replacing every `\n` and `\r\n` with the
+/// given line ending. Lone `\r`s are written as they are.
+///
+/// A `\r\n` can be split across two chunks, so a `\r` at the end of a chunk
+/// is held back until we know whether the next chunk starts with a `\n`.
+#[inline]
+pub(super) fn write_with_line_ending<
+    T: Write,
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    mut writer: T,
+    line_ending: LineEnding,
+) -> io::Result<()> {
+    let line_ending = line_ending.as_str().as_bytes();
+
+    let mut pending_cr = false;
+
+    for chunk in chunks {
+        let mut bytes = chunk.as_bytes();
+
+        if pending_cr {
+            pending_cr = false;
+
+            if let Some(rest) = bytes.strip_prefix(b"\n") {
+                writer.write_all(line_ending)?;
+                bytes = rest;
+            } else {
+                writer.write_all(b"\r")?;
+            }
+        }
+
+        while let Some(newline) = bytes.iter().position(|&b| b == b'\n') {
+            let line = &bytes[..newline];
+            writer.write_all(line.strip_suffix(b"\r").unwrap_or(line))?;
+            writer.write_all(line_ending)?;
+            bytes = &bytes[newline + 1..];
+        }
+
+        if let Some(rest) = bytes.strip_suffix(b"\r") {
+            pending_cr = true;
+            bytes = rest;
+        }
+
+        writer.write_all(bytes)?;
+    }
+
+    if pending_cr {
+        writer.write_all(b"\r")?;
+    }
+
+    Ok(())
+}
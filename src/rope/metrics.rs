@@ -1,51 +1,549 @@
+use core::marker::PhantomData;
 use core::ops::{Add, AddAssign, Sub, SubAssign};
 
-use super::gap_buffer::GapBuffer;
+#[cfg(any(
+    feature = "lines",
+    feature = "char-metric",
+    feature = "utf16-metric"
+))]
+use super::byte_counts::ByteCounts;
+use super::combined::Combined;
+use super::gap_buffer::RawGapBuffer;
 use super::gap_slice::GapSlice;
-use crate::tree::{DoubleEndedUnitMetric, Metric, SlicingMetric, UnitMetric};
+use super::line_breaks::{LfBreaks, LineBreaks};
+#[cfg(feature = "lines")]
+use crate::tree::{DoubleEndedUnitMetric, UnitMetric};
+use crate::tree::{Metric, SlicingMetric};
+
+/// The weight of every `char` in the custom metric of a
+/// [`RopeWith`](crate::RopeWith), e.g. the one returned by
+/// [`custom_len()`](crate::RopeWith::custom_len()).
+///
+/// The weight is part of the rope's type, so ropes measuring their text in
+/// different custom units can be used side by side. The custom measure of a
+/// piece of text is the sum of the weights of its `char`s, and it's stored
+/// in the summaries of the rope's B-tree like the number of bytes or line
+/// breaks are.
+///
+/// The custom metric only exists with the `custom-metric` feature enabled.
+/// Without it the `W` parameter of [`RopeWith`](crate::RopeWith) is still
+/// there, but it has no effect: the weight is never called, nothing is
+/// stored for it and ropes only differing in their weight behave the same.
+///
+/// # Examples
+///
+/// ```
+/// # use crop::{tree::AtomicCounter, CharWeight, RopeWith};
+/// #
+/// // Counts the curly braces.
+/// #[derive(Clone, Copy, Debug, Default, PartialEq)]
+/// struct Braces;
+///
+/// impl CharWeight for Braces {
+///     fn weight(ch: char) -> usize {
+///         matches!(ch, '{' | '}') as usize
+///     }
+/// }
+///
+/// type BraceRope = RopeWith<AtomicCounter, Braces>;
+///
+/// let r = BraceRope::from("fn main() { println!(\"{}\") }");
+/// # #[cfg(feature = "custom-metric")]
+/// assert_eq!(r.custom_len(), 4);
+/// ```
+pub trait CharWeight:
+    Copy + Default + core::fmt::Debug + PartialEq + Send + Sync + 'static
+{
+    /// Returns the weight of the `char`.
+    fn weight(ch: char) -> usize;
+}
+
+/// The default [`CharWeight`] of a [`Rope`](crate::Rope), which gives every
+/// `char` a weight of zero.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ZeroWeight;
+
+impl CharWeight for ZeroWeight {
+    #[inline]
+    fn weight(_: char) -> usize {
+        0
+    }
+}
+
+/// The components of the summaries that a [`RopeWith`](crate::RopeWith)
+/// keeps up to date, and so the metrics it can be queried for.
+///
+/// Every component is either [`Maintained`] or [`Omitted`]. An omitted
+/// component takes no room in the summaries and is never computed when the
+/// text is edited, and the methods measuring the text in its units don't
+/// exist for ropes using the set, so calling one of them doesn't compile.
+///
+/// A component only has an effect if the feature storing it is enabled,
+/// i.e. `lines` for [`Lines`](Self::Lines), `char-metric` for
+/// [`Chars`](Self::Chars), `utf16-metric` for
+/// [`Utf16CodeUnits`](Self::Utf16CodeUnits) and `width-metric` for
+/// [`Width`](Self::Width). The features decide which components the
+/// summaries can have, the set which ones a rope keeps.
+///
+/// # Examples
+///
+/// ```
+/// # use crop::{tree::AtomicCounter, LfBreaks, Maintained, MetricSet, Omitted, RopeWith, ZeroWeight};
+/// #
+/// // Only keeps track of the bytes and the line breaks.
+/// #[derive(Clone, Copy, Debug, Default, PartialEq)]
+/// struct LinesOnly;
+///
+/// impl MetricSet for LinesOnly {
+///     type Lines = Maintained;
+///     type Chars = Omitted;
+///     type Utf16CodeUnits = Omitted;
+///     type Width = Omitted;
+/// }
+///
+/// // The set comes after the chunk size parameters, so those have to be
+/// // spelled out, here with their default values.
+/// type LineRope =
+///     RopeWith<AtomicCounter, ZeroWeight, LfBreaks, 2048, 25, LinesOnly>;
+///
+/// let r = LineRope::from("Hello\nworld");
+/// # #[cfg(feature = "lines")]
+/// assert_eq!(r.line_len(), 2);
+/// ```
+///
+/// Measuring a rope in the units of an omitted component is a compile
+/// error:
+///
+/// ```compile_fail
+/// # use crop::{tree::AtomicCounter, LfBreaks, Maintained, MetricSet, Omitted, RopeWith, ZeroWeight};
+/// #
+/// # #[derive(Clone, Copy, Debug, Default, PartialEq)]
+/// # struct LinesOnly;
+/// #
+/// # impl MetricSet for LinesOnly {
+/// #     type Lines = Maintained;
+/// #     type Chars = Omitted;
+/// #     type Utf16CodeUnits = Omitted;
+/// #     type Width = Omitted;
+/// # }
+/// #
+/// # type LineRope =
+/// #     RopeWith<AtomicCounter, ZeroWeight, LfBreaks, 2048, 25, LinesOnly>;
+/// #
+/// let r = LineRope::from("Hello\nworld");
+/// r.char_len();
+/// ```
+pub trait MetricSet:
+    Copy + Default + core::fmt::Debug + PartialEq + Send + Sync + 'static
+{
+    /// Whether the line breaks are counted.
+    type Lines: Component;
+
+    /// Whether the `char`s are counted.
+    type Chars: Component;
+
+    /// Whether the UTF-16 code units are counted.
+    type Utf16CodeUnits: Component;
+
+    /// Whether the display width is measured.
+    type Width: Component;
+}
+
+/// The default [`MetricSet`] of a [`Rope`](crate::Rope), which maintains
+/// every component of the summaries enabled by the crate's features.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AllMetrics;
+
+impl MetricSet for AllMetrics {
+    type Lines = Maintained;
+    type Chars = Maintained;
+    type Utf16CodeUnits = Maintained;
+    type Width = Maintained;
+}
+
+/// Whether a [`MetricSet`] keeps a component of the summaries.
+///
+/// This trait is sealed and implemented by [`Maintained`] and [`Omitted`].
+pub trait Component:
+    sealed::Sealed
+    + Copy
+    + Default
+    + core::fmt::Debug
+    + PartialEq
+    + Send
+    + Sync
+    + 'static
+{
+}
+
+/// A component of the summaries which is stored and kept up to date.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Maintained(Count);
+
+/// A component of the summaries which is neither stored nor computed.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Omitted;
+
+impl Component for Maintained {}
+
+impl Component for Omitted {}
+
+pub(super) mod sealed {
+    use super::{from_count, to_count, Count, Maintained, Omitted};
+    #[cfg(feature = "lines")]
+    use super::{CharWeight, LineBreaks, MetricSet};
+    #[cfg(feature = "lines")]
+    use crate::rope::{utils, RopeSlice};
+    #[cfg(feature = "lines")]
+    use crate::tree::RefCounter;
+
+    /// How a component is stored in a [`ChunkSummary`].
+    ///
+    /// [`ChunkSummary`]: super::ChunkSummary
+    pub trait Sealed: Sized {
+        /// Whether the component is stored in the summaries.
+        const IS_MAINTAINED: bool;
+
+        /// Creates the component, only calling `count` if it's maintained.
+        fn of(count: impl FnOnce() -> usize) -> Self;
+
+        /// Creates the component from a field of a snapshot, returning
+        /// `None` if it doesn't fit in a [`Count`].
+        fn from_field(field: usize) -> Option<Self>;
+
+        /// Returns the count of the component, or zero if it isn't
+        /// maintained.
+        fn get(self) -> usize;
+
+        fn add(self, other: Self) -> Self;
+
+        fn sub(self, other: Self) -> Self;
+
+        /// Writes the lines of the slice with [`utils::debug_lines()`] if
+        /// they're maintained, or returns `None` without writing anything.
+        ///
+        /// This lets the `Debug` implementations of ropes and slices, which
+        /// exist whatever lines their `S` maintains, write them when they
+        /// can.
+        #[cfg(feature = "lines")]
+        fn debug_lines<
+            C: RefCounter,
+            W: CharWeight,
+            B: LineBreaks,
+            const MAX_BYTES: usize,
+            const MIN_FILL: usize,
+            S: MetricSet<Lines = Self>,
+        >(
+            name: &str,
+            slice: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+            f: &mut core::fmt::Formatter<'_>,
+        ) -> Option<core::fmt::Result>;
+    }
+
+    impl Sealed for Maintained {
+        const IS_MAINTAINED: bool = true;
+
+        #[inline(always)]
+        fn of(count: impl FnOnce() -> usize) -> Self {
+            Self(to_count(count()))
+        }
+
+        #[inline(always)]
+        fn from_field(field: usize) -> Option<Self> {
+            Count::try_from(field).ok().map(Self)
+        }
+
+        #[inline(always)]
+        fn get(self) -> usize {
+            from_count(self.0)
+        }
+
+        #[inline(always)]
+        fn add(self, other: Self) -> Self {
+            Self(super::add(self.0, other.0))
+        }
+
+        #[inline(always)]
+        fn sub(self, other: Self) -> Self {
+            Self(self.0 - other.0)
+        }
+
+        #[cfg(feature = "lines")]
+        #[inline]
+        fn debug_lines<
+            C: RefCounter,
+            W: CharWeight,
+            B: LineBreaks,
+            const MAX_BYTES: usize,
+            const MIN_FILL: usize,
+            S: MetricSet<Lines = Self>,
+        >(
+            name: &str,
+            slice: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+            f: &mut core::fmt::Formatter<'_>,
+        ) -> Option<core::fmt::Result> {
+            Some(utils::debug_lines(name, slice, f))
+        }
+    }
+
+    /// The field of an omitted component is always zero in a snapshot, and
+    /// is ignored when reading it back.
+    impl Sealed for Omitted {
+        const IS_MAINTAINED: bool = false;
+
+        #[inline(always)]
+        fn of(_: impl FnOnce() -> usize) -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        fn from_field(_: usize) -> Option<Self> {
+            Some(Self)
+        }
+
+        #[inline(always)]
+        fn get(self) -> usize {
+            0
+        }
+
+        #[inline(always)]
+        fn add(self, _: Self) -> Self {
+            Self
+        }
+
+        #[inline(always)]
+        fn sub(self, _: Self) -> Self {
+            Self
+        }
+
+        #[cfg(feature = "lines")]
+        #[inline]
+        fn debug_lines<
+            C: RefCounter,
+            W: CharWeight,
+            B: LineBreaks,
+            const MAX_BYTES: usize,
+            const MIN_FILL: usize,
+            S: MetricSet<Lines = Self>,
+        >(
+            _: &str,
+            _: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+            _: &mut core::fmt::Formatter<'_>,
+        ) -> Option<core::fmt::Result> {
+            None
+        }
+    }
+}
+
+#[cfg(any(
+    feature = "lines",
+    feature = "char-metric",
+    feature = "utf16-metric",
+    feature = "width-metric"
+))]
+use sealed::Sealed as _;
 
 #[derive(Copy, Clone, Default, Debug, PartialEq)]
 #[doc(hidden)]
-pub struct ChunkSummary {
-    bytes: usize,
-    line_breaks: usize,
+pub struct ChunkSummary<
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    S: MetricSet = AllMetrics,
+> {
+    bytes: Count,
+    #[cfg(feature = "lines")]
+    line_breaks: S::Lines,
+    #[cfg(feature = "char-metric")]
+    chars: S::Chars,
     #[cfg(feature = "utf16-metric")]
-    utf16_code_units: usize,
+    utf16_code_units: S::Utf16CodeUnits,
+    #[cfg(feature = "custom-metric")]
+    custom_units: Count,
+    #[cfg(feature = "width-metric")]
+    width: S::Width,
+    combined: Combined,
+    marker: PhantomData<(W, B, S)>,
+}
+
+/// The number of fields of a [`ChunkSummary`] in a snapshot, which depends
+/// on the enabled features. The components omitted by the summary's
+/// [`MetricSet`] still have a field, which is always zero.
+pub(crate) const SUMMARY_FIELDS: usize = 1
+    + cfg!(feature = "lines") as usize
+    + cfg!(feature = "char-metric") as usize
+    + cfg!(feature = "utf16-metric") as usize
+    + cfg!(feature = "custom-metric") as usize
+    + cfg!(feature = "width-metric") as usize
+    + Combined::FIELDS;
+
+/// The integer type used to store the fields of a [`ChunkSummary`].
+#[cfg(not(feature = "u32-summaries"))]
+pub(super) type Count = usize;
+
+/// The integer type used to store the fields of a [`ChunkSummary`].
+#[cfg(feature = "u32-summaries")]
+pub(super) type Count = u32;
+
+pub(super) mod count_ops {
+    //! Conversions and arithmetic on the fields of a `ChunkSummary`, which
+    //! are checked when the `u32-summaries` feature is enabled.
+
+    use super::Count;
+
+    #[cfg(not(feature = "u32-summaries"))]
+    #[inline(always)]
+    pub(in crate::rope) fn to_count(n: usize) -> Count {
+        n
+    }
+
+    #[cfg(feature = "u32-summaries")]
+    #[track_caller]
+    #[inline(always)]
+    pub(in crate::rope) fn to_count(n: usize) -> Count {
+        Count::try_from(n).unwrap_or_else(|_| overflow())
+    }
+
+    #[cfg(not(feature = "u32-summaries"))]
+    #[inline(always)]
+    pub(in crate::rope) fn from_count(n: Count) -> usize {
+        n
+    }
+
+    #[cfg(feature = "u32-summaries")]
+    #[inline(always)]
+    pub(in crate::rope) fn from_count(n: Count) -> usize {
+        n as usize
+    }
+
+    #[cfg(not(feature = "u32-summaries"))]
+    #[inline(always)]
+    pub(in crate::rope) fn add(a: Count, b: Count) -> Count {
+        a + b
+    }
+
+    #[cfg(feature = "u32-summaries")]
+    #[track_caller]
+    #[inline(always)]
+    pub(in crate::rope) fn add(a: Count, b: Count) -> Count {
+        a.checked_add(b).unwrap_or_else(|| overflow())
+    }
+
+    #[cfg(feature = "u32-summaries")]
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    fn overflow() -> ! {
+        panic!(
+            "text too large: with the `u32-summaries` feature enabled a Rope \
+             can't hold more than {} bytes",
+            Count::MAX
+        );
+    }
 }
 
-impl From<&str> for ChunkSummary {
+use count_ops::{add, from_count, to_count};
+
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> From<&str>
+    for ChunkSummary<W, B, S>
+{
     #[inline]
     fn from(s: &str) -> Self {
+        #[cfg(any(
+            feature = "lines",
+            feature = "char-metric",
+            feature = "utf16-metric"
+        ))]
+        let counts = ByteCounts::<S>::of(s);
+
         Self {
-            bytes: s.len(),
-            line_breaks: count::line_breaks(s),
+            bytes: to_count(s.len()),
+            #[cfg(feature = "lines")]
+            line_breaks: S::Lines::of(|| {
+                B::count_breaks_with_line_feeds(s, counts.line_feeds)
+            }),
+            #[cfg(feature = "char-metric")]
+            chars: S::Chars::of(|| counts.chars),
             #[cfg(feature = "utf16-metric")]
-            utf16_code_units: count::utf16_code_units(s),
+            utf16_code_units: S::Utf16CodeUnits::of(|| {
+                counts.utf16_code_units()
+            }),
+            #[cfg(feature = "custom-metric")]
+            custom_units: to_count(count::custom_units::<W>(s)),
+            #[cfg(feature = "width-metric")]
+            width: S::Width::of(|| count::width(s)),
+            combined: Combined::of::<B>(s),
+
+            marker: PhantomData,
         }
     }
 }
 
-impl From<char> for ChunkSummary {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> From<char>
+    for ChunkSummary<W, B, S>
+{
     #[inline]
     fn from(ch: char) -> Self {
         Self {
-            bytes: ch.len_utf8(),
-            line_breaks: (ch == '\n') as usize,
+            bytes: to_count(ch.len_utf8()),
+            #[cfg(feature = "lines")]
+            line_breaks: S::Lines::of(|| B::is_line_break(ch) as usize),
+            #[cfg(feature = "char-metric")]
+            chars: S::Chars::of(|| 1),
             #[cfg(feature = "utf16-metric")]
-            utf16_code_units: ch.len_utf16(),
+            utf16_code_units: S::Utf16CodeUnits::of(|| ch.len_utf16()),
+            #[cfg(feature = "custom-metric")]
+            custom_units: to_count(W::weight(ch)),
+            #[cfg(feature = "width-metric")]
+            width: S::Width::of(|| width_metric::char_width(ch)),
+            combined: Combined::of::<B>(ch.encode_utf8(&mut [0; 4])),
+
+            marker: PhantomData,
         }
     }
 }
 
-impl ChunkSummary {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> ChunkSummary<W, B, S> {
     #[inline]
     pub fn bytes(&self) -> usize {
-        self.bytes
+        from_count(self.bytes)
+    }
+
+    #[cfg(feature = "char-metric")]
+    #[inline]
+    pub fn chars(&self) -> usize
+    where
+        S: MetricSet<Chars = Maintained>,
+    {
+        self.chars.get()
+    }
+
+    /// Returns the number of `char`s, or `None` if `S` omits them.
+    #[cfg(feature = "char-metric")]
+    #[inline]
+    pub(super) fn maintained_chars(&self) -> Option<usize> {
+        S::Chars::IS_MAINTAINED.then(|| self.chars.get())
+    }
+
+    #[cfg(feature = "custom-metric")]
+    #[inline]
+    pub fn custom_units(&self) -> usize {
+        from_count(self.custom_units)
+    }
+
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn line_breaks(&self) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        self.line_breaks.get()
     }
 
+    /// Returns the number of line breaks, or `None` if `S` omits them.
+    #[cfg(feature = "lines")]
     #[inline]
-    pub fn line_breaks(&self) -> usize {
-        self.line_breaks
+    pub(super) fn maintained_line_breaks(&self) -> Option<usize> {
+        S::Lines::IS_MAINTAINED.then(|| self.line_breaks.get())
     }
 
     #[doc(hidden)]
@@ -54,14 +552,186 @@ impl ChunkSummary {
         Self::default()
     }
 
+    /// Creates a summary from the fields returned by
+    /// [`fields()`](Self::fields()), or returns `None` if one of them
+    /// doesn't fit in the integer type used to store it.
+    #[inline]
+    pub(crate) fn from_fields(
+        fields: [usize; SUMMARY_FIELDS],
+    ) -> Option<Self> {
+        let mut fields = fields.into_iter();
+        let mut field = || fields.next();
+        let count = |field: usize| Count::try_from(field).ok();
+
+        Some(Self {
+            bytes: count(field()?)?,
+            #[cfg(feature = "lines")]
+            line_breaks: S::Lines::from_field(field()?)?,
+            #[cfg(feature = "char-metric")]
+            chars: S::Chars::from_field(field()?)?,
+            #[cfg(feature = "utf16-metric")]
+            utf16_code_units: S::Utf16CodeUnits::from_field(field()?)?,
+            #[cfg(feature = "custom-metric")]
+            custom_units: count(field()?)?,
+            #[cfg(feature = "width-metric")]
+            width: S::Width::from_field(field()?)?,
+            combined: Combined::from_fields(&mut fields)?,
+
+            marker: PhantomData,
+        })
+    }
+
+    /// Returns the fields of the summary in the order they're declared in.
+    #[inline]
+    pub(crate) fn fields(&self) -> [usize; SUMMARY_FIELDS] {
+        let counts = [
+            from_count(self.bytes),
+            #[cfg(feature = "lines")]
+            self.line_breaks.get(),
+            #[cfg(feature = "char-metric")]
+            self.chars.get(),
+            #[cfg(feature = "utf16-metric")]
+            self.utf16_code_units.get(),
+            #[cfg(feature = "custom-metric")]
+            from_count(self.custom_units),
+            #[cfg(feature = "width-metric")]
+            self.width.get(),
+        ];
+
+        let mut fields = [0; SUMMARY_FIELDS];
+
+        for (field, value) in fields
+            .iter_mut()
+            .zip(counts.into_iter().chain(self.combined.fields()))
+        {
+            *field = value;
+        }
+
+        fields
+    }
+
+    /// Turns the sum of the summaries of the two segments of a chunk into
+    /// the summary of the chunk's text as a whole, whose combined parts
+    /// don't depend on where its gap is.
+    #[inline]
+    pub(super) fn join_segments(&mut self, left: &str, right: &str) {
+        self.combined.join_segments(left, right);
+    }
+
+    /// Returns whether the summaries are the same, including the combined
+    /// parts that `==` ignores.
+    #[inline]
+    pub(super) fn is_identical_to(&self, other: &Self) -> bool {
+        self == other && self.combined.is_identical_to(&other.combined)
+    }
+
+    /// Returns the length in bytes of the longest line of the text, which
+    /// is only meaningful if the summary wasn't obtained by a subtraction.
+    #[cfg(feature = "max-line-len")]
+    #[inline]
+    pub(super) fn max_line_len(&self) -> usize {
+        self.combined.max_line_len()
+    }
+
+    /// Returns the display width of the widest line of the text, which is
+    /// only meaningful if the summary wasn't obtained by a subtraction.
+    #[cfg(all(feature = "max-line-len", feature = "width-metric"))]
+    #[inline]
+    pub(super) fn max_line_width(&self) -> usize {
+        self.combined.max_line_width()
+    }
+
+    /// Returns the number of words of the text, which is only meaningful if
+    /// the summary wasn't obtained by a subtraction.
+    #[cfg(feature = "word-count")]
+    #[inline]
+    pub(super) fn words(&self) -> usize {
+        self.combined.words()
+    }
+
+    /// Returns the number of extended grapheme clusters of the text, which
+    /// is only meaningful if the summary wasn't obtained by a subtraction,
+    /// and only exact if there are no
+    /// [unsure joins](Self::unsure_grapheme_joins()) in it.
+    #[cfg(feature = "grapheme-count")]
+    #[inline]
+    pub(super) fn graphemes(&self) -> usize {
+        self.combined.graphemes()
+    }
+
+    /// Returns the number of joins between chunks after which the grapheme
+    /// clusters of the text have to be segmented again to be counted
+    /// exactly.
+    #[cfg(feature = "grapheme-count")]
+    #[inline]
+    pub(super) fn unsure_grapheme_joins(&self) -> usize {
+        self.combined.unsure_grapheme_joins()
+    }
+
+    /// Returns whether the join between the text of `self` and the text of
+    /// `next` right after it is one of the
+    /// [unsure joins](Self::unsure_grapheme_joins()).
+    #[cfg(feature = "grapheme-count")]
+    #[inline]
+    pub(super) fn has_unsure_grapheme_join(&self, next: &Self) -> bool {
+        self.combined.has_unsure_grapheme_join(&next.combined)
+    }
+
+    /// Returns the fingerprint of the text, which is only meaningful if the
+    /// summary wasn't obtained by a subtraction.
+    #[cfg(feature = "fingerprint")]
+    #[inline]
+    pub(super) fn fingerprint(&self) -> u64 {
+        self.combined.fingerprint()
+    }
+
+    /// Returns the number of paragraphs of the text, which is only
+    /// meaningful if the summary wasn't obtained by a subtraction.
+    #[cfg(feature = "paragraph-count")]
+    #[inline]
+    pub(super) fn paragraphs(&self) -> usize {
+        self.combined.paragraphs()
+    }
+
+    /// Returns the number of paragraphs of the text which are followed by a
+    /// blank line ending with a line break, which is only meaningful if the
+    /// summary wasn't obtained by a subtraction.
+    #[cfg(feature = "paragraph-count")]
+    #[inline]
+    pub(super) fn ended_paragraphs(&self) -> usize {
+        self.combined.ended_paragraphs()
+    }
+
+    /// Returns whether the text after the last line break is blank, which is
+    /// only meaningful if the summary wasn't obtained by a subtraction.
+    #[cfg(feature = "paragraph-count")]
+    #[inline]
+    pub(super) fn ends_in_blank_line(&self) -> bool {
+        self.combined.ends_in_blank_line()
+    }
+
     #[cfg(feature = "utf16-metric")]
     #[inline]
-    pub fn utf16_code_units(&self) -> usize {
-        self.utf16_code_units
+    pub fn utf16_code_units(&self) -> usize
+    where
+        S: MetricSet<Utf16CodeUnits = Maintained>,
+    {
+        self.utf16_code_units.get()
+    }
+
+    #[cfg(feature = "width-metric")]
+    #[inline]
+    pub fn width(&self) -> usize
+    where
+        S: MetricSet<Width = Maintained>,
+    {
+        self.width.get()
     }
 }
 
-impl Add<Self> for ChunkSummary {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> Add<Self>
+    for ChunkSummary<W, B, S>
+{
     type Output = Self;
 
     #[inline]
@@ -71,7 +741,9 @@ impl Add<Self> for ChunkSummary {
     }
 }
 
-impl Sub<Self> for ChunkSummary {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> Sub<Self>
+    for ChunkSummary<W, B, S>
+{
     type Output = Self;
 
     #[inline]
@@ -81,7 +753,9 @@ impl Sub<Self> for ChunkSummary {
     }
 }
 
-impl Add<&Self> for ChunkSummary {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> Add<&Self>
+    for ChunkSummary<W, B, S>
+{
     type Output = Self;
 
     #[inline]
@@ -91,7 +765,9 @@ impl Add<&Self> for ChunkSummary {
     }
 }
 
-impl Sub<&Self> for ChunkSummary {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> Sub<&Self>
+    for ChunkSummary<W, B, S>
+{
     type Output = Self;
 
     #[inline]
@@ -101,38 +777,81 @@ impl Sub<&Self> for ChunkSummary {
     }
 }
 
-impl AddAssign<Self> for ChunkSummary {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> AddAssign<Self>
+    for ChunkSummary<W, B, S>
+{
     #[inline]
     fn add_assign(&mut self, rhs: Self) {
-        self.bytes += rhs.bytes;
-        self.line_breaks += rhs.line_breaks;
+        self.bytes = add(self.bytes, rhs.bytes);
+        #[cfg(feature = "lines")]
+        {
+            self.line_breaks = self.line_breaks.add(rhs.line_breaks);
+        }
+        #[cfg(feature = "char-metric")]
+        {
+            self.chars = self.chars.add(rhs.chars);
+        }
         #[cfg(feature = "utf16-metric")]
         {
-            self.utf16_code_units += rhs.utf16_code_units;
+            self.utf16_code_units =
+                self.utf16_code_units.add(rhs.utf16_code_units);
+        }
+        #[cfg(feature = "custom-metric")]
+        {
+            self.custom_units = add(self.custom_units, rhs.custom_units);
+        }
+        #[cfg(feature = "width-metric")]
+        {
+            self.width = self.width.add(rhs.width);
         }
+        self.combined.append(&rhs.combined);
     }
 }
 
-impl SubAssign<Self> for ChunkSummary {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> SubAssign<Self>
+    for ChunkSummary<W, B, S>
+{
     #[inline]
     fn sub_assign(&mut self, rhs: Self) {
         self.bytes -= rhs.bytes;
-        self.line_breaks -= rhs.line_breaks;
+        #[cfg(feature = "lines")]
+        {
+            self.line_breaks = self.line_breaks.sub(rhs.line_breaks);
+        }
+        #[cfg(feature = "char-metric")]
+        {
+            self.chars = self.chars.sub(rhs.chars);
+        }
         #[cfg(feature = "utf16-metric")]
         {
-            self.utf16_code_units -= rhs.utf16_code_units;
+            self.utf16_code_units =
+                self.utf16_code_units.sub(rhs.utf16_code_units);
+        }
+        #[cfg(feature = "custom-metric")]
+        {
+            self.custom_units -= rhs.custom_units;
         }
+        #[cfg(feature = "width-metric")]
+        {
+            self.width = self.width.sub(rhs.width);
+        }
+        // The combined parts can't be subtracted, so they're left as they
+        // are and become meaningless.
     }
 }
 
-impl AddAssign<&Self> for ChunkSummary {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> AddAssign<&Self>
+    for ChunkSummary<W, B, S>
+{
     #[inline]
     fn add_assign(&mut self, rhs: &Self) {
         *self += *rhs;
     }
 }
 
-impl SubAssign<&Self> for ChunkSummary {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> SubAssign<&Self>
+    for ChunkSummary<W, B, S>
+{
     #[inline]
     fn sub_assign(&mut self, rhs: &Self) {
         *self -= *rhs;
@@ -141,23 +860,27 @@ impl SubAssign<&Self> for ChunkSummary {
 
 /// Conversion trait from the metric implement this trait to the corresponding
 /// byte offset.
-pub trait ToByteOffset: Metric<ChunkSummary> {
+pub trait ToByteOffset<W: CharWeight, B: LineBreaks, S: MetricSet>:
+    Metric<ChunkSummary<W, B, S>>
+{
     /// Should return the byte offset of `self` in the given string.
     fn to_byte_offset(&self, in_str: &str) -> usize;
 }
 
 /// Trait to get the summary of a string up to a given offset.
-pub trait SummaryUpTo: Metric<ChunkSummary> {
+pub trait SummaryUpTo<W: CharWeight, B: LineBreaks, S: MetricSet>:
+    Metric<ChunkSummary<W, B, S>>
+{
     /// Return the summary of the given string up to `offset`, where
     ///
     /// * `str_summary` is the string's summary,
     /// * `byte_offset` is byte offset of `offset`.
     fn up_to(
         in_str: &str,
-        str_summary: ChunkSummary,
+        str_summary: ChunkSummary<W, B, S>,
         offset: Self,
         byte_offset: usize,
-    ) -> ChunkSummary;
+    ) -> ChunkSummary<W, B, S>;
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
@@ -211,43 +934,91 @@ impl From<ByteMetric> for usize {
     }
 }
 
-impl ToByteOffset for ByteMetric {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> ToByteOffset<W, B, S>
+    for ByteMetric
+{
     #[inline]
     fn to_byte_offset(&self, _: &str) -> usize {
         self.0
     }
 }
 
-impl SummaryUpTo for ByteMetric {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> SummaryUpTo<W, B, S>
+    for ByteMetric
+{
+    #[cfg_attr(
+        not(any(
+            feature = "char-metric",
+            feature = "lines",
+            feature = "utf16-metric",
+            feature = "custom-metric",
+            feature = "width-metric"
+        )),
+        allow(unused_variables)
+    )]
     #[inline]
     fn up_to(
         in_str: &str,
-        str_summary: ChunkSummary,
+        str_summary: ChunkSummary<W, B, S>,
         offset: Self,
         byte_offset: usize,
-    ) -> ChunkSummary {
+    ) -> ChunkSummary<W, B, S> {
         debug_assert_eq!(offset.0, byte_offset);
 
         ChunkSummary {
-            bytes: byte_offset,
+            bytes: to_count(byte_offset),
 
-            line_breaks: count::line_breaks_up_to(
-                in_str,
-                byte_offset,
-                str_summary.line_breaks,
-            ),
+            #[cfg(feature = "char-metric")]
+            chars: S::Chars::of(|| {
+                count::chars_up_to(
+                    in_str,
+                    byte_offset,
+                    str_summary.chars.get(),
+                )
+            }),
+
+            #[cfg(feature = "lines")]
+            line_breaks: S::Lines::of(|| {
+                count::line_breaks_up_to::<B>(
+                    in_str,
+                    byte_offset,
+                    str_summary.line_breaks.get(),
+                )
+            }),
 
             #[cfg(feature = "utf16-metric")]
-            utf16_code_units: count::utf16_code_units_up_to(
+            utf16_code_units: S::Utf16CodeUnits::of(|| {
+                count::utf16_code_units_up_to(
+                    in_str,
+                    byte_offset,
+                    str_summary.utf16_code_units.get(),
+                )
+            }),
+
+            #[cfg(feature = "custom-metric")]
+            custom_units: to_count(count::custom_units_up_to::<W>(
                 in_str,
                 byte_offset,
-                str_summary.utf16_code_units,
-            ),
+                str_summary.custom_units(),
+            )),
+
+            #[cfg(feature = "width-metric")]
+            width: S::Width::of(|| {
+                count::width_up_to(
+                    in_str,
+                    byte_offset,
+                    str_summary.width.get(),
+                )
+            }),
+            combined: Combined::default(),
+            marker: PhantomData,
         }
     }
 }
 
-impl Metric<ChunkSummary> for ByteMetric {
+impl<W: CharWeight, B: LineBreaks, S: MetricSet> Metric<ChunkSummary<W, B, S>>
+    for ByteMetric
+{
     #[inline]
     fn zero() -> Self {
         Self(0)
@@ -259,21 +1030,27 @@ impl Metric<ChunkSummary> for ByteMetric {
     }
 
     #[inline]
-    fn measure(summary: &ChunkSummary) -> Self {
-        Self(summary.bytes)
+    fn measure(summary: &ChunkSummary<W, B, S>) -> Self {
+        Self(summary.bytes())
     }
 }
 
-impl<const MAX_BYTES: usize> SlicingMetric<GapBuffer<MAX_BYTES>>
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > SlicingMetric<RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>>
     for ByteMetric
 {
     #[track_caller]
     #[inline]
     fn slice_up_to<'a>(
-        chunk: GapSlice<'a>,
+        chunk: GapSlice<'a, W, B, S>,
         byte_offset: Self,
-        &summary: &ChunkSummary,
-    ) -> (GapSlice<'a>, ChunkSummary)
+        &summary: &ChunkSummary<W, B, S>,
+    ) -> (GapSlice<'a, W, B, S>, ChunkSummary<W, B, S>)
     where
         'a: 'a,
     {
@@ -286,10 +1063,10 @@ impl<const MAX_BYTES: usize> SlicingMetric<GapBuffer<MAX_BYTES>>
     #[track_caller]
     #[inline]
     fn slice_from<'a>(
-        chunk: GapSlice<'a>,
+        chunk: GapSlice<'a, W, B, S>,
         byte_offset: Self,
-        &summary: &ChunkSummary,
-    ) -> (GapSlice<'a>, ChunkSummary)
+        &summary: &ChunkSummary<W, B, S>,
+    ) -> (GapSlice<'a, W, B, S>, ChunkSummary<W, B, S>)
     where
         'a: 'a,
     {
@@ -301,8 +1078,11 @@ impl<const MAX_BYTES: usize> SlicingMetric<GapBuffer<MAX_BYTES>>
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+#[cfg(feature = "lines")]
 pub struct RawLineMetric(pub usize);
 
+#[cfg(feature = "lines")]
 impl Add for RawLineMetric {
     type Output = Self;
 
@@ -312,6 +1092,7 @@ impl Add for RawLineMetric {
     }
 }
 
+#[cfg(feature = "lines")]
 impl Sub for RawLineMetric {
     type Output = Self;
 
@@ -321,6 +1102,7 @@ impl Sub for RawLineMetric {
     }
 }
 
+#[cfg(feature = "lines")]
 impl AddAssign for RawLineMetric {
     #[inline]
     fn add_assign(&mut self, other: Self) {
@@ -328,6 +1110,7 @@ impl AddAssign for RawLineMetric {
     }
 }
 
+#[cfg(feature = "lines")]
 impl SubAssign for RawLineMetric {
     #[inline]
     fn sub_assign(&mut self, other: Self) {
@@ -335,38 +1118,85 @@ impl SubAssign for RawLineMetric {
     }
 }
 
-impl ToByteOffset for RawLineMetric {
+#[cfg(feature = "lines")]
+impl<W: CharWeight, B: LineBreaks, S: MetricSet<Lines = Maintained>>
+    ToByteOffset<W, B, S> for RawLineMetric
+{
     #[inline]
     fn to_byte_offset(&self, s: &str) -> usize {
-        convert::byte_of_line(s, self.0)
+        convert::byte_of_line::<B>(s, self.0)
     }
 }
 
-impl SummaryUpTo for RawLineMetric {
-    #[cfg_attr(not(feature = "utf16-metric"), allow(unused_variables))]
+#[cfg(feature = "lines")]
+impl<W: CharWeight, B: LineBreaks, S: MetricSet<Lines = Maintained>>
+    SummaryUpTo<W, B, S> for RawLineMetric
+{
+    #[cfg_attr(
+        not(any(
+            feature = "char-metric",
+            feature = "utf16-metric",
+            feature = "custom-metric",
+            feature = "width-metric"
+        )),
+        allow(unused_variables)
+    )]
     #[inline]
     fn up_to(
         in_str: &str,
-        str_summary: ChunkSummary,
+        str_summary: ChunkSummary<W, B, S>,
         Self(line_offset): Self,
         byte_offset: usize,
-    ) -> ChunkSummary {
+    ) -> ChunkSummary<W, B, S> {
         ChunkSummary {
-            bytes: byte_offset,
+            bytes: to_count(byte_offset),
+
+            #[cfg(feature = "lines")]
+            line_breaks: S::Lines::of(|| line_offset),
 
-            line_breaks: line_offset,
+            #[cfg(feature = "char-metric")]
+            chars: S::Chars::of(|| {
+                count::chars_up_to(
+                    in_str,
+                    byte_offset,
+                    str_summary.chars.get(),
+                )
+            }),
 
             #[cfg(feature = "utf16-metric")]
-            utf16_code_units: count::utf16_code_units_up_to(
+            utf16_code_units: S::Utf16CodeUnits::of(|| {
+                count::utf16_code_units_up_to(
+                    in_str,
+                    byte_offset,
+                    str_summary.utf16_code_units.get(),
+                )
+            }),
+
+            #[cfg(feature = "custom-metric")]
+            custom_units: to_count(count::custom_units_up_to::<W>(
                 in_str,
                 byte_offset,
-                str_summary.utf16_code_units,
-            ),
+                str_summary.custom_units(),
+            )),
+
+            #[cfg(feature = "width-metric")]
+            width: S::Width::of(|| {
+                count::width_up_to(
+                    in_str,
+                    byte_offset,
+                    str_summary.width.get(),
+                )
+            }),
+            combined: Combined::default(),
+            marker: PhantomData,
         }
     }
 }
 
-impl Metric<ChunkSummary> for RawLineMetric {
+#[cfg(feature = "lines")]
+impl<W: CharWeight, B: LineBreaks, S: MetricSet<Lines = Maintained>>
+    Metric<ChunkSummary<W, B, S>> for RawLineMetric
+{
     #[inline]
     fn zero() -> Self {
         Self(0)
@@ -378,20 +1208,27 @@ impl Metric<ChunkSummary> for RawLineMetric {
     }
 
     #[inline]
-    fn measure(summary: &ChunkSummary) -> Self {
-        Self(summary.line_breaks)
+    fn measure(summary: &ChunkSummary<W, B, S>) -> Self {
+        Self(summary.line_breaks())
     }
 }
 
-impl<const MAX_BYTES: usize> SlicingMetric<GapBuffer<MAX_BYTES>>
+#[cfg(feature = "lines")]
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > SlicingMetric<RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>>
     for RawLineMetric
 {
     #[inline]
     fn slice_up_to<'a>(
-        chunk: GapSlice<'a>,
+        chunk: GapSlice<'a, W, B, S>,
         line_offset: Self,
-        &summary: &ChunkSummary,
-    ) -> (GapSlice<'a>, ChunkSummary)
+        &summary: &ChunkSummary<W, B, S>,
+    ) -> (GapSlice<'a, W, B, S>, ChunkSummary<W, B, S>)
     where
         'a: 'a,
     {
@@ -401,10 +1238,10 @@ impl<const MAX_BYTES: usize> SlicingMetric<GapBuffer<MAX_BYTES>>
 
     #[inline]
     fn slice_from<'a>(
-        chunk: GapSlice<'a>,
+        chunk: GapSlice<'a, W, B, S>,
         line_offset: Self,
-        &summary: &ChunkSummary,
-    ) -> (GapSlice<'a>, ChunkSummary)
+        &summary: &ChunkSummary<W, B, S>,
+    ) -> (GapSlice<'a, W, B, S>, ChunkSummary<W, B, S>)
     where
         'a: 'a,
     {
@@ -413,14 +1250,27 @@ impl<const MAX_BYTES: usize> SlicingMetric<GapBuffer<MAX_BYTES>>
     }
 }
 
-impl<const MAX_BYTES: usize> UnitMetric<GapBuffer<MAX_BYTES>>
+#[cfg(feature = "lines")]
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > UnitMetric<RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>>
     for RawLineMetric
 {
     #[inline]
     fn first_unit<'a>(
-        chunk: GapSlice<'a>,
-        &summary: &ChunkSummary,
-    ) -> (GapSlice<'a>, ChunkSummary, ChunkSummary, GapSlice<'a>, ChunkSummary)
+        chunk: GapSlice<'a, W, B, S>,
+        &summary: &ChunkSummary<W, B, S>,
+    ) -> (
+        GapSlice<'a, W, B, S>,
+        ChunkSummary<W, B, S>,
+        ChunkSummary<W, B, S>,
+        GapSlice<'a, W, B, S>,
+        ChunkSummary<W, B, S>,
+    )
     where
         'a: 'a,
     {
@@ -429,21 +1279,39 @@ impl<const MAX_BYTES: usize> UnitMetric<GapBuffer<MAX_BYTES>>
 
         (first, first_summary, first_summary, rest, rest_summary)
     }
+
+    #[inline]
+    fn num_units(line_offset: Self) -> Option<usize> {
+        Some(line_offset.0)
+    }
 }
 
-impl<const MAX_BYTES: usize> DoubleEndedUnitMetric<GapBuffer<MAX_BYTES>>
+#[cfg(feature = "lines")]
+impl<
+        const MAX_BYTES: usize,
+        W: CharWeight,
+        B: LineBreaks,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    > DoubleEndedUnitMetric<RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>>
     for RawLineMetric
 {
     #[inline]
     fn last_unit<'a>(
-        slice: GapSlice<'a>,
-        &summary: &ChunkSummary,
-    ) -> (GapSlice<'a>, ChunkSummary, GapSlice<'a>, ChunkSummary, ChunkSummary)
+        slice: GapSlice<'a, W, B, S>,
+        &summary: &ChunkSummary<W, B, S>,
+    ) -> (
+        GapSlice<'a, W, B, S>,
+        ChunkSummary<W, B, S>,
+        GapSlice<'a, W, B, S>,
+        ChunkSummary<W, B, S>,
+        ChunkSummary<W, B, S>,
+    )
     where
         'a: 'a,
     {
         let split_offset =
-            summary.line_breaks - (slice.has_trailing_newline() as usize);
+            summary.line_breaks() - (slice.has_trailing_newline() as usize);
 
         let ((rest, rest_summary), (last, last_summary)) =
             slice.split_at_offset(RawLineMetric(split_offset), summary);
@@ -453,9 +1321,14 @@ impl<const MAX_BYTES: usize> DoubleEndedUnitMetric<GapBuffer<MAX_BYTES>>
 
     #[inline]
     fn remainder<'a>(
-        chunk: GapSlice<'a>,
-        summary: &ChunkSummary,
-    ) -> (GapSlice<'a>, ChunkSummary, GapSlice<'a>, ChunkSummary)
+        chunk: GapSlice<'a, W, B, S>,
+        summary: &ChunkSummary<W, B, S>,
+    ) -> (
+        GapSlice<'a, W, B, S>,
+        ChunkSummary<W, B, S>,
+        GapSlice<'a, W, B, S>,
+        ChunkSummary<W, B, S>,
+    )
     where
         'a: 'a,
     {
@@ -463,113 +1336,184 @@ impl<const MAX_BYTES: usize> DoubleEndedUnitMetric<GapBuffer<MAX_BYTES>>
             (chunk, *summary, GapSlice::empty(), ChunkSummary::new())
         } else {
             let (rest, rest_summary, last, last_summary, _) =
-                <Self as DoubleEndedUnitMetric<GapBuffer<MAX_BYTES>>>::last_unit(chunk, summary);
+                <Self as DoubleEndedUnitMetric<
+                    RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>,
+                >>::last_unit(chunk, summary);
 
             (rest, rest_summary, last, last_summary)
         }
     }
 }
 
-#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
-pub(super) struct LineMetric(pub(super) usize);
+#[cfg(feature = "char-metric")]
+pub use char_metric::CharMetric;
 
-impl Add for LineMetric {
-    type Output = Self;
+#[cfg(feature = "char-metric")]
+mod char_metric {
+    use super::*;
 
-    #[inline]
-    fn add(self, other: Self) -> Self {
-        Self(self.0 + other.0)
-    }
-}
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct CharMetric(pub usize);
 
-impl Sub for LineMetric {
-    type Output = Self;
+    impl Add for CharMetric {
+        type Output = Self;
 
-    #[inline]
-    fn sub(self, other: Self) -> Self {
-        Self(self.0 - other.0)
+        #[inline]
+        fn add(self, other: Self) -> Self {
+            Self(self.0 + other.0)
+        }
     }
-}
 
-impl AddAssign for LineMetric {
-    #[inline]
-    fn add_assign(&mut self, other: Self) {
-        self.0 += other.0
-    }
-}
+    impl Sub for CharMetric {
+        type Output = Self;
 
-impl SubAssign for LineMetric {
-    #[inline]
-    fn sub_assign(&mut self, other: Self) {
-        self.0 -= other.0
+        #[inline]
+        fn sub(self, other: Self) -> Self {
+            Self(self.0 - other.0)
+        }
     }
-}
 
-impl Metric<ChunkSummary> for LineMetric {
-    #[inline]
-    fn zero() -> Self {
-        Self(0)
+    impl AddAssign for CharMetric {
+        #[inline]
+        fn add_assign(&mut self, other: Self) {
+            self.0 += other.0
+        }
     }
 
-    #[inline]
-    fn one() -> Self {
-        Self(1)
+    impl SubAssign for CharMetric {
+        #[inline]
+        fn sub_assign(&mut self, other: Self) {
+            self.0 -= other.0
+        }
     }
 
-    #[inline]
-    fn measure(summary: &ChunkSummary) -> Self {
-        Self(summary.line_breaks)
+    impl<W: CharWeight, B: LineBreaks, S: MetricSet<Chars = Maintained>>
+        ToByteOffset<W, B, S> for CharMetric
+    {
+        #[inline]
+        fn to_byte_offset(&self, in_str: &str) -> usize {
+            convert::byte_of_char(in_str, self.0)
+        }
     }
-}
 
-impl<const MAX_BYTES: usize> UnitMetric<GapBuffer<MAX_BYTES>> for LineMetric {
-    #[inline]
-    fn first_unit<'a>(
-        chunk: GapSlice<'a>,
-        summary: &ChunkSummary,
-    ) -> (GapSlice<'a>, ChunkSummary, ChunkSummary, GapSlice<'a>, ChunkSummary)
-    where
-        'a: 'a,
+    impl<W: CharWeight, B: LineBreaks, S: MetricSet<Chars = Maintained>>
+        SummaryUpTo<W, B, S> for CharMetric
     {
-        let (mut first, mut first_summary, advance, rest, rest_summary) =
-            <RawLineMetric as UnitMetric<GapBuffer<MAX_BYTES>>>::first_unit(
-                chunk, summary,
-            );
-
-        first_summary = first.truncate_trailing_line_break(first_summary);
-
-        (first, first_summary, advance, rest, rest_summary)
+        #[cfg_attr(
+            not(any(
+                feature = "lines",
+                feature = "utf16-metric",
+                feature = "custom-metric",
+                feature = "width-metric"
+            )),
+            allow(unused_variables)
+        )]
+        #[inline]
+        fn up_to(
+            in_str: &str,
+            str_summary: ChunkSummary<W, B, S>,
+            Self(char_offset): Self,
+            byte_offset: usize,
+        ) -> ChunkSummary<W, B, S> {
+            ChunkSummary {
+                bytes: to_count(byte_offset),
+
+                #[cfg(feature = "lines")]
+                line_breaks: S::Lines::of(|| {
+                    count::line_breaks_up_to::<B>(
+                        in_str,
+                        byte_offset,
+                        str_summary.line_breaks.get(),
+                    )
+                }),
+
+                chars: S::Chars::of(|| char_offset),
+
+                #[cfg(feature = "utf16-metric")]
+                utf16_code_units: S::Utf16CodeUnits::of(|| {
+                    count::utf16_code_units_up_to(
+                        in_str,
+                        byte_offset,
+                        str_summary.utf16_code_units.get(),
+                    )
+                }),
+
+                #[cfg(feature = "custom-metric")]
+                custom_units: to_count(count::custom_units_up_to::<W>(
+                    in_str,
+                    byte_offset,
+                    str_summary.custom_units(),
+                )),
+
+                #[cfg(feature = "width-metric")]
+                width: S::Width::of(|| {
+                    count::width_up_to(
+                        in_str,
+                        byte_offset,
+                        str_summary.width.get(),
+                    )
+                }),
+                combined: Combined::default(),
+                marker: PhantomData,
+            }
+        }
     }
-}
 
-impl<const MAX_BYTES: usize> DoubleEndedUnitMetric<GapBuffer<MAX_BYTES>>
-    for LineMetric
-{
-    #[inline]
-    fn last_unit<'a>(
-        chunk: GapSlice<'a>,
-        summary: &ChunkSummary,
-    ) -> (GapSlice<'a>, ChunkSummary, GapSlice<'a>, ChunkSummary, ChunkSummary)
-    where
-        'a: 'a,
+    impl<W: CharWeight, B: LineBreaks, S: MetricSet<Chars = Maintained>>
+        Metric<ChunkSummary<W, B, S>> for CharMetric
     {
-        let (rest, rest_summary, mut last, mut last_summary, advance) =
-            <RawLineMetric as DoubleEndedUnitMetric<GapBuffer<MAX_BYTES>>>::last_unit(chunk, summary);
+        #[inline]
+        fn zero() -> Self {
+            Self(0)
+        }
 
-        last_summary = last.truncate_trailing_line_break(last_summary);
+        #[inline]
+        fn one() -> Self {
+            Self(1)
+        }
 
-        (rest, rest_summary, last, last_summary, advance)
+        #[inline]
+        fn measure(summary: &ChunkSummary<W, B, S>) -> Self {
+            Self(summary.chars())
+        }
     }
 
-    #[inline]
-    fn remainder<'a>(
-        chunk: GapSlice<'a>,
-        summary: &ChunkSummary,
-    ) -> (GapSlice<'a>, ChunkSummary, GapSlice<'a>, ChunkSummary)
-    where
-        'a: 'a,
+    impl<
+            const MAX_BYTES: usize,
+            W: CharWeight,
+            B: LineBreaks,
+            const MIN_FILL: usize,
+            S: MetricSet<Chars = Maintained>,
+        > SlicingMetric<RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>>
+        for CharMetric
     {
-        <RawLineMetric as DoubleEndedUnitMetric<GapBuffer<MAX_BYTES>>>::remainder(chunk, summary)
+        #[track_caller]
+        #[inline]
+        fn slice_up_to<'a>(
+            chunk: GapSlice<'a, W, B, S>,
+            char_offset: Self,
+            &summary: &ChunkSummary<W, B, S>,
+        ) -> (GapSlice<'a, W, B, S>, ChunkSummary<W, B, S>)
+        where
+            'a: 'a,
+        {
+            let (left, _) = chunk.split_at_offset(char_offset, summary);
+            left
+        }
+
+        #[track_caller]
+        #[inline]
+        fn slice_from<'a>(
+            chunk: GapSlice<'a, W, B, S>,
+            char_offset: Self,
+            &summary: &ChunkSummary<W, B, S>,
+        ) -> (GapSlice<'a, W, B, S>, ChunkSummary<W, B, S>)
+        where
+            'a: 'a,
+        {
+            let (_, right) = chunk.split_at_offset(char_offset, summary);
+            right
+        }
     }
 }
 
@@ -615,7 +1559,12 @@ mod utf16_metric {
         }
     }
 
-    impl ToByteOffset for Utf16Metric {
+    impl<
+            W: CharWeight,
+            B: LineBreaks,
+            S: MetricSet<Utf16CodeUnits = Maintained>,
+        > ToByteOffset<W, B, S> for Utf16Metric
+    {
         #[track_caller]
         #[inline]
         fn to_byte_offset(&self, in_str: &str) -> usize {
@@ -626,29 +1575,80 @@ mod utf16_metric {
         }
     }
 
-    impl SummaryUpTo for Utf16Metric {
+    impl<
+            W: CharWeight,
+            B: LineBreaks,
+            S: MetricSet<Utf16CodeUnits = Maintained>,
+        > SummaryUpTo<W, B, S> for Utf16Metric
+    {
+        #[cfg_attr(
+            not(any(
+                feature = "lines",
+                feature = "char-metric",
+                feature = "custom-metric",
+                feature = "width-metric"
+            )),
+            allow(unused_variables)
+        )]
         #[inline]
         fn up_to(
             in_str: &str,
-            str_summary: ChunkSummary,
+            str_summary: ChunkSummary<W, B, S>,
             Self(utf16_code_unit_offset): Self,
             byte_offset: usize,
-        ) -> ChunkSummary {
+        ) -> ChunkSummary<W, B, S> {
             ChunkSummary {
-                bytes: byte_offset,
-
-                line_breaks: count::line_breaks_up_to(
+                bytes: to_count(byte_offset),
+
+                #[cfg(feature = "lines")]
+                line_breaks: S::Lines::of(|| {
+                    count::line_breaks_up_to::<B>(
+                        in_str,
+                        byte_offset,
+                        str_summary.line_breaks.get(),
+                    )
+                }),
+
+                #[cfg(feature = "char-metric")]
+                chars: S::Chars::of(|| {
+                    count::chars_up_to(
+                        in_str,
+                        byte_offset,
+                        str_summary.chars.get(),
+                    )
+                }),
+
+                utf16_code_units: S::Utf16CodeUnits::of(|| {
+                    utf16_code_unit_offset
+                }),
+
+                #[cfg(feature = "custom-metric")]
+                custom_units: to_count(count::custom_units_up_to::<W>(
                     in_str,
                     byte_offset,
-                    str_summary.line_breaks,
-                ),
-
-                utf16_code_units: utf16_code_unit_offset,
+                    str_summary.custom_units(),
+                )),
+
+                #[cfg(feature = "width-metric")]
+                width: S::Width::of(|| {
+                    count::width_up_to(
+                        in_str,
+                        byte_offset,
+                        str_summary.width.get(),
+                    )
+                }),
+                combined: Combined::default(),
+                marker: PhantomData,
             }
         }
     }
 
-    impl Metric<ChunkSummary> for Utf16Metric {
+    impl<
+            W: CharWeight,
+            B: LineBreaks,
+            S: MetricSet<Utf16CodeUnits = Maintained>,
+        > Metric<ChunkSummary<W, B, S>> for Utf16Metric
+    {
         #[inline]
         fn zero() -> Self {
             Self(0)
@@ -660,21 +1660,27 @@ mod utf16_metric {
         }
 
         #[inline]
-        fn measure(summary: &ChunkSummary) -> Self {
-            Self(summary.utf16_code_units)
+        fn measure(summary: &ChunkSummary<W, B, S>) -> Self {
+            Self(summary.utf16_code_units())
         }
     }
 
-    impl<const MAX_BYTES: usize> SlicingMetric<GapBuffer<MAX_BYTES>>
+    impl<
+            const MAX_BYTES: usize,
+            W: CharWeight,
+            B: LineBreaks,
+            const MIN_FILL: usize,
+            S: MetricSet<Utf16CodeUnits = Maintained>,
+        > SlicingMetric<RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>>
         for Utf16Metric
     {
         #[track_caller]
         #[inline]
         fn slice_up_to<'a>(
-            chunk: GapSlice<'a>,
+            chunk: GapSlice<'a, W, B, S>,
             utf16_code_unit_offset: Self,
-            &summary: &ChunkSummary,
-        ) -> (GapSlice<'a>, ChunkSummary)
+            &summary: &ChunkSummary<W, B, S>,
+        ) -> (GapSlice<'a, W, B, S>, ChunkSummary<W, B, S>)
         where
             'a: 'a,
         {
@@ -686,10 +1692,10 @@ mod utf16_metric {
         #[track_caller]
         #[inline]
         fn slice_from<'a>(
-            chunk: GapSlice<'a>,
+            chunk: GapSlice<'a, W, B, S>,
             utf16_code_unit_offset: Self,
-            &summary: &ChunkSummary,
-        ) -> (GapSlice<'a>, ChunkSummary)
+            &summary: &ChunkSummary<W, B, S>,
+        ) -> (GapSlice<'a, W, B, S>, ChunkSummary<W, B, S>)
         where
             'a: 'a,
         {
@@ -700,27 +1706,396 @@ mod utf16_metric {
     }
 }
 
-use str_utils::*;
+#[cfg(feature = "custom-metric")]
+pub use custom_metric::CustomMetric;
+
+#[cfg(feature = "custom-metric")]
+mod custom_metric {
+    //! A metric whose units are defined by the user.
+    //!
+    //! Every `char` is given a weight by the [`CharWeight`] of the rope, and
+    //! the custom measure of a piece of text is the sum of the weights of its
+    //! `char`s. Since that's additive it can be stored in the summaries and
+    //! aggregated up the tree just like the number of bytes or line breaks.
+
+    use super::*;
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct CustomMetric(pub usize);
+
+    impl Add<Self> for CustomMetric {
+        type Output = Self;
+
+        #[inline]
+        fn add(self, other: Self) -> Self {
+            Self(self.0 + other.0)
+        }
+    }
+
+    impl Sub for CustomMetric {
+        type Output = Self;
+
+        #[inline]
+        fn sub(self, other: Self) -> Self {
+            Self(self.0 - other.0)
+        }
+    }
+
+    impl AddAssign for CustomMetric {
+        #[inline]
+        fn add_assign(&mut self, other: Self) {
+            self.0 += other.0
+        }
+    }
+
+    impl SubAssign for CustomMetric {
+        #[inline]
+        fn sub_assign(&mut self, other: Self) {
+            self.0 -= other.0
+        }
+    }
+
+    impl<W: CharWeight, B: LineBreaks, S: MetricSet> ToByteOffset<W, B, S>
+        for CustomMetric
+    {
+        #[inline]
+        fn to_byte_offset(&self, in_str: &str) -> usize {
+            convert::byte_of_custom_unit::<W>(in_str, self.0)
+        }
+    }
+
+    impl<W: CharWeight, B: LineBreaks, S: MetricSet> SummaryUpTo<W, B, S>
+        for CustomMetric
+    {
+        #[inline]
+        fn up_to(
+            in_str: &str,
+            str_summary: ChunkSummary<W, B, S>,
+            _: Self,
+            byte_offset: usize,
+        ) -> ChunkSummary<W, B, S> {
+            // The offset can fall inside the weight of a `char`, in which
+            // case the byte offset is the one right after that `char` and
+            // the custom units have to be recounted up to it.
+            ChunkSummary {
+                bytes: to_count(byte_offset),
+
+                #[cfg(feature = "lines")]
+                line_breaks: S::Lines::of(|| {
+                    count::line_breaks_up_to::<B>(
+                        in_str,
+                        byte_offset,
+                        str_summary.line_breaks.get(),
+                    )
+                }),
+
+                #[cfg(feature = "char-metric")]
+                chars: S::Chars::of(|| {
+                    count::chars_up_to(
+                        in_str,
+                        byte_offset,
+                        str_summary.chars.get(),
+                    )
+                }),
+
+                #[cfg(feature = "utf16-metric")]
+                utf16_code_units: S::Utf16CodeUnits::of(|| {
+                    count::utf16_code_units_up_to(
+                        in_str,
+                        byte_offset,
+                        str_summary.utf16_code_units.get(),
+                    )
+                }),
+
+                custom_units: to_count(count::custom_units_up_to::<W>(
+                    in_str,
+                    byte_offset,
+                    str_summary.custom_units(),
+                )),
+
+                #[cfg(feature = "width-metric")]
+                width: S::Width::of(|| {
+                    count::width_up_to(
+                        in_str,
+                        byte_offset,
+                        str_summary.width.get(),
+                    )
+                }),
+                combined: Combined::default(),
+                marker: PhantomData,
+            }
+        }
+    }
+
+    impl<W: CharWeight, B: LineBreaks, S: MetricSet>
+        Metric<ChunkSummary<W, B, S>> for CustomMetric
+    {
+        #[inline]
+        fn zero() -> Self {
+            Self(0)
+        }
+
+        #[inline]
+        fn one() -> Self {
+            Self(1)
+        }
+
+        #[inline]
+        fn measure(summary: &ChunkSummary<W, B, S>) -> Self {
+            Self(summary.custom_units())
+        }
+    }
+
+    impl<
+            const MAX_BYTES: usize,
+            W: CharWeight,
+            B: LineBreaks,
+            const MIN_FILL: usize,
+            S: MetricSet,
+        > SlicingMetric<RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>>
+        for CustomMetric
+    {
+        #[track_caller]
+        #[inline]
+        fn slice_up_to<'a>(
+            chunk: GapSlice<'a, W, B, S>,
+            custom_offset: Self,
+            &summary: &ChunkSummary<W, B, S>,
+        ) -> (GapSlice<'a, W, B, S>, ChunkSummary<W, B, S>)
+        where
+            'a: 'a,
+        {
+            let (left, _) = chunk.split_at_offset(custom_offset, summary);
+            left
+        }
+
+        #[track_caller]
+        #[inline]
+        fn slice_from<'a>(
+            chunk: GapSlice<'a, W, B, S>,
+            custom_offset: Self,
+            &summary: &ChunkSummary<W, B, S>,
+        ) -> (GapSlice<'a, W, B, S>, ChunkSummary<W, B, S>)
+        where
+            'a: 'a,
+        {
+            let (_, right) = chunk.split_at_offset(custom_offset, summary);
+            right
+        }
+    }
+}
+
+#[cfg(feature = "width-metric")]
+pub use width_metric::WidthMetric;
+
+#[cfg(feature = "width-metric")]
+mod width_metric {
+    //! A metric measuring the display width of the text, i.e. the number of
+    //! columns it takes up in a terminal.
+    //!
+    //! The width of a piece of text is the sum of the widths of its `char`s
+    //! as given by the [unicode-width](https://docs.rs/unicode-width) crate,
+    //! with control characters counting as zero. That's additive, so it can
+    //! be stored in the summaries and aggregated up the tree. Note that it
+    //! can differ from `UnicodeWidthStr::width()` for the few sequences
+    //! (e.g. some emoji) whose width depends on the surrounding `char`s.
+
+    use unicode_width::UnicodeWidthChar;
+
+    use super::*;
+
+    /// Returns the display width of a `char`.
+    #[inline]
+    pub(super) fn char_width(ch: char) -> usize {
+        ch.width().unwrap_or(0)
+    }
+
+    #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+    pub struct WidthMetric(pub usize);
+
+    impl Add<Self> for WidthMetric {
+        type Output = Self;
+
+        #[inline]
+        fn add(self, other: Self) -> Self {
+            Self(self.0 + other.0)
+        }
+    }
+
+    impl Sub for WidthMetric {
+        type Output = Self;
+
+        #[inline]
+        fn sub(self, other: Self) -> Self {
+            Self(self.0 - other.0)
+        }
+    }
+
+    impl AddAssign for WidthMetric {
+        #[inline]
+        fn add_assign(&mut self, other: Self) {
+            self.0 += other.0
+        }
+    }
+
+    impl SubAssign for WidthMetric {
+        #[inline]
+        fn sub_assign(&mut self, other: Self) {
+            self.0 -= other.0
+        }
+    }
+
+    impl<W: CharWeight, B: LineBreaks, S: MetricSet<Width = Maintained>>
+        ToByteOffset<W, B, S> for WidthMetric
+    {
+        #[inline]
+        fn to_byte_offset(&self, in_str: &str) -> usize {
+            convert::byte_of_column(in_str, self.0)
+        }
+    }
+
+    impl<W: CharWeight, B: LineBreaks, S: MetricSet<Width = Maintained>>
+        SummaryUpTo<W, B, S> for WidthMetric
+    {
+        #[inline]
+        fn up_to(
+            in_str: &str,
+            str_summary: ChunkSummary<W, B, S>,
+            _: Self,
+            byte_offset: usize,
+        ) -> ChunkSummary<W, B, S> {
+            // The offset can fall inside a wide `char`, in which case the
+            // byte offset is the one right after that `char` and the width
+            // has to be recounted up to it.
+            ChunkSummary {
+                bytes: to_count(byte_offset),
+
+                #[cfg(feature = "lines")]
+                line_breaks: S::Lines::of(|| {
+                    count::line_breaks_up_to::<B>(
+                        in_str,
+                        byte_offset,
+                        str_summary.line_breaks.get(),
+                    )
+                }),
+
+                #[cfg(feature = "char-metric")]
+                chars: S::Chars::of(|| {
+                    count::chars_up_to(
+                        in_str,
+                        byte_offset,
+                        str_summary.chars.get(),
+                    )
+                }),
+
+                #[cfg(feature = "utf16-metric")]
+                utf16_code_units: S::Utf16CodeUnits::of(|| {
+                    count::utf16_code_units_up_to(
+                        in_str,
+                        byte_offset,
+                        str_summary.utf16_code_units.get(),
+                    )
+                }),
+
+                #[cfg(feature = "custom-metric")]
+                custom_units: to_count(count::custom_units_up_to::<W>(
+                    in_str,
+                    byte_offset,
+                    str_summary.custom_units(),
+                )),
+
+                width: S::Width::of(|| {
+                    count::width_up_to(
+                        in_str,
+                        byte_offset,
+                        str_summary.width.get(),
+                    )
+                }),
+                combined: Combined::default(),
+                marker: PhantomData,
+            }
+        }
+    }
+
+    impl<W: CharWeight, B: LineBreaks, S: MetricSet<Width = Maintained>>
+        Metric<ChunkSummary<W, B, S>> for WidthMetric
+    {
+        #[inline]
+        fn zero() -> Self {
+            Self(0)
+        }
+
+        #[inline]
+        fn one() -> Self {
+            Self(1)
+        }
+
+        #[inline]
+        fn measure(summary: &ChunkSummary<W, B, S>) -> Self {
+            Self(summary.width())
+        }
+    }
+
+    impl<
+            const MAX_BYTES: usize,
+            W: CharWeight,
+            B: LineBreaks,
+            const MIN_FILL: usize,
+            S: MetricSet<Width = Maintained>,
+        > SlicingMetric<RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>>
+        for WidthMetric
+    {
+        #[track_caller]
+        #[inline]
+        fn slice_up_to<'a>(
+            chunk: GapSlice<'a, W, B, S>,
+            column: Self,
+            &summary: &ChunkSummary<W, B, S>,
+        ) -> (GapSlice<'a, W, B, S>, ChunkSummary<W, B, S>)
+        where
+            'a: 'a,
+        {
+            let (left, _) = chunk.split_at_offset(column, summary);
+            left
+        }
+
+        #[track_caller]
+        #[inline]
+        fn slice_from<'a>(
+            chunk: GapSlice<'a, W, B, S>,
+            column: Self,
+            &summary: &ChunkSummary<W, B, S>,
+        ) -> (GapSlice<'a, W, B, S>, ChunkSummary<W, B, S>)
+        where
+            'a: 'a,
+        {
+            let (_, right) = chunk.split_at_offset(column, summary);
+            right
+        }
+    }
+}
+
+pub(super) use str_utils::{convert, count};
 
 mod str_utils {
     #[cfg(not(miri))]
-    use str_indices::lines_lf as lines;
+    use str_indices::chars;
     #[cfg(all(not(miri), feature = "utf16-metric"))]
     use str_indices::utf16;
 
     pub mod count {
-        #[cfg(not(miri))]
+        #[cfg(all(not(miri), feature = "utf16-metric"))]
         use super::*;
 
         #[inline]
-        pub fn line_breaks(s: &str) -> usize {
+        pub fn chars(s: &str) -> usize {
             #[cfg(not(miri))]
             {
-                lines::count_breaks(s)
+                str_indices::chars::count(s)
             }
             #[cfg(miri)]
             {
-                s.bytes().filter(|&b| b == b'\n').count()
+                s.chars().count()
             }
         }
 
@@ -737,13 +2112,41 @@ mod str_utils {
             }
         }
 
+        #[cfg(feature = "custom-metric")]
+        #[inline]
+        pub fn custom_units<W: super::super::CharWeight>(s: &str) -> usize {
+            s.chars().map(W::weight).sum()
+        }
+
+        #[cfg(feature = "width-metric")]
+        #[inline]
+        pub fn width(s: &str) -> usize {
+            s.chars().map(super::super::width_metric::char_width).sum()
+        }
+
+        #[cfg(feature = "char-metric")]
+        #[inline(always)]
+        pub fn chars_up_to(
+            s: &str,
+            byte_offset: usize,
+            tot_chars: usize,
+        ) -> usize {
+            // Every byte of an ASCII string is a char.
+            if tot_chars == s.len() {
+                return byte_offset;
+            }
+
+            metric_up_to(s, byte_offset, tot_chars, chars)
+        }
+
+        #[cfg(feature = "lines")]
         #[inline(always)]
-        pub fn line_breaks_up_to(
+        pub fn line_breaks_up_to<B: super::super::LineBreaks>(
             s: &str,
             byte_offset: usize,
             tot_line_breaks: usize,
         ) -> usize {
-            metric_up_to(s, byte_offset, tot_line_breaks, line_breaks)
+            metric_up_to(s, byte_offset, tot_line_breaks, B::count_breaks)
         }
 
         #[cfg(feature = "utf16-metric")]
@@ -761,6 +2164,36 @@ mod str_utils {
             )
         }
 
+        #[cfg(feature = "custom-metric")]
+        #[inline(always)]
+        pub fn custom_units_up_to<W: super::super::CharWeight>(
+            s: &str,
+            byte_offset: usize,
+            tot_custom_units: usize,
+        ) -> usize {
+            metric_up_to(s, byte_offset, tot_custom_units, custom_units::<W>)
+        }
+
+        #[cfg(feature = "width-metric")]
+        #[inline(always)]
+        pub fn width_up_to(
+            s: &str,
+            byte_offset: usize,
+            tot_width: usize,
+        ) -> usize {
+            metric_up_to(s, byte_offset, tot_width, width)
+        }
+
+        #[cfg_attr(
+            not(any(
+                feature = "char-metric",
+                feature = "lines",
+                feature = "utf16-metric",
+                feature = "custom-metric",
+                feature = "width-metric"
+            )),
+            allow(dead_code)
+        )]
         #[inline(always)]
         fn metric_up_to(
             s: &str,
@@ -787,35 +2220,68 @@ mod str_utils {
         use super::*;
 
         #[inline]
-        pub fn byte_of_line(s: &str, line_offset: usize) -> usize {
+        pub fn byte_of_char(s: &str, char_offset: usize) -> usize {
             #[cfg(not(miri))]
             {
-                lines::to_byte_idx(s, line_offset)
+                chars::to_byte_idx(s, char_offset)
             }
 
             #[cfg(miri)]
             {
-                if line_offset == 0 {
-                    return 0;
+                s.char_indices()
+                    .nth(char_offset)
+                    .map_or(s.len(), |(idx, _)| idx)
+            }
+        }
+
+        #[cfg(feature = "lines")]
+        #[inline]
+        pub fn byte_of_line<B: super::super::LineBreaks>(
+            s: &str,
+            line_offset: usize,
+        ) -> usize {
+            B::byte_of_line(s, line_offset)
+        }
+
+        /// Returns the first byte offset at which the custom units of `s`
+        /// reach `custom_offset`. If the offset falls inside the weight of a
+        /// `char` this is the byte offset right after it.
+        #[cfg(feature = "custom-metric")]
+        #[inline]
+        pub fn byte_of_custom_unit<W: super::super::CharWeight>(
+            s: &str,
+            custom_offset: usize,
+        ) -> usize {
+            let mut seen = 0;
+
+            for (idx, ch) in s.char_indices() {
+                if seen >= custom_offset {
+                    return idx;
                 }
 
-                let mut seen = 0;
-                let mut stop = false;
-
-                s.bytes()
-                    .take_while(|&b| {
-                        !stop && {
-                            if b == b'\n' {
-                                seen += 1;
-                                if seen == line_offset {
-                                    stop = true;
-                                }
-                            }
-                            true
-                        }
-                    })
-                    .count()
+                seen += W::weight(ch);
             }
+
+            s.len()
+        }
+
+        /// Returns the first byte offset at which the display width of `s`
+        /// reaches `column`. If the column falls inside a wide `char` this is
+        /// the byte offset right after it.
+        #[cfg(feature = "width-metric")]
+        #[inline]
+        pub fn byte_of_column(s: &str, column: usize) -> usize {
+            let mut seen = 0;
+
+            for (idx, ch) in s.char_indices() {
+                if seen >= column {
+                    return idx;
+                }
+
+                seen += super::super::width_metric::char_width(ch);
+            }
+
+            s.len()
         }
 
         #[cfg(feature = "utf16-metric")]
@@ -1,12 +1,73 @@
+mod anchors;
+#[cfg(any(feature = "arbitrary", feature = "proptest"))]
+mod arbitrary;
+mod ascii_case;
+#[cfg(any(
+    feature = "lines",
+    feature = "char-metric",
+    feature = "utf16-metric"
+))]
+mod byte_counts;
+mod check_report;
+#[cfg(feature = "unicode-collation")]
+mod collation;
+mod combined;
+mod delta;
+mod diff;
+mod dirty;
+mod error;
 pub(crate) mod gap_buffer;
 pub(crate) mod gap_slice;
+mod history;
 pub(crate) mod iterators;
+mod join;
+pub(crate) mod leaf_bytes;
+mod line_breaks;
+mod line_ending;
 pub mod metrics;
+#[cfg(feature = "unicode-normalization")]
+mod normalization;
+#[cfg(feature = "lines")]
+pub(crate) mod patch;
 mod rope;
 mod rope_builder;
+mod rope_reader;
 mod rope_slice;
-mod utils;
+mod rope_stats;
+mod rope_writer;
+#[cfg(feature = "ropey")]
+mod ropey;
+mod search;
+#[cfg(feature = "serde")]
+mod serde;
+mod signature;
+mod snap;
+mod snapshot;
+mod tracked;
+#[cfg(feature = "tree-sitter")]
+pub(crate) mod tree_sitter;
+pub(crate) mod utils;
+mod versioned;
 
-pub use rope::Rope;
+pub use anchors::{Anchor, Gravity};
+pub use ascii_case::AsciiCaseText;
+pub use check_report::CheckReport;
+pub use delta::{Delta, DeltaOp};
+pub use error::Error;
+pub use join::JoinItem;
+pub use line_breaks::{LfBreaks, LineBreaks, UnicodeBreaks};
+pub use line_ending::LineEnding;
+#[cfg(feature = "unicode-normalization")]
+pub use normalization::NormalizationForm;
+pub(crate) use rope::CHUNK_MAX_BYTES;
+pub use rope::{LocalRope, Rope, RopeWith};
 pub use rope_builder::RopeBuilder;
+pub use rope_reader::RopeReader;
 pub use rope_slice::RopeSlice;
+pub use rope_stats::RopeStats;
+pub use rope_writer::RopeWriter;
+pub use search::SearchPattern;
+pub use signature::{BlockSignature, Signature};
+pub use snap::Snap;
+pub use tracked::Tracked;
+pub use versioned::Versioned;
@@ -0,0 +1,125 @@
+use unicode_normalization::{
+    is_nfc_quick,
+    is_nfd_quick,
+    is_nfkc_quick,
+    is_nfkd_quick,
+    IsNormalized,
+    UnicodeNormalization,
+};
+
+use super::iterators::Chars;
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use super::{RopeBuilder, RopeWith};
+use crate::tree::RefCounter;
+
+/// A Unicode normalization form, as defined in [Unicode Standard Annex
+/// #15](https://www.unicode.org/reports/tr15/).
+///
+/// Two strings that look the same to a user can be made of different
+/// sequences of code points (e.g. "é" can be a single code point or an "e"
+/// followed by a combining acute accent), so text should be normalized to
+/// the same form before being compared or hashed.
+#[cfg_attr(docsrs, doc(cfg(feature = "unicode-normalization")))]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NormalizationForm {
+    /// Canonical decomposition followed by canonical composition.
+    Nfc,
+
+    /// Canonical decomposition.
+    Nfd,
+
+    /// Compatibility decomposition followed by canonical composition.
+    Nfkc,
+
+    /// Compatibility decomposition.
+    Nfkd,
+}
+
+/// Returns `true` if the `char`s yielded by [`Chars`] are in the given
+/// normalization form.
+#[inline]
+pub(super) fn is_normalized<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chars: Chars<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    form: NormalizationForm,
+) -> bool {
+    let quick = match form {
+        NormalizationForm::Nfc => is_nfc_quick(chars.clone()),
+        NormalizationForm::Nfd => is_nfd_quick(chars.clone()),
+        NormalizationForm::Nfkc => is_nfkc_quick(chars.clone()),
+        NormalizationForm::Nfkd => is_nfkd_quick(chars.clone()),
+    };
+
+    match quick {
+        IsNormalized::Yes => true,
+        IsNormalized::No => false,
+        IsNormalized::Maybe => match form {
+            NormalizationForm::Nfc => chars.clone().eq(chars.nfc()),
+            NormalizationForm::Nfd => chars.clone().eq(chars.nfd()),
+            NormalizationForm::Nfkc => chars.clone().eq(chars.nfkc()),
+            NormalizationForm::Nfkd => chars.clone().eq(chars.nfkd()),
+        },
+    }
+}
+
+/// Builds a new [`Rope`] with the `char`s yielded by [`Chars`] converted to
+/// the given normalization form.
+///
+/// The normalization works on the stream of `char`s, so combining sequences
+/// split across chunks are handled like any other.
+#[inline]
+pub(super) fn normalize<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chars: Chars<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    form: NormalizationForm,
+) -> RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+    match form {
+        NormalizationForm::Nfc => build(chars.nfc()),
+        NormalizationForm::Nfd => build(chars.nfd()),
+        NormalizationForm::Nfkc => build(chars.nfkc()),
+        NormalizationForm::Nfkd => build(chars.nfkd()),
+    }
+}
+
+#[inline]
+fn build<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chars: impl Iterator<Item = char>,
+) -> RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+    let mut builder =
+        RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+
+    let mut buf = String::with_capacity(MAX_BYTES);
+
+    for ch in chars {
+        if buf.len() + ch.len_utf8() > MAX_BYTES {
+            builder.append(&buf);
+            buf.clear();
+        }
+
+        buf.push(ch);
+    }
+
+    builder.append(&buf);
+
+    builder.build()
+}
@@ -0,0 +1,451 @@
+//! Parsing and applying patches in the unified diff format.
+
+use core::str::FromStr;
+
+use super::delta::Delta;
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, Maintained, MetricSet};
+use super::RopeWith;
+use crate::tree::RefCounter;
+
+/// A patch in the [unified diff][unified] format, parsed into a list of
+/// hunks which can be applied to (or unapplied from) a
+/// [`Rope`](super::Rope).
+///
+/// Only the hunks are parsed: everything outside of them, like the `---` and
+/// `+++` file headers or the `diff` and `index` lines emitted by version
+/// control systems, is ignored.
+///
+/// The hunks are applied strictly at the line numbers given in their headers:
+/// both the context lines and the removed lines have to match the text of the
+/// `Rope` exactly, or the patch is rejected without modifying it.
+///
+/// # Examples
+///
+/// ```
+/// # use crop::{patch::Patch, Rope};
+/// #
+/// let patch = "\
+/// --- a/greeting.txt
+/// +++ b/greeting.txt
+/// @@ -1,3 +1,3 @@
+///  Hello
+/// -Earth
+/// +Saturn
+///  !
+/// "
+/// .parse::<Patch>()
+/// .unwrap();
+///
+/// let mut r = Rope::from("Hello\nEarth\n!\n");
+///
+/// let delta = patch.apply(&mut r).unwrap();
+/// assert_eq!(r, "Hello\nSaturn\n!\n");
+/// assert_eq!(delta.base_len(), "Hello\nEarth\n!\n".len());
+///
+/// patch.unapply(&mut r).unwrap();
+/// assert_eq!(r, "Hello\nEarth\n!\n");
+/// ```
+///
+/// [unified]: https://www.gnu.org/software/diffutils/manual/html_node/Unified-Format.html
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Patch {
+    hunks: Vec<Hunk>,
+}
+
+/// A single hunk of a [`Patch`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Hunk {
+    /// The line number of the first line of the old text, as written in the
+    /// hunk header.
+    old_start: usize,
+
+    /// The number of lines of the old text.
+    old_len: usize,
+
+    /// The line number of the first line of the new text, as written in the
+    /// hunk header.
+    new_start: usize,
+
+    /// The number of lines of the new text.
+    new_len: usize,
+
+    lines: Vec<HunkLine>,
+}
+
+/// A line of a [`Hunk`], including its line terminator unless the line is
+/// the last one of a text with no trailing newline.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum HunkLine {
+    Context(String),
+    Delete(String),
+    Insert(String),
+}
+
+/// The error type returned when parsing, applying or unapplying a
+/// [`Patch`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PatchError {
+    /// A line of the patch couldn't be parsed, either because a hunk header
+    /// was malformed, because a hunk had fewer or more lines than its header
+    /// announced or because a "no newline" marker didn't follow the last line
+    /// of the old or new text.
+    Malformed {
+        /// The index of the offending line in the patch, starting from 0.
+        line: usize,
+    },
+
+    /// A context or removed line of a hunk didn't match the text of the
+    /// `Rope`.
+    ContextMismatch {
+        /// The index of the hunk in the patch, starting from 0.
+        hunk: usize,
+
+        /// The index of the line of the `Rope` that didn't match.
+        line: usize,
+    },
+
+    /// A hunk started before the end of the previous one.
+    OverlappingHunks {
+        /// The index of the hunk in the patch, starting from 0.
+        hunk: usize,
+    },
+}
+
+impl core::fmt::Display for PatchError {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match *self {
+            PatchError::Malformed { line } => {
+                write!(f, "malformed patch at line {line}")
+            },
+
+            PatchError::ContextMismatch { hunk, line } => {
+                write!(f, "hunk {hunk} doesn't match the text at line {line}")
+            },
+
+            PatchError::OverlappingHunks { hunk } => {
+                write!(f, "hunk {hunk} overlaps the previous one")
+            },
+        }
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+impl Patch {
+    /// Applies the `Patch` to the `Rope`, returning the [`Delta`] that was
+    /// applied to it.
+    ///
+    /// If the patch doesn't match the text of the `Rope` an error is returned
+    /// and the `Rope` is left untouched.
+    #[inline]
+    pub fn apply<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    >(
+        &self,
+        rope: &mut RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> Result<Delta, PatchError> {
+        let delta = self.delta(rope)?;
+        delta.apply(rope);
+        Ok(delta)
+    }
+
+    /// Returns the [`Delta`] which applies the `Patch` to the `Rope`, without
+    /// modifying it.
+    #[inline]
+    pub fn delta<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    >(
+        &self,
+        rope: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> Result<Delta, PatchError> {
+        let mut delta = Delta::new();
+
+        // The byte offset and the line index just after the end of the last
+        // hunk.
+        let mut offset = 0;
+        let mut min_line = 0;
+
+        for (hunk_idx, hunk) in self.hunks.iter().enumerate() {
+            // A hunk which doesn't remove any lines gives the line after
+            // which its text is inserted instead of the first line it
+            // removes.
+            let start_line = if hunk.old_len == 0 {
+                hunk.old_start
+            } else {
+                hunk.old_start.saturating_sub(1)
+            };
+
+            if start_line < min_line {
+                return Err(PatchError::OverlappingHunks { hunk: hunk_idx });
+            }
+
+            if start_line > rope.line_len() {
+                return Err(PatchError::ContextMismatch {
+                    hunk: hunk_idx,
+                    line: start_line,
+                });
+            }
+
+            let start = rope.byte_of_line(start_line);
+
+            delta.retain(start - offset);
+
+            offset = start;
+
+            let mut line = start_line;
+
+            for hunk_line in &hunk.lines {
+                let old_text = match hunk_line {
+                    HunkLine::Context(text) | HunkLine::Delete(text) => text,
+                    HunkLine::Insert(text) => {
+                        delta.insert(text);
+                        continue;
+                    },
+                };
+
+                let end = offset + old_text.len();
+
+                // A line with no trailing newline has to be the last one.
+                let matches = end <= rope.byte_len()
+                    && rope.is_char_boundary(end)
+                    && rope.byte_slice(offset..end) == old_text.as_str()
+                    && (old_text.ends_with('\n') || end == rope.byte_len());
+
+                if !matches {
+                    return Err(PatchError::ContextMismatch {
+                        hunk: hunk_idx,
+                        line,
+                    });
+                }
+
+                if let HunkLine::Context(_) = hunk_line {
+                    delta.retain(old_text.len());
+                } else {
+                    delta.delete(old_text.len());
+                }
+
+                offset = end;
+                line += 1;
+            }
+
+            min_line = line;
+        }
+
+        delta.retain(rope.byte_len() - offset);
+
+        Ok(delta)
+    }
+
+    /// Returns `true` if the `Patch` doesn't contain any hunks.
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.hunks.is_empty()
+    }
+
+    /// Parses a `Patch` from the text of a unified diff.
+    ///
+    /// Everything outside of the hunks is ignored. Within a hunk, a line
+    /// starting with `\` (like `\ No newline at end of file`) means that the
+    /// line before it has no trailing newline, which is only accepted after
+    /// the last line of the old or new text. An empty line is treated as an
+    /// empty context line since some tools strip the leading space of those.
+    #[inline]
+    pub fn parse(text: &str) -> Result<Self, PatchError> {
+        let mut hunks = Vec::new();
+
+        let mut lines = text.split_inclusive('\n').enumerate().peekable();
+
+        while let Some((header_idx, header)) = lines.next() {
+            if !header.starts_with("@@") {
+                continue;
+            }
+
+            let (old_start, old_len, new_start, new_len) =
+                parse_header(header)
+                    .ok_or(PatchError::Malformed { line: header_idx })?;
+
+            let mut hunk = Hunk {
+                old_start,
+                old_len,
+                new_start,
+                new_len,
+                lines: Vec::new(),
+            };
+
+            let (mut old_left, mut new_left) = (old_len, new_len);
+
+            let mut last_idx = header_idx;
+
+            while let Some(&(idx, line)) = lines.peek() {
+                // The "no newline" marker can only follow the last line of
+                // the old or new text, once.
+                if line.starts_with('\\') {
+                    let text = match hunk.lines.last_mut() {
+                        Some(HunkLine::Context(text))
+                            if old_left == 0 && new_left == 0 =>
+                        {
+                            text
+                        },
+                        Some(HunkLine::Delete(text)) if old_left == 0 => text,
+                        Some(HunkLine::Insert(text)) if new_left == 0 => text,
+                        _ => return Err(PatchError::Malformed { line: idx }),
+                    };
+                    if text.pop() != Some('\n') {
+                        return Err(PatchError::Malformed { line: idx });
+                    }
+                    lines.next();
+                    continue;
+                }
+
+                if old_left == 0 && new_left == 0 {
+                    break;
+                }
+
+                let (marker, content) = match line.as_bytes().first() {
+                    Some(b'\n' | b'\r') => (b' ', line),
+                    Some(&marker) => (marker, &line[1..]),
+                    None => {
+                        unreachable!("split_inclusive yields no empty lines")
+                    },
+                };
+
+                let mut content =
+                    content.strip_suffix('\n').unwrap_or(content).to_owned();
+                content.push('\n');
+
+                let hunk_line = match marker {
+                    b' ' if old_left > 0 && new_left > 0 => {
+                        old_left -= 1;
+                        new_left -= 1;
+                        HunkLine::Context(content)
+                    },
+                    b'-' if old_left > 0 => {
+                        old_left -= 1;
+                        HunkLine::Delete(content)
+                    },
+                    b'+' if new_left > 0 => {
+                        new_left -= 1;
+                        HunkLine::Insert(content)
+                    },
+                    _ => return Err(PatchError::Malformed { line: idx }),
+                };
+
+                hunk.lines.push(hunk_line);
+                last_idx = idx;
+                lines.next();
+            }
+
+            if old_left > 0 || new_left > 0 {
+                return Err(PatchError::Malformed { line: last_idx + 1 });
+            }
+
+            hunks.push(hunk);
+        }
+
+        Ok(Self { hunks })
+    }
+
+    /// Returns the `Patch` which undoes this one, i.e. the one whose hunks
+    /// remove the lines added by this `Patch` and add back the lines it
+    /// removes.
+    #[inline]
+    pub fn reverse(&self) -> Self {
+        let hunks = self
+            .hunks
+            .iter()
+            .map(|hunk| Hunk {
+                old_start: hunk.new_start,
+                old_len: hunk.new_len,
+                new_start: hunk.old_start,
+                new_len: hunk.old_len,
+                lines: hunk
+                    .lines
+                    .iter()
+                    .map(|line| match line {
+                        HunkLine::Context(text) => {
+                            HunkLine::Context(text.clone())
+                        },
+                        HunkLine::Delete(text) => {
+                            HunkLine::Insert(text.clone())
+                        },
+                        HunkLine::Insert(text) => {
+                            HunkLine::Delete(text.clone())
+                        },
+                    })
+                    .collect(),
+            })
+            .collect();
+
+        Self { hunks }
+    }
+
+    /// Unapplies the `Patch` from the `Rope`, i.e. applies its
+    /// [`reverse()`](Self::reverse()), returning the [`Delta`] that was
+    /// applied to it.
+    ///
+    /// If the patch doesn't match the text of the `Rope` an error is returned
+    /// and the `Rope` is left untouched.
+    #[inline]
+    pub fn unapply<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet<Lines = Maintained>,
+    >(
+        &self,
+        rope: &mut RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> Result<Delta, PatchError> {
+        self.reverse().apply(rope)
+    }
+}
+
+impl FromStr for Patch {
+    type Err = PatchError;
+
+    #[inline]
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        Self::parse(text)
+    }
+}
+
+/// Parses a hunk header of the form `@@ -l,s +l,s @@`, where the lengths
+/// default to 1 when they're omitted.
+#[inline]
+fn parse_header(header: &str) -> Option<(usize, usize, usize, usize)> {
+    let ranges = header.strip_prefix("@@ -")?.split_once(" @@")?.0;
+
+    let (old, new) = ranges.split_once(" +")?;
+
+    let (old_start, old_len) = parse_range(old)?;
+
+    let (new_start, new_len) = parse_range(new)?;
+
+    Some((old_start, old_len, new_start, new_len))
+}
+
+/// Parses a range of the form `l,s` or `l`, which can only start at line 0
+/// if it's empty.
+#[inline]
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    let (start, len) = match range.split_once(',') {
+        Some((start, len)) => (start.parse().ok()?, len.parse().ok()?),
+        None => (range.parse().ok()?, 1),
+    };
+
+    (start > 0 || len == 0).then_some((start, len))
+}
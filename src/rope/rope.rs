@@ -1,12 +1,67 @@
-use core::ops::RangeBounds;
+use core::ops::{Range, RangeBounds};
 
-use super::gap_buffer::GapBuffer;
-use super::iterators::{Bytes, Chars, Chunks, Lines, RawLines};
-use super::metrics::{ByteMetric, RawLineMetric};
+use super::anchors::Gravity;
+use super::ascii_case::{chunks_eq_ignore_ascii_case, AsciiCaseText};
+use super::combined;
+use super::delta::Delta;
+use super::diff;
+use super::error::*;
+use super::gap_buffer::RawGapBuffer;
+use super::iterators::{Bytes, Chars, ChunkSegments, Chunks, RollingHashes};
+#[cfg(feature = "lines")]
+use super::iterators::{
+    Lines,
+    LinesInByteRange,
+    LinesWithOffsets,
+    Paragraphs,
+    RawLines,
+};
+use super::leaf_bytes::LeafBytes;
+use super::line_breaks::{LfBreaks, LineBreaks};
+use super::line_ending::{write_with_line_ending, LineEnding};
+#[cfg(feature = "lines")]
+use super::metrics::sealed::Sealed as _;
+#[cfg(feature = "char-metric")]
+use super::metrics::CharMetric;
+#[cfg(any(
+    feature = "lines",
+    feature = "char-metric",
+    feature = "utf16-metric",
+    feature = "width-metric"
+))]
+use super::metrics::Maintained;
+#[cfg(feature = "lines")]
+use super::metrics::RawLineMetric;
+use super::metrics::{
+    AllMetrics,
+    ByteMetric,
+    CharWeight,
+    ChunkSummary,
+    MetricSet,
+    ZeroWeight,
+};
+use super::search::{self, SearchPattern};
+use super::signature::{self, Signature};
+use super::snap::{snap_range, Snap};
+use super::snapshot;
 use super::utils::{panic_messages as panic, *};
-use super::RopeSlice;
+use super::{
+    CheckReport,
+    JoinItem,
+    RopeBuilder,
+    RopeReader,
+    RopeSlice,
+    RopeStats,
+    RopeWriter,
+};
 use crate::range_bounds_to_start_end;
-use crate::tree::Tree;
+use crate::tree::{
+    AtomicCounter,
+    InvariantViolation,
+    LocalCounter,
+    RefCounter,
+    Tree,
+};
 
 #[cfg(any(test, feature = "arity_4"))]
 const ARITY: usize = 4;
@@ -15,27 +70,128 @@ const ARITY: usize = 4;
 const ARITY: usize = 16;
 
 #[cfg(any(test, feature = "small_chunks"))]
-const CHUNK_MAX_BYTES: usize = 4;
+pub(crate) const CHUNK_MAX_BYTES: usize = 4;
 
 #[cfg(not(any(test, feature = "small_chunks")))]
-const CHUNK_MAX_BYTES: usize = 2048;
+pub(crate) const CHUNK_MAX_BYTES: usize = 2048;
 
-pub(super) type RopeChunk = GapBuffer<CHUNK_MAX_BYTES>;
+pub(crate) const CHUNK_MIN_FILL: usize = 25;
 
-/// A UTF-8 text rope.
+pub(super) type RopeChunk<
+    W,
+    B,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S,
+> = RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>;
+
+/// A UTF-8 text rope whose nodes are reference counted with `C`.
+///
+/// This is what both [`Rope`] and [`LocalRope`] are aliases of, and is only
+/// needed to write code that works with either of them, to measure the text
+/// with a [`CharWeight`] `W`, to break its lines with [`LineBreaks`] `B`, or
+/// to store it in chunks of a different size or maintain fewer metrics. The
+/// weight only has an effect with the `custom-metric` feature enabled.
+///
+/// # Chunk size
+///
+/// The text is stored in chunks of up to `MAX_BYTES` bytes each, 2KB by
+/// default, which has to be between 4 and 65535. Smaller chunks make editing
+/// cheaper, while larger chunks make the rope more compact and are faster to
+/// build and iterate over.
+///
+/// A chunk left with less than `MIN_FILL` percent of `MAX_BYTES` by an edit,
+/// 25% by default, is merged with or refilled from one of its neighbors.
+/// `MIN_FILL` has to be between 1 and 50.
+///
+/// # Metrics
+///
+/// The [`MetricSet`] `S` chooses which of the metrics enabled by the crate's
+/// features the summaries maintain, all of them by default. The methods
+/// measuring the text in the units of a metric that `S` omits aren't
+/// available, so calling one of them is a compile error.
+///
+/// # Examples
+///
+/// ```
+/// # use crop::{tree::AtomicCounter, LfBreaks, RopeWith, ZeroWeight};
+/// #
+/// type SmallChunksRope = RopeWith<AtomicCounter, ZeroWeight, LfBreaks, 256>;
+///
+/// let r = SmallChunksRope::from("Hello Earth!\n".repeat(100));
+///
+/// assert_eq!(r.byte_len(), 1300);
+/// assert!(r.stats().leaf_count() >= 1300 / 256);
+/// ```
 #[derive(Clone, Default)]
-pub struct Rope {
-    pub(super) tree: Tree<{ Self::arity() }, RopeChunk>,
-    pub(super) has_trailing_newline: bool,
+pub struct RopeWith<
+    C: RefCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet = AllMetrics,
+> {
+    pub(super) tree: Tree<ARITY, RopeChunk<W, B, MAX_BYTES, MIN_FILL, S>, C>,
 }
 
-impl Rope {
+/// A UTF-8 text rope.
+///
+/// Its nodes are reference counted atomically, so a `Rope` (and any
+/// [`RopeSlice`] borrowed from it) can be sent to and shared with other
+/// threads.
+pub type Rope = RopeWith<AtomicCounter>;
+
+/// A UTF-8 text rope which can't be shared between threads.
+///
+/// Its nodes are reference counted non-atomically, which makes cloning and
+/// editing it cheaper than a [`Rope`] at the cost of it being neither `Send`
+/// nor `Sync`. It has the same API as a `Rope` except for
+/// [`par_find_all()`](Self::par_find_all()), which has to share the text
+/// between threads, and its slices and iterators are the same types
+/// parameterized by [`LocalCounter`]. It can also be wrapped in a
+/// [`Tracked`](super::Tracked) or a [`Versioned`](super::Versioned).
+///
+/// # Examples
+///
+/// ```
+/// # use crop::LocalRope;
+/// #
+/// let mut r = LocalRope::from("Hello Earth!");
+/// let snapshot = r.clone();
+///
+/// r.replace(6..11, "Mars");
+///
+/// assert_eq!(r, "Hello Mars!");
+/// assert_eq!(snapshot, "Hello Earth!");
+/// ```
+pub type LocalRope = RopeWith<LocalCounter>;
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[doc(hidden)]
     pub fn assert_invariants(&self) {
         self.tree.assert_invariants();
 
+        if combined::IS_ENABLED {
+            assert!(
+                self.has_identical_summary(),
+                "The summary of the Rope doesn't match the one of its chunks"
+            );
+        }
+
         if let Some(last) = self.chunks().next_back() {
-            assert_eq!(self.has_trailing_newline, last.ends_with('\n'));
+            assert_eq!(
+                self.has_trailing_newline(),
+                B::ends_with_line_break(last)
+            );
         } else {
             return;
         }
@@ -48,11 +204,12 @@ impl Rope {
 
         for chunk in leaves {
             assert!(
-                chunk.len() >= RopeChunk::chunk_min(),
+                chunk.len()
+                    >= RopeChunk::<W, B, MAX_BYTES, MIN_FILL, S>::chunk_min(),
                 "The chunk {:?} was supposed to contain at least {} bytes \
                  but actually contains {}",
                 chunk,
-                RopeChunk::chunk_min(),
+                RopeChunk::<W, B, MAX_BYTES, MIN_FILL, S>::chunk_min(),
                 chunk.len()
             );
 
@@ -60,6 +217,44 @@ impl Rope {
         }
     }
 
+    /// Panics if any of the invariants checked by
+    /// [`validate()`](Self::validate()) doesn't hold.
+    #[cfg(feature = "validate-utf8")]
+    #[track_caller]
+    #[inline]
+    fn assert_valid(&self) {
+        let violations = self.validate();
+
+        if !violations.is_empty() {
+            panic::invalid_rope(&violations);
+        }
+    }
+
+    /// Appends the text of `other` at the end of the `Rope`.
+    ///
+    /// Unlike inserting `other` as a string, this reuses the chunks of
+    /// `other` and runs in time logarithmic in the length of the two
+    /// `Rope`s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello ");
+    ///
+    /// r.append(Rope::from("Earth 🌎!"));
+    /// assert_eq!(r, "Hello Earth 🌎!");
+    /// ```
+    #[inline]
+    pub fn append(&mut self, other: Self) {
+        let len = self.byte_len();
+
+        self.tree.append(other.tree);
+
+        self.finish_edit(len..len);
+    }
+
     /// Returns the byte at `byte_index`.
     ///
     /// # Panics
@@ -107,6 +302,130 @@ impl Rope {
         self.tree.summary().bytes()
     }
 
+    /// Returns the byte offset at which the display width of the `Rope`
+    /// reaches the given column, i.e. the sum of the display widths of the
+    /// `char`s before it.
+    ///
+    /// If the column falls inside a wide `char` the byte offset right after
+    /// that `char` is returned. Together with [`line()`](Self::line()) this
+    /// gives the byte offset of a visual column within a line.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column is out of bounds (i.e. greater than
+    /// [`width()`](Self::width())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("a中b\n🦀!");
+    /// assert_eq!(r.byte_of_column(1), 1);
+    /// assert_eq!(r.byte_of_column(2), 4);
+    /// assert_eq!(r.line(1).byte_of_column(2), 4);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "width-metric")))]
+    #[cfg(feature = "width-metric")]
+    #[track_caller]
+    #[inline]
+    pub fn byte_of_column(&self, column: usize) -> usize
+    where
+        S: MetricSet<Width = Maintained>,
+    {
+        if column > self.width() {
+            panic::column_out_of_bounds(column, self.width());
+        }
+
+        let ByteMetric(byte_offset) =
+            self.tree.convert_measure(super::metrics::WidthMetric(column));
+
+        byte_offset
+    }
+
+    /// Returns the byte offset corresponding to the given offset in the
+    /// custom metric given by its [`CharWeight`](crate::CharWeight).
+    ///
+    /// If the offset falls inside the weight of a `char` the byte offset
+    /// right after that `char` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset is out of bounds (i.e. greater than
+    /// [`custom_len()`](Self::custom_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crop::{tree::AtomicCounter, CharWeight, RopeWith};
+    ///
+    /// // Count the curly braces.
+    /// #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    /// struct Braces;
+    ///
+    /// impl CharWeight for Braces {
+    ///     fn weight(ch: char) -> usize {
+    ///         matches!(ch, '{' | '}') as usize
+    ///     }
+    /// }
+    ///
+    /// let r = RopeWith::<AtomicCounter, Braces>::from("a { b } c");
+    /// assert_eq!(r.byte_of_custom_unit(1), 3);
+    /// assert_eq!(r.byte_of_custom_unit(2), 7);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "custom-metric")))]
+    #[cfg(feature = "custom-metric")]
+    #[track_caller]
+    #[inline]
+    pub fn byte_of_custom_unit(&self, custom_offset: usize) -> usize {
+        if custom_offset > self.custom_len() {
+            panic::custom_offset_out_of_bounds(
+                custom_offset,
+                self.custom_len(),
+            );
+        }
+
+        let ByteMetric(byte_offset) = self
+            .tree
+            .convert_measure(super::metrics::CustomMetric(custom_offset));
+
+        byte_offset
+    }
+
+    /// Returns the byte offset of the start of the given extended grapheme
+    /// cluster.
+    ///
+    /// With the `grapheme-count` feature the number of clusters of every
+    /// node of the B-tree is known, so only the chunk containing the cluster
+    /// has to be segmented, and this runs in logarithmic time as long as
+    /// there aren't many chunk boundaries before it where the clusters have
+    /// to be segmented again to be counted (see
+    /// [`grapheme_len()`](Self::grapheme_len())).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grapheme offset is out of bounds (i.e. greater than
+    /// [`grapheme_len()`](Self::grapheme_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🐻‍❄️");
+    ///
+    /// assert_eq!(r.byte_of_grapheme(4), 4);
+    /// assert_eq!(r.byte_of_grapheme(5), 6);
+    /// assert_eq!(r.byte_of_grapheme(6), r.byte_len());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "grapheme-count")))]
+    #[cfg(feature = "grapheme-count")]
+    #[track_caller]
+    #[inline]
+    pub fn byte_of_grapheme(&self, grapheme_offset: usize) -> usize {
+        self.byte_slice(..).byte_of_grapheme(grapheme_offset)
+    }
+
     /// Returns the byte offset of the start of the given line.
     ///
     /// # Panics
@@ -126,9 +445,14 @@ impl Rope {
     /// assert_eq!(r.byte_of_line(2), "ƒoo\nbär\r\n".len());
     /// assert_eq!(r.byte_of_line(r.line_len()), r.byte_len());
     /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
     #[track_caller]
     #[inline]
-    pub fn byte_of_line(&self, line_offset: usize) -> usize {
+    pub fn byte_of_line(&self, line_offset: usize) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
         if line_offset > self.line_len() {
             panic::line_offset_out_of_bounds(line_offset, self.line_len());
         }
@@ -143,6 +467,61 @@ impl Rope {
         byte_offset
     }
 
+    /// Returns the byte offset of the start of the given paragraph, i.e. of
+    /// the start of its first line.
+    ///
+    /// The paragraphs are the ones yielded by
+    /// [`paragraphs()`](Self::paragraphs()), and passing
+    /// [`paragraph_len()`](Self::paragraph_len()) returns the byte length of
+    /// the `Rope`. The paragraphs are counted in the summaries of the B-tree,
+    /// so this runs in logarithmic time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the paragraph offset is out of bounds (i.e. greater than
+    /// [`paragraph_len()`](Self::paragraph_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\n\n  \n  baz\n");
+    ///
+    /// assert_eq!(r.byte_of_paragraph(0), 0);
+    /// assert_eq!(r.byte_of_paragraph(1), "foo\nbar\n\n  \n".len());
+    /// assert_eq!(r.byte_of_paragraph(2), r.byte_len());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "paragraph-count")))]
+    #[cfg(feature = "paragraph-count")]
+    #[track_caller]
+    #[inline]
+    pub fn byte_of_paragraph(&self, paragraph_offset: usize) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        let paragraph_len = self.paragraph_len();
+
+        if paragraph_offset > paragraph_len {
+            panic::paragraph_offset_out_of_bounds(
+                paragraph_offset,
+                paragraph_len,
+            );
+        }
+
+        if paragraph_offset == paragraph_len {
+            return self.byte_len();
+        }
+
+        let first_char = self
+            .byte_where_count_exceeds(paragraph_offset, |summary| {
+                summary.paragraphs()
+            })
+            .expect("the paragraph is in the rope");
+
+        self.byte_of_line(self.line_of_byte(first_char))
+    }
+
     /// Returns the byte offset corresponding to the given UTF-16 code unit
     /// offset.
     ///
@@ -166,7 +545,10 @@ impl Rope {
     #[cfg(feature = "utf16-metric")]
     #[track_caller]
     #[inline]
-    pub fn byte_of_utf16_code_unit(&self, utf16_offset: usize) -> usize {
+    pub fn byte_of_utf16_code_unit(&self, utf16_offset: usize) -> usize
+    where
+        S: MetricSet<Utf16CodeUnits = Maintained>,
+    {
         if utf16_offset > self.utf16_len() {
             panic::utf16_offset_out_of_bounds(utf16_offset, self.utf16_len())
         }
@@ -200,7 +582,10 @@ impl Rope {
     /// ```
     #[track_caller]
     #[inline]
-    pub fn byte_slice<R>(&self, byte_range: R) -> RopeSlice<'_>
+    pub fn byte_slice<R>(
+        &self,
+        byte_range: R,
+    ) -> RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
     where
         R: RangeBounds<usize>,
     {
@@ -218,6 +603,55 @@ impl Rope {
         self.tree.slice(ByteMetric(start)..ByteMetric(end)).into()
     }
 
+    /// Returns an immutable slice of the `Rope` in the specified byte range,
+    /// after moving its start and end to valid boundaries according to
+    /// `snap` instead of panicking if they don't lie on one.
+    ///
+    /// Endpoints past the end of the `Rope` are clamped to
+    /// [`byte_len()`](Self::byte_len()). If snapping inward moves the start
+    /// past the end the returned slice is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the byte range is greater than the end after
+    /// clamping them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Rope, Snap};
+    /// #
+    /// let r = Rope::from("🗻∈🌏");
+    ///
+    /// assert_eq!(r.byte_slice_snapped(2..5, Snap::CharOutward), "🗻∈");
+    /// assert_eq!(r.byte_slice_snapped(2..9, Snap::CharInward), "∈");
+    /// assert_eq!(r.byte_slice_snapped(5..100, Snap::CharInward), "🌏");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn byte_slice_snapped<R>(
+        &self,
+        byte_range: R,
+        snap: Snap,
+    ) -> RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        let start = start.min(self.byte_len());
+        let end = end.min(self.byte_len());
+
+        if start > end {
+            panic::byte_start_after_end(start, end);
+        }
+
+        let range = snap_range(self.byte_slice(..), start, end, snap);
+
+        self.byte_slice(range)
+    }
+
     /// Returns an iterator over the bytes of this `Rope`.
     ///
     /// # Examples
@@ -235,283 +669,446 @@ impl Rope {
     /// assert_eq!(None, bytes.next());
     /// ```
     #[inline]
-    pub fn bytes(&self) -> Bytes<'_> {
+    pub fn bytes(&self) -> Bytes<'_, C, W, B, MAX_BYTES, MIN_FILL, S> {
         Bytes::from(self)
     }
 
-    /// Returns an iterator over the [`char`]s of this `Rope`.
+    /// Returns the byte ranges of this `Rope` and of `other` which differ,
+    /// in increasing order.
+    ///
+    /// Every pair holds a range of this `Rope` and the range of `other` that
+    /// takes its place, one of which is empty if text was only inserted or
+    /// deleted. The ranges are exact for a single edit, while text that
+    /// happens to be equal in between nearby edits may be included in the
+    /// same pair.
+    ///
+    /// When one `Rope` is an edited clone of the other, the chunks they
+    /// share are skipped without being compared, so this runs in time
+    /// proportional to the size of the edits rather than to the length of
+    /// the two `Rope`s. This makes it cheap to find which part of a screen to
+    /// redraw or which part of a syntax tree to reparse after a batch of
+    /// edits.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("🐻‍❄️");
+    /// let r = Rope::from("Hello Earth!\n".repeat(1000));
     ///
-    /// let mut chars = r.chars();
+    /// let mut other = r.clone();
+    /// other.replace(6..11, "Saturn");
+    /// other.delete(6_001..6_007);
     ///
-    /// assert_eq!(Some('🐻'), chars.next());
-    /// assert_eq!(Some('\u{200d}'), chars.next());
-    /// assert_eq!(Some('❄'), chars.next());
-    /// assert_eq!(Some('\u{fe0f}'), chars.next());
-    /// assert_eq!(None, chars.next());
+    /// assert_eq!(
+    ///     r.changed_ranges(&other),
+    ///     [(6..11, 6..12), (6_000..6_006, 6_001..6_001)]
+    /// );
     /// ```
     #[inline]
-    pub fn chars(&self) -> Chars<'_> {
-        Chars::from(self)
-    }
-
-    /// Returns an iterator over the chunks of this [`Rope`].
-    #[inline]
-    pub fn chunks(&self) -> Chunks<'_> {
-        Chunks::from(self)
+    pub fn changed_ranges(
+        &self,
+        other: &Self,
+    ) -> Vec<(Range<usize>, Range<usize>)> {
+        diff::changed_ranges(self, other)
     }
 
-    /// Deletes the contents of the `Rope` within the specified byte range,
-    /// where the start and end of the range are interpreted as offsets.
+    /// Returns the [`char`] at `char_index`.
     ///
     /// # Panics
     ///
-    /// Panics if the start or the end of the byte range don't lie on a code
-    /// point boundary, if the start is greater than the end or if the end is
-    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    /// Panics if the char index is out of bounds (i.e. greater than or equal
+    /// to [`char_len()`](Self::char_len())).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let mut r = Rope::from("Hello Earth 🌎!");
+    /// let r = Rope::from("añ🦀");
     ///
-    /// r.delete(5..16);
-    /// assert_eq!(r, "Hello!");
+    /// assert_eq!(r.char(0), 'a');
+    /// assert_eq!(r.char(1), 'ñ');
+    /// assert_eq!(r.char(2), '🦀');
     /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "char-metric")))]
+    #[cfg(feature = "char-metric")]
     #[track_caller]
     #[inline]
-    pub fn delete<R>(&mut self, byte_range: R)
+    pub fn char(&self, char_index: usize) -> char
     where
-        R: RangeBounds<usize>,
+        S: MetricSet<Chars = Maintained>,
     {
-        self.replace(byte_range, "");
-    }
+        if char_index >= self.char_len() {
+            panic::char_index_out_of_bounds(char_index, self.char_len());
+        }
 
-    pub(super) const fn arity() -> usize {
-        ARITY
+        let ByteMetric(byte_offset) =
+            self.tree.convert_measure(CharMetric(char_index));
+
+        self.char_at_byte(byte_offset)
     }
 
-    /// Returns an iterator over the extended grapheme clusters of this
-    /// `Rope`.
+    /// Returns the [`char`] starting at `byte_index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte index is out of bounds (i.e. greater than or equal
+    /// to [`byte_len()`](Self::byte_len())) or if it doesn't lie on a code
+    /// point boundary.
     ///
     /// # Examples
     ///
-    /// ```no_run
+    /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("arg!\r\n🐻‍❄️");
-    ///
-    /// let mut graphemes = r.graphemes();
+    /// let r = Rope::from("añ🦀");
     ///
-    /// assert_eq!(Some("a"), graphemes.next().as_deref());
-    /// assert_eq!(Some("r"), graphemes.next().as_deref());
-    /// assert_eq!(Some("g"), graphemes.next().as_deref());
-    /// assert_eq!(Some("!"), graphemes.next().as_deref());
-    /// assert_eq!(Some("\r\n"), graphemes.next().as_deref());
-    /// assert_eq!(Some("🐻‍❄️"), graphemes.next().as_deref());
-    /// assert_eq!(None, graphemes.next());
+    /// assert_eq!(r.char_at_byte(0), 'a');
+    /// assert_eq!(r.char_at_byte(1), 'ñ');
+    /// assert_eq!(r.char_at_byte(3), '🦀');
     /// ```
-    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
-    #[cfg(feature = "graphemes")]
+    #[track_caller]
     #[inline]
-    pub fn graphemes(&self) -> crate::iter::Graphemes<'_> {
-        crate::iter::Graphemes::from(self)
+    pub fn char_at_byte(&self, byte_index: usize) -> char {
+        if byte_index >= self.byte_len() {
+            panic::byte_index_out_of_bounds(byte_index, self.byte_len());
+        }
+
+        let (chunk, ByteMetric(chunk_byte_offset)) =
+            self.tree.leaf_at_measure(ByteMetric(byte_index + 1));
+
+        chunk.char_at_byte(byte_index - chunk_byte_offset)
     }
 
-    /// Inserts `text` in the `Rope` at the given byte offset.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the byte offset doesn't lie on a code point boundary or if
-    /// it's out of bounds (i.e. greater than
-    /// [`byte_len()`](Self::byte_len())).
+    /// Returns the number of [`char`]s in the `Rope`.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let mut r = Rope::from("Hello Earth!");
+    /// let r = Rope::from("añ🦀");
     ///
-    /// r.insert(11, " 🌎");
-    /// assert_eq!(r, "Hello Earth 🌎!");
+    /// assert_eq!(r.char_len(), 3);
     /// ```
-    #[track_caller]
+    #[cfg_attr(docsrs, doc(cfg(feature = "char-metric")))]
+    #[cfg(feature = "char-metric")]
     #[inline]
-    pub fn insert<T>(&mut self, byte_offset: usize, text: T)
+    pub fn char_len(&self) -> usize
     where
-        T: AsRef<str>,
+        S: MetricSet<Chars = Maintained>,
     {
-        self.replace(byte_offset..byte_offset, text)
+        self.tree.summary().chars()
     }
 
-    /// Returns `true` if the given byte offset lies on a [`char`] boundary.
+    /// Returns the char offset of the start of the given line.
+    ///
+    /// This is like [`byte_of_line()`](Self::byte_of_line()), but for APIs
+    /// which address positions in `char`s rather than in bytes.
     ///
     /// # Panics
     ///
-    /// Panics if the byte offset is out of bounds (i.e. greater than
-    /// [`byte_len()`](Self::byte_len())).
+    /// Panics if the line offset is out of bounds (i.e. greater than
+    /// [`line_len()`](Self::line_len())).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("Löwe 老虎 Léopard");
+    /// let r = Rope::from("ƒoo\nbär\r\nbaz");
     ///
-    /// assert!(r.is_char_boundary(0));
-    /// assert!(r.is_char_boundary(r.byte_len()));
-    /// assert!(r.is_char_boundary(6)); // between ' ' and '老'
-    /// assert!(!r.is_char_boundary(2)); // between the 1st and 2nd byte of 'ö'
+    /// assert_eq!(r.char_of_line(0), 0);
+    /// assert_eq!(r.char_of_line(1), 4);
+    /// assert_eq!(r.char_of_line(2), 9);
+    /// assert_eq!(r.char_of_line(r.line_len()), r.char_len());
     /// ```
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(feature = "char-metric", feature = "lines")))
+    )]
+    #[cfg(all(feature = "char-metric", feature = "lines"))]
     #[track_caller]
     #[inline]
-    pub fn is_char_boundary(&self, byte_offset: usize) -> bool {
-        if byte_offset > self.byte_len() {
-            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+    pub fn char_of_line(&self, line_offset: usize) -> usize
+    where
+        S: MetricSet<Chars = Maintained>,
+        S: MetricSet<Lines = Maintained>,
+    {
+        if line_offset > self.line_len() {
+            panic::line_offset_out_of_bounds(line_offset, self.line_len());
         }
 
-        let (chunk, ByteMetric(chunk_byte_offset)) =
-            self.tree.leaf_at_measure(ByteMetric(byte_offset));
+        if line_offset > self.tree.summary().line_breaks() {
+            return self.char_len();
+        }
 
-        chunk.is_char_boundary(byte_offset - chunk_byte_offset)
+        let CharMetric(char_offset) =
+            self.tree.convert_measure(RawLineMetric(line_offset));
+
+        char_offset
     }
 
-    /// Returns `true` if the `Rope`'s byte length is zero.
+    /// Returns an iterator over the [`char`]s of this `Rope`.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let mut r = Rope::new();
-    /// assert!(r.is_empty());
+    /// let r = Rope::from("🐻‍❄️");
     ///
-    /// r.insert(0, "hey");
-    /// assert!(!r.is_empty());
+    /// let mut chars = r.chars();
+    ///
+    /// assert_eq!(Some('🐻'), chars.next());
+    /// assert_eq!(Some('\u{200d}'), chars.next());
+    /// assert_eq!(Some('❄'), chars.next());
+    /// assert_eq!(Some('\u{fe0f}'), chars.next());
+    /// assert_eq!(None, chars.next());
     /// ```
     #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.byte_len() == 0
+    pub fn chars(&self) -> Chars<'_, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        Chars::from(self)
     }
 
-    /// Returns `true` if the given byte offset lies on a grapheme cluster
-    /// boundary.
+    /// Checks the invariants of the `Rope`, returning a report of the ones
+    /// that don't hold.
+    ///
+    /// This runs all the checks done by [`validate()`](Self::validate()), and
+    /// also recounts the bytes, `char`s and line breaks of the `Rope` from
+    /// its text to compare them with the totals stored in its tree. It's
+    /// meant to be used in the test suites of crates built on top of crop,
+    /// and takes linear time in the length of the `Rope`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello, world!\n".repeat(100));
+    /// r.replace(7..12, "🌎");
+    ///
+    /// let report = r.check();
+    /// assert!(report.is_ok(), "{report}");
+    /// assert_eq!(report.stored_byte_len(), report.actual_byte_len());
+    /// assert_eq!(report.actual_byte_len(), r.byte_len());
+    /// ```
+    #[inline]
+    pub fn check(&self) -> CheckReport {
+        CheckReport::from(self)
+    }
+
+    /// Returns the chunk containing the byte at `byte_index`, together with
+    /// the byte and line offsets of its start in the `Rope`.
+    ///
+    /// The chunk is one of the `&str`s yielded by
+    /// [`chunks()`](Self::chunks()), and it's found in logarithmic time.
+    /// It's only available if `S` maintains the line breaks.
     ///
     /// # Panics
     ///
-    /// Panics if the byte offset is out of bounds (i.e. greater than
-    /// [`byte_len()`](Self::byte_len())).
+    /// Panics if the byte index is out of bounds (i.e. greater than or equal
+    /// to [`byte_len()`](Self::byte_len())).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("aargh!\r\n🐻‍❄️");
+    /// let r = Rope::from("foo\nbar\n".repeat(1000));
     ///
-    /// assert!(r.is_grapheme_boundary(0));
-    /// assert!(r.is_grapheme_boundary(r.byte_len()));
-    /// assert!(!r.is_grapheme_boundary(7)); // between '\r' and '\n'
-    /// assert!(r.is_grapheme_boundary(8)); // between '\n' and '🐻‍❄️'
-    /// assert!(!r.is_grapheme_boundary(12)); // between the 1st and 2nd code point of '🐻‍❄️'
+    /// let (chunk, chunk_byte, chunk_line) = r.chunk_at_byte(5000);
+    ///
+    /// assert!(chunk_byte <= 5000 && 5000 < chunk_byte + chunk.len());
+    /// assert_eq!(chunk_line, r.line_of_byte(chunk_byte));
+    /// assert_eq!(chunk.as_bytes()[5000 - chunk_byte], r.byte(5000));
     /// ```
-    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
-    #[cfg(feature = "graphemes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
     #[track_caller]
     #[inline]
-    pub fn is_grapheme_boundary(&self, byte_offset: usize) -> bool {
-        if byte_offset > self.byte_len() {
-            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+    pub fn chunk_at_byte(&self, byte_index: usize) -> (&str, usize, usize)
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        if byte_index >= self.byte_len() {
+            panic::byte_index_out_of_bounds(byte_index, self.byte_len());
         }
 
-        is_grapheme_boundary(self.chunks(), self.byte_len(), byte_offset)
+        let (leaf, ByteMetric(leaf_byte_offset)) =
+            self.tree.leaf_at_measure(ByteMetric(byte_index + 1));
+
+        let (chunk, chunk_offset) =
+            leaf.chunk_at_byte(byte_index - leaf_byte_offset);
+
+        let chunk_byte_offset = leaf_byte_offset + chunk_offset;
+
+        (chunk, chunk_byte_offset, self.line_of_byte(chunk_byte_offset))
     }
 
-    /// Returns the line at `line_index`, without its line terminator.
+    /// Returns the chunk containing the start of the line at `line_index`,
+    /// together with the byte and line offsets of its start in the
+    /// `Rope`.
     ///
-    /// If you want to include the line break consider taking a
-    /// [`line_slice()`](Self::line_slice()) in the
-    /// `line_index..line_index + 1` range.
+    /// See [`chunk_at_byte()`](Self::chunk_at_byte()) for more.
     ///
     /// # Panics
     ///
     /// Panics if the line index is out of bounds (i.e. greater than or equal
-    /// to [`line_len()`](Self::byte_len())).
+    /// to [`line_len()`](Self::line_len())).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let r = Rope::from("foo\nbar\n".repeat(1000));
     ///
-    /// assert_eq!(r.line(0), "foo");
-    /// assert_eq!(r.line(1), "bar");
-    /// assert_eq!(r.line(2), "baz");
+    /// let (chunk, chunk_byte, chunk_line) = r.chunk_at_line(1000);
+    ///
+    /// assert!(chunk_line <= 1000);
+    /// assert_eq!(chunk_line, r.line_of_byte(chunk_byte));
+    /// assert!(chunk_byte + chunk.len() > r.byte_of_line(1000));
     /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
     #[track_caller]
     #[inline]
-    pub fn line(&self, line_index: usize) -> RopeSlice<'_> {
+    pub fn chunk_at_line(&self, line_index: usize) -> (&str, usize, usize)
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
         if line_index >= self.line_len() {
             panic::line_index_out_of_bounds(line_index, self.line_len());
         }
 
-        let tree_slice = self
-            .tree
-            .slice(RawLineMetric(line_index)..RawLineMetric(line_index + 1));
-
-        let mut line = RopeSlice { tree_slice, has_trailing_newline: false };
-
-        if line.tree_slice.summary().line_breaks() == 1 {
-            line.truncate_trailing_line_break();
-        }
+        self.chunk_at_byte(self.byte_of_line(line_index))
+    }
 
-        line
+    /// Returns an iterator over the text of this [`Rope`], yielding the
+    /// two contiguous segments of every chunk as a pair.
+    ///
+    /// Chunks are stored in gap buffers, whose text is split in two by the
+    /// gap. While [`chunks()`](Self::chunks()) yields each segment on its
+    /// own, this method yields the segments before and after the gap of each
+    /// chunk together, which is useful when processing whole chunks at a
+    /// time, e.g. with vectored I/O, without having to copy them into a
+    /// contiguous buffer first. Either segment can be empty, but not both.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello Earth!\n".repeat(1000));
+    ///
+    /// let mut text = String::new();
+    ///
+    /// for (before_gap, after_gap) in r.chunk_segments() {
+    ///     text.push_str(before_gap);
+    ///     text.push_str(after_gap);
+    /// }
+    ///
+    /// assert_eq!(text, r.to_string());
+    /// ```
+    #[inline]
+    pub fn chunk_segments(
+        &self,
+    ) -> ChunkSegments<'_, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        ChunkSegments::from(self)
     }
 
-    /// Returns the number of lines in the `Rope`.
+    /// Returns an iterator over the chunks of this [`Rope`].
     ///
-    /// The final line break is optional and doesn't count as a separate empty
-    /// line.
+    /// The iterator is double-ended, so the chunks can also be walked from
+    /// the end of the `Rope` with [`rev()`](Iterator::rev()), or from both
+    /// ends at once.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let mut r = Rope::new();
+    /// let r = Rope::from("Hello Earth!\n".repeat(1000));
     ///
-    /// assert_eq!(r.line_len(), 0);
+    /// let mut reversed = r.chunks().rev().collect::<Vec<_>>();
+    /// reversed.reverse();
     ///
-    /// r.insert(0, "a");
-    /// assert_eq!(r.line_len(), 1);
+    /// assert_eq!(reversed.concat(), r.to_string());
+    /// ```
+    #[inline]
+    pub fn chunks(&self) -> Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        Chunks::from(self)
+    }
+
+    /// Compares the text of the `Rope` with the text of `other` according to
+    /// the rules of the given [`Collator`](icu_collator::Collator), i.e. in
+    /// the order a user of its language would expect them to be sorted in.
     ///
-    /// r.insert(1, "\n");
-    /// assert_eq!(r.line_len(), 1);
+    /// The chunks of the two `Rope`s are walked side by side to skip the text
+    /// they start with, without copying it. The collator only compares
+    /// contiguous strings though, so the rest of the text of a `Rope` is
+    /// copied if it spans more than one chunk. When sorting many `Rope`s the
+    /// same collator should be reused, since creating one is comparatively
+    /// expensive.
     ///
-    /// r.insert(2, "b");
-    /// assert_eq!(r.line_len(), 2);
+    /// # Examples
     ///
-    /// r.insert(3, "\r\n");
-    /// assert_eq!(r.line_len(), 2);
     /// ```
+    /// # use crop::Rope;
+    /// use icu_collator::{Collator, CollatorOptions};
+    ///
+    /// let sv = Collator::try_new(&"sv".parse().unwrap(), CollatorOptions::new())
+    ///     .unwrap();
+    ///
+    /// let mut ropes = ["äpple", "zebra", "apple"].map(Rope::from);
+    /// ropes.sort_by(|a, b| a.collate(b, &sv));
+    /// assert_eq!(ropes, ["apple", "zebra", "äpple"]);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode-collation")))]
+    #[cfg(feature = "unicode-collation")]
     #[inline]
-    pub fn line_len(&self) -> usize {
-        self.tree.summary().line_breaks() + 1
-            - (self.has_trailing_newline as usize)
-            - (self.is_empty() as usize)
+    pub fn collate(
+        &self,
+        other: &Self,
+        collator: &icu_collator::Collator,
+    ) -> core::cmp::Ordering {
+        super::collation::collate(
+            self.byte_slice(..),
+            other.byte_slice(..),
+            collator,
+        )
     }
 
-    /// Returns the line offset of the given byte.
+    /// Appends the contents of the `Rope` to the given `String`.
+    ///
+    /// The `String` is grown by the byte length of the `Rope` up front and the
+    /// text is then copied one chunk at a time. Clearing the same `String`
+    /// before each call lets you reuse its allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar");
+    ///
+    /// let mut buf = String::from("> ");
+    /// r.collect_into(&mut buf);
+    /// assert_eq!(buf, "> foo\nbar");
+    ///
+    /// buf.clear();
+    /// r.collect_into(&mut buf);
+    /// assert_eq!(buf, "foo\nbar");
+    /// ```
+    #[inline]
+    pub fn collect_into(&self, buf: &mut String) {
+        collect_chunks_into(self.chunks(), self.byte_len(), buf)
+    }
+
+    /// Returns the display width of the text of the `Rope` up to the given
+    /// byte offset, i.e. the visual column it's at.
     ///
     /// # Panics
     ///
@@ -523,160 +1120,3129 @@ impl Rope {
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz");
-    ///
-    /// assert_eq!(r.line_of_byte(0), 0);
-    /// assert_eq!(r.line_of_byte(3), 0);
-    /// assert_eq!(r.line_of_byte(4), 1);
-    /// assert_eq!(r.line_of_byte(8), 1); // between the '\r' and the '\n'
-    /// assert_eq!(r.line_of_byte(r.byte_len()), 2);
+    /// let r = Rope::from("a中b\n🦀!");
+    /// assert_eq!(r.column_of_byte(4), 3);
+    /// assert_eq!(r.column_of_byte(r.byte_len()), 7);
     /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "width-metric")))]
+    #[cfg(feature = "width-metric")]
     #[track_caller]
     #[inline]
-    pub fn line_of_byte(&self, byte_offset: usize) -> usize {
+    pub fn column_of_byte(&self, byte_offset: usize) -> usize
+    where
+        S: MetricSet<Width = Maintained>,
+    {
         if byte_offset > self.byte_len() {
             panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
         }
 
-        let RawLineMetric(line_offset) =
+        let super::metrics::WidthMetric(column) =
             self.tree.convert_measure(ByteMetric(byte_offset));
 
-        line_offset
+        column
     }
 
-    /// Returns an immutable slice of the `Rope` in the specified line range,
-    /// where the start and end of the range are interpreted as offsets.
-    ///
-    /// # Panics
+    /// Rebuilds the `Rope` so that its text is stored in as few chunks as
+    /// possible.
     ///
-    /// Panics if the start is greater than the end or if the end is out of
-    /// bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    /// After many edits, or after converting a `RopeSlice` into a `Rope`,
+    /// the chunks of a `Rope` can end up being only partially filled, which
+    /// slows down iterating over them. This method moves the text into new,
+    /// completely filled chunks and rebalances the underlying tree. It runs
+    /// in linear time in the length of the `Rope`, and it doesn't do anything
+    /// if the `Rope` is already compact.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
+    /// let mut r = Rope::from("Hello Earth 🌎!\n".repeat(100));
     ///
-    /// assert_eq!(r.line_slice(..1), "foo\n");
-    /// assert_eq!(r.line_slice(1..3), "bar\r\nbaz\n");
-    /// assert_eq!(r.line_slice(3..), "foobar\n");
+    /// for offset in (0..100).rev() {
+    ///     r.delete(offset * 18..offset * 18 + 6);
+    /// }
+    ///
+    /// r.compact();
+    /// assert_eq!(r, "Earth 🌎!\n".repeat(100));
     /// ```
-    #[track_caller]
     #[inline]
-    pub fn line_slice<R>(&self, line_range: R) -> RopeSlice<'_>
-    where
-        R: RangeBounds<usize>,
-    {
-        let (start, end) =
-            range_bounds_to_start_end(line_range, 0, self.line_len());
+    pub fn compact(&mut self) {
+        let min_leaf_count = (self.byte_len() + MAX_BYTES - 1) / MAX_BYTES;
 
-        if start > end {
-            panic::line_start_after_end(start, end);
+        if self.tree.leaf_count() <= min_leaf_count.max(1) {
+            return;
         }
 
-        if end > self.line_len() {
-            panic::line_offset_out_of_bounds(end, self.line_len());
+        let mut builder =
+            RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+
+        for chunk in self.chunks() {
+            builder.append(chunk);
         }
 
-        self.tree.slice(RawLineMetric(start)..RawLineMetric(end)).into()
+        *self = builder.build();
     }
 
-    /// Returns an iterator over the lines of this `Rope`, not including the
-    /// line terminators.
+    /// Compresses the chunks of the `Rope` with LZ4 to lower its memory
+    /// usage.
     ///
-    /// The final line break is optional and doesn't cause the iterator to
-    /// return a final empty line.
+    /// A compressed chunk is decompressed lazily the first time it's read,
+    /// and the decompressed copy is kept until the next call to this method,
+    /// which drops it again. Editing a chunk decompresses it for good. This
+    /// means that calling this method periodically, e.g. when the `Rope` is
+    /// idle, keeps in memory only the chunks that were accessed since the
+    /// last call, trading CPU time for memory when viewing huge texts of
+    /// which only a small part is looked at.
     ///
-    /// If you want to include the line breaks consider using the
-    /// [`raw_lines()`](Self::raw_lines()) method instead.
+    /// Chunks that are shared with other `Rope`s (e.g. with clones of this
+    /// `Rope`) are left untouched, as are chunks which wouldn't get any
+    /// smaller.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    /// let mut r = Rope::from("Hello Earth 🌎!\n".repeat(1000));
     ///
-    /// let mut lines = r.lines();
+    /// let before = r.stats().mem_usage();
+    /// r.compress();
+    /// assert!(r.stats().mem_usage() < before);
     ///
-    /// assert_eq!("foo", lines.next().unwrap());
-    /// assert_eq!("bar", lines.next().unwrap());
-    /// assert_eq!("baz", lines.next().unwrap());
-    /// assert_eq!(None, lines.next());
+    /// assert_eq!(r, "Hello Earth 🌎!\n".repeat(1000));
     /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "compression")))]
+    #[cfg(feature = "compression")]
     #[inline]
-    pub fn lines(&self) -> Lines<'_> {
-        Lines::from(self)
+    pub fn compress(&mut self) {
+        self.tree.for_each_unshared_leaf_mut(RawGapBuffer::compress);
     }
 
-    /// Returns an iterator over the lines of this `Rope`, including the
-    /// line terminators.
-    ///
-    /// The final line break is optional and doesn't cause the iterator to
-    /// return a final empty line.
+    /// Returns the number of non-overlapping occurrences of `pattern` in the
+    /// `Rope`, counted from left to right like
+    /// [`str::matches()`](str::matches()) does.
     ///
-    /// If you don't want to include the line breaks consider using the
-    /// [`lines()`](Self::lines()) method instead.
+    /// The chunks are searched one at a time without being collected into a
+    /// `String`, and occurrences spanning two or more chunks are counted as
+    /// well.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let mut r = Rope::from("foo\nbar\r\nbaz");
+    /// let r = Rope::from("abababa\n🦀🦀🦀");
     ///
-    /// let mut raw_lines = r.raw_lines();
+    /// assert_eq!(r.count_matches("aba"), 2);
+    /// assert_eq!(r.count_matches('🦀'), 3);
+    /// ```
+    #[inline]
+    pub fn count_matches<P>(&self, pattern: P) -> usize
+    where
+        P: SearchPattern,
+    {
+        pattern
+            .with_str(|pattern| search::count_matches(self.chunks(), pattern))
+    }
+
+    /// Returns the measure of the Rope in the custom metric given by
+    /// its [`CharWeight`](crate::CharWeight), i.e. the sum of the weights of
+    /// all its `char`s.
     ///
-    /// assert_eq!("foo\n", raw_lines.next().unwrap());
-    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
-    /// assert_eq!("baz", raw_lines.next().unwrap());
-    /// assert_eq!(None, raw_lines.next());
+    /// # Examples
     ///
-    /// r.insert(r.byte_len(), "\n");
+    /// ```
+    /// use crop::{tree::AtomicCounter, CharWeight, RopeWith};
     ///
-    /// let mut raw_lines = r.raw_lines();
+    /// // Count the curly braces.
+    /// #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    /// struct Braces;
     ///
-    /// assert_eq!("foo\n", raw_lines.next().unwrap());
-    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
-    /// assert_eq!("baz\n", raw_lines.next().unwrap());
-    /// assert_eq!(None, raw_lines.next());
+    /// impl CharWeight for Braces {
+    ///     fn weight(ch: char) -> usize {
+    ///         matches!(ch, '{' | '}') as usize
+    ///     }
+    /// }
+    ///
+    /// let r = RopeWith::<AtomicCounter, Braces>::from("{ a { b } }");
+    /// assert_eq!(r.custom_len(), 4);
     /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "custom-metric")))]
+    #[cfg(feature = "custom-metric")]
     #[inline]
-    pub fn raw_lines(&self) -> RawLines<'_> {
-        RawLines::from(self)
+    pub fn custom_len(&self) -> usize {
+        self.tree.summary().custom_units()
+    }
+
+    /// Returns an immutable slice of the `Rope` in the specified range of the
+    /// custom metric given by its [`CharWeight`](crate::CharWeight), where
+    /// the start and end of the range are interpreted as offsets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`custom_len()`](Self::custom_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crop::{tree::AtomicCounter, CharWeight, RopeWith};
+    ///
+    /// // Count the curly braces.
+    /// #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    /// struct Braces;
+    ///
+    /// impl CharWeight for Braces {
+    ///     fn weight(ch: char) -> usize {
+    ///         matches!(ch, '{' | '}') as usize
+    ///     }
+    /// }
+    ///
+    /// let r = RopeWith::<AtomicCounter, Braces>::from("a { b } c");
+    /// assert_eq!(r.custom_slice(..1), "a {");
+    /// assert_eq!(r.custom_slice(1..2), " b }");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "custom-metric")))]
+    #[cfg(feature = "custom-metric")]
+    #[track_caller]
+    #[inline]
+    pub fn custom_slice<R>(
+        &self,
+        custom_range: R,
+    ) -> RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(custom_range, 0, self.custom_len());
+
+        if start > end {
+            panic::custom_start_after_end(start, end);
+        }
+
+        if end > self.custom_len() {
+            panic::custom_offset_out_of_bounds(end, self.custom_len());
+        }
+
+        // A range inside the weight of a single `char` would give a start
+        // past the end if we sliced by the custom metric directly, so we go
+        // through the byte offsets instead.
+        let start = self.byte_of_custom_unit(start);
+        let end = self.byte_of_custom_unit(end);
+
+        self.tree.slice(ByteMetric(start)..ByteMetric(end)).into()
+    }
+
+    /// Returns the offset in the custom metric given by its
+    /// [`CharWeight`](crate::CharWeight) corresponding to the given byte
+    /// offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crop::{tree::AtomicCounter, CharWeight, RopeWith};
+    ///
+    /// // Count the curly braces.
+    /// #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    /// struct Braces;
+    ///
+    /// impl CharWeight for Braces {
+    ///     fn weight(ch: char) -> usize {
+    ///         matches!(ch, '{' | '}') as usize
+    ///     }
+    /// }
+    ///
+    /// let r = RopeWith::<AtomicCounter, Braces>::from("a { b } c");
+    /// assert_eq!(r.custom_unit_of_byte(4), 1);
+    /// assert_eq!(r.custom_unit_of_byte(r.byte_len()), 2);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "custom-metric")))]
+    #[cfg(feature = "custom-metric")]
+    #[track_caller]
+    #[inline]
+    pub fn custom_unit_of_byte(&self, byte_offset: usize) -> usize {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        let super::metrics::CustomMetric(custom_offset) =
+            self.tree.convert_measure(ByteMetric(byte_offset));
+
+        custom_offset
     }
 
-    /// Returns a new empty [`Rope`].
-    #[inline]
-    pub fn new() -> Self {
-        Self::default()
+    /// Returns a [Graphviz](https://graphviz.org) description of the B-tree
+    /// backing the `Rope`, with every node labelled with its summary and
+    /// with the number of references to it.
+    ///
+    /// This is meant to help with debugging performance issues: rendering
+    /// the output (e.g. with `dot -Tsvg`) shows how balanced the tree is,
+    /// and nodes with more than one reference are shared with clones of the
+    /// `Rope`.
+    ///
+    /// The format of the output is not stable and can change at any time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// let dot = r.debug_tree();
+    /// assert!(dot.starts_with("digraph {"));
+    /// assert!(dot.contains("refs: 1"));
+    /// ```
+    #[inline]
+    pub fn debug_tree(&self) -> String {
+        self.tree.to_dot()
+    }
+
+    /// Deletes the contents of the `Rope` within the specified byte range,
+    /// where the start and end of the range are interpreted as offsets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// r.delete(5..16);
+    /// assert_eq!(r, "Hello!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn delete<R>(&mut self, byte_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        if start > end {
+            panic::byte_start_after_end(start, end);
+        }
+
+        if end > self.byte_len() {
+            panic::byte_offset_out_of_bounds(end, self.byte_len());
+        }
+
+        self.tree.remove(ByteMetric(start)..ByteMetric(end));
+
+        self.finish_edit(start..start);
+    }
+
+    /// Returns the [`Delta`] which turns the text the given [`Signature`] was
+    /// computed from into this `Rope`.
+    ///
+    /// Every window of this `Rope` whose hashes are equal to the ones of a
+    /// block of the signature is retained from the base text, and only the
+    /// rest of the text is inserted. Blocks are matched in order, so text
+    /// which was moved past other unchanged blocks is inserted again instead
+    /// of being retained.
+    ///
+    /// This is meant to be used on a `Rope` which is an edited version of
+    /// the one the signature was computed from, when the latter is only
+    /// available on a remote peer. When both `Rope`s are at hand,
+    /// [`diff()`](Self::diff()) returns a smaller `Delta`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{DeltaOp, Rope};
+    /// #
+    /// let remote = Rope::from("Hello Earth! How are you doing today?");
+    /// let signature = remote.signature(8);
+    ///
+    /// let mut local = remote.clone();
+    /// local.replace(6..11, "Saturn");
+    ///
+    /// // Only the first two blocks have to be sent, the last two are kept.
+    /// let delta = local.delta_against(&signature);
+    /// assert!(delta.ops().contains(&DeltaOp::Retain(16)));
+    ///
+    /// let mut r = remote.clone();
+    /// delta.apply(&mut r);
+    /// assert_eq!(r, local);
+    /// ```
+    #[inline]
+    pub fn delta_against(&self, signature: &Signature) -> Delta {
+        signature::delta_against(self, signature)
+    }
+
+    /// Returns the [`Delta`] which turns this `Rope` into `other`.
+    ///
+    /// The chunks shared by the two `Rope`s (for example because one is an
+    /// edited clone of the other) are skipped without being compared, and
+    /// the rest of the text is diffed by lines and then by characters
+    /// within the changed lines.
+    ///
+    /// To keep the running time roughly linear on texts which have little
+    /// in common, the delta isn't guaranteed to be minimal: groups of
+    /// changes which are too long or too different are replaced as a whole.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth!\nHow are you?\n");
+    ///
+    /// let mut other = r.clone();
+    /// other.replace(6..11, "Saturn");
+    ///
+    /// let delta = r.diff(&other);
+    /// assert_eq!(delta.target_len(), other.byte_len());
+    ///
+    /// delta.apply(&mut r);
+    /// assert_eq!(r, other);
+    /// ```
+    #[inline]
+    pub fn diff(&self, other: &Self) -> Delta {
+        diff::diff(self, other)
+    }
+
+    pub(super) const fn arity() -> usize {
+        ARITY
+    }
+
+    /// Panics if the given in-bounds byte offset doesn't lie on a code point
+    /// boundary.
+    #[track_caller]
+    #[inline]
+    pub(super) fn assert_char_boundary(&self, byte_offset: usize) {
+        let (chunk, ByteMetric(chunk_byte_offset)) =
+            self.tree.leaf_at_measure(ByteMetric(byte_offset));
+
+        chunk.assert_char_boundary(byte_offset - chunk_byte_offset);
+    }
+
+    /// Checks whether the `Rope` and `other` are equal when ignoring the
+    /// case of ASCII letters, like
+    /// [`str::eq_ignore_ascii_case()`](str::eq_ignore_ascii_case()) does.
+    ///
+    /// `other` can be a `&str`, a `&String`, a `&Rope` or a `RopeSlice`. The
+    /// texts are compared chunk by chunk, without allocating lowercased
+    /// copies of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello, World!");
+    ///
+    /// assert!(r.eq_ignore_ascii_case("hello, world!"));
+    /// assert!(r.eq_ignore_ascii_case(&Rope::from("HELLO, WORLD!")));
+    /// assert!(r.eq_ignore_ascii_case(r.byte_slice(..)));
+    /// assert!(!r.eq_ignore_ascii_case("hello, world"));
+    /// ```
+    #[inline]
+    pub fn eq_ignore_ascii_case<'b, T>(&self, other: T) -> bool
+    where
+        T: AsciiCaseText<'b>,
+    {
+        self.byte_len() == other.byte_len()
+            && chunks_eq_ignore_ascii_case(self.chunks(), other.text_chunks())
+    }
+
+    /// Returns the byte offsets of the non-overlapping occurrences of
+    /// `pattern` in the `Rope`, from left to right, like
+    /// [`str::match_indices()`](str::match_indices()) does.
+    ///
+    /// Occurrences spanning two or more chunks are found as well.
+    /// See [`par_find_all()`](Self::par_find_all()) to search big texts
+    /// using multiple threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Rope};
+    /// #
+    /// let r = Rope::from("abababa\n🦀🦀🦀");
+    ///
+    /// assert_eq!(r.find_all("aba"), [0, 4]);
+    /// assert_eq!(r.find_all("🦀"), [8, 12, 16]);
+    /// ```
+    #[inline]
+    pub fn find_all<P>(&self, pattern: P) -> Vec<usize>
+    where
+        P: SearchPattern,
+    {
+        pattern.with_str(|pattern| search::find_all(self.chunks(), pattern))
+    }
+
+    /// Returns a 61-bit fingerprint of the text of the `Rope`, i.e. a hash of
+    /// its bytes which is kept in the summaries of the B-tree, so this runs
+    /// in constant time.
+    ///
+    /// `Rope`s with the same text have the same fingerprint however they
+    /// were built or edited, while `Rope`s with different texts almost
+    /// always have different ones. Comparing two `Rope`s with `==` checks
+    /// their fingerprints first, so different texts are usually told apart
+    /// without looking at them. The fingerprint isn't guaranteed to be the
+    /// same across versions of this crate.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth!");
+    /// r.replace(6..11, "World");
+    ///
+    /// assert_eq!(r.fingerprint(), Rope::from("Hello World!").fingerprint());
+    /// assert_ne!(r.fingerprint(), Rope::from("Hello Earth!").fingerprint());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "fingerprint")))]
+    #[cfg(feature = "fingerprint")]
+    #[inline]
+    pub fn fingerprint(&self) -> u64 {
+        self.tree.summary().fingerprint()
+    }
+
+    /// Creates a new `Rope` from a snapshot written by
+    /// [`write_snapshot()`](Self::write_snapshot()).
+    ///
+    /// The snapshot stores the tree of the `Rope` it was written from
+    /// together with the summaries of all of its nodes, so the text doesn't
+    /// have to be rebalanced again. The text of the leaves is copied into the
+    /// new `Rope` and checked to be valid UTF-8 and to match the stored
+    /// summaries.
+    /// See [`from_shared_snapshot()`](Self::from_shared_snapshot()) to borrow
+    /// it from the snapshot instead.
+    ///
+    /// If the snapshot was written with a different set of features, chunk
+    /// size or minimum chunk fill the `Rope` is instead rebuilt from its
+    /// text.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`](std::io::ErrorKind::InvalidData)
+    /// if the snapshot is malformed, e.g. if its checksum doesn't match, if
+    /// its text is not valid UTF-8 or if it doesn't match the summaries.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// let mut snapshot = Vec::new();
+    /// r.write_snapshot(&mut snapshot).unwrap();
+    ///
+    /// let r2 = Rope::from_snapshot(&snapshot).unwrap();
+    /// assert_eq!(r, r2);
+    ///
+    /// assert!(Rope::from_snapshot(b"foo").is_err());
+    /// ```
+    #[inline]
+    pub fn from_snapshot(snapshot: &[u8]) -> std::io::Result<Self> {
+        // SAFETY: the leaves are copied out of the snapshot.
+        unsafe { snapshot::read(snapshot, snapshot::LeafSource::Copied) }
+    }
+
+    /// Creates a new `Rope` from a snapshot written by
+    /// [`write_snapshot()`](Self::write_snapshot()), whose leaves borrow their
+    /// text from the snapshot instead of copying it.
+    ///
+    /// This is the same as [`from_snapshot()`](Self::from_snapshot()), except
+    /// that no text is copied: a leaf is only copied out of the snapshot the
+    /// first time it's edited, and the snapshot is kept alive until all the
+    /// leaves borrowing from it are dropped. The text is still checked to be
+    /// valid UTF-8 and to match the stored summaries, see
+    /// [`from_shared_snapshot_unchecked()`](Self::from_shared_snapshot_unchecked())
+    /// to skip that too.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`from_snapshot()`](Self::from_snapshot()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use std::sync::Arc;
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// let mut snapshot = Vec::new();
+    /// r.write_snapshot(&mut snapshot).unwrap();
+    ///
+    /// let mut r2 = Rope::from_shared_snapshot(Arc::from(snapshot)).unwrap();
+    /// assert_eq!(r, r2);
+    ///
+    /// r2.insert(3, "!");
+    /// assert_eq!(r2, "foo!\nbar\r\nbaz");
+    /// ```
+    #[inline]
+    pub fn from_shared_snapshot(
+        snapshot: std::sync::Arc<[u8]>,
+    ) -> std::io::Result<Self> {
+        let shared = std::sync::Arc::new(snapshot.clone());
+
+        // SAFETY: the text of the leaves is checked to be valid UTF-8, and
+        // the contents of an `Arc<[u8]>` can't change.
+        unsafe {
+            snapshot::read(
+                &snapshot,
+                snapshot::LeafSource::Shared {
+                    snapshot: shared,
+                    trusted: false,
+                },
+            )
+        }
+    }
+
+    /// Creates a new `Rope` from a snapshot written by
+    /// [`write_snapshot()`](Self::write_snapshot()) without checking that its
+    /// text is valid UTF-8 or that the summaries of its nodes add up.
+    ///
+    /// Like [`from_shared_snapshot()`](Self::from_shared_snapshot()) the
+    /// leaves of the new `Rope` borrow their text from the snapshot, which
+    /// can be e.g. a memory-mapped file. Loading it only reads the header and
+    /// the nodes of the snapshot, not its text.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error of kind [`InvalidData`](std::io::ErrorKind::InvalidData)
+    /// if the header or the nodes of the snapshot are malformed, e.g. if
+    /// their checksum doesn't match.
+    ///
+    /// # Safety
+    ///
+    /// The snapshot must have been written by
+    /// [`write_snapshot()`](Self::write_snapshot()) and not modified since,
+    /// and its `as_ref()` must always return the same bytes.
+    #[inline]
+    pub unsafe fn from_shared_snapshot_unchecked<T>(
+        snapshot: T,
+    ) -> std::io::Result<Self>
+    where
+        T: AsRef<[u8]> + Send + Sync + std::panic::RefUnwindSafe + 'static,
+    {
+        let shared: super::leaf_bytes::SharedBytes =
+            std::sync::Arc::new(snapshot);
+
+        // SAFETY: guaranteed by the caller.
+        unsafe {
+            snapshot::read(
+                (*shared).as_ref(),
+                snapshot::LeafSource::Shared {
+                    snapshot: std::sync::Arc::clone(&shared),
+                    trusted: true,
+                },
+            )
+        }
+    }
+
+    /// Creates a new `Rope` from a slice of bytes, returning an error if
+    /// they're not valid UTF-8.
+    ///
+    /// The bytes are validated in a single pass and then copied straight
+    /// into the chunks of the `Rope`, without going through a `String`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from_utf8(b"Hello \xF0\x9F\x8C\x8E!").unwrap();
+    /// assert_eq!(r, "Hello 🌎!");
+    ///
+    /// let err = Rope::from_utf8(b"Hello \xF0\x9F!").unwrap_err();
+    /// assert_eq!(err.valid_up_to(), 6);
+    /// ```
+    #[inline]
+    pub fn from_utf8<T>(bytes: T) -> Result<Self, core::str::Utf8Error>
+    where
+        T: AsRef<[u8]>,
+    {
+        core::str::from_utf8(bytes.as_ref()).map(Self::from)
+    }
+
+    /// Creates a new `Rope` from a slice of bytes, replacing any invalid
+    /// UTF-8 sequence with the replacement character
+    /// ([`U+FFFD`](char::REPLACEMENT_CHARACTER)).
+    ///
+    /// The invalid sequences are replaced like
+    /// [`String::from_utf8_lossy()`] does, but the valid parts are copied
+    /// straight into the chunks of the `Rope` instead of first being
+    /// collected into a `String`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from_utf8_lossy(b"Hello \xF0\x9F \xFFEarth!");
+    /// assert_eq!(r, "Hello \u{FFFD} \u{FFFD}Earth!");
+    /// ```
+    #[inline]
+    pub fn from_utf8_lossy<T>(bytes: T) -> Self
+    where
+        T: AsRef<[u8]>,
+    {
+        let mut bytes = bytes.as_ref();
+
+        let mut builder =
+            RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+
+        loop {
+            match core::str::from_utf8(bytes) {
+                Ok(valid) => {
+                    builder.append(valid);
+                    break;
+                },
+
+                Err(err) => {
+                    let (valid, rest) = bytes.split_at(err.valid_up_to());
+
+                    // SAFETY: `from_utf8()` just checked that the bytes up
+                    // to `valid_up_to()` are valid UTF-8.
+                    builder.append(unsafe {
+                        core::str::from_utf8_unchecked(valid)
+                    });
+
+                    builder.append("\u{FFFD}");
+
+                    // An incomplete sequence at the end of the input has no
+                    // error length and is replaced as a whole.
+                    let invalid_len = err.error_len().unwrap_or(rest.len());
+
+                    bytes = &rest[invalid_len..];
+                },
+            }
+        }
+
+        builder.build()
+    }
+
+    /// Called after every edit of the `Rope`, with the byte range of the
+    /// text that replaced the edited one.
+    ///
+    /// This is where the features which have to look at the result of an
+    /// edit do their work.
+    #[track_caller]
+    #[inline]
+    pub(super) fn finish_edit(&mut self, byte_range: Range<usize>) {
+        #[cfg(feature = "grapheme-chunks")]
+        self.realign_chunks(byte_range.clone());
+
+        #[cfg(feature = "validate-utf8")]
+        self.assert_valid();
+
+        let _ = byte_range;
+    }
+
+    /// Returns the number of extended grapheme clusters in the `Rope`.
+    ///
+    /// With the `grapheme-count` feature enabled the number of clusters of
+    /// every chunk is kept in the summaries of the B-tree, and this runs in
+    /// constant time as long as no boundary between two chunks falls where
+    /// the clusters after it depend on more than the two `char`s around it
+    /// (e.g. inside an emoji sequence or between two flags). The text after
+    /// each of those boundaries is segmented again, until its clusters line
+    /// up with the ones of the chunk, which usually takes a single cluster.
+    ///
+    /// Otherwise it segments the whole text, so it runs in linear time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🐻‍❄️");
+    /// assert_eq!(r.grapheme_len(), 6);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[inline]
+    pub fn grapheme_len(&self) -> usize {
+        #[cfg(feature = "grapheme-count")]
+        {
+            let summary = self.tree.summary();
+
+            if summary.unsure_grapheme_joins() == 0 {
+                summary.graphemes()
+            } else {
+                self.byte_slice(..).grapheme_len()
+            }
+        }
+
+        #[cfg(not(feature = "grapheme-count"))]
+        {
+            self.graphemes().count()
+        }
+    }
+
+    /// Returns the grapheme offset of the given byte offset, i.e. the number
+    /// of extended grapheme clusters before the one containing it.
+    ///
+    /// Like [`grapheme_len()`](Self::grapheme_len()) this runs in
+    /// logarithmic time in most cases.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🐻‍❄️");
+    ///
+    /// assert_eq!(r.grapheme_of_byte(4), 4); // the start of "\r\n"
+    /// assert_eq!(r.grapheme_of_byte(5), 4); // between '\r' and '\n'
+    /// assert_eq!(r.grapheme_of_byte(6), 5); // the start of '🐻‍❄️'
+    /// assert_eq!(r.grapheme_of_byte(r.byte_len()), 6);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "grapheme-count")))]
+    #[cfg(feature = "grapheme-count")]
+    #[track_caller]
+    #[inline]
+    pub fn grapheme_of_byte(&self, byte_offset: usize) -> usize {
+        self.byte_slice(..).grapheme_of_byte(byte_offset)
+    }
+
+    /// Returns an immutable slice of the `Rope` in the specified grapheme
+    /// range, where the start and end of the range are interpreted as
+    /// offsets.
+    ///
+    /// Like [`byte_of_grapheme()`](Self::byte_of_grapheme()) this runs in
+    /// logarithmic time in most cases.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`grapheme_len()`](Self::grapheme_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🐻‍❄️");
+    ///
+    /// assert_eq!(r.grapheme_slice(..3), "arg");
+    /// assert_eq!(r.grapheme_slice(4..5), "\r\n");
+    /// assert_eq!(r.grapheme_slice(5..), "🐻‍❄️");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "grapheme-count")))]
+    #[cfg(feature = "grapheme-count")]
+    #[track_caller]
+    #[inline]
+    pub fn grapheme_slice<R>(
+        &self,
+        grapheme_range: R,
+    ) -> RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.byte_slice(..).grapheme_slice(grapheme_range)
+    }
+
+    /// Returns an iterator over the extended grapheme clusters of this
+    /// `Rope`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🐻‍❄️");
+    ///
+    /// let mut graphemes = r.graphemes();
+    ///
+    /// assert_eq!(Some("a"), graphemes.next().as_deref());
+    /// assert_eq!(Some("r"), graphemes.next().as_deref());
+    /// assert_eq!(Some("g"), graphemes.next().as_deref());
+    /// assert_eq!(Some("!"), graphemes.next().as_deref());
+    /// assert_eq!(Some("\r\n"), graphemes.next().as_deref());
+    /// assert_eq!(Some("🐻‍❄️"), graphemes.next().as_deref());
+    /// assert_eq!(None, graphemes.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[inline]
+    pub fn graphemes(
+        &self,
+    ) -> crate::iter::Graphemes<'_, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        crate::iter::Graphemes::from(self)
+    }
+
+    /// Returns `true` if the `Rope` ends with a line break.
+    ///
+    /// This is computed from the last leaf of the tree instead of being
+    /// stored alongside it, so it's always in sync with the contents of the
+    /// `Rope` no matter how it was created or edited.
+    #[inline]
+    pub(super) fn has_trailing_newline(&self) -> bool {
+        self.tree.last_leaf().has_trailing_newline()
+    }
+
+    /// Inserts `text` in the `Rope` at the given byte offset.
+    ///
+    /// Inserting at the end of the `Rope`, e.g. to append the lines of a log
+    /// one at a time, follows the path to the last chunk remembered from the
+    /// previous append, which is kept even when that chunk has to be split.
+    /// The summaries of the nodes on the path still have to be updated, so
+    /// an append runs in time logarithmic in the length of the `Rope`, with
+    /// a small constant. A [`RopeBuilder`] appends in amortized constant time
+    /// if the text doesn't have to be read until it's all been appended.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth!");
+    ///
+    /// r.insert(11, " 🌎");
+    /// assert_eq!(r, "Hello Earth 🌎!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn insert<T>(&mut self, byte_offset: usize, text: T)
+    where
+        T: AsRef<str>,
+    {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        let text = text.as_ref();
+        self.tree.insert(ByteMetric(byte_offset), text);
+
+        self.finish_edit(byte_offset..byte_offset + text.len());
+    }
+
+    /// Inserts `text` in the `Rope` as one or more lines of its own, starting
+    /// at the given line.
+    ///
+    /// The text is never merged with the lines around it: if it doesn't end
+    /// with a line break and it's inserted before an existing line, a `\n` is
+    /// inserted after it, and if it's inserted at
+    /// [`line_len()`](Self::line_len()) while the last line doesn't end with
+    /// a line break, a `\n` is inserted before it. Inserting an empty string
+    /// does nothing.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line offset is out of bounds (i.e. greater than
+    /// [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo\nbaz\n");
+    ///
+    /// r.insert_at_line(1, "bar\n");
+    /// assert_eq!(r, "foo\nbar\nbaz\n");
+    ///
+    /// r.insert_at_line(0, "qux");
+    /// assert_eq!(r, "qux\nfoo\nbar\nbaz\n");
+    ///
+    /// let mut r = Rope::from("a\nb");
+    ///
+    /// r.insert_at_line(2, "c");
+    /// assert_eq!(r, "a\nb\nc");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn insert_at_line<T>(&mut self, line_offset: usize, text: T)
+    where
+        T: AsRef<str>,
+        S: MetricSet<Lines = Maintained>,
+    {
+        let byte_offset = self.byte_of_line(line_offset);
+
+        let text = text.as_ref();
+
+        if text.is_empty() {
+            return;
+        }
+
+        let (before, after) = if byte_offset < self.byte_len() {
+            ("", if B::ends_with_line_break(text) { "" } else { "\n" })
+        } else if byte_offset > 0 && !self.has_trailing_newline() {
+            ("\n", "")
+        } else {
+            ("", "")
+        };
+
+        let mut end = byte_offset;
+
+        for piece in [before, text, after] {
+            if !piece.is_empty() {
+                self.tree.insert(ByteMetric(end), piece);
+                end += piece.len();
+            }
+        }
+
+        self.finish_edit(byte_offset..end);
+    }
+
+    /// Returns `true` if the given byte offset lies on a [`char`] boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Löwe 老虎 Léopard");
+    ///
+    /// assert!(r.is_char_boundary(0));
+    /// assert!(r.is_char_boundary(r.byte_len()));
+    /// assert!(r.is_char_boundary(6)); // between ' ' and '老'
+    /// assert!(!r.is_char_boundary(2)); // between the 1st and 2nd byte of 'ö'
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn is_char_boundary(&self, byte_offset: usize) -> bool {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        #[cfg(feature = "char-metric")]
+        if self.is_ascii() {
+            return true;
+        }
+
+        let (chunk, ByteMetric(chunk_byte_offset)) =
+            self.tree.leaf_at_measure(ByteMetric(byte_offset));
+
+        chunk.is_char_boundary(byte_offset - chunk_byte_offset)
+    }
+
+    /// Returns `true` if all the characters of the `Rope` are in the ASCII
+    /// range.
+    ///
+    /// With the `char-metric` feature this runs in `O(1)`, since the number
+    /// of `char`s of the text is tracked alongside its byte length. Without
+    /// it every chunk is checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello, world!");
+    /// assert!(r.is_ascii());
+    ///
+    /// r.insert(5, " 🌎");
+    /// assert!(!r.is_ascii());
+    /// ```
+    #[inline]
+    pub fn is_ascii(&self) -> bool {
+        #[cfg(feature = "char-metric")]
+        {
+            let summary = self.tree.summary();
+            if let Some(chars) = summary.maintained_chars() {
+                return chars == summary.bytes();
+            }
+        }
+
+        self.chunks().all(str::is_ascii)
+    }
+
+    /// Returns `true` if the `Rope`'s byte length is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::new();
+    /// assert!(r.is_empty());
+    ///
+    /// r.insert(0, "hey");
+    /// assert!(!r.is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.byte_len() == 0
+    }
+
+    /// Returns `true` if the given byte offset lies on a grapheme cluster
+    /// boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("aargh!\r\n🐻‍❄️");
+    ///
+    /// assert!(r.is_grapheme_boundary(0));
+    /// assert!(r.is_grapheme_boundary(r.byte_len()));
+    /// assert!(!r.is_grapheme_boundary(7)); // between '\r' and '\n'
+    /// assert!(r.is_grapheme_boundary(8)); // between '\n' and '🐻‍❄️'
+    /// assert!(!r.is_grapheme_boundary(12)); // between the 1st and 2nd code point of '🐻‍❄️'
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[track_caller]
+    #[inline]
+    pub fn is_grapheme_boundary(&self, byte_offset: usize) -> bool {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        // In ASCII text the only grapheme cluster spanning more than one
+        // byte is "\r\n".
+        #[cfg(feature = "char-metric")]
+        if self.is_ascii() {
+            return byte_offset == 0
+                || byte_offset == self.byte_len()
+                || self.byte(byte_offset - 1) != b'\r'
+                || self.byte(byte_offset) != b'\n';
+        }
+
+        is_grapheme_boundary(self.chunks(), self.byte_len(), byte_offset)
+    }
+
+    /// Returns `true` if the text of the `Rope` is in the given Unicode
+    /// normalization form.
+    ///
+    /// Most text is checked in a single pass over its `char`s, and only the
+    /// parts the quick check can't decide on are normalized to compare them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{NormalizationForm, Rope};
+    /// #
+    /// let r = Rope::from("Cafe\u{301}");
+    /// assert!(!r.is_normalized(NormalizationForm::Nfc));
+    /// assert!(r.is_normalized(NormalizationForm::Nfd));
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode-normalization")))]
+    #[cfg(feature = "unicode-normalization")]
+    #[inline]
+    pub fn is_normalized(&self, form: super::NormalizationForm) -> bool {
+        super::normalization::is_normalized(self.chars(), form)
+    }
+
+    /// Creates a new `Rope` by concatenating the items yielded by the
+    /// iterator, placing the `separator` between each pair of adjacent
+    /// items.
+    ///
+    /// This is the `Rope` analogue of [`join()`](slice::join()) on slices of
+    /// strings. The items can be any mix of string slices, `String`s,
+    /// `Rope`s and `RopeSlice`s (see [`JoinItem`]), and their text is
+    /// chunked directly into the new `Rope` without building any
+    /// intermediate `String`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::join(["foo", "bar", "baz"], "\n");
+    /// assert_eq!(r, "foo\nbar\nbaz");
+    ///
+    /// let row = Rope::from("Hello Earth!");
+    /// let cells = [row.byte_slice(..5), row.byte_slice(6..)];
+    /// assert_eq!(Rope::join(cells, " | "), "Hello | Earth!");
+    ///
+    /// assert_eq!(Rope::join(Vec::<Rope>::new(), ", "), "");
+    /// ```
+    #[inline]
+    pub fn join<I>(iter: I, separator: &str) -> Self
+    where
+        I: IntoIterator,
+        I::Item: JoinItem,
+    {
+        let mut builder =
+            RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+
+        let mut iter = iter.into_iter();
+
+        if let Some(first) = iter.next() {
+            first.append_to(&mut builder);
+
+            for item in iter {
+                builder.append(separator);
+                item.append_to(&mut builder);
+            }
+        }
+
+        builder.build()
+    }
+
+    /// Returns the line at `line_index`, without its line terminator.
+    ///
+    /// If you want to include the line break consider using the
+    /// [`raw_line()`](Self::raw_line()) method instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// assert_eq!(r.line(0), "foo");
+    /// assert_eq!(r.line(1), "bar");
+    /// assert_eq!(r.line(2), "baz");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn line(
+        &self,
+        line_index: usize,
+    ) -> RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        if line_index >= self.line_len() {
+            panic::line_index_out_of_bounds(line_index, self.line_len());
+        }
+
+        let tree_slice = self
+            .tree
+            .slice(RawLineMetric(line_index)..RawLineMetric(line_index + 1));
+
+        let mut line = RopeSlice { tree_slice };
+
+        if line.tree_slice.summary().line_breaks() == 1 {
+            line.truncate_trailing_line_break();
+        }
+
+        line
+    }
+
+    /// Returns the byte offset of the end of the line containing the given
+    /// byte offset, not including its line terminator.
+    ///
+    /// Like in [`line_of_byte()`](Self::line_of_byte()), an offset inside
+    /// a line terminator belongs to the line it terminates, so the returned
+    /// offset can come before it. If the `Rope` ends with a line break its
+    /// length is returned for the offset at its end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// assert_eq!(r.line_boundary_after(0), 3);
+    /// assert_eq!(r.line_boundary_after(3), 3);
+    /// assert_eq!(r.line_boundary_after(4), 7);
+    /// assert_eq!(r.line_boundary_after(9), 12);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn line_boundary_after(&self, byte_offset: usize) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        let line_index = self.line_of_byte(byte_offset);
+
+        if line_index == self.line_len() {
+            return self.byte_len();
+        }
+
+        self.byte_of_line(line_index) + self.line(line_index).byte_len()
+    }
+
+    /// Returns the byte offset of the start of the line containing the given
+    /// byte offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// assert_eq!(r.line_boundary_before(0), 0);
+    /// assert_eq!(r.line_boundary_before(3), 0);
+    /// assert_eq!(r.line_boundary_before(4), 4);
+    /// assert_eq!(r.line_boundary_before(12), 9);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn line_boundary_before(&self, byte_offset: usize) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        self.byte_of_line(self.line_of_byte(byte_offset))
+    }
+
+    /// Returns the indentation of the line at `line_index`, i.e. the spaces
+    /// and tabs it starts with, as a `(bytes, columns)` pair.
+    ///
+    /// The columns are computed by advancing tabs to the next multiple of
+    /// `tab_width`. Only the chunks spanned by the indentation are scanned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())) or if `tab_width` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("fn main() {\n  \tlet a = 1;\n}\n");
+    ///
+    /// assert_eq!(r.line_indent(1, 4), (3, 4));
+    /// assert_eq!(r.line_indent(1, 8), (3, 8));
+    /// assert_eq!(r.line_indent(2, 4), (0, 0));
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn line_indent(
+        &self,
+        line_index: usize,
+        tab_width: usize,
+    ) -> (usize, usize)
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        line_indent(self.line(line_index), tab_width)
+    }
+
+    /// Returns the number of lines in the `Rope`.
+    ///
+    /// The final line break is optional and doesn't count as a separate empty
+    /// line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::new();
+    ///
+    /// assert_eq!(r.line_len(), 0);
+    ///
+    /// r.insert(0, "a");
+    /// assert_eq!(r.line_len(), 1);
+    ///
+    /// r.insert(1, "\n");
+    /// assert_eq!(r.line_len(), 1);
+    ///
+    /// r.insert(2, "b");
+    /// assert_eq!(r.line_len(), 2);
+    ///
+    /// r.insert(3, "\r\n");
+    /// assert_eq!(r.line_len(), 2);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn line_len(&self) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        self.tree.summary().line_breaks() + 1
+            - (self.has_trailing_newline() as usize)
+            - (self.is_empty() as usize)
+    }
+
+    /// Returns the line offset of the given byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// assert_eq!(r.line_of_byte(0), 0);
+    /// assert_eq!(r.line_of_byte(3), 0);
+    /// assert_eq!(r.line_of_byte(4), 1);
+    /// assert_eq!(r.line_of_byte(8), 1); // between the '\r' and the '\n'
+    /// assert_eq!(r.line_of_byte(r.byte_len()), 2);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn line_of_byte(&self, byte_offset: usize) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        let RawLineMetric(line_offset) =
+            self.tree.convert_measure(ByteMetric(byte_offset));
+
+        line_offset
+    }
+
+    /// Returns the line offset of the given char offset.
+    ///
+    /// This is like [`line_of_byte()`](Self::line_of_byte()), but for APIs
+    /// which address positions in `char`s rather than in bytes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the char offset is out of bounds (i.e. greater than
+    /// [`char_len()`](Self::char_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("ƒoo\nbär\r\nbaz");
+    ///
+    /// assert_eq!(r.line_of_char(0), 0);
+    /// assert_eq!(r.line_of_char(3), 0);
+    /// assert_eq!(r.line_of_char(4), 1);
+    /// assert_eq!(r.line_of_char(8), 1); // between the '\r' and the '\n'
+    /// assert_eq!(r.line_of_char(r.char_len()), 2);
+    /// ```
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(feature = "char-metric", feature = "lines")))
+    )]
+    #[cfg(all(feature = "char-metric", feature = "lines"))]
+    #[track_caller]
+    #[inline]
+    pub fn line_of_char(&self, char_offset: usize) -> usize
+    where
+        S: MetricSet<Chars = Maintained>,
+        S: MetricSet<Lines = Maintained>,
+    {
+        if char_offset > self.char_len() {
+            panic::char_offset_out_of_bounds(char_offset, self.char_len());
+        }
+
+        let RawLineMetric(line_offset) =
+            self.tree.convert_measure(CharMetric(char_offset));
+
+        line_offset
+    }
+
+    /// Returns an immutable slice of the `Rope` in the specified line range,
+    /// where the start and end of the range are interpreted as offsets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
+    ///
+    /// assert_eq!(r.line_slice(..1), "foo\n");
+    /// assert_eq!(r.line_slice(1..3), "bar\r\nbaz\n");
+    /// assert_eq!(r.line_slice(3..), "foobar\n");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn line_slice<R>(
+        &self,
+        line_range: R,
+    ) -> RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        R: RangeBounds<usize>,
+        S: MetricSet<Lines = Maintained>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(line_range, 0, self.line_len());
+
+        if start > end {
+            panic::line_start_after_end(start, end);
+        }
+
+        if end > self.line_len() {
+            panic::line_offset_out_of_bounds(end, self.line_len());
+        }
+
+        self.tree.slice(RawLineMetric(start)..RawLineMetric(end)).into()
+    }
+
+    /// Returns an iterator over the lines of this `Rope`, not including the
+    /// line terminators.
+    ///
+    /// The final line break is optional and doesn't cause the iterator to
+    /// return a final empty line.
+    ///
+    /// If you want to include the line breaks consider using the
+    /// [`raw_lines()`](Self::raw_lines()) method instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    ///
+    /// let mut lines = r.lines();
+    ///
+    /// assert_eq!("foo", lines.next().unwrap());
+    /// assert_eq!("bar", lines.next().unwrap());
+    /// assert_eq!("baz", lines.next().unwrap());
+    /// assert_eq!(None, lines.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn lines(&self) -> Lines<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        Lines::from(self)
+    }
+
+    /// Returns an iterator over the lines intersecting the given byte range,
+    /// together with their line indices.
+    ///
+    /// The first and last lines are clipped to the byte range, and the line
+    /// terminators are not included, not even the `\r` of a CRLF cut in half
+    /// by the end of the range. This is equivalent to slicing the
+    /// `Rope` and iterating over the [`lines()`](Self::lines()) of the
+    /// slice, except that the yielded indices are the ones of the lines in
+    /// the `Rope`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if its
+    /// end is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())), or if either endpoint doesn't lie
+    /// on a code point boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\nqux\n");
+    ///
+    /// let lines = r
+    ///     .lines_in_byte_range(5..15)
+    ///     .map(|(idx, line)| (idx, line.to_string()))
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(lines, [(1, "ar".into()), (2, "baz".into()), (3, "qu".into())]);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn lines_in_byte_range<R>(
+        &self,
+        byte_range: R,
+    ) -> LinesInByteRange<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        R: RangeBounds<usize>,
+        S: MetricSet<Lines = Maintained>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        let splits_crlf = start < end
+            && end < self.byte_len()
+            && self.byte(end - 1) == b'\r'
+            && self.byte(end) == b'\n';
+
+        // The `\r` is trimmed by slicing up to it, and not by truncating
+        // the last line, which can't be re-sliced if it's just the `\r`.
+        let trimmed_last = splits_crlf.then(|| {
+            let line_start = self.byte_of_line(self.line_of_byte(end - 1));
+            self.byte_slice(line_start.max(start)..end - 1)
+        });
+
+        LinesInByteRange::new(
+            self.byte_slice(start..end),
+            self.line_of_byte(start),
+            trimmed_last,
+        )
+    }
+
+    /// Returns an iterator over the lines of this `Rope` together with the
+    /// byte offsets of their starts, not including the line terminators.
+    ///
+    /// This is equivalent to zipping [`lines()`](Self::lines()) with the
+    /// [`byte_of_line()`](Self::byte_of_line()) of every line, but doesn't
+    /// need to query the offset of each line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    ///
+    /// let mut lines = r.lines_with_offsets();
+    ///
+    /// let (offset, line) = lines.next().unwrap();
+    /// assert_eq!(offset, 0);
+    /// assert_eq!(line, "foo");
+    ///
+    /// let (offset, line) = lines.next_back().unwrap();
+    /// assert_eq!(offset, 9);
+    /// assert_eq!(line, "baz");
+    ///
+    /// let (offset, line) = lines.next().unwrap();
+    /// assert_eq!(offset, 4);
+    /// assert_eq!(line, "bar");
+    ///
+    /// assert!(lines.next().is_none());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn lines_with_offsets(
+        &self,
+    ) -> LinesWithOffsets<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        LinesWithOffsets::from(self)
+    }
+
+    /// Returns the length in bytes of the longest line of the `Rope`, not
+    /// including its line terminator.
+    ///
+    /// With the `max-line-len` feature enabled the lengths of the longest
+    /// lines are kept in the summaries of the B-tree and this runs in
+    /// constant time. Otherwise it iterates over the lines, which runs in
+    /// time linear in the length of the `Rope`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbarbaz\r\nqux\n");
+    /// assert_eq!(r.max_line_len(), 6);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn max_line_len(&self) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        #[cfg(feature = "max-line-len")]
+        {
+            self.tree.summary().max_line_len()
+        }
+
+        #[cfg(not(feature = "max-line-len"))]
+        {
+            self.lines().map(|line| line.byte_len()).max().unwrap_or(0)
+        }
+    }
+
+    /// Returns the display width of the widest line of the `Rope`, as
+    /// given by [`width()`](Self::width()).
+    ///
+    /// Like [`max_line_len()`](Self::max_line_len()) this runs in constant
+    /// time with the `max-line-len` feature enabled, and in time linear in
+    /// the length of the `Rope` otherwise.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\n中文\nqux\n");
+    /// assert_eq!(r.max_line_width(), 4);
+    /// ```
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(feature = "lines", feature = "width-metric")))
+    )]
+    #[cfg(all(feature = "lines", feature = "width-metric"))]
+    #[inline]
+    pub fn max_line_width(&self) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+        S: MetricSet<Width = Maintained>,
+    {
+        #[cfg(feature = "max-line-len")]
+        {
+            self.tree.summary().max_line_width()
+        }
+
+        #[cfg(not(feature = "max-line-len"))]
+        {
+            self.lines().map(|line| line.width()).max().unwrap_or(0)
+        }
+    }
+
+    /// Returns a new `Rope` with the text of the `Rope` converted to the
+    /// given Unicode normalization form.
+    ///
+    /// The `char`s are normalized as a single stream, so combining sequences
+    /// split across chunks are handled correctly, and the result is built
+    /// one chunk at a time without collecting the text into a `String`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{NormalizationForm, Rope};
+    /// #
+    /// let r = Rope::from("Cafe\u{301}");
+    /// assert_eq!(r.normalize(NormalizationForm::Nfc), "Café");
+    /// assert_eq!(r.normalize(NormalizationForm::Nfd), r);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode-normalization")))]
+    #[cfg(feature = "unicode-normalization")]
+    #[inline]
+    pub fn normalize(&self, form: super::NormalizationForm) -> Self {
+        super::normalization::normalize(self.chars(), form)
+    }
+
+    /// Returns the number of paragraphs of the `Rope`, i.e. the number of
+    /// items yielded by [`paragraphs()`](Self::paragraphs()).
+    ///
+    /// The paragraphs are counted in the summaries of the B-tree, so this
+    /// runs in constant time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo\nbar\n\nbaz\n");
+    /// assert_eq!(r.paragraph_len(), 2);
+    ///
+    /// r.insert(4, "\n");
+    /// assert_eq!(r.paragraph_len(), 3);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "paragraph-count")))]
+    #[cfg(feature = "paragraph-count")]
+    #[inline]
+    pub fn paragraph_len(&self) -> usize {
+        self.tree.summary().paragraphs()
+    }
+
+    /// Returns an immutable slice of the `Rope` in the specified paragraph
+    /// range, where the start and end of the range are interpreted as
+    /// offsets.
+    ///
+    /// The slice goes from the start of the first paragraph to the end of
+    /// the last one, not including its line terminator, so slicing a single
+    /// paragraph returns the same slice as the one yielded by
+    /// [`paragraphs()`](Self::paragraphs()). Like
+    /// [`byte_of_paragraph()`](Self::byte_of_paragraph()) this runs in
+    /// logarithmic time.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`paragraph_len()`](Self::paragraph_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\n\n  \nbaz\n\nqux\n");
+    ///
+    /// assert_eq!(r.paragraph_slice(..1), "foo\nbar");
+    /// assert_eq!(r.paragraph_slice(1..), "baz\n\nqux");
+    /// assert_eq!(r.paragraph_slice(2..2), "");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "paragraph-count")))]
+    #[cfg(feature = "paragraph-count")]
+    #[track_caller]
+    #[inline]
+    pub fn paragraph_slice<R>(
+        &self,
+        paragraph_range: R,
+    ) -> RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        R: RangeBounds<usize>,
+        S: MetricSet<Lines = Maintained>,
+    {
+        let paragraph_len = self.paragraph_len();
+
+        let (start, end) =
+            range_bounds_to_start_end(paragraph_range, 0, paragraph_len);
+
+        if start > end {
+            panic::paragraph_start_after_end(start, end);
+        }
+
+        if end > paragraph_len {
+            panic::paragraph_offset_out_of_bounds(end, paragraph_len);
+        }
+
+        let start_byte = self.byte_of_paragraph(start);
+
+        let end_byte = if start == end {
+            start_byte
+        } else {
+            self.end_of_paragraph(end - 1)
+        };
+
+        self.byte_slice(start_byte..end_byte)
+    }
+
+    /// Returns the byte offset of the end of the given paragraph, i.e. of the
+    /// end of its last line, not including its line terminator.
+    #[cfg(feature = "paragraph-count")]
+    #[inline]
+    fn end_of_paragraph(&self, paragraph_index: usize) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        debug_assert!(paragraph_index < self.paragraph_len());
+
+        let line_after = match self
+            .byte_where_count_exceeds(paragraph_index, |summary| {
+                summary.ended_paragraphs()
+            }) {
+            // The paragraph is ended by the blank line this line break is
+            // the terminator of.
+            Some(line_break) => self.line_of_byte(line_break),
+
+            // Otherwise it's the last paragraph, which either goes on until
+            // the end of the text or is followed by a blank last line.
+            None if self.tree.summary().ends_in_blank_line() => {
+                self.line_of_byte(self.byte_len())
+            },
+
+            None => return self.byte_len(),
+        };
+
+        let last_line = line_after - 1;
+
+        self.byte_of_line(last_line) + self.line(last_line).byte_len()
+    }
+
+    /// Returns the byte offset of the first `char` such that `count` returns
+    /// more than `n` on the summary of the text up to and including it, or
+    /// `None` if there's no such `char`.
+    ///
+    /// `count` has to be non-decreasing as the text grows. The subtrees
+    /// before the one containing the `char` are summed up while descending
+    /// the B-tree, so only the prefixes of a single chunk have to be
+    /// summarized from their text.
+    #[cfg(feature = "paragraph-count")]
+    #[inline]
+    fn byte_where_count_exceeds(
+        &self,
+        n: usize,
+        count: impl Fn(&ChunkSummary<W, B, S>) -> usize,
+    ) -> Option<usize> {
+        use core::cell::Cell;
+
+        // Both closures need to know the summary of the text before the
+        // current subtree, and whether the chunk was already found.
+        let before = Cell::new(ChunkSummary::<W, B, S>::default());
+        let chunk_range = Cell::new(None);
+
+        let exceeds = |summary: &ChunkSummary<W, B, S>| {
+            count(&(before.get() + summary)) > n
+        };
+
+        self.tree.for_each_subtree(
+            |summary| chunk_range.get().is_none() && exceeds(summary),
+            |summary| {
+                if chunk_range.get().is_some() {
+                    return;
+                }
+
+                let start = before.get().bytes();
+
+                // Only the leaves are never descended into.
+                if exceeds(summary) {
+                    chunk_range.set(Some((start, start + summary.bytes())));
+                } else {
+                    before.set(before.get() + summary);
+                }
+            },
+        );
+
+        let (start, end) = chunk_range.get()?;
+
+        let chunk = self.byte_slice(start..end).to_string();
+
+        let before = before.get();
+
+        // The count over the prefixes of the chunk is non-decreasing too, so
+        // the `char` can be binary searched.
+        let chars = chunk.char_indices().collect::<Vec<_>>();
+
+        let idx = chars.partition_point(|&(offset, ch)| {
+            let prefix = &chunk[..offset + ch.len_utf8()];
+            count(&(before + ChunkSummary::from(prefix))) <= n
+        });
+
+        let (offset, _) =
+            chars.get(idx).expect("the chunk's summary exceeds the count");
+
+        Some(start + offset)
+    }
+
+    /// Returns an iterator over the paragraphs of this `Rope`, i.e. over
+    /// its runs of non-blank lines.
+    ///
+    /// A line is blank if it only contains whitespace. The paragraphs don't
+    /// include the line terminator of their last line, and the blank lines
+    /// separating them are skipped.
+    ///
+    /// Finding the paragraph boundaries requires looking at the contents of
+    /// every line, so iterating over the paragraphs takes time linear in the
+    /// length of the `Rope`. With the `paragraph-count` feature a single
+    /// paragraph can be sliced in logarithmic time with
+    /// [`paragraph_slice()`](Self::paragraph_slice()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\n\n  \nbaz\n");
+    ///
+    /// let mut paragraphs = r.paragraphs();
+    ///
+    /// assert_eq!("foo\nbar", paragraphs.next().unwrap());
+    /// assert_eq!("baz", paragraphs.next().unwrap());
+    /// assert_eq!(None, paragraphs.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn paragraphs(&self) -> Paragraphs<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        Paragraphs::from(self)
+    }
+
+    /// Like [`find_all()`](Self::find_all()), but splits the `Rope` into up
+    /// to `threads` parts of roughly the same length which are searched in
+    /// parallel.
+    ///
+    /// Occurrences starting in one part and ending in the next are found by
+    /// the part they start in, and occurrences overlapping the ones found by
+    /// the previous parts are resolved when merging, so the result is always
+    /// the same as the one of [`find_all()`](Self::find_all()). Texts that
+    /// are too short to benefit from being split are searched on the current
+    /// thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("abc".repeat(10_000));
+    ///
+    /// assert_eq!(r.par_find_all("ca", 4), r.find_all("ca"));
+    /// ```
+    #[inline]
+    pub fn par_find_all<P>(&self, pattern: P, threads: usize) -> Vec<usize>
+    where
+        P: SearchPattern,
+        C: Send + Sync,
+    {
+        pattern.with_str(|pattern| {
+            search::par_find_all(self.byte_slice(..), pattern, threads)
+        })
+    }
+
+    /// Returns `true` if this `Rope` and `other` are backed by the same tree,
+    /// i.e. if one is a clone of the other and neither has been edited since.
+    ///
+    /// This runs in constant time, so it can be used as a quick check before
+    /// comparing the text of two `Rope`s. `Rope`s which aren't backed by the
+    /// same tree can still have the same text, and may still share most of
+    /// their chunks: see [`shared_fraction()`](Self::shared_fraction()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello Earth!");
+    ///
+    /// let mut other = r.clone();
+    /// assert!(r.ptr_eq(&other));
+    ///
+    /// other.insert(0, "👋 ");
+    /// assert!(!r.ptr_eq(&other));
+    /// assert!(!r.ptr_eq(&Rope::from("Hello Earth!")));
+    /// ```
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        self.tree.ptr_eq(&other.tree)
+    }
+
+    /// Returns the line at `line_index`, including its line terminator (if it
+    /// has one).
+    ///
+    /// If you don't want to include the line break consider using the
+    /// [`line()`](Self::line()) method instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// assert_eq!(r.raw_line(0), "foo\n");
+    /// assert_eq!(r.raw_line(1), "bar\r\n");
+    /// assert_eq!(r.raw_line(2), "baz");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn raw_line(
+        &self,
+        line_index: usize,
+    ) -> RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        if line_index >= self.line_len() {
+            panic::line_index_out_of_bounds(line_index, self.line_len());
+        }
+
+        self.tree
+            .slice(RawLineMetric(line_index)..RawLineMetric(line_index + 1))
+            .into()
+    }
+
+    /// Returns an iterator over the lines of this `Rope`, including the
+    /// line terminators.
+    ///
+    /// The final line break is optional and doesn't cause the iterator to
+    /// return a final empty line.
+    ///
+    /// If you don't want to include the line breaks consider using the
+    /// [`lines()`](Self::lines()) method instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// let mut raw_lines = r.raw_lines();
+    ///
+    /// assert_eq!("foo\n", raw_lines.next().unwrap());
+    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
+    /// assert_eq!("baz", raw_lines.next().unwrap());
+    /// assert_eq!(None, raw_lines.next());
+    ///
+    /// r.insert(r.byte_len(), "\n");
+    ///
+    /// let mut raw_lines = r.raw_lines();
+    ///
+    /// assert_eq!("foo\n", raw_lines.next().unwrap());
+    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
+    /// assert_eq!("baz\n", raw_lines.next().unwrap());
+    /// assert_eq!(None, raw_lines.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn raw_lines(&self) -> RawLines<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        RawLines::from(self)
+    }
+
+    /// Returns a [`RopeReader`] over the contents of the `Rope`, which
+    /// implements [`Read`](std::io::Read), [`BufRead`](std::io::BufRead) and
+    /// [`Seek`](std::io::Seek).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// use std::io::{BufRead, Read, Seek, SeekFrom};
+    ///
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// let mut reader = r.reader();
+    ///
+    /// let mut line = String::new();
+    /// reader.read_line(&mut line).unwrap();
+    /// assert_eq!(line, "foo\n");
+    ///
+    /// reader.seek(SeekFrom::End(-3)).unwrap();
+    ///
+    /// let mut rest = String::new();
+    /// reader.read_to_string(&mut rest).unwrap();
+    /// assert_eq!(rest, "baz");
+    /// ```
+    #[inline]
+    pub fn reader(&self) -> RopeReader<'_, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        RopeReader::from(self.byte_slice(..))
+    }
+
+    /// Returns a new empty [`Rope`].
+    #[inline]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns a new empty [`Rope`] whose first chunk is already allocated,
+    /// unless `byte_capacity` is zero.
+    ///
+    /// This only preallocates that one chunk: a `Rope` allocates its chunks
+    /// one at a time as text is inserted, so any capacity beyond the size
+    /// of a chunk is ignored. To build a large `Rope` whose length is known
+    /// in advance, use
+    /// [`RopeBuilder::with_capacity()`](crate::RopeBuilder::with_capacity())
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::with_capacity(16);
+    /// assert!(r.is_empty());
+    ///
+    /// r.insert(0, "Hello, world!");
+    /// assert_eq!(r, "Hello, world!");
+    /// ```
+    #[inline]
+    pub fn with_capacity(byte_capacity: usize) -> Self {
+        if byte_capacity == 0 {
+            return Self::new();
+        }
+
+        // SAFETY: the chunk is empty.
+        let leaf = unsafe {
+            RopeChunk::<W, B, MAX_BYTES, MIN_FILL, S>::from_left_chunk(
+                LeafBytes::zeroed(),
+                ChunkSummary::new(),
+            )
+        };
+
+        Self { tree: Tree::from_leaves(core::iter::once(leaf)) }
+    }
+
+    /// Moves the grapheme clusters split between two chunks around the given
+    /// byte range (e.g. because they were just edited) to a single chunk.
+    ///
+    /// Only the boundaries of the chunks overlapping the range are checked,
+    /// which is enough to keep every chunk aligned to grapheme clusters as
+    /// long as they were before the edit.
+    #[cfg(feature = "grapheme-chunks")]
+    #[inline]
+    pub(super) fn realign_chunks(&mut self, byte_range: Range<usize>) {
+        let end = byte_range.end;
+
+        let (_, ByteMetric(mut boundary)) =
+            self.tree.leaf_at_measure(ByteMetric(byte_range.start));
+
+        loop {
+            if boundary > 0 && boundary < self.byte_len() {
+                self.realign_chunk_boundary(boundary);
+            }
+
+            if boundary >= end {
+                return;
+            }
+
+            let (leaf, ByteMetric(leaf_start)) =
+                self.tree.leaf_at_measure(ByteMetric(boundary + 1));
+
+            boundary = leaf_start + leaf.len();
+        }
+    }
+
+    /// If the boundary between two chunks at the given byte offset splits a
+    /// grapheme cluster, replaces the cluster with itself, which moves it to
+    /// the chunk before the boundary.
+    #[cfg(feature = "grapheme-chunks")]
+    #[inline]
+    fn realign_chunk_boundary(&mut self, boundary: usize) {
+        use unicode_segmentation::UnicodeSegmentation;
+
+        let (before, after) = (self.byte(boundary - 1), self.byte(boundary));
+
+        // The only grapheme cluster made of more than one ASCII character is
+        // "\r\n".
+        if before.is_ascii()
+            && after.is_ascii()
+            && (before, after) != (b'\r', b'\n')
+        {
+            return;
+        }
+
+        let (_, ByteMetric(window_start)) =
+            self.tree.leaf_at_measure(ByteMetric(boundary));
+
+        let (next, ByteMetric(next_start)) =
+            self.tree.leaf_at_measure(ByteMetric(boundary + 1));
+
+        debug_assert_eq!(next_start, boundary);
+
+        let window =
+            self.byte_slice(window_start..next_start + next.len()).to_string();
+
+        let offset = boundary - window_start;
+
+        let Some((cluster_start, cluster)) =
+            window.grapheme_indices(true).find(|&(start, cluster)| {
+                start < offset && offset < start + cluster.len()
+            })
+        else {
+            return;
+        };
+
+        // Clusters that don't fit in a chunk have to be split anyway.
+        if cluster.len() > MAX_BYTES {
+            return;
+        }
+
+        let start = window_start + cluster_start;
+        let end = start + cluster.len();
+        self.tree.replace(ByteMetric(start)..ByteMetric(end), cluster);
+    }
+
+    /// Removes the lines in the specified line range, where the start and end
+    /// of the range are interpreted as offsets. The line breaks of the
+    /// removed lines are removed with them.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
+    ///
+    /// r.remove_lines(1..3);
+    /// assert_eq!(r, "foo\nfoobar\n");
+    ///
+    /// r.remove_lines(1..);
+    /// assert_eq!(r, "foo\n");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn remove_lines<R>(&mut self, line_range: R)
+    where
+        R: RangeBounds<usize>,
+        S: MetricSet<Lines = Maintained>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(line_range, 0, self.line_len());
+
+        if start > end {
+            panic::line_start_after_end(start, end);
+        }
+
+        if end > self.line_len() {
+            panic::line_offset_out_of_bounds(end, self.line_len());
+        }
+
+        let byte_start = self.byte_of_line(start);
+        let byte_end = self.byte_of_line(end);
+        self.delete(byte_start..byte_end);
+    }
+
+    /// Replaces the contents of the `Rope` within the specified byte range
+    /// with the given string, where the start and end of the range are
+    /// interpreted as byte offsets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// r.replace(6..16, "Saturn 🪐");
+    /// assert_eq!(r, "Hello Saturn 🪐!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn replace<R, T>(&mut self, byte_range: R, text: T)
+    where
+        R: RangeBounds<usize>,
+        T: AsRef<str>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        if start > end {
+            panic::byte_start_after_end(start, end);
+        }
+
+        if end > self.byte_len() {
+            panic::byte_offset_out_of_bounds(end, self.byte_len());
+        }
+
+        let text = text.as_ref();
+        self.tree.replace(ByteMetric(start)..ByteMetric(end), text);
+
+        self.finish_edit(start..start + text.len());
+    }
+
+    /// Replaces the contents of the `Rope` within the specified byte range
+    /// with the text read from `reader`, returning the number of bytes that
+    /// were inserted.
+    ///
+    /// The reader is consumed until it reaches EOF, and its contents are
+    /// decoded in chunk-sized pieces, so the replacement text is never
+    /// buffered in a single allocation. This makes it suitable for reloading
+    /// part of a file or pasting the output of a pipe.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by `reader`, or an error of kind
+    /// [`InvalidData`](std::io::ErrorKind::InvalidData) if the bytes read
+    /// are not valid UTF-8. In both cases the `Rope` is left unchanged.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    /// The range is checked before anything is read from `reader`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// let inserted = r.replace_with_reader(6..16, "Saturn 🪐".as_bytes())?;
+    ///
+    /// assert_eq!(inserted, 11);
+    /// assert_eq!(r, "Hello Saturn 🪐!");
+    /// # Ok::<(), std::io::Error>(())
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn replace_with_reader<R, Rd>(
+        &mut self,
+        byte_range: R,
+        mut reader: Rd,
+    ) -> std::io::Result<usize>
+    where
+        R: RangeBounds<usize>,
+        Rd: std::io::Read,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        if start > end {
+            panic::byte_start_after_end(start, end);
+        }
+
+        if end > self.byte_len() {
+            panic::byte_offset_out_of_bounds(end, self.byte_len());
+        }
+
+        for byte_offset in [start, end] {
+            if !self.is_char_boundary(byte_offset) {
+                let (chunk, chunk_start) =
+                    self.byte_slice(..).chunk_containing_byte(byte_offset);
+                panic::byte_offset_not_char_boundary(
+                    chunk,
+                    byte_offset - chunk_start,
+                );
+            }
+        }
+
+        let inserted = read_to_rope(&mut reader)?;
+        let inserted_len = inserted.byte_len();
+
+        let right = self.tree.split_off(ByteMetric(end));
+        let _ = self.tree.split_off(ByteMetric(start));
+
+        self.tree.append(inserted.tree);
+        self.tree.append(right);
+
+        self.finish_edit(start..start + inserted_len);
+
+        Ok(inserted_len)
+    }
+
+    /// Applies several edits at once, each replacing a byte range with some
+    /// text, and returns where the given cursors end up in the edited text.
+    ///
+    /// The byte ranges of the edits refer to the text before any of them is
+    /// applied, and they must be sorted and disjoint (touching ranges are
+    /// allowed). The cursors are byte offsets in the text before the edits.
+    /// A cursor within the byte range of an edit is moved to the start of
+    /// its text if `gravity` is [`Gravity::Left`] or to its end if it's
+    /// [`Gravity::Right`], while the other cursors are shifted by the length
+    /// of the text inserted and deleted before them. A cursor at the
+    /// boundary between two touching edits belongs to the first one.
+    ///
+    /// This is what an editor with multiple cursors does when the user
+    /// types.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte ranges are not sorted and disjoint, if any of them
+    /// would make [`replace()`](Self::replace()) panic, or if any of the
+    /// cursors is out of bounds or doesn't lie on a code point boundary.
+    /// All the edits and the cursors are checked before the `Rope` is edited.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Gravity, Rope};
+    /// #
+    /// let mut r = Rope::from("foo(a);\nfoo(b);\n");
+    ///
+    /// let cursors = r.replace_many(
+    ///     [(0..3, "bar"), (8..11, "bazz")],
+    ///     &[3, 11, 15],
+    ///     Gravity::Right,
+    /// );
+    ///
+    /// assert_eq!(r, "bar(a);\nbazz(b);\n");
+    /// assert_eq!(cursors, [3, 12, 16]);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn replace_many<I, R, T>(
+        &mut self,
+        edits: I,
+        cursors: &[usize],
+        gravity: Gravity,
+    ) -> Vec<usize>
+    where
+        I: IntoIterator<Item = (R, T)>,
+        R: RangeBounds<usize>,
+        T: AsRef<str>,
+    {
+        let byte_len = self.byte_len();
+
+        // The edits, together with the byte range their text ends up at in
+        // the edited `Rope`.
+        let mut resolved = Vec::new();
+
+        let mut prev_end = 0;
+
+        let mut new_end = 0;
+
+        for (byte_range, text) in edits {
+            let (start, end) =
+                range_bounds_to_start_end(byte_range, 0, byte_len);
+
+            if start > end {
+                panic::byte_start_after_end(start, end);
+            }
+
+            if start < prev_end {
+                panic::edits_not_disjoint(start, prev_end);
+            }
+
+            if end > byte_len {
+                panic::byte_offset_out_of_bounds(end, byte_len);
+            }
+
+            self.assert_char_boundary(start);
+            self.assert_char_boundary(end);
+
+            let new_start = new_end + start - prev_end;
+            new_end = new_start + text.as_ref().len();
+            prev_end = end;
+
+            resolved.push((start..end, new_start..new_end, text));
+        }
+
+        let cursors = cursors
+            .iter()
+            .map(|&cursor| {
+                if cursor > byte_len {
+                    panic::byte_offset_out_of_bounds(cursor, byte_len);
+                }
+
+                self.assert_char_boundary(cursor);
+
+                // The first edit that doesn't end before the cursor.
+                let idx =
+                    resolved.partition_point(|(range, ..)| range.end < cursor);
+
+                match resolved.get(idx) {
+                    Some((range, new_range, _)) if range.start <= cursor => {
+                        match gravity {
+                            Gravity::Left => new_range.start,
+                            Gravity::Right => new_range.end,
+                        }
+                    },
+
+                    _ if idx > 0 => {
+                        let (range, new_range, _) = &resolved[idx - 1];
+                        cursor - range.end + new_range.end
+                    },
+
+                    _ => cursor,
+                }
+            })
+            .collect();
+
+        // Applying the edits back to front keeps the byte ranges of the
+        // remaining ones valid.
+        for (byte_range, _, text) in resolved.into_iter().rev() {
+            self.replace(byte_range, text);
+        }
+
+        cursors
+    }
+
+    /// Returns an iterator over the Rabin-Karp rolling hashes of every
+    /// window of `window` consecutive bytes of the `Rope`, in order.
+    ///
+    /// The `n`-th hash is the hash of the bytes in `n..n + window`, and each
+    /// one is computed from the previous one in constant time. This is a
+    /// building block for content-defined chunking and deduplication, e.g.
+    /// by cutting the text wherever the low bits of a hash are all zero.
+    ///
+    /// The iterator is empty if the `Rope` is shorter than the window.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `window` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("abcabc");
+    /// let hashes = r.rolling_hashes(3).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(hashes.len(), 4);
+    /// assert_eq!(hashes[0], hashes[3]);
+    /// assert_ne!(hashes[0], hashes[1]);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn rolling_hashes(
+        &self,
+        window: usize,
+    ) -> RollingHashes<'_, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        RollingHashes::new(self.bytes(), window)
+    }
+
+    /// Returns the fraction of the chunks of this `Rope` which are shared
+    /// with `other`, between 0 and 1.
+    ///
+    /// `Rope`s obtained by editing a clone of another `Rope` share all the
+    /// chunks that weren't affected by the edits, so this tells how much of
+    /// the work done on one `Rope` (e.g. highlighting or diffing it) can be
+    /// reused for the other. Like
+    /// [`changed_ranges()`](Self::changed_ranges()), it skips the subtrees
+    /// shared by the two `Rope`s without visiting them.
+    ///
+    /// This measures how much of their trees the two `Rope`s share, not how
+    /// much of their text is equal: two `Rope`s built separately from the
+    /// same text share none of their chunks. The only exception is two
+    /// empty `Rope`s, which have nothing that isn't shared, so the fraction
+    /// is 1.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello Earth!\n".repeat(10_000));
+    ///
+    /// let mut other = r.clone();
+    /// assert_eq!(r.shared_fraction(&other), 1.0);
+    ///
+    /// other.insert(6, "big ");
+    /// assert!(r.shared_fraction(&other) > 0.9);
+    ///
+    /// assert_eq!(r.shared_fraction(&Rope::from("Hello Earth!\n")), 0.0);
+    /// assert_eq!(Rope::new().shared_fraction(&Rope::new()), 1.0);
+    /// ```
+    #[inline]
+    pub fn shared_fraction(&self, other: &Self) -> f64 {
+        if self.ptr_eq(other) || (self.is_empty() && other.is_empty()) {
+            return 1.0;
+        }
+
+        self.tree.shared_leaf_count(&other.tree) as f64
+            / self.tree.leaf_count() as f64
+    }
+
+    /// Returns the [`Signature`] of the `Rope`, made of the hashes of its
+    /// consecutive blocks of `block_size` bytes.
+    ///
+    /// The signature can be sent to a peer holding an edited version of the
+    /// text, which can then call [`delta_against()`](Self::delta_against())
+    /// to compute a [`Delta`] that only contains the text of the blocks that
+    /// changed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("abcdabcdab");
+    /// let signature = r.signature(4);
+    ///
+    /// assert_eq!(signature.base_len(), 10);
+    /// assert_eq!(signature.blocks().len(), 2);
+    /// assert_eq!(signature.blocks()[0], signature.blocks()[1]);
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn signature(&self, block_size: usize) -> Signature {
+        signature::signature(self, block_size)
+    }
+
+    /// Replaces the contents of the `Rope` within the specified byte range
+    /// with the given string like [`replace()`](Self::replace()) does,
+    /// returning the text that was replaced as a new `Rope`.
+    ///
+    /// The returned `Rope` shares the chunks that are fully within the range
+    /// with the `Rope` before the edit, so that e.g. undo records and
+    /// clipboards can keep the removed text around without having to read
+    /// it out first.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start or the end of the byte range don't lie on a code
+    /// point boundary, if the start is greater than the end or if the end is
+    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// let removed = r.splice(6..16, "Saturn 🪐");
+    /// assert_eq!(r, "Hello Saturn 🪐!");
+    /// assert_eq!(removed, "Earth 🌎");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn splice<R, T>(&mut self, byte_range: R, replacement: T) -> Self
+    where
+        R: RangeBounds<usize>,
+        T: AsRef<str>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        let mut removed = Self::from(self.byte_slice(start..end));
+        removed.finish_edit(0..removed.byte_len());
+
+        self.replace(start..end, replacement);
+
+        removed
+    }
+
+    /// Splits the `Rope` at the given byte offset, returning the text before
+    /// and after it as two new `Rope`s and leaving this one untouched.
+    ///
+    /// Unlike [`split_off()`](Self::split_off()) this doesn't need a mutable
+    /// reference. Both halves share the chunks that aren't cut by the split
+    /// with this `Rope`, so this runs in time logarithmic in its length.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// let (hello, earth) = r.split_at(6);
+    /// assert_eq!(hello, "Hello ");
+    /// assert_eq!(earth, "Earth 🌎!");
+    /// assert_eq!(r, "Hello Earth 🌎!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn split_at(&self, byte_offset: usize) -> (Self, Self) {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        self.assert_char_boundary(byte_offset);
+
+        let mut left = Self::from(self.byte_slice(..byte_offset));
+        let mut right = Self::from(self.byte_slice(byte_offset..));
+
+        left.finish_edit(byte_offset..byte_offset);
+        right.finish_edit(0..0);
+
+        (left, right)
+    }
+
+    /// Splits the `Rope` at the given byte offset, returning the text after
+    /// it and keeping the text before it.
+    ///
+    /// The two halves share the chunks that aren't cut by the split, so this
+    /// runs in time logarithmic in the length of the `Rope`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// let earth = r.split_off(6);
+    /// assert_eq!(r, "Hello ");
+    /// assert_eq!(earth, "Earth 🌎!");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn split_off(&mut self, byte_offset: usize) -> Self {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        let mut right =
+            Self { tree: self.tree.split_off(ByteMetric(byte_offset)) };
+
+        self.finish_edit(byte_offset..byte_offset);
+        right.finish_edit(0..0);
+
+        right
+    }
+
+    /// Returns statistics about the memory usage and the shape of the tree
+    /// of the `Rope`.
+    ///
+    /// This walks all the internal nodes of the tree, so it runs in linear
+    /// time in the number of chunks of the `Rope`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!\n".repeat(1000));
+    ///
+    /// for offset in (0..1000).rev() {
+    ///     r.delete(offset * 18..offset * 18 + 6);
+    /// }
+    ///
+    /// let before = r.stats();
+    /// r.compact();
+    /// let after = r.stats();
+    ///
+    /// assert!(after.leaf_count() < before.leaf_count());
+    /// assert!(after.leaf_occupancy() > before.leaf_occupancy());
+    /// assert!(after.mem_usage() < before.mem_usage());
+    /// ```
+    #[inline]
+    pub fn stats(&self) -> RopeStats {
+        RopeStats::from(self)
+    }
+
+    /// Returns a new `Rope` with the lowercase equivalent of the text of the
+    /// `Rope`, as given by [`str::to_lowercase()`].
+    ///
+    /// The text is converted one chunk at a time and never collected into a
+    /// single `String`. Chunks that only contain ASCII are converted with a
+    /// faster ASCII-only routine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("ὈΔΥΣΣΕΎΣ says Hello!");
+    /// assert_eq!(r.to_lowercase(), "ὀδυσσεύς says hello!");
+    /// ```
+    #[inline]
+    pub fn to_lowercase(&self) -> Self {
+        chunks_to_lowercase(self.chunks())
+    }
+
+    /// Returns a new `Rope` with the uppercase equivalent of the text of the
+    /// `Rope`, as given by [`str::to_uppercase()`].
+    ///
+    /// The text is converted one chunk at a time and never collected into a
+    /// single `String`. Chunks that only contain ASCII are converted with a
+    /// faster ASCII-only routine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Straße, Ελλάδα");
+    /// assert_eq!(r.to_uppercase(), "STRASSE, ΕΛΛΆΔΑ");
+    /// ```
+    #[inline]
+    pub fn to_uppercase(&self) -> Self {
+        chunks_to_uppercase(self.chunks())
+    }
+
+    /// Returns the bytes of the `Rope` as a new `Vec<u8>`.
+    ///
+    /// The `Vec` is allocated with the byte length of the `Rope` up front and
+    /// the text is then copied one chunk at a time, which is a lot faster
+    /// than collecting the [`bytes()`](Self::bytes()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello 🌎!");
+    /// assert_eq!(r.to_vec(), "Hello 🌎!".as_bytes());
+    /// ```
+    #[inline]
+    pub fn to_vec(&self) -> Vec<u8> {
+        chunks_to_vec(self.chunks(), self.byte_len())
+    }
+
+    /// Returns a callback which can be passed to tree-sitter's
+    /// [`Parser::parse_with_options()`][parse_with_options] to parse the
+    /// `Rope` without copying it.
+    ///
+    /// Given a byte offset, the callback returns the bytes from that offset
+    /// to the end of the chunk containing it, or an empty slice if the offset
+    /// is past the end of the `Rope`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// use tree_sitter::Point;
+    ///
+    /// let r = Rope::from("foo\nbar");
+    ///
+    /// let mut callback = r.tree_sitter_callback();
+    ///
+    /// assert_eq!(callback(4, Point::new(1, 0)), b"bar");
+    /// assert_eq!(callback(7, Point::new(1, 3)), b"");
+    /// ```
+    ///
+    /// [parse_with_options]: ::tree_sitter::Parser::parse_with_options
+    #[cfg_attr(docsrs, doc(cfg(feature = "tree-sitter")))]
+    #[cfg(feature = "tree-sitter")]
+    #[inline]
+    pub fn tree_sitter_callback<'a>(
+        &'a self,
+    ) -> impl FnMut(usize, ::tree_sitter::Point) -> &'a [u8] + 'a {
+        self.byte_slice(..).tree_sitter_callback()
+    }
+
+    /// Non-panicking version of [`byte()`](Self::byte()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Error, Rope};
+    /// #
+    /// let r = Rope::from("bar");
+    ///
+    /// assert_eq!(r.try_byte(2), Ok(b'r'));
+    /// assert_eq!(
+    ///     r.try_byte(3),
+    ///     Err(Error::ByteIndexOutOfBounds { index: 3, len: 3 })
+    /// );
+    /// ```
+    #[inline]
+    pub fn try_byte(&self, byte_index: usize) -> Result<u8, Error> {
+        check_byte_index(byte_index, self.byte_len())?;
+        Ok(self.byte(byte_index))
+    }
+
+    /// Non-panicking version of [`byte_slice()`](Self::byte_slice()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Error, Rope};
+    /// #
+    /// let r = Rope::from("🗻∈🌏");
+    ///
+    /// assert_eq!(r.try_byte_slice(4..7).unwrap(), "∈");
+    ///
+    /// assert_eq!(
+    ///     r.try_byte_slice(..2),
+    ///     Err(Error::ByteOffsetNotCharBoundary { offset: 2 })
+    /// );
+    ///
+    /// assert_eq!(
+    ///     r.try_byte_slice(7..20),
+    ///     Err(Error::ByteOffsetOutOfBounds { offset: 20, len: 11 })
+    /// );
+    /// ```
+    #[inline]
+    pub fn try_byte_slice<R>(
+        &self,
+        byte_range: R,
+    ) -> Result<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>, Error>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        check_byte_range(start, end, self.byte_len(), |offset| {
+            self.is_char_boundary(offset)
+        })?;
+
+        Ok(self.tree.slice(ByteMetric(start)..ByteMetric(end)).into())
+    }
+
+    /// Non-panicking version of [`delete()`](Self::delete()).
+    ///
+    /// The `Rope` is left untouched if an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Error, Rope};
+    /// #
+    /// let mut r = Rope::from("Hello Earth 🌎!");
+    ///
+    /// assert_eq!(
+    ///     r.try_delete(5..13),
+    ///     Err(Error::ByteOffsetNotCharBoundary { offset: 13 })
+    /// );
+    ///
+    /// assert_eq!(r.try_delete(5..16), Ok(()));
+    /// assert_eq!(r, "Hello!");
+    /// ```
+    #[inline]
+    pub fn try_delete<R>(&mut self, byte_range: R) -> Result<(), Error>
+    where
+        R: RangeBounds<usize>,
+    {
+        self.try_replace(byte_range, "")
+    }
+
+    /// Non-panicking version of [`insert()`](Self::insert()).
+    ///
+    /// The `Rope` is left untouched if an error is returned.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Error, Rope};
+    /// #
+    /// let mut r = Rope::from("Hello Earth!");
+    ///
+    /// assert_eq!(
+    ///     r.try_insert(13, " 🌎"),
+    ///     Err(Error::ByteOffsetOutOfBounds { offset: 13, len: 12 })
+    /// );
+    ///
+    /// assert_eq!(r.try_insert(11, " 🌎"), Ok(()));
+    /// assert_eq!(r, "Hello Earth 🌎!");
+    /// ```
+    #[inline]
+    pub fn try_insert<T>(
+        &mut self,
+        byte_offset: usize,
+        text: T,
+    ) -> Result<(), Error>
+    where
+        T: AsRef<str>,
+    {
+        self.try_replace(byte_offset..byte_offset, text)
+    }
+
+    /// Non-panicking version of [`line()`](Self::line()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Error, Rope};
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// assert_eq!(r.try_line(1).unwrap(), "bar");
+    ///
+    /// assert_eq!(
+    ///     r.try_line(3),
+    ///     Err(Error::LineIndexOutOfBounds { index: 3, len: 3 })
+    /// );
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn try_line(
+        &self,
+        line_index: usize,
+    ) -> Result<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>, Error>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        check_line_index(line_index, self.line_len())?;
+        Ok(self.line(line_index))
+    }
+
+    /// Non-panicking version of [`line_slice()`](Self::line_slice()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Error, Rope};
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
+    ///
+    /// assert_eq!(r.try_line_slice(1..3).unwrap(), "bar\r\nbaz\n");
+    ///
+    /// assert_eq!(
+    ///     r.try_line_slice(3..2),
+    ///     Err(Error::LineStartAfterEnd { start: 3, end: 2 })
+    /// );
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn try_line_slice<R>(
+        &self,
+        line_range: R,
+    ) -> Result<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>, Error>
+    where
+        R: RangeBounds<usize>,
+        S: MetricSet<Lines = Maintained>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(line_range, 0, self.line_len());
+
+        check_line_range(start, end, self.line_len())?;
+
+        Ok(self.tree.slice(RawLineMetric(start)..RawLineMetric(end)).into())
     }
 
-    /// Replaces the contents of the `Rope` within the specified byte range
-    /// with the given string, where the start and end of the range are
-    /// interpreted as byte offsets.
-    ///
-    /// # Panics
+    /// Non-panicking version of [`replace()`](Self::replace()).
     ///
-    /// Panics if the start or the end of the byte range don't lie on a code
-    /// point boundary, if the start is greater than the end or if the end is
-    /// out of bounds (i.e. greater than [`byte_len()`](Self::byte_len())).
+    /// The `Rope` is left untouched if an error is returned.
     ///
     /// # Examples
     ///
     /// ```
-    /// # use crop::Rope;
+    /// # use crop::{Error, Rope};
     /// #
     /// let mut r = Rope::from("Hello Earth 🌎!");
     ///
-    /// r.replace(6..16, "Saturn 🪐");
+    /// assert_eq!(
+    ///     r.try_replace(16..6, "Saturn 🪐"),
+    ///     Err(Error::ByteStartAfterEnd { start: 16, end: 6 })
+    /// );
+    ///
+    /// assert_eq!(r.try_replace(6..16, "Saturn 🪐"), Ok(()));
     /// assert_eq!(r, "Hello Saturn 🪐!");
     /// ```
-    #[track_caller]
     #[inline]
-    pub fn replace<R, T>(&mut self, byte_range: R, text: T)
+    pub fn try_replace<R, T>(
+        &mut self,
+        byte_range: R,
+        text: T,
+    ) -> Result<(), Error>
     where
         R: RangeBounds<usize>,
         T: AsRef<str>,
@@ -684,34 +4250,13 @@ impl Rope {
         let (start, end) =
             range_bounds_to_start_end(byte_range, 0, self.byte_len());
 
-        if start > end {
-            panic::byte_start_after_end(start, end);
-        }
-
-        if end > self.byte_len() {
-            panic::byte_offset_out_of_bounds(end, self.line_len());
-        }
-
-        let text = text.as_ref();
-
-        let mut update_trailing = false;
-
-        if end == self.byte_len() {
-            if !text.is_empty() {
-                self.has_trailing_newline = text.ends_with('\n');
-            } else if start == 0 {
-                self.has_trailing_newline = false;
-            } else {
-                update_trailing = true;
-            }
-        }
+        check_byte_range(start, end, self.byte_len(), |offset| {
+            self.is_char_boundary(offset)
+        })?;
 
-        self.tree.replace(ByteMetric(start)..ByteMetric(end), text);
+        self.replace(start..end, text);
 
-        if update_trailing {
-            self.has_trailing_newline =
-                self.chunks().next_back().unwrap().ends_with('\n');
-        }
+        Ok(())
     }
 
     /// Returns the number of UTF-16 code units the `Rope` would have if it
@@ -729,7 +4274,10 @@ impl Rope {
     #[cfg_attr(docsrs, doc(cfg(feature = "utf16-metric")))]
     #[cfg(feature = "utf16-metric")]
     #[inline]
-    pub fn utf16_len(&self) -> usize {
+    pub fn utf16_len(&self) -> usize
+    where
+        S: MetricSet<Utf16CodeUnits = Maintained>,
+    {
         self.tree.summary().utf16_code_units()
     }
 
@@ -755,7 +4303,10 @@ impl Rope {
     #[cfg(feature = "utf16-metric")]
     #[track_caller]
     #[inline]
-    pub fn utf16_code_unit_of_byte(&self, byte_offset: usize) -> usize {
+    pub fn utf16_code_unit_of_byte(&self, byte_offset: usize) -> usize
+    where
+        S: MetricSet<Utf16CodeUnits = Maintained>,
+    {
         if byte_offset > self.byte_len() {
             panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
         }
@@ -791,9 +4342,13 @@ impl Rope {
     #[cfg(feature = "utf16-metric")]
     #[track_caller]
     #[inline]
-    pub fn utf16_slice<R>(&self, utf16_range: R) -> RopeSlice<'_>
+    pub fn utf16_slice<R>(
+        &self,
+        utf16_range: R,
+    ) -> RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
     where
         R: RangeBounds<usize>,
+        S: MetricSet<Utf16CodeUnits = Maintained>,
     {
         use super::metrics::Utf16Metric;
 
@@ -810,67 +4365,418 @@ impl Rope {
 
         self.tree.slice(Utf16Metric(start)..Utf16Metric(end)).into()
     }
-}
 
-impl From<RopeSlice<'_>> for Rope {
+    /// Checks the invariants of the `Rope`, returning the ones that don't
+    /// hold.
+    ///
+    /// On top of the checks done by [`Tree::validate()`], this checks that
+    /// every chunk is valid UTF-8 and, if the `Rope` is made of more than one
+    /// chunk, that none of them is underfilled.
+    ///
+    /// A `Rope` built through its public API is always valid, so this is
+    /// mostly useful when fuzzing crop itself.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello, world!\n".repeat(100));
+    /// assert!(r.validate().is_empty());
+    /// ```
+    #[inline]
+    pub fn validate(&self) -> Vec<InvariantViolation> {
+        let mut violations = self.tree.validate();
+
+        if combined::IS_ENABLED && !self.has_identical_summary() {
+            violations.push(InvariantViolation::StaleSummary { path: vec![] });
+        }
+
+        let leaves = self.tree.leaves();
+
+        let is_root = leaves.len() == 1;
+
+        for (leaf, chunk) in leaves.enumerate() {
+            if !chunk.is_valid_utf8() {
+                violations.push(InvariantViolation::InvalidUtf8 { leaf });
+            } else if !is_root
+                && chunk.len()
+                    < RopeChunk::<W, B, MAX_BYTES, MIN_FILL, S>::chunk_min()
+            {
+                violations.push(InvariantViolation::UnderfilledLeaf {
+                    leaf,
+                    len: chunk.len(),
+                    min: RopeChunk::<W, B, MAX_BYTES, MIN_FILL, S>::chunk_min(
+                    ),
+                });
+            }
+        }
+
+        violations
+    }
+
+    /// Returns whether the summary of the root, including the parts that
+    /// are combined instead of being added up, is the same as the one of
+    /// the chunks of the `Rope` put together.
     #[inline]
-    fn from(rope_slice: RopeSlice<'_>) -> Rope {
-        Self {
-            has_trailing_newline: rope_slice.has_trailing_newline,
-            tree: Tree::from(rope_slice.tree_slice),
+    fn has_identical_summary(&self) -> bool {
+        let summary = self.chunk_segments().fold(
+            ChunkSummary::default(),
+            |summary, (left, right)| {
+                let mut chunk =
+                    ChunkSummary::from(left) + ChunkSummary::from(right);
+                chunk.join_segments(left, right);
+                summary + chunk
+            },
+        );
+
+        self.tree.summary().is_identical_to(&summary)
+    }
+
+    /// Returns the display width of the `Rope`, i.e. the number of columns
+    /// its text takes up in a terminal, as given by the
+    /// [unicode-width](https://docs.rs/unicode-width) crate.
+    ///
+    /// The width is the sum of the widths of the `char`s, where control
+    /// characters (including line breaks) count as zero. It's stored in the
+    /// B-tree, so the width of any line can be computed in logarithmic time
+    /// by calling this on [`line()`](Self::line()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("a中b\n🦀!");
+    /// assert_eq!(r.width(), 7);
+    /// assert_eq!(r.line(0).width(), 4);
+    /// assert_eq!(r.line(1).width(), 3);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "width-metric")))]
+    #[cfg(feature = "width-metric")]
+    #[inline]
+    pub fn width(&self) -> usize
+    where
+        S: MetricSet<Width = Maintained>,
+    {
+        self.tree.summary().width()
+    }
+
+    /// Returns the number of words in the `Rope`, counted like `wc -w`
+    /// does, i.e. as the number of maximal runs of non-whitespace `char`s.
+    ///
+    /// With the `word-count` feature enabled the number of words is kept in
+    /// the summaries of the B-tree and this runs in constant time.
+    /// Otherwise it scans the whole text, so it runs in linear time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello,  Earth!\n🦀 is\ta crab.\n");
+    /// assert_eq!(r.word_len(), 6);
+    /// ```
+    #[inline]
+    pub fn word_len(&self) -> usize {
+        #[cfg(feature = "word-count")]
+        {
+            self.tree.summary().words()
+        }
+
+        #[cfg(not(feature = "word-count"))]
+        {
+            count_words(self.chunks())
         }
     }
+
+    /// Writes a binary snapshot of the `Rope` which can be loaded back with
+    /// [`from_snapshot()`](Self::from_snapshot()).
+    ///
+    /// The snapshot preserves the shape of the tree of the `Rope` and stores
+    /// the summary of each of its nodes, which is what allows it to be loaded
+    /// without re-scanning the text. The text of every leaf is padded to the
+    /// chunk size, so that the leaves of a `Rope` loaded with
+    /// [`from_shared_snapshot()`](Self::from_shared_snapshot()) can borrow it
+    /// as is.
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by the writer.
+    #[inline]
+    pub fn write_snapshot<T: std::io::Write>(
+        &self,
+        writer: T,
+    ) -> std::io::Result<()> {
+        snapshot::write(self, writer)
+    }
+
+    /// Writes the text of the `Rope` to `writer`, converting every `\n` and
+    /// `\r\n` line break to the given [`LineEnding`].
+    ///
+    /// The chunks are streamed to the writer as they are converted, without
+    /// building a converted copy of the text first. Since this issues many
+    /// small writes it's recommended to wrap unbuffered writers in a
+    /// [`BufWriter`](std::io::BufWriter).
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by the writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{LineEnding, Rope};
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// let mut buf = Vec::new();
+    /// r.write_to_with_line_ending(&mut buf, LineEnding::CrLf).unwrap();
+    /// assert_eq!(buf, b"foo\r\nbar\r\nbaz");
+    /// ```
+    #[inline]
+    pub fn write_to_with_line_ending<T: std::io::Write>(
+        &self,
+        writer: T,
+        line_ending: LineEnding,
+    ) -> std::io::Result<()> {
+        write_with_line_ending(self.chunks(), writer, line_ending)
+    }
+
+    /// Returns a [`RopeWriter`] which implements [`Write`](std::io::Write)
+    /// by appending everything written to it at the end of the `Rope`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// use std::io::Write;
+    ///
+    /// let mut r = Rope::from("foo\n");
+    ///
+    /// let mut writer = r.writer();
+    /// writeln!(writer, "bar").unwrap();
+    /// writer.write_all("baz".as_bytes()).unwrap();
+    /// writer.flush().unwrap();
+    /// drop(writer);
+    ///
+    /// assert_eq!(r, "foo\nbar\nbaz");
+    /// ```
+    #[inline]
+    pub fn writer(
+        &mut self,
+    ) -> RopeWriter<'_, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        RopeWriter::from(self)
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from(
+        rope_slice: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+        Self { tree: Tree::from(rope_slice.tree_slice) }
+    }
+}
+
+/// Reads `reader` until EOF into a new `Rope`, one chunk at a time.
+///
+/// A code point can be split across two reads, so the bytes of an incomplete
+/// code point at the end of a read are carried over to the front of the
+/// buffer before the next one.
+#[inline]
+fn read_to_rope<
+    R: std::io::Read,
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    reader: &mut R,
+) -> std::io::Result<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> {
+    use std::io::{Error, ErrorKind};
+
+    let invalid_data = || {
+        Error::new(
+            ErrorKind::InvalidData,
+            "stream did not contain valid UTF-8",
+        )
+    };
+
+    let mut builder =
+        RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+
+    // A UTF-8 code point is at most 4 bytes long, so at most 3 bytes are
+    // ever carried over.
+    let mut buffer = vec![0; MAX_BYTES + 3];
+    let mut carried = 0;
+
+    loop {
+        let read = match reader.read(&mut buffer[carried..]) {
+            Ok(0) if carried == 0 => return Ok(builder.build()),
+            Ok(0) => return Err(invalid_data()),
+            Ok(read) => read,
+            Err(err) if err.kind() == ErrorKind::Interrupted => continue,
+            Err(err) => return Err(err),
+        };
+
+        let filled = carried + read;
+
+        let valid_up_to = match core::str::from_utf8(&buffer[..filled]) {
+            Ok(_) => filled,
+            Err(err) if err.error_len().is_none() => err.valid_up_to(),
+            Err(_) => return Err(invalid_data()),
+        };
+
+        // SAFETY: the first `valid_up_to` bytes were just checked to be
+        // valid UTF-8.
+        builder.append(unsafe {
+            core::str::from_utf8_unchecked(&buffer[..valid_up_to])
+        });
+
+        buffer.copy_within(valid_up_to..filled, 0);
+        carried = filled - valid_up_to;
+    }
 }
 
-impl core::fmt::Debug for Rope {
+/// Formats the text as a quoted string, or in alternate mode (i.e. `{:#?}`)
+/// as a list of numbered lines if the `lines` feature is enabled. Only the
+/// first and last 20 lines of texts with more than 40 lines are printed in
+/// alternate mode.
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::fmt::Debug for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        #[cfg(feature = "lines")]
+        if f.alternate() {
+            if let Some(result) =
+                S::Lines::debug_lines("Rope", self.byte_slice(..), f)
+            {
+                return result;
+            }
+        }
+
         f.write_str("Rope(\"")?;
         debug_chunks(self.chunks(), f)?;
         f.write_str("\")")
     }
 }
 
-impl core::fmt::Display for Rope {
+/// Writes the text of the `Rope`, padded and truncated according to the
+/// width, precision and alignment of the formatter like `str`s are.
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::fmt::Display for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        for chunk in self.chunks() {
-            f.write_str(chunk)?;
-        }
-        Ok(())
+        #[cfg(feature = "char-metric")]
+        let char_len = self.tree.summary().maintained_chars();
+
+        #[cfg(not(feature = "char-metric"))]
+        let char_len = None;
+
+        display_chunks(self.chunks(), char_len, f)
     }
 }
 
-impl From<&str> for Rope {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&str> for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn from(s: &str) -> Self {
-        Rope {
-            has_trailing_newline: s.ends_with('\n'),
+        // Most ropes are small, so a string fitting in a single chunk is kept
+        // in a leaf that's only as big as the string.
+        if s.len() <= MAX_BYTES {
+            let leaf = RopeChunk::<W, B, MAX_BYTES, MIN_FILL, S>::small(s);
+            return Self { tree: Tree::from_leaves(core::iter::once(leaf)) };
+        }
+
+        Self {
             tree: Tree::from_leaves(
-                RopeChunk::segmenter(s).map(RopeChunk::from),
+                RopeChunk::<W, B, MAX_BYTES, MIN_FILL, S>::segmenter(s)
+                    .map(RopeChunk::from),
             ),
         }
     }
 }
 
-impl From<String> for Rope {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<String> for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn from(s: String) -> Self {
         s.as_str().into()
     }
 }
 
-impl From<alloc::borrow::Cow<'_, str>> for Rope {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<alloc::borrow::Cow<'_, str>>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn from(moo: alloc::borrow::Cow<'_, str>) -> Self {
         match moo {
-            alloc::borrow::Cow::Owned(s) => Rope::from(s),
-            alloc::borrow::Cow::Borrowed(s) => Rope::from(s),
+            alloc::borrow::Cow::Owned(s) => {
+                RopeWith::<C, W, B, MAX_BYTES, MIN_FILL, S>::from(s)
+            },
+            alloc::borrow::Cow::Borrowed(s) => {
+                RopeWith::<C, W, B, MAX_BYTES, MIN_FILL, S>::from(s)
+            },
         }
     }
 }
 
-impl core::str::FromStr for Rope {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::str::FromStr for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     type Err = core::convert::Infallible;
 
     #[inline]
@@ -879,78 +4785,595 @@ impl core::str::FromStr for Rope {
     }
 }
 
-impl core::cmp::PartialEq<Rope> for Rope {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> for String
+{
     #[inline]
-    fn eq(&self, rhs: &Rope) -> bool {
-        (self.byte_len() == rhs.byte_len())
-            && (self.line_len() == rhs.line_len())
-            && chunks_eq_chunks(self.chunks(), rhs.chunks())
+    fn from(rope: RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> String {
+        String::from(&rope)
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> for String
+{
+    #[inline]
+    fn from(rope: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> String {
+        let mut s = String::new();
+        rope.collect_into(&mut s);
+        s
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> for Vec<u8>
+{
+    #[inline]
+    fn from(rope: RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Vec<u8> {
+        rope.to_vec()
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> for Vec<u8>
+{
+    #[inline]
+    fn from(rope: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Vec<u8> {
+        rope.to_vec()
+    }
+}
+
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > FromIterator<&'a str> for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = &'a str>>(iter: I) -> Self {
+        let mut builder =
+            RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+        for s in iter {
+            builder.append(s);
+        }
+        builder.build()
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > FromIterator<String> for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = String>>(iter: I) -> Self {
+        let mut builder =
+            RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+        for s in iter {
+            builder.append(s);
+        }
+        builder.build()
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > FromIterator<char> for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = char>>(iter: I) -> Self {
+        let mut builder =
+            RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+        let mut buf = [0; 4];
+        for ch in iter {
+            builder.append(ch.encode_utf8(&mut buf));
+        }
+        builder.build()
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > FromIterator<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from_iter<
+        I: IntoIterator<Item = RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>,
+    >(
+        iter: I,
+    ) -> Self {
+        let mut builder =
+            RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+        for rope in iter {
+            for chunk in rope.chunks() {
+                builder.append(chunk);
+            }
+        }
+        builder.build()
+    }
+}
+
+/// Concatenates the `Rope`s by [`append()`](Rope::append())ing them one
+/// after the other, which reuses their chunks.
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::iter::Sum<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn sum<I: Iterator<Item = RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>>(
+        iter: I,
+    ) -> Self {
+        iter.fold(
+            RopeWith::<C, W, B, MAX_BYTES, MIN_FILL, S>::new(),
+            |mut acc, rope| {
+                acc.append(rope);
+                acc
+            },
+        )
+    }
+}
+
+/// Concatenates the `RopeSlice`s by converting them into `Rope`s, which
+/// shares the nodes they fully contain, and
+/// [`append()`](Rope::append())ing them one after the other.
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::iter::Sum<RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn sum<
+        I: Iterator<Item = RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>,
+    >(
+        iter: I,
+    ) -> Self {
+        iter.map(RopeWith::<C, W, B, MAX_BYTES, MIN_FILL, S>::from).sum()
     }
 }
 
-impl core::cmp::PartialEq<RopeSlice<'_>> for Rope {
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::iter::Sum<&'a str> for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn eq(&self, rhs: &RopeSlice<'_>) -> bool {
+    fn sum<I: Iterator<Item = &'a str>>(iter: I) -> Self {
+        iter.collect()
+    }
+}
+
+/// Compares the text of the `Rope`s, skipping the nodes they share at the
+/// start and at the end. A `Rope` and an edited clone of it are compared in
+/// time proportional to the size of the edits, but two `Rope`s built
+/// separately are compared in linear time. With the `fingerprint` feature
+/// the [fingerprints](RopeWith::fingerprint()) of the `Rope`s are compared
+/// first, so `Rope`s with different texts are almost always told apart in
+/// constant time.
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn eq(&self, rhs: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> bool {
+        if self.byte_len() != rhs.byte_len() {
+            return false;
+        }
+
+        #[cfg(feature = "fingerprint")]
+        if self.fingerprint() != rhs.fingerprint() {
+            return false;
+        }
+
+        // Ropes obtained by editing a clone of another rope share the nodes
+        // that weren't affected by the edits, so we only need to compare the
+        // text between the subtrees they share at the start and at the end.
+        let prefix = self.tree.shared_prefix(&rhs.tree).bytes();
+
+        if prefix == self.byte_len() {
+            return true;
+        }
+
+        let suffix = self
+            .tree
+            .shared_suffix(&rhs.tree)
+            .bytes()
+            .min(self.byte_len() - prefix);
+
+        let range = prefix..self.byte_len() - suffix;
+
+        chunks_eq_chunks(
+            self.byte_slice(range.clone()).chunks(),
+            rhs.byte_slice(range).chunks(),
+        )
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn eq(
+        &self,
+        rhs: &RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> bool {
         (self.byte_len() == rhs.byte_len())
-            && (self.line_len() == rhs.line_len())
             && chunks_eq_chunks(self.chunks(), rhs.chunks())
     }
 }
 
-impl core::cmp::PartialEq<str> for Rope {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<str> for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn eq(&self, rhs: &str) -> bool {
         (self.byte_len() == rhs.len()) && chunks_eq_str(self.chunks(), rhs)
     }
 }
 
-impl core::cmp::PartialEq<Rope> for str {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> for str
+{
     #[inline]
-    fn eq(&self, rhs: &Rope) -> bool {
+    fn eq(&self, rhs: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> bool {
         rhs == self
     }
 }
 
-impl core::cmp::PartialEq<&str> for Rope {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<&str>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn eq(&self, rhs: &&str) -> bool {
         self == *rhs
     }
 }
 
-impl core::cmp::PartialEq<Rope> for &str {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for &str
+{
     #[inline]
-    fn eq(&self, rhs: &Rope) -> bool {
+    fn eq(&self, rhs: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> bool {
         rhs == self
     }
 }
 
-impl core::cmp::PartialEq<String> for Rope {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<String>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn eq(&self, rhs: &String) -> bool {
         self == &**rhs
     }
 }
 
-impl core::cmp::PartialEq<Rope> for String {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for String
+{
     #[inline]
-    fn eq(&self, rhs: &Rope) -> bool {
+    fn eq(&self, rhs: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> bool {
         rhs == self
     }
 }
 
-impl core::cmp::PartialEq<alloc::borrow::Cow<'_, str>> for Rope {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<alloc::borrow::Cow<'_, str>>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn eq(&self, rhs: &alloc::borrow::Cow<'_, str>) -> bool {
         self == &**rhs
     }
 }
 
-impl core::cmp::PartialEq<Rope> for alloc::borrow::Cow<'_, str> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for alloc::borrow::Cow<'_, str>
+{
+    #[inline]
+    fn eq(&self, rhs: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> bool {
+        rhs == self
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<[u8]>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn eq(&self, rhs: &[u8]) -> bool {
+        (self.byte_len() == rhs.len()) && chunks_eq_bytes(self.chunks(), rhs)
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for [u8]
+{
+    #[inline]
+    fn eq(&self, rhs: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> bool {
+        rhs == self
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<&[u8]>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn eq(&self, rhs: &&[u8]) -> bool {
+        self == *rhs
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for &[u8]
+{
+    #[inline]
+    fn eq(&self, rhs: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> bool {
+        rhs == self
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<Vec<u8>>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn eq(&self, rhs: &Vec<u8>) -> bool {
+        self == &**rhs
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for Vec<u8>
+{
     #[inline]
-    fn eq(&self, rhs: &Rope) -> bool {
+    fn eq(&self, rhs: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> bool {
         rhs == self
     }
 }
 
-impl core::cmp::Eq for Rope {}
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::Eq for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialOrd<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn partial_cmp(
+        &self,
+        rhs: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> Option<core::cmp::Ordering> {
+        Some(self.cmp(rhs))
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialOrd<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn partial_cmp(
+        &self,
+        rhs: &RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> Option<core::cmp::Ordering> {
+        Some(chunks_cmp_chunks(self.chunks(), rhs.chunks()))
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::Ord for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn cmp(
+        &self,
+        rhs: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> core::cmp::Ordering {
+        chunks_cmp_chunks(self.chunks(), rhs.chunks())
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::hash::Hash for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        hash_chunks(self.chunks(), state)
+    }
+}
@@ -1,38 +1,107 @@
-use super::gap_buffer::GapBuffer;
-use super::metrics::ChunkSummary;
-use super::rope::RopeChunk;
-use super::utils::split_adjusted;
-use super::Rope;
-use crate::tree::TreeBuilder;
+use super::gap_buffer::RawGapBuffer;
+use super::line_breaks::{LfBreaks, LineBreaks};
+use super::line_ending::LineEnding;
+use super::metrics::{
+    AllMetrics,
+    CharWeight,
+    ChunkSummary,
+    MetricSet,
+    ZeroWeight,
+};
+use super::rope::{RopeChunk, CHUNK_MAX_BYTES, CHUNK_MIN_FILL};
+use super::utils::{split_adjusted, split_chunk_adjusted};
+use super::{Rope, RopeWith};
+use crate::tree::{AtomicCounter, RefCounter, TreeBuilder};
 
 /// An incremental [`Rope`](crate::Rope) builder.
+///
+/// A [`LocalRope`](crate::LocalRope) is built by a
+/// `RopeBuilder<LocalCounter>`, which can be created with
+/// `RopeBuilder::default()` and given a capacity with
+/// [`reserve()`](Self::reserve()).
 #[derive(Clone, Default)]
-pub struct RopeBuilder {
-    tree_builder: TreeBuilder<{ Rope::arity() }, RopeChunk>,
-    buffer: RopeChunk,
+pub struct RopeBuilder<
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet = AllMetrics,
+> {
+    tree_builder: TreeBuilder<
+        { Rope::arity() },
+        RopeChunk<W, B, MAX_BYTES, MIN_FILL, S>,
+        C,
+    >,
+    buffer: RopeChunk<W, B, MAX_BYTES, MIN_FILL, S>,
     buffer_len_left: usize,
-    rope_has_trailing_newline: bool,
+
+    /// The number of bytes the caller expects the final `Rope` to contain,
+    /// or zero if it's not known.
+    capacity: usize,
+
+    /// The number of bytes in the chunks already passed to the
+    /// `TreeBuilder`.
+    built_len: usize,
+
+    /// Whether `\r\n`s and lone `\r`s are replaced with `\n`s.
+    normalize_line_endings: bool,
+
+    /// Whether the last text appended ended with a `\r` that hasn't been
+    /// normalized yet because the next text could start with a `\n`.
+    pending_cr: bool,
+
+    /// The number of line endings of each kind normalized so far.
+    line_endings: LineEndingCounts,
+}
+
+#[derive(Clone, Copy, Default)]
+struct LineEndingCounts {
+    lf: usize,
+    crlf: usize,
+    cr: usize,
 }
 
 /// Pushes as mush of the slice as possible onto the left chunk of the gap
-/// buffer, returning the rest (if any).
+/// buffer without making it longer than `chunk_len` bytes, returning the rest
+/// (if any).
 ///
 /// Note that this doesn't update the summary of the left chunk of the gap
 /// buffer because it's faster to do it only once before passing the buffer to
 /// the `TreeBuilder`.
 #[inline]
-fn gap_buffer_push_with_remainder<'a, const MAX_BYTES: usize>(
-    buffer: &mut GapBuffer<MAX_BYTES>,
+fn gap_buffer_push_with_remainder<
+    'a,
+    const MAX_BYTES: usize,
+    W: CharWeight,
+    B: LineBreaks,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    buffer: &mut RawGapBuffer<MAX_BYTES, W, B, MIN_FILL, S>,
     buffer_len_left: &mut usize,
+    chunk_len: usize,
     s: &'a str,
 ) -> Option<&'a str> {
     debug_assert_eq!(buffer.len_right(), 0);
+    debug_assert!(chunk_len <= MAX_BYTES);
 
     let len_left = *buffer_len_left;
 
-    let space_left = MAX_BYTES - len_left;
+    let mut space_left = chunk_len.saturating_sub(len_left);
+
+    let (mut push, mut rest) = split_chunk_adjusted::<false>(
+        s,
+        space_left,
+        RawGapBuffer::<MAX_BYTES>::max_grapheme_shift(),
+    );
 
-    let (push, rest) = split_adjusted::<false>(s, space_left);
+    // The chunk length can cut through the first code point of the slice. If
+    // the buffer is empty we can't flush it, so we fill it up to `MAX_BYTES`.
+    if len_left == 0 && push.is_empty() && !rest.is_empty() {
+        space_left = MAX_BYTES;
+        (push, rest) = split_adjusted::<false>(s, space_left);
+    }
 
     debug_assert!(push.len() <= space_left);
 
@@ -48,33 +117,105 @@ fn gap_buffer_push_with_remainder<'a, const MAX_BYTES: usize>(
     }
 }
 
-impl RopeBuilder {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > RopeBuilder<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     /// Appends `text` to the end of the `Rope` being built.
     #[inline]
     pub fn append<T>(&mut self, text: T) -> &mut Self
     where
         T: AsRef<str>,
     {
-        let mut text = text.as_ref();
+        let text = text.as_ref();
+
+        if self.normalize_line_endings {
+            self.append_normalized(text);
+        } else {
+            self.append_raw(text);
+        }
+
+        self
+    }
+
+    /// Appends `text` after replacing its `\r\n`s and lone `\r`s with
+    /// `\n`s, counting the line endings it contains.
+    ///
+    /// A `\r\n` can be split across two calls, so a `\r` at the end of
+    /// `text` is held back until we know whether the next text starts with a
+    /// `\n`.
+    #[inline]
+    fn append_normalized(&mut self, mut text: &str) {
+        if text.is_empty() {
+            return;
+        }
+
+        if core::mem::take(&mut self.pending_cr) {
+            if let Some(rest) = text.strip_prefix('\n') {
+                self.line_endings.crlf += 1;
+                text = rest;
+            } else {
+                self.line_endings.cr += 1;
+            }
+            self.append_raw("\n");
+        }
+
+        while let Some(cr) = text.find('\r') {
+            let (before, after) = (&text[..cr], &text[cr + 1..]);
+
+            self.line_endings.lf += count_lfs(before);
+            self.append_raw(before);
+
+            if after.is_empty() {
+                self.pending_cr = true;
+                return;
+            }
+
+            if let Some(rest) = after.strip_prefix('\n') {
+                self.line_endings.crlf += 1;
+                text = rest;
+            } else {
+                self.line_endings.cr += 1;
+                text = after;
+            }
+
+            self.append_raw("\n");
+        }
+
+        self.line_endings.lf += count_lfs(text);
+        self.append_raw(text);
+    }
+
+    #[inline]
+    fn append_raw(&mut self, mut text: &str) {
+        loop {
+            let chunk_len = self.next_chunk_len();
+
+            let rest = match gap_buffer_push_with_remainder(
+                &mut self.buffer,
+                &mut self.buffer_len_left,
+                chunk_len,
+                text,
+            ) {
+                Some(rest) => rest,
+                None => break,
+            };
 
-        while let Some(rest) = gap_buffer_push_with_remainder(
-            &mut self.buffer,
-            &mut self.buffer_len_left,
-            text,
-        ) {
             self.buffer.left_summary =
                 ChunkSummary::from(self.buffer_left_chunk());
 
             self.tree_builder.append(core::mem::take(&mut self.buffer));
 
+            self.built_len += self.buffer_len_left;
             self.buffer_len_left = 0;
 
             text = rest;
         }
-
-        self.rope_has_trailing_newline = self.buffer.has_trailing_newline();
-
-        self
     }
 
     #[inline]
@@ -105,26 +246,176 @@ impl RopeBuilder {
     /// assert_eq!(rope, "ƒoo\nbär\r\nbaz");
     /// ```
     #[inline]
-    pub fn build(mut self) -> Rope {
-        if self.buffer_len_left > 0 {
-            self.rope_has_trailing_newline =
-                self.buffer.has_trailing_newline();
+    pub fn build(mut self) -> RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+        if core::mem::take(&mut self.pending_cr) {
+            self.line_endings.cr += 1;
+            self.append_raw("\n");
+        }
 
+        if self.buffer_len_left > 0 {
             self.buffer.left_summary =
                 ChunkSummary::from(self.buffer_left_chunk());
 
             self.tree_builder.append(self.buffer);
         }
 
-        Rope {
-            tree: self.tree_builder.build(),
-            has_trailing_newline: self.rope_has_trailing_newline,
+        let mut rope = RopeWith { tree: self.tree_builder.build() };
+
+        // The text is appended in arbitrary pieces, so e.g. a grapheme
+        // cluster can be split between two chunks.
+        rope.finish_edit(0..rope.byte_len());
+
+        rope
+    }
+
+    /// Returns the line ending that was used the most in the text appended
+    /// so far, or `None` if it didn't contain any line breaks.
+    ///
+    /// Line endings are only counted while
+    /// [`normalize_line_endings()`](Self::normalize_line_endings()) is
+    /// enabled, and a `\r` at the end of the last text appended isn't
+    /// counted until we know whether the next one starts with a `\n`. Ties
+    /// are resolved in favor of `\n`, then `\r\n`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{LineEnding, RopeBuilder};
+    /// #
+    /// let mut builder = RopeBuilder::new();
+    ///
+    /// builder.normalize_line_endings(true).append("foo\r\nbar\r\nbaz\n");
+    ///
+    /// assert_eq!(builder.line_ending(), Some(LineEnding::CrLf));
+    /// ```
+    #[inline]
+    pub fn line_ending(&self) -> Option<LineEnding> {
+        let LineEndingCounts { lf, crlf, cr } = self.line_endings;
+
+        if lf == 0 && crlf == 0 && cr == 0 {
+            None
+        } else if lf >= crlf && lf >= cr {
+            Some(LineEnding::Lf)
+        } else if crlf >= cr {
+            Some(LineEnding::CrLf)
+        } else {
+            Some(LineEnding::Cr)
+        }
+    }
+
+    /// Sets whether the `\r\n` and lone `\r` line endings in the text
+    /// appended from now on are replaced with `\n`s.
+    ///
+    /// This lets editors normalize the line endings of a file in the same
+    /// pass that loads it, and then use the original convention returned by
+    /// [`line_ending()`](Self::line_ending()) to convert them back with
+    /// [`Rope::write_to_with_line_ending()`] when saving it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{LineEnding, RopeBuilder};
+    /// #
+    /// let mut builder = RopeBuilder::new();
+    ///
+    /// builder
+    ///     .normalize_line_endings(true)
+    ///     .append("ƒoo\r")
+    ///     .append("\nbär\r")
+    ///     .append("baz\r\n");
+    ///
+    /// assert_eq!(builder.line_ending(), Some(LineEnding::CrLf));
+    /// assert_eq!(builder.build(), "ƒoo\nbär\nbaz\n");
+    /// ```
+    #[inline]
+    pub fn normalize_line_endings(&mut self, normalize: bool) -> &mut Self {
+        if !normalize && core::mem::take(&mut self.pending_cr) {
+            self.line_endings.cr += 1;
+            self.append_raw("\n");
+        }
+
+        self.normalize_line_endings = normalize;
+        self
+    }
+
+    /// Returns the length of the chunk currently being filled.
+    ///
+    /// This is `MAX_BYTES` unless we know the final length of the `Rope` and
+    /// a full chunk would leave too few bytes for the last one, in which case
+    /// we split the difference like `Rope::from()` does, so that the last
+    /// chunk doesn't have to be rebalanced when the build completes.
+    #[inline]
+    fn next_chunk_len(&self) -> usize {
+        let remaining = self.capacity.saturating_sub(self.built_len);
+
+        if remaining > MAX_BYTES {
+            let min_bytes =
+                RopeChunk::<W, B, MAX_BYTES, MIN_FILL, S>::min_bytes();
+
+            if remaining - MAX_BYTES < min_bytes {
+                return remaining - min_bytes;
+            }
         }
+
+        MAX_BYTES
     }
 
+    /// Reserves capacity for at least `additional` more bytes to be appended
+    /// to the `Rope` being built.
+    ///
+    /// See [`with_capacity()`](Self::with_capacity()) for more information.
+    #[inline]
+    pub fn reserve(&mut self, additional: usize) -> &mut Self {
+        self.capacity = self
+            .capacity
+            .max(self.built_len + self.buffer_len_left + additional);
+
+        let leaves = self.capacity / MAX_BYTES + 1;
+        self.tree_builder.reserve(leaves);
+
+        self
+    }
+}
+
+impl RopeBuilder {
     /// Creates a new `RopeBuilder`.
     #[inline]
     pub fn new() -> Self {
         Self::default()
     }
+
+    /// Creates a new `RopeBuilder` for a `Rope` of around `byte_capacity`
+    /// bytes, e.g. the length of the file it's being read from.
+    ///
+    /// Knowing the final length ahead of time lets the builder preallocate
+    /// the levels of the tree and size the last chunks so that none of them
+    /// needs to be rebalanced when the build completes. The capacity is only
+    /// a hint: appending more or less text than that is fine.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::RopeBuilder;
+    /// #
+    /// let text = "Hello Earth!\n".repeat(1000);
+    ///
+    /// let mut builder = RopeBuilder::with_capacity(text.len());
+    ///
+    /// for line in text.split_inclusive('\n') {
+    ///     builder.append(line);
+    /// }
+    ///
+    /// assert_eq!(builder.build(), text);
+    /// ```
+    #[inline]
+    pub fn with_capacity(byte_capacity: usize) -> Self {
+        let mut builder = Self::new();
+        builder.reserve(byte_capacity);
+        builder
+    }
+}
+
+#[inline]
+fn count_lfs(s: &str) -> usize {
+    s.bytes().filter(|&b| b == b'\n').count()
 }
@@ -0,0 +1,219 @@
+use std::io::{self, BufRead, Read, Seek, SeekFrom};
+
+use super::iterators::Chunks;
+use super::line_breaks::{LfBreaks, LineBreaks};
+use super::metrics::{AllMetrics, CharWeight, MetricSet, ZeroWeight};
+use super::rope::{CHUNK_MAX_BYTES, CHUNK_MIN_FILL};
+use super::RopeSlice;
+use crate::tree::{AtomicCounter, RefCounter};
+
+/// An adapter implementing [`Read`], [`BufRead`] and [`Seek`] over the
+/// contents of a [`Rope`](crate::Rope) or a [`RopeSlice`].
+///
+/// This is created by the [`reader()`](crate::Rope::reader()) method on
+/// `Rope` and `RopeSlice`. The bytes are read directly from the chunks of the
+/// rope, so no intermediate `String` is ever allocated.
+#[derive(Clone)]
+pub struct RopeReader<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet = AllMetrics,
+> {
+    /// The slice being read.
+    slice: RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+
+    /// The chunks of `slice` after the one `buf` is in.
+    chunks: Chunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+
+    /// The bytes of the current chunk that are yet to be read.
+    buf: &'a [u8],
+
+    /// The byte offset in `slice` of the first byte of `buf`. This can be
+    /// past the end of the slice if the reader was seeked there.
+    pos: usize,
+}
+
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeReader<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from(slice: RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        Self { chunks: slice.chunks(), slice, buf: &[], pos: 0 }
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::fmt::Debug for RopeReader<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RopeReader")
+            .field("slice", &self.slice)
+            .field("pos", &self.pos)
+            .finish()
+    }
+}
+
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > RopeReader<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    /// Returns the byte offset of the next byte that will be read.
+    #[inline]
+    pub fn position(&self) -> usize {
+        self.pos
+    }
+
+    /// Moves the reader to the given byte offset, which doesn't have to be a
+    /// char boundary nor to be within the slice.
+    #[inline]
+    fn seek_to(&mut self, byte_offset: usize) {
+        if byte_offset >= self.slice.byte_len() {
+            let end = self.slice.byte_len();
+            self.chunks = self.slice.byte_slice(end..).chunks();
+            self.buf = &[];
+            self.pos = byte_offset;
+            return;
+        }
+
+        // A char is at most 4 bytes long, so we'll find a char boundary in at
+        // most 3 steps.
+        let mut start = byte_offset;
+
+        while !self.slice.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        self.chunks = self.slice.byte_slice(start..).chunks();
+
+        // The first chunk starts at `start` and contains at least the whole
+        // char starting there, so it's at least `byte_offset - start` bytes
+        // long.
+        let chunk = self.chunks.next().unwrap().as_bytes();
+
+        self.buf = &chunk[byte_offset - start..];
+        self.pos = byte_offset;
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Read for RopeReader<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn read(&mut self, out: &mut [u8]) -> io::Result<usize> {
+        let buf = self.fill_buf()?;
+        let read = buf.len().min(out.len());
+        out[..read].copy_from_slice(&buf[..read]);
+        self.consume(read);
+        Ok(read)
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > BufRead for RopeReader<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn fill_buf(&mut self) -> io::Result<&[u8]> {
+        while self.buf.is_empty() {
+            match self.chunks.next() {
+                Some(chunk) => self.buf = chunk.as_bytes(),
+                None => break,
+            }
+        }
+
+        Ok(self.buf)
+    }
+
+    #[inline]
+    fn consume(&mut self, amount: usize) {
+        let amount = amount.min(self.buf.len());
+        self.buf = &self.buf[amount..];
+        self.pos += amount;
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Seek for RopeReader<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn seek(&mut self, pos: SeekFrom) -> io::Result<u64> {
+        let offset_from = |base: usize, offset: i64| {
+            let base = base as u64;
+            if offset >= 0 {
+                base.checked_add(offset as u64)
+            } else {
+                base.checked_sub(offset.unsigned_abs())
+            }
+        };
+
+        let new_pos = match pos {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => {
+                offset_from(self.slice.byte_len(), offset)
+            },
+            SeekFrom::Current(offset) => offset_from(self.pos, offset),
+        };
+
+        let Some(new_pos) =
+            new_pos.and_then(|new_pos| usize::try_from(new_pos).ok())
+        else {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            ));
+        };
+
+        if new_pos != self.pos {
+            self.seek_to(new_pos);
+        }
+
+        Ok(new_pos as u64)
+    }
+
+    #[inline]
+    fn stream_position(&mut self) -> io::Result<u64> {
+        Ok(self.pos as u64)
+    }
+}
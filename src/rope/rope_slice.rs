@@ -1,21 +1,77 @@
+#[cfg(feature = "grapheme-count")]
+use core::ops::Range;
 use core::ops::RangeBounds;
 
-use super::iterators::{Bytes, Chars, Chunks, Lines, RawLines};
-use super::metrics::{ByteMetric, RawLineMetric};
-use super::rope::RopeChunk;
+use super::ascii_case::{chunks_eq_ignore_ascii_case, AsciiCaseText};
+use super::error::*;
+use super::iterators::{Bytes, Chars, ChunkSegments, Chunks, RollingHashes};
+#[cfg(feature = "lines")]
+use super::iterators::{
+    Lines,
+    LinesInByteRange,
+    LinesWithOffsets,
+    Paragraphs,
+    RawLines,
+};
+use super::line_breaks::{LfBreaks, LineBreaks};
+use super::line_ending::{write_with_line_ending, LineEnding};
+#[cfg(feature = "lines")]
+use super::metrics::sealed::Sealed as _;
+#[cfg(feature = "char-metric")]
+use super::metrics::CharMetric;
+#[cfg(any(
+    feature = "lines",
+    feature = "char-metric",
+    feature = "utf16-metric",
+    feature = "width-metric"
+))]
+use super::metrics::Maintained;
+#[cfg(feature = "lines")]
+use super::metrics::RawLineMetric;
+use super::metrics::{
+    AllMetrics,
+    ByteMetric,
+    CharWeight,
+    MetricSet,
+    ZeroWeight,
+};
+use super::rope::{RopeChunk, CHUNK_MAX_BYTES, CHUNK_MIN_FILL};
+use super::search::{self, SearchPattern};
+use super::snap::{snap_range, Snap};
 use super::utils::{panic_messages as panic, *};
-use super::Rope;
+use super::{Rope, RopeReader, RopeWith};
 use crate::range_bounds_to_start_end;
-use crate::tree::TreeSlice;
+use crate::tree::{AtomicCounter, RefCounter, TreeSlice};
 
 /// An immutable slice of a [`Rope`](crate::Rope).
 #[derive(Copy, Clone)]
-pub struct RopeSlice<'a> {
-    pub(super) tree_slice: TreeSlice<'a, { Rope::arity() }, RopeChunk>,
-    pub(super) has_trailing_newline: bool,
+pub struct RopeSlice<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet = AllMetrics,
+> {
+    pub(super) tree_slice: TreeSlice<
+        'a,
+        { Rope::arity() },
+        RopeChunk<W, B, MAX_BYTES, MIN_FILL, S>,
+        C,
+    >,
 }
 
-impl<'a> RopeSlice<'a> {
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[doc(hidden)]
     pub fn assert_invariants(&self) {
         self.tree_slice.assert_invariants();
@@ -25,8 +81,6 @@ impl<'a> RopeSlice<'a> {
 
         let last = self.tree_slice.end_slice();
         last.assert_invariants();
-
-        assert_eq!(self.has_trailing_newline, last.has_trailing_newline())
     }
 
     /// Returns the byte at `byte_index`.
@@ -81,6 +135,162 @@ impl<'a> RopeSlice<'a> {
         self.tree_slice.summary().bytes()
     }
 
+    /// Returns the byte offset at which the display width of the `RopeSlice`
+    /// reaches the given column, i.e. the sum of the display widths of the
+    /// `char`s before it.
+    ///
+    /// If the column falls inside a wide `char` the byte offset right after
+    /// that `char` is returned. Together with [`line()`](Self::line()) this
+    /// gives the byte offset of a visual column within a line.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column is out of bounds (i.e. greater than
+    /// [`width()`](Self::width())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("a中b\n🦀!");
+    /// let s = r.byte_slice(..);
+    /// assert_eq!(s.byte_of_column(1), 1);
+    /// assert_eq!(s.byte_of_column(2), 4);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "width-metric")))]
+    #[cfg(feature = "width-metric")]
+    #[track_caller]
+    #[inline]
+    pub fn byte_of_column(&self, column: usize) -> usize
+    where
+        S: MetricSet<Width = Maintained>,
+    {
+        if column > self.width() {
+            panic::column_out_of_bounds(column, self.width());
+        }
+
+        let ByteMetric(byte_offset) = self
+            .tree_slice
+            .convert_measure(super::metrics::WidthMetric(column));
+
+        byte_offset
+    }
+
+    /// Returns the byte offset corresponding to the given offset in the
+    /// custom metric given by its [`CharWeight`](crate::CharWeight).
+    ///
+    /// If the offset falls inside the weight of a `char` the byte offset
+    /// right after that `char` is returned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the offset is out of bounds (i.e. greater than
+    /// [`custom_len()`](Self::custom_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crop::{tree::AtomicCounter, CharWeight, RopeWith};
+    ///
+    /// // Count the curly braces.
+    /// #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    /// struct Braces;
+    ///
+    /// impl CharWeight for Braces {
+    ///     fn weight(ch: char) -> usize {
+    ///         matches!(ch, '{' | '}') as usize
+    ///     }
+    /// }
+    ///
+    /// let r = RopeWith::<AtomicCounter, Braces>::from("a { b } c");
+    /// let s = r.byte_slice(..);
+    /// assert_eq!(s.byte_of_custom_unit(1), 3);
+    /// assert_eq!(s.byte_of_custom_unit(2), 7);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "custom-metric")))]
+    #[cfg(feature = "custom-metric")]
+    #[track_caller]
+    #[inline]
+    pub fn byte_of_custom_unit(&self, custom_offset: usize) -> usize {
+        if custom_offset > self.custom_len() {
+            panic::custom_offset_out_of_bounds(
+                custom_offset,
+                self.custom_len(),
+            );
+        }
+
+        let ByteMetric(byte_offset) = self
+            .tree_slice
+            .convert_measure(super::metrics::CustomMetric(custom_offset));
+
+        byte_offset
+    }
+
+    /// Returns the byte offset of the start of the given extended grapheme
+    /// cluster.
+    ///
+    /// With the `grapheme-count` feature the number of clusters of every
+    /// node of the B-tree is known, so only the chunk containing the cluster
+    /// has to be segmented, and this runs in logarithmic time as long as
+    /// there aren't many chunk boundaries before it where the clusters have
+    /// to be segmented again to be counted (see [`Rope::grapheme_len()`]).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the grapheme offset is out of bounds (i.e. greater than
+    /// [`grapheme_len()`](Self::grapheme_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🐻‍❄️");
+    /// let s = r.byte_slice(3..);
+    ///
+    /// assert_eq!(s.byte_of_grapheme(1), 1);
+    /// assert_eq!(s.byte_of_grapheme(2), 3);
+    /// assert_eq!(s.byte_of_grapheme(3), s.byte_len());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "grapheme-count")))]
+    #[cfg(feature = "grapheme-count")]
+    #[track_caller]
+    #[inline]
+    pub fn byte_of_grapheme(&self, grapheme_offset: usize) -> usize {
+        use super::snap::graphemes::is_boundary;
+
+        let (mut graphemes, chunk_range) =
+            self.chunk_where_graphemes_exceed(grapheme_offset);
+
+        let Some(chunk_range) = chunk_range else {
+            if grapheme_offset > graphemes {
+                panic::grapheme_offset_out_of_bounds(
+                    grapheme_offset,
+                    graphemes,
+                );
+            }
+
+            return self.byte_len();
+        };
+
+        let mut offset = chunk_range.start;
+
+        for ch in self.byte_slice(chunk_range).chars() {
+            if is_boundary(*self, offset) {
+                if graphemes == grapheme_offset {
+                    return offset;
+                }
+
+                graphemes += 1;
+            }
+
+            offset += ch.len_utf8();
+        }
+
+        unreachable!("the cluster starts in the chunk");
+    }
+
     /// Returns the byte offset of the start of the given line.
     ///
     /// # Panics
@@ -103,9 +313,14 @@ impl<'a> RopeSlice<'a> {
     /// assert_eq!(s.byte_of_line(1), "bär\r\n".len());
     /// assert_eq!(s.byte_of_line(s.line_len()), s.byte_len());
     /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
     #[track_caller]
     #[inline]
-    pub fn byte_of_line(&self, line_offset: usize) -> usize {
+    pub fn byte_of_line(&self, line_offset: usize) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
         if line_offset > self.line_len() {
             panic::line_offset_out_of_bounds(line_offset, self.line_len());
         }
@@ -144,7 +359,10 @@ impl<'a> RopeSlice<'a> {
     #[cfg(feature = "utf16-metric")]
     #[track_caller]
     #[inline]
-    pub fn byte_of_utf16_code_unit(&self, utf16_offset: usize) -> usize {
+    pub fn byte_of_utf16_code_unit(&self, utf16_offset: usize) -> usize
+    where
+        S: MetricSet<Utf16CodeUnits = Maintained>,
+    {
         if utf16_offset > self.utf16_len() {
             panic::utf16_offset_out_of_bounds(utf16_offset, self.utf16_len())
         }
@@ -178,7 +396,10 @@ impl<'a> RopeSlice<'a> {
     /// ```
     #[track_caller]
     #[inline]
-    pub fn byte_slice<R>(self, byte_range: R) -> RopeSlice<'a>
+    pub fn byte_slice<R>(
+        self,
+        byte_range: R,
+    ) -> RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
     where
         R: RangeBounds<usize>,
     {
@@ -196,6 +417,56 @@ impl<'a> RopeSlice<'a> {
         self.tree_slice.slice(ByteMetric(start)..ByteMetric(end)).into()
     }
 
+    /// Returns an immutable slice of the `RopeSlice` in the specified byte range,
+    /// after moving its start and end to valid boundaries according to
+    /// `snap` instead of panicking if they don't lie on one.
+    ///
+    /// Endpoints past the end of the `RopeSlice` are clamped to
+    /// [`byte_len()`](Self::byte_len()). If snapping inward moves the start
+    /// past the end the returned slice is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the byte range is greater than the end after
+    /// clamping them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Rope, Snap};
+    /// #
+    /// let r = Rope::from("a🗻∈🌏");
+    /// let s = r.byte_slice(1..);
+    ///
+    /// assert_eq!(s.byte_slice_snapped(2..5, Snap::CharOutward), "🗻∈");
+    /// assert_eq!(s.byte_slice_snapped(2..9, Snap::CharInward), "∈");
+    /// assert_eq!(s.byte_slice_snapped(5..100, Snap::CharInward), "🌏");
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn byte_slice_snapped<R>(
+        self,
+        byte_range: R,
+        snap: Snap,
+    ) -> RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        let start = start.min(self.byte_len());
+        let end = end.min(self.byte_len());
+
+        if start > end {
+            panic::byte_start_after_end(start, end);
+        }
+
+        let range = snap_range(self, start, end, snap);
+
+        self.byte_slice(range)
+    }
+
     /// Returns an iterator over the bytes of this `RopeSlice`.
     ///
     /// # Examples
@@ -215,223 +486,547 @@ impl<'a> RopeSlice<'a> {
     /// assert_eq!(None, bytes.next());
     /// ```
     #[inline]
-    pub fn bytes(&self) -> Bytes<'a> {
+    pub fn bytes(&self) -> Bytes<'a, C, W, B, MAX_BYTES, MIN_FILL, S> {
         Bytes::from(self)
     }
 
-    /// Returns an iterator over the [`char`]s of this `RopeSlice`.
+    /// Returns the [`char`] at `char_index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the char index is out of bounds (i.e. greater than or equal
+    /// to [`char_len()`](Self::char_len())).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("🐻‍❄️");
-    /// let s = r.byte_slice(4..);
-    ///
-    /// let mut chars = s.chars();
+    /// let r = Rope::from("xañ🦀");
+    /// let s = r.byte_slice(1..);
     ///
-    /// assert_eq!(Some('\u{200d}'), chars.next());
-    /// assert_eq!(Some('❄'), chars.next());
-    /// assert_eq!(Some('\u{fe0f}'), chars.next());
-    /// assert_eq!(None, chars.next());
+    /// assert_eq!(s.char(0), 'a');
+    /// assert_eq!(s.char(1), 'ñ');
+    /// assert_eq!(s.char(2), '🦀');
     /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "char-metric")))]
+    #[cfg(feature = "char-metric")]
+    #[track_caller]
     #[inline]
-    pub fn chars(&self) -> Chars<'a> {
-        Chars::from(self)
+    pub fn char(&self, char_index: usize) -> char
+    where
+        S: MetricSet<Chars = Maintained>,
+    {
+        if char_index >= self.char_len() {
+            panic::char_index_out_of_bounds(char_index, self.char_len());
+        }
+
+        let ByteMetric(byte_offset) =
+            self.tree_slice.convert_measure(CharMetric(char_index));
+
+        self.char_at_byte(byte_offset)
     }
 
-    /// Returns an iterator over the chunks of this `RopeSlice`.
+    /// Returns the [`char`] starting at `byte_index`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte index is out of bounds (i.e. greater than or equal
+    /// to [`byte_len()`](Self::byte_len())) or if it doesn't lie on a code
+    /// point boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("xañ🦀");
+    /// let s = r.byte_slice(1..);
+    ///
+    /// assert_eq!(s.char_at_byte(0), 'a');
+    /// assert_eq!(s.char_at_byte(1), 'ñ');
+    /// assert_eq!(s.char_at_byte(3), '🦀');
+    /// ```
+    #[track_caller]
     #[inline]
-    pub fn chunks(&self) -> Chunks<'a> {
-        Chunks::from(self)
+    pub fn char_at_byte(&self, byte_index: usize) -> char {
+        if byte_index >= self.byte_len() {
+            panic::byte_index_out_of_bounds(byte_index, self.byte_len());
+        }
+
+        let (chunk, ByteMetric(chunk_byte_offset)) =
+            self.tree_slice.leaf_at_measure(ByteMetric(byte_index + 1));
+
+        chunk.char_at_byte(byte_index - chunk_byte_offset)
     }
 
-    /// Returns an iterator over the extended grapheme clusters of this
-    /// `RopeSlice`.
+    /// Returns the number of [`char`]s in the `RopeSlice`.
     ///
     /// # Examples
     ///
-    /// ```no_run
+    /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("arg!\r\n🐻‍❄️");
-    /// let s = r.byte_slice(3..);
-    ///
-    /// let mut graphemes = s.graphemes();
+    /// let r = Rope::from("xañ🦀");
+    /// let s = r.byte_slice(1..);
     ///
-    /// assert_eq!(Some("!"), graphemes.next().as_deref());
-    /// assert_eq!(Some("\r\n"), graphemes.next().as_deref());
-    /// assert_eq!(Some("🐻‍❄️"), graphemes.next().as_deref());
-    /// assert_eq!(None, graphemes.next());
+    /// assert_eq!(s.char_len(), 3);
     /// ```
-    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
-    #[cfg(feature = "graphemes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "char-metric")))]
+    #[cfg(feature = "char-metric")]
     #[inline]
-    pub fn graphemes(&self) -> crate::iter::Graphemes<'a> {
-        crate::iter::Graphemes::from(self)
+    pub fn char_len(&self) -> usize
+    where
+        S: MetricSet<Chars = Maintained>,
+    {
+        self.tree_slice.summary().chars()
     }
 
-    /// Returns `true` if the given byte offset lies on a [`char`] boundary.
+    /// Returns the char offset of the start of the given line.
+    ///
+    /// See [`Rope::char_of_line()`] for more.
     ///
     /// # Panics
     ///
-    /// Panics if the byte offset is out of bounds (i.e. greater than
-    /// [`byte_len()`](Self::byte_len())).
+    /// Panics if the line offset is out of bounds (i.e. greater than
+    /// [`line_len()`](Self::line_len())).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("Löwe 老虎 Léopard");
-    /// let s = r.byte_slice(3..);
+    /// let r = Rope::from("ƒoo\nbär\r\nbaz");
     ///
-    /// assert!(s.is_char_boundary(0));
-    /// assert!(s.is_char_boundary(s.byte_len()));
-    /// assert!(s.is_char_boundary(6)); // between '老' and '虎'
-    /// assert!(!s.is_char_boundary(12)); // between the 1st and 2nd byte of 'é'
+    /// let s = r.byte_slice(..);
+    /// assert_eq!(s.char_of_line(1), 4);
+    ///
+    /// let s = r.byte_slice("ƒoo\n".len()..);
+    /// assert_eq!(s.char_of_line(1), 5);
+    /// assert_eq!(s.char_of_line(s.line_len()), s.char_len());
     /// ```
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(feature = "char-metric", feature = "lines")))
+    )]
+    #[cfg(all(feature = "char-metric", feature = "lines"))]
     #[track_caller]
     #[inline]
-    pub fn is_char_boundary(&self, byte_offset: usize) -> bool {
-        if byte_offset > self.byte_len() {
-            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+    pub fn char_of_line(&self, line_offset: usize) -> usize
+    where
+        S: MetricSet<Chars = Maintained>,
+        S: MetricSet<Lines = Maintained>,
+    {
+        if line_offset > self.line_len() {
+            panic::line_offset_out_of_bounds(line_offset, self.line_len());
         }
 
-        let (chunk, ByteMetric(chunk_byte_offset)) =
-            self.tree_slice.leaf_at_measure(ByteMetric(byte_offset));
+        if line_offset > self.tree_slice.summary().line_breaks() {
+            return self.char_len();
+        }
 
-        chunk.is_char_boundary(byte_offset - chunk_byte_offset)
+        let CharMetric(char_offset) =
+            self.tree_slice.convert_measure(RawLineMetric(line_offset));
+
+        char_offset
     }
 
-    /// Returns `true` if the `RopeSlice`'s byte length is zero.
+    /// Returns an iterator over the [`char`]s of this `RopeSlice`.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("");
-    /// assert!(r.byte_slice(..).is_empty());
+    /// let r = Rope::from("🐻‍❄️");
+    /// let s = r.byte_slice(4..);
     ///
-    /// let r = Rope::from("foo");
-    /// assert!(!r.line_slice(..).is_empty());
+    /// let mut chars = s.chars();
+    ///
+    /// assert_eq!(Some('\u{200d}'), chars.next());
+    /// assert_eq!(Some('❄'), chars.next());
+    /// assert_eq!(Some('\u{fe0f}'), chars.next());
+    /// assert_eq!(None, chars.next());
     /// ```
     #[inline]
-    pub fn is_empty(&self) -> bool {
-        self.byte_len() == 0
+    pub fn chars(&self) -> Chars<'a, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        Chars::from(self)
     }
 
-    /// Returns `true` if the given byte offset lies on a grapheme cluster
-    /// boundary.
+    /// Returns the chunk containing the byte at `byte_index`, together with
+    /// the byte and line offsets of its start in the `RopeSlice`.
+    ///
+    /// The chunk is one of the `&str`s yielded by
+    /// [`chunks()`](Self::chunks()), and it's found in logarithmic time.
+    /// It's only available if `S` maintains the line breaks.
     ///
     /// # Panics
     ///
-    /// Panics if the byte offset is out of bounds (i.e. greater than
-    /// [`byte_len()`](Self::byte_len())).
+    /// Panics if the byte index is out of bounds (i.e. greater than or equal
+    /// to [`byte_len()`](Self::byte_len())).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("aargh!\r\n🐻‍❄️");
-    /// let s = r.line_slice(1..);
+    /// let r = Rope::from("foo\nbar\n".repeat(1000));
+    /// let s = r.byte_slice(1000..);
     ///
-    /// assert!(s.is_grapheme_boundary(0));
-    /// assert!(s.is_grapheme_boundary(s.byte_len()));
-    /// assert!(!s.is_grapheme_boundary(4)); // between the 1st and 2nd code point of '🐻‍❄️'
+    /// let (chunk, chunk_byte, chunk_line) = s.chunk_at_byte(5000);
+    ///
+    /// assert!(chunk_byte <= 5000 && 5000 < chunk_byte + chunk.len());
+    /// assert_eq!(chunk_line, s.line_of_byte(chunk_byte));
+    /// assert_eq!(chunk.as_bytes()[5000 - chunk_byte], s.byte(5000));
     /// ```
-    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
-    #[cfg(feature = "graphemes")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
     #[track_caller]
     #[inline]
-    pub fn is_grapheme_boundary(&self, byte_offset: usize) -> bool {
-        if byte_offset > self.byte_len() {
-            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+    pub fn chunk_at_byte(&self, byte_index: usize) -> (&'a str, usize, usize)
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        let (chunk, chunk_byte_offset) =
+            self.chunk_containing_byte(byte_index);
+        (chunk, chunk_byte_offset, self.line_of_byte(chunk_byte_offset))
+    }
+
+    /// Like [`chunk_at_byte()`](Self::chunk_at_byte()), but without the line
+    /// offset of the chunk, so that it's also available when the `lines`
+    /// feature is disabled.
+    #[track_caller]
+    #[inline]
+    pub(super) fn chunk_containing_byte(
+        &self,
+        byte_index: usize,
+    ) -> (&'a str, usize) {
+        if byte_index >= self.byte_len() {
+            panic::byte_index_out_of_bounds(byte_index, self.byte_len());
         }
 
-        is_grapheme_boundary(self.chunks(), self.byte_len(), byte_offset)
+        let (leaf, ByteMetric(leaf_byte_offset)) =
+            self.tree_slice.leaf_at_measure(ByteMetric(byte_index + 1));
+
+        let (chunk, chunk_offset) =
+            leaf.chunk_at_byte(byte_index - leaf_byte_offset);
+
+        (chunk, leaf_byte_offset + chunk_offset)
     }
 
-    /// Returns the line at `line_index`, without its line terminator.
+    /// Returns the chunk containing the start of the line at `line_index`,
+    /// together with the byte and line offsets of its start in the
+    /// `RopeSlice`.
     ///
-    /// If you want to include the line break consider taking a
-    /// [`line_slice()`](Self::line_slice()) in the
-    /// `line_index..line_index + 1` range.
+    /// See [`chunk_at_byte()`](Self::chunk_at_byte()) for more.
     ///
     /// # Panics
     ///
     /// Panics if the line index is out of bounds (i.e. greater than or equal
-    /// to [`line_len()`](Self::byte_len())).
+    /// to [`line_len()`](Self::line_len())).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz");
-    /// let s = r.line_slice(..2);
+    /// let r = Rope::from("foo\nbar\n".repeat(1000));
+    /// let s = r.byte_slice(1000..);
     ///
-    /// assert_eq!(s.line(0), "foo");
-    /// assert_eq!(s.line(1), "bar");
+    /// let (chunk, chunk_byte, chunk_line) = s.chunk_at_line(1000);
+    ///
+    /// assert!(chunk_line <= 1000);
+    /// assert_eq!(chunk_line, s.line_of_byte(chunk_byte));
+    /// assert!(chunk_byte + chunk.len() > s.byte_of_line(1000));
     /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
     #[track_caller]
     #[inline]
-    pub fn line(self, line_index: usize) -> RopeSlice<'a> {
+    pub fn chunk_at_line(&self, line_index: usize) -> (&'a str, usize, usize)
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
         if line_index >= self.line_len() {
-            panic::line_offset_out_of_bounds(line_index, self.line_len());
+            panic::line_index_out_of_bounds(line_index, self.line_len());
         }
 
-        let tree_slice = self
-            .tree_slice
-            .slice(RawLineMetric(line_index)..RawLineMetric(line_index + 1));
+        self.chunk_at_byte(self.byte_of_line(line_index))
+    }
 
-        let mut line = Self { tree_slice, has_trailing_newline: false };
+    /// Returns an iterator over the text of this `RopeSlice`, yielding the
+    /// two contiguous segments of every chunk as a pair.
+    ///
+    /// Chunks are stored in gap buffers, whose text is split in two by the
+    /// gap. While [`chunks()`](Self::chunks()) yields each segment on its
+    /// own, this method yields the segments before and after the gap of each
+    /// chunk together, which is useful when processing whole chunks at a
+    /// time, e.g. with vectored I/O, without having to copy them into a
+    /// contiguous buffer first. Either segment can be empty, but not both.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello Earth!\n".repeat(1000));
+    /// let s = r.byte_slice(6..);
+    ///
+    /// let mut text = String::new();
+    ///
+    /// for (before_gap, after_gap) in s.chunk_segments() {
+    ///     text.push_str(before_gap);
+    ///     text.push_str(after_gap);
+    /// }
+    ///
+    /// assert_eq!(text, s.to_string());
+    /// ```
+    #[inline]
+    pub fn chunk_segments(
+        &self,
+    ) -> ChunkSegments<'a, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        ChunkSegments::from(self)
+    }
 
-        if line.tree_slice.summary().line_breaks() == 1 {
-            line.truncate_trailing_line_break();
+    /// Returns an iterator over the chunks of this `RopeSlice`.
+    ///
+    /// The iterator is double-ended, so the chunks can also be walked from
+    /// the end of the `RopeSlice` with [`rev()`](Iterator::rev()), or from
+    /// both ends at once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello Earth!\n".repeat(1000));
+    /// let s = r.byte_slice(6..);
+    ///
+    /// let mut reversed = s.chunks().rev().collect::<Vec<_>>();
+    /// reversed.reverse();
+    ///
+    /// assert_eq!(reversed.concat(), s.to_string());
+    /// ```
+    #[inline]
+    pub fn chunks(&self) -> Chunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        Chunks::from(self)
+    }
+
+    /// Compares the text of the `RopeSlice` with the text of `other`
+    /// according to the rules of the given
+    /// [`Collator`](icu_collator::Collator).
+    ///
+    /// See [`Rope::collate()`] for more.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// use core::cmp::Ordering;
+    ///
+    /// use icu_collator::{Collator, CollatorOptions};
+    ///
+    /// let sv = Collator::try_new(&"sv".parse().unwrap(), CollatorOptions::new())
+    ///     .unwrap();
+    ///
+    /// let r = Rope::from("zebra äpple");
+    /// let (zebra, apple) = (r.byte_slice(..5), r.byte_slice(6..));
+    /// assert_eq!(zebra.collate(&apple, &sv), Ordering::Less);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode-collation")))]
+    #[cfg(feature = "unicode-collation")]
+    #[inline]
+    pub fn collate(
+        &self,
+        other: &RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+        collator: &icu_collator::Collator,
+    ) -> core::cmp::Ordering {
+        super::collation::collate(*self, *other, collator)
+    }
+
+    /// Appends the contents of the `RopeSlice` to the given `String`.
+    ///
+    /// The `String` is grown by the byte length of the `RopeSlice` up front and the
+    /// text is then copied one chunk at a time. Clearing the same `String`
+    /// before each call lets you reuse its allocation.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar");
+    /// let s = r.byte_slice(..);
+    ///
+    /// let mut buf = String::from("> ");
+    /// s.collect_into(&mut buf);
+    /// assert_eq!(buf, "> foo\nbar");
+    ///
+    /// buf.clear();
+    /// s.collect_into(&mut buf);
+    /// assert_eq!(buf, "foo\nbar");
+    /// ```
+    #[inline]
+    pub fn collect_into(&self, buf: &mut String) {
+        collect_chunks_into(self.chunks(), self.byte_len(), buf)
+    }
+
+    /// Returns the display width of the text of the `RopeSlice` up to the given
+    /// byte offset, i.e. the visual column it's at.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("a中b\n🦀!");
+    /// let s = r.byte_slice(1..);
+    /// assert_eq!(s.column_of_byte(3), 2);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "width-metric")))]
+    #[cfg(feature = "width-metric")]
+    #[track_caller]
+    #[inline]
+    pub fn column_of_byte(&self, byte_offset: usize) -> usize
+    where
+        S: MetricSet<Width = Maintained>,
+    {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
         }
 
-        line
+        let super::metrics::WidthMetric(column) =
+            self.tree_slice.convert_measure(ByteMetric(byte_offset));
+
+        column
     }
 
-    /// Returns the number of lines in the `RopeSlice`.
+    /// Returns the number of non-overlapping occurrences of `pattern` in the
+    /// `RopeSlice`, counted from left to right like
+    /// [`str::matches()`](str::matches()) does.
     ///
-    /// The final line break is optional and doesn't count as a separate empty
-    /// line.
+    /// The chunks are searched one at a time without being collected into a
+    /// `String`, and occurrences spanning two or more chunks are counted as
+    /// well.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("a\nb\r\n");
+    /// let r = Rope::from("abababa\n🦀🦀🦀");
+    /// let s = r.byte_slice(1..);
+    ///
+    /// assert_eq!(s.count_matches("aba"), 1);
+    /// assert_eq!(s.count_matches('🦀'), 3);
+    /// ```
+    #[inline]
+    pub fn count_matches<P>(&self, pattern: P) -> usize
+    where
+        P: SearchPattern,
+    {
+        pattern
+            .with_str(|pattern| search::count_matches(self.chunks(), pattern))
+    }
+
+    /// Returns the measure of the RopeSlice in the custom metric given by
+    /// its [`CharWeight`](crate::CharWeight), i.e. the sum of the weights of
+    /// all its `char`s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use crop::{tree::AtomicCounter, CharWeight, RopeWith};
+    ///
+    /// // Count the curly braces.
+    /// #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    /// struct Braces;
     ///
+    /// impl CharWeight for Braces {
+    ///     fn weight(ch: char) -> usize {
+    ///         matches!(ch, '{' | '}') as usize
+    ///     }
+    /// }
+    ///
+    /// let r = RopeWith::<AtomicCounter, Braces>::from("{ a { b } }");
     /// let s = r.byte_slice(..);
-    /// assert_eq!(s.line_len(), 2);
+    /// assert_eq!(s.custom_len(), 4);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "custom-metric")))]
+    #[cfg(feature = "custom-metric")]
+    #[inline]
+    pub fn custom_len(&self) -> usize {
+        self.tree_slice.summary().custom_units()
+    }
+
+    /// Returns a sub-slice of this `RopeSlice` in the specified range of the
+    /// custom metric given by its [`CharWeight`](crate::CharWeight), where
+    /// the start and end of the range are interpreted as offsets.
     ///
-    /// let s = r.byte_slice(..3);
-    /// assert_eq!(s.line_len(), 2);
+    /// # Panics
     ///
-    /// let s = r.byte_slice(..2);
-    /// assert_eq!(s.line_len(), 1);
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`custom_len()`](Self::custom_len())).
     ///
-    /// let s = r.byte_slice(..1);
-    /// assert_eq!(s.line_len(), 1);
+    /// # Examples
     ///
-    /// let s = r.byte_slice(..0);
-    /// assert_eq!(s.line_len(), 0);
     /// ```
+    /// use crop::{tree::AtomicCounter, CharWeight, RopeWith};
+    ///
+    /// // Count the curly braces.
+    /// #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    /// struct Braces;
+    ///
+    /// impl CharWeight for Braces {
+    ///     fn weight(ch: char) -> usize {
+    ///         matches!(ch, '{' | '}') as usize
+    ///     }
+    /// }
+    ///
+    /// let r = RopeWith::<AtomicCounter, Braces>::from("a { b } c");
+    /// let s = r.byte_slice(..);
+    /// assert_eq!(s.custom_slice(..1), "a {");
+    /// assert_eq!(s.custom_slice(1..2), " b }");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "custom-metric")))]
+    #[cfg(feature = "custom-metric")]
+    #[track_caller]
     #[inline]
-    pub fn line_len(&self) -> usize {
-        self.tree_slice.summary().line_breaks() + 1
-            - (self.has_trailing_newline as usize)
-            - (self.is_empty() as usize)
+    pub fn custom_slice<R>(
+        self,
+        custom_range: R,
+    ) -> RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(custom_range, 0, self.custom_len());
+
+        if start > end {
+            panic::custom_start_after_end(start, end);
+        }
+
+        if end > self.custom_len() {
+            panic::custom_offset_out_of_bounds(end, self.custom_len());
+        }
+
+        // A range inside the weight of a single `char` would give a start
+        // past the end if we sliced by the custom metric directly, so we go
+        // through the byte offsets instead.
+        let start = self.byte_of_custom_unit(start);
+        let end = self.byte_of_custom_unit(end);
+
+        self.tree_slice.slice(ByteMetric(start)..ByteMetric(end)).into()
     }
 
-    /// Returns the line offset of the given byte.
+    /// Returns the offset in the custom metric given by its
+    /// [`CharWeight`](crate::CharWeight) corresponding to the given byte
+    /// offset.
     ///
     /// # Panics
     ///
@@ -441,136 +1036,1448 @@ impl<'a> RopeSlice<'a> {
     /// # Examples
     ///
     /// ```
+    /// use crop::{tree::AtomicCounter, CharWeight, RopeWith};
+    ///
+    /// // Count the curly braces.
+    /// #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    /// struct Braces;
+    ///
+    /// impl CharWeight for Braces {
+    ///     fn weight(ch: char) -> usize {
+    ///         matches!(ch, '{' | '}') as usize
+    ///     }
+    /// }
+    ///
+    /// let r = RopeWith::<AtomicCounter, Braces>::from("a { b } c");
+    /// let s = r.byte_slice(..);
+    /// assert_eq!(s.custom_unit_of_byte(4), 1);
+    /// assert_eq!(s.custom_unit_of_byte(s.byte_len()), 2);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "custom-metric")))]
+    #[cfg(feature = "custom-metric")]
+    #[track_caller]
+    #[inline]
+    pub fn custom_unit_of_byte(&self, byte_offset: usize) -> usize {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        let super::metrics::CustomMetric(custom_offset) =
+            self.tree_slice.convert_measure(ByteMetric(byte_offset));
+
+        custom_offset
+    }
+
+    /// Checks whether the `RopeSlice` and `other` are equal when ignoring the
+    /// case of ASCII letters, like
+    /// [`str::eq_ignore_ascii_case()`](str::eq_ignore_ascii_case()) does.
+    ///
+    /// `other` can be a `&str`, a `&String`, a `&Rope` or a `RopeSlice`. The
+    /// texts are compared chunk by chunk, without allocating lowercased
+    /// copies of them.
+    ///
+    /// # Examples
+    ///
+    /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let r = Rope::from("Hello, World!");
+    /// let s = r.byte_slice(7..);
     ///
+    /// assert!(s.eq_ignore_ascii_case("world!"));
+    /// assert!(s.eq_ignore_ascii_case(&Rope::from("WORLD!")));
+    /// assert!(!s.eq_ignore_ascii_case(r.byte_slice(..6)));
+    /// ```
+    #[inline]
+    pub fn eq_ignore_ascii_case<'b, T>(&self, other: T) -> bool
+    where
+        T: AsciiCaseText<'b>,
+    {
+        self.byte_len() == other.byte_len()
+            && chunks_eq_ignore_ascii_case(self.chunks(), other.text_chunks())
+    }
+
+    /// Returns the byte offsets of the non-overlapping occurrences of
+    /// `pattern` in the `RopeSlice`, from left to right, like
+    /// [`str::match_indices()`](str::match_indices()) does.
+    ///
+    /// Occurrences spanning two or more chunks are found as well.
+    /// See [`par_find_all()`](Self::par_find_all()) to search big texts
+    /// using multiple threads.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Rope};
+    /// #
+    /// let r = Rope::from("abababa\n🦀🦀🦀");
     /// let s = r.byte_slice(..);
-    /// assert_eq!(s.line_of_byte(0), 0);
-    /// assert_eq!(s.line_of_byte(4), 1);
     ///
-    /// let s = r.line_slice(1..);
-    /// assert_eq!(s.line_of_byte(0), 0);
-    /// assert_eq!(s.line_of_byte(4), 0); // between the '\r' and the '\n'
-    /// assert_eq!(s.line_of_byte(s.byte_len()), 1);
+    /// assert_eq!(s.find_all("aba"), [0, 4]);
+    /// assert_eq!(s.find_all("🦀"), [8, 12, 16]);
+    /// ```
+    #[inline]
+    pub fn find_all<P>(&self, pattern: P) -> Vec<usize>
+    where
+        P: SearchPattern,
+    {
+        pattern.with_str(|pattern| search::find_all(self.chunks(), pattern))
+    }
+
+    /// Returns the 61-bit fingerprint of the text of the `RopeSlice`, which
+    /// is the same as the one of a [`Rope`] with the same text (see
+    /// [`Rope::fingerprint()`]).
+    ///
+    /// This combines the fingerprints the B-tree keeps for every node the
+    /// slice spans, only hashing the text of the chunks at its two ends, so
+    /// it runs in logarithmic time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello World!");
+    /// let s = r.byte_slice(6..11);
+    ///
+    /// assert_eq!(s.fingerprint(), Rope::from("World").fingerprint());
+    /// assert_eq!(s.fingerprint(), r.byte_slice(6..11).fingerprint());
+    /// assert_ne!(s.fingerprint(), r.byte_slice(0..5).fingerprint());
     /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "fingerprint")))]
+    #[cfg(feature = "fingerprint")]
+    #[inline]
+    pub fn fingerprint(&self) -> u64 {
+        use super::metrics::ChunkSummary;
+
+        // The summary of the slice comes from a subtraction, so its
+        // fingerprint has to be combined again from the ones of its
+        // subtrees.
+        let mut summary = ChunkSummary::<W, B, S>::new();
+        self.tree_slice.for_each_subtree(|_| false, |s| summary += s);
+        summary.fingerprint()
+    }
+
+    /// Returns the number of extended grapheme clusters in the `RopeSlice`.
+    ///
+    /// With the `grapheme-count` feature enabled this adds up the number of
+    /// clusters the B-tree keeps for every node the slice spans, segmenting
+    /// only the chunks at its two ends and the text after the few chunk
+    /// boundaries the counts can't be combined across, so it runs in
+    /// logarithmic time in most cases (see [`Rope::grapheme_len()`]).
+    ///
+    /// Otherwise it segments the whole text, so it runs in linear time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🐻‍❄️");
+    /// assert_eq!(r.byte_slice(3..).grapheme_len(), 3);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[inline]
+    pub fn grapheme_len(&self) -> usize {
+        #[cfg(feature = "grapheme-count")]
+        {
+            let (graphemes, _) = self.chunk_where_graphemes_exceed(usize::MAX);
+            graphemes
+        }
+
+        #[cfg(not(feature = "grapheme-count"))]
+        {
+            self.graphemes().count()
+        }
+    }
+
+    /// Returns the byte range of the first subtree of the `RopeSlice` that
+    /// can't be split further (i.e. a chunk, or a piece of one) before the
+    /// end of which more than `n` of its grapheme clusters start, together
+    /// with the number of clusters starting before that subtree.
+    ///
+    /// If there's no such subtree the range is `None`, and the number of
+    /// clusters is the one of the whole slice.
+    ///
+    /// The counts in the summaries of the subtrees are added up in order,
+    /// and fixed up at the unsure joins between them by segmenting the text
+    /// after each one again until its clusters line up with the ones of the
+    /// slice.
+    #[cfg(feature = "grapheme-count")]
+    #[inline]
+    pub(super) fn chunk_where_graphemes_exceed(
+        &self,
+        n: usize,
+    ) -> (usize, Option<Range<usize>>) {
+        use core::cell::Cell;
+
+        use super::metrics::ChunkSummary;
+
+        // Both closures need to know the summary of the text before the
+        // current subtree, how many boundaries the fixups at the unsure
+        // joins in it added and removed, and whether the chunk was already
+        // found.
+        let before = Cell::new(ChunkSummary::<W, B, S>::default());
+        let fixups = Cell::new((0, 0));
+        let chunk_range = Cell::new(None);
+
+        // Returns the number of clusters starting before the end of the
+        // subtree, together with the fixups up to it.
+        let graphemes_up_to = |summary: &ChunkSummary<W, B, S>| {
+            let before = before.get();
+            let (mut added, mut removed) = fixups.get();
+
+            if before.has_unsure_grapheme_join(summary) {
+                let start = before.bytes();
+                let (a, r) =
+                    self.resegment_graphemes(start..start + summary.bytes());
+                added += a;
+                removed += r;
+            }
+
+            let graphemes = (before + summary).graphemes() + added - removed;
+
+            (graphemes, (added, removed))
+        };
+
+        self.tree_slice.for_each_subtree(
+            |summary| {
+                chunk_range.get().is_none()
+                    && (summary.unsure_grapheme_joins() > 0
+                        || graphemes_up_to(summary).0 > n)
+            },
+            |summary| {
+                if chunk_range.get().is_some() {
+                    return;
+                }
+
+                let (graphemes, up_to) = graphemes_up_to(summary);
+
+                // The subtrees with unsure joins in them are always
+                // descended into, and the leaves are never.
+                if graphemes > n {
+                    let start = before.get().bytes();
+                    chunk_range.set(Some((start, start + summary.bytes())));
+                } else {
+                    before.set(before.get() + summary);
+                    fixups.set(up_to);
+                }
+            },
+        );
+
+        let (added, removed) = fixups.get();
+
+        let graphemes = before.get().graphemes() + added - removed;
+
+        (graphemes, chunk_range.get().map(|(start, end)| start..end))
+    }
+
+    /// Segments the text in the given byte range, which starts at an unsure
+    /// join, both on its own and as part of the `RopeSlice`, up to the first
+    /// grapheme boundary the two have in common (after which they're the
+    /// same).
+    ///
+    /// Returns how many boundaries only the `RopeSlice` has and how many only
+    /// the text on its own has.
+    #[cfg(feature = "grapheme-count")]
+    #[inline]
+    fn resegment_graphemes(&self, byte_range: Range<usize>) -> (usize, usize) {
+        use super::snap::graphemes::is_boundary;
+
+        let piece = self.byte_slice(byte_range.clone());
+
+        let mut offset = byte_range.start;
+
+        let (mut added, mut removed) = (0, 0);
+
+        for ch in piece.chars() {
+            let in_slice = is_boundary(*self, offset);
+            let in_piece = is_boundary(piece, offset - byte_range.start);
+
+            if in_slice && in_piece {
+                break;
+            }
+
+            added += in_slice as usize;
+            removed += in_piece as usize;
+            offset += ch.len_utf8();
+        }
+
+        (added, removed)
+    }
+
+    /// Returns the grapheme offset of the given byte offset, i.e. the number
+    /// of extended grapheme clusters before the one containing it.
+    ///
+    /// Like [`grapheme_len()`](Self::grapheme_len()) this runs in
+    /// logarithmic time in most cases.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🐻‍❄️");
+    /// let s = r.byte_slice(3..);
+    ///
+    /// assert_eq!(s.grapheme_of_byte(1), 1); // the start of "\r\n"
+    /// assert_eq!(s.grapheme_of_byte(2), 1); // between '\r' and '\n'
+    /// assert_eq!(s.grapheme_of_byte(3), 2); // the start of '🐻‍❄️'
+    /// assert_eq!(s.grapheme_of_byte(s.byte_len()), 3);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "grapheme-count")))]
+    #[cfg(feature = "grapheme-count")]
     #[track_caller]
     #[inline]
-    pub fn line_of_byte(&self, byte_offset: usize) -> usize {
+    pub fn grapheme_of_byte(&self, byte_offset: usize) -> usize {
         if byte_offset > self.byte_len() {
             panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
         }
 
-        let RawLineMetric(line_offset) =
-            self.tree_slice.convert_measure(ByteMetric(byte_offset));
+        // The clusters of the text before the offset are the ones of the
+        // slice starting before it, including the one containing it.
+        let graphemes = self.byte_slice(..byte_offset).grapheme_len();
 
-        line_offset
+        if self.is_grapheme_boundary(byte_offset) {
+            graphemes
+        } else {
+            graphemes - 1
+        }
+    }
+
+    /// Returns an immutable slice of the `RopeSlice` in the specified
+    /// grapheme range, where the start and end of the range are interpreted
+    /// as offsets.
+    ///
+    /// Like [`grapheme_len()`](Self::grapheme_len()) this runs in
+    /// logarithmic time in most cases.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`grapheme_len()`](Self::grapheme_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🐻‍❄️");
+    /// let s = r.byte_slice(3..);
+    ///
+    /// assert_eq!(s.grapheme_slice(..2), "!\r\n");
+    /// assert_eq!(s.grapheme_slice(2..), "🐻‍❄️");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "grapheme-count")))]
+    #[cfg(feature = "grapheme-count")]
+    #[track_caller]
+    #[inline]
+    pub fn grapheme_slice<R>(
+        &self,
+        grapheme_range: R,
+    ) -> RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(grapheme_range, 0, self.grapheme_len());
+
+        if start > end {
+            panic::grapheme_start_after_end(start, end);
+        }
+
+        let start_byte = self.byte_of_grapheme(start);
+
+        let end_byte =
+            if start == end { start_byte } else { self.byte_of_grapheme(end) };
+
+        self.byte_slice(start_byte..end_byte)
+    }
+
+    /// Returns an iterator over the extended grapheme clusters of this
+    /// `RopeSlice`.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("arg!\r\n🐻‍❄️");
+    /// let s = r.byte_slice(3..);
+    ///
+    /// let mut graphemes = s.graphemes();
+    ///
+    /// assert_eq!(Some("!"), graphemes.next().as_deref());
+    /// assert_eq!(Some("\r\n"), graphemes.next().as_deref());
+    /// assert_eq!(Some("🐻‍❄️"), graphemes.next().as_deref());
+    /// assert_eq!(None, graphemes.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[inline]
+    pub fn graphemes(
+        &self,
+    ) -> crate::iter::Graphemes<'a, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        crate::iter::Graphemes::from(self)
+    }
+
+    /// Returns `true` if the `RopeSlice` ends with a line break.
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub(super) fn has_trailing_newline(&self) -> bool {
+        self.tree_slice.end_slice().has_trailing_newline()
+    }
+
+    /// Returns `true` if the given byte offset lies on a [`char`] boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Löwe 老虎 Léopard");
+    /// let s = r.byte_slice(3..);
+    ///
+    /// assert!(s.is_char_boundary(0));
+    /// assert!(s.is_char_boundary(s.byte_len()));
+    /// assert!(s.is_char_boundary(6)); // between '老' and '虎'
+    /// assert!(!s.is_char_boundary(12)); // between the 1st and 2nd byte of 'é'
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn is_char_boundary(&self, byte_offset: usize) -> bool {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        #[cfg(feature = "char-metric")]
+        if self.is_ascii() {
+            return true;
+        }
+
+        let (chunk, ByteMetric(chunk_byte_offset)) =
+            self.tree_slice.leaf_at_measure(ByteMetric(byte_offset));
+
+        chunk.is_char_boundary(byte_offset - chunk_byte_offset)
+    }
+
+    /// Returns `true` if all the characters of the `RopeSlice` are in the ASCII
+    /// range.
+    ///
+    /// With the `char-metric` feature this runs in `O(1)`, since the number
+    /// of `char`s of the text is tracked alongside its byte length. Without
+    /// it every chunk is checked.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello, 🌎");
+    /// assert!(r.byte_slice(..7).is_ascii());
+    /// assert!(!r.byte_slice(5..).is_ascii());
+    /// ```
+    #[inline]
+    pub fn is_ascii(&self) -> bool {
+        #[cfg(feature = "char-metric")]
+        {
+            let summary = self.tree_slice.summary();
+            if let Some(chars) = summary.maintained_chars() {
+                return chars == summary.bytes();
+            }
+        }
+
+        self.chunks().all(str::is_ascii)
+    }
+
+    /// Returns `true` if the `RopeSlice`'s byte length is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("");
+    /// assert!(r.byte_slice(..).is_empty());
+    ///
+    /// let r = Rope::from("foo");
+    /// assert!(!r.byte_slice(..).is_empty());
+    /// ```
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.byte_len() == 0
+    }
+
+    /// Returns `true` if the given byte offset lies on a grapheme cluster
+    /// boundary.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("aargh!\r\n🐻‍❄️");
+    /// let s = r.line_slice(1..);
+    ///
+    /// assert!(s.is_grapheme_boundary(0));
+    /// assert!(s.is_grapheme_boundary(s.byte_len()));
+    /// assert!(!s.is_grapheme_boundary(4)); // between the 1st and 2nd code point of '🐻‍❄️'
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    #[track_caller]
+    #[inline]
+    pub fn is_grapheme_boundary(&self, byte_offset: usize) -> bool {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        // In ASCII text the only grapheme cluster spanning more than one
+        // byte is "\r\n".
+        #[cfg(feature = "char-metric")]
+        if self.is_ascii() {
+            return byte_offset == 0
+                || byte_offset == self.byte_len()
+                || self.byte(byte_offset - 1) != b'\r'
+                || self.byte(byte_offset) != b'\n';
+        }
+
+        is_grapheme_boundary(self.chunks(), self.byte_len(), byte_offset)
+    }
+
+    /// Returns `true` if the text of the `RopeSlice` is in the given Unicode
+    /// normalization form.
+    ///
+    /// Most text is checked in a single pass over its `char`s, and only the
+    /// parts the quick check can't decide on are normalized to compare them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{NormalizationForm, Rope};
+    /// #
+    /// let r = Rope::from("Le Cafe\u{301}");
+    /// let s = r.byte_slice(3..);
+    /// assert!(!s.is_normalized(NormalizationForm::Nfc));
+    /// assert!(s.is_normalized(NormalizationForm::Nfd));
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode-normalization")))]
+    #[cfg(feature = "unicode-normalization")]
+    #[inline]
+    pub fn is_normalized(&self, form: super::NormalizationForm) -> bool {
+        super::normalization::is_normalized(self.chars(), form)
+    }
+
+    /// Returns the line at `line_index`, without its line terminator.
+    ///
+    /// If you want to include the line break consider using the
+    /// [`raw_line()`](Self::raw_line()) method instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let s = r.line_slice(..2);
+    ///
+    /// assert_eq!(s.line(0), "foo");
+    /// assert_eq!(s.line(1), "bar");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn line(
+        self,
+        line_index: usize,
+    ) -> RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        if line_index >= self.line_len() {
+            panic::line_index_out_of_bounds(line_index, self.line_len());
+        }
+
+        let tree_slice = self
+            .tree_slice
+            .slice(RawLineMetric(line_index)..RawLineMetric(line_index + 1));
+
+        let mut line = Self { tree_slice };
+
+        if line.tree_slice.summary().line_breaks() == 1 {
+            line.truncate_trailing_line_break();
+        }
+
+        line
+    }
+
+    /// Returns the byte offset of the end of the line containing the given
+    /// byte offset, not including its line terminator.
+    ///
+    /// Like in [`line_of_byte()`](Self::line_of_byte()), an offset inside
+    /// a line terminator belongs to the line it terminates, so the returned
+    /// offset can come before it. If the `RopeSlice` ends with a line break its
+    /// length is returned for the offset at its end.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("afoo\nbar\r\nbaz");
+    /// let s = r.byte_slice(1..);
+    ///
+    /// assert_eq!(s.line_boundary_after(0), 3);
+    /// assert_eq!(s.line_boundary_after(3), 3);
+    /// assert_eq!(s.line_boundary_after(4), 7);
+    /// assert_eq!(s.line_boundary_after(9), 12);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn line_boundary_after(&self, byte_offset: usize) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        let line_index = self.line_of_byte(byte_offset);
+
+        if line_index == self.line_len() {
+            return self.byte_len();
+        }
+
+        self.byte_of_line(line_index) + self.line(line_index).byte_len()
+    }
+
+    /// Returns the byte offset of the start of the line containing the given
+    /// byte offset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("afoo\nbar\r\nbaz");
+    /// let s = r.byte_slice(1..);
+    ///
+    /// assert_eq!(s.line_boundary_before(0), 0);
+    /// assert_eq!(s.line_boundary_before(3), 0);
+    /// assert_eq!(s.line_boundary_before(4), 4);
+    /// assert_eq!(s.line_boundary_before(12), 9);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn line_boundary_before(&self, byte_offset: usize) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        self.byte_of_line(self.line_of_byte(byte_offset))
+    }
+
+    /// Returns the indentation of the line at `line_index`, i.e. the spaces
+    /// and tabs it starts with, as a `(bytes, columns)` pair.
+    ///
+    /// The columns are computed by advancing tabs to the next multiple of
+    /// `tab_width`. Only the chunks spanned by the indentation are scanned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())) or if `tab_width` is zero.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("fn main() {\n  \tlet a = 1;\n}\n");
+    /// let s = r.byte_slice(1..);
+    ///
+    /// assert_eq!(s.line_indent(1, 4), (3, 4));
+    /// assert_eq!(s.line_indent(1, 8), (3, 8));
+    /// assert_eq!(s.line_indent(2, 4), (0, 0));
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn line_indent(
+        &self,
+        line_index: usize,
+        tab_width: usize,
+    ) -> (usize, usize)
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        line_indent(self.line(line_index), tab_width)
+    }
+
+    /// Returns the number of lines in the `RopeSlice`.
+    ///
+    /// The final line break is optional and doesn't count as a separate empty
+    /// line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("a\nb\r\n");
+    ///
+    /// let s = r.byte_slice(..);
+    /// assert_eq!(s.line_len(), 2);
+    ///
+    /// let s = r.byte_slice(..3);
+    /// assert_eq!(s.line_len(), 2);
+    ///
+    /// let s = r.byte_slice(..2);
+    /// assert_eq!(s.line_len(), 1);
+    ///
+    /// let s = r.byte_slice(..1);
+    /// assert_eq!(s.line_len(), 1);
+    ///
+    /// let s = r.byte_slice(..0);
+    /// assert_eq!(s.line_len(), 0);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn line_len(&self) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        self.tree_slice.summary().line_breaks() + 1
+            - (self.has_trailing_newline() as usize)
+            - (self.is_empty() as usize)
+    }
+
+    /// Returns the line offset of the given byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    ///
+    /// let s = r.byte_slice(..);
+    /// assert_eq!(s.line_of_byte(0), 0);
+    /// assert_eq!(s.line_of_byte(4), 1);
+    ///
+    /// let s = r.line_slice(1..);
+    /// assert_eq!(s.line_of_byte(0), 0);
+    /// assert_eq!(s.line_of_byte(4), 0); // between the '\r' and the '\n'
+    /// assert_eq!(s.line_of_byte(s.byte_len()), 1);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn line_of_byte(&self, byte_offset: usize) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        if byte_offset > self.byte_len() {
+            panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
+        }
+
+        let RawLineMetric(line_offset) =
+            self.tree_slice.convert_measure(ByteMetric(byte_offset));
+
+        line_offset
+    }
+
+    /// Returns the line offset of the given char offset.
+    ///
+    /// See [`Rope::line_of_char()`] for more.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the char offset is out of bounds (i.e. greater than
+    /// [`char_len()`](Self::char_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("ƒoo\nbär\r\nbaz");
+    ///
+    /// let s = r.line_slice(1..);
+    /// assert_eq!(s.line_of_char(0), 0);
+    /// assert_eq!(s.line_of_char(4), 0); // between the '\r' and the '\n'
+    /// assert_eq!(s.line_of_char(5), 1);
+    /// assert_eq!(s.line_of_char(s.char_len()), 1);
+    /// ```
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(feature = "char-metric", feature = "lines")))
+    )]
+    #[cfg(all(feature = "char-metric", feature = "lines"))]
+    #[track_caller]
+    #[inline]
+    pub fn line_of_char(&self, char_offset: usize) -> usize
+    where
+        S: MetricSet<Chars = Maintained>,
+        S: MetricSet<Lines = Maintained>,
+    {
+        if char_offset > self.char_len() {
+            panic::char_offset_out_of_bounds(char_offset, self.char_len());
+        }
+
+        let RawLineMetric(line_offset) =
+            self.tree_slice.convert_measure(CharMetric(char_offset));
+
+        line_offset
+    }
+
+    /// Returns a sub-slice of this `RopeSlice` in the specified line range,
+    /// where the start and end of the range are interpreted as offsets.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start is greater than the end or if the end is out of
+    /// bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
+    /// let s = r.byte_slice(2..17);
+    ///
+    /// assert_eq!(s.line_slice(..1), "o\n");
+    /// assert_eq!(s.line_slice(1..3), "bar\r\nbaz\n");
+    /// assert_eq!(s.line_slice(3..), "foob");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn line_slice<R>(
+        self,
+        line_range: R,
+    ) -> RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        R: RangeBounds<usize>,
+        S: MetricSet<Lines = Maintained>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(line_range, 0, self.line_len());
+
+        if start > end {
+            panic::line_start_after_end(start, end);
+        }
+
+        if end > self.line_len() {
+            panic::line_offset_out_of_bounds(end, self.line_len());
+        }
+
+        self.tree_slice.slice(RawLineMetric(start)..RawLineMetric(end)).into()
+    }
+
+    /// Returns an iterator over the lines of this `RopeSlice`, not including
+    /// the line terminators.
+    ///
+    /// The final line break is optional and doesn't cause the iterator to
+    /// return a final empty line.
+    ///
+    /// If you want to include the line breaks consider using the
+    /// [`raw_lines()`](Self::raw_lines()) method instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    /// let s = r.line_slice(1..);
+    ///
+    /// let mut lines = s.lines();
+    ///
+    /// assert_eq!("bar", lines.next().unwrap());
+    /// assert_eq!("baz", lines.next().unwrap());
+    /// assert_eq!(None, lines.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn lines(&self) -> Lines<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        Lines::from(self)
+    }
+
+    /// Returns an iterator over the lines intersecting the given byte range,
+    /// together with their line indices.
+    ///
+    /// The first and last lines are clipped to the byte range, and the line
+    /// terminators are not included, not even the `\r` of a CRLF cut in half
+    /// by the end of the range. This is equivalent to slicing the
+    /// `RopeSlice` and iterating over the [`lines()`](Self::lines()) of the
+    /// slice, except that the yielded indices are the ones of the lines in
+    /// the `RopeSlice`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the start of the range is greater than its end, or if its
+    /// end is out of bounds (i.e. greater than
+    /// [`byte_len()`](Self::byte_len())), or if either endpoint doesn't lie
+    /// on a code point boundary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("!!foo\nbar\r\nbaz\nqux\n");
+    /// let s = r.byte_slice(2..);
+    ///
+    /// let lines = s
+    ///     .lines_in_byte_range(5..15)
+    ///     .map(|(idx, line)| (idx, line.to_string()))
+    ///     .collect::<Vec<_>>();
+    ///
+    /// assert_eq!(lines, [(1, "ar".into()), (2, "baz".into()), (3, "qu".into())]);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn lines_in_byte_range<R>(
+        &self,
+        byte_range: R,
+    ) -> LinesInByteRange<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        R: RangeBounds<usize>,
+        S: MetricSet<Lines = Maintained>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        let splits_crlf = start < end
+            && end < self.byte_len()
+            && self.byte(end - 1) == b'\r'
+            && self.byte(end) == b'\n';
+
+        // The `\r` is trimmed by slicing up to it, and not by truncating
+        // the last line, which can't be re-sliced if it's just the `\r`.
+        let trimmed_last = splits_crlf.then(|| {
+            let line_start = self.byte_of_line(self.line_of_byte(end - 1));
+            self.byte_slice(line_start.max(start)..end - 1)
+        });
+
+        LinesInByteRange::new(
+            self.byte_slice(start..end),
+            self.line_of_byte(start),
+            trimmed_last,
+        )
+    }
+
+    /// Returns an iterator over the lines of this `RopeSlice` together with the
+    /// byte offsets of their starts, not including the line terminators.
+    ///
+    /// This is equivalent to zipping [`lines()`](Self::lines()) with the
+    /// [`byte_of_line()`](Self::byte_of_line()) of every line, but doesn't
+    /// need to query the offset of each line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    /// let s = r.byte_slice(1..);
+    ///
+    /// let mut lines = s.lines_with_offsets();
+    ///
+    /// let (offset, line) = lines.next().unwrap();
+    /// assert_eq!(offset, 0);
+    /// assert_eq!(line, "oo");
+    ///
+    /// let (offset, line) = lines.next_back().unwrap();
+    /// assert_eq!(offset, 8);
+    /// assert_eq!(line, "baz");
+    ///
+    /// let (offset, line) = lines.next().unwrap();
+    /// assert_eq!(offset, 3);
+    /// assert_eq!(line, "bar");
+    ///
+    /// assert!(lines.next().is_none());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn lines_with_offsets(
+        &self,
+    ) -> LinesWithOffsets<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        LinesWithOffsets::from(self)
+    }
+
+    /// Returns the length in bytes of the longest line of the `RopeSlice`, not
+    /// including its line terminator.
+    ///
+    /// Unlike [`Rope::max_line_len()`] this always iterates over the lines,
+    /// so it runs in time linear in the length of the `RopeSlice`: a slice
+    /// can start and end in the middle of the lines of the nodes the
+    /// `max-line-len` feature keeps their longest lines in.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbarbaz\r\nqux\n");
+    /// assert_eq!(r.byte_slice(6..).max_line_len(), 4);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn max_line_len(&self) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        self.lines().map(|line| line.byte_len()).max().unwrap_or(0)
+    }
+
+    /// Returns the display width of the widest line of the `RopeSlice`, as
+    /// given by [`width()`](Self::width()).
+    ///
+    /// Like [`max_line_len()`](Self::max_line_len()) this iterates over the
+    /// lines, so it runs in time linear in the length of the `RopeSlice`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\n中文\nqux\n");
+    /// assert_eq!(r.byte_slice(7..).max_line_width(), 3);
+    /// ```
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(all(feature = "lines", feature = "width-metric")))
+    )]
+    #[cfg(all(feature = "lines", feature = "width-metric"))]
+    #[inline]
+    pub fn max_line_width(&self) -> usize
+    where
+        S: MetricSet<Lines = Maintained>,
+        S: MetricSet<Width = Maintained>,
+    {
+        self.lines().map(|line| line.width()).max().unwrap_or(0)
+    }
+
+    /// Returns a new `Rope` with the text of the `RopeSlice` converted to the
+    /// given Unicode normalization form.
+    ///
+    /// The `char`s are normalized as a single stream, so combining sequences
+    /// split across chunks are handled correctly, and the result is built
+    /// one chunk at a time without collecting the text into a `String`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{NormalizationForm, Rope};
+    /// #
+    /// let r = Rope::from("Le Cafe\u{301}");
+    /// let s = r.byte_slice(3..);
+    /// assert_eq!(s.normalize(NormalizationForm::Nfc), "Café");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "unicode-normalization")))]
+    #[cfg(feature = "unicode-normalization")]
+    #[inline]
+    pub fn normalize(
+        &self,
+        form: super::NormalizationForm,
+    ) -> RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+        super::normalization::normalize(self.chars(), form)
+    }
+
+    /// Returns an iterator over the paragraphs of this `RopeSlice`, i.e. over
+    /// its runs of non-blank lines.
+    ///
+    /// A line is blank if it only contains whitespace. The paragraphs don't
+    /// include the line terminator of their last line, and the blank lines
+    /// separating them are skipped.
+    ///
+    /// Finding the paragraph boundaries requires looking at the contents of
+    /// every line, so iterating over the paragraphs takes time linear in the
+    /// length of the `RopeSlice`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\n\n  \nbaz\n");
+    /// let s = r.byte_slice(4..);
+    ///
+    /// let mut paragraphs = s.paragraphs();
+    ///
+    /// assert_eq!("bar", paragraphs.next().unwrap());
+    /// assert_eq!("baz", paragraphs.next().unwrap());
+    /// assert_eq!(None, paragraphs.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn paragraphs(&self) -> Paragraphs<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        Paragraphs::from(self)
+    }
+
+    /// Returns the number of paragraphs of the `RopeSlice`, i.e. the number
+    /// of items yielded by [`paragraphs()`](Self::paragraphs()).
+    ///
+    /// This combines the paragraph counts the B-tree keeps for every node the
+    /// slice spans, only looking at the text of the chunks at its two ends,
+    /// so it runs in logarithmic time.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\n\nbaz\n\n  \nqux\n");
+    /// assert_eq!(r.paragraph_len(), 3);
+    /// assert_eq!(r.byte_slice(4..).paragraph_len(), 3);
+    /// assert_eq!(r.byte_slice(8..).paragraph_len(), 2);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "paragraph-count")))]
+    #[cfg(feature = "paragraph-count")]
+    #[inline]
+    pub fn paragraph_len(&self) -> usize {
+        use super::metrics::ChunkSummary;
+
+        // The summary of the slice comes from a subtraction, so its
+        // paragraphs have to be combined again from the ones of its
+        // subtrees.
+        let mut summary = ChunkSummary::<W, B, S>::new();
+        self.tree_slice.for_each_subtree(|_| false, |s| summary += s);
+        summary.paragraphs()
+    }
+
+    /// Like [`find_all()`](Self::find_all()), but splits the `RopeSlice` into up
+    /// to `threads` parts of roughly the same length which are searched in
+    /// parallel.
+    ///
+    /// Occurrences starting in one part and ending in the next are found by
+    /// the part they start in, and occurrences overlapping the ones found by
+    /// the previous parts are resolved when merging, so the result is always
+    /// the same as the one of [`find_all()`](Self::find_all()). Texts that
+    /// are too short to benefit from being split are searched on the current
+    /// thread.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("abc".repeat(10_000));
+    /// let s = r.byte_slice(1..);
+    ///
+    /// assert_eq!(s.par_find_all("ca", 4), s.find_all("ca"));
+    /// ```
+    #[inline]
+    pub fn par_find_all<P>(&self, pattern: P, threads: usize) -> Vec<usize>
+    where
+        P: SearchPattern,
+        C: Send + Sync,
+    {
+        pattern
+            .with_str(|pattern| search::par_find_all(*self, pattern, threads))
+    }
+
+    /// Returns the line at `line_index`, including its line terminator (if it
+    /// has one).
+    ///
+    /// If you don't want to include the line break consider using the
+    /// [`line()`](Self::line()) method instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the line index is out of bounds (i.e. greater than or equal
+    /// to [`line_len()`](Self::line_len())).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let s = r.byte_slice(2..10);
+    ///
+    /// assert_eq!(s.raw_line(0), "o\n");
+    /// assert_eq!(s.raw_line(1), "bar\r\n");
+    /// assert_eq!(s.raw_line(2), "b");
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[track_caller]
+    #[inline]
+    pub fn raw_line(
+        self,
+        line_index: usize,
+    ) -> RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        if line_index >= self.line_len() {
+            panic::line_index_out_of_bounds(line_index, self.line_len());
+        }
+
+        self.tree_slice
+            .slice(RawLineMetric(line_index)..RawLineMetric(line_index + 1))
+            .into()
+    }
+
+    /// Returns an iterator over the lines of this `RopeSlice`, including the
+    /// line terminators.
+    ///
+    /// The final line break is optional and doesn't cause the iterator to
+    /// return a final empty line.
+    ///
+    /// If you don't  want to include the line breaks consider using the
+    /// [`raw_lines()`](Self::raw_lines()) method instead.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\n");
+    /// let s = r.byte_slice(..r.byte_len() - 1);
+    ///
+    /// let mut raw_lines = s.raw_lines();
+    ///
+    /// assert_eq!("foo\n", raw_lines.next().unwrap());
+    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
+    /// assert_eq!("baz", raw_lines.next().unwrap());
+    /// assert_eq!(None, raw_lines.next());
+    ///
+    /// let s = r.byte_slice(..);
+    ///
+    /// let mut raw_lines = s.raw_lines();
+    ///
+    /// assert_eq!("foo\n", raw_lines.next().unwrap());
+    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
+    /// assert_eq!("baz\n", raw_lines.next().unwrap());
+    /// assert_eq!(None, raw_lines.next());
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn raw_lines(&self) -> RawLines<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        RawLines::from(self)
+    }
+
+    /// Returns a [`RopeReader`] over the contents of the `RopeSlice`, which
+    /// implements [`Read`](std::io::Read), [`BufRead`](std::io::BufRead) and
+    /// [`Seek`](std::io::Seek).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// use std::io::{BufRead, Read, Seek, SeekFrom};
+    ///
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let s = r.byte_slice(..);
+    ///
+    /// let mut reader = s.reader();
+    ///
+    /// let mut line = String::new();
+    /// reader.read_line(&mut line).unwrap();
+    /// assert_eq!(line, "foo\n");
+    ///
+    /// reader.seek(SeekFrom::End(-3)).unwrap();
+    ///
+    /// let mut rest = String::new();
+    /// reader.read_to_string(&mut rest).unwrap();
+    /// assert_eq!(rest, "baz");
+    /// ```
+    #[inline]
+    pub fn reader(self) -> RopeReader<'a, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        RopeReader::from(self)
     }
 
-    /// Returns a sub-slice of this `RopeSlice` in the specified line range,
-    /// where the start and end of the range are interpreted as offsets.
+    /// Returns an iterator over the Rabin-Karp rolling hashes of every
+    /// window of `window` consecutive bytes of the `RopeSlice`, in order.
+    ///
+    /// The `n`-th hash is the hash of the bytes in `n..n + window`, and each
+    /// one is computed from the previous one in constant time. This is a
+    /// building block for content-defined chunking and deduplication, e.g.
+    /// by cutting the text wherever the low bits of a hash are all zero.
+    ///
+    /// The iterator is empty if the `RopeSlice` is shorter than the window.
     ///
     /// # Panics
     ///
-    /// Panics if the start is greater than the end or if the end is out of
-    /// bounds (i.e. greater than [`line_len()`](Self::line_len())).
+    /// Panics if `window` is zero.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
-    /// let s = r.byte_slice(2..17);
+    /// let r = Rope::from("--abcabc--");
+    /// let s = r.byte_slice(2..8);
     ///
-    /// assert_eq!(s.line_slice(..1), "o\n");
-    /// assert_eq!(s.line_slice(1..3), "bar\r\nbaz\n");
-    /// assert_eq!(s.line_slice(3..), "foob");
+    /// let hashes = s.rolling_hashes(3).collect::<Vec<_>>();
+    ///
+    /// assert_eq!(hashes.len(), 4);
+    /// assert_eq!(hashes[0], hashes[3]);
+    /// assert_ne!(hashes[0], hashes[1]);
     /// ```
     #[track_caller]
     #[inline]
-    pub fn line_slice<R>(self, line_range: R) -> RopeSlice<'a>
-    where
-        R: RangeBounds<usize>,
-    {
-        let (start, end) =
-            range_bounds_to_start_end(line_range, 0, self.line_len());
-
-        if start > end {
-            panic::line_start_after_end(start, end);
-        }
-
-        if end > self.line_len() {
-            panic::line_offset_out_of_bounds(end, self.line_len());
-        }
-
-        self.tree_slice.slice(RawLineMetric(start)..RawLineMetric(end)).into()
+    pub fn rolling_hashes(
+        &self,
+        window: usize,
+    ) -> RollingHashes<'a, C, W, B, MAX_BYTES, MIN_FILL, S> {
+        RollingHashes::new(self.bytes(), window)
     }
 
-    /// Returns an iterator over the lines of this `RopeSlice`, not including
-    /// the line terminators.
-    ///
-    /// The final line break is optional and doesn't cause the iterator to
-    /// return a final empty line.
+    /// Returns a new `Rope` with the lowercase equivalent of the text of the
+    /// `RopeSlice`, as given by [`str::to_lowercase()`].
     ///
-    /// If you want to include the line breaks consider using the
-    /// [`raw_lines()`](Self::raw_lines()) method instead.
+    /// The text is converted one chunk at a time and never collected into a
+    /// single `String`. Chunks that only contain ASCII are converted with a
+    /// faster ASCII-only routine.
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz\n");
-    /// let s = r.line_slice(1..);
+    /// let r = Rope::from("Hello ὈΔΥΣΣΕΎΣ!");
+    /// assert_eq!(r.byte_slice(6..).to_lowercase(), "ὀδυσσεύς!");
+    /// ```
+    #[inline]
+    pub fn to_lowercase(&self) -> RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+        chunks_to_lowercase(self.chunks())
+    }
+
+    /// Returns a new `Rope` with the uppercase equivalent of the text of the
+    /// `RopeSlice`, as given by [`str::to_uppercase()`].
     ///
-    /// let mut lines = s.lines();
+    /// The text is converted one chunk at a time and never collected into a
+    /// single `String`. Chunks that only contain ASCII are converted with a
+    /// faster ASCII-only routine.
+    ///
+    /// # Examples
     ///
-    /// assert_eq!("bar", lines.next().unwrap());
-    /// assert_eq!("baz", lines.next().unwrap());
-    /// assert_eq!(None, lines.next());
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello Straße");
+    /// assert_eq!(r.byte_slice(6..).to_uppercase(), "STRASSE");
     /// ```
     #[inline]
-    pub fn lines(&self) -> Lines<'a> {
-        Lines::from(self)
+    pub fn to_uppercase(&self) -> RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+        chunks_to_uppercase(self.chunks())
     }
 
-    /// Returns an iterator over the lines of this `RopeSlice`, including the
-    /// line terminators.
-    ///
-    /// The final line break is optional and doesn't cause the iterator to
-    /// return a final empty line.
+    /// Returns the bytes of the `RopeSlice` as a new `Vec<u8>`.
     ///
-    /// If you don't  want to include the line breaks consider using the
-    /// [`raw_lines()`](Self::raw_lines()) method instead.
+    /// The `Vec` is allocated with the byte length of the `RopeSlice` up
+    /// front and the text is then copied one chunk at a time, which is a lot
+    /// faster than collecting the [`bytes()`](Self::bytes()).
     ///
     /// # Examples
     ///
     /// ```
     /// # use crop::Rope;
     /// #
-    /// let r = Rope::from("foo\nbar\r\nbaz\n");
-    /// let s = r.byte_slice(..r.byte_len() - 1);
+    /// let r = Rope::from("Hello 🌎!");
+    /// assert_eq!(r.byte_slice(6..).to_vec(), "🌎!".as_bytes());
+    /// ```
+    #[inline]
+    pub fn to_vec(&self) -> Vec<u8> {
+        chunks_to_vec(self.chunks(), self.byte_len())
+    }
+
+    /// Returns a callback which can be passed to tree-sitter's
+    /// [`Parser::parse_with_options()`][parse_with_options] to parse the
+    /// `RopeSlice` without copying it.
     ///
-    /// let mut raw_lines = s.raw_lines();
+    /// Given a byte offset, the callback returns the bytes from that offset
+    /// to the end of the chunk containing it, or an empty slice if the offset
+    /// is past the end of the `RopeSlice`.
     ///
-    /// assert_eq!("foo\n", raw_lines.next().unwrap());
-    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
-    /// assert_eq!("baz", raw_lines.next().unwrap());
-    /// assert_eq!(None, raw_lines.next());
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// use tree_sitter::Point;
     ///
+    /// let r = Rope::from("foo\nbar");
     /// let s = r.byte_slice(..);
     ///
-    /// let mut raw_lines = s.raw_lines();
+    /// let mut callback = s.tree_sitter_callback();
     ///
-    /// assert_eq!("foo\n", raw_lines.next().unwrap());
-    /// assert_eq!("bar\r\n", raw_lines.next().unwrap());
-    /// assert_eq!("baz\n", raw_lines.next().unwrap());
-    /// assert_eq!(None, raw_lines.next());
+    /// assert_eq!(callback(4, Point::new(1, 0)), b"bar");
+    /// assert_eq!(callback(7, Point::new(1, 3)), b"");
     /// ```
+    ///
+    /// [parse_with_options]: ::tree_sitter::Parser::parse_with_options
+    #[cfg_attr(docsrs, doc(cfg(feature = "tree-sitter")))]
+    #[cfg(feature = "tree-sitter")]
     #[inline]
-    pub fn raw_lines(&self) -> RawLines<'a> {
-        RawLines::from(self)
+    pub fn tree_sitter_callback(
+        self,
+    ) -> impl FnMut(usize, ::tree_sitter::Point) -> &'a [u8] + 'a {
+        move |byte_offset, _| super::tree_sitter::bytes_from(self, byte_offset)
     }
 
     /// Removes the last char from the range spanned by this slice.
@@ -579,18 +2486,26 @@ impl<'a> RopeSlice<'a> {
     ///
     /// Panics if the slice is empty or if the relative offset is not on a char
     /// boundary.
+    #[cfg(feature = "lines")]
     #[inline]
     pub(super) fn truncate_last_char(&mut self) {
         debug_assert!(!self.is_empty());
 
         let slice = &mut self.tree_slice;
 
-        // The last slice only contains one byte so we have to re-slice.
-        if slice.end_summary.bytes() == 1 {
-            *self = self.byte_slice(..self.byte_len() - 1);
+        let last_char_len = slice
+            .end_slice
+            .last_chunk()
+            .chars()
+            .next_back()
+            .map_or(0, char::len_utf8);
+
+        // The last slice only contains the last char so we have to re-slice.
+        if slice.end_summary.bytes() == last_char_len {
+            *self = self.byte_slice(..self.byte_len() - last_char_len);
         }
-        // The last slice contains more than 2 bytes so we can just mutate
-        // in place.
+        // The last slice contains more than just the last char so we can just
+        // mutate in place.
         else {
             let last = &mut slice.end_slice;
 
@@ -609,27 +2524,154 @@ impl<'a> RopeSlice<'a> {
         }
     }
 
-    /// Removes the trailing line break (either LF or CRLF) from the range
-    /// spanned by this slice.
+    /// Removes the trailing line break (either LF, CRLF or, with
+    /// [`UnicodeBreaks`](crate::UnicodeBreaks), any of the other Unicode line
+    /// breaks) from the range spanned by this slice.
     ///
     /// # Panics
     ///
     /// Panics if this slice doesn't have a trailing line break.
+    #[cfg(feature = "lines")]
     #[inline]
     pub(super) fn truncate_trailing_line_break(&mut self) {
-        debug_assert!(self
-            .tree_slice
-            .end_slice()
-            .last_chunk()
-            .ends_with('\n'));
+        debug_assert!(self.has_trailing_newline());
+
+        let is_lf = self.tree_slice.end_slice().last_chunk().ends_with('\n');
 
         self.truncate_last_char();
 
-        if self.tree_slice.end_slice().last_chunk().ends_with('\r') {
+        if is_lf && self.tree_slice.end_slice().last_chunk().ends_with('\r') {
             self.truncate_last_char();
         }
     }
 
+    /// Non-panicking version of [`byte()`](Self::byte()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Error, Rope};
+    /// #
+    /// let r = Rope::from("foobar");
+    /// let s = r.byte_slice(3..);
+    ///
+    /// assert_eq!(s.try_byte(0), Ok(b'b'));
+    /// assert_eq!(
+    ///     s.try_byte(3),
+    ///     Err(Error::ByteIndexOutOfBounds { index: 3, len: 3 })
+    /// );
+    /// ```
+    #[inline]
+    pub fn try_byte(&self, byte_index: usize) -> Result<u8, Error> {
+        check_byte_index(byte_index, self.byte_len())?;
+        Ok(self.byte(byte_index))
+    }
+
+    /// Non-panicking version of [`byte_slice()`](Self::byte_slice()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Error, Rope};
+    /// #
+    /// let r = Rope::from("🗻∈🌏");
+    /// let s = r.byte_slice(4..);
+    ///
+    /// assert_eq!(s.try_byte_slice(..3).unwrap(), "∈");
+    ///
+    /// assert_eq!(
+    ///     s.try_byte_slice(..1),
+    ///     Err(Error::ByteOffsetNotCharBoundary { offset: 1 })
+    /// );
+    /// ```
+    #[inline]
+    pub fn try_byte_slice<R>(
+        self,
+        byte_range: R,
+    ) -> Result<RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>, Error>
+    where
+        R: RangeBounds<usize>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.byte_len());
+
+        check_byte_range(start, end, self.byte_len(), |offset| {
+            self.is_char_boundary(offset)
+        })?;
+
+        Ok(self.tree_slice.slice(ByteMetric(start)..ByteMetric(end)).into())
+    }
+
+    /// Non-panicking version of [`line()`](Self::line()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Error, Rope};
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz");
+    /// let s = r.line_slice(1..);
+    ///
+    /// assert_eq!(s.try_line(0).unwrap(), "bar");
+    ///
+    /// assert_eq!(
+    ///     s.try_line(2),
+    ///     Err(Error::LineIndexOutOfBounds { index: 2, len: 2 })
+    /// );
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn try_line(
+        self,
+        line_index: usize,
+    ) -> Result<RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>, Error>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        check_line_index(line_index, self.line_len())?;
+        Ok(self.line(line_index))
+    }
+
+    /// Non-panicking version of [`line_slice()`](Self::line_slice()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Error, Rope};
+    /// #
+    /// let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
+    /// let s = r.line_slice(1..);
+    ///
+    /// assert_eq!(s.try_line_slice(..2).unwrap(), "bar\r\nbaz\n");
+    ///
+    /// assert_eq!(
+    ///     s.try_line_slice(..4),
+    ///     Err(Error::LineOffsetOutOfBounds { offset: 4, len: 3 })
+    /// );
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn try_line_slice<R>(
+        self,
+        line_range: R,
+    ) -> Result<RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>, Error>
+    where
+        R: RangeBounds<usize>,
+        S: MetricSet<Lines = Maintained>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(line_range, 0, self.line_len());
+
+        check_line_range(start, end, self.line_len())?;
+
+        Ok(self
+            .tree_slice
+            .slice(RawLineMetric(start)..RawLineMetric(end))
+            .into())
+    }
+
     /// Returns the number of UTF-16 code units this `RopeSlice` would span if
     /// it stores its contents as UTF-16 instead of UTF-8.
     ///
@@ -646,7 +2688,10 @@ impl<'a> RopeSlice<'a> {
     #[cfg_attr(docsrs, doc(cfg(feature = "utf16-metric")))]
     #[cfg(feature = "utf16-metric")]
     #[inline]
-    pub fn utf16_len(&self) -> usize {
+    pub fn utf16_len(&self) -> usize
+    where
+        S: MetricSet<Utf16CodeUnits = Maintained>,
+    {
         self.tree_slice.summary().utf16_code_units()
     }
 
@@ -673,7 +2718,10 @@ impl<'a> RopeSlice<'a> {
     #[cfg(feature = "utf16-metric")]
     #[track_caller]
     #[inline]
-    pub fn utf16_code_unit_of_byte(&self, byte_offset: usize) -> usize {
+    pub fn utf16_code_unit_of_byte(&self, byte_offset: usize) -> usize
+    where
+        S: MetricSet<Utf16CodeUnits = Maintained>,
+    {
         if byte_offset > self.byte_len() {
             panic::byte_offset_out_of_bounds(byte_offset, self.byte_len());
         }
@@ -710,9 +2758,13 @@ impl<'a> RopeSlice<'a> {
     #[cfg(feature = "utf16-metric")]
     #[track_caller]
     #[inline]
-    pub fn utf16_slice<R>(self, utf16_range: R) -> RopeSlice<'a>
+    pub fn utf16_slice<R>(
+        self,
+        utf16_range: R,
+    ) -> RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
     where
         R: RangeBounds<usize>,
+        S: MetricSet<Utf16CodeUnits = Maintained>,
     {
         use super::metrics::Utf16Metric;
 
@@ -729,110 +2781,569 @@ impl<'a> RopeSlice<'a> {
 
         self.tree_slice.slice(Utf16Metric(start)..Utf16Metric(end)).into()
     }
+
+    /// Returns the display width of the `RopeSlice`, i.e. the number of columns
+    /// its text takes up in a terminal, as given by the
+    /// [unicode-width](https://docs.rs/unicode-width) crate.
+    ///
+    /// The width is the sum of the widths of the `char`s, where control
+    /// characters (including line breaks) count as zero. It's stored in the
+    /// B-tree, so the width of any line can be computed in logarithmic time
+    /// by calling this on [`line()`](Self::line()).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("a中b\n🦀!");
+    /// assert_eq!(r.byte_slice(1..).width(), 6);
+    /// assert_eq!(r.line(0).width(), 4);
+    /// assert_eq!(r.line(1).width(), 3);
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "width-metric")))]
+    #[cfg(feature = "width-metric")]
+    #[inline]
+    pub fn width(&self) -> usize
+    where
+        S: MetricSet<Width = Maintained>,
+    {
+        self.tree_slice.summary().width()
+    }
+
+    /// Returns the number of words in the `RopeSlice`, counted like `wc -w`
+    /// does, i.e. as the number of maximal runs of non-whitespace `char`s.
+    ///
+    /// Unlike [`Rope::word_len()`] this always scans the whole text, so it
+    /// runs in linear time: a slice can start and end in the middle of the
+    /// nodes the `word-count` feature keeps the number of words of.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::Rope;
+    /// #
+    /// let r = Rope::from("Hello,  Earth!\n🦀 is\ta crab.\n");
+    /// assert_eq!(r.byte_slice(3..).word_len(), 6);
+    /// assert_eq!(r.byte_slice(15..).word_len(), 4);
+    /// ```
+    #[inline]
+    pub fn word_len(&self) -> usize {
+        count_words(self.chunks())
+    }
+
+    /// Writes the text of the `RopeSlice` to `writer`, converting every `\n` and
+    /// `\r\n` line break to the given [`LineEnding`].
+    ///
+    /// The chunks are streamed to the writer as they are converted, without
+    /// building a converted copy of the text first. Since this issues many
+    /// small writes it's recommended to wrap unbuffered writers in a
+    /// [`BufWriter`](std::io::BufWriter).
+    ///
+    /// # Errors
+    ///
+    /// Returns any error returned by the writer.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{LineEnding, Rope};
+    /// #
+    /// let r = Rope::from("!foo\nbar\r\nbaz");
+    /// let s = r.byte_slice(1..);
+    ///
+    /// let mut buf = Vec::new();
+    /// s.write_to_with_line_ending(&mut buf, LineEnding::CrLf).unwrap();
+    /// assert_eq!(buf, b"foo\r\nbar\r\nbaz");
+    /// ```
+    #[inline]
+    pub fn write_to_with_line_ending<T: std::io::Write>(
+        &self,
+        writer: T,
+        line_ending: LineEnding,
+    ) -> std::io::Result<()> {
+        write_with_line_ending(self.chunks(), writer, line_ending)
+    }
 }
 
-impl<'a> From<TreeSlice<'a, { Rope::arity() }, RopeChunk>> for RopeSlice<'a> {
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    >
+    From<
+        TreeSlice<
+            'a,
+            { Rope::arity() },
+            RopeChunk<W, B, MAX_BYTES, MIN_FILL, S>,
+            C,
+        >,
+    > for RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn from(tree_slice: TreeSlice<'a, { Rope::arity() }, RopeChunk>) -> Self {
-        Self {
-            has_trailing_newline: tree_slice
-                .end_slice()
-                .has_trailing_newline(),
+    fn from(
+        tree_slice: TreeSlice<
+            'a,
+            { Rope::arity() },
+            RopeChunk<W, B, MAX_BYTES, MIN_FILL, S>,
+            C,
+        >,
+    ) -> Self {
+        Self { tree_slice }
+    }
+}
 
-            tree_slice,
-        }
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>> for String
+{
+    #[inline]
+    fn from(
+        rope_slice: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> String {
+        let mut s = String::new();
+        rope_slice.collect_into(&mut s);
+        s
     }
 }
 
-impl core::fmt::Debug for RopeSlice<'_> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>> for Vec<u8>
+{
+    #[inline]
+    fn from(
+        rope_slice: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> Vec<u8> {
+        rope_slice.to_vec()
+    }
+}
+
+/// Formats the text as a quoted string, or in alternate mode (i.e. `{:#?}`)
+/// as a list of numbered lines if the `lines` feature is enabled. Only the
+/// first and last 20 lines of texts with more than 40 lines are printed in
+/// alternate mode.
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::fmt::Debug for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        #[cfg(feature = "lines")]
+        if f.alternate() {
+            if let Some(result) = S::Lines::debug_lines("RopeSlice", *self, f)
+            {
+                return result;
+            }
+        }
+
         f.write_str("RopeSlice(\"")?;
         debug_chunks(self.chunks(), f)?;
         f.write_str("\")")
     }
 }
 
-impl core::fmt::Display for RopeSlice<'_> {
+/// Writes the text of the `RopeSlice`, padded and truncated according to the
+/// width, precision and alignment of the formatter like `str`s are.
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::fmt::Display for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
-        for chunk in self.chunks() {
-            f.write_str(chunk)?;
-        }
-        Ok(())
+        #[cfg(feature = "char-metric")]
+        let char_len = self.tree_slice.summary().maintained_chars();
+
+        #[cfg(not(feature = "char-metric"))]
+        let char_len = None;
+
+        display_chunks(self.chunks(), char_len, f)
     }
 }
 
-impl core::cmp::PartialEq<RopeSlice<'_>> for RopeSlice<'_> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn eq(&self, rhs: &RopeSlice<'_>) -> bool {
+    fn eq(
+        &self,
+        rhs: &RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> bool {
         (self.byte_len() == rhs.byte_len())
-            && (self.line_len() == rhs.line_len())
             && chunks_eq_chunks(self.chunks(), rhs.chunks())
     }
 }
 
-impl core::cmp::PartialEq<Rope> for RopeSlice<'_> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
-    fn eq(&self, rhs: &Rope) -> bool {
+    fn eq(&self, rhs: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> bool {
         rhs == self
     }
 }
 
-impl core::cmp::PartialEq<str> for RopeSlice<'_> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<str>
+    for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn eq(&self, rhs: &str) -> bool {
         (self.byte_len() == rhs.len()) && chunks_eq_str(self.chunks(), rhs)
     }
 }
 
-impl core::cmp::PartialEq<RopeSlice<'_>> for str {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for str
+{
     #[inline]
-    fn eq(&self, rhs: &RopeSlice<'_>) -> bool {
+    fn eq(
+        &self,
+        rhs: &RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> bool {
         rhs == self
     }
 }
 
-impl core::cmp::PartialEq<&str> for RopeSlice<'_> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<&str>
+    for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn eq(&self, rhs: &&str) -> bool {
         self == *rhs
     }
 }
 
-impl core::cmp::PartialEq<RopeSlice<'_>> for &str {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for &str
+{
     #[inline]
-    fn eq(&self, rhs: &RopeSlice<'_>) -> bool {
+    fn eq(
+        &self,
+        rhs: &RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> bool {
         rhs == self
     }
 }
 
-impl core::cmp::PartialEq<String> for RopeSlice<'_> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<String>
+    for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn eq(&self, rhs: &String) -> bool {
         self == &**rhs
     }
 }
 
-impl core::cmp::PartialEq<RopeSlice<'_>> for String {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for String
+{
     #[inline]
-    fn eq(&self, rhs: &RopeSlice<'_>) -> bool {
+    fn eq(
+        &self,
+        rhs: &RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> bool {
         rhs == self
     }
 }
 
-impl core::cmp::PartialEq<alloc::borrow::Cow<'_, str>> for RopeSlice<'_> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<alloc::borrow::Cow<'_, str>>
+    for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
     #[inline]
     fn eq(&self, rhs: &alloc::borrow::Cow<'_, str>) -> bool {
         self == &**rhs
     }
 }
 
-impl core::cmp::PartialEq<RopeSlice<'_>> for alloc::borrow::Cow<'_, str> {
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for alloc::borrow::Cow<'_, str>
+{
+    #[inline]
+    fn eq(
+        &self,
+        rhs: &RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> bool {
+        rhs == self
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<[u8]>
+    for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn eq(&self, rhs: &[u8]) -> bool {
+        (self.byte_len() == rhs.len()) && chunks_eq_bytes(self.chunks(), rhs)
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for [u8]
+{
+    #[inline]
+    fn eq(
+        &self,
+        rhs: &RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> bool {
+        rhs == self
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<&[u8]>
+    for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn eq(&self, rhs: &&[u8]) -> bool {
+        self == *rhs
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for &[u8]
+{
+    #[inline]
+    fn eq(
+        &self,
+        rhs: &RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> bool {
+        rhs == self
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<Vec<u8>>
+    for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn eq(&self, rhs: &Vec<u8>) -> bool {
+        self == &**rhs
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialEq<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for Vec<u8>
+{
     #[inline]
-    fn eq(&self, rhs: &RopeSlice<'_>) -> bool {
+    fn eq(
+        &self,
+        rhs: &RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> bool {
         rhs == self
     }
 }
 
-impl core::cmp::Eq for RopeSlice<'_> {}
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::Eq for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialOrd<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn partial_cmp(
+        &self,
+        rhs: &RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> Option<core::cmp::Ordering> {
+        Some(chunks_cmp_chunks(self.chunks(), rhs.chunks()))
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::PartialOrd<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn partial_cmp(
+        &self,
+        rhs: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> Option<core::cmp::Ordering> {
+        Some(chunks_cmp_chunks(self.chunks(), rhs.chunks()))
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::cmp::Ord for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn cmp(
+        &self,
+        rhs: &RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    ) -> core::cmp::Ordering {
+        chunks_cmp_chunks(self.chunks(), rhs.chunks())
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::hash::Hash for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        hash_chunks(self.chunks(), state)
+    }
+}
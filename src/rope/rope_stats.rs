@@ -0,0 +1,95 @@
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use super::RopeWith;
+use crate::tree::RefCounter;
+
+/// Statistics about the memory usage and the shape of the tree of a
+/// [`Rope`](super::Rope).
+///
+/// This is returned by the [`stats()`](super::Rope::stats()) method on `Rope`, and
+/// can be used to monitor how fragmented a `Rope` is to decide when to call
+/// [`compact()`](super::Rope::compact()) on it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RopeStats {
+    byte_len: usize,
+    depth: usize,
+    leaf_count: usize,
+    leaf_capacity: usize,
+    mem_usage: usize,
+}
+
+impl RopeStats {
+    /// Returns the depth of the `Rope`'s tree, i.e. the number of internal
+    /// nodes on the path from its root to any of its leaves. A `Rope` whose
+    /// text is stored in a single chunk has a depth of zero.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the number of leaves of the `Rope`'s tree, i.e. the number of
+    /// chunks its text is stored in.
+    #[inline]
+    pub fn leaf_count(&self) -> usize {
+        self.leaf_count
+    }
+
+    /// Returns the average fraction of the capacity of a leaf that's
+    /// occupied by text, between 0 and 1.
+    #[inline]
+    pub fn leaf_occupancy(&self) -> f64 {
+        self.byte_len as f64 / (self.leaf_count * self.leaf_capacity) as f64
+    }
+
+    /// Returns the number of bytes allocated on the heap by the `Rope`,
+    /// including both its text and its tree.
+    ///
+    /// Parts of the tree that are shared with other `Rope`s (e.g. with
+    /// clones of this `Rope`) are counted as if they were owned by this
+    /// `Rope`.
+    #[inline]
+    pub fn mem_usage(&self) -> usize {
+        self.mem_usage
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> for RopeStats
+{
+    #[inline]
+    fn from(rope: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        let leaf_count = rope.tree.leaf_count();
+
+        Self {
+            byte_len: rope.byte_len(),
+            depth: rope.tree.depth(),
+            leaf_count,
+            leaf_capacity: MAX_BYTES,
+            mem_usage: rope.tree.heap_size() + leaves_heap_size(rope),
+        }
+    }
+}
+
+/// Returns the number of bytes allocated on the heap by the leaves of the
+/// `Rope`'s tree.
+#[inline]
+fn leaves_heap_size<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    rope: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+) -> usize {
+    let mut heap_size = 0;
+    rope.tree.for_each_leaf(|leaf| heap_size += leaf.bytes.heap_size());
+    heap_size
+}
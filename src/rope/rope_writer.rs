@@ -0,0 +1,171 @@
+use std::io::{self, Write};
+
+use super::line_breaks::{LfBreaks, LineBreaks};
+use super::metrics::{AllMetrics, CharWeight, MetricSet, ZeroWeight};
+use super::rope::{CHUNK_MAX_BYTES, CHUNK_MIN_FILL};
+use super::RopeWith;
+use crate::tree::{AtomicCounter, RefCounter};
+
+/// An adapter implementing [`Write`] which appends everything written to it
+/// at the end of a [`Rope`](super::Rope).
+///
+/// This is created by the [`writer()`](super::Rope::writer()) method on `Rope`.
+///
+/// The written bytes are buffered until they fill a whole chunk, so that
+/// writing many small pieces of text doesn't result in an edit of the `Rope`
+/// for each of them. The buffer is flushed when calling [`flush()`] or when
+/// the `RopeWriter` is dropped.
+///
+/// The bytes must be valid UTF-8, but a code point can be split across
+/// multiple writes. Writing bytes that are not valid UTF-8 returns an error of
+/// kind [`InvalidData`](io::ErrorKind::InvalidData) and doesn't modify the
+/// `Rope`.
+///
+/// [`flush()`]: Write::flush()
+pub struct RopeWriter<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet = AllMetrics,
+> {
+    /// The `Rope` the bytes are appended to.
+    rope: &'a mut RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+
+    /// The bytes that were written but not yet appended to the `Rope`.
+    buffer: Vec<u8>,
+
+    /// The length of the longest prefix of `buffer` that is valid UTF-8. The
+    /// bytes after it are the start of a code point that's yet to be
+    /// completed by the next write.
+    valid_up_to: usize,
+}
+
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&'a mut RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+    for RopeWriter<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from(rope: &'a mut RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        Self { rope, buffer: Vec::new(), valid_up_to: 0 }
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::fmt::Debug for RopeWriter<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("RopeWriter")
+            .field("rope", &self.rope)
+            .field("buffer", &self.buffer)
+            .finish()
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Drop for RopeWriter<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn drop(&mut self) {
+        self.append_valid();
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > RopeWriter<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    /// Appends the valid UTF-8 prefix of the buffer to the `Rope`.
+    #[inline]
+    fn append_valid(&mut self) {
+        if self.valid_up_to == 0 {
+            return;
+        }
+
+        // SAFETY: the first `valid_up_to` bytes of the buffer have already
+        // been checked to be valid UTF-8.
+        let text = unsafe {
+            core::str::from_utf8_unchecked(&self.buffer[..self.valid_up_to])
+        };
+
+        self.rope.insert(self.rope.byte_len(), text);
+
+        self.buffer.drain(..self.valid_up_to);
+        self.valid_up_to = 0;
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Write for RopeWriter<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn write(&mut self, bytes: &[u8]) -> io::Result<usize> {
+        let old_len = self.buffer.len();
+
+        self.buffer.extend_from_slice(bytes);
+
+        // Only the bytes after the valid prefix need to be checked.
+        match core::str::from_utf8(&self.buffer[self.valid_up_to..]) {
+            Ok(_) => self.valid_up_to = self.buffer.len(),
+
+            // The buffer ends in the middle of a code point.
+            Err(err) if err.error_len().is_none() => {
+                self.valid_up_to += err.valid_up_to();
+            },
+
+            Err(_) => {
+                self.buffer.truncate(old_len);
+
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "stream did not contain valid UTF-8",
+                ));
+            },
+        }
+
+        if self.valid_up_to >= MAX_BYTES {
+            self.append_valid();
+        }
+
+        Ok(bytes.len())
+    }
+
+    #[inline]
+    fn flush(&mut self) -> io::Result<()> {
+        self.append_valid();
+        Ok(())
+    }
+}
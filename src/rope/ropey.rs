@@ -0,0 +1,88 @@
+//! Conversions between crop's and [ropey](https://docs.rs/ropey)'s ropes,
+//! enabled by the `ropey` feature.
+//!
+//! The text is moved chunk by chunk using the builders of the two crates, so
+//! no intermediate `String` holding the whole text is ever allocated.
+//!
+//! Note that the two crates don't agree on what a line break is: ropey
+//! also breaks lines on lone carriage returns and on a few Unicode line
+//! separators, so the same text can have a different number of lines.
+
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use super::{RopeBuilder, RopeSlice, RopeWith};
+use crate::tree::RefCounter;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "ropey")))]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&::ropey::Rope> for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from(rope: &::ropey::Rope) -> Self {
+        Self::from(rope.slice(..))
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "ropey")))]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<::ropey::RopeSlice<'_>>
+    for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn from(slice: ::ropey::RopeSlice<'_>) -> Self {
+        let mut builder =
+            RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+        for chunk in slice.chunks() {
+            builder.append(chunk);
+        }
+        builder.build()
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "ropey")))]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<&RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> for ::ropey::Rope
+{
+    #[inline]
+    fn from(rope: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        Self::from(rope.byte_slice(..))
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "ropey")))]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > From<RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>> for ::ropey::Rope
+{
+    #[inline]
+    fn from(slice: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        let mut builder = ::ropey::RopeBuilder::new();
+        for chunk in slice.chunks() {
+            builder.append(chunk);
+        }
+        builder.finish()
+    }
+}
@@ -0,0 +1,397 @@
+//! Searching for the occurrences of a pattern in the text of `Rope`s and
+//! `RopeSlice`s.
+
+use core::str::MatchIndices;
+
+use super::iterators::Chunks;
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use crate::tree::RefCounter;
+
+/// A pattern that can be searched for in a `Rope` or a `RopeSlice`, e.g. by
+/// [`Rope::count_matches()`](super::Rope::count_matches()) and
+/// [`Rope::find_all()`](super::Rope::find_all()).
+///
+/// This trait is implemented for `char`, `&str` and `&String`, and can't be
+/// implemented outside of this crate.
+pub trait SearchPattern: sealed::Sealed {
+    #[doc(hidden)]
+    fn with_str<F, R>(self, f: F) -> R
+    where
+        F: FnOnce(&str) -> R;
+}
+
+mod sealed {
+    pub trait Sealed {}
+
+    impl Sealed for char {}
+    impl Sealed for &str {}
+    impl Sealed for &String {}
+}
+
+impl SearchPattern for char {
+    #[inline]
+    fn with_str<F, R>(self, f: F) -> R
+    where
+        F: FnOnce(&str) -> R,
+    {
+        f(self.encode_utf8(&mut [0; 4]))
+    }
+}
+
+impl SearchPattern for &str {
+    #[inline]
+    fn with_str<F, R>(self, f: F) -> R
+    where
+        F: FnOnce(&str) -> R,
+    {
+        f(self)
+    }
+}
+
+impl SearchPattern for &String {
+    #[inline]
+    fn with_str<F, R>(self, f: F) -> R
+    where
+        F: FnOnce(&str) -> R,
+    {
+        f(self)
+    }
+}
+
+/// An iterator over the byte offsets of the non-overlapping occurrences of a
+/// non-empty pattern in the concatenation of some chunks, from left to right.
+///
+/// The chunks are searched with `str::match_indices()`. An occurrence
+/// spanning two or more chunks starts in the last `pattern.len() - 1` bytes
+/// of the text before the current chunk, which are kept around in `carry`
+/// to look for it once the next chunk is reached. Since the pattern is
+/// valid UTF-8 those bytes can be compared without caring about char
+/// boundaries.
+pub(super) struct MatchOffsets<
+    'a,
+    'p,
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+> {
+    chunks: Chunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+
+    pattern: &'p str,
+
+    /// The chunk currently being searched.
+    chunk: &'a str,
+
+    /// The byte offset of the start of `chunk` in the text.
+    chunk_offset: usize,
+
+    /// The occurrences in `chunk` after `search_start`.
+    matches: MatchIndices<'a, &'p str>,
+
+    /// The byte offset in `chunk` at which `matches` starts.
+    search_start: usize,
+
+    /// The byte offset in `chunk` of the end of the last occurrence yielded
+    /// in it, if any.
+    last_end: Option<usize>,
+
+    /// The last `pattern.len() - 1` bytes (or fewer) of the text before
+    /// `chunk` which come after the end of the last occurrence.
+    carry: Vec<u8>,
+}
+
+impl<
+        'a,
+        'p,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > MatchOffsets<'a, 'p, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    pub(super) fn new(
+        chunks: Chunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+        pattern: &'p str,
+    ) -> Self {
+        debug_assert!(!pattern.is_empty());
+
+        Self {
+            chunks,
+            pattern,
+            chunk: "",
+            chunk_offset: 0,
+            matches: "".match_indices(pattern),
+            search_start: 0,
+            last_end: None,
+            carry: Vec::with_capacity(2 * (pattern.len() - 1)),
+        }
+    }
+
+    /// Looks for an occurrence starting in `carry` and ending in `chunk`,
+    /// returning the byte offset of its start in `carry`.
+    #[inline]
+    fn find_in_seam(&mut self) -> Option<usize> {
+        let carry_len = self.carry.len();
+
+        let seam_len = self.chunk.len().min(self.pattern.len() - 1);
+
+        self.carry.extend_from_slice(&self.chunk.as_bytes()[..seam_len]);
+
+        let start = (0..carry_len).find(|&offset| {
+            self.carry[offset..].starts_with(self.pattern.as_bytes())
+        });
+
+        self.carry.truncate(carry_len);
+
+        start
+    }
+
+    /// Updates the `carry` with the bytes of `chunk` after the end of the
+    /// last occurrence.
+    #[inline]
+    fn update_carry(&mut self) {
+        let tail_start = match self.last_end {
+            Some(end) => {
+                self.carry.clear();
+                end
+            },
+            None => 0,
+        };
+
+        self.carry.extend_from_slice(&self.chunk.as_bytes()[tail_start..]);
+
+        let max_carry = self.pattern.len() - 1;
+
+        if self.carry.len() > max_carry {
+            self.carry.drain(..self.carry.len() - max_carry);
+        }
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Iterator for MatchOffsets<'_, '_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type Item = usize;
+
+    #[inline]
+    fn next(&mut self) -> Option<usize> {
+        loop {
+            if let Some((offset, _)) = self.matches.next() {
+                let start = self.search_start + offset;
+                self.last_end = Some(start + self.pattern.len());
+                return Some(self.chunk_offset + start);
+            }
+
+            self.update_carry();
+
+            let chunk = self.chunks.next()?;
+
+            self.chunk_offset += self.chunk.len();
+            self.chunk = chunk;
+            self.search_start = 0;
+            self.last_end = None;
+
+            let seam_match =
+                if self.carry.is_empty() { None } else { self.find_in_seam() };
+
+            if let Some(offset) = seam_match {
+                let carry_len = self.carry.len();
+                self.search_start = offset + self.pattern.len() - carry_len;
+                self.last_end = Some(self.search_start);
+            }
+
+            self.matches =
+                self.chunk[self.search_start..].match_indices(self.pattern);
+
+            if let Some(offset) = seam_match {
+                return Some(self.chunk_offset - self.carry.len() + offset);
+            }
+        }
+    }
+}
+
+/// Counts the non-overlapping occurrences of `pattern` in the concatenation
+/// of the chunks, like `str::matches(pattern).count()` would.
+#[inline]
+pub(super) fn count_matches<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    pattern: &str,
+) -> usize {
+    if pattern.is_empty() {
+        return chunks.map(|chunk| chunk.chars().count()).sum::<usize>() + 1;
+    }
+
+    MatchOffsets::new(chunks, pattern).count()
+}
+
+/// Returns the byte offsets of the non-overlapping occurrences of `pattern`
+/// in the concatenation of the chunks, like `str::match_indices(pattern)`
+/// would.
+#[inline]
+pub(super) fn find_all<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    pattern: &str,
+) -> Vec<usize> {
+    if pattern.is_empty() {
+        return char_boundaries(chunks);
+    }
+
+    MatchOffsets::new(chunks, pattern).collect()
+}
+
+/// The byte offsets of all the char boundaries in the concatenation of the
+/// chunks, which is where an empty pattern matches.
+#[inline]
+fn char_boundaries<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+) -> Vec<usize> {
+    let mut offsets = Vec::new();
+    let mut offset = 0;
+
+    for chunk in chunks {
+        offsets.extend(chunk.char_indices().map(|(idx, _)| offset + idx));
+        offset += chunk.len();
+    }
+
+    offsets.push(offset);
+
+    offsets
+}
+
+/// Like [`find_all()`], but splits the slice into up to `threads` parts
+/// which are searched in parallel.
+///
+/// Every part is searched as if an occurrence could start at its first byte,
+/// including the occurrences starting in it and ending in the next part.
+/// When merging, an occurrence overlapping the last one of the previous
+/// parts means that the previous parts' occurrences "shifted" where the
+/// search should resume, so we search again from the end of the last
+/// occurrence until we land on one that was also found by the part, after
+/// which the two searches would find the same occurrences.
+#[inline]
+pub(super) fn par_find_all<
+    C: RefCounter + Send + Sync,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    slice: super::RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    pattern: &str,
+    threads: usize,
+) -> Vec<usize> {
+    // Every thread searches at least a chunk's worth of bytes.
+    let parts = threads.min(slice.byte_len() / MAX_BYTES);
+
+    if pattern.is_empty() || parts <= 1 {
+        return find_all(slice.chunks(), pattern);
+    }
+
+    let bounds = (0..=parts)
+        .map(|idx| {
+            let mut offset = slice.byte_len() * idx / parts;
+            while !slice.is_char_boundary(offset) {
+                offset += 1;
+            }
+            offset
+        })
+        .collect::<Vec<_>>();
+
+    let search_part = |start: usize, end: usize| {
+        // Extend the part to include the occurrences starting in it and
+        // ending in the next one.
+        let mut extended_end = (end + pattern.len() - 1).min(slice.byte_len());
+        while !slice.is_char_boundary(extended_end) {
+            extended_end += 1;
+        }
+
+        MatchOffsets::new(
+            slice.byte_slice(start..extended_end).chunks(),
+            pattern,
+        )
+        .map(|offset| start + offset)
+        .take_while(|&offset| offset < end)
+        .collect::<Vec<_>>()
+    };
+
+    let found = std::thread::scope(|scope| {
+        let handles = bounds
+            .windows(2)
+            .map(|part| scope.spawn(move || search_part(part[0], part[1])))
+            .collect::<Vec<_>>();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    let mut offsets = Vec::<usize>::new();
+
+    for (part, part_offsets) in bounds.windows(2).zip(found) {
+        let resume_at = offsets.last().map_or(0, |&last| last + pattern.len());
+
+        if part_offsets.first().map_or(true, |&first| first >= resume_at) {
+            offsets.extend_from_slice(&part_offsets);
+            continue;
+        }
+
+        if resume_at >= part[1] {
+            // Every occurrence starting in this part overlaps the last one.
+            continue;
+        }
+
+        // Search again from `resume_at` until we find an occurrence that
+        // the part also found.
+        let rest = search_part(resume_at, part[1]);
+
+        let synced = rest
+            .iter()
+            .position(|offset| part_offsets.binary_search(offset).is_ok());
+
+        match synced {
+            Some(idx) => {
+                offsets.extend_from_slice(&rest[..idx]);
+                let from = part_offsets.binary_search(&rest[idx]).unwrap();
+                offsets.extend_from_slice(&part_offsets[from..]);
+            },
+            None => offsets.extend_from_slice(&rest),
+        }
+    }
+
+    offsets
+}
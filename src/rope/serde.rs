@@ -0,0 +1,129 @@
+//! `Serialize` and `Deserialize` implementations for `Rope`s and
+//! `RopeSlice`s, enabled by the `serde` feature.
+//!
+//! Both are serialized as a single string. To avoid allocating a `String`
+//! with the whole contents of the rope the chunks are streamed to the
+//! serializer via [`collect_str`](::serde::Serializer::collect_str), which
+//! relies on the `Display` implementations of `Rope` and `RopeSlice`.
+
+use core::marker::PhantomData;
+
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use super::{RopeSlice, RopeWith};
+use crate::tree::RefCounter;
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Serialize for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn serialize<Ser: Serializer>(
+        &self,
+        serializer: Ser,
+    ) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Serialize for RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn serialize<Ser: Serializer>(
+        &self,
+        serializer: Ser,
+    ) -> Result<Ser::Ok, Ser::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "serde")))]
+impl<
+        'de,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Deserialize<'de> for RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn deserialize<D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(RopeVisitor(PhantomData))
+    }
+}
+
+/// A [`Visitor`] building a `Rope` from a string or from UTF-8 encoded bytes.
+struct RopeVisitor<C, W, B, const MAX_BYTES: usize, const MIN_FILL: usize, S>(
+    PhantomData<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>,
+)
+where
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    S: MetricSet;
+
+impl<
+        'de,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Visitor<'de> for RopeVisitor<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type Value = RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>;
+
+    #[inline]
+    fn expecting(
+        &self,
+        formatter: &mut core::fmt::Formatter,
+    ) -> core::fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    #[inline]
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Self::Value, E> {
+        Ok(RopeWith::from(s))
+    }
+
+    #[inline]
+    fn visit_string<E: de::Error>(self, s: String) -> Result<Self::Value, E> {
+        Ok(RopeWith::from(s))
+    }
+
+    #[inline]
+    fn visit_bytes<E: de::Error>(
+        self,
+        bytes: &[u8],
+    ) -> Result<Self::Value, E> {
+        match core::str::from_utf8(bytes) {
+            Ok(s) => Ok(RopeWith::from(s)),
+            Err(_) => Err(de::Error::invalid_value(
+                de::Unexpected::Bytes(bytes),
+                &self,
+            )),
+        }
+    }
+}
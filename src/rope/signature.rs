@@ -0,0 +1,302 @@
+//! This module contains the logic used to implement [`Rope::signature()`](super::Rope::signature())
+//! and [`Rope::delta_against()`](super::Rope::delta_against()).
+//!
+//! The signature of a rope is the list of the hashes of its consecutive
+//! blocks of a fixed size. To compute the delta of a new rope against it we
+//! slide a window of the same size over the new rope, looking up the weak
+//! (rolling) hash of every window among the ones of the blocks, and
+//! confirming every candidate match with the strong hash before retaining
+//! the block. This is the same scheme used by rsync.
+
+use std::collections::HashMap;
+
+use super::delta::Delta;
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use super::RopeWith;
+use crate::tree::RefCounter;
+
+/// The hashes of a block of a [`Signature`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct BlockSignature {
+    /// The rolling hash of the block, as yielded by
+    /// [`Rope::rolling_hashes()`](super::Rope::rolling_hashes()).
+    pub weak: u64,
+
+    /// The 64-bit FNV-1a hash of the block.
+    pub strong: u64,
+}
+
+/// A compact description of the contents of a [`Rope`](super::Rope), made of the hashes
+/// of its blocks of a fixed byte size.
+///
+/// This is returned by the [`signature()`](super::Rope::signature()) method on
+/// `Rope`. A peer holding a copy of the same text can send its `Signature`
+/// to the one holding a newer version of it, which can then compute a
+/// [`Delta`] containing only the text of the blocks that changed with
+/// [`delta_against()`](super::Rope::delta_against()).
+///
+/// The hashes are stable across platforms and versions of this crate, but
+/// they're not cryptographic: they shouldn't be relied upon when the text
+/// can be crafted by an adversary.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Signature {
+    block_size: usize,
+    base_len: usize,
+    blocks: Vec<BlockSignature>,
+}
+
+impl Signature {
+    /// Returns the byte length of the text the `Signature` was computed
+    /// from.
+    #[inline]
+    pub fn base_len(&self) -> usize {
+        self.base_len
+    }
+
+    /// Returns the size in bytes of the blocks of the `Signature`.
+    #[inline]
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Returns the signatures of the blocks of the text, in order.
+    ///
+    /// Only the blocks spanning a whole [`block_size()`](Self::block_size())
+    /// are included, so the last `base_len() % block_size()` bytes of the
+    /// text aren't part of any block.
+    #[inline]
+    pub fn blocks(&self) -> &[BlockSignature] {
+        &self.blocks
+    }
+
+    /// Creates a `Signature` from its parts, e.g. after receiving them over
+    /// the network.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `block_size` is zero or if the number of `blocks` is not
+    /// equal to `base_len / block_size`.
+    #[track_caller]
+    #[inline]
+    pub fn from_parts(
+        block_size: usize,
+        base_len: usize,
+        blocks: Vec<BlockSignature>,
+    ) -> Self {
+        assert!(block_size > 0, "the block size must be greater than zero");
+
+        assert_eq!(
+            blocks.len(),
+            base_len / block_size,
+            "a text of {base_len} bytes has {} blocks of {block_size} bytes, \
+             but {} were given",
+            base_len / block_size,
+            blocks.len()
+        );
+
+        Self { block_size, base_len, blocks }
+    }
+}
+
+/// Returns the [`Signature`] of `rope` with blocks of `block_size` bytes.
+#[track_caller]
+#[inline]
+pub(super) fn signature<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    rope: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    block_size: usize,
+) -> Signature {
+    assert!(block_size > 0, "the block size must be greater than zero");
+
+    let mut windows = rope.rolling_hashes(block_size);
+
+    let mut blocks = Vec::with_capacity(rope.byte_len() / block_size);
+
+    let mut strong = FNV_OFFSET_BASIS;
+
+    let mut block_len = 0;
+
+    for chunk in rope.chunks() {
+        let mut chunk = chunk.as_bytes();
+
+        while !chunk.is_empty() {
+            let (head, rest) =
+                chunk.split_at(chunk.len().min(block_size - block_len));
+
+            strong = fnv1a(strong, head);
+            block_len += head.len();
+            chunk = rest;
+
+            if block_len == block_size {
+                let weak = windows.next().expect("the block is full");
+                blocks.push(BlockSignature { weak, strong });
+                strong = FNV_OFFSET_BASIS;
+                block_len = 0;
+
+                // Skip the windows starting inside the block we just hashed.
+                if block_size > 1 {
+                    windows.nth(block_size - 2);
+                }
+            }
+        }
+    }
+
+    Signature { block_size, base_len: rope.byte_len(), blocks }
+}
+
+/// Returns the [`Delta`] which turns the text `signature` was computed from
+/// into `rope`.
+#[inline]
+pub(super) fn delta_against<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    rope: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    signature: &Signature,
+) -> Delta {
+    let block_size = signature.block_size;
+
+    let mut blocks_by_weak = HashMap::<u64, Vec<usize>>::new();
+
+    for (idx, block) in signature.blocks.iter().enumerate() {
+        blocks_by_weak.entry(block.weak).or_default().push(idx);
+    }
+
+    let find_block = |weak: u64, window_start: usize, next_block: usize| {
+        let candidates = blocks_by_weak.get(&weak)?;
+        find_block(rope, signature, candidates, next_block, window_start)
+    };
+
+    let mut delta = Delta::new();
+
+    // The index of the first block that can still be matched. Blocks are
+    // only matched in order, so that the delta can be a single pass over
+    // the base text.
+    let mut next_block = 0;
+
+    // The byte offset where the text which didn't match any block starts.
+    let mut literal_start = 0;
+
+    let mut windows = rope.rolling_hashes(block_size);
+
+    // The start of the next window yielded by `windows`.
+    let mut offset = 0;
+
+    while let Some(weak) = windows.next() {
+        let mut match_start = offset;
+
+        offset += 1;
+
+        let Some(mut block) = find_block(weak, match_start, next_block) else {
+            continue;
+        };
+
+        // A match which skips some blocks could be spurious, e.g. in text
+        // made of repeated sentences, so before accepting it we look for a
+        // closer block in the windows overlapping with it.
+        let lookahead_end = match_start + block_size;
+
+        while block > next_block && offset < lookahead_end {
+            let Some(weak) = windows.next() else { break };
+
+            if let Some(closer) = find_block(weak, offset, next_block)
+                .filter(|&closer| closer < block)
+            {
+                block = closer;
+                match_start = offset;
+            }
+
+            offset += 1;
+        }
+
+        for chunk in rope.byte_slice(literal_start..match_start).chunks() {
+            delta.insert(chunk);
+        }
+
+        delta.delete((block - next_block) * block_size).retain(block_size);
+
+        next_block = block + 1;
+        literal_start = match_start + block_size;
+
+        // Skip the windows overlapping with the block we just matched.
+        if literal_start > offset {
+            windows.nth(literal_start - offset - 1);
+            offset = literal_start;
+        }
+    }
+
+    for chunk in rope.byte_slice(literal_start..).chunks() {
+        delta.insert(chunk);
+    }
+
+    delta.delete(signature.base_len - next_block * block_size);
+
+    delta
+}
+
+/// Returns the index of the first block among `candidates` which comes at
+/// or after `next_block` and whose contents are equal to the window of
+/// `rope` starting at `window_start`, if any.
+#[inline]
+fn find_block<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    rope: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    signature: &Signature,
+    candidates: &[usize],
+    next_block: usize,
+    window_start: usize,
+) -> Option<usize> {
+    let start = candidates.partition_point(|&idx| idx < next_block);
+
+    if start == candidates.len() {
+        return None;
+    }
+
+    let window_end = window_start + signature.block_size;
+
+    // The delta can only retain whole code points.
+    if !rope.is_char_boundary(window_start)
+        || !rope.is_char_boundary(window_end)
+    {
+        return None;
+    }
+
+    let strong = rope
+        .byte_slice(window_start..window_end)
+        .chunks()
+        .fold(FNV_OFFSET_BASIS, |hash, chunk| fnv1a(hash, chunk.as_bytes()));
+
+    candidates[start..]
+        .iter()
+        .copied()
+        .find(|&idx| signature.blocks[idx].strong == strong)
+}
+
+const FNV_OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+
+/// Feeds `bytes` to a 64-bit FNV-1a hash whose current state is `hash`.
+#[inline]
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes
+        .iter()
+        .fold(hash, |hash, &byte| (hash ^ byte as u64).wrapping_mul(FNV_PRIME))
+}
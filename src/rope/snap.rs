@@ -0,0 +1,250 @@
+//! Snapping byte offsets to char or grapheme cluster boundaries.
+
+use core::ops::Range;
+
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use super::RopeSlice;
+use crate::tree::RefCounter;
+
+/// How the endpoints of a byte range are moved to valid boundaries by
+/// [`Rope::byte_slice_snapped()`](crate::Rope::byte_slice_snapped()) and
+/// [`RopeSlice::byte_slice_snapped()`].
+///
+/// Moving outward never removes text covered by the original range, while
+/// moving inward never adds text that it didn't cover.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Snap {
+    /// Moves the start of the range back and its end forward to the nearest
+    /// [`char`] boundaries.
+    CharOutward,
+
+    /// Moves the start of the range forward and its end back to the nearest
+    /// [`char`] boundaries.
+    CharInward,
+
+    /// Moves the start of the range back and its end forward to the nearest
+    /// extended grapheme cluster boundaries.
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    GraphemeOutward,
+
+    /// Moves the start of the range forward and its end back to the nearest
+    /// extended grapheme cluster boundaries.
+    #[cfg_attr(docsrs, doc(cfg(feature = "graphemes")))]
+    #[cfg(feature = "graphemes")]
+    GraphemeInward,
+}
+
+/// Snaps the `start..end` range of the slice according to `snap`. Both
+/// endpoints are assumed to be in bounds, with `start <= end`.
+///
+/// If snapping inward moves the start past the end, which happens when both
+/// endpoints are inside the same char or grapheme cluster, the range is
+/// collapsed onto its start.
+#[inline]
+pub(super) fn snap_range<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    slice: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    start: usize,
+    end: usize,
+    snap: Snap,
+) -> Range<usize> {
+    debug_assert!(start <= end && end <= slice.byte_len());
+
+    let (start, end) = match snap {
+        Snap::CharOutward => {
+            (floor_char_boundary(slice, start), ceil_char_boundary(slice, end))
+        },
+
+        Snap::CharInward => {
+            (ceil_char_boundary(slice, start), floor_char_boundary(slice, end))
+        },
+
+        #[cfg(feature = "graphemes")]
+        Snap::GraphemeOutward => (
+            graphemes::snap(slice, floor_char_boundary(slice, start), false),
+            graphemes::snap(slice, ceil_char_boundary(slice, end), true),
+        ),
+
+        #[cfg(feature = "graphemes")]
+        Snap::GraphemeInward => (
+            graphemes::snap(slice, ceil_char_boundary(slice, start), true),
+            graphemes::snap(slice, floor_char_boundary(slice, end), false),
+        ),
+    };
+
+    start..end.max(start)
+}
+
+#[inline]
+fn floor_char_boundary<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    slice: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    mut byte_offset: usize,
+) -> usize {
+    while !slice.is_char_boundary(byte_offset) {
+        byte_offset -= 1;
+    }
+    byte_offset
+}
+
+#[inline]
+fn ceil_char_boundary<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    slice: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    mut byte_offset: usize,
+) -> usize {
+    while !slice.is_char_boundary(byte_offset) {
+        byte_offset += 1;
+    }
+    byte_offset
+}
+
+#[cfg(feature = "graphemes")]
+pub(super) mod graphemes {
+    use unicode_segmentation::{GraphemeCursor, GraphemeIncomplete};
+
+    use super::{CharWeight, LineBreaks, MetricSet, RefCounter, RopeSlice};
+
+    /// Returns whether the byte offset, which must be a char boundary, is a
+    /// grapheme boundary.
+    ///
+    /// Unlike moving a `GraphemeCursor` to the next boundary, which can get
+    /// regional indicators split across chunks wrong, checking a single
+    /// offset is given all the context it needs.
+    #[cfg(feature = "grapheme-count")]
+    #[inline]
+    pub(in crate::rope) fn is_boundary<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    >(
+        slice: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+        byte_offset: usize,
+    ) -> bool {
+        let len = slice.byte_len();
+
+        if byte_offset == 0 || byte_offset == len {
+            return true;
+        }
+
+        let mut cursor = GraphemeCursor::new(byte_offset, len, true);
+
+        let (chunk, chunk_start) = slice.chunk_containing_byte(byte_offset);
+
+        loop {
+            match cursor.is_boundary(chunk, chunk_start) {
+                Ok(is_boundary) => return is_boundary,
+                Err(GraphemeIncomplete::PreContext(end)) => {
+                    let (prev, prev_start) =
+                        slice.chunk_containing_byte(end - 1);
+                    cursor.provide_context(prev, prev_start);
+                },
+                Err(_) => unreachable!(),
+            }
+        }
+    }
+
+    /// Returns the byte offset if it's a grapheme boundary, or the nearest
+    /// boundary after or before it otherwise.
+    ///
+    /// The `GraphemeCursor` is fed the chunks it asks for, which are looked
+    /// up in logarithmic time with `RopeSlice::chunk_containing_byte()`.
+    #[inline]
+    pub(super) fn snap<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    >(
+        slice: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+        byte_offset: usize,
+        forward: bool,
+    ) -> usize {
+        let len = slice.byte_len();
+
+        if byte_offset == 0 || byte_offset == len {
+            return byte_offset;
+        }
+
+        let mut cursor = GraphemeCursor::new(byte_offset, len, true);
+
+        let (mut chunk, mut chunk_start) =
+            slice.chunk_containing_byte(byte_offset);
+
+        let provide_context =
+            |cursor: &mut GraphemeCursor, context_end: usize| {
+                let (prev, prev_start) =
+                    slice.chunk_containing_byte(context_end - 1);
+                cursor.provide_context(prev, prev_start);
+            };
+
+        loop {
+            match cursor.is_boundary(chunk, chunk_start) {
+                Ok(true) => return byte_offset,
+                Ok(false) => break,
+                Err(GraphemeIncomplete::PreContext(end)) => {
+                    provide_context(&mut cursor, end)
+                },
+                Err(_) => unreachable!(),
+            }
+        }
+
+        loop {
+            let boundary = if forward {
+                cursor.next_boundary(chunk, chunk_start)
+            } else {
+                cursor.prev_boundary(chunk, chunk_start)
+            };
+
+            match boundary {
+                Ok(Some(boundary)) => return boundary,
+
+                Ok(None) => return if forward { len } else { 0 },
+
+                Err(GraphemeIncomplete::NextChunk) => {
+                    chunk_start += chunk.len();
+                    chunk = slice.chunk_containing_byte(chunk_start).0;
+                },
+
+                Err(GraphemeIncomplete::PrevChunk) => {
+                    let (prev, prev_start) =
+                        slice.chunk_containing_byte(chunk_start - 1);
+                    chunk = prev;
+                    chunk_start = prev_start;
+                },
+
+                Err(GraphemeIncomplete::PreContext(end)) => {
+                    provide_context(&mut cursor, end)
+                },
+
+                Err(GraphemeIncomplete::InvalidOffset) => unreachable!(),
+            }
+        }
+    }
+}
@@ -0,0 +1,650 @@
+//! A binary snapshot format for `Rope`s.
+//!
+//! A snapshot stores the shape of the tree of a `Rope` together with the
+//! summary of every one of its nodes, so that loading it back doesn't have
+//! to split, summarize and rebalance the text again. The text of every leaf
+//! is stored in a slot as big as a leaf, which lets the leaves of a `Rope`
+//! read from a shared snapshot borrow their bytes from it instead of copying
+//! them.
+//!
+//! The header and the nodes are covered by a checksum, and a snapshot whose
+//! checksum doesn't match is rejected. The text of the leaves isn't, so
+//! unless the snapshot is trusted the summary of every leaf is recomputed
+//! from its text and checked against the stored one, and the summaries of the
+//! internal nodes are checked against the ones of their children.
+//!
+//! All the integers are stored in little-endian order. The layout is:
+//!
+//! - the magic bytes `b"CROPSNAP"`;
+//!
+//! - the version of the format (`u32`);
+//!
+//! - a set of flags (`u32`) recording which of the features affecting the
+//!   summaries or the leaves were enabled when the snapshot was written, which
+//!   of their metrics the [`MetricSet`] of the rope maintained, and whether
+//!   the rope broke its lines with [`UnicodeBreaks`](super::UnicodeBreaks);
+//!
+//! - the maximum number of bytes in a leaf (`u32`);
+//!
+//! - the minimum fill of a leaf, as a percentage of the maximum number of
+//!   bytes (`u32`);
+//!
+//! - the maximum number of children of an internal node (`u32`);
+//!
+//! - the number of fields of a summary (`u32`);
+//!
+//! - an identifier of the [`CharWeight`] of the rope (`u64`), or zero if the
+//!   `custom-metric` flag is not set;
+//!
+//! - the number of nodes (`u64`);
+//!
+//! - the number of leaves (`u64`);
+//!
+//! - the checksum of everything before it and of the nodes (`u64`);
+//!
+//! - the nodes of the tree in pre-order, each one made of its number of
+//!   children (`u32`, zero for a leaf) followed by the fields of its summary
+//!   (`u64`s), the first of which is its byte length;
+//!
+//! - the text of every leaf, each one padded with zeros to the maximum number
+//!   of bytes in a leaf.
+//!
+//! If a snapshot was written with a different configuration than the one
+//! it's loaded with, the stored summaries can't be used and the `Rope` is
+//! rebuilt from the text of the leaves. Snapshots written by older versions
+//! of the format, which only stored the summaries of the leaves, are always
+//! rebuilt.
+
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+
+use super::gap_buffer::RawGapBuffer;
+use super::leaf_bytes::{LeafBytes, SharedBytes};
+use super::line_breaks::LineBreaks;
+use super::metrics::sealed::Sealed as _;
+use super::metrics::{CharWeight, ChunkSummary, MetricSet, SUMMARY_FIELDS};
+use super::rope::RopeChunk;
+use super::{RopeBuilder, RopeWith};
+use crate::tree::{PreorderNode, RefCounter, Tree};
+
+const MAGIC: &[u8; 8] = b"CROPSNAP";
+
+const VERSION: u32 = 4;
+
+/// The length of the header, including the checksum.
+const HEADER_LEN: usize = 64;
+
+const FLAG_UTF16_METRIC: u32 = 1 << 0;
+
+const FLAG_UNICODE_LINES: u32 = 1 << 1;
+
+const FLAG_NO_LINES: u32 = 1 << 2;
+
+const FLAG_NO_CHARS: u32 = 1 << 3;
+
+const FLAG_CUSTOM_METRIC: u32 = 1 << 4;
+
+const FLAG_WIDTH_METRIC: u32 = 1 << 5;
+
+const FLAG_GRAPHEME_CHUNKS: u32 = 1 << 6;
+
+const FLAG_MAX_LINE_LEN: u32 = 1 << 7;
+
+const FLAG_WORD_COUNT: u32 = 1 << 8;
+
+const FLAG_GRAPHEME_COUNT: u32 = 1 << 9;
+
+const FLAG_FINGERPRINT: u32 = 1 << 10;
+
+const FLAG_PARAGRAPH_COUNT: u32 = 1 << 11;
+
+/// The flags of the snapshots written by this build of the crate for ropes
+/// breaking their lines with `B` and maintaining the metrics of `S`.
+#[inline]
+fn snapshot_flags<B: LineBreaks, S: MetricSet>() -> u32 {
+    let mut flags = 0;
+
+    if cfg!(feature = "utf16-metric") && S::Utf16CodeUnits::IS_MAINTAINED {
+        flags |= FLAG_UTF16_METRIC;
+    }
+
+    if B::is_line_break('\u{2028}') {
+        flags |= FLAG_UNICODE_LINES;
+    }
+
+    if !cfg!(feature = "lines") || !S::Lines::IS_MAINTAINED {
+        flags |= FLAG_NO_LINES;
+    }
+
+    if !cfg!(feature = "char-metric") || !S::Chars::IS_MAINTAINED {
+        flags |= FLAG_NO_CHARS;
+    }
+
+    if cfg!(feature = "custom-metric") {
+        flags |= FLAG_CUSTOM_METRIC;
+    }
+
+    if cfg!(feature = "width-metric") && S::Width::IS_MAINTAINED {
+        flags |= FLAG_WIDTH_METRIC;
+    }
+
+    if cfg!(feature = "grapheme-chunks") {
+        flags |= FLAG_GRAPHEME_CHUNKS;
+    }
+
+    if cfg!(feature = "max-line-len") {
+        flags |= FLAG_MAX_LINE_LEN;
+    }
+
+    if cfg!(feature = "word-count") {
+        flags |= FLAG_WORD_COUNT;
+    }
+
+    if cfg!(feature = "grapheme-count") {
+        flags |= FLAG_GRAPHEME_COUNT;
+    }
+
+    if cfg!(feature = "fingerprint") {
+        flags |= FLAG_FINGERPRINT;
+    }
+
+    if cfg!(feature = "paragraph-count") {
+        flags |= FLAG_PARAGRAPH_COUNT;
+    }
+
+    flags
+}
+
+/// An identifier of the `CharWeight` used to compute the custom units of a
+/// rope, which can only be trusted if the snapshot is read with the same
+/// one.
+#[inline]
+fn weight_id<W: CharWeight>() -> u64 {
+    if cfg!(feature = "custom-metric") {
+        checksum(&[core::any::type_name::<W>().as_bytes()])
+    } else {
+        0
+    }
+}
+
+/// Returns a 64-bit checksum of the concatenation of the parts.
+///
+/// It's only meant to catch snapshots that were truncated or corrupted by
+/// accident, not ones that were crafted on purpose.
+#[inline]
+fn checksum(parts: &[&[u8]]) -> u64 {
+    const K: u64 = 0x9e37_79b9_7f4a_7c15;
+
+    let mix =
+        |hash: u64, word: u64| (hash ^ word).wrapping_mul(K).rotate_left(29);
+
+    let len = parts.iter().map(|part| part.len()).sum::<usize>();
+
+    let mut hash = (len as u64).wrapping_mul(K);
+
+    for part in parts {
+        let mut words = part.chunks_exact(8);
+
+        for word in &mut words {
+            hash = mix(hash, u64::from_le_bytes(word.try_into().unwrap()));
+        }
+
+        for &byte in words.remainder() {
+            hash = mix(hash, byte as u64);
+        }
+    }
+
+    hash ^ (hash >> 32)
+}
+
+#[inline]
+fn invalid(msg: &'static str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, msg)
+}
+
+/// Splits the first `len` bytes off `bytes`.
+#[inline]
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> io::Result<&'a [u8]> {
+    if bytes.len() < len {
+        return Err(invalid("unexpected end of rope snapshot"));
+    }
+
+    let (taken, rest) = bytes.split_at(len);
+    *bytes = rest;
+    Ok(taken)
+}
+
+#[inline]
+fn take_u32(bytes: &mut &[u8]) -> io::Result<u32> {
+    let taken = take(bytes, 4)?;
+    Ok(u32::from_le_bytes(taken.try_into().unwrap()))
+}
+
+#[inline]
+fn take_u64(bytes: &mut &[u8]) -> io::Result<u64> {
+    let taken = take(bytes, 8)?;
+    Ok(u64::from_le_bytes(taken.try_into().unwrap()))
+}
+
+#[inline]
+fn take_usize(bytes: &mut &[u8]) -> io::Result<usize> {
+    usize::try_from(take_u64(bytes)?)
+        .map_err(|_| invalid("rope snapshot too large for this platform"))
+}
+
+/// Writes a snapshot of the `Rope` to the writer.
+#[inline]
+pub(super) fn write<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    T: Write,
+    S: MetricSet,
+>(
+    rope: &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+    mut writer: T,
+) -> io::Result<()> {
+    let mut nodes = Vec::new();
+    let mut num_nodes = 0u64;
+
+    rope.tree.for_each_node(|node| {
+        let (children, summary) = match node {
+            PreorderNode::Internal { children, summary } => {
+                (children, summary)
+            },
+            PreorderNode::Leaf { summary, .. } => (0, summary),
+        };
+
+        nodes.extend_from_slice(&(children as u32).to_le_bytes());
+
+        for field in summary.fields() {
+            nodes.extend_from_slice(&(field as u64).to_le_bytes());
+        }
+
+        num_nodes += 1;
+    });
+
+    let mut header = Vec::with_capacity(HEADER_LEN);
+    header.extend_from_slice(MAGIC);
+    header.extend_from_slice(&VERSION.to_le_bytes());
+    header.extend_from_slice(&snapshot_flags::<B, S>().to_le_bytes());
+    header.extend_from_slice(&(MAX_BYTES as u32).to_le_bytes());
+    header.extend_from_slice(&(MIN_FILL as u32).to_le_bytes());
+    header.extend_from_slice(
+        &(RopeWith::<C, W, B, MAX_BYTES, MIN_FILL, S>::arity() as u32)
+            .to_le_bytes(),
+    );
+    header.extend_from_slice(&(SUMMARY_FIELDS as u32).to_le_bytes());
+    header.extend_from_slice(&weight_id::<W>().to_le_bytes());
+    header.extend_from_slice(&num_nodes.to_le_bytes());
+    header.extend_from_slice(&(rope.tree.leaf_count() as u64).to_le_bytes());
+
+    let checksum = checksum(&[&header, &nodes]);
+    header.extend_from_slice(&checksum.to_le_bytes());
+
+    debug_assert_eq!(header.len(), HEADER_LEN);
+
+    writer.write_all(&header)?;
+    writer.write_all(&nodes)?;
+
+    for leaf in rope.tree.leaves() {
+        writer.write_all(leaf.left_chunk().as_bytes())?;
+        writer.write_all(leaf.right_chunk().as_bytes())?;
+
+        let padding = (MAX_BYTES - leaf.len()) as u64;
+        io::copy(&mut io::repeat(0).take(padding), &mut writer)?;
+    }
+
+    Ok(())
+}
+
+/// Where the leaves of a `Rope` read from a snapshot get their bytes from.
+pub(super) enum LeafSource {
+    /// The bytes are copied out of the snapshot, after checking that they're
+    /// valid UTF-8.
+    Copied,
+
+    /// The bytes are borrowed from the snapshot, which is the same one the
+    /// `Rope` is being read from. They're checked to be valid UTF-8 unless
+    /// `trusted` is true.
+    Shared { snapshot: SharedBytes, trusted: bool },
+}
+
+/// Reads a `Rope` from a snapshot previously written by [`write`].
+///
+/// # Safety
+///
+/// If `leaves` is [`LeafSource::Shared`] and `trusted` is true, the snapshot
+/// must have been written by [`write`] and not modified since, and every
+/// call to `as_ref()` on it must return the same bytes.
+#[inline]
+pub(super) unsafe fn read<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    snapshot: &[u8],
+    leaves: LeafSource,
+) -> io::Result<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> {
+    let mut bytes = snapshot;
+
+    if take(&mut bytes, MAGIC.len())? != MAGIC {
+        return Err(invalid("not a rope snapshot"));
+    }
+
+    let version = take_u32(&mut bytes)?;
+
+    if !(1..=VERSION).contains(&version) {
+        return Err(invalid("unsupported rope snapshot version"));
+    }
+
+    if version < VERSION {
+        return read_legacy(version, bytes);
+    }
+
+    let flags = take_u32(&mut bytes)?;
+    let max_bytes = take_u32(&mut bytes)? as usize;
+    let min_fill = take_u32(&mut bytes)? as usize;
+    let arity = take_u32(&mut bytes)? as usize;
+    let summary_fields = take_u32(&mut bytes)? as usize;
+    let weight_id = take_u64(&mut bytes)?;
+    let num_nodes = take_usize(&mut bytes)?;
+    let num_leaves = take_usize(&mut bytes)?;
+    let stored_checksum = take_u64(&mut bytes)?;
+
+    let node_len = summary_fields
+        .checked_mul(8)
+        .and_then(|len| len.checked_add(4))
+        .ok_or_else(|| invalid("too many summary fields in rope snapshot"))?;
+
+    let nodes_len = num_nodes
+        .checked_mul(node_len)
+        .ok_or_else(|| invalid("too many nodes in rope snapshot"))?;
+
+    let mut nodes = take(&mut bytes, nodes_len)?;
+
+    if checksum(&[&snapshot[..HEADER_LEN - 8], nodes]) != stored_checksum {
+        return Err(invalid("checksum mismatch in rope snapshot"));
+    }
+
+    let slots = bytes;
+
+    if num_leaves.checked_mul(max_bytes) != Some(slots.len()) {
+        return Err(invalid("wrong number of leaves in rope snapshot"));
+    }
+
+    let mut next_node = || -> io::Result<(usize, &[u8])> {
+        let children = take_u32(&mut nodes)? as usize;
+        let summary = take(&mut nodes, node_len - 4)?;
+        Ok((children, summary))
+    };
+
+    // The snapshot was written with a different configuration, so we can't
+    // reuse its tree.
+    if flags != snapshot_flags::<B, S>()
+        || max_bytes != MAX_BYTES
+        || min_fill != MIN_FILL
+        || arity != RopeWith::<C, W, B, MAX_BYTES, MIN_FILL, S>::arity()
+        || summary_fields != SUMMARY_FIELDS
+        || weight_id != self::weight_id::<W>()
+    {
+        let mut builder =
+            RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+
+        let mut slots = slots.chunks_exact(max_bytes.max(1));
+
+        for _ in 0..num_nodes {
+            let (children, mut summary) = next_node()?;
+
+            if children != 0 {
+                continue;
+            }
+
+            let len = take_usize(&mut summary)?;
+
+            let slot = slots
+                .next()
+                .ok_or_else(|| invalid("too many leaves in rope snapshot"))?;
+
+            let text = slot.get(..len).ok_or_else(|| {
+                invalid("invalid leaf size in rope snapshot")
+            })?;
+
+            builder.append(
+                core::str::from_utf8(text).map_err(|_| {
+                    invalid("rope snapshot is not valid UTF-8")
+                })?,
+            );
+        }
+
+        return Ok(builder.build());
+    }
+
+    let chunk_min = RopeChunk::<W, B, MAX_BYTES, MIN_FILL, S>::chunk_min();
+
+    let mut error = None;
+
+    let mut leaf_idx = 0;
+
+    let mut read_node =
+        || -> io::Result<PreorderNode<RopeChunk<W, B, MAX_BYTES, MIN_FILL, S>, _>> {
+            let (children, mut summary) = next_node()?;
+
+            let mut fields = [0; SUMMARY_FIELDS];
+
+            for field in &mut fields {
+                *field = take_usize(&mut summary)?;
+            }
+
+            let summary = ChunkSummary::<W, B, S>::from_fields(fields)
+                .ok_or_else(|| invalid("summary too large in rope snapshot"))?;
+
+            if children != 0 {
+                return Ok(PreorderNode::Internal { children, summary });
+            }
+
+            let len = summary.bytes();
+
+            if len > MAX_BYTES
+                || (num_leaves > 1 && len < chunk_min)
+                || leaf_idx == num_leaves
+            {
+                return Err(invalid("invalid leaf in rope snapshot"));
+            }
+
+            let start = leaf_idx * MAX_BYTES;
+
+            leaf_idx += 1;
+
+            let bytes = match &leaves {
+                LeafSource::Copied => {
+                    let mut bytes = LeafBytes::zeroed();
+                    bytes[..len].copy_from_slice(&slots[start..start + len]);
+                    bytes
+                },
+
+                LeafSource::Shared { snapshot, .. } => LeafBytes::shared(
+                    Arc::clone(snapshot),
+                    HEADER_LEN + nodes_len + start,
+                ),
+            };
+
+            let is_trusted =
+                matches!(leaves, LeafSource::Shared { trusted: true, .. });
+
+            // The text isn't covered by the checksum, so unless we trust the
+            // snapshot we have to make sure it's valid UTF-8 and that it
+            // matches the summary stored for it.
+            if !is_trusted {
+                let text = core::str::from_utf8(&bytes[..len]).map_err(|_| {
+                    invalid("rope snapshot is not valid UTF-8")
+                })?;
+
+                let mut actual = ChunkSummary::<W, B, S>::from(text);
+                actual.join_segments(text, "");
+
+                if !actual.is_identical_to(&summary) {
+                    return Err(invalid(
+                        "leaf summary doesn't match its text in rope snapshot",
+                    ));
+                }
+            }
+
+            // SAFETY: the text of the leaf was either just checked to be
+            // valid UTF-8, or the caller guaranteed that the snapshot was
+            // written by us, and the length of the text is at most
+            // `MAX_BYTES`.
+            let leaf = unsafe { RawGapBuffer::from_left_chunk(bytes, summary) };
+
+            Ok(PreorderNode::Leaf { leaf, summary })
+        };
+
+    // The summaries of the internal nodes can't cause any harm if we trust
+    // the snapshot, but a crafted snapshot could otherwise use them to make
+    // the tree point inside a code point.
+    let check_summaries =
+        !matches!(leaves, LeafSource::Shared { trusted: true, .. });
+
+    let tree = Tree::from_preorder(
+        (0..num_nodes)
+            .map_while(|_| read_node().map_err(|err| error = Some(err)).ok()),
+        check_summaries,
+    );
+
+    if let Some(err) = error {
+        return Err(err);
+    }
+
+    match tree {
+        Some(tree) if leaf_idx == num_leaves => Ok(RopeWith { tree }),
+        _ => Err(invalid("invalid tree in rope snapshot")),
+    }
+}
+
+/// Reads a `Rope` from a snapshot written by one of the versions of the
+/// format before the current one, which only stored the summaries of the
+/// leaves. The `Rope` is always rebuilt from their text.
+///
+/// Version 1 snapshots didn't store the number of `char`s of the leaves,
+/// and versions 1 and 2 didn't store the minimum fill of the leaves.
+#[inline]
+fn read_legacy<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    version: u32,
+    mut snapshot: &[u8],
+) -> io::Result<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> {
+    let flags = take_u32(&mut snapshot)?;
+
+    let _max_bytes = take_u32(&mut snapshot)?;
+
+    if version >= 3 {
+        let _min_fill = take_u32(&mut snapshot)?;
+    }
+
+    let num_leaves = take_usize(&mut snapshot)?;
+
+    let mut summary_len = 8;
+
+    if version >= 2 {
+        summary_len += 4;
+    }
+
+    if flags & FLAG_UTF16_METRIC != 0 {
+        summary_len += 4;
+    }
+
+    let summaries_len = num_leaves
+        .checked_mul(summary_len)
+        .ok_or_else(|| invalid("too many leaves in rope snapshot"))?;
+
+    let mut summaries = take(&mut snapshot, summaries_len)?;
+
+    let mut texts = snapshot;
+
+    let mut builder =
+        RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+
+    for _ in 0..num_leaves {
+        let len = take_u32(&mut summaries)? as usize;
+        take(&mut summaries, summary_len - 4)?;
+
+        let text = take(&mut texts, len)?;
+
+        builder.append(
+            core::str::from_utf8(text)
+                .map_err(|_| invalid("rope snapshot is not valid UTF-8"))?,
+        );
+    }
+
+    if texts.is_empty() {
+        Ok(builder.build())
+    } else {
+        Err(invalid("trailing bytes in rope snapshot"))
+    }
+}
+
+#[cfg(all(test, feature = "lines"))]
+mod tests {
+    use super::*;
+    use crate::Rope;
+
+    /// Replaces the checksum of the snapshot with the one of its contents.
+    fn fix_checksum(snapshot: &mut [u8]) {
+        let num_nodes =
+            u64::from_le_bytes(snapshot[40..48].try_into().unwrap()) as usize;
+
+        let nodes_len = num_nodes * (4 + 8 * SUMMARY_FIELDS);
+
+        let checksum = checksum(&[
+            &snapshot[..HEADER_LEN - 8],
+            &snapshot[HEADER_LEN..HEADER_LEN + nodes_len],
+        ]);
+
+        snapshot[HEADER_LEN - 8..HEADER_LEN]
+            .copy_from_slice(&checksum.to_le_bytes());
+    }
+
+    /// The stored summaries are only trusted by the unchecked constructor,
+    /// and only if the snapshot wasn't written with a different set of
+    /// features, in which case the rope is rebuilt from its text.
+    #[test]
+    fn snapshot_trusts_summaries_only_if_unchecked() {
+        // A single leaf, so that there's no internal node whose summary
+        // would stop adding up.
+        let r = Rope::from("a\nb");
+
+        let mut snapshot = Vec::new();
+        r.write_snapshot(&mut snapshot).unwrap();
+
+        // Bump the number of line breaks of the leaf.
+        snapshot[HEADER_LEN + 12] += 1;
+        fix_checksum(&mut snapshot);
+
+        assert!(Rope::from_snapshot(&snapshot).is_err());
+
+        // SAFETY: the snapshot is a `Vec`, so its contents can't change, and
+        // the rope is only used to check that the summary was trusted.
+        let trusted =
+            unsafe { Rope::from_shared_snapshot_unchecked(snapshot.clone()) }
+                .unwrap();
+        assert_eq!(trusted.line_len(), 3);
+
+        snapshot[12] ^= FLAG_GRAPHEME_CHUNKS as u8;
+        fix_checksum(&mut snapshot);
+
+        let rebuilt = Rope::from_snapshot(&snapshot).unwrap();
+        rebuilt.assert_invariants();
+        assert_eq!(rebuilt.line_len(), 2);
+    }
+}
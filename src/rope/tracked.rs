@@ -0,0 +1,585 @@
+use core::ops::{Range, RangeBounds};
+
+use super::anchors::{Anchor, Anchors, Gravity};
+use super::delta::Delta;
+use super::dirty::DirtyRange;
+use super::history::History;
+use super::line_breaks::LineBreaks;
+#[cfg(feature = "lines")]
+use super::metrics::Maintained;
+use super::metrics::{CharWeight, MetricSet};
+use super::utils::panic_messages as panic;
+use super::RopeWith;
+use crate::range_bounds_to_start_end;
+use crate::tree::RefCounter;
+
+/// A wrapper around a [`Rope`](super::Rope) (or any other
+/// [`RopeWith`](super::RopeWith), like a [`LocalRope`](super::LocalRope))
+/// which keeps track of the edits made through it.
+///
+/// A plain `Rope` only stores its text. Wrapping it in a `Tracked<Rope>`
+/// adds:
+///
+/// - the [`Anchor`]s created with [`anchor()`](Self::anchor()), which follow
+///   the edits made to the text and are suited to represent cursors,
+///   bookmarks, diagnostics and the like;
+/// - the [`Delta`] recorded between calls to
+///   [`start_recording()`](Self::start_recording()) and
+///   [`stop_recording()`](Self::stop_recording());
+/// - the [`revision()`](Self::revision()), which counts the edits, and the
+///   history enabled by [`track_history()`](Self::track_history()), with
+///   which byte offsets from past revisions can be resolved;
+/// - the part of the text edited since the last call to
+///   [`mark_clean()`](Self::mark_clean()), e.g. to only redraw the lines
+///   which changed.
+///
+/// The edits made with [`insert()`](Self::insert()),
+/// [`delete()`](Self::delete()) and [`replace()`](Self::replace()) are
+/// tracked exactly. Any other edit can be made with
+/// [`edit()`](Self::edit()), in which case the byte ranges it changed are
+/// found by comparing the text before and after it.
+///
+/// # Examples
+///
+/// ```
+/// # use crop::{Gravity, Rope, Tracked};
+/// #
+/// let mut t = Tracked::new(Rope::from("Hello Earth!"));
+///
+/// let cursor = t.anchor(6, Gravity::Right);
+///
+/// t.insert(0, "Oh, ");
+/// t.edit(|rope| rope.append(Rope::from(" 🌎")));
+///
+/// assert_eq!(t.rope(), "Oh, Hello Earth! 🌎");
+/// assert_eq!(t.anchor_offset(cursor), Some(10));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct Tracked<T> {
+    current: T,
+
+    /// The anchors created by [`anchor()`](Self::anchor()), which are
+    /// updated on every edit.
+    anchors: Anchors,
+
+    /// The edits made since the last call to
+    /// [`start_recording()`](Self::start_recording()), if any.
+    recording: Option<Delta>,
+
+    /// The current revision and, if it's being tracked, the history of the
+    /// edits.
+    history: History,
+
+    /// The text edited since the last call to
+    /// [`mark_clean()`](Self::mark_clean()), if it was ever called.
+    dirty: Option<DirtyRange>,
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Tracked<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+{
+    /// Creates an [`Anchor`] at the given byte offset.
+    ///
+    /// The anchor follows the edits made to the `Rope`: text inserted or
+    /// deleted before it shifts it, while text inserted exactly at its
+    /// offset goes after or before it depending on its [`Gravity`]. If the
+    /// text around it is deleted or replaced it's moved to the start of the
+    /// replacement if its gravity is [`Left`](Gravity::Left), or to its end
+    /// if it's [`Right`](Gravity::Right).
+    ///
+    /// Every edit takes time linear in the number of anchors, so anchors that
+    /// are no longer needed should be removed with
+    /// [`remove_anchor()`](Self::remove_anchor()).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset doesn't lie on a code point boundary or if
+    /// it's out of bounds (i.e. greater than the byte length of the `Rope`).
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Gravity, Rope, Tracked};
+    /// #
+    /// let mut t = Tracked::new(Rope::from("Hello Earth!"));
+    ///
+    /// let before = t.anchor(6, Gravity::Left);
+    /// let after = t.anchor(6, Gravity::Right);
+    ///
+    /// t.insert(6, "🌎 ");
+    /// t.insert(0, "👋 ");
+    ///
+    /// assert_eq!(t.rope(), "👋 Hello 🌎 Earth!");
+    /// assert_eq!(t.anchor_offset(before), Some(11));
+    /// assert_eq!(t.anchor_offset(after), Some(16));
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn anchor(&mut self, byte_offset: usize, gravity: Gravity) -> Anchor {
+        let byte_len = self.current.byte_len();
+
+        if byte_offset > byte_len {
+            panic::byte_offset_out_of_bounds(byte_offset, byte_len);
+        }
+
+        self.current.assert_char_boundary(byte_offset);
+
+        self.anchors.insert(byte_offset, gravity)
+    }
+
+    /// Returns the current byte offset of the given [`Anchor`], or `None` if
+    /// it's been removed or if it wasn't created by this `Tracked`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Gravity, Rope, Tracked};
+    /// #
+    /// let mut t = Tracked::new(Rope::from("Hello Earth!"));
+    ///
+    /// let earth = t.anchor(6, Gravity::Right);
+    ///
+    /// t.replace(0..5, "Hi");
+    /// assert_eq!(t.anchor_offset(earth), Some(3));
+    ///
+    /// t.remove_anchor(earth);
+    /// assert_eq!(t.anchor_offset(earth), None);
+    /// ```
+    #[inline]
+    pub fn anchor_offset(&self, anchor: Anchor) -> Option<usize> {
+        self.anchors.byte_offset(anchor)
+    }
+
+    /// Applies the given [`Delta`] to the `Rope`.
+    ///
+    /// Every deletion and insertion of the `Delta` is tracked as if it was
+    /// made with [`replace()`](Self::replace()).
+    ///
+    /// See [`Delta::apply()`] for more.
+    #[track_caller]
+    #[inline]
+    pub fn apply(&mut self, delta: &Delta) {
+        delta.for_each_edit(self.current.byte_len(), |byte_range, text| {
+            self.replace(byte_range, text)
+        });
+    }
+
+    /// Deletes the given byte range.
+    ///
+    /// See [`Rope::delete()`](super::Rope::delete()) for more.
+    #[track_caller]
+    #[inline]
+    pub fn delete<R>(&mut self, byte_range: R)
+    where
+        R: RangeBounds<usize>,
+    {
+        self.replace(byte_range, "");
+    }
+
+    /// Returns the smallest byte range of the `Rope` covering all the text
+    /// inserted or changed since the last call to
+    /// [`mark_clean()`](Self::mark_clean()), or `None` if it hasn't been
+    /// edited since or if `mark_clean()` was never called.
+    ///
+    /// The range is empty if the edits only deleted text, in which case it
+    /// points to where the text used to be.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Rope, Tracked};
+    /// #
+    /// let mut t = Tracked::new(Rope::from("Hello Earth!"));
+    /// assert_eq!(t.dirty_byte_range(), None);
+    ///
+    /// t.mark_clean();
+    /// t.replace(6..11, "Mars");
+    /// t.insert(0, "Oh, ");
+    /// assert_eq!(t.dirty_byte_range(), Some(0..14));
+    ///
+    /// t.mark_clean();
+    /// t.delete(4..8);
+    /// assert_eq!(t.dirty_byte_range(), Some(4..4));
+    /// ```
+    #[inline]
+    pub fn dirty_byte_range(&self) -> Option<Range<usize>> {
+        self.dirty.as_ref().and_then(DirtyRange::byte_range)
+    }
+
+    /// Returns the range of lines of the `Rope` touched by the edits made
+    /// since the last call to [`mark_clean()`](Self::mark_clean()), or
+    /// `None` if it hasn't been edited since or if `mark_clean()` was never
+    /// called.
+    ///
+    /// These are the lines overlapping the
+    /// [`dirty_byte_range()`](Self::dirty_byte_range()), so this runs in
+    /// logarithmic time no matter how many edits were made. Like the byte
+    /// range it's the union of all the edits, so it can include lines in
+    /// between them that weren't changed. If the edits inserted or deleted
+    /// line breaks, the lines after the range have also moved.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Rope, Tracked};
+    /// #
+    /// let mut t = Tracked::new(Rope::from("foo\nbar\nbaz\nqux\n"));
+    ///
+    /// t.mark_clean();
+    /// t.insert(5, "aaa");
+    /// t.insert(12, "zzz");
+    /// assert_eq!(t.dirty_lines(), Some(1..3));
+    ///
+    /// // Joining two lines only touches the first one.
+    /// t.mark_clean();
+    /// t.delete(3..4);
+    /// assert_eq!(t.dirty_lines(), Some(0..1));
+    /// ```
+    #[cfg_attr(docsrs, doc(cfg(feature = "lines")))]
+    #[cfg(feature = "lines")]
+    #[inline]
+    pub fn dirty_lines(&self) -> Option<Range<usize>>
+    where
+        S: MetricSet<Lines = Maintained>,
+    {
+        let Range { start, end } = self.dirty_byte_range()?;
+
+        let rope = &self.current;
+
+        let start_line = rope.line_of_byte(start);
+
+        let end_line = if end > start {
+            rope.line_of_byte(end - 1) + 1
+        } else {
+            start_line + 1
+        };
+
+        // Deleting the text after the last line break leaves an empty range
+        // past the last line.
+        Some(start_line..end_line.min(rope.line_len().max(start_line)))
+    }
+
+    /// Edits the `Rope` with the given closure, returning its result.
+    ///
+    /// The edits made by the closure are tracked as the replacements of the
+    /// byte ranges which differ between the text before and after it, each
+    /// of which counts as an edit. The chunks the two texts share are skipped
+    /// without being compared, so this is fast even for large texts when the
+    /// edits are small.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Gravity, Rope, Tracked};
+    /// #
+    /// let mut t = Tracked::new(Rope::from("Hello Earth!"));
+    ///
+    /// let bang = t.anchor(11, Gravity::Right);
+    ///
+    /// let len = t.edit(|rope| {
+    ///     rope.replace(6..11, "Saturn");
+    ///     rope.byte_len()
+    /// });
+    ///
+    /// assert_eq!(len, 13);
+    /// assert_eq!(t.rope(), "Hello Saturn!");
+    /// assert_eq!(t.anchor_offset(bang), Some(12));
+    /// ```
+    #[inline]
+    pub fn edit<F, R>(&mut self, edit: F) -> R
+    where
+        F: FnOnce(&mut RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> R,
+    {
+        let old = self.current.clone();
+
+        let ret = edit(&mut self.current);
+
+        let new = self.current.clone();
+
+        // The ranges are sorted, so the edits have to be tracked from left
+        // to right, at which point the text before each of them is already
+        // in its edited form.
+        for (old_range, new_range) in old.changed_ranges(&new) {
+            let start = new_range.start;
+
+            self.track_edit(
+                start..start + old_range.len(),
+                new_range.len(),
+                new.byte_slice(new_range).chunks(),
+            );
+        }
+
+        ret
+    }
+
+    /// Drops the edits made before the given revision from the history,
+    /// after which offsets from those revisions can no longer be resolved
+    /// with [`resolve_offset()`](Self::resolve_offset()).
+    ///
+    /// This does nothing if the history isn't being tracked.
+    #[inline]
+    pub fn forget_history(&mut self, before: u64) {
+        self.history.forget_before(before);
+    }
+
+    /// Inserts `text` at the given byte offset.
+    ///
+    /// See [`Rope::insert()`](super::Rope::insert()) for more.
+    #[track_caller]
+    #[inline]
+    pub fn insert<T>(&mut self, byte_offset: usize, text: T)
+    where
+        T: AsRef<str>,
+    {
+        self.replace(byte_offset..byte_offset, text);
+    }
+
+    /// Returns the wrapped `Rope`.
+    #[inline]
+    pub fn into_rope(self) -> RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+        self.current
+    }
+
+    /// Marks the `Rope` as clean, starting to track the text edited from now
+    /// on, which can be queried with
+    /// [`dirty_byte_range()`](Self::dirty_byte_range()) and
+    /// [`dirty_lines()`](Self::dirty_lines()).
+    ///
+    /// This is meant to be called e.g. after a UI has redrawn the `Rope`, so
+    /// that the next redraw can be limited to the lines that were edited in
+    /// between. Tracking the edits only costs a constant amount of work per
+    /// edit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Rope, Tracked};
+    /// #
+    /// let mut t = Tracked::new(Rope::from("Hello Earth!"));
+    ///
+    /// t.mark_clean();
+    /// t.insert(11, " 🌎");
+    /// assert_eq!(t.dirty_byte_range(), Some(11..16));
+    ///
+    /// t.mark_clean();
+    /// assert_eq!(t.dirty_byte_range(), None);
+    /// ```
+    #[inline]
+    pub fn mark_clean(&mut self) {
+        self.dirty = Some(DirtyRange::default());
+    }
+
+    /// Creates a new `Tracked` wrapping the given `Rope`.
+    #[inline]
+    pub fn new(rope: RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        Self {
+            current: rope,
+            anchors: Anchors::default(),
+            recording: None,
+            history: History::default(),
+            dirty: None,
+        }
+    }
+
+    /// Removes the given [`Anchor`], returning its byte offset, or `None` if
+    /// it had already been removed or if it wasn't created by this
+    /// `Tracked`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Gravity, Rope, Tracked};
+    /// #
+    /// let mut t = Tracked::new(Rope::from("Hello Earth!"));
+    ///
+    /// let anchor = t.anchor(6, Gravity::Left);
+    ///
+    /// assert_eq!(t.remove_anchor(anchor), Some(6));
+    /// assert_eq!(t.remove_anchor(anchor), None);
+    /// ```
+    #[inline]
+    pub fn remove_anchor(&mut self, anchor: Anchor) -> Option<usize> {
+        self.anchors.remove(anchor)
+    }
+
+    /// Replaces the given byte range with `text`.
+    ///
+    /// See [`Rope::replace()`](super::Rope::replace()) for more.
+    #[track_caller]
+    #[inline]
+    pub fn replace<R, T>(&mut self, byte_range: R, text: T)
+    where
+        R: RangeBounds<usize>,
+        T: AsRef<str>,
+    {
+        let (start, end) =
+            range_bounds_to_start_end(byte_range, 0, self.current.byte_len());
+
+        let text = text.as_ref();
+
+        self.current.replace(start..end, text);
+
+        self.track_edit(start..end, text.len(), [text]);
+    }
+
+    /// Maps a byte offset in the text of the `Rope` as it was at the given
+    /// [`revision()`](Self::revision()) to the corresponding byte offset in
+    /// the current text.
+    ///
+    /// Offsets that have been deleted or at which some text has been
+    /// inserted since then are resolved using the given [`Gravity`], like
+    /// [`Anchor`]s are. This lets a CRDT or any other layer receiving
+    /// positions from the past (e.g. from a remote peer) find out where they
+    /// are now without keeping a parallel data structure.
+    ///
+    /// Returns `None` if the edits made since that revision are not known,
+    /// i.e. if the history isn't being tracked, if it was enabled after that
+    /// revision, if the edits have been dropped with
+    /// [`forget_history()`](Self::forget_history()) or if the revision is in
+    /// the future.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte offset is greater than the byte length of the text
+    /// at that revision.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Gravity, Rope, Tracked};
+    /// #
+    /// let mut t = Tracked::new(Rope::from("Hello Earth!"));
+    ///
+    /// t.track_history();
+    /// let revision = t.revision();
+    ///
+    /// t.insert(0, "Oh, ");
+    /// t.replace(10..15, "Saturn");
+    ///
+    /// assert_eq!(t.rope(), "Oh, Hello Saturn!");
+    /// assert_eq!(t.resolve_offset(revision, 0, Gravity::Left), Some(0));
+    /// assert_eq!(t.resolve_offset(revision, 0, Gravity::Right), Some(4));
+    /// assert_eq!(t.resolve_offset(revision, 11, Gravity::Right), Some(16));
+    /// assert_eq!(t.resolve_offset(revision, 8, Gravity::Left), Some(10));
+    /// assert_eq!(t.resolve_offset(revision, 8, Gravity::Right), Some(16));
+    /// ```
+    #[track_caller]
+    #[inline]
+    pub fn resolve_offset(
+        &self,
+        revision: u64,
+        byte_offset: usize,
+        gravity: Gravity,
+    ) -> Option<usize> {
+        let (offset, len) = self.history.resolve(
+            revision,
+            byte_offset,
+            gravity,
+            self.current.byte_len(),
+        )?;
+
+        if byte_offset > len {
+            panic::byte_offset_out_of_bounds(byte_offset, len);
+        }
+
+        Some(offset)
+    }
+
+    /// Returns the current revision, i.e. the number of edits that have been
+    /// made through the `Tracked` since it was created.
+    ///
+    /// A clone of a `Tracked` starts from the same revision as the original,
+    /// so revisions are only meaningful within the `Tracked` they come from.
+    #[inline]
+    pub fn revision(&self) -> u64 {
+        self.history.revision()
+    }
+
+    /// Returns the wrapped `Rope`.
+    #[inline]
+    pub fn rope(&self) -> &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+        &self.current
+    }
+
+    /// Starts recording the edits made to the `Rope` into a [`Delta`],
+    /// which can be retrieved with
+    /// [`stop_recording()`](Self::stop_recording()).
+    ///
+    /// If the edits were already being recorded, the ones recorded so far
+    /// are discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Rope, Tracked};
+    /// #
+    /// let mut t = Tracked::new(Rope::from("Hello Earth!"));
+    ///
+    /// t.start_recording();
+    /// t.replace(6..11, "Saturn");
+    /// t.insert(0, "Oh, ");
+    ///
+    /// let delta = t.stop_recording().unwrap();
+    ///
+    /// let mut other = Rope::from("Hello Earth!");
+    /// delta.apply(&mut other);
+    /// assert_eq!(other, "Oh, Hello Saturn!");
+    /// ```
+    #[inline]
+    pub fn start_recording(&mut self) {
+        let mut delta = Delta::new();
+        delta.retain(self.current.byte_len());
+        self.recording = Some(delta);
+    }
+
+    /// Stops recording the edits made to the `Rope`, returning the [`Delta`]
+    /// which describes all the edits made since the last call to
+    /// [`start_recording()`](Self::start_recording()), or `None` if they
+    /// weren't being recorded.
+    #[inline]
+    pub fn stop_recording(&mut self) -> Option<Delta> {
+        self.recording.take()
+    }
+
+    /// Starts keeping track of the edits made from the current revision
+    /// onwards, which makes it possible to resolve offsets from those
+    /// revisions with [`resolve_offset()`](Self::resolve_offset()).
+    ///
+    /// Every edit adds a few bytes to the history, which can be trimmed with
+    /// [`forget_history()`](Self::forget_history()). Calling this when the
+    /// history is already being tracked does nothing.
+    #[inline]
+    pub fn track_history(&mut self) {
+        self.history.track();
+    }
+
+    /// Updates the anchors, the history, the dirty range and the recorded
+    /// delta (if any) after the bytes in `replaced` have been replaced by
+    /// `inserted_len` bytes, made of the given chunks of text.
+    #[inline]
+    fn track_edit<'a, I>(
+        &mut self,
+        replaced: Range<usize>,
+        inserted_len: usize,
+        inserted: I,
+    ) where
+        I: IntoIterator<Item = &'a str>,
+    {
+        self.anchors.apply_edit(replaced.clone(), inserted_len);
+        self.history.push(replaced.clone(), inserted_len);
+
+        if let Some(dirty) = &mut self.dirty {
+            dirty.apply_edit(replaced.clone(), inserted_len);
+        }
+
+        if let Some(recording) = &mut self.recording {
+            recording.push_edit(replaced, inserted);
+        }
+    }
+}
@@ -0,0 +1,174 @@
+//! Integration with [tree-sitter](https://docs.rs/tree-sitter), enabled by the
+//! `tree-sitter` feature.
+//!
+//! tree-sitter can read the text it parses and queries in pieces, so both
+//! the `TextProvider` implementations and the parse callbacks hand it the
+//! chunks of the rope directly, without copying them.
+
+use tree_sitter::{Node, TextProvider};
+
+use super::line_breaks::{LfBreaks, LineBreaks};
+use super::metrics::{
+    AllMetrics,
+    ByteMetric,
+    CharWeight,
+    MetricSet,
+    ZeroWeight,
+};
+use super::rope::{CHUNK_MAX_BYTES, CHUNK_MIN_FILL};
+use super::{RopeSlice, RopeWith};
+use crate::tree::{AtomicCounter, RefCounter};
+
+/// Returns the bytes of the `RopeSlice` from the given byte offset to the end
+/// of the chunk containing it, or an empty slice if the offset is at or past
+/// the end of the `RopeSlice`.
+///
+/// The offset doesn't have to be a char boundary.
+#[inline]
+pub(super) fn bytes_from<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    slice: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    byte_offset: usize,
+) -> &[u8] {
+    if byte_offset >= slice.byte_len() {
+        return &[];
+    }
+
+    // We look for the leaf containing the byte *after* the offset to avoid
+    // getting the previous leaf when the offset is at a leaf boundary.
+    let (leaf, ByteMetric(leaf_offset)) =
+        slice.tree_slice.leaf_at_measure(ByteMetric(byte_offset + 1));
+
+    let offset = byte_offset - leaf_offset;
+
+    if offset < leaf.len_left() {
+        &leaf.left_chunk().as_bytes()[offset..]
+    } else {
+        &leaf.right_chunk().as_bytes()[offset - leaf.len_left()..]
+    }
+}
+
+/// An iterator over the chunks of a [`Rope`](super::Rope) or [`RopeSlice`] spanned by a
+/// tree-sitter [`Node`].
+///
+/// This struct is returned by the `TextProvider` implementations of `&Rope`
+/// and `RopeSlice`.
+#[cfg_attr(docsrs, doc(cfg(feature = "tree-sitter")))]
+#[derive(Debug, Clone)]
+pub struct NodeChunks<
+    'a,
+    C: RefCounter = AtomicCounter,
+    W: CharWeight = ZeroWeight,
+    B: LineBreaks = LfBreaks,
+    const MAX_BYTES: usize = CHUNK_MAX_BYTES,
+    const MIN_FILL: usize = CHUNK_MIN_FILL,
+    S: MetricSet = AllMetrics,
+> {
+    slice: RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    start: usize,
+    end: usize,
+}
+
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > NodeChunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    #[inline]
+    fn new(
+        slice: RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>,
+        node: Node<'_>,
+    ) -> Self {
+        let end = node.end_byte().min(slice.byte_len());
+        let start = node.start_byte().min(end);
+        Self { slice, start, end }
+    }
+}
+
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Iterator for NodeChunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type Item = &'a [u8];
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.start == self.end {
+            return None;
+        }
+
+        let bytes = bytes_from(self.slice, self.start);
+        let bytes = &bytes[..bytes.len().min(self.end - self.start)];
+        self.start += bytes.len();
+        Some(bytes)
+    }
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > core::iter::FusedIterator
+    for NodeChunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tree-sitter")))]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > TextProvider<&'a [u8]>
+    for &'a RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type I = NodeChunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>;
+
+    #[inline]
+    fn text(&mut self, node: Node) -> Self::I {
+        NodeChunks::new(self.byte_slice(..), node)
+    }
+}
+
+#[cfg_attr(docsrs, doc(cfg(feature = "tree-sitter")))]
+impl<
+        'a,
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > TextProvider<&'a [u8]>
+    for RopeSlice<'a, C, W, B, MAX_BYTES, MIN_FILL, S>
+{
+    type I = NodeChunks<'a, C, W, B, MAX_BYTES, MIN_FILL, S>;
+
+    #[inline]
+    fn text(&mut self, node: Node) -> Self::I {
+        NodeChunks::new(*self, node)
+    }
+}
@@ -2,6 +2,14 @@
 //! between `Rope`s and `RopeSlice`s, `RopeChunk`s and `ChunkSlice`s.
 
 use super::iterators::Chunks;
+use super::line_breaks::LineBreaks;
+#[cfg(feature = "lines")]
+use super::metrics::Maintained;
+use super::metrics::{count, CharWeight, MetricSet};
+#[cfg(feature = "lines")]
+use super::RopeSlice;
+use super::{RopeBuilder, RopeWith};
+use crate::tree::RefCounter;
 
 /// Adjusts the candidate byte offset to make sure it's a char boundary for
 /// `s`. Offsets past the end of the string will be clipped to the length of
@@ -37,6 +45,57 @@ pub(super) fn adjust_split_point<const WITH_RIGHT_BIAS: bool>(
     offset
 }
 
+/// Like [`adjust_split_point()`], but used when splitting the text of a
+/// chunk between two leaves.
+///
+/// With the `grapheme-chunks` feature the split point is also moved to the
+/// closest grapheme cluster boundary of `s` in the same direction, as long as
+/// that's at most `max_shift` bytes away from the initial candidate.
+#[inline]
+pub(super) fn adjust_chunk_split_point<const WITH_RIGHT_BIAS: bool>(
+    s: &str,
+    candidate: usize,
+    max_shift: usize,
+) -> usize {
+    let offset = adjust_split_point::<WITH_RIGHT_BIAS>(s, candidate);
+
+    #[cfg(feature = "grapheme-chunks")]
+    {
+        use unicode_segmentation::GraphemeCursor;
+
+        if offset == 0 || offset == s.len() {
+            return offset;
+        }
+
+        let mut cursor = GraphemeCursor::new(offset, s.len(), true);
+
+        if cursor.is_boundary(s, 0) == Ok(true) {
+            return offset;
+        }
+
+        let boundary = if WITH_RIGHT_BIAS {
+            cursor.next_boundary(s, 0)
+        } else {
+            cursor.prev_boundary(s, 0)
+        };
+
+        match boundary {
+            Ok(Some(boundary))
+                if boundary.abs_diff(candidate) <= max_shift =>
+            {
+                boundary
+            },
+            _ => offset,
+        }
+    }
+
+    #[cfg(not(feature = "grapheme-chunks"))]
+    {
+        let _ = max_shift;
+        offset
+    }
+}
+
 /// Checks equality between the chunks yielded by iterating over two
 /// [`Chunks`].
 ///
@@ -44,9 +103,16 @@ pub(super) fn adjust_split_point<const WITH_RIGHT_BIAS: bool>(
 /// `RopeSlice`s. It's assumed that if we get this far both chunks yield the
 /// same number of bytes.
 #[inline]
-pub(super) fn chunks_eq_chunks(
-    mut lhs: Chunks<'_>,
-    mut rhs: Chunks<'_>,
+pub(super) fn chunks_eq_chunks<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    mut lhs: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    mut rhs: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
 ) -> bool {
     let mut left_chunk = lhs.next().unwrap_or("").as_bytes();
     let mut right_chunk = rhs.next().unwrap_or("").as_bytes();
@@ -88,6 +154,62 @@ pub(super) fn chunks_eq_chunks(
     }
 }
 
+/// Lexicographically compares the bytes yielded by iterating over two
+/// [`Chunks`], which don't need to yield the same number of bytes.
+///
+/// This is used in the `Ord` and `PartialOrd` implementations of `Rope`s and
+/// `RopeSlice`s.
+#[inline]
+pub(super) fn chunks_cmp_chunks<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    mut lhs: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    mut rhs: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+) -> core::cmp::Ordering {
+    use core::cmp::Ordering;
+
+    let mut left_chunk: &[u8] = &[];
+    let mut right_chunk: &[u8] = &[];
+
+    loop {
+        if left_chunk.is_empty() {
+            if let Some(chunk) = lhs.next() {
+                left_chunk = chunk.as_bytes();
+            }
+        }
+
+        if right_chunk.is_empty() {
+            if let Some(chunk) = rhs.next() {
+                right_chunk = chunk.as_bytes();
+            }
+        }
+
+        // This works because the iterators never yield empty chunks, so if
+        // a chunk is still empty the corresponding iterator is done.
+        match (left_chunk.is_empty(), right_chunk.is_empty()) {
+            (true, true) => return Ordering::Equal,
+            (true, false) => return Ordering::Less,
+            (false, true) => return Ordering::Greater,
+            (false, false) => {},
+        }
+
+        let len = left_chunk.len().min(right_chunk.len());
+
+        match left_chunk[..len].cmp(&right_chunk[..len]) {
+            Ordering::Equal => {
+                left_chunk = &left_chunk[len..];
+                right_chunk = &right_chunk[len..];
+            },
+            ordering => return ordering,
+        }
+    }
+}
+
 /// Checks equality between the chunks yielded by iterating over a [`Chunks`]
 /// and a string slice.
 ///
@@ -95,11 +217,38 @@ pub(super) fn chunks_eq_chunks(
 /// and strings. It's assumed that if we get this far `chunks` and `s` have the
 /// same number of bytes.
 #[inline]
-pub(super) fn chunks_eq_str(chunks: Chunks<'_>, s: &str) -> bool {
-    let s = s.as_bytes();
+pub(super) fn chunks_eq_str<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    s: &str,
+) -> bool {
+    chunks_eq_bytes(chunks, s.as_bytes())
+}
+
+/// Checks equality between the chunks yielded by iterating over [`Chunks`]
+/// and byte slices. It's assumed that if we get this far `chunks` and `bytes`
+/// have the same number of bytes.
+#[inline]
+pub(super) fn chunks_eq_bytes<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    bytes: &[u8],
+) -> bool {
     let mut checked = 0;
     for chunk in chunks {
-        if chunk.as_bytes() != &s[checked..(checked + chunk.len())] {
+        if chunk.as_bytes() != &bytes[checked..(checked + chunk.len())] {
             return false;
         }
         checked += chunk.len();
@@ -107,11 +256,221 @@ pub(super) fn chunks_eq_str(chunks: Chunks<'_>, s: &str) -> bool {
     true
 }
 
+/// Appends the chunks yielded by [`Chunks`] to `buf`, reserving `byte_len`
+/// bytes up front.
+#[inline]
+pub(super) fn collect_chunks_into<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    byte_len: usize,
+    buf: &mut String,
+) {
+    buf.reserve(byte_len);
+
+    for chunk in chunks {
+        buf.push_str(chunk);
+    }
+}
+
+/// Copies the chunks yielded by [`Chunks`] into a new `Vec`, allocating
+/// `byte_len` bytes up front.
+#[inline]
+pub(super) fn chunks_to_vec<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    byte_len: usize,
+) -> Vec<u8> {
+    let mut vec = Vec::with_capacity(byte_len);
+
+    for chunk in chunks {
+        vec.extend_from_slice(chunk.as_bytes());
+    }
+
+    vec
+}
+
+/// Builds a new [`Rope`] with the lowercase equivalent of the text yielded by
+/// [`Chunks`], as given by [`str::to_lowercase()`].
+///
+/// Lowercasing is done one `char` at a time except for the Greek capital
+/// sigma, which maps to a final sigma at the end of a word. Whether it's at
+/// the end of a word can depend on the next chunk, so the text after the last
+/// whitespace of a chunk is held back and lowercased together with the next
+/// one. Whitespace is never part of the context of a sigma, so this gives
+/// the same result as lowercasing the whole text at once.
+#[inline]
+pub(super) fn chunks_to_lowercase<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+) -> RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+    let mut builder =
+        RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+
+    // The text that hasn't been lowercased yet.
+    let mut pending = String::new();
+
+    for chunk in chunks {
+        let split = chunk
+            .char_indices()
+            .rev()
+            .find(|(_, ch)| ch.is_whitespace())
+            .map_or(0, |(idx, ch)| idx + ch.len_utf8());
+
+        let (words, tail) = chunk.split_at(split);
+
+        if !words.is_empty() {
+            pending.push_str(words);
+
+            if pending.is_ascii() {
+                pending.make_ascii_lowercase();
+                builder.append(&pending);
+            } else {
+                builder.append(pending.to_lowercase());
+            }
+
+            pending.clear();
+        }
+
+        pending.push_str(tail);
+    }
+
+    builder.append(pending.to_lowercase());
+
+    builder.build()
+}
+
+/// Builds a new [`Rope`] with the uppercase equivalent of the text yielded by
+/// [`Chunks`], as given by [`str::to_uppercase()`].
+#[inline]
+pub(super) fn chunks_to_uppercase<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+) -> RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+    let mut builder =
+        RopeBuilder::<C, W, B, MAX_BYTES, MIN_FILL, S>::default();
+
+    let mut buf = String::new();
+
+    for chunk in chunks {
+        if chunk.is_ascii() {
+            buf.push_str(chunk);
+            buf.make_ascii_uppercase();
+        } else {
+            buf.extend(chunk.chars().flat_map(char::to_uppercase));
+        }
+
+        builder.append(&buf);
+        buf.clear();
+    }
+
+    builder.build()
+}
+
+/// Counts the words in the concatenation of the chunks, where a word is a
+/// maximal run of non-whitespace `char`s. A word split across two chunks is
+/// only counted once.
+#[inline]
+pub(super) fn count_words<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+) -> usize {
+    let mut words = 0;
+    let mut in_word = false;
+
+    for chunk in chunks {
+        for ch in chunk.chars() {
+            let is_word = !ch.is_whitespace();
+            if is_word && !in_word {
+                words += 1;
+            }
+            in_word = is_word;
+        }
+    }
+
+    words
+}
+
+/// Returns the length in bytes of the indentation of the line, i.e. of the
+/// spaces and tabs it starts with, and its width in columns when tabs
+/// advance to the next multiple of `tab_width`.
+///
+/// Only the chunks up to the first character which isn't a space or a tab
+/// are scanned.
+#[cfg(feature = "lines")]
+#[track_caller]
+#[inline]
+pub(super) fn line_indent<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    line: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    tab_width: usize,
+) -> (usize, usize) {
+    assert!(tab_width > 0, "the tab width must be greater than zero");
+
+    let mut bytes = 0;
+    let mut columns = 0;
+
+    for chunk in line.chunks() {
+        for &byte in chunk.as_bytes() {
+            match byte {
+                b' ' => columns += 1,
+                b'\t' => columns += tab_width - columns % tab_width,
+                _ => return (bytes, columns),
+            }
+            bytes += 1;
+        }
+    }
+
+    (bytes, columns)
+}
+
 /// Iterates over the string slices yielded by [`Chunks`], writing the debug
 /// output of each chunk to a formatter.
 #[inline]
-pub(super) fn debug_chunks(
-    chunks: Chunks<'_>,
+pub(super) fn debug_chunks<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
     f: &mut core::fmt::Formatter<'_>,
 ) -> core::fmt::Result {
     for chunk in chunks {
@@ -121,6 +480,188 @@ pub(super) fn debug_chunks(
     Ok(())
 }
 
+/// Writes the chunks yielded by [`Chunks`] to a formatter, honoring its
+/// width, precision and alignment like `str`'s `Display` implementation does.
+///
+/// The precision is the maximum number of `char`s to write and the width the
+/// minimum, both independently of how the text is split into chunks. If
+/// `char_len` isn't known it's only counted when one of them is set.
+#[inline]
+pub(super) fn display_chunks<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    char_len: Option<usize>,
+    f: &mut core::fmt::Formatter<'_>,
+) -> core::fmt::Result {
+    use core::fmt::{Alignment, Write};
+
+    if f.width().is_none() && f.precision().is_none() {
+        for chunk in chunks {
+            f.write_str(chunk)?;
+        }
+        return Ok(());
+    }
+
+    let char_len = char_len
+        .unwrap_or_else(|| chunks.clone().map(count::chars).sum::<usize>());
+
+    let char_len = f.precision().map_or(char_len, |max| char_len.min(max));
+
+    let padding = f.width().map_or(0, |width| width.saturating_sub(char_len));
+
+    let (pre_padding, post_padding) = match f.align() {
+        Some(Alignment::Right) => (padding, 0),
+        Some(Alignment::Center) => (padding / 2, (padding + 1) / 2),
+        Some(Alignment::Left) | None => (0, padding),
+    };
+
+    let fill = f.fill();
+
+    for _ in 0..pre_padding {
+        f.write_char(fill)?;
+    }
+
+    let mut chars_left = char_len;
+
+    for chunk in chunks {
+        if chars_left == 0 {
+            break;
+        }
+
+        match chunk.char_indices().nth(chars_left) {
+            Some((byte_offset, _)) => {
+                f.write_str(&chunk[..byte_offset])?;
+                chars_left = 0;
+            },
+
+            None => {
+                f.write_str(chunk)?;
+                chars_left -= chunk.chars().count();
+            },
+        }
+    }
+
+    for _ in 0..post_padding {
+        f.write_char(fill)?;
+    }
+
+    Ok(())
+}
+
+/// The maximum number of lines written by [`debug_lines()`]. Longer texts
+/// only have their first and last `DEBUG_MAX_LINES / 2` lines written.
+#[cfg(feature = "lines")]
+const DEBUG_MAX_LINES: usize = 40;
+
+/// Writes the lines of the slice to a formatter, one per row and prefixed by
+/// their line number (starting from 1), enclosed in `name { .. }`.
+///
+/// This is used in the alternate mode (i.e. `{:#?}`) of the `Debug`
+/// implementations of `Rope`s and `RopeSlice`s.
+#[cfg(feature = "lines")]
+#[inline]
+pub(super) fn debug_lines<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet<Lines = Maintained>,
+>(
+    name: &str,
+    slice: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    f: &mut core::fmt::Formatter<'_>,
+) -> core::fmt::Result {
+    let line_len = slice.line_len();
+
+    let (head, tail) = if line_len > DEBUG_MAX_LINES {
+        (DEBUG_MAX_LINES / 2, DEBUG_MAX_LINES / 2)
+    } else {
+        (line_len, 0)
+    };
+
+    let mut width = 1;
+    while line_len >= 10usize.pow(width as u32) {
+        width += 1;
+    }
+
+    if tail > 0 {
+        // Leave room for the "..." row.
+        width = width.max(3);
+    }
+
+    writeln!(f, "{name} {{")?;
+
+    for line_idx in 0..head {
+        debug_line(slice.line(line_idx), line_idx, width, f)?;
+    }
+
+    if tail > 0 {
+        writeln!(
+            f,
+            "    {:>width$} | ({} more lines)",
+            "...",
+            line_len - head - tail
+        )?;
+
+        for line_idx in line_len - tail..line_len {
+            debug_line(slice.line(line_idx), line_idx, width, f)?;
+        }
+    }
+
+    f.write_str("}")
+}
+
+/// Writes a single row of [`debug_lines()`], escaping control characters
+/// like tabs and lone carriage returns.
+#[cfg(feature = "lines")]
+#[inline]
+fn debug_line<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    line: RopeSlice<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    line_idx: usize,
+    width: usize,
+    f: &mut core::fmt::Formatter<'_>,
+) -> core::fmt::Result {
+    use core::fmt::Write;
+
+    write!(f, "    {:>width$} |", line_idx + 1)?;
+
+    if !line.is_empty() {
+        f.write_char(' ')?;
+    }
+
+    for chunk in line.chunks() {
+        let mut written = 0;
+
+        for (idx, char) in chunk.char_indices() {
+            if char.is_control() {
+                f.write_str(&chunk[written..idx])?;
+                for c in char.escape_debug() {
+                    f.write_char(c)?;
+                }
+                written = idx + char.len_utf8();
+            }
+        }
+
+        f.write_str(&chunk[written..])?;
+    }
+
+    f.write_char('\n')
+}
+
 /// Writes the `Debug` output of the given string to the formatter without
 /// enclosing it in double quotes.
 pub(super) fn debug_no_quotes(
@@ -145,12 +686,66 @@ pub(super) fn debug_no_quotes(
     f.write_str(&s[written..])
 }
 
+/// Feeds the bytes yielded by the chunks to the hasher in blocks of fixed
+/// size.
+///
+/// The hasher doesn't see the chunk boundaries, so two `Rope`s or
+/// `RopeSlice`s with the same contents always produce the same hash, no
+/// matter how their text is split into chunks.
+#[inline]
+pub(super) fn hash_chunks<
+    H: core::hash::Hasher,
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
+    state: &mut H,
+) {
+    const BLOCK_BYTES: usize = 256;
+
+    let mut block = [0u8; BLOCK_BYTES];
+    let mut block_len = 0;
+
+    for chunk in chunks {
+        let mut bytes = chunk.as_bytes();
+
+        while !bytes.is_empty() {
+            let len = bytes.len().min(BLOCK_BYTES - block_len);
+            block[block_len..block_len + len].copy_from_slice(&bytes[..len]);
+            block_len += len;
+            bytes = &bytes[len..];
+
+            if block_len == BLOCK_BYTES {
+                state.write(&block);
+                block_len = 0;
+            }
+        }
+    }
+
+    state.write(&block[..block_len]);
+
+    // Same as `str`'s `Hash` implementation, this makes sure that
+    // `(a, b)` and `(a + b, "")` hash differently.
+    state.write_u8(0xff);
+}
+
 /// Returns whether `byte_offset` is a grapheme boundary in the string
 /// constructed by concatenating the chunks yielded by `chunks`.
 #[cfg(feature = "graphemes")]
 #[inline]
-pub(super) fn is_grapheme_boundary(
-    mut chunks: Chunks<'_>,
+pub(super) fn is_grapheme_boundary<
+    C: RefCounter,
+    W: CharWeight,
+    B: LineBreaks,
+    const MAX_BYTES: usize,
+    const MIN_FILL: usize,
+    S: MetricSet,
+>(
+    mut chunks: Chunks<'_, C, W, B, MAX_BYTES, MIN_FILL, S>,
     byte_len: usize,
     byte_offset: usize,
 ) -> bool {
@@ -207,6 +802,17 @@ pub(super) fn split_adjusted<const WITH_RIGHT_BIAS: bool>(
     (&s[..split_point], &s[split_point..])
 }
 
+#[inline]
+pub(super) fn split_chunk_adjusted<const WITH_RIGHT_BIAS: bool>(
+    s: &str,
+    candidate: usize,
+    max_shift: usize,
+) -> (&str, &str) {
+    let split_point =
+        adjust_chunk_split_point::<WITH_RIGHT_BIAS>(s, candidate, max_shift);
+    (&s[..split_point], &s[split_point..])
+}
+
 pub mod panic_messages {
     #[track_caller]
     #[cold]
@@ -284,6 +890,81 @@ pub mod panic_messages {
         );
     }
 
+    #[cfg(feature = "char-metric")]
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn char_index_out_of_bounds(
+        char_index: usize,
+        char_len: usize,
+    ) -> ! {
+        debug_assert!(char_index >= char_len);
+
+        panic!(
+            "char index out of bounds: the index is {char_index} but the \
+             length is {char_len}"
+        );
+    }
+
+    #[cfg(all(feature = "char-metric", feature = "lines"))]
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn char_offset_out_of_bounds(
+        char_offset: usize,
+        char_len: usize,
+    ) -> ! {
+        debug_assert!(char_offset > char_len);
+
+        panic!(
+            "char offset out of bounds: the offset is {char_offset} but the \
+             length is {char_len}"
+        );
+    }
+
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn edits_not_disjoint(byte_start: usize, prev_end: usize) -> ! {
+        debug_assert!(byte_start < prev_end);
+
+        panic!(
+            "edits are not sorted and disjoint: an edit starts at \
+             {byte_start} but the previous one ends at {prev_end}"
+        );
+    }
+
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn gap_buffer_overflow(new_len: usize, capacity: usize) -> ! {
+        debug_assert!(new_len > capacity);
+
+        panic!(
+            "gap buffer overflow: the buffer can hold {capacity} bytes but \
+             the edit would make it {new_len} bytes long"
+        );
+    }
+
+    #[cfg(feature = "validate-utf8")]
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn invalid_rope(
+        violations: &[crate::tree::InvariantViolation],
+    ) -> ! {
+        debug_assert!(!violations.is_empty());
+
+        let violations = violations
+            .iter()
+            .map(ToString::to_string)
+            .collect::<Vec<_>>()
+            .join("; ");
+
+        panic!("the Rope is invalid after the last edit: {violations}");
+    }
+
+    #[cfg(feature = "lines")]
     #[track_caller]
     #[cold]
     #[inline(never)]
@@ -299,6 +980,7 @@ pub mod panic_messages {
         );
     }
 
+    #[cfg(feature = "lines")]
     #[track_caller]
     #[cold]
     #[inline(never)]
@@ -314,6 +996,7 @@ pub mod panic_messages {
         );
     }
 
+    #[cfg(feature = "lines")]
     #[track_caller]
     #[cold]
     #[inline(never)]
@@ -329,6 +1012,115 @@ pub mod panic_messages {
         );
     }
 
+    #[cfg(feature = "paragraph-count")]
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn paragraph_offset_out_of_bounds(
+        paragraph_offset: usize,
+        paragraph_len: usize,
+    ) -> ! {
+        debug_assert!(paragraph_offset > paragraph_len);
+
+        panic!(
+            "paragraph offset out of bounds: the offset is \
+             {paragraph_offset} but the length is {paragraph_len}"
+        );
+    }
+
+    #[cfg(feature = "paragraph-count")]
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn paragraph_start_after_end(
+        paragraph_start: usize,
+        paragraph_end: usize,
+    ) -> ! {
+        debug_assert!(paragraph_start > paragraph_end);
+
+        panic!(
+            "paragraph start after end: the start is {paragraph_start} but \
+             the end is {paragraph_end}"
+        );
+    }
+
+    #[cfg(feature = "grapheme-count")]
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn grapheme_offset_out_of_bounds(
+        grapheme_offset: usize,
+        grapheme_len: usize,
+    ) -> ! {
+        debug_assert!(grapheme_offset > grapheme_len);
+
+        panic!(
+            "grapheme offset out of bounds: the offset is {grapheme_offset} \
+             but the length is {grapheme_len}"
+        );
+    }
+
+    #[cfg(feature = "grapheme-count")]
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn grapheme_start_after_end(
+        grapheme_start: usize,
+        grapheme_end: usize,
+    ) -> ! {
+        debug_assert!(grapheme_start > grapheme_end);
+
+        panic!(
+            "grapheme start after end: the start is {grapheme_start} but the \
+             end is {grapheme_end}"
+        );
+    }
+
+    #[cfg(feature = "width-metric")]
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn column_out_of_bounds(column: usize, width: usize) -> ! {
+        debug_assert!(column > width);
+
+        panic!(
+            "column out of bounds: the column is {column} but the width is \
+             {width}"
+        );
+    }
+
+    #[cfg(feature = "custom-metric")]
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn custom_offset_out_of_bounds(
+        custom_offset: usize,
+        custom_len: usize,
+    ) -> ! {
+        debug_assert!(custom_offset > custom_len);
+
+        panic!(
+            "custom offset out of bounds: the offset is {custom_offset} but \
+             the length is {custom_len}"
+        );
+    }
+
+    #[cfg(feature = "custom-metric")]
+    #[track_caller]
+    #[cold]
+    #[inline(never)]
+    pub(crate) fn custom_start_after_end(
+        custom_start: usize,
+        custom_end: usize,
+    ) -> ! {
+        debug_assert!(custom_start > custom_end);
+
+        panic!(
+            "custom offset start after end: the start is {custom_start} but \
+             the end is {custom_end}"
+        );
+    }
+
     #[cfg(feature = "utf16-metric")]
     #[track_caller]
     #[cold]
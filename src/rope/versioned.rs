@@ -0,0 +1,274 @@
+use alloc::collections::VecDeque;
+use core::ops::{Range, RangeBounds};
+
+use super::diff::{common_prefix, common_suffix};
+use super::line_breaks::LineBreaks;
+use super::metrics::{CharWeight, MetricSet};
+use super::{RopeWith, Tracked};
+use crate::tree::RefCounter;
+
+/// The number of snapshots kept by [`Versioned::new()`].
+const DEFAULT_MAX_SNAPSHOTS: usize = 64;
+
+/// A wrapper around a [`Tracked`] rope which keeps snapshots of its past
+/// revisions.
+///
+/// The revisions are the ones counted by the `Tracked`, so they can also be
+/// used to resolve offsets with
+/// [`Tracked::resolve_offset()`](super::Tracked::resolve_offset()). Every
+/// edit made through a `Versioned` retains a snapshot of the text before it.
+/// Since cloning a `Rope` is cheap and the clones share all the chunks that
+/// aren't edited, a snapshot only costs the memory of the parts of the text
+/// that have changed since.
+///
+/// Only the most recent snapshots are kept, up to a maximum configured with
+/// [`with_max_snapshots()`](Self::with_max_snapshots()).
+///
+/// # Examples
+///
+/// ```
+/// # use crop::{Rope, Versioned};
+/// #
+/// let mut v = Versioned::new(Rope::from("Hello Earth!"));
+///
+/// let first = v.revision();
+/// v.replace(6..11, "Saturn");
+/// let second = v.revision();
+/// v.insert(0, "Oh, ");
+///
+/// assert_eq!(v.rope(), "Oh, Hello Saturn!");
+/// assert_eq!(v.at_revision(first).unwrap(), "Hello Earth!");
+/// assert_eq!(v.at_revision(second).unwrap(), "Hello Saturn!");
+///
+/// // The bytes of the current text which differ from the first revision.
+/// assert_eq!(v.changed_bytes(first, v.revision()), Some(0..16));
+/// ```
+#[derive(Clone, Debug)]
+pub struct Versioned<T> {
+    tracked: Tracked<T>,
+
+    /// The snapshots of the past revisions, from oldest to newest, together
+    /// with their revision.
+    snapshots: VecDeque<(u64, T)>,
+
+    max_snapshots: usize,
+}
+
+impl<
+        C: RefCounter,
+        W: CharWeight,
+        B: LineBreaks,
+        const MAX_BYTES: usize,
+        const MIN_FILL: usize,
+        S: MetricSet,
+    > Versioned<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>
+{
+    /// Returns the text of the `Rope` as it was at the given revision, or
+    /// `None` if no snapshot of that revision is retained.
+    #[inline]
+    pub fn at_revision(
+        &self,
+        revision: u64,
+    ) -> Option<&RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> {
+        if revision == self.revision() {
+            return Some(self.tracked.rope());
+        }
+
+        let idx = self
+            .snapshots
+            .binary_search_by_key(&revision, |(revision, _)| *revision)
+            .ok()?;
+
+        Some(&self.snapshots[idx].1)
+    }
+
+    /// Returns the byte range of the text at revision `to` which differs from
+    /// the text at revision `from`, or `None` if either revision is not
+    /// retained.
+    ///
+    /// Everything before the start and after the end of the returned range
+    /// is the same in the two revisions, and the range is empty if their
+    /// text is the same. The subtrees and chunks the two
+    /// snapshots share are skipped without being compared, so this is fast
+    /// even for large texts when the edits between the two revisions are
+    /// small.
+    #[inline]
+    pub fn changed_bytes(&self, from: u64, to: u64) -> Option<Range<usize>> {
+        let old = self.at_revision(from)?;
+        let new = self.at_revision(to)?;
+
+        let prefix = common_prefix(old, new);
+        let suffix = common_suffix(old, new, prefix);
+
+        Some(prefix..new.byte_len() - suffix)
+    }
+
+    /// Deletes the given byte range, returning the new revision.
+    ///
+    /// See [`Rope::delete()`](super::Rope::delete()) for more.
+    #[track_caller]
+    #[inline]
+    pub fn delete<R>(&mut self, byte_range: R) -> u64
+    where
+        R: RangeBounds<usize>,
+    {
+        self.snapshot_edit(|tracked| tracked.delete(byte_range))
+    }
+
+    /// Edits the `Rope` with the given closure, returning the new revision.
+    ///
+    /// The edits made by the closure are tracked like the ones made with
+    /// [`Tracked::edit()`](super::Tracked::edit()), so they can span more
+    /// than one revision. Only a snapshot of the text before them is
+    /// retained, and only if the closure changes the text.
+    #[inline]
+    pub fn edit<F>(&mut self, edit: F) -> u64
+    where
+        F: FnOnce(&mut RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>),
+    {
+        self.snapshot_edit(|tracked| tracked.edit(edit))
+    }
+
+    /// Creates a new `Versioned` wrapping the given [`Tracked`] rope, which
+    /// keeps the snapshots of at most `max_snapshots` past revisions.
+    ///
+    /// The anchors, the history and the recorded [`Delta`](super::Delta) of
+    /// the `Tracked` keep being updated by the edits made through the
+    /// `Versioned`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use crop::{Gravity, Rope, Tracked, Versioned};
+    /// #
+    /// let mut t = Tracked::new(Rope::from("Hello Earth!"));
+    /// t.track_history();
+    /// let earth = t.anchor(6, Gravity::Right);
+    ///
+    /// let mut v = Versioned::from_tracked(t, 8);
+    /// let first = v.revision();
+    /// v.insert(0, "Oh, ");
+    ///
+    /// assert_eq!(v.at_revision(first).unwrap(), "Hello Earth!");
+    /// assert_eq!(v.tracked().anchor_offset(earth), Some(10));
+    ///
+    /// let offset = v.tracked().resolve_offset(first, 6, Gravity::Right);
+    /// assert_eq!(offset, Some(10));
+    /// ```
+    #[inline]
+    pub fn from_tracked(
+        tracked: Tracked<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>,
+        max_snapshots: usize,
+    ) -> Self {
+        Self { tracked, snapshots: VecDeque::new(), max_snapshots }
+    }
+
+    /// Inserts `text` at the given byte offset, returning the new revision.
+    ///
+    /// See [`Rope::insert()`](super::Rope::insert()) for more.
+    #[track_caller]
+    #[inline]
+    pub fn insert<T>(&mut self, byte_offset: usize, text: T) -> u64
+    where
+        T: AsRef<str>,
+    {
+        self.snapshot_edit(|tracked| tracked.insert(byte_offset, text))
+    }
+
+    /// Returns the wrapped `Rope`.
+    #[inline]
+    pub fn into_rope(self) -> RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+        self.tracked.into_rope()
+    }
+
+    /// Returns the wrapped [`Tracked`] rope.
+    #[inline]
+    pub fn into_tracked(
+        self,
+    ) -> Tracked<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> {
+        self.tracked
+    }
+
+    /// Creates a new `Versioned` wrapping the given `Rope` in a new
+    /// [`Tracked`], which keeps the snapshots of the last 64 revisions.
+    #[inline]
+    pub fn new(rope: RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>) -> Self {
+        Self::with_max_snapshots(rope, DEFAULT_MAX_SNAPSHOTS)
+    }
+
+    /// Replaces the given byte range with `text`, returning the new revision.
+    ///
+    /// See [`Rope::replace()`](super::Rope::replace()) for more.
+    #[track_caller]
+    #[inline]
+    pub fn replace<R, T>(&mut self, byte_range: R, text: T) -> u64
+    where
+        R: RangeBounds<usize>,
+        T: AsRef<str>,
+    {
+        self.snapshot_edit(|tracked| tracked.replace(byte_range, text))
+    }
+
+    /// Returns the current revision of the wrapped [`Tracked`] rope.
+    ///
+    /// See [`Tracked::revision()`](super::Tracked::revision()) for more.
+    #[inline]
+    pub fn revision(&self) -> u64 {
+        self.tracked.revision()
+    }
+
+    /// Returns the wrapped `Rope`.
+    #[inline]
+    pub fn rope(&self) -> &RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S> {
+        self.tracked.rope()
+    }
+
+    /// Runs the given edit on the wrapped [`Tracked`] rope, retaining a
+    /// snapshot of the text before it if it made any, and returns the new
+    /// revision.
+    #[inline]
+    fn snapshot_edit<F>(&mut self, edit: F) -> u64
+    where
+        F: FnOnce(&mut Tracked<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>>),
+    {
+        let revision = self.revision();
+
+        let snapshot = self.tracked.rope().clone();
+
+        edit(&mut self.tracked);
+
+        if self.revision() == revision {
+            return revision;
+        }
+
+        if self.max_snapshots > 0 {
+            if self.snapshots.len() == self.max_snapshots {
+                self.snapshots.pop_front();
+            }
+
+            self.snapshots.push_back((revision, snapshot));
+        }
+
+        self.revision()
+    }
+
+    /// Returns the wrapped [`Tracked`] rope, e.g. to create
+    /// [`Anchor`](super::Anchor)s or to resolve offsets from past revisions.
+    #[inline]
+    pub fn tracked(
+        &self,
+    ) -> &Tracked<RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>> {
+        &self.tracked
+    }
+
+    /// Creates a new `Versioned` wrapping the given `Rope` in a new
+    /// [`Tracked`], which keeps the snapshots of at most `max_snapshots` past
+    /// revisions.
+    #[inline]
+    pub fn with_max_snapshots(
+        rope: RopeWith<C, W, B, MAX_BYTES, MIN_FILL, S>,
+        max_snapshots: usize,
+    ) -> Self {
+        Self::from_tracked(Tracked::new(rope), max_snapshots)
+    }
+}
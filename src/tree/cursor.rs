@@ -0,0 +1,268 @@
+use super::{
+    AtomicCounter,
+    Inode,
+    Leaf,
+    Lnode,
+    Metric,
+    Node,
+    RefCounter,
+    Tree,
+};
+
+/// A cursor over the leaves of a [`Tree`] which remembers the path from the
+/// root to the leaf it's on.
+///
+/// Moving to an adjacent leaf or seeking to an offset close to the current
+/// one only walks up the path as far as needed before walking back down, so
+/// it takes amortized constant time instead of starting from the root every
+/// time.
+pub struct Cursor<
+    'a,
+    const ARITY: usize,
+    L: Leaf,
+    C: RefCounter = AtomicCounter,
+> {
+    /// The inodes on the path from the root down to the current leaf, each
+    /// with the index of the child the path goes through and the summary of
+    /// all the leaves before the inode. It's empty if the root is a leaf.
+    path: Vec<(&'a Inode<ARITY, L, C>, usize, L::Summary)>,
+
+    /// The leaf the cursor is on.
+    leaf: &'a Lnode<L>,
+
+    /// The summary of all the leaves before [`leaf`](Self::leaf).
+    offset: L::Summary,
+}
+
+impl<const ARITY: usize, L: Leaf, C: RefCounter> Clone
+    for Cursor<'_, ARITY, L, C>
+{
+    #[inline]
+    fn clone(&self) -> Self {
+        Self {
+            path: self.path.clone(),
+            leaf: self.leaf,
+            offset: self.offset.clone(),
+        }
+    }
+}
+
+impl<'a, const ARITY: usize, L: Leaf, C: RefCounter>
+    From<&'a Tree<ARITY, L, C>> for Cursor<'a, ARITY, L, C>
+{
+    #[inline]
+    fn from(tree: &'a Tree<ARITY, L, C>) -> Cursor<'a, ARITY, L, C> {
+        let mut path = Vec::with_capacity(tree.depth());
+
+        let mut node = &**tree.root();
+
+        let leaf = loop {
+            match node {
+                Node::Internal(inode) => {
+                    path.push((&**inode, 0, L::Summary::default()));
+                    node = &**inode.first();
+                },
+                Node::Leaf(leaf) => break leaf,
+            }
+        };
+
+        Self { path, leaf, offset: L::Summary::default() }
+    }
+}
+
+impl<'a, const ARITY: usize, L: Leaf, C: RefCounter> Cursor<'a, ARITY, L, C> {
+    /// Returns `true` if the subtree starting after the leaves summarized by
+    /// `offset` and summarized by `summary` is the one a search from the
+    /// root for the `measure`-th unit of the `M`-metric would end up in.
+    #[inline]
+    fn contains<M>(
+        offset: &L::Summary,
+        summary: &L::Summary,
+        measure: M,
+    ) -> bool
+    where
+        M: Metric<L::Summary>,
+    {
+        let start = M::measure(offset);
+
+        let end = start + M::measure(summary);
+
+        measure <= end
+            && (start < measure
+                || L::BaseMetric::measure(offset) == L::BaseMetric::zero())
+    }
+
+    /// Walks down from `inode` to the leaf containing the `measure`-th unit
+    /// of the `M`-metric, pushing the inodes it goes through on the path.
+    #[inline]
+    fn descend<M>(
+        &mut self,
+        mut inode: &'a Inode<ARITY, L, C>,
+        mut offset: L::Summary,
+        measure: M,
+    ) where
+        M: Metric<L::Summary>,
+    {
+        loop {
+            let (child_idx, _) =
+                inode.child_at_measure(measure - M::measure(&offset));
+
+            let mut child_offset = offset.clone();
+
+            for summary in &inode.child_summaries()[..child_idx] {
+                child_offset += summary;
+            }
+
+            self.path.push((inode, child_idx, offset));
+
+            offset = child_offset;
+
+            match &**inode.child(child_idx) {
+                Node::Internal(child) => inode = child,
+
+                Node::Leaf(leaf) => {
+                    self.leaf = leaf;
+                    self.offset = offset;
+                    return;
+                },
+            }
+        }
+    }
+
+    /// Returns the leaf the cursor is on.
+    #[inline]
+    pub fn leaf(&self) -> L::Slice<'a> {
+        self.leaf.as_slice()
+    }
+
+    /// Returns the summary of the leaf the cursor is on.
+    #[inline]
+    pub fn leaf_summary(&self) -> &'a L::Summary {
+        self.leaf.summary()
+    }
+
+    /// Moves the cursor to the next leaf and returns it, or returns `None`
+    /// and stays put if the cursor is on the last leaf.
+    #[inline]
+    pub fn next_leaf(&mut self) -> Option<L::Slice<'a>> {
+        let depth = self
+            .path
+            .iter()
+            .rposition(|&(inode, child_idx, _)| child_idx + 1 < inode.len())?;
+
+        self.path.truncate(depth + 1);
+
+        self.offset += self.leaf.summary();
+
+        let (inode, child_idx, _) = self.path.last_mut().unwrap();
+
+        *child_idx += 1;
+
+        let mut node = &**inode.child(*child_idx);
+
+        loop {
+            match node {
+                Node::Internal(inode) => {
+                    self.path.push((inode, 0, self.offset.clone()));
+                    node = &**inode.first();
+                },
+
+                Node::Leaf(leaf) => {
+                    self.leaf = leaf;
+                    return Some(leaf.as_slice());
+                },
+            }
+        }
+    }
+
+    /// Returns the `M`-measure of all the leaves before the one the cursor
+    /// is on.
+    #[inline]
+    pub fn offset<M>(&self) -> M
+    where
+        M: Metric<L::Summary>,
+    {
+        M::measure(&self.offset)
+    }
+
+    /// Moves the cursor to the previous leaf and returns it, or returns
+    /// `None` and stays put if the cursor is on the first leaf.
+    #[inline]
+    pub fn prev_leaf(&mut self) -> Option<L::Slice<'a>> {
+        let depth =
+            self.path.iter().rposition(|&(_, child_idx, _)| child_idx > 0)?;
+
+        self.path.truncate(depth + 1);
+
+        let (inode, child_idx, _) = self.path.last_mut().unwrap();
+
+        *child_idx -= 1;
+
+        // The current leaf is the first one of the child after the new one,
+        // so the new child starts right before it.
+        let mut start =
+            self.offset.clone() - &inode.child_summaries()[*child_idx];
+
+        let mut node = &**inode.child(*child_idx);
+
+        loop {
+            match node {
+                Node::Internal(inode) => {
+                    let last_idx = inode.len() - 1;
+
+                    let last_start = start.clone() + inode.summary()
+                        - &inode.child_summaries()[last_idx];
+
+                    self.path.push((inode, last_idx, start));
+
+                    start = last_start;
+
+                    node = &**inode.last();
+                },
+
+                Node::Leaf(leaf) => {
+                    self.leaf = leaf;
+                    self.offset = start;
+                    return Some(leaf.as_slice());
+                },
+            }
+        }
+    }
+
+    /// Moves the cursor to the leaf containing the `measure`-th unit of the
+    /// `M`-metric, i.e. the same leaf returned by
+    /// [`Tree::leaf_at_measure()`], and returns it.
+    ///
+    /// If the target leaf is close to the current one this only walks up
+    /// the cached path to the first inode containing both of them.
+    #[track_caller]
+    #[inline]
+    pub fn seek<M>(&mut self, measure: M) -> L::Slice<'a>
+    where
+        M: Metric<L::Summary>,
+    {
+        if Self::contains(&self.offset, self.leaf.summary(), measure) {
+            return self.leaf.as_slice();
+        }
+
+        // The root contains every measure, so we never pop it. If the root
+        // was a leaf the check above would've already returned.
+        while self.path.len() > 1 {
+            let (inode, _, offset) = self.path.last().unwrap();
+
+            if Self::contains(offset, inode.summary(), measure) {
+                break;
+            }
+
+            self.path.pop();
+        }
+
+        let (inode, _, offset) = self.path.pop().unwrap();
+
+        debug_assert!(measure <= M::measure(&offset) + inode.measure::<M>());
+
+        self.descend(inode, offset, measure);
+
+        self.leaf.as_slice()
+    }
+}
@@ -0,0 +1,175 @@
+use core::ops::{Deref, DerefMut, Range};
+
+/// The summaries of the children of an [`Inode`](super::Inode), stored in a
+/// fixed-capacity array inside the inode itself.
+///
+/// Keeping the summaries inline means that picking the child to descend into
+/// is a linear scan of contiguous memory, without an extra pointer to chase
+/// before reading the first of them. The slots past [`len()`](Self::len())
+/// always hold the default summary.
+#[derive(Clone)]
+pub(super) struct InlineSummaries<S, const N: usize> {
+    summaries: [S; N],
+    len: usize,
+}
+
+impl<S: Default, const N: usize> Default for InlineSummaries<S, N> {
+    #[inline]
+    fn default() -> Self {
+        Self { summaries: core::array::from_fn(|_| S::default()), len: 0 }
+    }
+}
+
+impl<S: core::fmt::Debug, const N: usize> core::fmt::Debug
+    for InlineSummaries<S, N>
+{
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<S, const N: usize> Deref for InlineSummaries<S, N> {
+    type Target = [S];
+
+    #[inline]
+    fn deref(&self) -> &[S] {
+        &self.summaries[..self.len]
+    }
+}
+
+impl<S, const N: usize> DerefMut for InlineSummaries<S, N> {
+    #[inline]
+    fn deref_mut(&mut self) -> &mut [S] {
+        &mut self.summaries[..self.len]
+    }
+}
+
+impl<S: Default, const N: usize> InlineSummaries<S, N> {
+    /// Moves all the summaries of `other` at the end of `self`, leaving
+    /// `other` empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the two together hold more than `N` summaries.
+    #[inline]
+    pub(super) fn append(&mut self, other: &mut Self) {
+        assert!(self.len + other.len <= N);
+
+        for summary in &mut other.summaries[..other.len] {
+            self.summaries[self.len] = core::mem::take(summary);
+            self.len += 1;
+        }
+
+        other.len = 0;
+    }
+
+    /// Removes the summaries in the given range, shifting the ones after it
+    /// to the left.
+    #[inline]
+    pub(super) fn drain(&mut self, range: Range<usize>) {
+        assert!(range.start <= range.end && range.end <= self.len);
+
+        let removed = range.len();
+
+        self.summaries[range.start..self.len].rotate_left(removed);
+
+        for summary in &mut self.summaries[self.len - removed..self.len] {
+            *summary = S::default();
+        }
+
+        self.len -= removed;
+    }
+
+    /// Inserts a summary at the given index, shifting the ones after it to
+    /// the right.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the array is full or if the index is greater than
+    /// [`len()`](Self::len()).
+    #[inline]
+    pub(super) fn insert(&mut self, idx: usize, summary: S) {
+        assert!(idx <= self.len && self.len < N);
+        self.summaries[self.len] = summary;
+        self.summaries[idx..=self.len].rotate_right(1);
+        self.len += 1;
+    }
+
+    /// # Panics
+    ///
+    /// Panics if the array is full.
+    #[inline]
+    pub(super) fn push(&mut self, summary: S) {
+        assert!(self.len < N);
+        self.summaries[self.len] = summary;
+        self.len += 1;
+    }
+
+    /// Removes the summary at the given index, shifting the ones after it to
+    /// the left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is out of bounds.
+    #[inline]
+    pub(super) fn remove(&mut self, idx: usize) -> S {
+        assert!(idx < self.len);
+        self.summaries[idx..self.len].rotate_left(1);
+        self.len -= 1;
+        core::mem::take(&mut self.summaries[self.len])
+    }
+}
+
+impl<S: Default, const N: usize> FromIterator<S> for InlineSummaries<S, N> {
+    #[inline]
+    fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+        let mut summaries = Self::default();
+
+        for summary in iter {
+            summaries.push(summary);
+        }
+
+        summaries
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edits() {
+        let mut s = (1..=3).collect::<InlineSummaries<usize, 6>>();
+        assert_eq!(*s, [1, 2, 3]);
+
+        s.insert(0, 0);
+        s.insert(4, 4);
+        s.insert(2, 9);
+        assert_eq!(*s, [0, 1, 9, 2, 3, 4]);
+
+        assert_eq!(s.remove(2), 9);
+        assert_eq!(*s, [0, 1, 2, 3, 4]);
+
+        s.drain(1..3);
+        assert_eq!(*s, [0, 3, 4]);
+
+        let mut other = [5, 6].into_iter().collect::<InlineSummaries<_, 6>>();
+        s.append(&mut other);
+        assert_eq!(*s, [0, 3, 4, 5, 6]);
+        assert!(other.is_empty());
+
+        s.drain(0..5);
+        assert!(s.is_empty());
+
+        // The slots past the length are reset.
+        assert_eq!(s.summaries, [0; 6]);
+    }
+
+    #[test]
+    #[should_panic]
+    fn push_full() {
+        let mut s = (0..4).collect::<InlineSummaries<usize, 4>>();
+        s.push(4);
+    }
+}
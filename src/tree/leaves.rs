@@ -1,7 +1,22 @@
-use super::{Arc, Inode, Leaf, Metric, Node, Tree, TreeSlice};
+use super::{
+    Arc,
+    AtomicCounter,
+    Inode,
+    Leaf,
+    Metric,
+    Node,
+    RefCounter,
+    Tree,
+    TreeSlice,
+};
 
 /// An iterator over the leaves of `Tree`s and `TreeSlice`s.
-pub struct Leaves<'a, const ARITY: usize, L: Leaf> {
+pub struct Leaves<
+    'a,
+    const ARITY: usize,
+    L: Leaf,
+    C: RefCounter = AtomicCounter,
+> {
     /*
       This iterator is implemented using two independent iterators advancing in
       opposite directions.
@@ -9,10 +24,10 @@ pub struct Leaves<'a, const ARITY: usize, L: Leaf> {
     #[rustfmt::skip]
 
     /// Iterates over the leaves from front to back.
-    forward: LeavesForward<'a, ARITY, L>,
+    forward: LeavesForward<'a, ARITY, L, C>,
 
     /// Iterates over the leaves from back to front.
-    backward: LeavesBackward<'a, ARITY, L>,
+    backward: LeavesBackward<'a, ARITY, L, C>,
 
     /// The number of leaves that have been yielded so far.
     leaves_yielded: usize,
@@ -21,7 +36,9 @@ pub struct Leaves<'a, const ARITY: usize, L: Leaf> {
     leaves_total: usize,
 }
 
-impl<const ARITY: usize, L: Leaf> Clone for Leaves<'_, ARITY, L> {
+impl<const ARITY: usize, L: Leaf, C: RefCounter> Clone
+    for Leaves<'_, ARITY, L, C>
+{
     #[inline]
     fn clone(&self) -> Self {
         Self {
@@ -32,11 +49,11 @@ impl<const ARITY: usize, L: Leaf> Clone for Leaves<'_, ARITY, L> {
     }
 }
 
-impl<'a, const ARITY: usize, L: Leaf> From<&'a Tree<ARITY, L>>
-    for Leaves<'a, ARITY, L>
+impl<'a, const ARITY: usize, L: Leaf, C: RefCounter>
+    From<&'a Tree<ARITY, L, C>> for Leaves<'a, ARITY, L, C>
 {
     #[inline]
-    fn from(tree: &'a Tree<ARITY, L>) -> Leaves<'a, ARITY, L> {
+    fn from(tree: &'a Tree<ARITY, L, C>) -> Leaves<'a, ARITY, L, C> {
         Self {
             forward: LeavesForward::from(tree),
             backward: LeavesBackward::from(tree),
@@ -46,11 +63,11 @@ impl<'a, const ARITY: usize, L: Leaf> From<&'a Tree<ARITY, L>>
     }
 }
 
-impl<'a, const ARITY: usize, L: Leaf> From<&TreeSlice<'a, ARITY, L>>
-    for Leaves<'a, ARITY, L>
+impl<'a, const ARITY: usize, L: Leaf, C: RefCounter>
+    From<&TreeSlice<'a, ARITY, L, C>> for Leaves<'a, ARITY, L, C>
 {
     #[inline]
-    fn from(slice: &TreeSlice<'a, ARITY, L>) -> Leaves<'a, ARITY, L> {
+    fn from(slice: &TreeSlice<'a, ARITY, L, C>) -> Leaves<'a, ARITY, L, C> {
         Self {
             forward: LeavesForward::from(slice),
             backward: LeavesBackward::from(slice),
@@ -60,7 +77,9 @@ impl<'a, const ARITY: usize, L: Leaf> From<&TreeSlice<'a, ARITY, L>>
     }
 }
 
-impl<'a, const ARITY: usize, L: Leaf> Iterator for Leaves<'a, ARITY, L> {
+impl<'a, const ARITY: usize, L: Leaf, C: RefCounter> Iterator
+    for Leaves<'a, ARITY, L, C>
+{
     type Item = L::Slice<'a>;
 
     #[inline]
@@ -80,8 +99,8 @@ impl<'a, const ARITY: usize, L: Leaf> Iterator for Leaves<'a, ARITY, L> {
     }
 }
 
-impl<const ARITY: usize, L: Leaf> DoubleEndedIterator
-    for Leaves<'_, ARITY, L>
+impl<const ARITY: usize, L: Leaf, C: RefCounter> DoubleEndedIterator
+    for Leaves<'_, ARITY, L, C>
 {
     #[inline]
     fn next_back(&mut self) -> Option<Self::Item> {
@@ -94,35 +113,37 @@ impl<const ARITY: usize, L: Leaf> DoubleEndedIterator
     }
 }
 
-impl<const ARITY: usize, L: Leaf> ExactSizeIterator for Leaves<'_, ARITY, L> {
+impl<const ARITY: usize, L: Leaf, C: RefCounter> ExactSizeIterator
+    for Leaves<'_, ARITY, L, C>
+{
     #[inline]
     fn len(&self) -> usize {
         self.leaves_total - self.leaves_yielded
     }
 }
 
-impl<const ARITY: usize, L: Leaf> core::iter::FusedIterator
-    for Leaves<'_, ARITY, L>
+impl<const ARITY: usize, L: Leaf, C: RefCounter> core::iter::FusedIterator
+    for Leaves<'_, ARITY, L, C>
 {
 }
 
 #[derive(Debug)]
-struct LeavesForward<'a, const N: usize, L: Leaf> {
+struct LeavesForward<'a, const N: usize, L: Leaf, C: RefCounter> {
     /// Whether `Self` has been initialized by calling
     /// [`initialize`](Self::initialize()).
     is_initialized: bool,
 
     /// The root of the `Tree` or `TreeSlice` we're iterating over.
-    root: &'a Node<N, L>,
+    root: &'a Node<N, L, C>,
 
     /// The path from the root down to (but not including) the internal node
     /// containing `leaves`. It follows that the depth of the last node (if
     /// there is one) is 2.
-    path: Vec<(&'a Inode<N, L>, usize)>,
+    path: Vec<(&'a Inode<N, L, C>, usize)>,
 
     /// The current leaves. All the nodes in the slice are guaranteed to be
     /// leaf nodes.
-    leaves: &'a [Arc<Node<N, L>>],
+    leaves: &'a [Arc<Node<N, L, C>, C>],
 
     /// The index of the next leaf in [`leaves`](Self::leaves) that'll be
     /// yielded by [`next`](Self::next()).
@@ -149,18 +170,20 @@ struct LeavesForward<'a, const N: usize, L: Leaf> {
     whole_total: usize,
 }
 
-impl<const N: usize, L: Leaf> Clone for LeavesForward<'_, N, L> {
+impl<const N: usize, L: Leaf, C: RefCounter> Clone
+    for LeavesForward<'_, N, L, C>
+{
     #[inline]
     fn clone(&self) -> Self {
         Self { path: self.path.clone(), ..*self }
     }
 }
 
-impl<'a, const N: usize, L: Leaf> From<&'a Tree<N, L>>
-    for LeavesForward<'a, N, L>
+impl<'a, const N: usize, L: Leaf, C: RefCounter> From<&'a Tree<N, L, C>>
+    for LeavesForward<'a, N, L, C>
 {
     #[inline]
-    fn from(tree: &'a Tree<N, L>) -> LeavesForward<'a, N, L> {
+    fn from(tree: &'a Tree<N, L, C>) -> LeavesForward<'a, N, L, C> {
         Self {
             is_initialized: false,
             base_offset: L::BaseMetric::zero(),
@@ -178,11 +201,13 @@ impl<'a, const N: usize, L: Leaf> From<&'a Tree<N, L>>
     }
 }
 
-impl<'a, const ARITY: usize, L: Leaf> From<&TreeSlice<'a, ARITY, L>>
-    for LeavesForward<'a, ARITY, L>
+impl<'a, const ARITY: usize, L: Leaf, C: RefCounter>
+    From<&TreeSlice<'a, ARITY, L, C>> for LeavesForward<'a, ARITY, L, C>
 {
     #[inline]
-    fn from(slice: &TreeSlice<'a, ARITY, L>) -> LeavesForward<'a, ARITY, L> {
+    fn from(
+        slice: &TreeSlice<'a, ARITY, L, C>,
+    ) -> LeavesForward<'a, ARITY, L, C> {
         Self {
             is_initialized: false,
             base_offset: L::BaseMetric::measure(&slice.offset),
@@ -198,16 +223,16 @@ impl<'a, const ARITY: usize, L: Leaf> From<&TreeSlice<'a, ARITY, L>>
     }
 }
 
-impl<'a, const N: usize, L: Leaf> LeavesForward<'a, N, L> {
+impl<'a, const N: usize, L: Leaf, C: RefCounter> LeavesForward<'a, N, L, C> {
     #[allow(clippy::type_complexity)]
     #[inline]
-    fn initialize(&mut self) -> (L::Slice<'a>, &'a [Arc<Node<N, L>>]) {
+    fn initialize(&mut self) -> (L::Slice<'a>, &'a [Arc<Node<N, L, C>, C>]) {
         debug_assert!(!self.is_initialized);
 
         self.is_initialized = true;
 
         let mut inode = match self.root {
-            Node::Internal(inode) => inode,
+            Node::Internal(inode) => &**inode,
 
             Node::Leaf(leaf) => {
                 let first = self.first_slice.take().unwrap_or(leaf.as_slice());
@@ -283,7 +308,7 @@ impl<'a, const N: usize, L: Leaf> LeavesForward<'a, N, L> {
     }
 
     #[inline]
-    fn next_bunch(&mut self) -> &'a [Arc<Node<N, L>>] {
+    fn next_bunch(&mut self) -> &'a [Arc<Node<N, L, C>, C>] {
         let mut inode = loop {
             let &mut (inode, ref mut visited) = self.path.last_mut().unwrap();
 
@@ -350,22 +375,22 @@ impl<'a, const N: usize, L: Leaf> LeavesForward<'a, N, L> {
 }
 
 #[derive(Debug)]
-struct LeavesBackward<'a, const N: usize, L: Leaf> {
+struct LeavesBackward<'a, const N: usize, L: Leaf, C: RefCounter> {
     /// Whether `Self` has been initialized by calling
     /// [`initialize`](Self::initialize()).
     is_initialized: bool,
 
     /// The root of the `Tree` or `TreeSlice` we're iterating over.
-    root: &'a Node<N, L>,
+    root: &'a Node<N, L, C>,
 
     /// The path from the root down to (but not including) the internal node
     /// containing `leaves`. It follows that the depth of the last node (if
     /// there is one) is 2.
-    path: Vec<(&'a Inode<N, L>, usize)>,
+    path: Vec<(&'a Inode<N, L, C>, usize)>,
 
     /// The current leaves. All the nodes in the slice are guaranteed to be
     /// leaf nodes.
-    leaves: &'a [Arc<Node<N, L>>],
+    leaves: &'a [Arc<Node<N, L, C>, C>],
 
     /// The index of the last leaf in [`leaves`](Self::leaves) that was yielded
     /// by [`previous`](Self::previous()).
@@ -393,18 +418,20 @@ struct LeavesBackward<'a, const N: usize, L: Leaf> {
     whole_total: usize,
 }
 
-impl<'a, const N: usize, L: Leaf> Clone for LeavesBackward<'a, N, L> {
+impl<'a, const N: usize, L: Leaf, C: RefCounter> Clone
+    for LeavesBackward<'a, N, L, C>
+{
     #[inline]
     fn clone(&self) -> Self {
         Self { path: self.path.clone(), ..*self }
     }
 }
 
-impl<'a, const N: usize, L: Leaf> From<&'a Tree<N, L>>
-    for LeavesBackward<'a, N, L>
+impl<'a, const N: usize, L: Leaf, C: RefCounter> From<&'a Tree<N, L, C>>
+    for LeavesBackward<'a, N, L, C>
 {
     #[inline]
-    fn from(tree: &'a Tree<N, L>) -> LeavesBackward<'a, N, L> {
+    fn from(tree: &'a Tree<N, L, C>) -> LeavesBackward<'a, N, L, C> {
         Self {
             is_initialized: false,
             base_offset: L::BaseMetric::zero(),
@@ -422,11 +449,13 @@ impl<'a, const N: usize, L: Leaf> From<&'a Tree<N, L>>
     }
 }
 
-impl<'a, const ARITY: usize, L: Leaf> From<&TreeSlice<'a, ARITY, L>>
-    for LeavesBackward<'a, ARITY, L>
+impl<'a, const ARITY: usize, L: Leaf, C: RefCounter>
+    From<&TreeSlice<'a, ARITY, L, C>> for LeavesBackward<'a, ARITY, L, C>
 {
     #[inline]
-    fn from(slice: &TreeSlice<'a, ARITY, L>) -> LeavesBackward<'a, ARITY, L> {
+    fn from(
+        slice: &TreeSlice<'a, ARITY, L, C>,
+    ) -> LeavesBackward<'a, ARITY, L, C> {
         let base_offset = slice.root().base_measure()
             - L::BaseMetric::measure(&slice.offset)
             - slice.base_measure();
@@ -446,16 +475,16 @@ impl<'a, const ARITY: usize, L: Leaf> From<&TreeSlice<'a, ARITY, L>>
     }
 }
 
-impl<'a, const N: usize, L: Leaf> LeavesBackward<'a, N, L> {
+impl<'a, const N: usize, L: Leaf, C: RefCounter> LeavesBackward<'a, N, L, C> {
     #[allow(clippy::type_complexity)]
     #[inline]
-    fn initialize(&mut self) -> (L::Slice<'a>, &'a [Arc<Node<N, L>>]) {
+    fn initialize(&mut self) -> (L::Slice<'a>, &'a [Arc<Node<N, L, C>, C>]) {
         debug_assert!(!self.is_initialized);
 
         self.is_initialized = true;
 
         let mut inode = match self.root {
-            Node::Internal(inode) => inode,
+            Node::Internal(inode) => &**inode,
 
             Node::Leaf(leaf) => {
                 let last = self.last_slice.take().unwrap_or(leaf.as_slice());
@@ -530,7 +559,7 @@ impl<'a, const N: usize, L: Leaf> LeavesBackward<'a, N, L> {
     }
 
     #[inline]
-    fn previous_bunch(&mut self) -> &'a [Arc<Node<N, L>>] {
+    fn previous_bunch(&mut self) -> &'a [Arc<Node<N, L, C>, C>] {
         let mut inode = loop {
             let &mut (inode, ref mut visited) = self.path.last_mut().unwrap();
 
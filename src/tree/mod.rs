@@ -1,3 +1,152 @@
+//! A generic B-tree with metadata stored in each node, on top of which
+//! [`Rope`](crate::Rope) is built.
+//!
+//! A [`Tree`] stores a sequence of leaves, each of which can be
+//! [summarized](Summarize) into a value that's cheap to add and subtract. Every
+//! internal node caches the sum of the summaries of the leaves under it,
+//! which lets a [`Metric`] find the leaf containing a given offset, slice the
+//! tree or iterate over its units in logarithmic time. The same engine can be
+//! used for any sequence whose positions can be described by a summary, like
+//! the pieces of a piece table or the widths of the lines of a document.
+//!
+//! The `ARITY` const parameter of [`Tree`] is the maximum number of children
+//! of its internal nodes, and must be at least 4.
+//!
+//! # Example
+//!
+//! A tree of numbers that can be indexed by position and knows the sum of any
+//! prefix of them:
+//!
+//! ```
+//! use core::ops::{Add, AddAssign, Sub, SubAssign};
+//!
+//! use crop::tree::{AsSlice, BaseMeasured, Metric, Summarize, Tree};
+//!
+//! #[derive(Debug, Default)]
+//! struct Number(u64);
+//!
+//! #[derive(Copy, Clone, Debug)]
+//! struct NumberSlice<'a>(&'a Number);
+//!
+//! #[derive(Copy, Clone, Debug, Default, PartialEq)]
+//! struct Total {
+//!     count: usize,
+//!     sum: u64,
+//! }
+//!
+//! /// Measures a `Total` by how many numbers it contains.
+//! #[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+//! struct Count(usize);
+//!
+//! impl Summarize for Number {
+//!     type Summary = Total;
+//!
+//!     fn summarize(&self) -> Total {
+//!         Total { count: 1, sum: self.0 }
+//!     }
+//! }
+//!
+//! impl Summarize for NumberSlice<'_> {
+//!     type Summary = Total;
+//!
+//!     fn summarize(&self) -> Total {
+//!         self.0.summarize()
+//!     }
+//! }
+//!
+//! impl AsSlice for Number {
+//!     type Slice<'a> = NumberSlice<'a>;
+//!
+//!     fn as_slice(&self) -> NumberSlice<'_> {
+//!         NumberSlice(self)
+//!     }
+//! }
+//!
+//! impl BaseMeasured for Number {
+//!     type BaseMetric = Count;
+//! }
+//!
+//! impl Metric<Total> for Count {
+//!     fn zero() -> Self {
+//!         Count(0)
+//!     }
+//!
+//!     fn one() -> Self {
+//!         Count(1)
+//!     }
+//!
+//!     fn measure(total: &Total) -> Self {
+//!         Count(total.count)
+//!     }
+//! }
+//!
+//! # impl Add<&Total> for Total {
+//! #     type Output = Total;
+//! #     fn add(self, rhs: &Total) -> Total {
+//! #         Total { count: self.count + rhs.count, sum: self.sum + rhs.sum }
+//! #     }
+//! # }
+//! # impl Sub<&Total> for Total {
+//! #     type Output = Total;
+//! #     fn sub(self, rhs: &Total) -> Total {
+//! #         Total { count: self.count - rhs.count, sum: self.sum - rhs.sum }
+//! #     }
+//! # }
+//! # impl AddAssign<&Total> for Total {
+//! #     fn add_assign(&mut self, rhs: &Total) {
+//! #         *self = *self + rhs;
+//! #     }
+//! # }
+//! # impl SubAssign<&Total> for Total {
+//! #     fn sub_assign(&mut self, rhs: &Total) {
+//! #         *self = *self - rhs;
+//! #     }
+//! # }
+//! # impl Add for Count {
+//! #     type Output = Count;
+//! #     fn add(self, rhs: Count) -> Count {
+//! #         Count(self.0 + rhs.0)
+//! #     }
+//! # }
+//! # impl Sub for Count {
+//! #     type Output = Count;
+//! #     fn sub(self, rhs: Count) -> Count {
+//! #         Count(self.0 - rhs.0)
+//! #     }
+//! # }
+//! # impl AddAssign for Count {
+//! #     fn add_assign(&mut self, rhs: Count) {
+//! #         self.0 += rhs.0;
+//! #     }
+//! # }
+//! # impl SubAssign for Count {
+//! #     fn sub_assign(&mut self, rhs: Count) {
+//! #         self.0 -= rhs.0;
+//! #     }
+//! # }
+//! #
+//! let tree = Tree::<4, Number>::from_leaves((1..=100).map(Number));
+//!
+//! assert_eq!(tree.measure::<Count>(), Count(100));
+//! assert_eq!(tree.summary().sum, 5050);
+//!
+//! // The 10th number and the count of the numbers before it.
+//! let (leaf, offset) = tree.leaf_at_measure(Count(10));
+//! assert_eq!(leaf.0 .0, 10);
+//! assert_eq!(offset, Count(9));
+//!
+//! assert_eq!(tree.leaves().map(|slice| slice.0 .0).sum::<u64>(), 5050);
+//! ```
+//!
+//! # Stability
+//!
+//! This module is part of crop's public API and follows the same semver
+//! guarantees as the rest of the crate. The only exceptions are the items
+//! hidden from the documentation, like the `assert_invariants()` methods,
+//! which are only meant to be used in crop's own tests.
+
+mod cursor;
+mod inline_summaries;
 mod leaves;
 mod node;
 mod node_internal;
@@ -8,18 +157,24 @@ mod tree;
 mod tree_builder;
 mod tree_slice;
 mod units;
+mod validate;
 
+pub use cursor::Cursor;
+use inline_summaries::InlineSummaries;
 use iter_chain::ExactChain;
 pub use leaves::Leaves;
-use node::Node;
+use node::{ArcNode, Node};
 use node_internal::Inode;
 use node_leaf::Lnode;
 use tiny_arc::Arc;
+pub use tiny_arc::{AtomicCounter, LocalCounter, RefCounter};
 pub use traits::*;
+pub(crate) use tree::PreorderNode;
 pub use tree::Tree;
 pub use tree_builder::TreeBuilder;
 pub use tree_slice::TreeSlice;
 pub use units::Units;
+pub use validate::InvariantViolation;
 
 mod iter_chain {
     //! This module contains a `Chain` iterator similar to
@@ -1,20 +1,34 @@
+#[cfg(any(
+    feature = "grapheme-count",
+    feature = "paragraph-count",
+    feature = "fingerprint"
+))]
+use core::ops::Range;
+
 use super::traits::{BalancedLeaf, Leaf, Metric, SlicingMetric};
-use super::{Arc, Inode, Lnode};
+use super::{Arc, Inode, Lnode, RefCounter};
+
+/// A reference counted node, shared between the trees it's part of.
+pub(super) type ArcNode<const N: usize, L, C> = Arc<Node<N, L, C>, C>;
 
 #[derive(Clone)]
-pub(super) enum Node<const N: usize, L: Leaf> {
-    Internal(Inode<N, L>),
+pub(super) enum Node<const N: usize, L: Leaf, C: RefCounter> {
+    Internal(Box<Inode<N, L, C>>),
     Leaf(Lnode<L>),
 }
 
-impl<const N: usize, L: Leaf + Default> Default for Node<N, L> {
+impl<const N: usize, L: Leaf + Default, C: RefCounter> Default
+    for Node<N, L, C>
+{
     #[inline]
     fn default() -> Self {
         Node::Leaf(Lnode::default())
     }
 }
 
-impl<const N: usize, L: Leaf> core::fmt::Debug for Node<N, L> {
+impl<const N: usize, L: Leaf, C: RefCounter> core::fmt::Debug
+    for Node<N, L, C>
+{
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         if !f.alternate() {
@@ -35,7 +49,17 @@ impl<const N: usize, L: Leaf> core::fmt::Debug for Node<N, L> {
     }
 }
 
-impl<const N: usize, L: Leaf> Node<N, L> {
+impl<const N: usize, L: Leaf, C: RefCounter> Node<N, L, C> {
+    /// Creates an internal node from the given inode.
+    ///
+    /// Inodes store the summaries of their children inline, which makes them
+    /// much larger than leaves, so they're boxed to keep every leaf from
+    /// taking as much memory as an inode.
+    #[inline]
+    pub(super) fn internal(inode: Inode<N, L, C>) -> Self {
+        Node::Internal(Box::new(inode))
+    }
+
     /// Asserts the invariants of this node, then if it's an inode it calls
     /// itself recursively on all of its children.
     pub(super) fn assert_invariants(&self) {
@@ -150,7 +174,7 @@ impl<const N: usize, L: Leaf> Node<N, L> {
     }
 
     #[inline]
-    pub(super) fn get_internal(&self) -> &Inode<N, L> {
+    pub(super) fn get_internal(&self) -> &Inode<N, L, C> {
         match self {
             Node::Internal(inode) => inode,
             Node::Leaf(_) => panic!(""),
@@ -158,7 +182,7 @@ impl<const N: usize, L: Leaf> Node<N, L> {
     }
 
     #[inline]
-    pub(super) fn get_internal_mut(&mut self) -> &mut Inode<N, L> {
+    pub(super) fn get_internal_mut(&mut self) -> &mut Inode<N, L, C> {
         match self {
             Node::Internal(inode) => inode,
             Node::Leaf(_) => panic!(""),
@@ -224,6 +248,122 @@ impl<const N: usize, L: Leaf> Node<N, L> {
         }
     }
 
+    #[inline]
+    pub(super) fn for_each_leaf(&self, fun: &mut impl FnMut(&L)) {
+        match self {
+            Node::Internal(inode) => {
+                for child in inode.children() {
+                    child.for_each_leaf(fun);
+                }
+            },
+
+            Node::Leaf(leaf) => fun(leaf.value()),
+        }
+    }
+
+    /// Calls `fun` on the summaries of the leaves under this node and of the
+    /// internal nodes whose summary `descend` returns `false` on, without
+    /// descending into the latter, in order.
+    #[cfg(any(
+        feature = "grapheme-count",
+        feature = "paragraph-count",
+        feature = "fingerprint"
+    ))]
+    #[inline]
+    pub(super) fn for_each_subtree(
+        &self,
+        descend: &mut impl FnMut(&L::Summary) -> bool,
+        fun: &mut impl FnMut(&L::Summary),
+    ) {
+        match self {
+            Node::Internal(inode) if descend(inode.summary()) => {
+                for child in inode.children() {
+                    child.for_each_subtree(descend, fun);
+                }
+            },
+
+            _ => fun(self.summary()),
+        }
+    }
+
+    /// Same as [`for_each_subtree`](Self::for_each_subtree()), but only on
+    /// the subtrees in the given base range. The start and end of the range
+    /// have to fall on the boundaries between two leaves.
+    #[cfg(any(
+        feature = "grapheme-count",
+        feature = "paragraph-count",
+        feature = "fingerprint"
+    ))]
+    #[inline]
+    pub(super) fn for_each_subtree_in_range(
+        &self,
+        range: Range<L::BaseMetric>,
+        descend: &mut impl FnMut(&L::Summary) -> bool,
+        fun: &mut impl FnMut(&L::Summary),
+    ) {
+        let Node::Internal(inode) = self else {
+            debug_assert_eq!(range.start, L::BaseMetric::zero());
+            debug_assert_eq!(range.end, self.base_measure());
+            return fun(self.summary());
+        };
+
+        let mut offset = L::BaseMetric::zero();
+
+        for child in inode.children() {
+            if offset >= range.end {
+                break;
+            }
+
+            let child_end = offset + child.base_measure();
+
+            if child_end > range.start {
+                if range.start <= offset && child_end <= range.end {
+                    child.for_each_subtree(descend, fun);
+                } else {
+                    let start = range.start.max(offset) - offset;
+                    let end = range.end.min(child_end) - offset;
+                    child.for_each_subtree_in_range(start..end, descend, fun);
+                }
+            }
+
+            offset = child_end;
+        }
+    }
+
+    /// Calls `fun` on the leaves under this node, skipping the subtrees
+    /// that are shared with other nodes.
+    #[cfg(feature = "compression")]
+    #[inline]
+    pub(super) fn for_each_unshared_leaf_mut(
+        &mut self,
+        fun: &mut impl FnMut(&mut L),
+    ) {
+        match self {
+            Node::Internal(inode) => {
+                for child in inode.children_mut() {
+                    if let Some(child) = Arc::get_mut(child) {
+                        child.for_each_unshared_leaf_mut(fun);
+                    }
+                }
+            },
+
+            Node::Leaf(leaf) => fun(leaf.value_mut()),
+        }
+    }
+
+    /// Returns the number of bytes allocated for the nodes under this node
+    /// and for its boxed inode, not including the node itself nor the heap
+    /// allocations owned by the leaves.
+    #[inline]
+    pub(super) fn heap_size(&self) -> usize {
+        match self {
+            Node::Internal(inode) => {
+                core::mem::size_of::<Inode<N, L, C>>() + inode.heap_size()
+            },
+            Node::Leaf(_) => 0,
+        }
+    }
+
     #[inline]
     pub(super) fn leaf_count(&self) -> usize {
         match self {
@@ -251,7 +391,7 @@ impl<const N: usize, L: Leaf> Node<N, L> {
     ///
     /// Panics if the `Arc` enclosing the root has a strong counter > 1.
     #[inline]
-    pub(super) fn replace_with_single_child(node: &mut Arc<Self>) {
+    pub(super) fn replace_with_single_child(node: &mut Arc<Self, C>) {
         while let Self::Internal(inode) = Arc::get_mut(node).unwrap() {
             if inode.len() == 1 {
                 *node = Arc::clone(inode.first());
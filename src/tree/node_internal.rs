@@ -1,18 +1,27 @@
 use core::ops::{Range, RangeBounds};
 
 use super::traits::*;
-use super::{Arc, ExactChain, Node};
+use super::{Arc, ArcNode, ExactChain, InlineSummaries, Node, RefCounter};
 use crate::range_bounds_to_start_end;
 
 #[derive(Clone)]
-pub(super) struct Inode<const N: usize, L: Leaf> {
-    children: Vec<Arc<Node<N, L>>>,
+pub(super) struct Inode<const N: usize, L: Leaf, C: RefCounter> {
+    children: Vec<Arc<Node<N, L, C>, C>>,
+
+    /// The summaries of the children, stored inline next to the pointers to
+    /// them so that picking the child to descend into doesn't have to
+    /// dereference every child before it. The `i`-th summary is always equal
+    /// to the summary of the `i`-th child.
+    summaries: InlineSummaries<L::Summary, N>,
+
     summary: L::Summary,
     depth: usize,
     leaf_count: usize,
 }
 
-impl<const N: usize, L: Leaf> core::fmt::Debug for Inode<N, L> {
+impl<const N: usize, L: Leaf, C: RefCounter> core::fmt::Debug
+    for Inode<N, L, C>
+{
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         if !f.alternate() {
@@ -28,7 +37,7 @@ impl<const N: usize, L: Leaf> core::fmt::Debug for Inode<N, L> {
     }
 }
 
-impl<const N: usize, L: Leaf> Inode<N, L> {
+impl<const N: usize, L: Leaf, C: RefCounter> Inode<N, L, C> {
     /// Appends the node at the right depth.
     ///
     /// If all the nodes on the right side of the subtree up to the one to
@@ -37,7 +46,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     #[inline]
     pub(super) fn append_at_depth(
         &mut self,
-        mut node: Arc<Node<N, L>>,
+        mut node: Arc<Node<N, L, C>, C>,
     ) -> Option<Self>
     where
         L: BalancedLeaf + Clone,
@@ -53,7 +62,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
                 last.get_internal_mut().append_at_depth(node)
             })?;
 
-            node = Arc::new(Node::Internal(extra));
+            node = Arc::new(Node::internal(extra));
         }
 
         debug_assert_eq!(self.depth(), node.depth() + 1);
@@ -125,6 +134,20 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
                 child.depth()
             );
         }
+
+        assert_eq!(self.summaries.len(), self.len());
+
+        for (idx, (child, summary)) in
+            self.children().iter().zip(self.summaries.iter()).enumerate()
+        {
+            assert_eq!(
+                child.summary(),
+                summary,
+                "The summary of the child at index {idx} of an internal node \
+                 of depth {} is out of date",
+                self.depth(),
+            );
+        }
     }
 
     /// Balances itself with another inode at the same depth. Note that `other`
@@ -202,7 +225,11 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
 
         Arc::make_mut(left).balance(Arc::make_mut(right));
 
-        if right.is_empty() {
+        self.refresh_summary(left_idx);
+        self.refresh_summary(right_idx);
+        self.resummarize();
+
+        if self.child(right_idx).is_empty() {
             self.remove(right_idx);
         }
     }
@@ -275,7 +302,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     /// - this inode has only one child (the second child is assumed to exist);
     ///
     /// - the `Arc` enclosing the first child has a strong counter > 1. This
-    /// function assumes that there are zero `Arc::clone`s of the first child.
+    ///   function assumes that there are zero `Arc::clone`s of the first child.
     #[inline]
     pub(super) fn balance_first_child_with_second(&mut self)
     where
@@ -296,9 +323,11 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
                 // remove the second child.
                 if first.len() + second.len() <= Self::max_children() {
                     first.children.append(&mut second.children);
+                    first.summaries.append(&mut second.summaries);
                     first.leaf_count += second.leaf_count;
                     first.summary += second.summary();
                     self.children.remove(1);
+                    self.summaries.remove(1);
                 }
                 // Move the minimum number of children from the second child
                 // to the first child, keeping both.
@@ -320,6 +349,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
                 if second.is_empty() {
                     self.leaf_count -= 1;
                     self.children.remove(1);
+                    self.summaries.remove(1);
                 }
             },
 
@@ -327,6 +357,14 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
             // the same kind.
             _ => unreachable!(),
         }
+
+        self.refresh_summary(0);
+
+        if self.len() > 1 {
+            self.refresh_summary(1);
+        }
+
+        self.resummarize();
     }
 
     /// Balances the last child using the contents of the penultimate (i.e.
@@ -340,10 +378,10 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     /// Panics if:
     ///
     /// - this inode has only one child (the penultimate child is assumed to
-    /// exist);
+    ///   exist);
     ///
     /// - the `Arc` enclosing the last child has a strong counter > 1. This
-    /// function assumes that there are zero `Arc::clone`s of the last child.
+    ///   function assumes that there are zero `Arc::clone`s of the last child.
     #[inline]
     pub(super) fn balance_last_child_with_penultimate(&mut self)
     where
@@ -366,9 +404,11 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
                 // then remove the last child.
                 if penultimate.len() + last.len() <= Self::max_children() {
                     penultimate.children.append(&mut last.children);
+                    penultimate.summaries.append(&mut last.summaries);
                     penultimate.leaf_count += last.leaf_count;
                     penultimate.summary += last.summary();
                     self.children.remove(last_idx);
+                    self.summaries.remove(last_idx);
                 }
                 // Move the minimum number of children from the penultimate
                 // child to the last child, keeping both.
@@ -391,6 +431,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
                 if last.is_empty() {
                     self.leaf_count -= 1;
                     self.children.remove(last_idx);
+                    self.summaries.remove(last_idx);
                 }
             },
 
@@ -398,6 +439,14 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
             // of the same kind.
             _ => unreachable!(),
         }
+
+        self.refresh_summary(last_idx - 1);
+
+        if self.len() > last_idx {
+            self.refresh_summary(last_idx);
+        }
+
+        self.resummarize();
     }
 
     #[inline]
@@ -406,15 +455,32 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     }
 
     #[inline]
-    pub(super) fn child(&self, child_idx: usize) -> &Arc<Node<N, L>> {
+    pub(super) fn child(&self, child_idx: usize) -> &Arc<Node<N, L, C>, C> {
         &self.children[child_idx]
     }
 
     #[inline]
-    pub(super) fn children(&self) -> &[Arc<Node<N, L>>] {
+    pub(super) fn children(&self) -> &[Arc<Node<N, L, C>, C>] {
         &self.children
     }
 
+    /// Returns mutable references to this inode's children.
+    ///
+    /// The summaries of the children are not updated, so their contents must
+    /// not change.
+    #[cfg(feature = "compression")]
+    #[inline]
+    pub(super) fn children_mut(&mut self) -> &mut [Arc<Node<N, L, C>, C>] {
+        &mut self.children
+    }
+
+    /// Returns the summaries of this inode's children, in the same order as
+    /// [`children()`](Self::children()).
+    #[inline]
+    pub(super) fn child_summaries(&self) -> &[L::Summary] {
+        &self.summaries
+    }
+
     /// Returns the index of the child at the given measure together
     /// with the combined `M`-offset of the other children up to but not
     /// including that child.
@@ -427,17 +493,17 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
 
         let mut idx = 0;
         let mut offset = M::zero();
-        let children = self.children();
+        let summaries = self.child_summaries();
 
         loop {
-            debug_assert!(idx < children.len());
+            debug_assert!(idx < summaries.len());
 
             // SAFETY: the measure is not of out bounds so one of the children
             // must contain it, which means we always return before the index
             // goes out of bounds.
-            let child = unsafe { children.get_unchecked(idx) };
+            let child_summary = unsafe { summaries.get_unchecked(idx) };
 
-            let child_measure = child.measure::<M>();
+            let child_measure = M::measure(child_summary);
 
             offset += child_measure;
 
@@ -450,44 +516,58 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     }
 
     /// Returns the index of the child containing the entire range together
-    /// with the combined `M`-offset of the other children up to but not
-    /// including that child, or `None` if none of the children contain the
-    /// range entirely.
+    /// with the combined `M`-offset and summary of the other children up to
+    /// but not including that child, or `None` if none of the children
+    /// contain the range entirely.
     #[inline]
     pub(super) fn child_containing_range<M>(
         &self,
         range: Range<M>,
-    ) -> Option<(usize, M)>
+    ) -> Option<(usize, M, L::Summary)>
     where
         M: Metric<L::Summary>,
     {
         debug_assert!(range.start <= range.end);
         debug_assert!(range.end <= self.measure::<M>());
 
+        let summaries = self.child_summaries();
+
+        // Appending at the end is by far the most common edit after the ones
+        // at the cursor, so we go straight to the last child instead of
+        // scanning all the children before it.
+        if range.start == self.measure::<M>() {
+            let last = summaries.len() - 1;
+            let measure = M::measure(&summaries[last]);
+            let summary = self.summary().clone() - &summaries[last];
+            return Some((last, range.start - measure, summary));
+        }
+
         let mut idx = 0;
         let mut offset = M::zero();
-        let children = self.children();
+        let mut summary = L::Summary::default();
 
         loop {
-            debug_assert!(idx < children.len());
+            debug_assert!(idx < summaries.len());
 
             // SAFETY: the start of the range is not of out bounds so one of
             // the children must contain it, which means we always return
             // before the index goes out of bounds.
-            let child = unsafe { children.get_unchecked(idx) };
+            let child_summary = unsafe { summaries.get_unchecked(idx) };
 
-            let measure = child.measure::<M>();
+            let measure = M::measure(child_summary);
 
             offset += measure;
 
             if offset >= range.start {
                 if offset >= range.end {
-                    return Some((idx, offset - measure));
+                    return Some((idx, offset - measure, summary));
                 } else {
                     return None;
                 }
             }
 
+            summary += child_summary;
+
             idx += 1;
         }
     }
@@ -503,7 +583,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     pub(super) fn drain<R>(
         &mut self,
         idx_range: R,
-    ) -> alloc::vec::Drain<'_, Arc<Node<N, L>>>
+    ) -> alloc::vec::Drain<'_, Arc<Node<N, L, C>, C>>
     where
         R: RangeBounds<usize>,
     {
@@ -517,6 +597,10 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
             self.leaf_count -= child.leaf_count();
         }
 
+        self.summaries.drain(start..end);
+
+        self.resummarize();
+
         self.children.drain(start..end)
     }
 
@@ -524,6 +608,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     pub(super) fn empty() -> Self {
         Self {
             children: Vec::with_capacity(N),
+            summaries: InlineSummaries::default(),
             depth: 1,
             leaf_count: 0,
             summary: Default::default(),
@@ -534,7 +619,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     ///
     /// Panics if the inode is empty.
     #[inline]
-    pub(super) fn first(&self) -> &Arc<Node<N, L>> {
+    pub(super) fn first(&self) -> &Arc<Node<N, L, C>, C> {
         &self.children[0]
     }
 
@@ -547,24 +632,76 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     #[inline]
     pub(super) fn from_children<I>(children: I) -> Self
     where
-        I: IntoIterator<Item = Arc<Node<N, L>>>,
+        I: IntoIterator<Item = Arc<Node<N, L, C>, C>>,
     {
-        let children = children.into_iter().collect::<Vec<Arc<Node<N, L>>>>();
+        let children =
+            children.into_iter().collect::<Vec<Arc<Node<N, L, C>, C>>>();
 
         debug_assert!(!children.is_empty());
         debug_assert!(children.len() <= Self::max_children());
 
         let depth = children[0].depth() + 1;
 
+        let summaries = children
+            .iter()
+            .map(|child| child.summary().clone())
+            .collect::<InlineSummaries<_, N>>();
+
         let mut leaf_count = children[0].leaf_count();
-        let mut summary = children[0].summary().clone();
+        let mut summary = summaries[0].clone();
 
-        for child in &children[1..] {
+        for (child, child_summary) in children[1..].iter().zip(&summaries[1..])
+        {
             leaf_count += child.leaf_count();
-            summary += child.summary();
+            summary += child_summary;
+        }
+
+        Self { children, summaries, depth, leaf_count, summary }
+    }
+
+    /// Creates a new inode from its children and its summary, which is
+    /// trusted to be the sum of their summaries.
+    #[inline]
+    pub(super) fn from_summarized_children(
+        children: Vec<Arc<Node<N, L, C>, C>>,
+        summary: L::Summary,
+    ) -> Self {
+        debug_assert!(!children.is_empty());
+        debug_assert!(children.len() <= Self::max_children());
+
+        let depth = children[0].depth() + 1;
+
+        let summaries = children
+            .iter()
+            .map(|child| child.summary().clone())
+            .collect::<InlineSummaries<_, N>>();
+
+        let leaf_count = children.iter().map(|child| child.leaf_count()).sum();
+
+        Self { children, summaries, depth, leaf_count, summary }
+    }
+
+    /// Returns whether the summary of this inode is the sum of the summaries
+    /// of its children, replacing it with that sum if it is.
+    ///
+    /// Summaries that aren't subtractable can have parts which are combined
+    /// instead of being added up and that aren't compared by `==`, and this
+    /// makes sure those are recomputed too.
+    #[inline]
+    pub(super) fn check_summary(&mut self) -> bool {
+        let mut sum = self.summaries[0].clone();
+
+        for summary in &self.summaries[1..] {
+            sum += summary;
+        }
+
+        if sum != self.summary {
+            return false;
         }
 
-        Self { children, depth, leaf_count, summary }
+        self.summary = sum;
+
+        true
     }
 
     /// Constructs a new inode from an arbitrarily long sequence of nodes.
@@ -580,7 +717,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     #[inline]
     pub(super) fn from_nodes<I>(nodes: I) -> Self
     where
-        I: IntoIterator<Item = Arc<Node<N, L>>>,
+        I: IntoIterator<Item = Arc<Node<N, L, C>, C>>,
         I::IntoIter: ExactSizeIterator,
     {
         let nodes = nodes.into_iter();
@@ -592,13 +729,13 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
         }
 
         let mut nodes = ChildSegmenter::new(nodes)
-            .map(Node::Internal)
+            .map(Node::internal)
             .map(Arc::new)
             .collect::<Vec<_>>();
 
         while nodes.len() > Self::max_children() {
             nodes = ChildSegmenter::new(nodes.into_iter())
-                .map(Node::Internal)
+                .map(Node::internal)
                 .map(Arc::new)
                 .collect();
         }
@@ -625,7 +762,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     pub(super) fn insert(
         &mut self,
         child_offset: usize,
-        child: Arc<Node<N, L>>,
+        child: Arc<Node<N, L, C>, C>,
     ) {
         if self.is_empty() {
             self.depth = child.depth() + 1;
@@ -636,7 +773,9 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
 
         self.leaf_count += child.leaf_count();
         self.summary += child.summary();
+        self.summaries.insert(child_offset, child.summary().clone());
         self.children.insert(child_offset, child);
+        self.resummarize();
     }
 
     /// Inserts a node shallower than this inode's children at the right depth
@@ -652,7 +791,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     pub(super) fn insert_at_depth(
         &mut self,
         child_offset: usize,
-        node: Arc<Node<N, L>>,
+        node: Arc<Node<N, L, C>, C>,
     ) where
         L: BalancedLeaf + Clone,
     {
@@ -670,7 +809,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
             });
 
             if let Some(extra) = extra {
-                self.insert(child_offset, Arc::new(Node::Internal(extra)));
+                self.insert(child_offset, Arc::new(Node::internal(extra)));
             }
         } else {
             let extra = self.with_child_mut(0, |first| {
@@ -681,7 +820,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
             });
 
             if let Some(extra) = extra {
-                self.insert(0, Arc::new(Node::Internal(extra)));
+                self.insert(0, Arc::new(Node::internal(extra)));
             }
         }
     }
@@ -710,7 +849,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
         children: I,
     ) -> Option<impl ExactSizeIterator<Item = Self>>
     where
-        I: IntoIterator<Item = Arc<Node<N, L>>>,
+        I: IntoIterator<Item = Arc<Node<N, L, C>, C>>,
         I::IntoIter: ExactSizeIterator,
     {
         let mut children = children.into_iter();
@@ -778,7 +917,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     }
 
     #[inline]
-    pub(super) fn last(&self) -> &Arc<Node<N, L>> {
+    pub(super) fn last(&self) -> &Arc<Node<N, L, C>, C> {
         let last_idx = self.len() - 1;
         &self.children[last_idx]
     }
@@ -789,6 +928,22 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
         self.children.len()
     }
 
+    /// Returns the number of bytes allocated for this inode's children and
+    /// for all the nodes under them, not including the heap allocations
+    /// owned by the leaves.
+    #[inline]
+    pub(super) fn heap_size(&self) -> usize {
+        self.children.capacity()
+            * core::mem::size_of::<Arc<Node<N, L, C>, C>>()
+            + self
+                .children
+                .iter()
+                .map(|child| {
+                    Arc::<Node<N, L, C>, C>::alloc_size() + child.heap_size()
+                })
+                .sum::<usize>()
+    }
+
     #[inline]
     pub(super) fn leaf_count(&self) -> usize {
         self.leaf_count
@@ -820,7 +975,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     #[inline]
     pub(super) fn prepend_at_depth(
         &mut self,
-        mut node: Arc<Node<N, L>>,
+        mut node: Arc<Node<N, L, C>, C>,
     ) -> Option<Self>
     where
         L: BalancedLeaf + Clone,
@@ -836,7 +991,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
                 first.prepend_at_depth(node)
             })?;
 
-            node = Arc::new(Node::Internal(extra));
+            node = Arc::new(Node::internal(extra));
         }
 
         debug_assert_eq!(self.depth(), node.depth() + 1);
@@ -874,7 +1029,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     /// Panics if the inode is already full or if `child` is a depth different
     /// than `self.depth() - 1` if the inode already contained some children.
     #[inline]
-    pub(super) fn push(&mut self, child: Arc<Node<N, L>>) {
+    pub(super) fn push(&mut self, child: Arc<Node<N, L, C>, C>) {
         if self.is_empty() {
             self.depth = child.depth() + 1;
         }
@@ -884,20 +1039,52 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
 
         self.leaf_count += child.leaf_count();
         self.summary += child.summary();
+        self.summaries.push(child.summary().clone());
         self.children.push(child);
     }
 
+    /// Recomputes the summary of this inode from the ones of its children
+    /// after they've changed, if it couldn't be updated by subtracting the
+    /// summaries of the children that were removed.
+    #[inline]
+    fn resummarize(&mut self) {
+        if L::SUMMARY_IS_SUBTRACTABLE {
+            return;
+        }
+
+        let mut summaries = self.summaries.iter();
+
+        self.summary = summaries.next().cloned().unwrap_or_default();
+
+        for summary in summaries {
+            self.summary += summary;
+        }
+    }
+
+    /// Updates the stored summary of the child at `child_idx` after it's been
+    /// modified in place. This inode's own summary is left untouched, so the
+    /// caller is responsible for keeping it in sync.
+    #[inline]
+    fn refresh_summary(&mut self, child_idx: usize) {
+        self.summaries[child_idx] = self.children[child_idx].summary().clone();
+    }
+
     /// Removes the child at `child_idx`, returning it.
     ///
     /// # Panics
     ///
     /// Panics if `child_idx` is greater or equal to the length of this inode.
     #[inline]
-    pub(super) fn remove(&mut self, child_idx: usize) -> Arc<Node<N, L>> {
+    pub(super) fn remove(
+        &mut self,
+        child_idx: usize,
+    ) -> Arc<Node<N, L, C>, C> {
         debug_assert!(child_idx < self.len());
         let child = self.children.remove(child_idx);
+        self.summaries.remove(child_idx);
         self.leaf_count -= child.leaf_count();
         self.summary -= child.summary();
+        self.resummarize();
         child
     }
 
@@ -916,7 +1103,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
     pub(super) fn swap(
         &mut self,
         child_idx: usize,
-        new_child: Arc<Node<N, L>>,
+        new_child: Arc<Node<N, L, C>, C>,
     ) {
         debug_assert!(child_idx < self.len());
         debug_assert_eq!(new_child.depth() + 1, self.depth());
@@ -927,7 +1114,9 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
 
         self.summary += new_child.summary();
         self.leaf_count += new_child.leaf_count();
+        self.summaries[child_idx] = new_child.summary().clone();
         self.children[child_idx] = new_child;
+        self.resummarize();
     }
 
     /// Returns mutable references to the child nodes at `first_idx` and
@@ -942,7 +1131,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
         &mut self,
         first_idx: usize,
         second_idx: usize,
-    ) -> (&mut Arc<Node<N, L>>, &mut Arc<Node<N, L>>) {
+    ) -> (&mut ArcNode<N, L, C>, &mut ArcNode<N, L, C>) {
         debug_assert!(first_idx < second_idx);
         debug_assert!(second_idx < self.len());
 
@@ -962,7 +1151,7 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
         fun: F,
     ) -> T
     where
-        F: FnOnce(&mut Arc<Node<N, L>>) -> T,
+        F: FnOnce(&mut Arc<Node<N, L, C>, C>) -> T,
     {
         let child = &mut self.children[child_idx];
 
@@ -973,6 +1162,8 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
 
         self.summary += child.summary();
         self.leaf_count += child.leaf_count();
+        self.summaries[child_idx] = child.summary().clone();
+        self.resummarize();
 
         ret
     }
@@ -981,40 +1172,42 @@ impl<const N: usize, L: Leaf> Inode<N, L> {
 /// Takes an iterator of `n` nodes (with `n >= min_children`) at depth `d`
 /// and gives back inodes of depth `d + 1` that are all guaranteed to have
 /// between `min_children` and `max_children` children.
-struct ChildSegmenter<const N: usize, L, Children>
+struct ChildSegmenter<const N: usize, L, Children, C: RefCounter>
 where
     L: Leaf,
-    Children: ExactSizeIterator<Item = Arc<Node<N, L>>>,
+    Children: ExactSizeIterator<Item = Arc<Node<N, L, C>, C>>,
 {
     children: Children,
 }
 
-impl<const N: usize, L, Children> ChildSegmenter<N, L, Children>
+impl<const N: usize, L, C: RefCounter, Children>
+    ChildSegmenter<N, L, Children, C>
 where
     L: Leaf,
-    Children: ExactSizeIterator<Item = Arc<Node<N, L>>>,
+    Children: ExactSizeIterator<Item = Arc<Node<N, L, C>, C>>,
 {
     /// # Panics
     ///
     /// Panics if `children` yields less than `min_children` children.
     #[inline]
     fn new(children: Children) -> Self {
-        debug_assert!(children.len() >= Inode::<N, L>::min_children());
+        debug_assert!(children.len() >= Inode::<N, L, C>::min_children());
         Self { children }
     }
 }
 
-impl<const N: usize, L, Children> Iterator for ChildSegmenter<N, L, Children>
+impl<const N: usize, L, C: RefCounter, Children> Iterator
+    for ChildSegmenter<N, L, Children, C>
 where
     L: Leaf,
-    Children: ExactSizeIterator<Item = Arc<Node<N, L>>>,
+    Children: ExactSizeIterator<Item = Arc<Node<N, L, C>, C>>,
 {
-    type Item = Inode<N, L>;
+    type Item = Inode<N, L, C>;
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
-        let min_children = Inode::<N, L>::min_children();
-        let max_children = Inode::<N, L>::max_children();
+        let min_children = Inode::<N, L, C>::min_children();
+        let max_children = Inode::<N, L, C>::max_children();
         let remaining = self.children.len();
 
         debug_assert!(remaining == 0 || remaining >= min_children);
@@ -1045,16 +1238,16 @@ where
     }
 }
 
-impl<const N: usize, L, Children> ExactSizeIterator
-    for ChildSegmenter<N, L, Children>
+impl<const N: usize, L, C: RefCounter, Children> ExactSizeIterator
+    for ChildSegmenter<N, L, Children, C>
 where
     L: Leaf,
-    Children: ExactSizeIterator<Item = Arc<Node<N, L>>>,
+    Children: ExactSizeIterator<Item = Arc<Node<N, L, C>, C>>,
 {
     #[inline]
     fn len(&self) -> usize {
         let remaining = self.children.len();
-        let max_children = Inode::<N, L>::max_children();
+        let max_children = Inode::<N, L, C>::max_children();
         remaining / max_children + ((remaining % max_children != 0) as usize)
     }
 }
@@ -1064,8 +1257,8 @@ where
 /// Called by the `Debug` impl of [`Inode`] when using the pretty-print
 /// modifier (i.e. `{:#?}`).
 #[inline]
-fn pretty_print_inode<const N: usize, L: Leaf>(
-    inode: &Inode<N, L>,
+fn pretty_print_inode<const N: usize, L: Leaf, C: RefCounter>(
+    inode: &Inode<N, L, C>,
     shifts: &mut String,
     ident: &str,
     last_shift_byte_len: usize,
@@ -1,4 +1,4 @@
-use core::ops::RangeBounds;
+use core::ops::{Range, RangeBounds};
 
 use super::traits::{BalancedLeaf, Leaf, Metric, ReplaceableLeaf};
 
@@ -54,7 +54,10 @@ impl<L: Leaf> Lnode<L> {
         L::balance_leaves(
             (&mut self.value, &mut self.summary),
             (&mut other.value, &mut other.summary),
-        )
+        );
+
+        self.resummarize();
+        other.resummarize();
     }
 
     #[inline]
@@ -80,9 +83,16 @@ impl<L: Leaf> Lnode<L> {
         M::measure(self.summary())
     }
 
+    /// Creates a new leaf node from a value and its summary, which is only
+    /// trusted if summaries can be subtracted, since it's usually computed by
+    /// subtracting the summary of the rest of a slice.
     #[inline]
     pub(super) fn new(value: L, summary: L::Summary) -> Self {
-        Self { value, summary }
+        if L::SUMMARY_IS_SUBTRACTABLE {
+            Self { value, summary }
+        } else {
+            Self::from(value)
+        }
     }
 
     #[inline]
@@ -92,6 +102,7 @@ impl<L: Leaf> Lnode<L> {
         L: ReplaceableLeaf<M>,
     {
         self.value.remove_up_to(&mut self.summary, up_to);
+        self.resummarize();
     }
 
     #[track_caller]
@@ -106,13 +117,52 @@ impl<L: Leaf> Lnode<L> {
         R: RangeBounds<M>,
         L: ReplaceableLeaf<M>,
     {
-        self.value
+        let extra_leaves = self
+            .value
             .replace(&mut self.summary, range, replace_with)
-            .map(|extra_leaves| extra_leaves.map(Self::from))
+            .map(|extra_leaves| extra_leaves.map(Self::from));
+
+        self.resummarize();
+
+        extra_leaves
+    }
+
+    #[inline]
+    pub(super) fn replace_fits<M>(
+        &self,
+        range: Range<M>,
+        replace_with: &L::Replacement<'_>,
+    ) -> bool
+    where
+        M: Metric<L::Summary>,
+        L: ReplaceableLeaf<M>,
+    {
+        self.value.replace_fits(&self.summary, range, replace_with)
+    }
+
+    /// Recomputes the summary of the leaf after it's been edited, if it
+    /// couldn't be updated by subtracting the summary of the removed
+    /// contents.
+    #[inline]
+    fn resummarize(&mut self) {
+        if !L::SUMMARY_IS_SUBTRACTABLE {
+            self.summary = self.value.summarize();
+        }
     }
 
     #[inline]
     pub(super) fn summary(&self) -> &L::Summary {
         &self.summary
     }
+
+    #[inline]
+    pub(super) fn value(&self) -> &L {
+        &self.value
+    }
+
+    #[cfg(feature = "compression")]
+    #[inline]
+    pub(super) fn value_mut(&mut self) -> &mut L {
+        &mut self.value
+    }
 }
@@ -3,28 +3,60 @@
 //! all bugs are mine).
 //!
 //! [rclite]: https://github.com/fereidani/rclite
+//!
+//! The reference counter is a type parameter: an [`AtomicCounter`] makes the
+//! `Arc` `Send` and `Sync`, while a [`LocalCounter`] is a plain
+//! `Cell<usize>`, which makes cloning and dropping an `Arc` cheaper at the
+//! cost of it no longer being `Send` nor `Sync`.
+//!
+//! With the `node-pool` feature enabled the memory of the dropped `Arc`s is
+//! kept in a thread-local pool and reused by the next `Arc`s of the same
+//! size, instead of being given back to the allocator right away.
 
-use core::mem::MaybeUninit;
+use core::alloc::Layout;
 use core::ptr::{addr_of_mut, NonNull};
 use core::sync::atomic;
 
+pub use counter::{AtomicCounter, LocalCounter, RefCounter};
+
 /// A tiny `Arc` without weak references.
-pub(super) struct Arc<T> {
-    ptr: NonNull<ArcInner<T>>,
+pub(super) struct Arc<T, C: RefCounter> {
+    ptr: NonNull<ArcInner<T, C>>,
 }
 
-unsafe impl<T: Sync + Send> Send for Arc<T> {}
-unsafe impl<T: Sync + Send> Sync for Arc<T> {}
+// A `LocalCounter` is neither `Send` nor `Sync`, so two `Arc`s sharing a
+// non-atomic counter can't end up on different threads.
+unsafe impl<T, C> Send for Arc<T, C>
+where
+    T: Sync + Send,
+    C: RefCounter + Sync + Send,
+{
+}
 
-struct ArcInner<T> {
-    counter: atomic::AtomicUsize,
-    data: T,
+unsafe impl<T, C> Sync for Arc<T, C>
+where
+    T: Sync + Send,
+    C: RefCounter + Sync + Send,
+{
 }
 
-unsafe impl<T: Sync + Send> Send for ArcInner<T> {}
-unsafe impl<T: Sync + Send> Sync for ArcInner<T> {}
+struct ArcInner<T, C: RefCounter> {
+    counter: C::Count,
+    data: T,
+}
 
-impl<T> Arc<T> {
+// Unlike `alloc::sync::Arc`, which also stores a weak count, the only
+// overhead of an `Arc` over its data is a single reference counter.
+const _: () = assert!(
+    core::mem::size_of::<ArcInner<usize, AtomicCounter>>()
+        == 2 * core::mem::size_of::<usize>()
+);
+const _: () = assert!(
+    core::mem::size_of::<ArcInner<usize, LocalCounter>>()
+        == 2 * core::mem::size_of::<usize>()
+);
+
+impl<T, C: RefCounter> Arc<T, C> {
     #[inline]
     pub(super) fn get_mut(this: &mut Self) -> Option<&mut T> {
         if this.is_unique() {
@@ -42,7 +74,7 @@ impl<T> Arc<T> {
     }
 
     #[inline]
-    fn inner(&self) -> &ArcInner<T> {
+    fn inner(&self) -> &ArcInner<T, C> {
         // SAFETY: the inner pointer is valid as long as there's at least one
         // `Arc` pointing to it.
         unsafe { self.ptr.as_ref() }
@@ -50,28 +82,47 @@ impl<T> Arc<T> {
 
     #[inline]
     fn is_unique(&self) -> bool {
-        self.inner().counter.load(atomic::Ordering::Relaxed) == 1
+        C::load(&self.inner().counter, atomic::Ordering::Relaxed) == 1
+    }
+
+    /// Allocates the memory for a new `ArcInner<T>` without initializing it.
+    #[inline]
+    fn allocate() -> NonNull<ArcInner<T, C>> {
+        allocation::allocate(Layout::new::<ArcInner<T, C>>()).cast()
     }
 
     #[inline]
     pub(super) fn new(data: T) -> Self {
-        let inner = ArcInner { counter: atomic::AtomicUsize::new(1), data };
+        let inner = ArcInner { counter: C::new(1), data };
+
+        let ptr = Self::allocate();
 
-        // SAFETY: the pointer returned by `Box::into_raw()` is guaranteed to
-        // be non-null.
-        let ptr =
-            unsafe { NonNull::new_unchecked(Box::into_raw(Box::new(inner))) };
+        // SAFETY: the pointer was just allocated with the layout of an
+        // `ArcInner<T>`.
+        unsafe { ptr.as_ptr().write(inner) };
 
         Self { ptr }
     }
 
+    /// Returns the number of bytes allocated by each `Arc<T>`.
+    #[inline]
+    pub(super) const fn alloc_size() -> usize {
+        core::mem::size_of::<ArcInner<T, C>>()
+    }
+
     #[inline]
     pub(super) fn ptr_eq(this: &Self, other: &Self) -> bool {
         this.ptr == other.ptr
     }
+
+    /// Returns the number of `Arc`s pointing to the same value as `this`.
+    #[inline]
+    pub(super) fn strong_count(this: &Self) -> usize {
+        C::load(&this.inner().counter, atomic::Ordering::Relaxed)
+    }
 }
 
-impl<T: Clone> Arc<T> {
+impl<T: Clone, C: RefCounter> Arc<T, C> {
     #[inline]
     pub(super) fn make_mut(this: &mut Self) -> &mut T {
         if !this.is_unique() {
@@ -86,40 +137,39 @@ impl<T: Clone> Arc<T> {
     fn optimized_clone(&self) -> Self {
         // See the homonymous function in `rclite` for more details.
 
-        let mut buffer: Box<MaybeUninit<ArcInner<T>>> =
-            Box::new(MaybeUninit::uninit());
+        let ptr = Self::allocate();
 
-        let ptr = unsafe {
-            let ptr = buffer.as_mut_ptr();
+        unsafe {
+            let ptr = ptr.as_ptr();
             // Here we use `write()` instead of assignment via `=` to avoid
             // dropping the old, uninitialized value.
             addr_of_mut!((*ptr).data).write(T::clone(self));
-            (*ptr).counter = atomic::AtomicUsize::new(1);
-            NonNull::new_unchecked(Box::into_raw(buffer) as *mut ArcInner<T>)
+            addr_of_mut!((*ptr).counter).write(C::new(1));
         };
 
         Arc { ptr }
     }
 }
 
-impl<T: core::fmt::Debug> core::fmt::Debug for Arc<T> {
+impl<T: core::fmt::Debug, C: RefCounter> core::fmt::Debug for Arc<T, C> {
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         core::fmt::Debug::fmt(&**self, f)
     }
 }
 
-impl<T: Default> Default for Arc<T> {
+impl<T: Default, C: RefCounter> Default for Arc<T, C> {
     #[inline]
     fn default() -> Self {
         Self::new(T::default())
     }
 }
 
-impl<T> Clone for Arc<T> {
+impl<T, C: RefCounter> Clone for Arc<T, C> {
     #[inline]
     fn clone(&self) -> Self {
-        let old = self.inner().counter.fetch_add(1, atomic::Ordering::Relaxed);
+        let old =
+            C::fetch_add(&self.inner().counter, 1, atomic::Ordering::Relaxed);
 
         // Check for overflow on the counter. See the `Arc` implementation in
         // `alloc` for more details.
@@ -132,7 +182,7 @@ impl<T> Clone for Arc<T> {
     }
 }
 
-impl<T> core::ops::Deref for Arc<T> {
+impl<T, C: RefCounter> core::ops::Deref for Arc<T, C> {
     type Target = T;
 
     #[inline]
@@ -141,17 +191,170 @@ impl<T> core::ops::Deref for Arc<T> {
     }
 }
 
-impl<T> Drop for Arc<T> {
+impl<T, C: RefCounter> Drop for Arc<T, C> {
     #[inline]
     fn drop(&mut self) {
-        let old = self.inner().counter.fetch_sub(1, atomic::Ordering::Release);
+        let old =
+            C::fetch_sub(&self.inner().counter, 1, atomic::Ordering::Release);
 
         if old == 1 {
-            atomic::fence(atomic::Ordering::Acquire);
+            if C::IS_ATOMIC {
+                atomic::fence(atomic::Ordering::Acquire);
+            }
 
             // SAFETY: this is the last owner of the `Arc` so the memory has
-            // not yet been reclaimed by a previous call to `Box::from_raw()`.
-            let _ = unsafe { Box::from_raw(self.ptr.as_ptr()) };
+            // not yet been reclaimed by a previous call to `deallocate()`.
+            unsafe {
+                core::ptr::drop_in_place(self.ptr.as_ptr());
+                allocation::deallocate(
+                    self.ptr.cast(),
+                    Layout::new::<ArcInner<T, C>>(),
+                );
+            }
+        }
+    }
+}
+
+mod allocation {
+    //! Allocation and deallocation of the memory of an `Arc`.
+
+    #[cfg(not(feature = "node-pool"))]
+    pub(super) use global::{allocate, deallocate};
+    #[cfg(feature = "node-pool")]
+    pub(super) use pool::{allocate, deallocate};
+
+    #[cfg(not(feature = "node-pool"))]
+    mod global {
+        //! Goes straight to the global allocator.
+
+        use core::alloc::Layout;
+        use core::ptr::NonNull;
+
+        #[inline]
+        pub(in super::super) fn allocate(layout: Layout) -> NonNull<u8> {
+            debug_assert!(layout.size() > 0);
+
+            // SAFETY: the layout of an `ArcInner` is never zero-sized.
+            let ptr = unsafe { alloc::alloc::alloc(layout) };
+
+            NonNull::new(ptr)
+                .unwrap_or_else(|| alloc::alloc::handle_alloc_error(layout))
+        }
+
+        /// # Safety
+        ///
+        /// The pointer must have been returned by [`allocate()`] called with
+        /// the same layout, and must not be used afterwards.
+        #[inline]
+        pub(in super::super) unsafe fn deallocate(
+            ptr: NonNull<u8>,
+            layout: Layout,
+        ) {
+            alloc::alloc::dealloc(ptr.as_ptr(), layout);
+        }
+    }
+
+    #[cfg(feature = "node-pool")]
+    mod pool {
+        //! A thread-local pool of recycled allocations.
+        //!
+        //! Editing a `Rope` replaces every node on the path from the root to
+        //! the edited leaf, so a fast stream of edits keeps allocating and
+        //! freeing nodes of the same few sizes. Keeping the freed memory
+        //! around and handing it back out avoids going through the global
+        //! allocator every time.
+
+        use alloc::vec::Vec;
+        use core::alloc::Layout;
+        use core::cell::RefCell;
+        use core::ptr::NonNull;
+
+        /// The maximum number of allocations kept for each layout. Anything
+        /// freed past this is given back to the global allocator.
+        const MAX_POOLED: usize = 256;
+
+        #[derive(Default)]
+        struct Pool {
+            /// The free allocations of each layout. There are only a handful
+            /// of distinct layouts so a linear scan is the fastest lookup.
+            free: Vec<(Layout, Vec<NonNull<u8>>)>,
+        }
+
+        impl Pool {
+            #[inline]
+            fn free_list(&mut self, layout: Layout) -> &mut Vec<NonNull<u8>> {
+                let idx = self.free.iter().position(|&(l, _)| l == layout);
+
+                let idx = idx.unwrap_or_else(|| {
+                    self.free.push((layout, Vec::new()));
+                    self.free.len() - 1
+                });
+
+                &mut self.free[idx].1
+            }
+        }
+
+        impl Drop for Pool {
+            fn drop(&mut self) {
+                for (layout, free_list) in self.free.drain(..) {
+                    for ptr in free_list {
+                        // SAFETY: every pointer in the pool was allocated
+                        // with the layout it's stored with.
+                        unsafe { alloc::alloc::dealloc(ptr.as_ptr(), layout) };
+                    }
+                }
+            }
+        }
+
+        std::thread_local! {
+            static POOL: RefCell<Pool> = RefCell::new(Pool::default());
+        }
+
+        #[inline]
+        pub(in super::super) fn allocate(layout: Layout) -> NonNull<u8> {
+            debug_assert!(layout.size() > 0);
+
+            let recycled = POOL
+                .try_with(|pool| pool.borrow_mut().free_list(layout).pop())
+                .ok()
+                .flatten();
+
+            recycled.unwrap_or_else(|| {
+                // SAFETY: the layout of an `ArcInner` is never zero-sized.
+                let ptr = unsafe { alloc::alloc::alloc(layout) };
+
+                NonNull::new(ptr).unwrap_or_else(|| {
+                    alloc::alloc::handle_alloc_error(layout)
+                })
+            })
+        }
+
+        /// # Safety
+        ///
+        /// The pointer must have been returned by [`allocate()`] called with
+        /// the same layout, and must not be used afterwards.
+        #[inline]
+        pub(in super::super) unsafe fn deallocate(
+            ptr: NonNull<u8>,
+            layout: Layout,
+        ) {
+            // The pool can't be accessed while its thread-local is being
+            // destroyed, in which case we just free the memory.
+            let pooled = POOL
+                .try_with(|pool| {
+                    let mut pool = pool.borrow_mut();
+                    let free_list = pool.free_list(layout);
+                    let has_room = free_list.len() < MAX_POOLED;
+                    if has_room {
+                        free_list.push(ptr);
+                    }
+                    has_room
+                })
+                .unwrap_or(false);
+
+            if !pooled {
+                alloc::alloc::dealloc(ptr.as_ptr(), layout);
+            }
         }
     }
 }
@@ -173,3 +376,141 @@ mod predictions {
     #[cold]
     fn cold() {}
 }
+
+mod counter {
+    //! The reference counters of an `Arc`.
+
+    use core::cell::Cell;
+    use core::marker::PhantomData;
+    use core::sync::atomic::{AtomicUsize, Ordering};
+
+    /// The kind of reference counter used by the nodes of a
+    /// [`Tree`](super::super::Tree), which decides whether the tree can be
+    /// shared between threads.
+    ///
+    /// This trait is sealed and implemented by [`AtomicCounter`] and
+    /// [`LocalCounter`].
+    pub trait RefCounter:
+        sealed::Sealed + Copy + Default + core::fmt::Debug + 'static
+    {
+    }
+
+    /// Atomic reference counting, the default. Trees using it are `Send` and
+    /// `Sync`.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct AtomicCounter;
+
+    /// Non-atomic reference counting, which makes cloning and editing trees
+    /// cheaper in single-threaded programs at the cost of them being neither
+    /// `Send` nor `Sync`.
+    #[derive(Clone, Copy, Debug, Default)]
+    pub struct LocalCounter(PhantomData<*const ()>);
+
+    impl RefCounter for AtomicCounter {}
+
+    impl RefCounter for LocalCounter {}
+
+    mod sealed {
+        use super::*;
+
+        /// The API of an `AtomicUsize` needed by an `Arc`.
+        pub trait Sealed {
+            /// The counter stored next to the data of an `Arc`.
+            type Count;
+
+            /// Whether the counter can be shared between threads, in which
+            /// case the last `Arc` has to synchronize with the other ones
+            /// before dropping the data.
+            const IS_ATOMIC: bool;
+
+            fn new(value: usize) -> Self::Count;
+
+            fn load(count: &Self::Count, ordering: Ordering) -> usize;
+
+            fn fetch_add(
+                count: &Self::Count,
+                value: usize,
+                ordering: Ordering,
+            ) -> usize;
+
+            fn fetch_sub(
+                count: &Self::Count,
+                value: usize,
+                ordering: Ordering,
+            ) -> usize;
+        }
+
+        impl Sealed for AtomicCounter {
+            type Count = AtomicUsize;
+
+            const IS_ATOMIC: bool = true;
+
+            #[inline(always)]
+            fn new(value: usize) -> AtomicUsize {
+                AtomicUsize::new(value)
+            }
+
+            #[inline(always)]
+            fn load(count: &AtomicUsize, ordering: Ordering) -> usize {
+                count.load(ordering)
+            }
+
+            #[inline(always)]
+            fn fetch_add(
+                count: &AtomicUsize,
+                value: usize,
+                ordering: Ordering,
+            ) -> usize {
+                count.fetch_add(value, ordering)
+            }
+
+            #[inline(always)]
+            fn fetch_sub(
+                count: &AtomicUsize,
+                value: usize,
+                ordering: Ordering,
+            ) -> usize {
+                count.fetch_sub(value, ordering)
+            }
+        }
+
+        /// The memory orderings are ignored.
+        impl Sealed for LocalCounter {
+            type Count = Cell<usize>;
+
+            const IS_ATOMIC: bool = false;
+
+            #[inline(always)]
+            fn new(value: usize) -> Cell<usize> {
+                Cell::new(value)
+            }
+
+            #[inline(always)]
+            fn load(count: &Cell<usize>, _: Ordering) -> usize {
+                count.get()
+            }
+
+            #[inline(always)]
+            fn fetch_add(
+                count: &Cell<usize>,
+                value: usize,
+                _: Ordering,
+            ) -> usize {
+                let old = count.get();
+                count.set(old.wrapping_add(value));
+                old
+            }
+
+            #[inline(always)]
+            fn fetch_sub(
+                count: &Cell<usize>,
+                value: usize,
+                _: Ordering,
+            ) -> usize {
+                let old = count.get();
+                count.set(old.wrapping_sub(value));
+                old
+            }
+        }
+    }
+}
@@ -1,7 +1,14 @@
 use core::fmt::Debug;
-use core::ops::{Add, AddAssign, RangeBounds, Sub, SubAssign};
+use core::ops::{Add, AddAssign, Range, RangeBounds, Sub, SubAssign};
 
+/// Types that can be summarized into a value which is cheap to add and
+/// subtract, like the number of bytes and line breaks of a string.
+///
+/// Every node of a [`Tree`](super::Tree) stores the sum of the summaries of
+/// all the leaves under it.
 pub trait Summarize: Debug {
+    /// The summary of the type. Adding the summaries of two values should
+    /// give the summary of their concatenation.
     type Summary: Debug
         + Default
         + Clone
@@ -11,25 +18,53 @@ pub trait Summarize: Debug {
         + for<'a> SubAssign<&'a Self::Summary>
         + PartialEq<Self::Summary>;
 
+    /// Whether subtracting the summary of a piece of a value from the summary
+    /// of the value always gives the summary of the rest of it.
+    ///
+    /// If it doesn't (e.g. because the summary keeps the longest line of a
+    /// string, which can't be taken back out) the summaries stored in a
+    /// [`Tree`](super::Tree) are recomputed, from the leaves themselves and
+    /// from the summaries of the children of the nodes, instead of being
+    /// updated with the difference every time they change. Adding two
+    /// summaries then has to combine them in order, i.e. the left one has to
+    /// be the summary of the text before the right one.
+    const SUMMARY_IS_SUBTRACTABLE: bool = true;
+
+    /// Returns the summary of `self`.
     fn summarize(&self) -> Self::Summary;
 }
 
+/// Types with a metric that's fine-grained enough to address every position
+/// in them, like the byte offsets of a string.
 pub trait BaseMeasured: Summarize {
+    /// The finest-grained metric of the type, used to address positions in
+    /// [`Tree`](super::Tree)s and [`TreeSlice`](super::TreeSlice)s when no
+    /// other metric is given.
     type BaseMetric: Metric<Self::Summary>;
 }
 
+/// Types that can be borrowed as a (cheap to copy) slice.
 pub trait AsSlice: Summarize {
+    /// The borrowed slice. Its summary has to be the same as the summary of
+    /// the value it was borrowed from.
     type Slice<'a>: Copy + Summarize<Summary = Self::Summary>
     where
         Self: 'a;
 
+    /// Returns a slice over the whole of `self`.
     fn as_slice(&self) -> Self::Slice<'_>;
 }
 
+/// The leaves of a [`Tree`](super::Tree).
+///
+/// This is implemented for every type implementing [`Summarize`],
+/// [`BaseMeasured`] and [`AsSlice`].
 pub trait Leaf: Summarize + BaseMeasured + AsSlice {}
 
 impl<T: Summarize + BaseMeasured + AsSlice> Leaf for T {}
 
+/// Leaves which can be too small to be on their own, and which can then be
+/// rebalanced with one of their neighbours.
 pub trait BalancedLeaf: Leaf + for<'a> From<Self::Slice<'a>> {
     /// Returns whether the leaf node is too small to be on its own and should
     /// be rebalanced with another leaf.
@@ -45,9 +80,13 @@ pub trait BalancedLeaf: Leaf + for<'a> From<Self::Slice<'a>> {
     );
 }
 
+/// Leaves whose contents can be edited in the ranges of the `M`-metric.
 pub trait ReplaceableLeaf<M: Metric<Self::Summary>>: BalancedLeaf {
+    /// The content that can be inserted in a leaf, like a `&str`.
     type Replacement<'a>;
 
+    /// The iterator returned by [`replace`](Self::replace()) when the
+    /// replacement doesn't fit in a single leaf.
     type ExtraLeaves: ExactSizeIterator<Item = Self>;
 
     /// Replace the contents of the leaf in the range with the given
@@ -66,9 +105,33 @@ pub trait ReplaceableLeaf<M: Metric<Self::Summary>>: BalancedLeaf {
     where
         R: RangeBounds<M>;
 
+    /// Removes the contents of the leaf up to the given `M`-offset.
     fn remove_up_to(&mut self, summary: &mut Self::Summary, up_to: M);
+
+    /// Returns `true` if replacing the contents of the leaf in the range
+    /// with the given replacement is guaranteed to leave the leaf neither too
+    /// big nor underfilled, i.e. if [`replace`](Self::replace()) would return
+    /// `None` and the leaf wouldn't need to be rebalanced afterwards.
+    ///
+    /// Returning `false` is always correct, it just means the `Tree` won't
+    /// be able to take some shortcuts when editing the leaf. This is what the
+    /// default implementation does.
+    #[inline]
+    fn replace_fits(
+        &self,
+        _summary: &Self::Summary,
+        _range: Range<M>,
+        _replace_with: &Self::Replacement<'_>,
+    ) -> bool {
+        false
+    }
 }
 
+/// A way of measuring a summary, like the number of bytes or of line breaks
+/// it contains.
+///
+/// Metrics are used to index into [`Tree`](super::Tree)s, to slice them and
+/// to iterate over their units.
 pub trait Metric<Summary: ?Sized>:
     Debug
     + Copy
@@ -94,12 +157,16 @@ pub trait Metric<Summary: ?Sized>:
 
 /// Metrics that can be used to slice `Tree`s and `TreeSlice`s.
 pub trait SlicingMetric<L: Leaf>: Metric<L::Summary> {
+    /// Returns the left sub-slice of `slice` up to the given `M`-offset,
+    /// together with its summary.
     fn slice_up_to<'a>(
         slice: L::Slice<'a>,
         up_to: Self,
         summary: &L::Summary,
     ) -> (L::Slice<'a>, L::Summary);
 
+    /// Returns the right sub-slice of `slice` starting from the given
+    /// `M`-offset, together with its summary.
     fn slice_from<'a>(
         slice: L::Slice<'a>,
         from: Self,
@@ -125,6 +192,16 @@ pub trait UnitMetric<L: Leaf>: Metric<L::Summary> {
         slice: L::Slice<'a>,
         summary: &L::Summary,
     ) -> (L::Slice<'a>, L::Summary, L::Summary, L::Slice<'a>, L::Summary);
+
+    /// Returns the number of units in the given measure, or `None` if it
+    /// can't be known without iterating over them.
+    ///
+    /// This is used by [`Units`](super::Units) to count the units it has
+    /// left without yielding them. The default implementation returns `None`.
+    #[inline]
+    fn num_units(_measure: Self) -> Option<usize> {
+        None
+    }
 }
 
 /// Allows iterating backward over the units of this metric.
@@ -3,19 +3,137 @@ use core::ops::Range;
 use super::*;
 
 /// A self-balancing tree with metadata stored in each node.
-#[derive(Default)]
-pub struct Tree<const ARITY: usize, L: Leaf> {
-    pub(super) root: Arc<Node<ARITY, L>>,
+pub struct Tree<const ARITY: usize, L: Leaf, C: RefCounter = AtomicCounter> {
+    pub(super) root: Arc<Node<ARITY, L, C>, C>,
+
+    /// The path to the leaf modified by the last call to
+    /// [`replace`](Self::replace()).
+    ///
+    /// It's boxed so that it doesn't make every `Tree` bigger, and it's only
+    /// allocated the first time a `Tree` with more than one leaf is edited.
+    edit_hint: Option<Box<EditHint<L>>>,
+}
+
+/// The path from the root of a `Tree` down to one of its leaves.
+///
+/// Consecutive edits tend to happen close to each other, so after an edit
+/// that didn't change the structure of the tree we remember which leaf was
+/// modified. If the next edit falls within the same leaf and doesn't cause it
+/// to be split or rebalanced we can follow the path straight down to it,
+/// without having to look for the child containing the range at every level.
+///
+/// The hint is cleared every time the structure of the tree changes, since
+/// the indices in the path could then point to different nodes.
+pub(super) struct EditHint<L: Leaf> {
+    /// The indices of the children to follow from the root to get to the
+    /// leaf. It's empty if there's no hint.
+    path: Vec<usize>,
+
+    /// The summary of the tree up to the start of the leaf.
+    offset: L::Summary,
+
+    /// Whether the hint being recorded by the current edit is still valid.
+    is_valid: bool,
 }
 
-impl<const ARITY: usize, L: Leaf> Clone for Tree<ARITY, L> {
+impl<L: Leaf> Default for EditHint<L> {
+    #[inline]
+    fn default() -> Self {
+        Self {
+            path: Vec::new(),
+            offset: L::Summary::default(),
+            is_valid: false,
+        }
+    }
+}
+
+impl<L: Leaf> EditHint<L> {
+    #[inline]
+    fn clear(&mut self) {
+        self.path.clear();
+        self.offset = L::Summary::default();
+        self.is_valid = false;
+    }
+
+    #[inline]
+    pub(super) fn invalidate(&mut self) {
+        self.is_valid = false;
+    }
+
+    #[inline]
+    fn is_empty(&self) -> bool {
+        self.path.is_empty()
+    }
+
+    /// Records that the edit descended into the child at index `child_idx`,
+    /// whose offset in its parent is `offset`.
+    #[inline]
+    pub(super) fn push(&mut self, child_idx: usize, offset: &L::Summary) {
+        self.path.push(child_idx);
+        self.offset += offset;
+    }
+
+    /// Replaces the hint with the path to the last leaf under `root`.
+    #[inline]
+    fn follow_last_leaf<const N: usize, C: RefCounter>(
+        &mut self,
+        root: &Node<N, L, C>,
+    ) {
+        self.clear();
+
+        let mut node = root;
+
+        while let Node::Internal(inode) = node {
+            let last = inode.len() - 1;
+            let offset = inode.summary().clone() - inode.child(last).summary();
+            self.push(last, &offset);
+            node = &**inode.child(last);
+        }
+    }
+
+    #[inline]
+    fn start(&mut self) {
+        self.clear();
+        self.is_valid = true;
+    }
+}
+
+/// A node of a `Tree` in the pre-order walk done by
+/// [`for_each_node()`](Tree::for_each_node()), from which
+/// [`from_preorder()`](Tree::from_preorder()) can build the same tree back.
+pub(crate) enum PreorderNode<L, S> {
+    /// An internal node, followed in the walk by the subtrees of its
+    /// `children`.
+    Internal {
+        children: usize,
+        summary: S,
+    },
+
+    Leaf {
+        leaf: L,
+        summary: S,
+    },
+}
+
+impl<const ARITY: usize, L: Leaf, C: RefCounter> Clone for Tree<ARITY, L, C> {
     #[inline]
     fn clone(&self) -> Self {
-        Tree { root: Arc::clone(&self.root) }
+        Tree::new(Arc::clone(&self.root))
     }
 }
 
-impl<const ARITY: usize, L: Leaf> core::fmt::Debug for Tree<ARITY, L> {
+impl<const ARITY: usize, L: Leaf + Default, C: RefCounter> Default
+    for Tree<ARITY, L, C>
+{
+    #[inline]
+    fn default() -> Self {
+        Tree::new(Arc::default())
+    }
+}
+
+impl<const ARITY: usize, L: Leaf, C: RefCounter> core::fmt::Debug
+    for Tree<ARITY, L, C>
+{
     #[inline]
     fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
         if !f.alternate() {
@@ -26,11 +144,11 @@ impl<const ARITY: usize, L: Leaf> core::fmt::Debug for Tree<ARITY, L> {
     }
 }
 
-impl<const ARITY: usize, L: BalancedLeaf + Clone> From<TreeSlice<'_, ARITY, L>>
-    for Tree<ARITY, L>
+impl<const ARITY: usize, L: BalancedLeaf + Clone, C: RefCounter>
+    From<TreeSlice<'_, ARITY, L, C>> for Tree<ARITY, L, C>
 {
     #[inline]
-    fn from(slice: TreeSlice<'_, ARITY, L>) -> Tree<ARITY, L> {
+    fn from(slice: TreeSlice<'_, ARITY, L, C>) -> Tree<ARITY, L, C> {
         let root = if slice.base_measure() == slice.root().base_measure() {
             // If the TreeSlice and its root have the same base measure it
             // means the TreeSlice spanned the whole Tree from which it was
@@ -55,7 +173,7 @@ impl<const ARITY: usize, L: BalancedLeaf + Clone> From<TreeSlice<'_, ARITY, L>>
             if !second.is_empty() {
                 let second = Arc::new(Node::Leaf(second));
                 let root = Inode::from_children([first, second]);
-                Arc::new(Node::Internal(root))
+                Arc::new(Node::internal(root))
             } else {
                 first
             }
@@ -63,11 +181,85 @@ impl<const ARITY: usize, L: BalancedLeaf + Clone> From<TreeSlice<'_, ARITY, L>>
             from_treeslice::into_tree_root(slice)
         };
 
-        Tree { root }
+        Tree::new(root)
     }
 }
 
-impl<const ARITY: usize, L: Leaf> Tree<ARITY, L> {
+impl<const ARITY: usize, L: Leaf, C: RefCounter> Tree<ARITY, L, C> {
+    /// Appends the leaves of `other` after the leaves of this `Tree`.
+    ///
+    /// The shallower of the two roots is attached to the side of the deeper
+    /// one at the right depth, so this only touches the nodes on that side
+    /// and runs in time logarithmic in the number of leaves of the two
+    /// `Tree`s.
+    #[inline]
+    pub fn append(&mut self, other: Self)
+    where
+        L: BalancedLeaf + Clone + Default,
+    {
+        if other.base_measure() == L::BaseMetric::zero() {
+            return;
+        }
+
+        if self.base_measure() == L::BaseMetric::zero() {
+            *self = other;
+            return;
+        }
+
+        let mut left = core::mem::take(&mut self.root);
+
+        let mut right = other.root;
+
+        use core::cmp::Ordering::*;
+
+        let root = match left.depth().cmp(&right.depth()) {
+            Equal => {
+                Arc::make_mut(&mut left).balance(Arc::make_mut(&mut right));
+
+                if right.is_empty() {
+                    left
+                } else {
+                    let root = Inode::from_children([left, right]);
+                    Arc::new(Node::internal(root))
+                }
+            },
+
+            Greater => {
+                let extra = {
+                    let l = Arc::make_mut(&mut left).get_internal_mut();
+                    l.append_at_depth(right)
+                };
+
+                match extra {
+                    Some(extra) => {
+                        let extra = Arc::new(Node::internal(extra));
+                        let root = Inode::from_children([left, extra]);
+                        Arc::new(Node::internal(root))
+                    },
+                    None => left,
+                }
+            },
+
+            Less => {
+                let extra = {
+                    let r = Arc::make_mut(&mut right).get_internal_mut();
+                    r.prepend_at_depth(left)
+                };
+
+                match extra {
+                    Some(extra) => {
+                        let extra = Arc::new(Node::internal(extra));
+                        let root = Inode::from_children([extra, right]);
+                        Arc::new(Node::internal(root))
+                    },
+                    None => right,
+                }
+            },
+        };
+
+        *self = Self::new(root);
+    }
+
     #[doc(hidden)]
     pub fn assert_invariants(&self) {
         match &*self.root {
@@ -76,155 +268,1064 @@ impl<const ARITY: usize, L: Leaf> Tree<ARITY, L> {
                 // children.
                 assert!(root.len() >= 2 && root.len() <= ARITY);
 
-                for child in root.children() {
-                    child.assert_invariants()
+                for child in root.children() {
+                    child.assert_invariants()
+                }
+            },
+
+            Node::Leaf(leaf) => leaf.assert_invariants(),
+        }
+    }
+
+    /// Returns the measure of this `Tree` according to the
+    /// [`BaseMetric`](BaseMeasured::BaseMetric) of its leaves.
+    #[inline]
+    pub fn base_measure(&self) -> L::BaseMetric {
+        self.measure::<L::BaseMetric>()
+    }
+
+    /// Returns the `M2`-measure of all the leaves before `up_to` plus the
+    /// `M2`-measure of the left sub-slice of the leaf at `up_to`.
+    #[track_caller]
+    #[inline]
+    pub fn convert_measure<M1, M2>(&self, up_to: M1) -> M2
+    where
+        M1: SlicingMetric<L>,
+        M2: Metric<L::Summary>,
+    {
+        debug_assert!(up_to <= self.measure::<M1>());
+        self.root.convert_measure(up_to)
+    }
+
+    /// Returns a [`Cursor`] over the leaves of this `Tree`, starting at its
+    /// first leaf.
+    #[inline]
+    pub fn cursor(&self) -> Cursor<'_, ARITY, L, C> {
+        Cursor::from(self)
+    }
+
+    /// Returns the depth of this `Tree`, i.e. the number of inodes between
+    /// its root and its leaves, including the root. A `Tree` with a single
+    /// leaf has a depth of zero.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.root.depth()
+    }
+
+    /// Calls `fun` on every leaf of this `Tree`, in order.
+    #[inline]
+    pub(crate) fn for_each_leaf(&self, mut fun: impl FnMut(&L)) {
+        self.root.for_each_leaf(&mut fun);
+    }
+
+    /// Calls `fun` on every node of this `Tree` in pre-order, i.e. on every
+    /// internal node before the nodes under it.
+    #[inline]
+    pub(crate) fn for_each_node(
+        &self,
+        mut fun: impl FnMut(PreorderNode<&L, &L::Summary>),
+    ) {
+        preorder::visit(&self.root, &mut fun);
+    }
+
+    /// Calls `fun` on the summaries of the subtrees of this `Tree` in order,
+    /// descending into the internal nodes whose summary `descend` returns
+    /// `true` on.
+    ///
+    /// This is used to find the few places of the tree where the summaries
+    /// of two adjacent subtrees don't tell everything about their text.
+    #[cfg(feature = "paragraph-count")]
+    #[inline]
+    pub(crate) fn for_each_subtree(
+        &self,
+        mut descend: impl FnMut(&L::Summary) -> bool,
+        mut fun: impl FnMut(&L::Summary),
+    ) {
+        self.root.for_each_subtree(&mut descend, &mut fun);
+    }
+
+    /// Calls `fun` on every leaf of this `Tree` which isn't shared with other
+    /// `Tree`s, i.e. that can be mutated without being cloned first.
+    ///
+    /// The summaries of the nodes are not updated, so `fun` must not change
+    /// the contents of the leaves, only how they're stored.
+    #[cfg(feature = "compression")]
+    #[inline]
+    pub(crate) fn for_each_unshared_leaf_mut(
+        &mut self,
+        mut fun: impl FnMut(&mut L),
+    ) {
+        if let Some(root) = Arc::get_mut(&mut self.root) {
+            root.for_each_unshared_leaf_mut(&mut fun);
+        }
+    }
+
+    /// Creates a new `Tree` from a sequence of leaves.
+    ///
+    /// If the iterator doesn't yield any items the `Tree` will contain a
+    /// single leaf with its default value.
+    #[inline]
+    pub fn from_leaves<I>(leaves: I) -> Self
+    where
+        I: IntoIterator<Item = L>,
+        L: Default,
+    {
+        Self::from_summarized_leaves(leaves.into_iter().map(|leaf| {
+            let summary = leaf.summarize();
+            (leaf, summary)
+        }))
+    }
+
+    /// Creates a new `Tree` from a sequence of leaves whose summaries are
+    /// already known, which avoids having to re-summarize them.
+    ///
+    /// Every summary has to be the same as the one returned by calling
+    /// `summarize()` on its leaf.
+    ///
+    /// If the iterator doesn't yield any items the `Tree` will contain a
+    /// single leaf with its default value.
+    #[inline]
+    pub fn from_summarized_leaves<I>(leaves: I) -> Self
+    where
+        I: IntoIterator<Item = (L, L::Summary)>,
+        L: Default,
+    {
+        let mut levels = from_leaves::Levels::new();
+
+        for leaf in leaves {
+            levels.push_leaf(Arc::new(Node::Leaf(Lnode::from(leaf))));
+        }
+
+        match levels.finish() {
+            Some(root) => Self::new(root),
+            None => Self::default(),
+        }
+    }
+
+    /// Builds a `Tree` back from the nodes yielded by
+    /// [`for_each_node()`](Self::for_each_node()), reusing their summaries.
+    ///
+    /// The summaries of the leaves are trusted to be the same as the ones
+    /// returned by calling `summarize()` on them, while the ones of the
+    /// internal nodes are checked to be the sum of the summaries of their
+    /// children if `check_summaries` is true.
+    ///
+    /// Returns `None` if the nodes don't describe a valid tree, i.e. if an
+    /// internal node has too few or too many children, if the leaves are at
+    /// different depths, if a checked summary is wrong or if there are nodes
+    /// left over.
+    #[inline]
+    pub(crate) fn from_preorder<I>(
+        nodes: I,
+        check_summaries: bool,
+    ) -> Option<Self>
+    where
+        I: IntoIterator<Item = PreorderNode<L, L::Summary>>,
+    {
+        let mut nodes = nodes.into_iter();
+
+        let root = preorder::build(&mut nodes, true, check_summaries)?;
+
+        nodes.next().is_none().then(|| Self::new(root))
+    }
+
+    /// Returns the number of bytes allocated for the nodes of this `Tree`,
+    /// not including the heap allocations owned by its leaves.
+    ///
+    /// Nodes shared with other `Tree`s are counted as if they were owned by
+    /// this `Tree`.
+    #[inline]
+    pub fn heap_size(&self) -> usize {
+        Arc::<Node<ARITY, L, C>, C>::alloc_size() + self.root.heap_size()
+    }
+
+    /// Returns the last leaf of this `Tree`.
+    #[inline]
+    pub fn last_leaf(&self) -> L::Slice<'_> {
+        let mut node = &*self.root;
+
+        loop {
+            match node {
+                Node::Internal(inode) => node = &**inode.last(),
+                Node::Leaf(leaf) => return leaf.as_slice(),
+            }
+        }
+    }
+
+    /// Returns a description of the nodes of this `Tree` in the
+    /// [DOT](https://graphviz.org/doc/info/lang.html) language, which can be
+    /// rendered with Graphviz (e.g. `dot -Tsvg`) to look at its shape.
+    ///
+    /// Every node is labelled with its summary and with the number of
+    /// references to it. A node with more than one reference is shared with
+    /// at least another `Tree`, e.g. one this `Tree` was cloned from.
+    #[inline]
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph {\n");
+        dot::write_node(&self.root, &mut 0, &mut dot);
+        dot.push_str("}\n");
+        dot
+    }
+
+    /// Returns the leaf containing the `measure`-th unit of the `M`-metric,
+    /// plus the `M`-measure of all the leaves before it.
+    #[inline]
+    pub fn leaf_at_measure<M>(&self, measure: M) -> (L::Slice<'_>, M)
+    where
+        M: Metric<L::Summary>,
+    {
+        debug_assert!(measure <= self.measure::<M>() + M::one());
+
+        self.root.leaf_at_measure(measure)
+    }
+
+    /// Returns the number of leaves in this `Tree`.
+    #[inline]
+    pub fn leaf_count(&self) -> usize {
+        self.root.leaf_count()
+    }
+
+    /// Returns an iterator over the leaves of this `Tree`.
+    #[inline]
+    pub fn leaves(&self) -> Leaves<'_, ARITY, L, C> {
+        Leaves::from(self)
+    }
+
+    /// Returns the `M`-measure of this `Tree` obtaining by summing up the
+    /// `M`-measures of all its leaves.
+    #[inline]
+    pub fn measure<M>(&self) -> M
+    where
+        M: Metric<L::Summary>,
+    {
+        M::measure(self.summary())
+    }
+
+    #[inline]
+    pub(super) fn new(root: Arc<Node<ARITY, L, C>, C>) -> Self {
+        Self { root, edit_hint: None }
+    }
+
+    /// Inserts the given content at the `M`-offset.
+    ///
+    /// The content is inserted in the leaf containing the offset, which can
+    /// split into several leaves via [`ReplaceableLeaf::replace()`] if it
+    /// becomes too big.
+    #[track_caller]
+    #[inline]
+    pub fn insert<M>(&mut self, at: M, insert: L::Replacement<'_>)
+    where
+        M: Metric<L::Summary>,
+        L: ReplaceableLeaf<M> + Clone,
+    {
+        self.replace(at..at, insert);
+    }
+
+    /// Removes the `M`-range from the `Tree`.
+    ///
+    /// Leaves left underfilled by the removal are merged with their
+    /// neighbours via [`BalancedLeaf::balance_leaves()`].
+    #[track_caller]
+    #[inline]
+    pub fn remove<M>(&mut self, range: Range<M>)
+    where
+        M: Metric<L::Summary>,
+        L: ReplaceableLeaf<M> + Clone,
+        for<'a> L::Replacement<'a>: Default,
+    {
+        self.replace(range, L::Replacement::default());
+    }
+
+    /// Replaces a range of the `Tree` with the given replacement.
+    #[track_caller]
+    #[inline]
+    pub fn replace<M>(
+        &mut self,
+        range: Range<M>,
+        replace_with: L::Replacement<'_>,
+    ) where
+        M: Metric<L::Summary>,
+        L: ReplaceableLeaf<M> + Clone,
+    {
+        let Err(replace_with) =
+            self.replace_in_hinted_leaf(range.clone(), replace_with)
+        else {
+            return;
+        };
+
+        // A single leaf is edited without descending into the tree, so
+        // there's no path worth remembering.
+        let mut no_hint = EditHint::default();
+
+        let hint = if self.root.is_leaf() {
+            &mut no_hint
+        } else {
+            self.edit_hint.get_or_insert_with(Box::default)
+        };
+
+        hint.start();
+
+        if let Some(extras) =
+            tree_replace::replace(&mut self.root, range, replace_with, hint)
+        {
+            debug_assert!(extras
+                .iter()
+                .all(|n| n.depth() == self.root.depth()));
+
+            self.root = Arc::new(Node::internal(Inode::from_nodes(
+                core::iter::once(Arc::clone(&self.root)).exact_chain(extras),
+            )));
+
+            hint.invalidate();
+        }
+
+        if !hint.is_valid {
+            hint.clear();
+        }
+    }
+
+    /// Applies the replacement directly to the leaf of the
+    /// [`EditHint`], if the range is fully contained in it and the leaf
+    /// doesn't need to be split or rebalanced afterwards. Gives back the
+    /// replacement if that's not the case.
+    #[track_caller]
+    #[inline]
+    fn replace_in_hinted_leaf<'a, M>(
+        &mut self,
+        mut range: Range<M>,
+        replace_with: L::Replacement<'a>,
+    ) -> Result<(), L::Replacement<'a>>
+    where
+        M: Metric<L::Summary>,
+        L: ReplaceableLeaf<M> + Clone,
+    {
+        let Some(hint) = self.edit_hint.as_deref_mut() else {
+            return Err(replace_with);
+        };
+
+        if hint.is_empty() {
+            return Err(replace_with);
+        }
+
+        let leaf_offset = M::measure(&hint.offset);
+
+        if range.start < leaf_offset {
+            return Err(replace_with);
+        }
+
+        range.start -= leaf_offset;
+        range.end -= leaf_offset;
+
+        let root = &mut self.root;
+
+        let mut node = &**root;
+        let mut is_last = true;
+
+        for &child_idx in &hint.path {
+            let inode = node.get_internal();
+            is_last &= child_idx == inode.len() - 1;
+            node = &**inode.child(child_idx);
+        }
+
+        let leaf = node.get_leaf();
+
+        if range.end > leaf.measure::<M>() {
+            return Err(replace_with);
+        }
+
+        if leaf.replace_fits(range.clone(), &replace_with) {
+            tree_replace::replace_along_path(
+                root,
+                &hint.path,
+                range,
+                replace_with,
+            );
+        } else if is_last && range.start == leaf.measure::<M>() {
+            // Appending at the end of the tree only ever adds nodes after the
+            // last leaf, so the path to the new last leaf is known and the
+            // hint can be kept even if the leaf was split.
+            if let Some(extras) = tree_replace::append_along_path(
+                root,
+                &hint.path,
+                range,
+                replace_with,
+            ) {
+                *root = Arc::new(Node::internal(Inode::from_nodes(
+                    core::iter::once(Arc::clone(root)).exact_chain(extras),
+                )));
+            }
+
+            hint.follow_last_leaf(root);
+        } else {
+            return Err(replace_with);
+        }
+
+        Ok(())
+    }
+
+    #[inline]
+    pub(super) fn root(&self) -> &Arc<Node<ARITY, L, C>, C> {
+        &self.root
+    }
+
+    /// Returns the ranges of this `Tree` and of `other` which aren't shared by
+    /// the two trees, measured with the metric `M`.
+    ///
+    /// Every pair holds a range of this `Tree` and the range of `other` that
+    /// takes its place, in increasing order. Trees obtained by editing a clone
+    /// of another tree share all the nodes that weren't affected by the
+    /// edits, and those are skipped without being looked at, so this runs in
+    /// time proportional to the number of nodes touched by the edits.
+    ///
+    /// Leaves that aren't shared are reported as changed even if their
+    /// contents happen to be equal.
+    #[inline]
+    pub fn changed_ranges<M>(&self, other: &Self) -> Vec<(Range<M>, Range<M>)>
+    where
+        M: Metric<L::Summary>,
+    {
+        changed::ranges(&self.root, &other.root)
+    }
+
+    /// Returns `true` if this `Tree` and `other` have the same root, i.e. if
+    /// one is a clone of the other and neither has been edited since.
+    ///
+    /// This runs in constant time. Trees which don't have the same root can
+    /// still contain the same leaves.
+    #[inline]
+    pub fn ptr_eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.root, &other.root)
+    }
+
+    /// Returns the number of leaves of this `Tree` which are shared with
+    /// `other`, i.e. which are stored in the same nodes.
+    ///
+    /// Like [`changed_ranges()`](Self::changed_ranges()), this skips the
+    /// subtrees shared by the two trees without visiting them.
+    #[inline]
+    pub fn shared_leaf_count(&self, other: &Self) -> usize {
+        changed::shared_leaf_count(&self.root, &other.root)
+    }
+
+    /// Returns the summary of the leaves at the start of this `Tree` which
+    /// are shared with `other`, i.e. which are stored in the same nodes.
+    ///
+    /// Trees obtained by editing a clone of another tree share all the
+    /// nodes that weren't affected by the edits, so this is a quick way to
+    /// find a lower bound on the length of their common prefix. It runs in
+    /// time logarithmic in the number of leaves of the two trees.
+    #[inline]
+    pub fn shared_prefix(&self, other: &Self) -> L::Summary {
+        shared::summary(&self.root, &other.root, false)
+    }
+
+    /// Returns the summary of the leaves at the end of this `Tree` which are
+    /// shared with `other`.
+    ///
+    /// See [`shared_prefix()`](Self::shared_prefix()) for more.
+    #[inline]
+    pub fn shared_suffix(&self, other: &Self) -> L::Summary {
+        shared::summary(&self.root, &other.root, true)
+    }
+
+    /// Returns a slice of the `Tree` in the range of the given metric.
+    #[track_caller]
+    #[inline]
+    pub fn slice<M>(&self, range: Range<M>) -> TreeSlice<'_, ARITY, L, C>
+    where
+        M: SlicingMetric<L>,
+        L::BaseMetric: SlicingMetric<L>,
+        for<'d> L::Slice<'d>: Default,
+    {
+        debug_assert!(M::zero() <= range.start);
+        debug_assert!(range.start <= range.end);
+        debug_assert!(range.end <= self.measure::<M>() + M::one());
+
+        TreeSlice::from_range_in_root(&self.root, range)
+    }
+
+    /// Splits the `Tree` at the given `M`-offset, returning the leaves after
+    /// it and keeping the ones before it.
+    ///
+    /// Both halves share all the nodes they don't cut through with the
+    /// original `Tree`, so this runs in time logarithmic in its number of
+    /// leaves.
+    #[track_caller]
+    #[inline]
+    pub fn split_off<M>(&mut self, at: M) -> Self
+    where
+        M: SlicingMetric<L>,
+        L: BalancedLeaf + Clone,
+        L::BaseMetric: SlicingMetric<L>,
+        for<'d> L::Slice<'d>: Default,
+    {
+        debug_assert!(at <= self.measure::<M>());
+
+        let right = Self::from(self.slice(at..self.measure::<M>()));
+
+        *self = Self::from(self.slice(M::zero()..at));
+
+        right
+    }
+
+    /// Returns the summary of this `Tree`, i.e. the sum of the summaries of
+    /// all its leaves.
+    #[inline]
+    pub fn summary(&self) -> &L::Summary {
+        self.root.summary()
+    }
+
+    /// Returns an iterator over the `M`-units of this `Tree`.
+    #[inline]
+    pub fn units<M>(&self) -> Units<'_, ARITY, L, M, C>
+    where
+        M: Metric<L::Summary>,
+        for<'d> L::Slice<'d>: Default,
+    {
+        Units::from(self)
+    }
+
+    /// Checks the invariants of this `Tree`, returning the ones that don't
+    /// hold.
+    ///
+    /// This checks that every internal node has the right number of
+    /// children, that all the leaves are at the same depth, and that the
+    /// summaries and leaf counts cached in every node match the ones
+    /// computed from their contents. An empty `Vec` means the tree is
+    /// well-formed.
+    ///
+    /// This never panics, which makes it suited for fuzzers and for
+    /// anyone building their own leaf types who wants to know exactly which
+    /// invariant was broken.
+    #[inline]
+    pub fn validate(&self) -> Vec<InvariantViolation> {
+        let mut violations = Vec::new();
+        validate::validate_node(&self.root, &mut Vec::new(), &mut violations);
+        violations
+    }
+}
+
+mod shared {
+    //! This module contains the logic used to implement
+    //! [`Tree::shared_prefix()`] and [`Tree::shared_suffix()`].
+
+    use super::*;
+
+    /// Returns the summary of the nodes at the start (or at the end, if
+    /// `from_end` is true) of the two trees which are shared by both.
+    ///
+    /// The two trees are walked in lockstep: every child that's shared by
+    /// both inodes is skipped as a whole, and the walk continues into the
+    /// first pair of children which differ.
+    #[inline]
+    pub(super) fn summary<const N: usize, L: Leaf, C: RefCounter>(
+        mut node: &Arc<Node<N, L, C>, C>,
+        mut other: &Arc<Node<N, L, C>, C>,
+        from_end: bool,
+    ) -> L::Summary {
+        let mut summary = L::Summary::default();
+
+        loop {
+            if Arc::ptr_eq(node, other) {
+                summary += node.summary();
+                return summary;
+            }
+
+            let (Node::Internal(inode), Node::Internal(other_inode)) =
+                (&**node, &**other)
+            else {
+                return summary;
+            };
+
+            let children = inode.children();
+            let other_children = other_inode.children();
+
+            let pairs =
+                (0..children.len().min(other_children.len())).map(|idx| {
+                    if from_end {
+                        (
+                            &children[children.len() - 1 - idx],
+                            &other_children[other_children.len() - 1 - idx],
+                        )
+                    } else {
+                        (&children[idx], &other_children[idx])
+                    }
+                });
+
+            let mut differ = None;
+
+            for (child, other_child) in pairs {
+                if Arc::ptr_eq(child, other_child) {
+                    summary += child.summary();
+                } else {
+                    differ = Some((child, other_child));
+                    break;
+                }
+            }
+
+            match differ {
+                Some((child, other_child)) => {
+                    node = child;
+                    other = other_child;
+                },
+                None => return summary,
+            }
+        }
+    }
+}
+
+mod changed {
+    //! This module contains the logic used to implement
+    //! [`Tree::changed_ranges()`] and [`Tree::shared_leaf_count()`].
+
+    use std::collections::HashMap;
+
+    use super::*;
+
+    /// A sequence of sibling or cousin nodes of one of the two trees,
+    /// together with the range they cover.
+    struct Nodes<'a, const N: usize, L: Leaf, M, C: RefCounter> {
+        nodes: Vec<&'a Arc<Node<N, L, C>, C>>,
+        range: Range<M>,
+    }
+
+    impl<
+            'a,
+            const N: usize,
+            L: Leaf,
+            C: RefCounter,
+            M: Metric<L::Summary>,
+        > Nodes<'a, N, L, M, C>
+    {
+        /// Returns the nodes in the given index range, and the range they
+        /// cover given that the first one starts at `start`.
+        #[inline]
+        fn sub(&self, idxs: Range<usize>, start: M) -> Self {
+            let nodes = self.nodes[idxs].to_vec();
+
+            let mut end = start;
+
+            for node in &nodes {
+                end += node.measure::<M>();
+            }
+
+            Self { nodes, range: start..end }
+        }
+
+        /// Replaces every internal node at the given depth with its children.
+        #[inline]
+        fn expand(&mut self, depth: usize) {
+            let mut expanded = Vec::with_capacity(self.nodes.len() * N);
+
+            for &node in &self.nodes {
+                match &**node {
+                    Node::Internal(inode) if inode.depth() == depth => {
+                        expanded.extend(inode.children())
+                    },
+                    _ => expanded.push(node),
+                }
+            }
+
+            self.nodes = expanded;
+        }
+    }
+
+    /// What the two trees share and what they don't.
+    struct Diff<M> {
+        /// The ranges of the two trees which aren't shared by both.
+        ranges: Vec<(Range<M>, Range<M>)>,
+
+        /// The number of leaves under the nodes shared by both trees.
+        shared_leaves: usize,
+    }
+
+    /// Returns the ranges of the two trees which aren't shared by both.
+    #[inline]
+    pub(super) fn ranges<
+        const N: usize,
+        L: Leaf,
+        C: RefCounter,
+        M: Metric<L::Summary>,
+    >(
+        node: &Arc<Node<N, L, C>, C>,
+        other: &Arc<Node<N, L, C>, C>,
+    ) -> Vec<(Range<M>, Range<M>)> {
+        diff_roots::<N, L, C, M>(node, other).ranges
+    }
+
+    /// Returns the number of leaves shared by the two trees.
+    #[inline]
+    pub(super) fn shared_leaf_count<const N: usize, L: Leaf, C: RefCounter>(
+        node: &Arc<Node<N, L, C>, C>,
+        other: &Arc<Node<N, L, C>, C>,
+    ) -> usize {
+        diff_roots::<N, L, C, L::BaseMetric>(node, other).shared_leaves
+    }
+
+    #[inline]
+    fn diff_roots<
+        const N: usize,
+        L: Leaf,
+        C: RefCounter,
+        M: Metric<L::Summary>,
+    >(
+        node: &Arc<Node<N, L, C>, C>,
+        other: &Arc<Node<N, L, C>, C>,
+    ) -> Diff<M> {
+        let mut result = Diff { ranges: Vec::new(), shared_leaves: 0 };
+
+        diff(
+            Nodes { nodes: vec![node], range: M::zero()..node.measure() },
+            Nodes { nodes: vec![other], range: M::zero()..other.measure() },
+            &mut result,
+        );
+
+        result
+    }
+
+    /// Pushes the ranges of `old` and `new` which aren't shared by both to
+    /// `result`, and adds the leaves they share to its count.
+    ///
+    /// Shared nodes are always at the same depth in the two trees, so we
+    /// look for them level by level: the nodes shared by the two sequences
+    /// split them into smaller sequences to diff independently, and when
+    /// there are none the tallest nodes are replaced by their children.
+    /// Nodes that are only in one of the trees are expanded until we get to
+    /// their leaves, since they could still contain shared subtrees.
+    #[inline]
+    fn diff<const N: usize, L: Leaf, C: RefCounter, M: Metric<L::Summary>>(
+        mut old: Nodes<'_, N, L, M, C>,
+        mut new: Nodes<'_, N, L, M, C>,
+        result: &mut Diff<M>,
+    ) {
+        loop {
+            let depth = old
+                .nodes
+                .iter()
+                .chain(&new.nodes)
+                .map(|node| node.depth())
+                .max()
+                .unwrap_or(0);
+
+            if old.nodes.is_empty() || new.nodes.is_empty() {
+                break;
+            }
+
+            let shared = shared_nodes(&old.nodes, &new.nodes);
+
+            if shared.is_empty() {
+                if depth == 0 {
+                    break;
+                }
+
+                old.expand(depth);
+                new.expand(depth);
+                continue;
+            }
+
+            let mut old_idx = 0;
+            let mut new_idx = 0;
+            let mut old_start = old.range.start;
+            let mut new_start = new.range.start;
+
+            for (old_shared, new_shared) in shared {
+                let old_gap = old.sub(old_idx..old_shared, old_start);
+                let new_gap = new.sub(new_idx..new_shared, new_start);
+
+                old_start =
+                    old_gap.range.end + old.nodes[old_shared].measure();
+                new_start =
+                    new_gap.range.end + new.nodes[new_shared].measure();
+
+                diff(old_gap, new_gap, result);
+
+                result.shared_leaves += old.nodes[old_shared].leaf_count();
+
+                old_idx = old_shared + 1;
+                new_idx = new_shared + 1;
+            }
+
+            let old_gap = old.sub(old_idx..old.nodes.len(), old_start);
+            let new_gap = new.sub(new_idx..new.nodes.len(), new_start);
+
+            return diff(old_gap, new_gap, result);
+        }
+
+        if old.range.start < old.range.end || new.range.start < new.range.end {
+            result.ranges.push((old.range, new.range));
+        }
+    }
+
+    /// Returns the indices of the nodes shared by the two sequences, pairing
+    /// them in order.
+    #[inline]
+    fn shared_nodes<const N: usize, L: Leaf, C: RefCounter>(
+        old: &[&Arc<Node<N, L, C>, C>],
+        new: &[&Arc<Node<N, L, C>, C>],
+    ) -> Vec<(usize, usize)> {
+        let mut new_idxs = HashMap::with_capacity(new.len());
+
+        for (idx, &node) in new.iter().enumerate() {
+            new_idxs.entry(&**node as *const Node<N, L, C>).or_insert(idx);
+        }
+
+        let mut shared = Vec::new();
+
+        let mut next_new = 0;
+
+        for (old_idx, &node) in old.iter().enumerate() {
+            if let Some(&new_idx) = new_idxs.get(&(&**node as *const _)) {
+                if new_idx >= next_new {
+                    shared.push((old_idx, new_idx));
+                    next_new = new_idx + 1;
+                }
+            }
+        }
+
+        shared
+    }
+}
+
+mod preorder {
+    //! This module contains the logic used to implement
+    //! [`Tree::for_each_node()`] and [`Tree::from_preorder()`].
+
+    use super::*;
+
+    #[inline]
+    pub(super) fn visit<const N: usize, L: Leaf, C: RefCounter>(
+        node: &Arc<Node<N, L, C>, C>,
+        fun: &mut impl FnMut(PreorderNode<&L, &L::Summary>),
+    ) {
+        match &**node {
+            Node::Internal(inode) => {
+                fun(PreorderNode::Internal {
+                    children: inode.len(),
+                    summary: inode.summary(),
+                });
+
+                for child in inode.children() {
+                    visit(child, fun);
                 }
             },
 
-            Node::Leaf(leaf) => leaf.assert_invariants(),
+            Node::Leaf(leaf) => fun(PreorderNode::Leaf {
+                leaf: leaf.value(),
+                summary: leaf.summary(),
+            }),
         }
     }
 
+    /// Builds the subtree rooted at the next node, which is the root of the
+    /// whole tree if `is_root` is true.
     #[inline]
-    pub fn base_measure(&self) -> L::BaseMetric {
-        self.measure::<L::BaseMetric>()
-    }
+    pub(super) fn build<const N: usize, L: Leaf, C: RefCounter>(
+        nodes: &mut impl Iterator<Item = PreorderNode<L, L::Summary>>,
+        is_root: bool,
+        check_summaries: bool,
+    ) -> Option<Arc<Node<N, L, C>, C>> {
+        let (num_children, summary) = match nodes.next()? {
+            PreorderNode::Internal { children, summary } => {
+                (children, summary)
+            },
 
-    /// Returns the `M2`-measure of all the leaves before `up_to` plus the
-    /// `M2`-measure of the left sub-slice of the leaf at `up_to`.
-    #[track_caller]
-    #[inline]
-    pub fn convert_measure<M1, M2>(&self, up_to: M1) -> M2
-    where
-        M1: SlicingMetric<L>,
-        M2: Metric<L::Summary>,
-    {
-        debug_assert!(up_to <= self.measure::<M1>());
-        self.root.convert_measure(up_to)
+            PreorderNode::Leaf { leaf, summary } => {
+                let lnode = Lnode::from((leaf, summary));
+                return Some(Arc::new(Node::Leaf(lnode)));
+            },
+        };
+
+        // The root can have as few as two children.
+        let min_children =
+            if is_root { 2 } else { Inode::<N, L, C>::min_children() };
+
+        if !(min_children..=Inode::<N, L, C>::max_children())
+            .contains(&num_children)
+        {
+            return None;
+        }
+
+        let mut children = Vec::<Arc<Node<N, L, C>, C>>::with_capacity(N);
+
+        for _ in 0..num_children {
+            let child = build(nodes, false, check_summaries)?;
+
+            if !children.is_empty() && children[0].depth() != child.depth() {
+                return None;
+            }
+
+            children.push(child);
+        }
+
+        let mut inode = Inode::from_summarized_children(children, summary);
+
+        if check_summaries && !inode.check_summary() {
+            return None;
+        }
+
+        Some(Arc::new(Node::internal(inode)))
     }
+}
 
-    /// Creates a new `Tree` from a sequence of leaves.
+mod dot {
+    //! This module contains the logic used to implement [`Tree::to_dot()`].
+
+    use core::fmt::Write;
+
+    use super::*;
+
+    /// Writes the DOT statements of `node` and of all the nodes under it,
+    /// naming them with consecutive ids starting from `next_id`.
     ///
-    /// If the iterator doesn't yield any items the `Tree` will contain a
-    /// single leaf with its default value.
-    #[inline]
-    pub fn from_leaves<I>(leaves: I) -> Self
-    where
-        I: IntoIterator<Item = L>,
-        L: Default,
-    {
-        let mut leaves =
-            leaves.into_iter().map(Lnode::from).map(Node::Leaf).map(Arc::new);
+    /// Returns the id of `node`.
+    pub(super) fn write_node<const N: usize, L: Leaf, C: RefCounter>(
+        node: &Arc<Node<N, L, C>, C>,
+        next_id: &mut usize,
+        dot: &mut String,
+    ) -> usize {
+        let id = *next_id;
 
-        let Some(first) = leaves.next() else { return Self::default() };
+        *next_id += 1;
 
-        let Some(second) = leaves.next() else { return Self { root: first } };
+        let shape = if node.is_leaf() { "box" } else { "ellipse" };
 
-        let leaves = {
-            let (lo, hi) = leaves.size_hint();
-            let mut l = Vec::with_capacity(2 + hi.unwrap_or(lo));
-            l.push(first);
-            l.push(second);
-            l.extend(leaves);
-            l
-        };
+        let summary = format!("{:?}", node.summary())
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"");
 
-        let root = Inode::from_nodes(leaves);
+        let refs = Arc::strong_count(node);
 
-        Self { root: Arc::new(Node::Internal(root)) }
-    }
+        // Writing to a `String` can't fail.
+        let _ = writeln!(
+            dot,
+            "    n{id} [shape={shape}, label=\"{summary}\\nrefs: {refs}\"];"
+        );
 
-    /// Returns the leaf containing the `measure`-th unit of the `M`-metric,
-    /// plus the `M`-measure of all the leaves before it.
-    #[inline]
-    pub fn leaf_at_measure<M>(&self, measure: M) -> (L::Slice<'_>, M)
-    where
-        M: Metric<L::Summary>,
-    {
-        debug_assert!(measure <= self.measure::<M>() + M::one());
+        if let Node::Internal(inode) = &**node {
+            for child in inode.children() {
+                let child_id = write_node(child, next_id, dot);
+                let _ = writeln!(dot, "    n{id} -> n{child_id};");
+            }
+        }
 
-        self.root.leaf_at_measure(measure)
+        id
     }
+}
 
-    #[inline]
-    pub fn leaf_count(&self) -> usize {
-        self.root.leaf_count()
-    }
+mod from_leaves {
+    //! This module handles the logic used to build a `Tree` bottom-up from a
+    //! stream of leaves.
 
-    /// Returns an iterator over the leaves of this `Tree`.
-    #[inline]
-    pub fn leaves(&self) -> Leaves<'_, ARITY, L> {
-        Leaves::from(self)
-    }
+    use super::*;
 
-    /// Returns the `M`-measure of this `Tree` obtaining by summing up the
-    /// `M`-measures of all its leaves.
-    #[inline]
-    pub fn measure<M>(&self) -> M
-    where
-        M: Metric<L::Summary>,
-    {
-        M::measure(self.summary())
+    /// A stack of partially-filled levels of a `Tree` being built from its
+    /// leaves.
+    ///
+    /// The first level holds the leaves, and every other level holds the
+    /// inodes built from the level below it. A level is only grouped into a
+    /// new inode once it holds `max_children + min_children` nodes, and only
+    /// the first `max_children` of those are taken. This way there are always
+    /// at least `min_children` nodes left over to close the level with when
+    /// the stream ends, so that no inode on the right side of the tree ends up
+    /// underfilled without having to rebalance it.
+    ///
+    /// Only `O(ARITY)` nodes are kept around per level, so the auxiliary
+    /// memory is logarithmic in the number of leaves.
+    pub(super) struct Levels<const N: usize, L: Leaf, C: RefCounter> {
+        levels: Vec<Vec<Arc<Node<N, L, C>, C>>>,
     }
 
-    /// Replaces a range of the `Tree` with the given replacement.
-    #[track_caller]
-    #[inline]
-    pub fn replace<M>(
-        &mut self,
-        range: Range<M>,
-        replace_with: L::Replacement<'_>,
-    ) where
-        M: Metric<L::Summary>,
-        L: ReplaceableLeaf<M> + Clone,
-    {
-        if let Some(extras) =
-            tree_replace::replace(&mut self.root, range, replace_with)
-        {
-            debug_assert!(extras
-                .iter()
-                .all(|n| n.depth() == self.root.depth()));
+    impl<const N: usize, L: Leaf, C: RefCounter> Levels<N, L, C> {
+        /// The maximum number of nodes held in a level.
+        const LEVEL_CAPACITY: usize = Inode::<N, L, C>::max_children()
+            + Inode::<N, L, C>::min_children();
 
-            self.root = Arc::new(Node::Internal(Inode::from_nodes(
-                core::iter::once(Arc::clone(self.root())).exact_chain(extras),
-            )));
+        /// Consumes the stack, returning the root of the tree, or `None` if
+        /// no leaves were pushed.
+        #[inline]
+        pub(super) fn finish(mut self) -> Option<Arc<Node<N, L, C>, C>> {
+            if self.levels.is_empty() {
+                return None;
+            }
+
+            let mut level_idx = 0;
+
+            loop {
+                let is_last = level_idx + 1 == self.levels.len();
+
+                let mut nodes = core::mem::take(&mut self.levels[level_idx]);
+
+                // The level has never been grouped into an inode, so its
+                // nodes are the children of the root.
+                if is_last && nodes.len() <= Inode::<N, L, C>::max_children() {
+                    return if nodes.len() == 1 {
+                        nodes.pop()
+                    } else {
+                        let root = Inode::from_children(nodes);
+                        Some(Arc::new(Node::internal(root)))
+                    };
+                }
+
+                debug_assert!(nodes.len() >= Inode::<N, L, C>::min_children());
+
+                if nodes.len() > Inode::<N, L, C>::max_children() {
+                    let split_at =
+                        nodes.len() - Inode::<N, L, C>::min_children();
+                    let last = Inode::from_children(nodes.drain(split_at..));
+                    let first = Inode::from_children(nodes);
+                    self.push(level_idx + 1, Arc::new(Node::internal(first)));
+                    self.push(level_idx + 1, Arc::new(Node::internal(last)));
+                } else {
+                    let inode = Inode::from_children(nodes);
+                    self.push(level_idx + 1, Arc::new(Node::internal(inode)));
+                }
+
+                level_idx += 1;
+            }
         }
-    }
 
-    #[inline]
-    pub(super) fn root(&self) -> &Arc<Node<ARITY, L>> {
-        &self.root
-    }
+        #[inline]
+        pub(super) fn new() -> Self {
+            Self { levels: Vec::new() }
+        }
 
-    /// Returns a slice of the `Tree` in the range of the given metric.
-    #[track_caller]
-    #[inline]
-    pub fn slice<M>(&self, range: Range<M>) -> TreeSlice<'_, ARITY, L>
-    where
-        M: SlicingMetric<L>,
-        L::BaseMetric: SlicingMetric<L>,
-        for<'d> L::Slice<'d>: Default,
-    {
-        debug_assert!(M::zero() <= range.start);
-        debug_assert!(range.start <= range.end);
-        debug_assert!(range.end <= self.measure::<M>() + M::one());
+        /// Pushes a node to the level at `level_idx`, grouping the nodes of
+        /// that level (and then of the ones above it) into a new inode if it
+        /// gets full.
+        #[inline]
+        fn push(
+            &mut self,
+            mut level_idx: usize,
+            mut node: Arc<Node<N, L, C>, C>,
+        ) {
+            loop {
+                if level_idx == self.levels.len() {
+                    self.levels.push(Vec::with_capacity(Self::LEVEL_CAPACITY));
+                }
 
-        TreeSlice::from_range_in_root(&self.root, range)
-    }
+                let level = &mut self.levels[level_idx];
 
-    #[inline]
-    pub fn summary(&self) -> &L::Summary {
-        self.root.summary()
-    }
+                level.push(node);
 
-    /// Returns an iterator over the `M`-units of this `Tree`.
-    #[inline]
-    pub fn units<M>(&self) -> Units<'_, ARITY, L, M>
-    where
-        M: Metric<L::Summary>,
-        for<'d> L::Slice<'d>: Default,
-    {
-        Units::from(self)
+                if level.len() < Self::LEVEL_CAPACITY {
+                    return;
+                }
+
+                let max_children = Inode::<N, L, C>::max_children();
+
+                let inode = Inode::from_children(level.drain(..max_children));
+
+                node = Arc::new(Node::internal(inode));
+
+                level_idx += 1;
+            }
+        }
+
+        #[inline]
+        pub(super) fn push_leaf(&mut self, leaf: Arc<Node<N, L, C>, C>) {
+            debug_assert!(leaf.is_leaf());
+            self.push(0, leaf);
+        }
     }
 }
 
@@ -242,14 +1343,18 @@ mod from_treeslice {
     /// This function can only be called if the slice spans at least 3 leaves.
     /// Leaf counts of 1 and 2 must be handled by the caller.
     #[inline]
-    pub(super) fn into_tree_root<const N: usize, L: BalancedLeaf + Clone>(
-        slice: TreeSlice<'_, N, L>,
-    ) -> Arc<Node<N, L>> {
+    pub(super) fn into_tree_root<
+        const N: usize,
+        L: BalancedLeaf + Clone,
+        C: RefCounter,
+    >(
+        slice: TreeSlice<'_, N, L, C>,
+    ) -> Arc<Node<N, L, C>, C> {
         debug_assert!(slice.leaf_count() >= 3);
 
         let (root, invalid_in_first, invalid_in_last) = cut_tree_slice(slice);
 
-        let mut root = Arc::new(Node::Internal(root));
+        let mut root = Arc::new(Node::internal(root));
 
         if invalid_in_first > 0 {
             {
@@ -262,12 +1367,11 @@ mod from_treeslice {
             Node::replace_with_single_child(&mut root);
         }
 
-        if invalid_in_last > 0 {
+        // Balancing the left side can merge all the leaves of the slice into
+        // a single one if they fit in it, in which case there's nothing left
+        // to balance on the right side.
+        if invalid_in_last > 0 && root.is_internal() {
             {
-                // For the root to become a leaf node after the previous call
-                // to `pull_up_singular` the TreeSlice would've had to span 2
-                // leaves, and that case case should have already been handled
-                // before calling this function.
                 let inode =
                     Arc::get_mut(&mut root).unwrap().get_internal_mut();
 
@@ -283,10 +1387,10 @@ mod from_treeslice {
     /// Returns a `(root, invalid_first, invalid_last)` tuple where:
     ///
     /// - `root` is the internal node obtained by removing all the nodes before
-    /// `slice.before` and after `slice.before + slice.base_measure`,
+    ///   `slice.before` and after `slice.before + slice.base_measure`,
     ///
     /// - `invalid_{first,last}` are the number of invalid nodes contained in
-    /// the subtrees of the first and last child, respectively.
+    ///   the subtrees of the first and last child, respectively.
     ///
     /// Note that all the `Arc`s enclosing the nodes on the left and right side
     /// of the subtree under `root` are guaranteed to have a strong count of 1,
@@ -297,9 +1401,13 @@ mod from_treeslice {
     ///
     /// Panics if the slice spans less than 3 leaves.
     #[inline]
-    fn cut_tree_slice<const N: usize, L: BalancedLeaf + Clone>(
-        slice: TreeSlice<'_, N, L>,
-    ) -> (Inode<N, L>, usize, usize) {
+    fn cut_tree_slice<
+        const N: usize,
+        L: BalancedLeaf + Clone,
+        C: RefCounter,
+    >(
+        slice: TreeSlice<'_, N, L, C>,
+    ) -> (Inode<N, L, C>, usize, usize) {
         debug_assert!(slice.leaf_count() >= 3);
 
         let mut root = Inode::empty();
@@ -372,13 +1480,17 @@ mod from_treeslice {
     /// Recursively removes all the nodes before `take_from`, replacing the
     /// leaf at `take_from` with `start_slice`. Returns the resulting node.
     #[inline]
-    fn cut_start_rec<const N: usize, L: BalancedLeaf + Clone>(
-        node: &Arc<Node<N, L>>,
+    fn cut_start_rec<
+        const N: usize,
+        L: BalancedLeaf + Clone,
+        C: RefCounter,
+    >(
+        node: &Arc<Node<N, L, C>, C>,
         take_from: L::BaseMetric,
         start_slice: L::Slice<'_>,
         start_summary: L::Summary,
         invalid_nodes: &mut usize,
-    ) -> Arc<Node<N, L>> {
+    ) -> Arc<Node<N, L, C>, C> {
         match &**node {
             Node::Internal(i) => {
                 let mut inode = Inode::empty();
@@ -416,7 +1528,7 @@ mod from_treeslice {
                             *invalid_nodes += 1;
                         }
 
-                        return Arc::new(Node::Internal(inode));
+                        return Arc::new(Node::internal(inode));
                     } else {
                         offset += this;
                     }
@@ -440,13 +1552,13 @@ mod from_treeslice {
     /// Recursively removes all the nodes after `take_up_to`, replacing the
     /// leaf at `take_up_to` with `end_slice`. Returns the resulting node.
     #[inline]
-    fn cut_end_rec<const N: usize, L: BalancedLeaf + Clone>(
-        node: &Arc<Node<N, L>>,
+    fn cut_end_rec<const N: usize, L: BalancedLeaf + Clone, C: RefCounter>(
+        node: &Arc<Node<N, L, C>, C>,
         take_up_to: L::BaseMetric,
         end_slice: L::Slice<'_>,
         end_summary: L::Summary,
         invalid_nodes: &mut usize,
-    ) -> Arc<Node<N, L>> {
+    ) -> Arc<Node<N, L, C>, C> {
         match &**node {
             Node::Internal(i) => {
                 let mut inode = Inode::empty();
@@ -478,7 +1590,7 @@ mod from_treeslice {
                             *invalid_nodes += 1;
                         }
 
-                        return Arc::new(Node::Internal(inode));
+                        return Arc::new(Node::internal(inode));
                     } else {
                         inode.push(Arc::clone(child));
                         offset += this;
@@ -519,11 +1631,12 @@ mod tree_replace {
     /// it was before calling this function.
     #[track_caller]
     #[inline]
-    pub(super) fn replace<const N: usize, M, L>(
-        node: &mut Arc<Node<N, L>>,
+    pub(super) fn replace<const N: usize, M, L, C: RefCounter>(
+        node: &mut Arc<Node<N, L, C>, C>,
         mut range: Range<M>,
         replace_with: L::Replacement<'_>,
-    ) -> Option<Vec<Arc<Node<N, L>>>>
+        hint: &mut EditHint<L>,
+    ) -> Option<Vec<Arc<Node<N, L, C>, C>>>
     where
         M: Metric<L::Summary>,
         L: ReplaceableLeaf<M> + Clone,
@@ -533,14 +1646,17 @@ mod tree_replace {
 
             Node::Leaf(leaf) => {
                 return leaf.replace(range, replace_with).map(|extras| {
+                    hint.invalidate();
                     extras.map(Node::Leaf).map(Arc::new).collect()
                 });
             },
         };
 
-        let Some((child_idx, offset)) =
+        let Some((child_idx, offset, summary_offset)) =
             inode.child_containing_range(range.clone())
         else {
+            hint.invalidate();
+
             let extras = replace_range_in_deepest(inode, range, replace_with);
 
             Node::replace_with_single_child(node);
@@ -548,11 +1664,13 @@ mod tree_replace {
             return extras;
         };
 
+        hint.push(child_idx, &summary_offset);
+
         range.start -= offset;
         range.end -= offset;
 
         let extras = inode.with_child_mut(child_idx, |child| {
-            replace(child, range, replace_with)
+            replace(child, range, replace_with, hint)
         });
 
         let child = inode.child(child_idx);
@@ -564,14 +1682,17 @@ mod tree_replace {
                 .iter()
                 .all(|n| n.depth() == inode.depth() - 1));
 
+            hint.invalidate();
+
             inode.insert_children(child_idx + 1, extras).map(|extras| {
-                extras.map(Node::Internal).map(Arc::new).collect()
+                extras.map(Node::internal).map(Arc::new).collect()
             })
         }
         // Case 2: the child stayed at the same depth but it's now underfilled
         // and needs to be rebalanced with one if its siblings.
         else if child.depth() == inode.depth() - 1 && child.is_underfilled()
         {
+            hint.invalidate();
             inode.balance_child(child_idx);
             Node::replace_with_single_child(node);
             None
@@ -580,6 +1701,7 @@ mod tree_replace {
         // needs to be appended/prepended to another child.
         else if child.depth() < inode.depth() - 1 {
             debug_assert!(inode.depth() >= 2);
+            hint.invalidate();
             let child = inode.remove(child_idx);
             inode.insert_at_depth(child_idx, child);
             Node::replace_with_single_child(node);
@@ -594,6 +1716,78 @@ mod tree_replace {
         }
     }
 
+    /// Replaces the range of the leaf found by following the children at the
+    /// given indices from `node`. The replacement is assumed to neither
+    /// overflow nor underfill the leaf.
+    #[track_caller]
+    #[inline]
+    pub(super) fn replace_along_path<const N: usize, M, L, C: RefCounter>(
+        node: &mut Arc<Node<N, L, C>, C>,
+        path: &[usize],
+        range: Range<M>,
+        replace_with: L::Replacement<'_>,
+    ) where
+        M: Metric<L::Summary>,
+        L: ReplaceableLeaf<M> + Clone,
+    {
+        match Arc::make_mut(node) {
+            Node::Internal(inode) => {
+                inode.with_child_mut(path[0], |child| {
+                    replace_along_path(child, &path[1..], range, replace_with)
+                });
+            },
+
+            Node::Leaf(leaf) => {
+                debug_assert!(path.is_empty());
+                let extras = leaf.replace(range, replace_with);
+                debug_assert!(extras.is_none());
+                debug_assert!(!leaf.is_underfilled());
+            },
+        }
+    }
+
+    /// Appends to the last leaf under `node`, found by following the children
+    /// at the given indices, inserting the leaves it's split into after it.
+    ///
+    /// Like [`replace`], it can return a vector of extra nodes to be inserted
+    /// right after `node`. Since nothing is removed, no node is ever left
+    /// underfilled.
+    #[track_caller]
+    #[inline]
+    pub(super) fn append_along_path<const N: usize, M, L, C: RefCounter>(
+        node: &mut Arc<Node<N, L, C>, C>,
+        path: &[usize],
+        range: Range<M>,
+        replace_with: L::Replacement<'_>,
+    ) -> Option<Vec<Arc<Node<N, L, C>, C>>>
+    where
+        M: Metric<L::Summary>,
+        L: ReplaceableLeaf<M> + Clone,
+    {
+        match Arc::make_mut(node) {
+            Node::Internal(inode) => {
+                let child_idx = path[0];
+
+                debug_assert_eq!(child_idx, inode.len() - 1);
+
+                let extras = inode.with_child_mut(child_idx, |child| {
+                    append_along_path(child, &path[1..], range, replace_with)
+                })?;
+
+                inode.insert_children(child_idx + 1, extras).map(|extras| {
+                    extras.map(Node::internal).map(Arc::new).collect()
+                })
+            },
+
+            Node::Leaf(leaf) => {
+                debug_assert!(path.is_empty());
+                leaf.replace(range, replace_with).map(|extras| {
+                    extras.map(Node::Leaf).map(Arc::new).collect()
+                })
+            },
+        }
+    }
+
     /// Replaces the given range of the inode. At this point `inode` is assumed
     /// to be the deepest node that fully contains the range.
     ///
@@ -602,11 +1796,11 @@ mod tree_replace {
     /// even contain a single child) if it was deletion-heavy.
     #[track_caller]
     #[inline]
-    fn replace_range_in_deepest<const N: usize, M, L>(
-        inode: &mut Inode<N, L>,
+    fn replace_range_in_deepest<const N: usize, M, L, C: RefCounter>(
+        inode: &mut Inode<N, L, C>,
         range: Range<M>,
         replace_with: L::Replacement<'_>,
-    ) -> Option<Vec<Arc<Node<N, L>>>>
+    ) -> Option<Vec<Arc<Node<N, L, C>, C>>>
     where
         M: Metric<L::Summary>,
         L: ReplaceableLeaf<M> + Clone,
@@ -682,7 +1876,7 @@ mod tree_replace {
                     } else if let Some(extra) =
                         previous_node.append_at_depth(last)
                     {
-                        extras.push(Arc::new(Node::Internal(extra)));
+                        extras.push(Arc::new(Node::internal(extra)));
                     }
                 });
             }
@@ -694,7 +1888,7 @@ mod tree_replace {
 
         inode
             .insert_children(start_idx + 1, extras)
-            .map(|extras| extras.map(Node::Internal).map(Arc::new).collect())
+            .map(|extras| extras.map(Node::internal).map(Arc::new).collect())
     }
 
     /// Returns the following values:
@@ -710,11 +1904,16 @@ mod tree_replace {
     /// insertion-heavy.
     #[track_caller]
     #[inline]
-    fn inode_replace_nodes_in_start_and_end_subtrees<const N: usize, M, L>(
-        inode: &mut Inode<N, L>,
+    fn inode_replace_nodes_in_start_and_end_subtrees<
+        const N: usize,
+        M,
+        L,
+        C: RefCounter,
+    >(
+        inode: &mut Inode<N, L, C>,
         range: Range<M>,
         replace_with: L::Replacement<'_>,
-    ) -> (usize, usize, Option<Vec<Arc<Node<N, L>>>>)
+    ) -> (usize, usize, Option<Vec<ArcNode<N, L, C>>>)
     where
         M: Metric<L::Summary>,
         L: ReplaceableLeaf<M> + Clone,
@@ -803,12 +2002,12 @@ mod tree_replace {
     /// removes them if there are no extra leaves.
     #[track_caller]
     #[inline]
-    fn replace_nodes_in_start_subtree<const N: usize, M, L>(
-        node: &mut Node<N, L>,
+    fn replace_nodes_in_start_subtree<const N: usize, M, L, C: RefCounter>(
+        node: &mut Node<N, L, C>,
         replace_from: M,
         replace_with: L::Replacement<'_>,
         should_rebalance: &mut bool,
-    ) -> Option<impl ExactSizeIterator<Item = Arc<Node<N, L>>>>
+    ) -> Option<impl ExactSizeIterator<Item = Arc<Node<N, L, C>, C>>>
     where
         M: Metric<L::Summary>,
         L: ReplaceableLeaf<M> + Clone,
@@ -862,10 +2061,10 @@ mod tree_replace {
     /// nodes before that leaf, or removes them if there are no extra leaves.
     #[track_caller]
     #[inline]
-    fn replace_nodes_in_end_subtree<const N: usize, M, L>(
-        node: &mut Node<N, L>,
+    fn replace_nodes_in_end_subtree<const N: usize, M, L, C: RefCounter>(
+        node: &mut Node<N, L, C>,
         replace_up_to: M,
-        extra_leaves: &mut Option<Vec<Arc<Node<N, L>>>>,
+        extra_leaves: &mut Option<Vec<Arc<Node<N, L, C>, C>>>,
         should_rebalance: &mut bool,
     ) where
         M: Metric<L::Summary>,
@@ -951,12 +2150,12 @@ mod tree_replace {
     /// If the iterator is exhausted before the whole range has been replaced
     /// it'll just remove the remaining children.
     #[inline]
-    fn replace_child_range_with_leaves<const N: usize, L, I>(
-        inode: &mut Inode<N, L>,
+    fn replace_child_range_with_leaves<const N: usize, L, C: RefCounter, I>(
+        inode: &mut Inode<N, L, C>,
         child_range: Range<usize>,
         leaves: &mut I,
     ) where
-        I: Iterator<Item = Arc<Node<N, L>>> + ExactSizeIterator,
+        I: Iterator<Item = Arc<Node<N, L, C>, C>> + ExactSizeIterator,
         L: BalancedLeaf + Clone,
     {
         debug_assert!(child_range.start >= 1);
@@ -1007,7 +2206,7 @@ mod tree_replace {
                                 } else {
                                     previous_child
                                         .append_at_depth(last)
-                                        .map(Node::Internal)
+                                        .map(Node::internal)
                                         .map(Arc::new)
                                 }
                             });
@@ -1035,10 +2234,14 @@ mod tree_replace {
     /// replaces the children in the given index range going backwards, i.e.
     /// starting from the last child.
     #[inline]
-    fn replace_child_range_with_leaves_from_back<const N: usize, L>(
-        inode: &mut Inode<N, L>,
+    fn replace_child_range_with_leaves_from_back<
+        const N: usize,
+        L,
+        C: RefCounter,
+    >(
+        inode: &mut Inode<N, L, C>,
         child_range: Range<usize>,
-        leaves: &mut Vec<Arc<Node<N, L>>>,
+        leaves: &mut Vec<Arc<Node<N, L, C>, C>>,
     ) where
         L: BalancedLeaf + Clone,
     {
@@ -1094,7 +2297,7 @@ mod tree_replace {
                                 } else {
                                     next_child
                                         .prepend_at_depth(last)
-                                        .map(Node::Internal)
+                                        .map(Node::internal)
                                         .map(Arc::new)
                                 }
                             });
@@ -1123,8 +2326,8 @@ mod tree_replace {
     /// The left and right side of the seam are under the children before and
     /// after the `seam_offset`, respectively.
     #[inline]
-    fn fix_seam_between_subtrees<const N: usize, L>(
-        inode: &mut Inode<N, L>,
+    fn fix_seam_between_subtrees<const N: usize, L, C: RefCounter>(
+        inode: &mut Inode<N, L, C>,
         seam_offset: usize,
         start_should_rebalance: bool,
         end_should_rebalance: bool,
@@ -1191,7 +2394,7 @@ mod tree_replace {
 
                 if let Some(end) = end {
                     debug_assert_eq!(end.depth(), original_depth);
-                    inode.insert(end_idx, Arc::new(Node::Internal(end)));
+                    inode.insert(end_idx, Arc::new(Node::internal(end)));
                 }
 
                 if inode.len() > 1 {
@@ -1211,12 +2414,12 @@ mod tree_replace {
                     if inode.is_empty() {
                         *inode = i
                     } else if inode.depth() == i.depth() + 1 {
-                        inode.insert(start_idx, Arc::new(Node::Internal(i)));
+                        inode.insert(start_idx, Arc::new(Node::internal(i)));
                         inode.balance_child(start_idx);
                     } else {
                         inode.insert_at_depth(
                             start_idx,
-                            Arc::new(Node::Internal(i)),
+                            Arc::new(Node::internal(i)),
                         );
                     }
                 } else if inode.is_empty() {
@@ -1238,18 +2441,18 @@ mod tree_replace {
                 if let Some(end) = end {
                     let i = Inode::from_children([
                         start,
-                        Arc::new(Node::Internal(end)),
+                        Arc::new(Node::internal(end)),
                     ]);
 
                     if inode.is_empty() {
                         *inode = i;
                     } else if inode.depth() == i.depth() + 1 {
-                        inode.insert(start_idx, Arc::new(Node::Internal(i)));
+                        inode.insert(start_idx, Arc::new(Node::internal(i)));
                         inode.balance_child(start_idx);
                     } else {
                         inode.insert_at_depth(
                             start_idx,
-                            Arc::new(Node::Internal(i)),
+                            Arc::new(Node::internal(i)),
                         );
                     }
                 } else if inode.is_empty() {
@@ -1271,7 +2474,7 @@ mod tree_replace {
                 });
 
                 if let Some(start) = start {
-                    inode.insert(start_idx, Arc::new(Node::Internal(start)));
+                    inode.insert(start_idx, Arc::new(Node::internal(start)));
                     inode.balance_child(end_idx);
                 } else if inode.len() > 1 {
                     inode.balance_child(start_idx);
@@ -1291,19 +2494,19 @@ mod tree_replace {
 
                 if let Some(start) = start {
                     let i = Inode::from_children([
-                        Arc::new(Node::Internal(start)),
+                        Arc::new(Node::internal(start)),
                         end,
                     ]);
 
                     if inode.is_empty() {
                         *inode = i;
                     } else if inode.depth() == i.depth() + 1 {
-                        inode.insert(start_idx, Arc::new(Node::Internal(i)));
+                        inode.insert(start_idx, Arc::new(Node::internal(i)));
                         inode.balance_child(start_idx);
                     } else {
                         inode.insert_at_depth(
                             start_idx,
-                            Arc::new(Node::Internal(i)),
+                            Arc::new(Node::internal(i)),
                         );
                     }
                 } else if inode.is_empty() {
@@ -1328,21 +2531,29 @@ mod tree_replace {
         /// The minimum number of leaves required by [`Inode::from_nodes()`] to
         /// produce an internal node of the target depth with at least
         /// [`Inode::min_children()`] children.
-        const fn min_leaves_for_depth<const N: usize, L: Leaf>(
+        const fn min_leaves_for_depth<
+            const N: usize,
+            L: Leaf,
+            C: RefCounter,
+        >(
             target_depth: usize,
         ) -> usize {
-            (Inode::<N, L>::min_children() - 1)
-                * max_leaves_for_depth::<N, L>(target_depth - 1)
+            (Inode::<N, L, C>::min_children() - 1)
+                * max_leaves_for_depth::<N, L, C>(target_depth - 1)
                 + 1
         }
 
         /// The maximum number of leaves that can be fed to
         /// [`Inode::from_nodes()`] to produce an internal node of the target
         /// depth with no more than [`Inode::max_children()`] children.
-        const fn max_leaves_for_depth<const N: usize, L: Leaf>(
+        const fn max_leaves_for_depth<
+            const N: usize,
+            L: Leaf,
+            C: RefCounter,
+        >(
             target_depth: usize,
         ) -> usize {
-            Inode::<N, L>::max_children().pow(target_depth as u32)
+            Inode::<N, L, C>::max_children().pow(target_depth as u32)
         }
 
         /// Transforms an iterator over leaf nodes into internal nodes at a
@@ -1350,10 +2561,10 @@ mod tree_replace {
         /// `min_children` and `max_children` children, except for the last
         /// node which can be at a lower depth than the target (can even be a
         /// leaf node) and contain less than `min_children` children.
-        pub(super) struct TargetDepth<const N: usize, L, Leaves>
+        pub(super) struct TargetDepth<const N: usize, L, Leaves, C: RefCounter>
         where
             L: Leaf,
-            Leaves: ExactSizeIterator<Item = Arc<Node<N, L>>>,
+            Leaves: ExactSizeIterator<Item = Arc<Node<N, L, C>, C>>,
         {
             leaves: Leaves,
             target_depth: usize,
@@ -1361,10 +2572,10 @@ mod tree_replace {
             max_leaves_for_depth: usize,
         }
 
-        impl<const N: usize, L, Leaves> TargetDepth<N, L, Leaves>
+        impl<const N: usize, L, C: RefCounter, Leaves> TargetDepth<N, L, Leaves, C>
         where
             L: Leaf,
-            Leaves: ExactSizeIterator<Item = Arc<Node<N, L>>>,
+            Leaves: ExactSizeIterator<Item = Arc<Node<N, L, C>, C>>,
         {
             /// # Panics
             ///
@@ -1380,22 +2591,23 @@ mod tree_replace {
                 Self {
                     leaves,
                     target_depth,
-                    min_leaves_for_depth: min_leaves_for_depth::<N, L>(
+                    min_leaves_for_depth: min_leaves_for_depth::<N, L, C>(
                         target_depth,
                     ),
-                    max_leaves_for_depth: max_leaves_for_depth::<N, L>(
+                    max_leaves_for_depth: max_leaves_for_depth::<N, L, C>(
                         target_depth,
                     ),
                 }
             }
         }
 
-        impl<const N: usize, L, Leaves> Iterator for TargetDepth<N, L, Leaves>
+        impl<const N: usize, L, C: RefCounter, Leaves> Iterator
+            for TargetDepth<N, L, Leaves, C>
         where
             L: Leaf,
-            Leaves: ExactSizeIterator<Item = Arc<Node<N, L>>>,
+            Leaves: ExactSizeIterator<Item = Arc<Node<N, L, C>, C>>,
         {
-            type Item = Arc<Node<N, L>>;
+            type Item = Arc<Node<N, L, C>, C>;
 
             #[inline]
             fn next(&mut self) -> Option<Self::Item> {
@@ -1411,14 +2623,15 @@ mod tree_replace {
 
                         debug_assert!(
                             last.depth() < self.target_depth
-                                || last.len() < Inode::<N, L>::min_children()
+                                || last.len()
+                                    < Inode::<N, L, C>::min_children()
                         );
 
                         debug_assert!(
-                            last.len() <= Inode::<N, L>::max_children()
+                            last.len() <= Inode::<N, L, C>::max_children()
                         );
 
-                        Arc::new(Node::Internal(last))
+                        Arc::new(Node::internal(last))
                     };
 
                     Some(last)
@@ -1430,14 +2643,14 @@ mod tree_replace {
                     debug_assert_eq!(inode.depth(), self.target_depth);
 
                     debug_assert!(
-                        inode.len() >= Inode::<N, L>::min_children()
+                        inode.len() >= Inode::<N, L, C>::min_children()
                     );
 
                     debug_assert!(
-                        inode.len() <= Inode::<N, L>::max_children()
+                        inode.len() <= Inode::<N, L, C>::max_children()
                     );
 
-                    Some(Arc::new(Node::Internal(inode)))
+                    Some(Arc::new(Node::internal(inode)))
                 }
             }
         }
@@ -1445,17 +2658,22 @@ mod tree_replace {
         /// Same as `TargetDepth` except the inodes are constructed from back
         /// to front instead of front to back by draining the nodes off of the
         /// vector.
-        pub(super) struct TargetDepthFromBack<'a, const N: usize, L>
+        pub(super) struct TargetDepthFromBack<
+            'a,
+            const N: usize,
+            L,
+            C: RefCounter,
+        >
         where
             L: Leaf,
         {
-            leaves: &'a mut Vec<Arc<Node<N, L>>>,
+            leaves: &'a mut Vec<Arc<Node<N, L, C>, C>>,
             target_depth: usize,
             min_leaves_for_depth: usize,
             max_leaves_for_depth: usize,
         }
 
-        impl<'a, const N: usize, L> TargetDepthFromBack<'a, N, L>
+        impl<'a, const N: usize, L, C: RefCounter> TargetDepthFromBack<'a, N, L, C>
         where
             L: Leaf,
         {
@@ -1464,7 +2682,7 @@ mod tree_replace {
             /// Panics if `leaves` is empty or if the target_depth is 0.
             #[inline]
             pub(in crate::tree) fn new(
-                leaves: &'a mut Vec<Arc<Node<N, L>>>,
+                leaves: &'a mut Vec<Arc<Node<N, L, C>, C>>,
                 target_depth: usize,
             ) -> Self {
                 debug_assert!(!leaves.is_empty());
@@ -1473,21 +2691,22 @@ mod tree_replace {
                 Self {
                     leaves,
                     target_depth,
-                    min_leaves_for_depth: min_leaves_for_depth::<N, L>(
+                    min_leaves_for_depth: min_leaves_for_depth::<N, L, C>(
                         target_depth,
                     ),
-                    max_leaves_for_depth: max_leaves_for_depth::<N, L>(
+                    max_leaves_for_depth: max_leaves_for_depth::<N, L, C>(
                         target_depth,
                     ),
                 }
             }
         }
 
-        impl<const N: usize, L> Iterator for TargetDepthFromBack<'_, N, L>
+        impl<const N: usize, L, C: RefCounter> Iterator
+            for TargetDepthFromBack<'_, N, L, C>
         where
             L: Leaf,
         {
-            type Item = Arc<Node<N, L>>;
+            type Item = Arc<Node<N, L, C>, C>;
 
             #[inline]
             fn next(&mut self) -> Option<Self::Item> {
@@ -1506,14 +2725,15 @@ mod tree_replace {
 
                         debug_assert!(
                             last.depth() < self.target_depth
-                                || last.len() < Inode::<N, L>::min_children()
+                                || last.len()
+                                    < Inode::<N, L, C>::min_children()
                         );
 
                         debug_assert!(
-                            last.len() <= Inode::<N, L>::max_children()
+                            last.len() <= Inode::<N, L, C>::max_children()
                         );
 
-                        Arc::new(Node::Internal(last))
+                        Arc::new(Node::internal(last))
                     };
 
                     Some(last)
@@ -1529,14 +2749,14 @@ mod tree_replace {
                     debug_assert_eq!(inode.depth(), self.target_depth);
 
                     debug_assert!(
-                        inode.len() >= Inode::<N, L>::min_children()
+                        inode.len() >= Inode::<N, L, C>::min_children()
                     );
 
                     debug_assert!(
-                        inode.len() <= Inode::<N, L>::max_children()
+                        inode.len() <= Inode::<N, L, C>::max_children()
                     );
 
-                    Some(Arc::new(Node::Internal(inode)))
+                    Some(Arc::new(Node::internal(inode)))
                 }
             }
         }
@@ -1652,4 +2872,127 @@ mod tests {
         let tree = Tree::<4, usize>::from_leaves(0..20);
         assert_eq!(190, tree.summary().count);
     }
+
+    #[test]
+    fn from_leaves_is_balanced() {
+        for n in 1..300 {
+            let tree = Tree::<4, usize>::from_leaves(0..n);
+            tree.assert_invariants();
+            assert_eq!(tree.leaf_count(), n);
+            assert_eq!((0..n).sum::<usize>(), tree.summary().count);
+        }
+    }
+
+    #[test]
+    fn cursor_next_prev() {
+        let tree = Tree::<4, usize>::from_leaves(0..100);
+
+        let mut cursor = tree.cursor();
+
+        assert_eq!(*cursor.leaf().0, 0);
+        assert_eq!(cursor.prev_leaf(), None);
+
+        for leaf in 1..100 {
+            assert_eq!(*cursor.next_leaf().unwrap().0, leaf);
+            assert_eq!(cursor.offset::<LeavesMetric>(), leaf);
+        }
+
+        assert_eq!(cursor.next_leaf(), None);
+
+        for leaf in (0..99).rev() {
+            assert_eq!(*cursor.prev_leaf().unwrap().0, leaf);
+            assert_eq!(cursor.offset::<LeavesMetric>(), leaf);
+        }
+    }
+
+    #[test]
+    fn cursor_seek() {
+        for n in [1, 2, 5, 17, 100] {
+            let tree = Tree::<4, usize>::from_leaves(0..n);
+
+            let mut cursor = tree.cursor();
+
+            // Seek forward, backward and then jump around.
+            let measures = (0..=n)
+                .chain((0..=n).rev())
+                .chain((0..=n).map(|m| (m * 37) % (n + 1)));
+
+            for measure in measures {
+                let (leaf, offset) = tree.leaf_at_measure(measure);
+                assert_eq!(cursor.seek(measure), leaf);
+                assert_eq!(cursor.offset::<LeavesMetric>(), offset);
+            }
+
+            // The cached path is still valid after seeking.
+            cursor.seek(n / 2);
+            let mut expected = tree.leaf_at_measure(n / 2).1;
+            while let Some(leaf) = cursor.next_leaf() {
+                expected += 1;
+                assert_eq!(*leaf.0, expected);
+            }
+        }
+    }
+
+    #[test]
+    fn to_dot() {
+        let tree = Tree::<4, usize>::from_leaves(0..20);
+
+        let dot = tree.to_dot();
+
+        assert!(dot.starts_with("digraph {\n"));
+        assert!(dot.ends_with("}\n"));
+
+        let count = |pattern: &str| {
+            dot.lines().filter(|line| line.contains(pattern)).count()
+        };
+
+        // Every node except the root has exactly one parent.
+        assert_eq!(count("label="), count("->") + 1);
+        assert_eq!(count("shape=box"), tree.leaf_count());
+
+        // The clone shares the root, so it shows up with 2 references.
+        let clone = tree.clone();
+        assert!(clone.to_dot().contains("refs: 2"));
+    }
+
+    /// A `Tree` with a single leaf still keeps it behind an `Arc`, so cloning
+    /// it doesn't copy the leaf.
+    #[test]
+    fn single_leaf_clone_shares_root() {
+        let tree = Tree::<4, usize>::from_leaves([1]);
+        assert!(tree.root().is_leaf());
+
+        let clone = tree.clone();
+        assert!(tree.ptr_eq(&clone));
+        assert_eq!(Arc::strong_count(tree.root()), 2);
+        assert_eq!(tree.shared_leaf_count(&clone), 1);
+
+        drop(clone);
+        assert_eq!(Arc::strong_count(tree.root()), 1);
+    }
+
+    #[test]
+    fn validate() {
+        for n in [1, 2, 20, 200] {
+            let tree = Tree::<4, usize>::from_leaves(0..n);
+            assert!(tree.validate().is_empty());
+        }
+
+        // Lie about the summary of one of the leaves.
+        let tree =
+            Tree::<4, usize>::from_summarized_leaves((0..20).map(|n| {
+                let leaves = 1;
+                (n, Count { count: if n == 7 { 0 } else { n }, leaves })
+            }));
+
+        let violations = tree.validate();
+
+        assert_eq!(violations.len(), 1);
+
+        let InvariantViolation::StaleSummary { path } = &violations[0] else {
+            panic!("unexpected violation: {:?}", violations[0]);
+        };
+
+        assert_eq!(path.len(), tree.depth());
+    }
 }
@@ -1,9 +1,13 @@
 use super::traits::{BalancedLeaf, Leaf};
-use super::{Arc, Inode, Lnode, Node, Tree};
+use super::{Arc, AtomicCounter, Inode, Lnode, Node, RefCounter, Tree};
 
 /// An incremental [`Tree`] builder.
 #[derive(Clone)]
-pub struct TreeBuilder<const ARITY: usize, L: Leaf> {
+pub struct TreeBuilder<
+    const ARITY: usize,
+    L: Leaf,
+    C: RefCounter = AtomicCounter,
+> {
     /// A stack of internal nodes.
     ///
     /// # Invariants
@@ -13,31 +17,34 @@ pub struct TreeBuilder<const ARITY: usize, L: Leaf> {
     /// - all the inodes within a stack level have the same depth;
     ///
     /// - all the vectors at every stack level have a length strictly less than
-    /// `ARITY` (but it could also be zero, i.e. all levels except the first
-    /// one can be empty);
+    ///   `ARITY` (but it could also be zero, i.e. all levels except the first
+    ///   one can be empty);
     ///
     /// - the inodes are grouped in order of descending depth, with each stack
-    /// level containing inodes of depth one less than the previous level;
+    ///   level containing inodes of depth one less than the previous level;
     ///
     /// - every inode at every stack level is completely full, i.e. for every
-    /// inode it holds `inode.leaf_count() == max_children ^ inode.depth()`;
+    ///   inode it holds `inode.leaf_count() == max_children ^ inode.depth()`;
     ///
     /// - all the inodes in the last stack level (assuming there are any) have
-    /// a depth of 1.
-    stack: Vec<Vec<Arc<Node<ARITY, L>>>>,
+    ///   a depth of 1.
+    stack: Vec<Vec<Arc<Node<ARITY, L, C>, C>>>,
 
     /// A bunch of leaves waiting to be grouped into an internal node.
-    leaves: Vec<Arc<Node<ARITY, L>>>,
+    leaves: Vec<Arc<Node<ARITY, L, C>, C>>,
 }
 
-impl<const ARITY: usize, L: Leaf> Default for TreeBuilder<ARITY, L> {
+impl<const ARITY: usize, L: Leaf, C: RefCounter> Default
+    for TreeBuilder<ARITY, L, C>
+{
     #[inline]
     fn default() -> Self {
         Self { stack: Vec::new(), leaves: Vec::with_capacity(ARITY) }
     }
 }
 
-impl<const ARITY: usize, L: Leaf> TreeBuilder<ARITY, L> {
+impl<const ARITY: usize, L: Leaf, C: RefCounter> TreeBuilder<ARITY, L, C> {
+    /// Appends a leaf after all the ones appended so far.
     #[inline]
     pub fn append(&mut self, leaf: L) {
         debug_assert!(self.leaves.len() < ARITY);
@@ -48,7 +55,7 @@ impl<const ARITY: usize, L: Leaf> TreeBuilder<ARITY, L> {
             return;
         }
 
-        let mut inode = Arc::new(Node::Internal(Inode::from_children(
+        let mut inode = Arc::new(Node::internal(Inode::from_children(
             self.leaves.drain(..),
         )));
 
@@ -79,7 +86,7 @@ impl<const ARITY: usize, L: Leaf> TreeBuilder<ARITY, L> {
                 return;
             }
 
-            inode = Arc::new(Node::Internal(Inode::from_children(
+            inode = Arc::new(Node::internal(Inode::from_children(
                 stack_level.drain(..),
             )));
 
@@ -101,7 +108,7 @@ impl<const ARITY: usize, L: Leaf> TreeBuilder<ARITY, L> {
 
     /// Completes the build and outputs the final `Tree`, consuming `self`.
     #[inline]
-    pub fn build(mut self) -> Tree<ARITY, L>
+    pub fn build(mut self) -> Tree<ARITY, L, C>
     where
         L: Default + BalancedLeaf + Clone,
     {
@@ -112,13 +119,13 @@ impl<const ARITY: usize, L: Leaf> TreeBuilder<ARITY, L> {
                 // empty Tree. This is why we need the `Default` bound on `L`.
                 return Tree::default();
             } else if self.leaves.len() == 1 {
-                return Tree { root: self.leaves.into_iter().next().unwrap() };
+                return Tree::new(self.leaves.into_iter().next().unwrap());
             }
         }
 
         let mut root = if !self.leaves.is_empty() {
             debug_assert!(self.leaves.len() < ARITY);
-            Arc::new(Node::Internal(Inode::from_children(self.leaves)))
+            Arc::new(Node::internal(Inode::from_children(self.leaves)))
         } else {
             loop {
                 let stack_level = self.stack.pop().unwrap();
@@ -133,7 +140,7 @@ impl<const ARITY: usize, L: Leaf> TreeBuilder<ARITY, L> {
                     },
 
                     _ => {
-                        break Arc::new(Node::Internal(Inode::from_children(
+                        break Arc::new(Node::internal(Inode::from_children(
                             stack_level,
                         )))
                     },
@@ -151,7 +158,7 @@ impl<const ARITY: usize, L: Leaf> TreeBuilder<ARITY, L> {
 
             stack_level.push(root);
 
-            root = Arc::new(Node::Internal(Inode::from_children(stack_level)));
+            root = Arc::new(Node::internal(Inode::from_children(stack_level)));
         }
 
         {
@@ -165,9 +172,25 @@ impl<const ARITY: usize, L: Leaf> TreeBuilder<ARITY, L> {
 
         Node::replace_with_single_child(&mut root);
 
-        Tree { root }
+        Tree::new(root)
     }
 
+    /// Preallocates the levels of the stack needed to build a tree with (at
+    /// least) `leaf_count` leaves.
+    #[inline]
+    pub fn reserve(&mut self, leaf_count: usize) {
+        let mut levels = 0usize;
+        let mut nodes = leaf_count;
+
+        while nodes >= ARITY {
+            nodes /= ARITY;
+            levels += 1;
+        }
+
+        self.stack.reserve(levels.saturating_sub(self.stack.len()));
+    }
+
+    /// Creates a new, empty `TreeBuilder`.
     #[allow(dead_code)]
     #[inline]
     pub fn new() -> Self {
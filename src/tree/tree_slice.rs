@@ -4,10 +4,15 @@ use super::*;
 
 /// An immutable slice of a [`Tree`].
 #[derive(Debug)]
-pub struct TreeSlice<'a, const ARITY: usize, L: Leaf> {
+pub struct TreeSlice<
+    'a,
+    const ARITY: usize,
+    L: Leaf,
+    C: RefCounter = AtomicCounter,
+> {
     /// The deepest node that contains all the leaves between (and including)
     /// [`start_slice`](Self::start_slice) and [`end_slice`](Self::end_slice).
-    pub(super) root: &'a Arc<Node<ARITY, L>>,
+    pub(super) root: &'a Arc<Node<ARITY, L, C>, C>,
 
     /// The summary of the subtree under [`root`](Self::root) up to the start
     /// of the [`start_slice`](Self::start_slice).
@@ -33,7 +38,9 @@ pub struct TreeSlice<'a, const ARITY: usize, L: Leaf> {
     pub(super) leaf_count: usize,
 }
 
-impl<const ARITY: usize, L: Leaf> Clone for TreeSlice<'_, ARITY, L> {
+impl<const ARITY: usize, L: Leaf, C: RefCounter> Clone
+    for TreeSlice<'_, ARITY, L, C>
+{
     #[inline]
     fn clone(&self) -> Self {
         TreeSlice {
@@ -46,12 +53,16 @@ impl<const ARITY: usize, L: Leaf> Clone for TreeSlice<'_, ARITY, L> {
     }
 }
 
-impl<const ARITY: usize, L: Leaf> Copy for TreeSlice<'_, ARITY, L> where
-    L::Summary: Copy
+impl<const ARITY: usize, L: Leaf, C: RefCounter> Copy
+    for TreeSlice<'_, ARITY, L, C>
+where
+    L::Summary: Copy,
 {
 }
 
-impl<'a, const ARITY: usize, L: Leaf> TreeSlice<'a, ARITY, L> {
+impl<'a, const ARITY: usize, L: Leaf, C: RefCounter>
+    TreeSlice<'a, ARITY, L, C>
+{
     /*
       Public methods
     */
@@ -116,6 +127,8 @@ impl<'a, const ARITY: usize, L: Leaf> TreeSlice<'a, ARITY, L> {
         }
     }
 
+    /// Returns the measure of this `TreeSlice` according to the
+    /// [`BaseMetric`](BaseMeasured::BaseMetric) of its leaves.
     #[inline]
     pub fn base_measure(&self) -> L::BaseMetric {
         self.measure::<L::BaseMetric>()
@@ -140,11 +153,14 @@ impl<'a, const ARITY: usize, L: Leaf> TreeSlice<'a, ARITY, L> {
         }
     }
 
+    /// Returns the left sub-slice of the leaf containing the end of this
+    /// `TreeSlice`.
     #[inline]
     pub fn end_slice(&self) -> L::Slice<'a> {
         self.end_slice
     }
 
+    /// Returns the summary of the [`end_slice`](Self::end_slice()).
     #[inline]
     pub fn end_summary(&self) -> &L::Summary {
         &self.end_summary
@@ -177,16 +193,20 @@ impl<'a, const ARITY: usize, L: Leaf> TreeSlice<'a, ARITY, L> {
         }
     }
 
+    /// Returns the number of leaves spanned by this `TreeSlice`, including
+    /// the ones containing its start and its end.
     #[inline]
     pub fn leaf_count(&self) -> usize {
         self.leaf_count
     }
 
+    /// Returns an iterator over the leaf slices of this `TreeSlice`.
     #[inline]
-    pub fn leaves(&self) -> Leaves<'a, ARITY, L> {
+    pub fn leaves(&self) -> Leaves<'a, ARITY, L, C> {
         Leaves::from(self)
     }
 
+    /// Returns the `M`-measure of this `TreeSlice`.
     #[inline]
     pub fn measure<M>(&self) -> M
     where
@@ -196,34 +216,38 @@ impl<'a, const ARITY: usize, L: Leaf> TreeSlice<'a, ARITY, L> {
     }
 
     #[inline]
-    pub(super) fn root(&self) -> &'a Arc<Node<ARITY, L>> {
+    pub(super) fn root(&self) -> &'a Arc<Node<ARITY, L, C>, C> {
         self.root
     }
 
+    /// Returns the right sub-slice of the leaf containing the start of this
+    /// `TreeSlice`.
     #[inline]
     pub fn start_slice(&self) -> L::Slice<'a> {
         self.start_slice
     }
 
+    /// Returns the summary of the [`start_slice`](Self::start_slice()).
     #[inline]
     pub fn start_summary(&self) -> &L::Summary {
         &self.start_summary
     }
 
+    /// Returns the summary of this `TreeSlice`.
     #[inline]
     pub fn summary(&self) -> &L::Summary {
         &self.summary
     }
 }
 
-impl<'a, const ARITY: usize, L: Leaf> TreeSlice<'a, ARITY, L>
+impl<'a, const ARITY: usize, L: Leaf, C: RefCounter> TreeSlice<'a, ARITY, L, C>
 where
     for<'d> L::Slice<'d>: Default,
 {
     #[track_caller]
     #[inline]
     pub(super) fn from_range_in_root<M>(
-        root: &'a Arc<Node<ARITY, L>>,
+        root: &'a Arc<Node<ARITY, L, C>, C>,
         range: Range<M>,
     ) -> Self
     where
@@ -243,6 +267,11 @@ where
         }
     }
 
+    /// Returns the sub-slice of this `TreeSlice` in the given `M`-range.
+    ///
+    /// The range is relative to the start of this slice. The sub-slice
+    /// borrows from the same `Tree` as this slice, so slicing a slice never
+    /// needs to build a new `Tree` first.
     #[track_caller]
     #[inline]
     pub fn slice<M>(self, mut range: Range<M>) -> Self
@@ -299,7 +328,7 @@ where
     #[track_caller]
     #[inline]
     fn slice_impl<S, E>(
-        root: &'a Arc<Node<ARITY, L>>,
+        root: &'a Arc<Node<ARITY, L, C>, C>,
         start: S,
         end: E,
     ) -> Self
@@ -352,8 +381,57 @@ where
         slice
     }
 
+    /// Calls `fun` on the summaries of the subtrees of this `TreeSlice` in
+    /// order, descending into the internal nodes whose summary `descend`
+    /// returns `true` on, like [`Tree::for_each_subtree()`].
+    ///
+    /// The first and last subtrees are always the start and end slices. If
+    /// the summaries can't be subtracted theirs are computed again from
+    /// their contents, since the ones stored in the `TreeSlice` come from a
+    /// subtraction.
+    #[cfg(any(
+        feature = "grapheme-count",
+        feature = "paragraph-count",
+        feature = "fingerprint"
+    ))]
+    #[inline]
+    pub(crate) fn for_each_subtree(
+        &self,
+        mut descend: impl FnMut(&L::Summary) -> bool,
+        mut fun: impl FnMut(&L::Summary),
+    ) {
+        let summarize = |slice: L::Slice<'a>, summary: &L::Summary| {
+            if L::SUMMARY_IS_SUBTRACTABLE {
+                summary.clone()
+            } else {
+                slice.summarize()
+            }
+        };
+
+        fun(&summarize(self.start_slice, &self.start_summary));
+
+        if self.leaf_count == 1 {
+            return;
+        }
+
+        let start = L::BaseMetric::measure(&self.offset)
+            + L::BaseMetric::measure(&self.start_summary);
+
+        let end = L::BaseMetric::measure(&self.offset) + self.base_measure()
+            - L::BaseMetric::measure(&self.end_summary);
+
+        self.root.for_each_subtree_in_range(
+            start..end,
+            &mut descend,
+            &mut fun,
+        );
+
+        fun(&summarize(self.end_slice, &self.end_summary));
+    }
+
+    /// Returns an iterator over the `M`-units of this `TreeSlice`.
     #[inline]
-    pub fn units<M>(&self) -> Units<'a, ARITY, L, M>
+    pub fn units<M>(&self) -> Units<'a, ARITY, L, M, C>
     where
         M: Metric<L::Summary>,
     {
@@ -365,11 +443,11 @@ where
 /// range between `start` and `end`, together with the `S` and `E` offsets with
 /// respect to that node.
 #[inline]
-fn deepest_node_containing_range<const N: usize, L, S, E>(
-    mut node: &Arc<Node<N, L>>,
+fn deepest_node_containing_range<const N: usize, L, C: RefCounter, S, E>(
+    mut node: &Arc<Node<N, L, C>, C>,
     mut start: S,
     mut end: E,
-) -> (&Arc<Node<N, L>>, S, E)
+) -> (&Arc<Node<N, L, C>, C>, S, E)
 where
     L: Leaf,
     S: Metric<L::Summary>,
@@ -421,11 +499,15 @@ where
 ///
 /// Also returns the summary between the input `node` and the returned node.
 #[inline]
-pub(super) fn deepest_node_containing_base_range<const N: usize, L>(
-    mut node: &Arc<Node<N, L>>,
+pub(super) fn deepest_node_containing_base_range<
+    const N: usize,
+    L,
+    C: RefCounter,
+>(
+    mut node: &Arc<Node<N, L, C>, C>,
     mut start: L::BaseMetric,
     mut end: L::BaseMetric,
-) -> (&Arc<Node<N, L>>, L::Summary)
+) -> (&Arc<Node<N, L, C>, C>, L::Summary)
 where
     L: Leaf,
 {
@@ -492,9 +574,9 @@ where
 /// the other fields of the slice are valid.
 #[track_caller]
 #[inline]
-fn build_slice<'a, const N: usize, L, S, E>(
-    slice: &mut TreeSlice<'a, N, L>,
-    node: &'a Arc<Node<N, L>>,
+fn build_slice<'a, const N: usize, L, C: RefCounter, S, E>(
+    slice: &mut TreeSlice<'a, N, L, C>,
+    node: &'a Arc<Node<N, L, C>, C>,
     start: S,
     end: E,
     recompute_root: &mut bool,
@@ -1,10 +1,25 @@
 use super::traits::{DoubleEndedUnitMetric, Leaf, Metric, UnitMetric};
 use super::tree_slice;
-use super::{Arc, Lnode, Node, Tree, TreeSlice};
+use super::{
+    Arc,
+    ArcNode,
+    AtomicCounter,
+    Lnode,
+    Node,
+    RefCounter,
+    Tree,
+    TreeSlice,
+};
 
 /// An iterator over the units of a metric.
 #[derive(Clone)]
-pub struct Units<'a, const ARITY: usize, L: Leaf, M: Metric<L::Summary>> {
+pub struct Units<
+    'a,
+    const ARITY: usize,
+    L: Leaf,
+    M: Metric<L::Summary>,
+    C: RefCounter = AtomicCounter,
+> {
     /*
       Just like the `Leaves` iterator, this iterator is also implemented using
       two separate iterators, one for iterating forward (used in the `Iterator`
@@ -23,22 +38,27 @@ pub struct Units<'a, const ARITY: usize, L: Leaf, M: Metric<L::Summary>> {
     #[rustfmt::skip]
 
     /// Iterates over the `M`-units from front to back.
-    forward: UnitsForward<'a, ARITY, L, M>,
+    forward: UnitsForward<'a, ARITY, L, M, C>,
 
     /// Iterates over the `M`-units from back to front.
-    backward: UnitsBackward<'a, ARITY, L, M>,
+    backward: UnitsBackward<'a, ARITY, L, M, C>,
 
     /// The base measure of all the `TreeSlice`s which are yet to be yielded.
     remaining: L::BaseMetric,
 }
 
-impl<'a, const ARITY: usize, L: Leaf, M: Metric<L::Summary>>
-    From<&'a Tree<ARITY, L>> for Units<'a, ARITY, L, M>
+impl<
+        'a,
+        const ARITY: usize,
+        L: Leaf,
+        C: RefCounter,
+        M: Metric<L::Summary>,
+    > From<&'a Tree<ARITY, L, C>> for Units<'a, ARITY, L, M, C>
 where
     for<'d> L::Slice<'d>: Default,
 {
     #[inline]
-    fn from(tree: &'a Tree<ARITY, L>) -> Units<'a, ARITY, L, M> {
+    fn from(tree: &'a Tree<ARITY, L, C>) -> Units<'a, ARITY, L, M, C> {
         Self {
             forward: UnitsForward::from(tree),
             backward: UnitsBackward::from(tree),
@@ -47,13 +67,20 @@ where
     }
 }
 
-impl<'a, const ARITY: usize, L: Leaf, M: Metric<L::Summary>>
-    From<&TreeSlice<'a, ARITY, L>> for Units<'a, ARITY, L, M>
+impl<
+        'a,
+        const ARITY: usize,
+        L: Leaf,
+        C: RefCounter,
+        M: Metric<L::Summary>,
+    > From<&TreeSlice<'a, ARITY, L, C>> for Units<'a, ARITY, L, M, C>
 where
     for<'d> L::Slice<'d>: Default,
 {
     #[inline]
-    fn from(tree_slice: &TreeSlice<'a, ARITY, L>) -> Units<'a, ARITY, L, M> {
+    fn from(
+        tree_slice: &TreeSlice<'a, ARITY, L, C>,
+    ) -> Units<'a, ARITY, L, M, C> {
         Self {
             forward: UnitsForward::from(tree_slice),
             backward: UnitsBackward::from(tree_slice),
@@ -62,8 +89,8 @@ where
     }
 }
 
-impl<'a, const ARITY: usize, L: Leaf, M: UnitMetric<L>> Iterator
-    for Units<'a, ARITY, L, M>
+impl<'a, const ARITY: usize, L: Leaf, C: RefCounter, M: UnitMetric<L>> Iterator
+    for Units<'a, ARITY, L, M, C>
 {
     /// The iterator returns the next `TreeSlice` in the iterating range
     /// together with its advance.
@@ -91,7 +118,7 @@ impl<'a, const ARITY: usize, L: Leaf, M: UnitMetric<L>> Iterator
     /// following glyph.
     ///
     /// [1]: https://freetype.org/freetype2/docs/glyphs/glyph-metrics-3.svg
-    type Item = (TreeSlice<'a, ARITY, L>, L::BaseMetric);
+    type Item = (TreeSlice<'a, ARITY, L, C>, L::BaseMetric);
 
     #[inline]
     fn next(&mut self) -> Option<Self::Item> {
@@ -122,6 +149,8 @@ impl<'a, const ARITY: usize, L: Leaf, M: UnitMetric<L>> Iterator
 
                 iter.base_yielded = iter.base_total;
 
+                self.remaining -= L::BaseMetric::measure(&advance);
+
                 return Some((remainder, L::BaseMetric::measure(&advance)));
             } else {
                 return None;
@@ -132,12 +161,99 @@ impl<'a, const ARITY: usize, L: Leaf, M: UnitMetric<L>> Iterator
         iter.base_yielded += L::BaseMetric::measure(&advance);
         iter.units_yielded += M::one();
 
+        self.remaining -= L::BaseMetric::measure(&advance);
+
         Some((tree_slice, L::BaseMetric::measure(&advance)))
     }
+
+    #[inline]
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        // The number of units left can't be converted to a `usize` for a
+        // generic metric, but we know whether there's at least one.
+        if self.remaining == L::BaseMetric::zero() {
+            (0, Some(0))
+        } else {
+            (1, None)
+        }
+    }
+
+    #[inline]
+    fn count(self) -> usize {
+        match self.num_left() {
+            Some(count) => count,
+            None => self.fold(0, |count, _| count + 1),
+        }
+    }
+}
+
+impl<const ARITY: usize, L: Leaf, C: RefCounter, M: UnitMetric<L>>
+    Units<'_, ARITY, L, M, C>
+{
+    /// Returns the number of `TreeSlice`s which are yet to be yielded, or
+    /// `None` if `M` can't count its units without iterating over them.
+    ///
+    /// This is the number of `M`-units between the forward and backward
+    /// iterators, plus one if the remainder after the last unit is not
+    /// empty and hasn't been yielded yet.
+    #[inline]
+    fn num_left(&self) -> Option<usize> {
+        if self.remaining == L::BaseMetric::zero() {
+            return Some(0);
+        }
+
+        let units_left = M::num_units(
+            self.backward.units_remaining - self.forward.units_yielded,
+        )?;
+
+        Some(units_left + self.has_remainder_left() as usize)
+    }
+
+    /// Returns whether the remainder after the last `M`-unit of the
+    /// iterating range is not empty and is yet to be yielded.
+    ///
+    /// The backward iterator yields the remainder as soon as it's
+    /// initialized, while the forward one only yields it after all the
+    /// units, at which point there's nothing else left.
+    #[inline]
+    fn has_remainder_left(&self) -> bool {
+        if self.backward.is_initialized {
+            return false;
+        }
+
+        // The last slice of the range is only set for `TreeSlice`s. Its base
+        // measure is always positive, so if it doesn't contain any unit then
+        // it's part of the remainder.
+        let (mut slice, mut summary) = match &self.backward.last_slice {
+            Some((slice, summary)) => (*slice, summary.clone()),
+
+            None => {
+                let mut node = &**self.backward.leaf_node;
+
+                loop {
+                    match node {
+                        Node::Internal(inode) => node = &**inode.last(),
+                        Node::Leaf(leaf) => {
+                            break (leaf.as_slice(), leaf.summary().clone())
+                        },
+                    }
+                }
+            },
+        };
+
+        while M::measure(&summary) > M::zero() {
+            (_, _, _, slice, summary) = M::first_unit(slice, &summary);
+        }
+
+        L::BaseMetric::measure(&summary) > L::BaseMetric::zero()
+    }
 }
 
-impl<const ARITY: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
-    DoubleEndedIterator for Units<'_, ARITY, L, M>
+impl<
+        const ARITY: usize,
+        L: Leaf,
+        C: RefCounter,
+        M: DoubleEndedUnitMetric<L>,
+    > DoubleEndedIterator for Units<'_, ARITY, L, M, C>
 {
     #[inline]
     fn next_back(&mut self) -> Option<Self::Item> {
@@ -156,6 +272,8 @@ impl<const ARITY: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
 
                     iter.base_remaining -= L::BaseMetric::measure(&advance);
 
+                    self.remaining -= L::BaseMetric::measure(&advance);
+
                     return Some((
                         remainder,
                         L::BaseMetric::measure(&advance),
@@ -181,17 +299,25 @@ impl<const ARITY: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
         iter.base_remaining -= L::BaseMetric::measure(&advance);
         iter.units_remaining -= M::one();
 
+        self.remaining -= L::BaseMetric::measure(&advance);
+
         Some((tree_slice, L::BaseMetric::measure(&advance)))
     }
 }
 
-impl<const ARITY: usize, L: Leaf, M: UnitMetric<L>> core::iter::FusedIterator
-    for Units<'_, ARITY, L, M>
+impl<const ARITY: usize, L: Leaf, C: RefCounter, M: UnitMetric<L>>
+    core::iter::FusedIterator for Units<'_, ARITY, L, M, C>
 {
 }
 
 #[derive(Debug)]
-struct UnitsForward<'a, const N: usize, L: Leaf, M: Metric<L::Summary>> {
+struct UnitsForward<
+    'a,
+    const N: usize,
+    L: Leaf,
+    M: Metric<L::Summary>,
+    C: RefCounter,
+> {
     /// Whether `Self` has been initialized by calling
     /// [`initialize`](UnitsForward::initialize()).
     is_initialized: bool,
@@ -200,10 +326,10 @@ struct UnitsForward<'a, const N: usize, L: Leaf, M: Metric<L::Summary>> {
     /// path are guaranteed to be internal nodes, and the second item in each
     /// tuple represents the child index of next node in the path, or the index
     /// of the leaf node for the last node.
-    path: Vec<(&'a Arc<Node<N, L>>, usize)>,
+    path: Vec<(&'a ArcNode<N, L, C>, usize)>,
 
     /// The current leaf node.
-    leaf_node: &'a Arc<Node<N, L>>,
+    leaf_node: &'a Arc<Node<N, L, C>, C>,
 
     /// How much of `leaf_node`'s summary has already been yielded.
     yielded_in_leaf: L::Summary,
@@ -224,8 +350,8 @@ struct UnitsForward<'a, const N: usize, L: Leaf, M: Metric<L::Summary>> {
     /// we're iterating over a `TreeSlice`.
     last_slice: Option<(L::Slice<'a>, L::Summary)>,
 
-    /// The start of the yielding range as an offset into the root.
-    base_start: L::BaseMetric,
+    /// The summary of the root up to the start of the yielding range.
+    offset: L::Summary,
 
     /// The base measure of all the advances yielded so far.
     base_yielded: L::BaseMetric,
@@ -240,8 +366,8 @@ struct UnitsForward<'a, const N: usize, L: Leaf, M: Metric<L::Summary>> {
     units_total: M,
 }
 
-impl<const N: usize, L: Leaf, M: Metric<L::Summary>> Clone
-    for UnitsForward<'_, N, L, M>
+impl<const N: usize, L: Leaf, C: RefCounter, M: Metric<L::Summary>> Clone
+    for UnitsForward<'_, N, L, M, C>
 {
     #[inline]
     fn clone(&self) -> Self {
@@ -251,18 +377,24 @@ impl<const N: usize, L: Leaf, M: Metric<L::Summary>> Clone
             first_slice: self.first_slice.clone(),
             last_slice: self.last_slice.clone(),
             start_summary: self.start_summary.clone(),
+            offset: self.offset.clone(),
             ..*self
         }
     }
 }
 
-impl<'a, const ARITY: usize, L: Leaf, M: Metric<L::Summary>>
-    From<&'a Tree<ARITY, L>> for UnitsForward<'a, ARITY, L, M>
+impl<
+        'a,
+        const ARITY: usize,
+        L: Leaf,
+        C: RefCounter,
+        M: Metric<L::Summary>,
+    > From<&'a Tree<ARITY, L, C>> for UnitsForward<'a, ARITY, L, M, C>
 where
     for<'d> L::Slice<'d>: Default,
 {
     #[inline]
-    fn from(tree: &'a Tree<ARITY, L>) -> UnitsForward<'a, ARITY, L, M> {
+    fn from(tree: &'a Tree<ARITY, L, C>) -> UnitsForward<'a, ARITY, L, M, C> {
         Self {
             is_initialized: false,
             path: Vec::with_capacity(tree.root().depth()),
@@ -272,7 +404,7 @@ where
             start_summary: L::Summary::default(),
             first_slice: None,
             last_slice: None,
-            base_start: L::BaseMetric::zero(),
+            offset: L::Summary::default(),
             base_yielded: L::BaseMetric::zero(),
             base_total: tree.base_measure(),
             units_yielded: M::zero(),
@@ -281,15 +413,20 @@ where
     }
 }
 
-impl<'a, const ARITY: usize, L: Leaf, M: Metric<L::Summary>>
-    From<&TreeSlice<'a, ARITY, L>> for UnitsForward<'a, ARITY, L, M>
+impl<
+        'a,
+        const ARITY: usize,
+        L: Leaf,
+        C: RefCounter,
+        M: Metric<L::Summary>,
+    > From<&TreeSlice<'a, ARITY, L, C>> for UnitsForward<'a, ARITY, L, M, C>
 where
     for<'d> L::Slice<'d>: Default,
 {
     #[inline]
     fn from(
-        tree_slice: &TreeSlice<'a, ARITY, L>,
-    ) -> UnitsForward<'a, ARITY, L, M> {
+        tree_slice: &TreeSlice<'a, ARITY, L, C>,
+    ) -> UnitsForward<'a, ARITY, L, M, C> {
         Self {
             is_initialized: false,
             path: Vec::with_capacity(tree_slice.root().depth()),
@@ -305,7 +442,7 @@ where
                 tree_slice.end_slice,
                 tree_slice.end_summary.clone(),
             )),
-            base_start: L::BaseMetric::measure(&tree_slice.offset),
+            offset: tree_slice.offset.clone(),
             base_yielded: L::BaseMetric::zero(),
             base_total: tree_slice.base_measure(),
             units_yielded: M::zero(),
@@ -314,7 +451,9 @@ where
     }
 }
 
-impl<'a, const N: usize, L: Leaf, M: UnitMetric<L>> UnitsForward<'a, N, L, M> {
+impl<'a, const N: usize, L: Leaf, C: RefCounter, M: UnitMetric<L>>
+    UnitsForward<'a, N, L, M, C>
+{
     /// Initializes `Self` by populating the path down to the internal node
     /// containing the leaf node at `base_offset`, which is set to `leaf_node`.
     ///
@@ -328,7 +467,10 @@ impl<'a, const N: usize, L: Leaf, M: UnitMetric<L>> UnitsForward<'a, N, L, M> {
         // The leaf node is actually the root at this point.
         let mut node = self.leaf_node;
 
-        let mut offset = L::BaseMetric::zero();
+        let base_start = L::BaseMetric::measure(&self.offset);
+
+        // The summary of the leaves before the current node.
+        let mut before = L::Summary::default();
 
         'outer: loop {
             match &**node {
@@ -336,12 +478,14 @@ impl<'a, const N: usize, L: Leaf, M: UnitMetric<L>> UnitsForward<'a, N, L, M> {
                     for (idx, child) in inode.children().iter().enumerate() {
                         let child_measure = child.base_measure();
 
-                        if offset + child_measure > self.base_start {
+                        if L::BaseMetric::measure(&before) + child_measure
+                            > base_start
+                        {
                             self.path.push((node, idx));
                             node = child;
                             continue 'outer;
                         } else {
-                            offset += child_measure;
+                            before += child.summary();
                         }
                     }
 
@@ -352,9 +496,12 @@ impl<'a, const N: usize, L: Leaf, M: UnitMetric<L>> UnitsForward<'a, N, L, M> {
                     self.leaf_node = node;
 
                     match self.first_slice.take() {
+                        // The first slice doesn't necessarily extend to the
+                        // end of the leaf, e.g. if the whole range is
+                        // contained in it.
                         Some((slice, summary)) => {
                             self.yielded_in_leaf =
-                                leaf.summary().clone() - &summary;
+                                self.offset.clone() - &before;
 
                             self.start_slice = slice;
                             self.start_summary = summary;
@@ -428,7 +575,7 @@ impl<'a, const N: usize, L: Leaf, M: UnitMetric<L>> UnitsForward<'a, N, L, M> {
     /// should only be called when `self.start_slice` has an `M`-measure of at
     /// least `M::one()`.
     #[inline]
-    fn next_unit_in_leaf(&mut self) -> (TreeSlice<'a, N, L>, L::Summary) {
+    fn next_unit_in_leaf(&mut self) -> (TreeSlice<'a, N, L, C>, L::Summary) {
         debug_assert!(M::measure(&self.start_summary) > M::zero());
         debug_assert!(self.units_total > self.units_yielded);
 
@@ -464,17 +611,17 @@ impl<'a, const N: usize, L: Leaf, M: UnitMetric<L>> UnitsForward<'a, N, L, M> {
     /// - `leaf` is that leaf node;
     ///
     /// - `root` is the deepest internal node containing both the current
-    /// `self.leaf_node` and `leaf` in its subtree;
+    ///   `self.leaf_node` and `leaf` in its subtree;
     ///
     /// - `before` is the total base measure of all the nodes from the first
-    /// leaf in `root`'s subtree to the leaf preceding the current
-    /// `self.leaf_node`. If `self.leaf_node` is the first leaf in `root`'s
-    /// subtree this measure will be zero;
+    ///   leaf in `root`'s subtree to the leaf preceding the current
+    ///   `self.leaf_node`. If `self.leaf_node` is the first leaf in `root`'s
+    ///   subtree this measure will be zero;
     ///
     /// - `summary` and `count` are the total summary and leaf count of all the
-    /// nodes between (but not including) `self.leaf_node` and `leaf`. If
-    /// `leaf` is the leaf node immediately after `self.leaf` then `summary`
-    /// will be empty and `count` will be zero.
+    ///   nodes between (but not including) `self.leaf_node` and `leaf`. If
+    ///   `leaf` is the leaf node immediately after `self.leaf` then `summary`
+    ///   will be empty and `count` will be zero.
     ///
     /// NOTE: it assumes that such a leaf node exists. If that's not the case
     /// this function may panic or return a leaf node outside of the valid
@@ -487,7 +634,7 @@ impl<'a, const N: usize, L: Leaf, M: UnitMetric<L>> UnitsForward<'a, N, L, M> {
     #[inline]
     fn next_leaf_with_measure(
         &mut self,
-    ) -> (&'a Lnode<L>, &'a Arc<Node<N, L>>, L::Summary, L::Summary, usize)
+    ) -> (&'a Lnode<L>, &'a Arc<Node<N, L, C>, C>, L::Summary, L::Summary, usize)
     {
         debug_assert!(self.units_total > self.units_yielded);
 
@@ -561,7 +708,7 @@ impl<'a, const N: usize, L: Leaf, M: UnitMetric<L>> UnitsForward<'a, N, L, M> {
     ///
     /// Invariants: the returned [`Node`] is guaranteed to be a leaf node.
     #[inline]
-    fn previous_leaf(&self) -> &'a Arc<Node<N, L>> {
+    fn previous_leaf(&self) -> &'a Arc<Node<N, L, C>, C> {
         let mut path_idx = self.path.len() - 1;
 
         let mut node = loop {
@@ -593,7 +740,7 @@ impl<'a, const N: usize, L: Leaf, M: UnitMetric<L>> UnitsForward<'a, N, L, M> {
     /// when the `TreeSlice` is not totally contained in `self.leaf_node` and
     /// it's not the remainder.
     #[inline]
-    fn next_unit_in_range(&mut self) -> (TreeSlice<'a, N, L>, L::Summary) {
+    fn next_unit_in_range(&mut self) -> (TreeSlice<'a, N, L, C>, L::Summary) {
         debug_assert_eq!(M::measure(&self.start_summary), M::zero());
         debug_assert!(self.units_total > self.units_yielded);
 
@@ -718,13 +865,15 @@ impl<'a, const N: usize, L: Leaf, M: UnitMetric<L>> UnitsForward<'a, N, L, M> {
     #[inline]
     fn last_leaf(
         &self,
-    ) -> (&'a Lnode<L>, &'a Arc<Node<N, L>>, L::Summary, L::Summary, usize)
+    ) -> (&'a Lnode<L>, &'a Arc<Node<N, L, C>, C>, L::Summary, L::Summary, usize)
     {
         // Step 1: find the index of deepest node in the path that fully
         // contains `range`.
 
-        let mut range = (self.base_start + self.base_yielded)
-            ..(self.base_start + self.base_total);
+        let base_start = L::BaseMetric::measure(&self.offset);
+
+        let mut range =
+            (base_start + self.base_yielded)..(base_start + self.base_total);
 
         let root_idx = {
             let mut root_idx = self.path.len() - 1;
@@ -853,7 +1002,7 @@ impl<'a, const N: usize, L: Leaf, M: UnitMetric<L>> UnitsForward<'a, N, L, M> {
     /// iterate forward this only gets called when we are sure there's a
     /// remainder to yield.
     #[inline]
-    fn remainder(&mut self) -> (TreeSlice<'a, N, L>, L::Summary) {
+    fn remainder(&mut self) -> (TreeSlice<'a, N, L, C>, L::Summary) {
         debug_assert_eq!(self.units_total, self.units_yielded);
         debug_assert!(self.base_total > self.base_yielded);
 
@@ -926,7 +1075,13 @@ impl<'a, const N: usize, L: Leaf, M: UnitMetric<L>> UnitsForward<'a, N, L, M> {
 }
 
 #[derive(Debug)]
-struct UnitsBackward<'a, const N: usize, L: Leaf, M: Metric<L::Summary>> {
+struct UnitsBackward<
+    'a,
+    const N: usize,
+    L: Leaf,
+    M: Metric<L::Summary>,
+    C: RefCounter,
+> {
     /// Whether `Self` has been initialized by calling
     /// [`initialize`](UnitsBackward::initialize()).
     is_initialized: bool,
@@ -935,10 +1090,10 @@ struct UnitsBackward<'a, const N: usize, L: Leaf, M: Metric<L::Summary>> {
     /// path are guaranteed to be internal nodes, and the second item in each
     /// tuple represents the child index of next node in the path, or the index
     /// of the leaf node for the last node.
-    path: Vec<(&'a Arc<Node<N, L>>, usize)>,
+    path: Vec<(&'a ArcNode<N, L, C>, usize)>,
 
     /// The current leaf node.
-    leaf_node: &'a Arc<Node<N, L>>,
+    leaf_node: &'a Arc<Node<N, L, C>, C>,
 
     /// How much of `leaf_node`'s base measure has already been yielded.
     yielded_in_leaf: L::Summary,
@@ -959,8 +1114,8 @@ struct UnitsBackward<'a, const N: usize, L: Leaf, M: Metric<L::Summary>> {
     /// we're iterating over a `TreeSlice`.
     last_slice: Option<(L::Slice<'a>, L::Summary)>,
 
-    /// The start of the yielding range as an offset into the root.
-    base_start: L::BaseMetric,
+    /// The summary of the root up to the start of the yielding range.
+    offset: L::Summary,
 
     /// The base measure of all the advances which are yet to be yielded.
     base_remaining: L::BaseMetric,
@@ -969,8 +1124,8 @@ struct UnitsBackward<'a, const N: usize, L: Leaf, M: Metric<L::Summary>> {
     units_remaining: M,
 }
 
-impl<const N: usize, L: Leaf, M: Metric<L::Summary>> Clone
-    for UnitsBackward<'_, N, L, M>
+impl<const N: usize, L: Leaf, C: RefCounter, M: Metric<L::Summary>> Clone
+    for UnitsBackward<'_, N, L, M, C>
 {
     #[inline]
     fn clone(&self) -> Self {
@@ -980,18 +1135,24 @@ impl<const N: usize, L: Leaf, M: Metric<L::Summary>> Clone
             first_slice: self.first_slice.clone(),
             last_slice: self.last_slice.clone(),
             end_summary: self.end_summary.clone(),
+            offset: self.offset.clone(),
             ..*self
         }
     }
 }
 
-impl<'a, const ARITY: usize, L: Leaf, M: Metric<L::Summary>>
-    From<&'a Tree<ARITY, L>> for UnitsBackward<'a, ARITY, L, M>
+impl<
+        'a,
+        const ARITY: usize,
+        L: Leaf,
+        C: RefCounter,
+        M: Metric<L::Summary>,
+    > From<&'a Tree<ARITY, L, C>> for UnitsBackward<'a, ARITY, L, M, C>
 where
     for<'d> L::Slice<'d>: Default,
 {
     #[inline]
-    fn from(tree: &'a Tree<ARITY, L>) -> UnitsBackward<'a, ARITY, L, M> {
+    fn from(tree: &'a Tree<ARITY, L, C>) -> UnitsBackward<'a, ARITY, L, M, C> {
         Self {
             is_initialized: false,
             path: Vec::with_capacity(tree.root().depth()),
@@ -1001,22 +1162,27 @@ where
             end_summary: L::Summary::default(),
             first_slice: None,
             last_slice: None,
-            base_start: L::BaseMetric::zero(),
+            offset: L::Summary::default(),
             base_remaining: tree.base_measure(),
             units_remaining: tree.root().measure::<M>(),
         }
     }
 }
 
-impl<'a, const ARITY: usize, L: Leaf, M: Metric<L::Summary>>
-    From<&TreeSlice<'a, ARITY, L>> for UnitsBackward<'a, ARITY, L, M>
+impl<
+        'a,
+        const ARITY: usize,
+        L: Leaf,
+        C: RefCounter,
+        M: Metric<L::Summary>,
+    > From<&TreeSlice<'a, ARITY, L, C>> for UnitsBackward<'a, ARITY, L, M, C>
 where
     for<'d> L::Slice<'d>: Default,
 {
     #[inline]
     fn from(
-        tree_slice: &TreeSlice<'a, ARITY, L>,
-    ) -> UnitsBackward<'a, ARITY, L, M> {
+        tree_slice: &TreeSlice<'a, ARITY, L, C>,
+    ) -> UnitsBackward<'a, ARITY, L, M, C> {
         Self {
             is_initialized: false,
             path: Vec::with_capacity(tree_slice.root().depth()),
@@ -1032,18 +1198,23 @@ where
                 tree_slice.end_slice,
                 tree_slice.end_summary.clone(),
             )),
-            base_start: L::BaseMetric::measure(&tree_slice.offset),
+            offset: tree_slice.offset.clone(),
             base_remaining: tree_slice.base_measure(),
             units_remaining: tree_slice.measure::<M>(),
         }
     }
 }
 
-impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
-    UnitsBackward<'a, N, L, M>
+impl<
+        'a,
+        const N: usize,
+        L: Leaf,
+        C: RefCounter,
+        M: DoubleEndedUnitMetric<L>,
+    > UnitsBackward<'a, N, L, M, C>
 {
     /// Initializes `Self` by populating the path down to the internal node
-    /// containing the leaf node at `base_start + base_remaining`, which is set
+    /// containing the leaf node at the end of the yielding range, which is set
     /// to `leaf_node`.
     ///
     /// Also sets `yielded_in_leaf`, `end_slice` and `end_summary`.
@@ -1056,9 +1227,12 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
         // The leaf node is actually the root at this point.
         let mut node = self.leaf_node;
 
-        let last_slice_offset = self.base_start + self.base_remaining;
+        let base_start = L::BaseMetric::measure(&self.offset);
 
-        let mut offset = L::BaseMetric::zero();
+        let last_slice_offset = base_start + self.base_remaining;
+
+        // The summary of the leaves before the current node.
+        let mut before = L::Summary::default();
 
         'outer: loop {
             match &**node {
@@ -1066,12 +1240,14 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
                     for (idx, child) in inode.children().iter().enumerate() {
                         let child_measure = child.base_measure();
 
-                        if offset + child_measure >= last_slice_offset {
+                        if L::BaseMetric::measure(&before) + child_measure
+                            >= last_slice_offset
+                        {
                             self.path.push((node, idx));
                             node = child;
                             continue 'outer;
                         } else {
-                            offset += child_measure;
+                            before += child.summary();
                         }
                     }
 
@@ -1083,9 +1259,18 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
 
                     match self.last_slice.take() {
                         Some((slice, summary)) => {
-                            self.yielded_in_leaf =
+                            let mut yielded_in_leaf =
                                 leaf.summary().clone() - &summary;
 
+                            // The last slice doesn't start at the start of
+                            // the leaf if the whole range is contained in it.
+                            if L::BaseMetric::measure(&before) < base_start {
+                                yielded_in_leaf += &before;
+                                yielded_in_leaf -= &self.offset;
+                            }
+
+                            self.yielded_in_leaf = yielded_in_leaf;
+
                             self.end_slice = slice;
                             self.end_summary = summary;
                         },
@@ -1152,13 +1337,14 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
     #[inline]
     fn first_leaf(
         &self,
-    ) -> (&'a Lnode<L>, &'a Arc<Node<N, L>>, L::Summary, L::Summary, usize)
+    ) -> (&'a Lnode<L>, &'a Arc<Node<N, L, C>, C>, L::Summary, L::Summary, usize)
     {
         // Step 1: find the index of deepest node in the path that fully
         // contains `range`.
 
-        let mut range =
-            self.base_start..(self.base_start + self.base_remaining);
+        let base_start = L::BaseMetric::measure(&self.offset);
+
+        let mut range = base_start..(base_start + self.base_remaining);
 
         let root_idx = {
             let mut root_idx = self.path.len() - 1;
@@ -1281,11 +1467,11 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
     /// Yields the first unit in the range. This function is used by
     ///
     /// - [`Self::remainder()`] if there are no units in the iterating range, in
-    /// which case it'll yield the whole range;
+    ///   which case it'll yield the whole range;
     ///
     /// - by [`Self::previous()`] when there's one final unit to yield.
     #[inline]
-    fn first(&mut self) -> (TreeSlice<'a, N, L>, L::Summary) {
+    fn first(&mut self) -> (TreeSlice<'a, N, L, C>, L::Summary) {
         debug_assert!(self.base_remaining > L::BaseMetric::zero());
 
         let (_, _, end_slice, end_summary, mut advance) =
@@ -1294,10 +1480,15 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
         // First, check if the current leaf node is the root. If it is we're
         // done.
         if self.base_remaining == L::BaseMetric::measure(&advance) {
+            // The range doesn't necessarily start at the start of the leaf.
+            let offset = self.leaf_node.summary().clone()
+                - &self.yielded_in_leaf
+                - &advance;
+
             return (
                 TreeSlice {
                     root: self.leaf_node,
-                    offset: L::Summary::default(),
+                    offset,
                     summary: end_summary.clone(),
                     start_slice: end_slice,
                     start_summary: end_summary.clone(),
@@ -1384,7 +1575,9 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
     /// correctly `self.end_slice` cannot have any `M`-remainder and it needs
     /// to contain at least 2 `M`-units.
     #[inline]
-    fn previous_unit_in_leaf(&mut self) -> (TreeSlice<'a, N, L>, L::Summary) {
+    fn previous_unit_in_leaf(
+        &mut self,
+    ) -> (TreeSlice<'a, N, L, C>, L::Summary) {
         debug_assert!(M::measure(&self.end_summary) > M::one());
         debug_assert!(self.units_remaining > M::zero());
 
@@ -1395,12 +1588,15 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
             L::BaseMetric::measure(&rest_summary) > L::BaseMetric::zero()
         );
 
-        let offset = rest_summary.clone();
-
         self.yielded_in_leaf += &advance;
         self.end_slice = rest;
         self.end_summary = rest_summary;
 
+        // The end slice doesn't start at the start of the leaf if the whole
+        // range is contained in it, so the offset can't just be the summary
+        // of the rest.
+        let offset = self.leaf_node.summary().clone() - &self.yielded_in_leaf;
+
         (
             TreeSlice {
                 root: self.leaf_node,
@@ -1424,17 +1620,17 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
     /// - `leaf` is that leaf node;
     ///
     /// - `root` is the deepest internal node containing both `leaf` and the
-    /// current `self.leaf_node` in its subtree;
+    ///   current `self.leaf_node` in its subtree;
     ///
     /// - `after` is the total base measure of all the nodes from the last leaf
-    /// in `root`'s subtree to the leaf after the current `self.leaf_node`. If
-    /// `self.leaf_node` if the last leaf in `root`'s subtree this measure will
-    /// be zero;
+    ///   in `root`'s subtree to the leaf after the current `self.leaf_node`. If
+    ///   `self.leaf_node` if the last leaf in `root`'s subtree this measure will
+    ///   be zero;
     ///
     /// - `summary` and `count` are the total summary and leaf count of all the
-    /// nodes between (but not including) `leaf` and `self.leaf_node`. If
-    /// `leaf` is the leaf node immediately before `self.leaf` then `summary`
-    /// will be empty and `count` will be zero.
+    ///   nodes between (but not including) `leaf` and `self.leaf_node`. If
+    ///   `leaf` is the leaf node immediately before `self.leaf` then `summary`
+    ///   will be empty and `count` will be zero.
     ///
     /// NOTE: it assumes that such a leaf node exists. If that's not the case
     /// this function may panic or return a leaf node outside of the valid
@@ -1447,7 +1643,7 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
     #[inline]
     fn previous_leaf_with_measure(
         &mut self,
-    ) -> (&'a Lnode<L>, &'a Arc<Node<N, L>>, L::Summary, L::Summary, usize)
+    ) -> (&'a Lnode<L>, &'a Arc<Node<N, L, C>, C>, L::Summary, L::Summary, usize)
     {
         debug_assert!(self.units_remaining > M::zero());
 
@@ -1523,7 +1719,7 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
     ///
     /// Invariants: the returned node is guaranteed to be a leaf node.
     #[inline]
-    fn next_leaf(&self) -> &'a Arc<Node<N, L>> {
+    fn next_leaf(&self) -> &'a Arc<Node<N, L, C>, C> {
         let mut path_idx = self.path.len() - 1;
 
         let mut node = loop {
@@ -1564,7 +1760,9 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
     ///
     /// [1]: UnitsBackward::previous_leaf_with_measure()
     #[inline]
-    fn previous_unit_in_range(&mut self) -> (TreeSlice<'a, N, L>, L::Summary) {
+    fn previous_unit_in_range(
+        &mut self,
+    ) -> (TreeSlice<'a, N, L, C>, L::Summary) {
         debug_assert!(self.units_remaining > M::zero());
 
         let (_, _, end_slice, end_summary, mut advance) =
@@ -1741,7 +1939,7 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
     /// It also follows that if `M` is the `BaseMetric` this function will
     /// always return `None`.
     #[inline]
-    fn remainder(&mut self) -> Option<(TreeSlice<'a, N, L>, L::Summary)> {
+    fn remainder(&mut self) -> Option<(TreeSlice<'a, N, L, C>, L::Summary)> {
         debug_assert!(self.base_remaining > L::BaseMetric::zero());
 
         if M::measure(&self.end_summary) > M::zero() {
@@ -1749,12 +1947,13 @@ impl<'a, const N: usize, L: Leaf, M: DoubleEndedUnitMetric<L>>
                 M::remainder(self.end_slice, &self.end_summary);
 
             if L::BaseMetric::measure(&summary) > L::BaseMetric::zero() {
-                let offset = rest_summary.clone();
-
                 self.yielded_in_leaf += &summary;
                 self.end_slice = rest;
                 self.end_summary = rest_summary;
 
+                let offset =
+                    self.leaf_node.summary().clone() - &self.yielded_in_leaf;
+
                 Some((
                     TreeSlice {
                         root: self.leaf_node,
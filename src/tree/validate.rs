@@ -0,0 +1,228 @@
+use super::{Inode, Leaf, Node, RefCounter, Summarize};
+
+/// An invariant of a [`Tree`](super::Tree) or of a [`Rope`](crate::Rope)
+/// that doesn't hold, as reported by [`Tree::validate()`](super::Tree::validate())
+/// and [`Rope::validate()`](crate::Rope::validate()).
+///
+/// The variants about the structure of the tree identify the offending node
+/// by its `path`, i.e. the indices of the children to follow from the root
+/// to get to it (an empty path being the root itself). The ones about the
+/// contents of a leaf identify it by its index among all the leaves.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum InvariantViolation {
+    /// An internal node has fewer or more children than allowed.
+    ChildCount {
+        /// The path to the internal node.
+        path: Vec<usize>,
+
+        /// The number of children of the internal node.
+        children: usize,
+
+        /// The minimum number of children it's allowed to have.
+        min: usize,
+
+        /// The maximum number of children it's allowed to have.
+        max: usize,
+    },
+
+    /// The summary stored for a node doesn't match the one computed from its
+    /// contents, i.e. from its children if it's an internal node or from its
+    /// value if it's a leaf.
+    StaleSummary {
+        /// The path to the node.
+        path: Vec<usize>,
+    },
+
+    /// The number of leaves stored in an internal node doesn't match the
+    /// number of leaves in its subtree.
+    StaleLeafCount {
+        /// The path to the internal node.
+        path: Vec<usize>,
+
+        /// The number of leaves stored in the internal node.
+        stored: usize,
+
+        /// The actual number of leaves in its subtree.
+        actual: usize,
+    },
+
+    /// A child of an internal node is not exactly one level below it, which
+    /// means that not all the leaves are at the same depth.
+    UnevenDepth {
+        /// The path to the child.
+        path: Vec<usize>,
+
+        /// The depth of the child's parent.
+        parent_depth: usize,
+
+        /// The depth of the child.
+        depth: usize,
+    },
+
+    /// A leaf is smaller than the minimum size. This is allowed only when
+    /// the leaf is the root.
+    UnderfilledLeaf {
+        /// The index of the leaf.
+        leaf: usize,
+
+        /// The size of the leaf.
+        len: usize,
+
+        /// The minimum size it's allowed to have.
+        min: usize,
+    },
+
+    /// A total stored at the root of a `Rope`'s tree doesn't match the one
+    /// recounted from its text.
+    StaleTotal {
+        /// The name of the total, e.g. `"byte length"`.
+        metric: &'static str,
+
+        /// The total stored at the root.
+        stored: usize,
+
+        /// The total recounted from the text.
+        actual: usize,
+    },
+
+    /// A chunk of a `Rope` contains bytes which aren't valid UTF-8, or is
+    /// split in the middle of a code point.
+    InvalidUtf8 {
+        /// The index of the chunk.
+        leaf: usize,
+    },
+}
+
+impl core::fmt::Display for InvariantViolation {
+    #[inline]
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::ChildCount { path, children, min, max } => write!(
+                f,
+                "the internal node at {path:?} has {children} children, but \
+                 it should have between {min} and {max}"
+            ),
+
+            Self::StaleSummary { path } => {
+                write!(f, "the summary of the node at {path:?} is out of date")
+            },
+
+            Self::StaleLeafCount { path, stored, actual } => write!(
+                f,
+                "the internal node at {path:?} thinks it contains {stored} \
+                 leaves, but it actually contains {actual}"
+            ),
+
+            Self::UnevenDepth { path, parent_depth, depth } => write!(
+                f,
+                "the node at {path:?} has depth {depth}, but its parent has \
+                 depth {parent_depth}"
+            ),
+
+            Self::UnderfilledLeaf { leaf, len, min } => write!(
+                f,
+                "the leaf at index {leaf} has size {len}, but it should have \
+                 at least {min}"
+            ),
+
+            Self::StaleTotal { metric, stored, actual } => write!(
+                f,
+                "the rope thinks its {metric} is {stored}, but it's actually \
+                 {actual}"
+            ),
+
+            Self::InvalidUtf8 { leaf } => {
+                write!(f, "the chunk at index {leaf} is not valid UTF-8")
+            },
+        }
+    }
+}
+
+impl std::error::Error for InvariantViolation {}
+
+/// Checks the invariants of `node` and of all the nodes under it, pushing
+/// the ones that don't hold to `violations`.
+#[inline]
+pub(super) fn validate_node<const N: usize, L: Leaf, C: RefCounter>(
+    node: &Node<N, L, C>,
+    path: &mut Vec<usize>,
+    violations: &mut Vec<InvariantViolation>,
+) {
+    match node {
+        Node::Internal(inode) => validate_inode(inode, path, violations),
+
+        Node::Leaf(leaf) => {
+            if leaf.as_slice().summarize() != *leaf.summary() {
+                violations.push(InvariantViolation::StaleSummary {
+                    path: path.clone(),
+                });
+            }
+        },
+    }
+}
+
+#[inline]
+fn validate_inode<const N: usize, L: Leaf, C: RefCounter>(
+    inode: &Inode<N, L, C>,
+    path: &mut Vec<usize>,
+    violations: &mut Vec<InvariantViolation>,
+) {
+    // The root is the only inode that can have as few as 2 children.
+    let min =
+        if path.is_empty() { 2 } else { Inode::<N, L, C>::min_children() };
+
+    let max = Inode::<N, L, C>::max_children();
+
+    if inode.len() < min || inode.len() > max {
+        violations.push(InvariantViolation::ChildCount {
+            path: path.clone(),
+            children: inode.len(),
+            min,
+            max,
+        });
+    }
+
+    let mut summary = L::Summary::default();
+
+    let mut leaf_count = 0;
+
+    for (idx, (child, child_summary)) in
+        inode.children().iter().zip(inode.child_summaries()).enumerate()
+    {
+        path.push(idx);
+
+        if child.depth() + 1 != inode.depth() {
+            violations.push(InvariantViolation::UnevenDepth {
+                path: path.clone(),
+                parent_depth: inode.depth(),
+                depth: child.depth(),
+            });
+        }
+
+        if child.summary() != child_summary {
+            violations
+                .push(InvariantViolation::StaleSummary { path: path.clone() });
+        }
+
+        validate_node(child, path, violations);
+
+        path.pop();
+
+        summary += child.summary();
+        leaf_count += child.leaf_count();
+    }
+
+    if *inode.summary() != summary {
+        violations
+            .push(InvariantViolation::StaleSummary { path: path.clone() });
+    }
+
+    if inode.leaf_count() != leaf_count {
+        violations.push(InvariantViolation::StaleLeafCount {
+            path: path.clone(),
+            stored: inode.leaf_count(),
+            actual: leaf_count,
+        });
+    }
+}
@@ -0,0 +1,218 @@
+use std::ops::Range;
+
+use crop::{Anchor, Gravity, Rope, Tracked};
+use rand::Rng;
+
+mod common;
+
+use common::{LARGE, SMALL};
+
+/// Where an anchor with the given offset and gravity should end up after
+/// the bytes in `replaced` have been replaced by `inserted_len` new ones.
+fn model_offset(
+    offset: usize,
+    gravity: Gravity,
+    replaced: Range<usize>,
+    inserted_len: usize,
+) -> usize {
+    if offset < replaced.start {
+        offset
+    } else if offset > replaced.end {
+        offset - replaced.len() + inserted_len
+    } else if gravity == Gravity::Left {
+        replaced.start
+    } else {
+        replaced.start + inserted_len
+    }
+}
+
+fn random_gravity(rng: &mut impl Rng) -> Gravity {
+    if rng.gen() {
+        Gravity::Left
+    } else {
+        Gravity::Right
+    }
+}
+
+#[test]
+fn insert_at_anchor() {
+    let mut t = Tracked::new(Rope::from("ab"));
+
+    let left = t.anchor(1, Gravity::Left);
+    let right = t.anchor(1, Gravity::Right);
+
+    t.insert(1, "xyz");
+
+    assert_eq!(t.anchor_offset(left), Some(1));
+    assert_eq!(t.anchor_offset(right), Some(4));
+}
+
+#[test]
+fn delete_around_anchor() {
+    let mut t = Tracked::new(Rope::from("Hello Earth!"));
+
+    let left = t.anchor(8, Gravity::Left);
+    let right = t.anchor(8, Gravity::Right);
+
+    t.replace(6..11, "Mars");
+
+    assert_eq!(t.rope(), "Hello Mars!");
+    assert_eq!(t.anchor_offset(left), Some(6));
+    assert_eq!(t.anchor_offset(right), Some(10));
+}
+
+#[test]
+fn append_and_writer() {
+    use std::io::Write;
+
+    let mut t = Tracked::new(Rope::from("foo"));
+
+    let left = t.anchor(3, Gravity::Left);
+    let right = t.anchor(3, Gravity::Right);
+
+    t.edit(|rope| rope.append(Rope::from("bar")));
+
+    assert_eq!(t.anchor_offset(left), Some(3));
+    assert_eq!(t.anchor_offset(right), Some(6));
+
+    t.edit(|rope| write!(rope.writer(), "baz").unwrap());
+
+    assert_eq!(t.anchor_offset(left), Some(3));
+    assert_eq!(t.anchor_offset(right), Some(9));
+}
+
+#[test]
+fn edit_with_distant_changes() {
+    let mut t = Tracked::new(Rope::from(LARGE));
+
+    let start = t.anchor(1000, Gravity::Right);
+    let middle = t.anchor(5000, Gravity::Left);
+    let end = t.anchor(LARGE.len(), Gravity::Left);
+
+    t.edit(|rope| {
+        rope.replace(10000..10005, "a");
+        rope.insert(500, "🐸🐸");
+    });
+
+    assert_eq!(t.anchor_offset(start), Some(1008));
+    assert_eq!(t.anchor_offset(middle), Some(5008));
+    assert_eq!(t.anchor_offset(end), Some(LARGE.len() + 4));
+}
+
+#[test]
+fn split_off_clamps_anchors() {
+    let mut t = Tracked::new(Rope::from("Hello Earth!"));
+
+    let hello = t.anchor(0, Gravity::Right);
+    let earth = t.anchor(6, Gravity::Right);
+
+    let rest = t.edit(|rope| rope.split_off(3));
+
+    assert_eq!(rest, "lo Earth!");
+    assert_eq!(t.anchor_offset(hello), Some(0));
+    assert_eq!(t.anchor_offset(earth), Some(3));
+}
+
+#[test]
+fn clones_dont_share_edits() {
+    let mut t = Tracked::new(Rope::from("Hello Earth!"));
+
+    let anchor = t.anchor(6, Gravity::Left);
+
+    let clone = t.clone();
+
+    t.insert(0, "Oh, ");
+
+    assert_eq!(t.anchor_offset(anchor), Some(10));
+    assert_eq!(clone.anchor_offset(anchor), Some(6));
+}
+
+#[test]
+fn anchors_of_other_trackeds() {
+    let mut first = Tracked::new(Rope::from("Hello Earth!"));
+    let mut second = Tracked::new(Rope::from("Hello Mars!"));
+
+    let hello = first.anchor(0, Gravity::Left);
+    let mars = second.anchor(6, Gravity::Left);
+
+    assert_eq!(first.anchor_offset(mars), None);
+    assert_eq!(second.anchor_offset(hello), None);
+    assert_eq!(second.remove_anchor(hello), None);
+    assert_eq!(second.anchor_offset(mars), Some(6));
+
+    // The anchors created by a clone after cloning don't resolve in the
+    // original, and vice versa.
+    let mut clone = first.clone();
+    let earth = first.anchor(6, Gravity::Left);
+    let bang = clone.anchor(11, Gravity::Left);
+
+    assert_eq!(clone.anchor_offset(hello), Some(0));
+    assert_eq!(clone.anchor_offset(earth), None);
+    assert_eq!(first.anchor_offset(bang), None);
+    assert_eq!(first.anchor_offset(earth), Some(6));
+    assert_eq!(clone.anchor_offset(bang), Some(11));
+}
+
+#[test]
+fn removed_anchors_are_not_reused() {
+    let mut t = Tracked::new(Rope::from("Hello Earth!"));
+
+    let first = t.anchor(3, Gravity::Left);
+    t.remove_anchor(first);
+
+    let second = t.anchor(5, Gravity::Left);
+
+    assert_ne!(first, second);
+    assert_eq!(t.anchor_offset(first), None);
+    assert_eq!(t.anchor_offset(second), Some(5));
+}
+
+#[test]
+#[should_panic]
+fn anchor_not_char_boundary() {
+    let mut t = Tracked::new(Rope::from("🌎"));
+    t.anchor(1, Gravity::Left);
+}
+
+/// Tests that random edits move the anchors of a `Tracked` like a naive
+/// implementation would.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_against_model() {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..20 {
+        let mut t = Tracked::new(Rope::from(SMALL));
+
+        let mut anchors = Vec::<(Anchor, usize, Gravity)>::new();
+
+        for _ in 0..200 {
+            let byte_len = t.rope().byte_len();
+
+            if rng.gen_range(0..3) == 0 {
+                let offset = rng.gen_range(0..=byte_len);
+                let gravity = random_gravity(&mut rng);
+                anchors.push((t.anchor(offset, gravity), offset, gravity));
+            }
+
+            if !anchors.is_empty() && rng.gen_range(0..10) == 0 {
+                let (anchor, offset, _) =
+                    anchors.swap_remove(rng.gen_range(0..anchors.len()));
+                assert_eq!(t.remove_anchor(anchor), Some(offset));
+            }
+
+            let start = rng.gen_range(0..=byte_len);
+            let end = rng.gen_range(start..=(start + 50).min(byte_len));
+            let inserted_len = rng.gen_range(0..20);
+
+            t.replace(start..end, "a".repeat(inserted_len));
+
+            for (anchor, offset, gravity) in &mut anchors {
+                *offset =
+                    model_offset(*offset, *gravity, start..end, inserted_len);
+
+                assert_eq!(t.anchor_offset(*anchor), Some(*offset));
+            }
+        }
+    }
+}
@@ -0,0 +1,302 @@
+use crop::Rope;
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+#[test]
+fn append_empty() {
+    let mut r = Rope::from("");
+    r.append(Rope::from(""));
+    r.assert_invariants();
+    assert_eq!(r, "");
+
+    r.append(Rope::from("foo"));
+    r.assert_invariants();
+    assert_eq!(r, "foo");
+
+    r.append(Rope::new());
+    r.assert_invariants();
+    assert_eq!(r, "foo");
+}
+
+#[test]
+fn split_off_at_ends() {
+    let mut r = Rope::from(MEDIUM);
+
+    let right = r.split_off(r.byte_len());
+    r.assert_invariants();
+    right.assert_invariants();
+    assert_eq!(r, MEDIUM);
+    assert_eq!(right, "");
+
+    let right = r.split_off(0);
+    r.assert_invariants();
+    right.assert_invariants();
+    assert_eq!(r, "");
+    assert_eq!(right, MEDIUM);
+}
+
+#[test]
+#[should_panic]
+fn split_off_out_of_bounds() {
+    let mut r = Rope::from("foo");
+    r.split_off(4);
+}
+
+#[test]
+fn split_at_keeps_original() {
+    let r = Rope::from(MEDIUM);
+
+    let (left, right) = r.split_at(0);
+    assert_eq!(left, "");
+    assert_eq!(right, MEDIUM);
+
+    let (left, right) = r.split_at(r.byte_len());
+    assert_eq!(left, MEDIUM);
+    assert_eq!(right, "");
+
+    let (left, right) = r.split_at(MEDIUM.len() / 2);
+    left.assert_invariants();
+    right.assert_invariants();
+    assert_eq!(left, MEDIUM[..MEDIUM.len() / 2]);
+    assert_eq!(right, MEDIUM[MEDIUM.len() / 2..]);
+
+    r.assert_invariants();
+    assert_eq!(r, MEDIUM);
+}
+
+#[test]
+#[should_panic]
+fn split_at_out_of_bounds() {
+    let r = Rope::from("foo");
+    let _ = r.split_at(4);
+}
+
+#[test]
+#[should_panic]
+fn split_at_not_char_boundary() {
+    let r = Rope::from("🌎");
+    let _ = r.split_at(1);
+}
+
+/// Tests that appending `Rope`s of very different lengths, and so of very
+/// different depths, on either side always matches concatenating the
+/// equivalent strings.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn append_different_depths() {
+    for left in [TINY, SMALL, MEDIUM, LARGE] {
+        for right in [TINY, SMALL, MEDIUM, LARGE] {
+            let mut r = Rope::from(left);
+            r.append(Rope::from(right));
+            r.assert_invariants();
+            assert_eq!(r, format!("{left}{right}"));
+        }
+    }
+}
+
+/// Tests that splitting a `Rope` at random offsets and appending the two
+/// halves back together always gives back the original `Rope`.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn split_off_then_append_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [CURSED_LIPSUM, TINY, SMALL, MEDIUM, LARGE] {
+        for _ in 0..10 {
+            let mut at = rng.gen_range(0..=s.len());
+
+            while !s.is_char_boundary(at) {
+                at -= 1;
+            }
+
+            let mut left = Rope::from(s);
+            let right = left.split_off(at);
+
+            left.assert_invariants();
+            right.assert_invariants();
+            assert_eq!(left, s[..at]);
+            assert_eq!(right, s[at..]);
+
+            left.append(right);
+            left.assert_invariants();
+            assert_eq!(left, s);
+        }
+    }
+}
+
+/// Tests that splitting a `Rope` at random offsets with `split_at()` gives
+/// the same halves as `split_off()`, without changing the original.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn split_at_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [CURSED_LIPSUM, TINY, SMALL, MEDIUM, LARGE] {
+        let r = Rope::from(s);
+
+        for _ in 0..10 {
+            let mut at = rng.gen_range(0..=s.len());
+
+            while !s.is_char_boundary(at) {
+                at -= 1;
+            }
+
+            let (left, right) = r.split_at(at);
+
+            left.assert_invariants();
+            right.assert_invariants();
+            assert_eq!(left, s[..at]);
+            assert_eq!(right, s[at..]);
+
+            let mut split_off = r.clone();
+            assert_eq!(split_off.split_off(at), right);
+            assert_eq!(split_off, left);
+        }
+
+        assert_eq!(r, s);
+    }
+}
+
+/// Tests that a `Rope` built by appending many small `Rope`s is the same as
+/// the one built from the whole string at once.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn append_many_small() {
+    let mut rng = rand::thread_rng();
+
+    let mut r = Rope::new();
+    let mut s = String::new();
+
+    let mut rest = MEDIUM;
+
+    // Checking the invariants after every append would make the test
+    // quadratic in the length of `MEDIUM`, which is too slow when the
+    // metrics that have to be recomputed from the text are enabled.
+    let mut appends = 0;
+
+    while !rest.is_empty() {
+        let mut len = rng.gen_range(0..=rest.len().min(100));
+
+        while !rest.is_char_boundary(len) {
+            len -= 1;
+        }
+
+        let (piece, after) = rest.split_at(len);
+
+        r.append(Rope::from(piece));
+        s.push_str(piece);
+
+        appends += 1;
+
+        if appends % 64 == 0 {
+            r.assert_invariants();
+        }
+
+        rest = after;
+    }
+
+    r.assert_invariants();
+    assert_eq!(r, s);
+}
+
+#[test]
+fn sum_empty() {
+    let r = core::iter::empty::<Rope>().sum::<Rope>();
+    r.assert_invariants();
+    assert_eq!(r, "");
+
+    let r = ["", ""].into_iter().sum::<Rope>();
+    r.assert_invariants();
+    assert_eq!(r, "");
+}
+
+/// Tests that summing `Rope`s, `RopeSlice`s and `&str`s gives the
+/// concatenation of their texts.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn sum_ropes_slices_and_strs() {
+    let texts = [TINY, "", SMALL, CURSED_LIPSUM, MEDIUM, "a", LARGE, "\n"];
+    let expected = texts.concat();
+
+    let r = texts.iter().map(|&s| Rope::from(s)).sum::<Rope>();
+    r.assert_invariants();
+    assert_eq!(r, expected);
+
+    let sum = texts.iter().copied().sum::<Rope>();
+    sum.assert_invariants();
+    assert_eq!(sum, expected);
+
+    // Slice the concatenation back into the original texts.
+    let mut start = 0;
+    let slices = texts.iter().map(|s| {
+        let slice = r.byte_slice(start..start + s.len());
+        start += s.len();
+        slice
+    });
+
+    let sum = slices.sum::<Rope>();
+    sum.assert_invariants();
+    assert_eq!(sum, expected);
+}
+
+#[test]
+fn join_empty() {
+    assert_eq!(Rope::join(Vec::<&str>::new(), ", "), "");
+    assert_eq!(Rope::join([""], ", "), "");
+    assert_eq!(Rope::join(["", ""], ", "), ", ");
+    assert_eq!(Rope::join(["a", "b", "c"], ""), "abc");
+}
+
+#[test]
+fn join_mixed_items() {
+    let r = Rope::from(MEDIUM);
+
+    let strs = [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM];
+
+    let joined = Rope::join(strs, "\n---\n");
+    joined.assert_invariants();
+    assert_eq!(joined, strs.join("\n---\n"));
+
+    let owned = strs.map(String::from);
+
+    assert_eq!(Rope::join(&owned, "🦀"), strs.join("🦀"));
+    assert_eq!(Rope::join(owned.clone(), "🦀"), strs.join("🦀"));
+
+    let ropes = strs.map(Rope::from);
+
+    assert_eq!(Rope::join(&ropes, ", "), strs.join(", "));
+    assert_eq!(Rope::join(ropes, ", "), strs.join(", "));
+
+    let slices = [r.byte_slice(..10), r.byte_slice(100..), r.byte_slice(..)];
+
+    let joined = Rope::join(slices, "\r\n");
+    joined.assert_invariants();
+    assert_eq!(joined, [&MEDIUM[..10], &MEDIUM[100..], MEDIUM].join("\r\n"));
+}
+
+/// Tests that joining many lines gives the same result as joining them as
+/// strings.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn join_random() {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..20 {
+        let lines = (0..rng.gen_range(0..500))
+            .map(|_| {
+                let mut end = rng.gen_range(0..=CURSED_LIPSUM.len());
+                while !CURSED_LIPSUM.is_char_boundary(end) {
+                    end -= 1;
+                }
+                &CURSED_LIPSUM[..end]
+            })
+            .collect::<Vec<_>>();
+
+        let r = Rope::join(lines.iter().copied(), "\n");
+        r.assert_invariants();
+        assert_eq!(r, lines.join("\n"));
+    }
+}
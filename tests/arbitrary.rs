@@ -0,0 +1,42 @@
+#[cfg(feature = "arbitrary")]
+mod arbitrary {
+    use arbitrary::{Arbitrary, Unstructured};
+    use crop::Rope;
+    use rand::Rng;
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn arbitrary_ropes() {
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..1000 {
+            let len = rng.gen_range(0..8192);
+            let bytes = (0..len).map(|_| rng.gen::<u8>()).collect::<Vec<_>>();
+
+            let r = Rope::arbitrary(&mut Unstructured::new(&bytes)).unwrap();
+            r.assert_invariants();
+
+            let s = String::from(&r);
+            assert_eq!(r, Rope::from(s.as_str()));
+            assert_eq!(r.line_len(), Rope::from(s.as_str()).line_len());
+        }
+    }
+}
+
+#[cfg(feature = "proptest")]
+mod proptest {
+    use crop::Rope;
+    use proptest::prelude::*;
+
+    proptest! {
+        #[cfg_attr(miri, ignore)]
+        #[test]
+        fn proptest_ropes(r in any::<Rope>()) {
+            r.assert_invariants();
+
+            let s = String::from(&r);
+            prop_assert_eq!(&r, &Rope::from(s.as_str()));
+            prop_assert_eq!(r.line_len(), Rope::from(s.as_str()).line_len());
+        }
+    }
+}
@@ -0,0 +1,95 @@
+use crop::Rope;
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+#[test]
+fn eq_ignore_ascii_case_empty() {
+    let r = Rope::new();
+    assert!(r.eq_ignore_ascii_case(""));
+    assert!(r.eq_ignore_ascii_case(&r));
+    assert!(r.byte_slice(..).eq_ignore_ascii_case(""));
+    assert!(!r.eq_ignore_ascii_case("a"));
+}
+
+#[test]
+fn eq_ignore_ascii_case_non_ascii() {
+    let r = Rope::from("Ärger über Öl");
+
+    assert!(r.eq_ignore_ascii_case("ÄRGER üBER Öl"));
+
+    // Only the case of ASCII letters is ignored.
+    assert!(!r.eq_ignore_ascii_case("ärger über öl"));
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn eq_ignore_ascii_case_chunked() {
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let upper = s.to_ascii_uppercase();
+        let lower = s.to_ascii_lowercase();
+
+        let r = Rope::from(s);
+        let r_upper = Rope::from(upper.as_str());
+
+        assert!(r.eq_ignore_ascii_case(&upper));
+        assert!(r.eq_ignore_ascii_case(lower.as_str()));
+        assert!(r.eq_ignore_ascii_case(&r_upper));
+        assert!(r_upper.byte_slice(..).eq_ignore_ascii_case(&r));
+
+        let mut start = s.len() / 3;
+        while !s.is_char_boundary(start) {
+            start += 1;
+        }
+
+        // Slices starting in the middle of a chunk.
+        assert!(r
+            .byte_slice(start..)
+            .eq_ignore_ascii_case(r_upper.byte_slice(start..)));
+
+        assert!(r.byte_slice(start..).eq_ignore_ascii_case(&lower[start..]));
+    }
+}
+
+/// Tests that comparing `Rope`s whose chunks are split at random points
+/// gives the same result as comparing `String`s.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn eq_ignore_ascii_case_random() {
+    let mut rng = rand::thread_rng();
+
+    let alphabet = ["a", "B", "é", "🦀"];
+
+    for _ in 0..20 {
+        let mut s = String::new();
+        let mut r = Rope::new();
+
+        for _ in 0..500 {
+            let mut offset = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(offset) {
+                offset -= 1;
+            }
+
+            let text = (0..rng.gen_range(1..10))
+                .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+                .collect::<String>();
+
+            s.insert_str(offset, &text);
+            r.insert(offset, &text);
+        }
+
+        let mut other = s.to_ascii_uppercase();
+
+        assert!(r.eq_ignore_ascii_case(&other));
+        assert!(r.eq_ignore_ascii_case(&Rope::from(other.as_str())));
+
+        // Change a single character.
+        let offset = other.find(['A', 'B']).unwrap();
+        other.replace_range(offset..offset + 1, "C");
+
+        assert!(!r.eq_ignore_ascii_case(&other));
+        assert!(!r.eq_ignore_ascii_case(&Rope::from(other.as_str())));
+    }
+}
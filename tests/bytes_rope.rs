@@ -0,0 +1,102 @@
+use crop::BytesRope;
+use rand::Rng;
+
+#[test]
+fn empty() {
+    let r = BytesRope::new();
+    assert!(r.is_empty());
+    assert_eq!(r.chunks().count(), 0);
+    assert_eq!(r.bytes().count(), 0);
+    assert_eq!(r, [][..]);
+    assert_eq!(r.slice(..), [][..]);
+}
+
+#[test]
+fn not_utf8() {
+    let bytes = [0xff, 0xfe, 0x00, 0xc3, 0x28, 0xa0, 0xa1, 0xe2, 0x82];
+
+    let mut r = BytesRope::from(&bytes[..]);
+    assert_eq!(r.len(), bytes.len());
+    assert_eq!(r.to_vec(), bytes);
+
+    // Editing and slicing in the middle of what would be a multi-byte
+    // sequence is fine.
+    r.replace(3..4, [0xf0]);
+    assert_eq!(r.byte(3), 0xf0);
+    assert_eq!(r.slice(2..5), [0x00, 0xf0, 0x28][..]);
+}
+
+#[test]
+fn bytes_double_ended() {
+    let bytes = (0..=255).cycle().take(10_000).collect::<Vec<u8>>();
+    let r = BytesRope::from(bytes.clone());
+
+    assert!(r.bytes().eq(bytes.iter().copied()));
+    assert!(r.bytes().rev().eq(bytes.iter().rev().copied()));
+
+    let slice = r.slice(1234..8765);
+    assert!(slice.bytes().eq(bytes[1234..8765].iter().copied()));
+    assert!(slice.bytes().rev().eq(bytes[1234..8765].iter().rev().copied()));
+}
+
+#[test]
+fn append_split_off() {
+    let mut r = BytesRope::from(vec![1; 5000]);
+    r.append(BytesRope::from(vec![2; 5000]));
+    r.assert_invariants();
+    assert_eq!(r.len(), 10_000);
+
+    let right = r.split_off(4000);
+    r.assert_invariants();
+    right.assert_invariants();
+    assert_eq!(r, vec![1; 4000]);
+    assert_eq!(right.slice(..1000), vec![1; 1000]);
+    assert_eq!(right.slice(1000..), vec![2; 5000]);
+}
+
+#[test]
+#[should_panic]
+fn byte_out_of_bounds() {
+    let r = BytesRope::from(&[1, 2, 3][..]);
+    r.byte(3);
+}
+
+/// Tests that random edits and slices of a `BytesRope` give the same result
+/// as the same operations on a `Vec<u8>`.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_edits() {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..20 {
+        let len = rng.gen_range(0..20_000);
+        let mut vec = (0..len).map(|_| rng.gen()).collect::<Vec<u8>>();
+        let mut r = BytesRope::from(vec.clone());
+
+        for _ in 0..100 {
+            let start = rng.gen_range(0..=vec.len());
+            let end = rng.gen_range(start..=vec.len().min(start + 5000));
+            let insert_len = rng.gen_range(0..5000);
+            let insert =
+                (0..insert_len).map(|_| rng.gen()).collect::<Vec<u8>>();
+
+            r.replace(start..end, &insert);
+            vec.splice(start..end, insert);
+
+            r.assert_invariants();
+            assert_eq!(r, vec);
+
+            let start = rng.gen_range(0..=vec.len());
+            let end = rng.gen_range(start..=vec.len());
+            let slice = r.slice(start..end);
+            slice.assert_invariants();
+            assert_eq!(slice, vec[start..end]);
+            assert_eq!(BytesRope::from(slice), vec[start..end]);
+
+            if start < end {
+                assert_eq!(slice.byte(0), vec[start]);
+                assert_eq!(slice.byte(end - start - 1), vec[end - 1]);
+            }
+        }
+    }
+}
@@ -0,0 +1,63 @@
+use crop::Rope;
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TEXT_EMOJI, TINY};
+
+#[test]
+fn case_conversion_empty() {
+    let r = Rope::new();
+    assert_eq!(r.to_lowercase(), "");
+    assert_eq!(r.to_uppercase(), "");
+}
+
+#[test]
+fn case_conversion_matches_str() {
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM, TEXT_EMOJI] {
+        let r = Rope::from(s);
+
+        let lower = r.to_lowercase();
+        lower.assert_invariants();
+        assert_eq!(lower, s.to_lowercase());
+
+        let upper = r.to_uppercase();
+        upper.assert_invariants();
+        assert_eq!(upper, s.to_uppercase());
+    }
+}
+
+/// Tests that capital sigmas are lowercased according to their context even
+/// when the words they're in span several chunks.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn lowercase_final_sigma_random() {
+    let mut rng = rand::thread_rng();
+
+    let words = ["ΟΔΟΣ", "Σ", "ΣΑ", "A'Σ", "ΣΣ ", " ", "\n", "a.", "ΑΣ."];
+
+    let mut r = Rope::from(LARGE);
+    let mut s = String::from(LARGE);
+
+    for _ in 0..200 {
+        let mut offset = rng.gen_range(0..=s.len());
+
+        while !s.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let word = words[rng.gen_range(0..words.len())];
+
+        r.insert(offset, word);
+        s.insert_str(offset, word);
+    }
+
+    assert_eq!(r.to_lowercase(), s.to_lowercase());
+
+    let start = (s.len() / 3..).find(|&i| s.is_char_boundary(i)).unwrap();
+    let end = (2 * s.len() / 3..).find(|&i| s.is_char_boundary(i)).unwrap();
+    assert_eq!(
+        r.byte_slice(start..end).to_lowercase(),
+        s[start..end].to_lowercase()
+    );
+}
@@ -0,0 +1,80 @@
+use crop::Rope;
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+#[test]
+fn check_empty() {
+    let report = Rope::new().check();
+
+    assert!(report.is_ok());
+    assert!(report.violations().is_empty());
+    assert_eq!(report.stored_byte_len(), 0);
+    assert_eq!(report.actual_byte_len(), 0);
+    assert_eq!(report.to_string(), "all the invariants hold");
+}
+
+#[test]
+fn check_totals() {
+    for s in ["", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let report = Rope::from(s).check();
+
+        assert!(report.is_ok(), "{report}");
+
+        assert_eq!(report.stored_byte_len(), s.len());
+        assert_eq!(report.actual_byte_len(), s.len());
+
+        #[cfg(feature = "char-metric")]
+        {
+            assert_eq!(report.stored_char_len(), s.chars().count());
+            assert_eq!(report.actual_char_len(), s.chars().count());
+        }
+
+        #[cfg(feature = "lines")]
+        {
+            let line_breaks = s.matches('\n').count();
+            assert_eq!(report.stored_line_breaks(), line_breaks);
+            assert_eq!(report.actual_line_breaks(), line_breaks);
+        }
+    }
+}
+
+/// Tests that `Rope::check()` doesn't report anything after random edits.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn check_random_edits() {
+    let mut rng = rand::thread_rng();
+
+    let mut r = Rope::from(MEDIUM);
+    let mut s = String::from(MEDIUM);
+
+    for _ in 0..200 {
+        let mut start = rng.gen_range(0..=s.len());
+        while !s.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        let mut end = rng.gen_range(start..=s.len().min(start + 1000));
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        let mut text_end = rng.gen_range(0..=CURSED_LIPSUM.len());
+        while !CURSED_LIPSUM.is_char_boundary(text_end) {
+            text_end -= 1;
+        }
+
+        let text = &CURSED_LIPSUM[..text_end];
+
+        r.replace(start..end, text);
+        s.replace_range(start..end, text);
+
+        let report = r.check();
+        assert!(report.is_ok(), "{report}");
+        assert_eq!(report.actual_byte_len(), s.len());
+    }
+
+    assert_eq!(r, s);
+}
@@ -0,0 +1,162 @@
+use crop::tree::AtomicCounter;
+use crop::{LfBreaks, Rope, RopeBuilder, RopeWith, ZeroWeight};
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+type RopeOf<const MAX_BYTES: usize, const MIN_FILL: usize = 25> =
+    RopeWith<AtomicCounter, ZeroWeight, LfBreaks, MAX_BYTES, MIN_FILL>;
+
+/// Tests that random edits on a rope with the given chunk size and minimum
+/// fill give the same text as the same edits on a `String`.
+fn random_edits<const MAX_BYTES: usize, const MIN_FILL: usize>() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut r = RopeOf::<MAX_BYTES, MIN_FILL>::from(s);
+        let mut string = s.to_owned();
+
+        for _ in 0..20 {
+            let mut start = rng.gen_range(0..=string.len());
+            while !string.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=string.len());
+            while !string.is_char_boundary(end) {
+                end += 1;
+            }
+
+            r.replace(start..end, "foo\r\nbär🐸");
+            string.replace_range(start..end, "foo\r\nbär🐸");
+
+            r.assert_invariants();
+            assert_eq!(r, string.as_str());
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn chunk_size_smallest() {
+    random_edits::<4, 25>();
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn chunk_size_small() {
+    random_edits::<64, 25>();
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn chunk_size_large() {
+    random_edits::<8192, 25>();
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn chunk_size_largest() {
+    random_edits::<{ u16::MAX as usize }, 25>();
+}
+
+/// No chunk is ever longer than the chunk size.
+#[test]
+fn chunk_size_bounds_chunks() {
+    let r = RopeOf::<16>::from(MEDIUM);
+    assert!(r.chunks().all(|chunk| chunk.len() <= 16));
+    assert!(r.stats().leaf_count() >= MEDIUM.len() / 16);
+
+    let mut builder =
+        RopeBuilder::<AtomicCounter, ZeroWeight, LfBreaks, 16>::default();
+    for line in MEDIUM.split_inclusive('\n') {
+        builder.append(line);
+    }
+    let r = builder.build();
+    r.assert_invariants();
+    assert_eq!(r, MEDIUM);
+    assert!(r.chunks().all(|chunk| chunk.len() <= 16));
+}
+
+/// The leaf occupancy is relative to the chunk size of the rope.
+#[test]
+fn chunk_size_leaf_occupancy() {
+    let r = RopeOf::<1024>::from(LARGE);
+    let occupancy = r.stats().leaf_occupancy();
+    assert!(occupancy > 0.5 && occupancy <= 1.0);
+
+    let r = RopeOf::<65535>::from(SMALL);
+    assert_eq!(r.stats().leaf_count(), 1);
+    assert_eq!(r.stats().leaf_occupancy(), SMALL.len() as f64 / 65535.0);
+}
+
+/// Slices and iterators work the same whatever the chunk size.
+#[test]
+fn chunk_size_same_text() {
+    let small = RopeOf::<8>::from(LARGE);
+    let default = Rope::from(LARGE);
+
+    assert_eq!(small.byte_len(), default.byte_len());
+    assert!(small.bytes().eq(default.bytes()));
+    assert!(small.chars().eq(default.chars()));
+
+    let range = LARGE.len() / 3..LARGE.len() / 2;
+    assert_eq!(small.byte_slice(range.clone()), &LARGE[range.clone()]);
+    assert_eq!(
+        small.byte_slice(range.clone()).to_string(),
+        default.byte_slice(range).to_string()
+    );
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn min_fill_lowest() {
+    random_edits::<4, 1>();
+    random_edits::<64, 1>();
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn min_fill_highest() {
+    random_edits::<4, 50>();
+    random_edits::<64, 50>();
+}
+
+/// Returns the leaf occupancy of a rope with the given minimum fill after
+/// deleting most of its text in small pieces.
+fn occupancy_after_deletions<const MIN_FILL: usize>() -> f64 {
+    let mut r = RopeOf::<64, MIN_FILL>::from(LARGE);
+
+    let mut offset = 0;
+
+    while offset + 10 < r.byte_len() {
+        let mut end = offset + 7;
+        while !r.is_char_boundary(end) {
+            end += 1;
+        }
+        r.delete(offset..end);
+
+        offset += 3;
+        while !r.is_char_boundary(offset) {
+            offset += 1;
+        }
+    }
+
+    r.assert_invariants();
+    r.stats().leaf_occupancy()
+}
+
+/// A higher minimum fill keeps the chunks fuller.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn min_fill_occupancy() {
+    // Every chunk holds at least half of its capacity, minus the few bytes
+    // it can be short of to not split a code point or a grapheme cluster.
+    assert!(occupancy_after_deletions::<50>() >= 24.0 / 64.0);
+
+    assert!(
+        occupancy_after_deletions::<50>() > occupancy_after_deletions::<1>()
+    );
+}
@@ -0,0 +1,124 @@
+mod common;
+
+#[cfg(feature = "unicode-collation")]
+mod tests {
+    use core::cmp::Ordering;
+
+    use crop::Rope;
+    use icu_collator::{Collator, CollatorOptions};
+
+    use crate::common::{CURSED_LIPSUM, MEDIUM, SMALL};
+
+    fn collator(locale: &str) -> Collator {
+        Collator::try_new(&locale.parse().unwrap(), CollatorOptions::new())
+            .unwrap()
+    }
+
+    #[test]
+    fn collate_locales() {
+        let (en, sv) = (collator("en"), collator("sv"));
+
+        let apple = Rope::from("äpple");
+        let zebra = Rope::from("zebra");
+
+        assert_eq!(apple.collate(&zebra, &en), Ordering::Less);
+        assert_eq!(apple.collate(&zebra, &sv), Ordering::Greater);
+        assert_eq!(apple.collate(&apple, &sv), Ordering::Equal);
+    }
+
+    #[test]
+    fn collate_sort() {
+        let en = collator("en");
+
+        let words = ["banana", "Apple", "cherry", "apple", "Éclair", "eclair"];
+
+        let mut ropes = words.map(Rope::from);
+        ropes.sort_by(|a, b| a.collate(b, &en));
+
+        let mut strings = words;
+        strings.sort_by(|a, b| en.compare(a, b));
+
+        assert_eq!(ropes, strings);
+    }
+
+    #[test]
+    fn collate_empty() {
+        let en = collator("en");
+
+        let empty = Rope::new();
+
+        assert_eq!(empty.collate(&empty, &en), Ordering::Equal);
+        assert_eq!(empty.collate(&Rope::from("a"), &en), Ordering::Less);
+        assert_eq!(Rope::from("a").collate(&empty, &en), Ordering::Greater);
+    }
+
+    /// Tests that collating `Rope`s and `RopeSlice`s made of many chunks
+    /// gives the same results as collating the corresponding `String`s.
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn collate_multiple_chunks() {
+        let en = collator("en");
+
+        let s = format!("{MEDIUM}{CURSED_LIPSUM}");
+
+        let mut changed = s.clone();
+        changed.push('!');
+
+        let r = Rope::from(s.as_str());
+        let r_changed = Rope::from(changed.as_str());
+
+        assert!(r.chunks().count() > 1);
+
+        assert_eq!(r.collate(&r.clone(), &en), Ordering::Equal);
+        assert_eq!(r.collate(&r_changed, &en), en.compare(&s, &changed));
+        assert_eq!(r_changed.collate(&r, &en), en.compare(&changed, &s));
+
+        let (a, b) = (r.byte_slice(..s.len() / 2), r.byte_slice(1..));
+        assert_eq!(a.collate(&b, &en), en.compare(&s[..s.len() / 2], &s[1..]));
+    }
+
+    /// Tests that collating texts which share a prefix spanning many chunks
+    /// gives the same results as collating the corresponding `String`s, also
+    /// when the first difference is in a contraction or is only a secondary
+    /// one followed by a primary one.
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn collate_shared_prefix() {
+        let (en, cs) = (collator("en"), collator("cs"));
+
+        let prefix = SMALL.repeat(5);
+        assert!(Rope::from(&*prefix).chunks().count() > 1);
+
+        let suffixes = [
+            "",
+            " ",
+            "a",
+            "chata",
+            "cxata",
+            "hata",
+            " résumé a",
+            " resume b",
+            " resume a\u{301}",
+            "\u{301}",
+        ];
+
+        for lhs in suffixes {
+            for rhs in suffixes {
+                let lhs = format!("{prefix}{lhs}");
+                let rhs = format!("{prefix}{rhs}");
+
+                let (r_lhs, r_rhs) = (Rope::from(&*lhs), Rope::from(&*rhs));
+
+                for collator in [&en, &cs] {
+                    assert_eq!(
+                        r_lhs.collate(&r_rhs, collator),
+                        collator.compare(&lhs, &rhs),
+                        "{:?} vs {:?}",
+                        &lhs[prefix.len()..],
+                        &rhs[prefix.len()..],
+                    );
+                }
+            }
+        }
+    }
+}
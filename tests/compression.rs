@@ -0,0 +1,91 @@
+mod common;
+
+#[cfg(feature = "compression")]
+mod tests {
+    use crop::Rope;
+    use rand::Rng;
+
+    use crate::common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL};
+
+    #[test]
+    fn compress_lowers_mem_usage() {
+        let mut r = Rope::from(LARGE);
+
+        let before = r.stats().mem_usage();
+        r.compress();
+        let after = r.stats().mem_usage();
+
+        assert!(after < before);
+        assert_eq!(r, LARGE);
+
+        // Reading the text decompresses the chunks, compressing again drops
+        // the decompressed copies.
+        assert_eq!(r.to_string(), LARGE);
+        assert!(r.stats().mem_usage() >= before);
+
+        r.compress();
+        assert_eq!(r.stats().mem_usage(), after);
+    }
+
+    // With 4-byte chunks the compressed text is never shorter than the
+    // chunk, so compressing can't lower the memory usage.
+    #[cfg_attr(feature = "small_chunks", ignore)]
+    #[test]
+    fn compress_shared() {
+        let mut r = Rope::from(MEDIUM);
+
+        let clone = r.clone();
+
+        let before = r.stats().mem_usage();
+        r.compress();
+
+        // The whole tree is shared with the clone, so nothing is compressed.
+        assert_eq!(r.stats().mem_usage(), before);
+
+        drop(clone);
+        r.compress();
+        assert!(r.stats().mem_usage() < before);
+        assert_eq!(r, MEDIUM);
+    }
+
+    /// Tests that editing compressed `Rope`s gives the same results as
+    /// editing `String`s.
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn random_edits_compressed() {
+        let mut rng = rand::thread_rng();
+
+        for text in [SMALL, MEDIUM, CURSED_LIPSUM] {
+            let mut r = Rope::from(text);
+            let mut s = String::from(text);
+
+            for _ in 0..50 {
+                if rng.gen_bool(0.3) {
+                    r.compress();
+                }
+
+                let mut start = rng.gen_range(0..=s.len());
+                while !s.is_char_boundary(start) {
+                    start -= 1;
+                }
+
+                let mut end = rng.gen_range(start..=s.len().min(start + 30));
+                while !s.is_char_boundary(end) {
+                    end -= 1;
+                }
+
+                let inserted =
+                    ["", "a", "\n", "é\nè", "🐸🐸"][rng.gen_range(0..5)];
+
+                r.replace(start..end, inserted);
+                s.replace_range(start..end, inserted);
+
+                assert_eq!(r, s);
+            }
+
+            r.compress();
+            r.assert_invariants();
+            assert_eq!(r, s);
+        }
+    }
+}
@@ -0,0 +1,87 @@
+use crop::Rope;
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE};
+
+#[test]
+fn count_matches_empty_pattern() {
+    for s in ["", "a", "🦀b", CURSED_LIPSUM] {
+        assert_eq!(Rope::from(s).count_matches(""), s.matches("").count());
+    }
+}
+
+#[test]
+fn count_matches_large() {
+    let r = Rope::from(LARGE);
+
+    for pattern in ["a", "the", "Lorem ipsum", "\n", "foo"] {
+        assert_eq!(r.count_matches(pattern), LARGE.matches(pattern).count());
+    }
+}
+
+#[test]
+fn count_matches_char() {
+    let r = Rope::from(CURSED_LIPSUM);
+
+    for ch in ['\n', 'a', '🦀', 'ø'] {
+        assert_eq!(r.count_matches(ch), CURSED_LIPSUM.matches(ch).count());
+        assert_eq!(
+            r.byte_slice(..).count_matches(ch),
+            CURSED_LIPSUM.matches(ch).count()
+        );
+        assert_eq!(r.find_all(ch).len(), r.count_matches(ch));
+    }
+}
+
+/// Tests that counting the occurrences of a pattern in a `Rope` whose chunks
+/// and gap buffers are split at random points gives the same result as
+/// counting them in a `String`.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn count_matches_random() {
+    let mut rng = rand::thread_rng();
+
+    let alphabet = ["a", "b", "🦀"];
+
+    for _ in 0..20 {
+        let mut s = String::new();
+        let mut r = Rope::new();
+
+        for _ in 0..2000 {
+            let mut offset = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(offset) {
+                offset -= 1;
+            }
+
+            let text = (0..rng.gen_range(1..10))
+                .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+                .collect::<String>();
+
+            s.insert_str(offset, &text);
+            r.insert(offset, &text);
+        }
+
+        for _ in 0..20 {
+            let pattern = (0..rng.gen_range(1..8))
+                .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+                .collect::<String>();
+
+            assert_eq!(
+                r.count_matches(&pattern),
+                s.matches(&*pattern).count()
+            );
+
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            assert_eq!(
+                r.byte_slice(start..).count_matches(&pattern),
+                s[start..].matches(&*pattern).count()
+            );
+        }
+    }
+}
@@ -0,0 +1,181 @@
+mod common;
+
+#[cfg(feature = "custom-metric")]
+mod tests {
+    use crop::tree::AtomicCounter;
+    use crop::{CharWeight, Rope, RopeWith};
+    use rand::Rng;
+
+    use crate::common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TEXT_EMOJI};
+
+    /// Counts the bytes of the non-ASCII `char`s, so that a `char` can weigh
+    /// zero, one or more units.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct NonAsciiBytes;
+
+    impl CharWeight for NonAsciiBytes {
+        fn weight(ch: char) -> usize {
+            non_ascii_bytes(ch)
+        }
+    }
+
+    /// Gives every `char` a weight of one.
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct One;
+
+    impl CharWeight for One {
+        fn weight(_: char) -> usize {
+            1
+        }
+    }
+
+    type WeightedRope = RopeWith<AtomicCounter, NonAsciiBytes>;
+
+    fn non_ascii_bytes(ch: char) -> usize {
+        if ch.is_ascii() {
+            0
+        } else {
+            ch.len_utf8()
+        }
+    }
+
+    fn custom_len(s: &str) -> usize {
+        s.chars().map(non_ascii_bytes).sum()
+    }
+
+    /// The first byte offset at which the custom units of `s` reach
+    /// `custom_offset`.
+    fn byte_of_custom_unit(s: &str, custom_offset: usize) -> usize {
+        let mut seen = 0;
+
+        for (idx, ch) in s.char_indices() {
+            if seen >= custom_offset {
+                return idx;
+            }
+            seen += non_ascii_bytes(ch);
+        }
+
+        s.len()
+    }
+
+    /// Tests that ropes with different weights can be used side by side.
+    #[test]
+    fn weights_are_per_type() {
+        let s = "aèb🐸";
+
+        assert_eq!(Rope::from(s).custom_len(), 0);
+        assert_eq!(WeightedRope::from(s).custom_len(), 6);
+        assert_eq!(RopeWith::<AtomicCounter, One>::from(s).custom_len(), 4);
+    }
+
+    #[test]
+    fn custom_len_0() {
+        let r = WeightedRope::new();
+        assert_eq!(r.custom_len(), 0);
+
+        let r = WeightedRope::from(TEXT_EMOJI);
+        assert_eq!(r.custom_len(), custom_len(TEXT_EMOJI));
+
+        let s = r.byte_slice(16..39);
+        assert_eq!(s.custom_len(), custom_len(&TEXT_EMOJI[16..39]));
+    }
+
+    #[should_panic]
+    #[test]
+    fn byte_of_custom_unit_out_of_bounds() {
+        let r = WeightedRope::from("aèb");
+        let _ = r.byte_of_custom_unit(3);
+    }
+
+    #[test]
+    fn conversions_random() {
+        let mut rng = rand::thread_rng();
+
+        for s in [CURSED_LIPSUM, SMALL, MEDIUM, LARGE] {
+            let r = WeightedRope::from(s);
+
+            assert_eq!(r.custom_len(), custom_len(s));
+
+            for _ in 0..100 {
+                let mut byte_offset = rng.gen_range(0..=s.len());
+
+                while !s.is_char_boundary(byte_offset) {
+                    byte_offset -= 1;
+                }
+
+                assert_eq!(
+                    r.custom_unit_of_byte(byte_offset),
+                    custom_len(&s[..byte_offset])
+                );
+
+                let custom_offset = rng.gen_range(0..=r.custom_len());
+
+                assert_eq!(
+                    r.byte_of_custom_unit(custom_offset),
+                    byte_of_custom_unit(s, custom_offset)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn custom_slice_random() {
+        let mut rng = rand::thread_rng();
+
+        for s in [CURSED_LIPSUM, SMALL, MEDIUM] {
+            let r = WeightedRope::from(s);
+
+            for _ in 0..100 {
+                let start = rng.gen_range(0..=r.custom_len());
+                let end = rng.gen_range(start..=r.custom_len());
+
+                let slice = r.custom_slice(start..end);
+                slice.assert_invariants();
+
+                let byte_start = byte_of_custom_unit(s, start);
+                let byte_end = byte_of_custom_unit(s, end);
+
+                assert_eq!(slice, s[byte_start..byte_end]);
+                assert_eq!(
+                    slice.custom_len(),
+                    custom_len(slice.to_string().as_str())
+                );
+            }
+        }
+    }
+
+    /// Tests that the custom units stay in sync with the text as the `Rope`
+    /// is edited.
+    #[test]
+    fn custom_len_after_edits() {
+        let mut rng = rand::thread_rng();
+
+        let mut r = WeightedRope::from(SMALL);
+        let mut s = String::from(SMALL);
+
+        for _ in 0..200 {
+            let mut start = rng.gen_range(0..=s.len());
+
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=(start + 20).min(s.len()));
+
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            let text = CURSED_LIPSUM
+                .chars()
+                .take(rng.gen_range(0..8))
+                .collect::<String>();
+
+            r.replace(start..end, &text);
+            s.replace_range(start..end, &text);
+
+            r.assert_invariants();
+            assert_eq!(r.custom_len(), custom_len(&s));
+        }
+    }
+}
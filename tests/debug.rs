@@ -0,0 +1,67 @@
+use crop::Rope;
+
+#[test]
+fn debug_compact() {
+    let r = Rope::from("Hello\n\"World\"\t!");
+    assert_eq!(format!("{r:?}"), r#"Rope("Hello\n\"World\"\t!")"#);
+    assert_eq!(
+        format!("{:?}", r.byte_slice(6..)),
+        r#"RopeSlice("\"World\"\t!")"#
+    );
+}
+
+#[cfg(feature = "lines")]
+#[test]
+fn debug_alternate() {
+    let r = Rope::from("fn main() {\n\tlet s = \"hi\";\r\n\n}\n");
+
+    assert_eq!(
+        format!("{r:#?}"),
+        "Rope {\n    1 | fn main() {\n    2 | \\tlet s = \"hi\";\n    3 |\n    \
+         4 | }\n}"
+    );
+
+    assert_eq!(
+        format!("{:#?}", r.line_slice(3..)),
+        "RopeSlice {\n    1 | }\n}"
+    );
+
+    assert_eq!(format!("{:#?}", Rope::new()), "Rope {\n}");
+}
+
+#[cfg(feature = "lines")]
+#[test]
+fn debug_alternate_truncated() {
+    let r = (0..100).map(|i| format!("line {i}\n")).collect::<Rope>();
+
+    let debug = format!("{r:#?}");
+
+    let lines = debug.lines().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 43);
+    assert_eq!(lines[0], "Rope {");
+    assert_eq!(lines[1], "      1 | line 0");
+    assert_eq!(lines[20], "     20 | line 19");
+    assert_eq!(lines[21], "    ... | (60 more lines)");
+    assert_eq!(lines[22], "     81 | line 80");
+    assert_eq!(lines[41], "    100 | line 99");
+    assert_eq!(lines[42], "}");
+}
+
+#[cfg(feature = "lines")]
+#[test]
+fn debug_alternate_nested() {
+    #[derive(Debug)]
+    #[allow(dead_code)]
+    struct Buffer {
+        text: Rope,
+    }
+
+    let buffer = Buffer { text: Rope::from("foo\nbar") };
+
+    assert_eq!(
+        format!("{buffer:#?}"),
+        "Buffer {\n    text: Rope {\n        1 | foo\n        2 | bar\n    \
+         },\n}"
+    );
+}
@@ -0,0 +1,295 @@
+use crop::{Delta, DeltaOp, Gravity, Rope, Tracked};
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, SMALL};
+
+/// Returns a random code point boundary of `rope` in `range`.
+fn random_boundary(
+    rng: &mut impl Rng,
+    rope: &Rope,
+    range: core::ops::RangeInclusive<usize>,
+) -> usize {
+    let mut offset = rng.gen_range(range);
+    while !rope.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    offset
+}
+
+/// Returns a random `Delta` which can be applied to `rope`.
+fn random_delta(rng: &mut impl Rng, rope: &Rope) -> Delta {
+    let mut delta = Delta::new();
+
+    let mut offset = 0;
+
+    while offset < rope.byte_len() {
+        let end = random_boundary(
+            rng,
+            rope,
+            offset..=(offset + 20).min(rope.byte_len()),
+        );
+
+        match rng.gen_range(0..3) {
+            0 => delta.retain(end - offset),
+            1 => delta.delete(end - offset),
+            _ => {
+                delta.insert(["a", "é", "🐸a"][rng.gen_range(0..3)]);
+                continue;
+            },
+        };
+
+        offset = end;
+    }
+
+    if rng.gen() {
+        delta.insert("🐸");
+    }
+
+    delta
+}
+
+fn applied(delta: &Delta, rope: &Rope) -> Rope {
+    let mut rope = rope.clone();
+    delta.apply(&mut rope);
+    rope
+}
+
+#[test]
+fn canonical_form() {
+    let mut a = Delta::new();
+    a.retain(1).retain(2).insert("a").insert("b").delete(1).delete(2);
+
+    let mut b = Delta::new();
+    b.retain(3).delete(3).insert("ab");
+
+    assert_eq!(a, b);
+    assert_eq!(
+        a.ops(),
+        [DeltaOp::Retain(3), DeltaOp::Delete(3), DeltaOp::Insert("ab".into())]
+    );
+    assert_eq!(a.base_len(), 6);
+    assert_eq!(a.target_len(), 5);
+}
+
+#[test]
+fn map_deletion() {
+    let mut delta = Delta::new();
+    delta.retain(2).delete(3).insert("xy").retain(2);
+
+    assert_eq!(delta.map(1, Gravity::Right), 1);
+    assert_eq!(delta.map(2, Gravity::Left), 2);
+    assert_eq!(delta.map(2, Gravity::Right), 4);
+    assert_eq!(delta.map(4, Gravity::Left), 2);
+    assert_eq!(delta.map(5, Gravity::Right), 4);
+    assert_eq!(delta.map(6, Gravity::Left), 5);
+    assert_eq!(delta.map(7, Gravity::Left), 6);
+}
+
+#[test]
+#[should_panic]
+fn apply_wrong_length() {
+    let mut delta = Delta::new();
+    delta.retain(3);
+    delta.apply(&mut Rope::from("ab"));
+}
+
+#[test]
+#[should_panic]
+fn compose_wrong_length() {
+    let mut a = Delta::new();
+    a.retain(3);
+    let mut b = Delta::new();
+    b.retain(4);
+    let _ = a.compose(&b);
+}
+
+#[test]
+fn recording_nothing() {
+    let mut t = Tracked::new(Rope::from(SMALL));
+
+    assert_eq!(t.stop_recording(), None);
+
+    t.start_recording();
+
+    let delta = t.stop_recording().unwrap();
+    assert!(delta.is_noop());
+    assert_eq!(delta.base_len(), SMALL.len());
+}
+
+#[test]
+fn recording_typing() {
+    let mut t = Tracked::new(Rope::from("Hello!"));
+
+    t.start_recording();
+
+    t.insert(5, " E");
+    t.insert(7, "arht");
+    t.delete(9..11);
+    t.insert(9, "th");
+    t.replace(0..5, "Hi");
+
+    assert_eq!(t.rope(), "Hi Earth!");
+
+    let delta = t.stop_recording().unwrap();
+
+    assert_eq!(
+        delta.ops(),
+        [
+            DeltaOp::Delete(5),
+            DeltaOp::Insert("Hi Earth".into()),
+            DeltaOp::Retain(1),
+        ]
+    );
+}
+
+/// Tests that composing two random deltas is the same as applying them one
+/// after the other, and that inverting a delta undoes it.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_compose_invert() {
+    let mut rng = rand::thread_rng();
+
+    let base = Rope::from(SMALL);
+
+    for _ in 0..100 {
+        let first = random_delta(&mut rng, &base);
+        let middle = applied(&first, &base);
+
+        let second = random_delta(&mut rng, &middle);
+        let last = applied(&second, &middle);
+
+        let composed = first.compose(&second);
+        assert_eq!(applied(&composed, &base), last);
+
+        assert_eq!(applied(&first.invert(&base), &middle), base);
+
+        let undo = composed.invert(&base);
+        assert_eq!(applied(&undo, &last), base);
+    }
+}
+
+/// Tests that mapping an offset through a delta gives the same result as
+/// an anchor of the `Tracked` rope the delta is applied to.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_map_like_anchors() {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..100 {
+        let mut t = Tracked::new(Rope::from(CURSED_LIPSUM));
+
+        let delta = random_delta(&mut rng, t.rope());
+
+        let offsets = (0..=t.rope().byte_len())
+            .filter(|&offset| t.rope().is_char_boundary(offset))
+            .collect::<Vec<_>>();
+
+        let anchors = offsets
+            .into_iter()
+            .flat_map(|offset| {
+                [(offset, Gravity::Left), (offset, Gravity::Right)]
+            })
+            .map(|(offset, gravity)| {
+                (offset, gravity, t.anchor(offset, gravity))
+            })
+            .collect::<Vec<_>>();
+
+        t.apply(&delta);
+
+        for (offset, gravity, anchor) in anchors {
+            assert_eq!(
+                t.anchor_offset(anchor),
+                Some(delta.map(offset, gravity))
+            );
+        }
+    }
+}
+
+/// Tests that the delta recorded by a rope reproduces the edits made to
+/// it.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_recording() {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..20 {
+        let base = Rope::from(SMALL);
+
+        let mut t = Tracked::new(base.clone());
+
+        t.start_recording();
+
+        for _ in 0..50 {
+            let r = t.rope();
+
+            let start = random_boundary(&mut rng, r, 0..=r.byte_len());
+            let end = random_boundary(
+                &mut rng,
+                r,
+                start..=(start + 20).min(r.byte_len()),
+            );
+
+            match rng.gen_range(0..5) {
+                0 => t.delete(start..end),
+                1 => t.insert(start, "🐸"),
+                2 => t.edit(|rope| rope.append(Rope::from("abc"))),
+                3 => t.edit(|rope| rope.replace(start..end, "ab")),
+                _ => t.replace(start..end, "Ḽơ"),
+            }
+        }
+
+        let delta = t.stop_recording().unwrap();
+
+        assert_eq!(applied(&delta, &base), *t.rope());
+        assert_eq!(applied(&delta.invert(&base), t.rope()), base);
+    }
+}
+
+/// Tests that two random deltas made against the same rope converge once
+/// each one is transformed against the other.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_transform() {
+    let mut rng = rand::thread_rng();
+
+    let base = Rope::from(CURSED_LIPSUM);
+
+    for _ in 0..100 {
+        let a = random_delta(&mut rng, &base);
+        let b = random_delta(&mut rng, &base);
+
+        let (a_after_b, b_after_a) = a.transform(&b);
+
+        assert_eq!(a_after_b.base_len(), b.target_len());
+        assert_eq!(b_after_a.base_len(), a.target_len());
+
+        let first = applied(&b_after_a, &applied(&a, &base));
+        let second = applied(&a_after_b, &applied(&b, &base));
+
+        assert_eq!(first, second);
+        assert_eq!(a.compose(&b_after_a), b.compose(&a_after_b));
+    }
+}
+
+#[test]
+fn transform_same_offset() {
+    let mut a = Delta::new();
+    a.retain(1).insert("a").retain(1);
+
+    let mut b = Delta::new();
+    b.retain(1).insert("b").retain(1);
+
+    let (a_after_b, b_after_a) = a.transform(&b);
+
+    let mut r = Rope::from("xy");
+    a.apply(&mut r);
+    b_after_a.apply(&mut r);
+    assert_eq!(r, "xaby");
+
+    let mut r = Rope::from("xy");
+    b.apply(&mut r);
+    a_after_b.apply(&mut r);
+    assert_eq!(r, "xaby");
+}
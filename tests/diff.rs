@@ -0,0 +1,323 @@
+use crop::{DeltaOp, Rope};
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+fn assert_diff(old: &Rope, new: &Rope) {
+    let delta = old.diff(new);
+
+    assert_eq!(delta.base_len(), old.byte_len());
+    assert_eq!(delta.target_len(), new.byte_len());
+
+    let mut r = old.clone();
+    delta.apply(&mut r);
+    assert_eq!(&r, new);
+}
+
+/// Checks that replacing the changed ranges of `old` with the text in the
+/// corresponding ranges of `new` turns `old` into `new`.
+fn assert_changed_ranges(old: &Rope, new: &Rope) {
+    let ranges = old.changed_ranges(new);
+
+    for window in ranges.windows(2) {
+        assert!(window[0].0.end <= window[1].0.start);
+        assert!(window[0].1.end <= window[1].1.start);
+    }
+
+    let mut r = old.clone();
+
+    for (old_range, new_range) in ranges.into_iter().rev() {
+        assert!(!old_range.is_empty() || !new_range.is_empty());
+        r.replace(old_range, new.byte_slice(new_range).to_string());
+    }
+
+    assert_eq!(&r, new);
+}
+
+#[test]
+fn changed_ranges_equal() {
+    let r = Rope::from(LARGE);
+
+    assert!(r.changed_ranges(&r.clone()).is_empty());
+    assert!(r.changed_ranges(&Rope::from(LARGE)).is_empty());
+    assert!(Rope::new().changed_ranges(&Rope::new()).is_empty());
+}
+
+#[test]
+fn changed_ranges_single_edit() {
+    let old = Rope::from(LARGE);
+
+    let mut new = old.clone();
+    new.replace(1000..1010, "Hello there");
+    assert_eq!(old.changed_ranges(&new), [(1000..1010, 1000..1011)]);
+
+    let mut new = old.clone();
+    new.insert(LARGE.len(), "Hello there");
+    assert_eq!(
+        old.changed_ranges(&new),
+        [(LARGE.len()..LARGE.len(), LARGE.len()..LARGE.len() + 11)]
+    );
+}
+
+#[test]
+fn changed_ranges_unrelated() {
+    assert_changed_ranges(&Rope::from(SMALL), &Rope::from(MEDIUM));
+    assert_changed_ranges(&Rope::from(LARGE), &Rope::new());
+    assert_changed_ranges(&Rope::new(), &Rope::from(TINY));
+    assert_changed_ranges(&Rope::from("aé"), &Rope::from("aè"));
+}
+
+/// Tests that the changed ranges between a rope and an edited clone of
+/// itself reproduce the edits.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_changed_ranges() {
+    let mut rng = rand::thread_rng();
+
+    for text in [SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let old = Rope::from(text);
+
+        for _ in 0..20 {
+            let mut new = old.clone();
+
+            for _ in 0..rng.gen_range(1..20) {
+                let mut start = rng.gen_range(0..=new.byte_len());
+                while !new.is_char_boundary(start) {
+                    start -= 1;
+                }
+
+                let mut end =
+                    rng.gen_range(start..=new.byte_len().min(start + 3000));
+                while !new.is_char_boundary(end) {
+                    end -= 1;
+                }
+
+                let inserted =
+                    ["", "a", "\n", "é\nè", "🐸🐸"][rng.gen_range(0..5)];
+
+                new.replace(start..end, inserted);
+            }
+
+            assert_changed_ranges(&old, &new);
+            assert_changed_ranges(&new, &old);
+        }
+    }
+}
+
+#[test]
+fn ptr_eq() {
+    let r = Rope::from(LARGE);
+
+    let mut other = r.clone();
+    assert!(r.ptr_eq(&other));
+    assert!(other.ptr_eq(&r));
+
+    other.insert(0, "a");
+    assert!(!r.ptr_eq(&other));
+
+    other.delete(0..1);
+    assert!(!r.ptr_eq(&other));
+    assert_eq!(r, other);
+}
+
+/// Ropes stored in a single chunk share it with their clones until one of
+/// them is edited.
+#[test]
+fn ptr_eq_single_chunk() {
+    let r = Rope::from("abc");
+
+    let mut other = r.clone();
+    assert!(r.ptr_eq(&other));
+    assert!(r.changed_ranges(&other).is_empty());
+    assert_eq!(r.shared_fraction(&other), 1.0);
+
+    other.insert(0, "a");
+    assert!(!r.ptr_eq(&other));
+    assert_changed_ranges(&r, &other);
+
+    other.delete(0..1);
+    assert!(!r.ptr_eq(&other));
+    assert_eq!(r, other);
+    assert!(!r.ptr_eq(&Rope::from("abc")));
+}
+
+#[test]
+fn shared_fraction_unrelated() {
+    let r = Rope::from(LARGE);
+
+    assert_eq!(r.shared_fraction(&r.clone()), 1.0);
+    assert_eq!(r.shared_fraction(&Rope::from(LARGE)), 0.0);
+}
+
+/// Two empty ropes have nothing that isn't shared, even when they're built
+/// separately.
+#[test]
+fn shared_fraction_empty() {
+    assert_eq!(Rope::new().shared_fraction(&Rope::new()), 1.0);
+    assert_eq!(Rope::from("").shared_fraction(&Rope::new()), 1.0);
+
+    let mut r = Rope::from("abc");
+    r.delete(..);
+    assert_eq!(r.shared_fraction(&Rope::new()), 1.0);
+
+    assert_eq!(Rope::new().shared_fraction(&Rope::from("abc")), 0.0);
+    assert_eq!(Rope::from("abc").shared_fraction(&Rope::new()), 0.0);
+}
+
+/// Tests that the fraction of shared chunks between a rope and an edited
+/// clone of itself matches the fraction of chunks stored at the same
+/// address.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_shared_fraction() {
+    let mut rng = rand::thread_rng();
+
+    let chunk_ptrs = |r: &Rope| {
+        r.chunk_segments()
+            .map(|(left, right)| (left.as_ptr(), right.as_ptr()))
+            .collect::<std::collections::HashSet<_>>()
+    };
+
+    let old = Rope::from(LARGE);
+
+    let old_ptrs = chunk_ptrs(&old);
+
+    for _ in 0..20 {
+        let mut new = old.clone();
+
+        for _ in 0..rng.gen_range(1..20) {
+            let start = rng.gen_range(0..=new.byte_len());
+            let end = rng.gen_range(start..=new.byte_len().min(start + 3000));
+            new.replace(start..end, "Hello there");
+        }
+
+        let shared = chunk_ptrs(&new).intersection(&old_ptrs).count();
+
+        assert_eq!(
+            old.shared_fraction(&new),
+            shared as f64 / old.stats().leaf_count() as f64
+        );
+    }
+}
+
+#[test]
+fn diff_equal() {
+    let r = Rope::from(LARGE);
+
+    assert!(r.diff(&r.clone()).is_noop());
+    assert!(r.diff(&Rope::from(LARGE)).is_noop());
+    assert!(Rope::new().diff(&Rope::new()).is_noop());
+}
+
+#[test]
+fn diff_single_edit() {
+    let old = Rope::from(MEDIUM);
+
+    let mut new = old.clone();
+    new.replace(1000..1010, "Hello there");
+
+    let delta = old.diff(&new);
+
+    let edited = delta
+        .ops()
+        .iter()
+        .filter(|op| !matches!(op, DeltaOp::Retain(_)))
+        .map(|op| match op {
+            DeltaOp::Delete(n) => *n,
+            DeltaOp::Insert(text) => text.len(),
+            DeltaOp::Retain(_) => unreachable!(),
+        })
+        .sum::<usize>();
+
+    assert!(edited <= 10 + "Hello there".len());
+
+    assert_diff(&old, &new);
+}
+
+#[test]
+fn diff_unrelated() {
+    let texts = ["", TINY, SMALL, CURSED_LIPSUM, "🐸\n🐸", "a\nb\nc\n"];
+
+    for old in texts {
+        for new in texts {
+            assert_diff(&Rope::from(old), &Rope::from(new));
+        }
+    }
+}
+
+/// Tests that diffing long unrelated texts gives up on finding a minimal
+/// delta instead of taking time quadratic in their length.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn diff_unrelated_large() {
+    let mut rng = rand::thread_rng();
+
+    let mut random_text = |len: usize, line_len: usize| {
+        (0..len)
+            .map(|idx| {
+                if idx % line_len == line_len - 1 {
+                    '\n'
+                } else {
+                    rng.gen_range('a'..='z')
+                }
+            })
+            .collect::<String>()
+    };
+
+    for line_len in [usize::MAX, 80] {
+        let old = Rope::from(random_text(200_000, line_len));
+        let new = Rope::from(random_text(200_000, line_len));
+
+        assert_diff(&old, &new);
+
+        // The texts share no lines, so they're replaced as a whole.
+        let ops = old.diff(&new).ops().len();
+        assert!(ops <= 4, "{ops} ops");
+    }
+}
+
+#[test]
+fn diff_multibyte_common_prefix() {
+    // "é" and "è" only differ in their last byte.
+    assert_diff(&Rope::from("aé"), &Rope::from("aè"));
+    assert_diff(&Rope::from("éa"), &Rope::from("èa"));
+}
+
+/// Tests that diffing a rope against an edited clone of itself gives a
+/// delta reproducing the edits.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_diff() {
+    let mut rng = rand::thread_rng();
+
+    for text in [SMALL, MEDIUM, CURSED_LIPSUM] {
+        let old = Rope::from(text);
+
+        for _ in 0..20 {
+            let mut new = old.clone();
+
+            for _ in 0..rng.gen_range(1..20) {
+                let mut start = rng.gen_range(0..=new.byte_len());
+                while !new.is_char_boundary(start) {
+                    start -= 1;
+                }
+
+                let mut end =
+                    rng.gen_range(start..=new.byte_len().min(start + 30));
+                while !new.is_char_boundary(end) {
+                    end -= 1;
+                }
+
+                let inserted =
+                    ["", "a", "\n", "é\nè", "🐸🐸"][rng.gen_range(0..5)];
+
+                new.replace(start..end, inserted);
+            }
+
+            assert_diff(&old, &new);
+            assert_diff(&new, &old);
+        }
+    }
+}
@@ -0,0 +1,135 @@
+use crop::{Rope, Tracked};
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, MEDIUM};
+
+#[test]
+fn dirty_not_tracking() {
+    let mut r = Tracked::new(Rope::from("Hello Earth!"));
+    r.replace(6..11, "Mars");
+    assert_eq!(r.dirty_byte_range(), None);
+
+    r.mark_clean();
+    assert_eq!(r.dirty_byte_range(), None);
+}
+
+#[test]
+fn dirty_byte_range_union() {
+    let mut r = Tracked::new(Rope::from("Hello Earth!"));
+    r.mark_clean();
+
+    r.insert(12, " 🌎");
+    assert_eq!(r.dirty_byte_range(), Some(12..17));
+
+    // Inserting before the range shifts it.
+    r.insert(0, "Oh, ");
+    assert_eq!(r.dirty_byte_range(), Some(0..21));
+
+    r.mark_clean();
+
+    // Deleting the text within the range shrinks it.
+    r.insert(4, "well, ");
+    r.delete(5..10);
+    assert_eq!(*r.rope(), "Oh, wHello Earth! 🌎");
+    assert_eq!(r.dirty_byte_range(), Some(4..5));
+
+    // Deleting text overlapping the range extends it.
+    r.delete(3..7);
+    assert_eq!(*r.rope(), "Oh,llo Earth! 🌎");
+    assert_eq!(r.dirty_byte_range(), Some(3..3));
+}
+
+#[test]
+fn dirty_clones() {
+    let mut r = Tracked::new(Rope::from("Hello Earth!"));
+    r.mark_clean();
+
+    let mut clone = r.clone();
+    clone.insert(0, "Oh, ");
+
+    assert_eq!(r.dirty_byte_range(), None);
+    assert_eq!(clone.dirty_byte_range(), Some(0..4));
+}
+
+#[cfg(feature = "lines")]
+#[test]
+fn dirty_lines() {
+    let mut r = Tracked::new(Rope::from("foo\nbar\nbaz\n"));
+    r.mark_clean();
+
+    r.insert(12, "qux");
+    assert_eq!(r.dirty_lines(), Some(3..4));
+
+    r.mark_clean();
+    r.insert(4, "\n");
+    assert_eq!(r.dirty_lines(), Some(1..2));
+
+    r.mark_clean();
+    r.delete(12..);
+    assert_eq!(*r.rope(), "foo\n\nbar\nbaz");
+    assert_eq!(r.dirty_lines(), Some(3..4));
+
+    r.mark_clean();
+    r.delete(8..);
+    assert_eq!(*r.rope(), "foo\n\nbar");
+    assert_eq!(r.dirty_lines(), Some(2..3));
+
+    // Removing everything after the last line break leaves an empty range
+    // past the last line.
+    r.insert(8, "\n");
+    r.mark_clean();
+    r.insert(9, "baz");
+    r.delete(9..);
+    assert_eq!(r.rope().line_len(), 3);
+    assert_eq!(r.dirty_lines(), Some(3..3));
+}
+
+/// Tests that the text outside of the dirty range is the same as the text
+/// at the start and at the end of the `Tracked` rope when it was marked
+/// clean.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn dirty_byte_range_random() {
+    let mut rng = rand::thread_rng();
+
+    let mut r = Tracked::new(Rope::from(MEDIUM));
+    let mut s = String::from(MEDIUM);
+
+    for _ in 0..50 {
+        r.mark_clean();
+        let clean = s.clone();
+
+        for _ in 0..rng.gen_range(1..20) {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=s.len().min(start + 100));
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            let mut text_end = rng.gen_range(0..=100);
+            while !CURSED_LIPSUM.is_char_boundary(text_end) {
+                text_end -= 1;
+            }
+
+            let text = &CURSED_LIPSUM[..text_end];
+
+            r.replace(start..end, text);
+            s.replace_range(start..end, text);
+        }
+
+        assert_eq!(*r.rope(), s);
+
+        let dirty = r.dirty_byte_range().unwrap();
+        let suffix_len = s.len() - dirty.end;
+
+        assert!(dirty.start <= clean.len() && suffix_len <= clean.len());
+        assert_eq!(s[..dirty.start], clean[..dirty.start]);
+        assert_eq!(s[dirty.end..], clean[clean.len() - suffix_len..]);
+    }
+}
@@ -0,0 +1,56 @@
+use crop::Rope;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, TINY};
+
+#[test]
+fn display_width_precision_alignment() {
+    let s = "Löwe 老虎 Léopard";
+    let r = Rope::from(s);
+
+    assert_eq!(format!("{r:>20.10}"), format!("{s:>20.10}"));
+    assert_eq!(format!("{r:*^20}"), format!("{s:*^20}"));
+    assert_eq!(format!("{r:<5}"), s);
+    assert_eq!(format!("{:.4}", r.byte_slice(6..)), "老虎 L");
+}
+
+/// Tests that formatting a `Rope` or a `RopeSlice` gives the same result as
+/// formatting a `&str` with the same text, whatever the chunks it's split
+/// into.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn display_matches_str() {
+    for s in ["", "a", TINY, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+        let chars = s.chars().count();
+
+        for width in [0, 1, chars / 2, chars, chars + 7] {
+            for precision in [0, 1, chars / 3, chars, chars + 1] {
+                assert_eq!(
+                    format!("{r:w$.p$}", w = width, p = precision),
+                    format!("{s:w$.p$}", w = width, p = precision)
+                );
+
+                assert_eq!(
+                    format!("{r:->w$.p$}", w = width, p = precision),
+                    format!("{s:->w$.p$}", w = width, p = precision)
+                );
+
+                assert_eq!(
+                    format!(
+                        "{:^w$.p$}",
+                        r.byte_slice(..),
+                        w = width,
+                        p = precision
+                    ),
+                    format!("{s:^w$.p$}", w = width, p = precision)
+                );
+            }
+        }
+    }
+
+    let r = Rope::from(LARGE);
+    assert_eq!(format!("{r}"), LARGE);
+    assert_eq!(format!("{r:.1000}"), format!("{LARGE:.1000}"));
+}
@@ -0,0 +1,145 @@
+use crop::{Error, Rope};
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+/// Tests that the `try_*` slicing methods succeed exactly when the byte range
+/// is valid for the corresponding `&str`.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn try_byte_slice_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..100 {
+            let start = rng.gen_range(0..=s.len() + 1);
+            let end = rng.gen_range(0..=s.len() + 1);
+
+            match r.try_byte_slice(start..end) {
+                Ok(slice) => {
+                    assert_eq!(slice, s.get(start..end).unwrap());
+                    assert_eq!(slice, r.byte_slice(..).byte_slice(start..end));
+                },
+
+                Err(Error::ByteStartAfterEnd { .. }) => assert!(start > end),
+
+                Err(Error::ByteOffsetOutOfBounds { offset, len }) => {
+                    assert_eq!(offset, end);
+                    assert_eq!(len, s.len());
+                    assert!(end > s.len());
+                },
+
+                Err(Error::ByteOffsetNotCharBoundary { offset }) => {
+                    assert!(!s.is_char_boundary(offset))
+                },
+
+                Err(err) => panic!("unexpected error: {err}"),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "lines")]
+#[allow(clippy::reversed_empty_ranges)]
+#[test]
+fn try_line_slice() {
+    let r = Rope::from("foo\nbar\r\nbaz\n");
+
+    assert_eq!(r.try_line_slice(..).unwrap(), r);
+    assert_eq!(r.try_line_slice(3..3).unwrap(), "");
+
+    assert_eq!(
+        r.try_line_slice(..4),
+        Err(Error::LineOffsetOutOfBounds { offset: 4, len: 3 })
+    );
+
+    assert_eq!(
+        r.byte_slice(4..).try_line_slice(1..0),
+        Err(Error::LineStartAfterEnd { start: 1, end: 0 })
+    );
+
+    assert_eq!(
+        r.line_slice(1..).try_line(2),
+        Err(Error::LineIndexOutOfBounds { index: 2, len: 2 })
+    );
+}
+
+/// Tests that inclusive ranges ending at `usize::MAX` are reported as out of
+/// bounds instead of overflowing.
+#[test]
+fn try_inclusive_range_to_usize_max() {
+    use core::ops::Bound;
+
+    let mut r = Rope::from("foo\nbar\n");
+
+    assert_eq!(
+        r.try_byte_slice(..=usize::MAX),
+        Err(Error::ByteOffsetOutOfBounds { offset: usize::MAX, len: 8 })
+    );
+
+    assert_eq!(
+        r.byte_slice(..).try_byte_slice(2..=usize::MAX),
+        Err(Error::ByteOffsetOutOfBounds { offset: usize::MAX, len: 8 })
+    );
+
+    assert_eq!(
+        r.try_byte_slice((Bound::Excluded(usize::MAX), Bound::Unbounded)),
+        Err(Error::ByteStartAfterEnd { start: usize::MAX, end: 8 })
+    );
+
+    assert_eq!(
+        r.try_replace(4..=usize::MAX, "baz"),
+        Err(Error::ByteOffsetOutOfBounds { offset: usize::MAX, len: 8 })
+    );
+
+    #[cfg(feature = "lines")]
+    assert_eq!(
+        r.try_line_slice(1..=usize::MAX),
+        Err(Error::LineOffsetOutOfBounds { offset: usize::MAX, len: 2 })
+    );
+
+    assert_eq!(r, "foo\nbar\n");
+}
+
+/// Tests that failed edits leave the `Rope` untouched.
+#[test]
+fn try_replace_error_doesnt_modify() {
+    let mut r = Rope::from("Löwe 老虎 Léopard");
+
+    assert_eq!(
+        r.try_insert(2, "a"),
+        Err(Error::ByteOffsetNotCharBoundary { offset: 2 })
+    );
+
+    assert_eq!(
+        r.try_delete(..100),
+        Err(Error::ByteOffsetOutOfBounds { offset: 100, len: 21 })
+    );
+
+    assert_eq!(
+        r.try_replace(6..7, "a"),
+        Err(Error::ByteOffsetNotCharBoundary { offset: 7 })
+    );
+
+    r.assert_invariants();
+    assert_eq!(r, "Löwe 老虎 Léopard");
+
+    assert_eq!(r.try_replace(6..12, "Tiger"), Ok(()));
+
+    r.assert_invariants();
+    assert_eq!(r, "Löwe Tiger Léopard");
+}
+
+#[test]
+fn error_display() {
+    let err = Rope::from("foo").try_byte(3).unwrap_err();
+
+    assert_eq!(
+        err.to_string(),
+        "byte index out of bounds: the index is 3 but the length is 3"
+    );
+}
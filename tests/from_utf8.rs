@@ -0,0 +1,63 @@
+use crop::Rope;
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, TEXT_EMOJI};
+
+#[test]
+fn from_utf8_valid() {
+    for s in [CURSED_LIPSUM, LARGE, TEXT_EMOJI] {
+        let r = Rope::from_utf8(s.as_bytes()).unwrap();
+        r.assert_invariants();
+        assert_eq!(r, s);
+
+        let r = Rope::from_utf8(String::from(s).into_bytes()).unwrap();
+        assert_eq!(r, s);
+
+        assert_eq!(Rope::from_utf8_lossy(s.as_bytes()), s);
+    }
+}
+
+#[test]
+fn from_utf8_invalid() {
+    let err = Rope::from_utf8(b"abc\xC3").unwrap_err();
+    assert_eq!(err.valid_up_to(), 3);
+    assert_eq!(err.error_len(), None);
+
+    let err = Rope::from_utf8(b"abc\xFFdef").unwrap_err();
+    assert_eq!(err.valid_up_to(), 3);
+    assert_eq!(err.error_len(), Some(1));
+}
+
+#[test]
+fn from_utf8_lossy_trailing_incomplete() {
+    assert_eq!(Rope::from_utf8_lossy(b"abc\xF0\x9F\x8C"), "abc\u{FFFD}");
+    assert_eq!(Rope::from_utf8_lossy(b"\xFF\xFF"), "\u{FFFD}\u{FFFD}");
+    assert_eq!(Rope::from_utf8_lossy(b""), "");
+}
+
+/// Tests that `from_utf8_lossy()` replaces the same sequences as
+/// `String::from_utf8_lossy()`.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn from_utf8_lossy_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [CURSED_LIPSUM, LARGE] {
+        let mut bytes = s.as_bytes().to_vec();
+
+        for _ in 0..100 {
+            let idx = rng.gen_range(0..bytes.len());
+            bytes[idx] = rng.gen();
+        }
+
+        let r = Rope::from_utf8_lossy(&bytes);
+        r.assert_invariants();
+        assert_eq!(r, String::from_utf8_lossy(&bytes));
+        assert_eq!(
+            Rope::from_utf8(&bytes).is_ok(),
+            core::str::from_utf8(&bytes).is_ok()
+        );
+    }
+}
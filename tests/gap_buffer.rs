@@ -0,0 +1,113 @@
+use crop::GapBuffer;
+use rand::Rng;
+
+mod common;
+
+use common::CURSED_LIPSUM;
+
+#[test]
+fn gap_buffer_empty() {
+    let buffer = GapBuffer::<16>::new();
+
+    assert!(buffer.is_empty());
+    assert_eq!(buffer.len(), 0);
+    assert_eq!(buffer.capacity(), 16);
+    assert_eq!(buffer.byte_slice(..), "");
+    assert_eq!(buffer.to_string(), "");
+}
+
+#[test]
+fn gap_buffer_typing() {
+    let mut buffer = GapBuffer::<64>::new();
+
+    for (offset, ch) in "Hello Earth!".char_indices() {
+        buffer.insert(offset, ch.encode_utf8(&mut [0; 4]));
+    }
+
+    assert_eq!(buffer, "Hello Earth!");
+
+    // Backspace the exclamation mark and type it in again.
+    buffer.delete(11..12);
+    buffer.insert(11, "🌎!");
+
+    assert_eq!(buffer, "Hello Earth🌎!");
+    assert_eq!(buffer.char_len(), 13);
+    assert_eq!(buffer.to_string(), "Hello Earth🌎!");
+}
+
+#[test]
+fn gap_buffer_eq() {
+    let mut a = GapBuffer::<16>::from("Hello Earth!");
+    let mut b = GapBuffer::<16>::from("Hello Earth!");
+
+    // Same text, gap in a different place.
+    a.insert(2, "");
+    b.insert(9, "");
+    assert_eq!(a, b);
+
+    b.replace(6..11, "Mars");
+    assert_ne!(a, b);
+}
+
+#[test]
+#[should_panic]
+fn gap_buffer_overflow() {
+    let mut buffer = GapBuffer::<8>::from("Hello");
+    buffer.insert(5, " Earth");
+}
+
+#[test]
+#[should_panic]
+fn gap_buffer_not_char_boundary() {
+    let mut buffer = GapBuffer::<8>::from("🌎");
+    buffer.delete(1..4);
+}
+
+/// Tests that editing and slicing `GapBuffer`s gives the same results as
+/// editing and slicing `String`s.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_gap_buffer_edits() {
+    let mut rng = rand::thread_rng();
+
+    let mut buffer = GapBuffer::<256>::new();
+    let mut s = String::new();
+
+    let random_range = |rng: &mut rand::rngs::ThreadRng, s: &str| {
+        let mut start = rng.gen_range(0..=s.len());
+        while !s.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        let mut end = rng.gen_range(start..=s.len());
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        start..end
+    };
+
+    for _ in 0..1000 {
+        let range = random_range(&mut rng, &s);
+
+        let mut text_end = rng.gen_range(0..=16);
+        while !CURSED_LIPSUM.is_char_boundary(text_end) {
+            text_end -= 1;
+        }
+
+        let text = &CURSED_LIPSUM[..text_end];
+
+        if s.len() - range.len() + text.len() <= buffer.capacity() {
+            buffer.replace(range.clone(), text);
+            s.replace_range(range, text);
+        } else {
+            buffer.delete(range.clone());
+            s.replace_range(range, "");
+        }
+
+        assert_eq!(buffer, s.as_str());
+
+        let range = random_range(&mut rng, &s);
+        assert_eq!(buffer.byte_slice(range.clone()), &s[range]);
+    }
+}
@@ -0,0 +1,129 @@
+mod common;
+
+#[cfg(all(feature = "grapheme-chunks", not(feature = "small_chunks")))]
+mod tests {
+    use crop::{Rope, RopeBuilder};
+    use rand::Rng;
+
+    use crate::common::{CURSED_LIPSUM, MEDIUM};
+
+    /// Grapheme clusters made of more than one code point, and a few
+    /// combining marks which are added to whatever precedes them.
+    const CLUSTERS: [&str; 6] =
+        ["e\u{301}", "\r\n", "🐻‍❄️", "👨‍👩‍👧‍👦", "🇮🇹", "\u{301}\u{302}\u{303}"];
+
+    #[track_caller]
+    fn assert_chunks_aligned(r: &Rope) {
+        let mut offset = 0;
+
+        for (left, right) in r.chunk_segments() {
+            assert!(
+                r.is_grapheme_boundary(offset),
+                "chunk boundary at {offset} splits a grapheme cluster"
+            );
+            offset += left.len() + right.len();
+        }
+    }
+
+    fn clustered_text(rng: &mut impl Rng, len: usize) -> String {
+        let mut s = String::with_capacity(len);
+
+        while s.len() < len {
+            if rng.gen_bool(0.3) {
+                s.push_str(CLUSTERS[rng.gen_range(0..CLUSTERS.len())]);
+            } else {
+                s.push('a');
+            }
+        }
+
+        s
+    }
+
+    #[test]
+    fn from_str_aligned() {
+        let mut rng = rand::thread_rng();
+
+        for _ in 0..10 {
+            let s = clustered_text(&mut rng, 20_000);
+            let r = Rope::from(s.as_str());
+            assert_chunks_aligned(&r);
+            assert_eq!(r, s);
+        }
+
+        assert_chunks_aligned(&Rope::from(CURSED_LIPSUM.repeat(100)));
+    }
+
+    #[test]
+    fn builder_aligned() {
+        let mut rng = rand::thread_rng();
+
+        let s = clustered_text(&mut rng, 20_000);
+
+        // Split the text at every code point, so that most clusters span
+        // two calls to `append()`.
+        let mut b = RopeBuilder::new();
+
+        for ch in s.chars() {
+            b.append(ch.encode_utf8(&mut [0; 4]));
+        }
+
+        let r = b.build();
+        r.assert_invariants();
+        assert_chunks_aligned(&r);
+        assert_eq!(r, s);
+    }
+
+    #[test]
+    fn insert_combining_mark_at_chunk_boundary() {
+        let mut r = Rope::from("a".repeat(10_000));
+
+        let boundaries = r
+            .chunk_segments()
+            .scan(0, |offset, (left, right)| {
+                *offset += left.len() + right.len();
+                Some(*offset)
+            })
+            .collect::<Vec<_>>();
+
+        // Insert from the back so the offsets of the boundaries left to do
+        // don't change.
+        for &boundary in boundaries.iter().rev() {
+            r.insert(boundary, "\u{301}");
+        }
+
+        assert_chunks_aligned(&r);
+    }
+
+    /// Tests that randomly editing a `Rope` keeps its chunks aligned to
+    /// grapheme clusters.
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn random_edits_aligned() {
+        let mut rng = rand::thread_rng();
+
+        let mut r = Rope::from(MEDIUM);
+        let mut s = String::from(MEDIUM);
+
+        for _ in 0..500 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=s.len().min(start + 100));
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            let len = rng.gen_range(0..3000);
+            let text = clustered_text(&mut rng, len);
+
+            r.replace(start..end, &text);
+            s.replace_range(start..end, &text);
+        }
+
+        r.assert_invariants();
+        assert_chunks_aligned(&r);
+        assert_eq!(r, s);
+    }
+}
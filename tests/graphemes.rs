@@ -131,6 +131,29 @@ fn graphemes_is_boundary_two_flags() {
     assert!(r.is_grapheme_boundary(16));
 }
 
+#[cfg(feature = "graphemes")]
+#[test]
+fn graphemes_is_boundary_ascii() {
+    let s = "a\r\n\r\rb\n\n\r";
+    let r = Rope::from(s);
+    let slice = r.byte_slice(1..);
+
+    for i in 0..=s.len() {
+        let expected =
+            !(i > 0 && &s[i - 1..i] == "\r" && s[i..].starts_with('\n'));
+        assert_eq!(r.is_grapheme_boundary(i), expected);
+    }
+
+    for i in 0..=slice.byte_len() {
+        assert_eq!(
+            slice.is_grapheme_boundary(i),
+            r.is_grapheme_boundary(i + 1)
+        );
+    }
+
+    assert!(r.byte_slice(2..).is_grapheme_boundary(0));
+}
+
 #[cfg(feature = "graphemes")]
 #[should_panic]
 #[test]
@@ -138,3 +161,177 @@ fn graphemes_is_boundary_out_of_bounds() {
     let r = Rope::from("🇷🇸🇮🇴");
     assert!(r.is_grapheme_boundary(17));
 }
+
+#[cfg(feature = "graphemes")]
+#[test]
+fn grapheme_len() {
+    let r = Rope::from("e\u{301}\r\n🐻‍❄️ab");
+    assert_eq!(r.grapheme_len(), 5);
+    assert_eq!(r.byte_slice(1..).grapheme_len(), 5);
+    assert_eq!(r.byte_slice(..5).grapheme_len(), 2);
+    assert_eq!(Rope::new().grapheme_len(), 0);
+}
+
+/// Tests that the grapheme clusters spanning the boundaries between chunks are
+/// counted once, and that prepending a regional indicator pairs up every
+/// other one differently.
+///
+/// Without the `grapheme-count` feature the clusters are counted by the
+/// `Graphemes` iterator, which is affected by the issue mentioned at the top
+/// of this file.
+#[cfg(feature = "grapheme-count")]
+#[test]
+fn grapheme_len_across_chunks() {
+    for (cluster, count) in [
+        ("🇺🇸", 1000),
+        ("👩\u{200D}👩\u{200D}👧", 400),
+        ("\u{915}\u{94D}\u{937}", 500),
+        ("e\u{301}", 1500),
+    ] {
+        let r = Rope::from(cluster.repeat(count));
+        assert_eq!(r.grapheme_len(), count);
+    }
+
+    let mut r = Rope::from("🇺🇸".repeat(1000));
+    r.insert(0, "🇫");
+    assert_eq!(r.grapheme_len(), 1001);
+}
+
+/// Tests that the number of grapheme clusters is kept up to date while
+/// editing text full of clusters whose boundaries depend on what comes
+/// before them, like flags, emoji sequences and Indic conjuncts.
+#[cfg(feature = "grapheme-count")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn grapheme_len_after_random_edits() {
+    use rand::Rng;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let pieces = [
+        "a", "\r", "\n", "🇺", "🇸", "\u{200D}", "👩", "\u{301}", "क",
+        "\u{94D}", "中",
+    ];
+
+    let mut rng = rand::thread_rng();
+
+    let mut s = (0..3000)
+        .map(|_| pieces[rng.gen_range(0..pieces.len())])
+        .collect::<String>();
+
+    let mut r = Rope::from(s.as_str());
+
+    assert_eq!(r.grapheme_len(), s.graphemes(true).count());
+
+    for _ in 0..100 {
+        let mut start = rng.gen_range(0..=r.byte_len());
+        while !r.is_char_boundary(start) {
+            start -= 1;
+        }
+        let mut end = (start + rng.gen_range(0..30)).min(r.byte_len());
+        while !r.is_char_boundary(end) {
+            end -= 1;
+        }
+        let text = (0..rng.gen_range(0..4))
+            .map(|_| pieces[rng.gen_range(0..pieces.len())])
+            .collect::<String>();
+
+        r.replace(start..end, &text);
+        s.replace_range(start..end, &text);
+
+        assert_eq!(r.grapheme_len(), s.graphemes(true).count());
+    }
+
+    r.assert_invariants();
+}
+
+/// Tests that converting between byte and grapheme offsets, slicing by
+/// grapheme offsets and counting the clusters of a `RopeSlice` agree with
+/// segmenting the whole text, in text full of clusters which span the
+/// boundaries between chunks.
+#[cfg(feature = "grapheme-count")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn grapheme_offsets_random() {
+    use rand::Rng;
+    use unicode_segmentation::UnicodeSegmentation;
+
+    let pieces = [
+        "a", "\r", "\n", "🇺", "🇸", "\u{200D}", "👩", "\u{301}", "क",
+        "\u{94D}", "中",
+    ];
+
+    let mut rng = rand::thread_rng();
+
+    let random = (0..5000)
+        .map(|_| pieces[rng.gen_range(0..pieces.len())])
+        .collect::<String>();
+
+    // Every flag is split differently than in the chunk it's in.
+    let flags = format!("🇫{}", "🇺🇸".repeat(1000));
+
+    for s in [random, flags] {
+        let r = Rope::from(s.as_str());
+
+        for _ in 0..20 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+            let mut end = rng.gen_range(start..=s.len());
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+            let text = &s[start..end];
+
+            let boundaries = text
+                .grapheme_indices(true)
+                .map(|(offset, _)| offset)
+                .chain(core::iter::once(text.len()))
+                .collect::<Vec<_>>();
+
+            let grapheme_len = boundaries.len() - 1;
+
+            assert_eq!(slice.grapheme_len(), grapheme_len);
+
+            for _ in 0..5 {
+                let grapheme_start = rng.gen_range(0..=grapheme_len);
+                let grapheme_end =
+                    rng.gen_range(grapheme_start..=grapheme_len);
+
+                let byte_start = boundaries[grapheme_start];
+                let byte_end = boundaries[grapheme_end];
+
+                assert_eq!(slice.byte_of_grapheme(grapheme_start), byte_start);
+                assert_eq!(slice.grapheme_of_byte(byte_start), grapheme_start);
+
+                assert_eq!(
+                    slice.grapheme_slice(grapheme_start..grapheme_end),
+                    &text[byte_start..byte_end]
+                );
+
+                // A byte offset inside a cluster belongs to that cluster.
+                let mut byte_offset = rng.gen_range(0..=text.len());
+                while !text.is_char_boundary(byte_offset) {
+                    byte_offset -= 1;
+                }
+
+                assert_eq!(
+                    slice.grapheme_of_byte(byte_offset),
+                    boundaries.partition_point(|&b| b <= byte_offset) - 1
+                );
+            }
+        }
+
+        assert_eq!(r.grapheme_len(), s.graphemes(true).count());
+    }
+}
+
+#[cfg(feature = "grapheme-count")]
+#[test]
+#[should_panic]
+fn byte_of_grapheme_out_of_bounds() {
+    let r = Rope::from("🇺🇸🇮🇹");
+    let _ = r.byte_of_grapheme(3);
+}
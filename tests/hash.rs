@@ -0,0 +1,62 @@
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+
+use crop::{Rope, RopeBuilder};
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+fn hash<T: Hash>(value: &T) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    value.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Tests that `Rope`s and `RopeSlice`s with the same contents hash to the
+/// same value even if their text is split into chunks differently.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn hash_chunk_independent() {
+    let mut rng = rand::thread_rng();
+
+    for s in ["", "Hi", "🐕‍🦺", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM]
+    {
+        let r = Rope::from(s);
+
+        let mut builder = RopeBuilder::new();
+        let mut rest = s;
+        while !rest.is_empty() {
+            let mut len = rng.gen_range(1..=rest.len());
+            while !rest.is_char_boundary(len) {
+                len += 1;
+            }
+            builder.append(&rest[..len]);
+            rest = &rest[len..];
+        }
+        let built = builder.build();
+
+        let mut edited = Rope::from(s);
+        edited.insert(0, "foo");
+        edited.delete(..3);
+
+        let sliced = Rope::from(format!("bar{s}baz"));
+        let slice = sliced.byte_slice(3..3 + s.len());
+
+        assert_eq!(hash(&r), hash(&built));
+        assert_eq!(hash(&r), hash(&edited));
+        assert_eq!(hash(&r), hash(&slice));
+        assert_eq!(hash(&r), hash(&Rope::from(slice)));
+    }
+}
+
+#[test]
+fn hash_different_contents() {
+    assert_ne!(hash(&Rope::from("foo")), hash(&Rope::from("bar")));
+
+    assert_ne!(
+        hash(&(Rope::from("foo"), Rope::from("bar"))),
+        hash(&(Rope::from("foobar"), Rope::new()))
+    );
+}
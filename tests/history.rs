@@ -0,0 +1,114 @@
+use crop::{Gravity, Rope, Tracked};
+use rand::Rng;
+
+mod common;
+
+use common::CURSED_LIPSUM;
+
+#[test]
+fn revision_counts_edits() {
+    let mut t = Tracked::new(Rope::from("Hello"));
+    assert_eq!(t.revision(), 0);
+
+    t.insert(5, " Earth");
+    t.delete(0..1);
+    t.edit(|rope| rope.append(Rope::from("!")));
+    assert_eq!(t.revision(), 3);
+
+    assert_eq!(t.clone().revision(), 3);
+
+    // Edits which don't change the text don't count.
+    t.edit(|rope| rope.replace(0..1, "e"));
+    assert_eq!(t.revision(), 3);
+}
+
+#[test]
+fn resolve_without_history() {
+    let mut t = Tracked::new(Rope::from("Hello"));
+    t.insert(0, "a");
+    assert_eq!(t.resolve_offset(0, 0, Gravity::Left), None);
+    assert_eq!(t.resolve_offset(1, 0, Gravity::Left), None);
+
+    t.track_history();
+    assert_eq!(t.resolve_offset(0, 0, Gravity::Left), None);
+    assert_eq!(t.resolve_offset(1, 6, Gravity::Left), Some(6));
+    assert_eq!(t.resolve_offset(2, 0, Gravity::Left), None);
+}
+
+#[test]
+fn forget_history() {
+    let mut t = Tracked::new(Rope::from("Hello"));
+    t.track_history();
+
+    t.insert(0, "a");
+    t.insert(0, "b");
+    t.insert(0, "c");
+
+    t.forget_history(2);
+
+    assert_eq!(t.resolve_offset(1, 0, Gravity::Right), None);
+    assert_eq!(t.resolve_offset(2, 0, Gravity::Right), Some(1));
+    assert_eq!(t.resolve_offset(3, 0, Gravity::Right), Some(0));
+}
+
+#[test]
+#[should_panic]
+fn resolve_out_of_bounds() {
+    let mut t = Tracked::new(Rope::from("Hello"));
+    t.track_history();
+    t.insert(5, " Earth");
+    t.resolve_offset(0, 6, Gravity::Left);
+}
+
+/// Tests that resolving an offset from a past revision gives the same
+/// result as an anchor created at that revision.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_resolve_like_anchors() {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..20 {
+        let mut t = Tracked::new(Rope::from(CURSED_LIPSUM));
+
+        t.track_history();
+
+        let mut anchors = Vec::new();
+
+        for _ in 0..50 {
+            let mut offset = rng.gen_range(0..=t.rope().byte_len());
+            while !t.rope().is_char_boundary(offset) {
+                offset -= 1;
+            }
+
+            let gravity =
+                if rng.gen() { Gravity::Left } else { Gravity::Right };
+
+            anchors.push((
+                t.revision(),
+                offset,
+                gravity,
+                t.anchor(offset, gravity),
+            ));
+
+            let mut start = rng.gen_range(0..=t.rope().byte_len());
+            while !t.rope().is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end =
+                rng.gen_range(start..=t.rope().byte_len().min(start + 20));
+            while !t.rope().is_char_boundary(end) {
+                end -= 1;
+            }
+
+            t.replace(start..end, ["", "a", "🐸"][rng.gen_range(0..3)]);
+
+            for &(revision, offset, gravity, anchor) in &anchors {
+                assert_eq!(
+                    t.resolve_offset(revision, offset, gravity),
+                    t.anchor_offset(anchor)
+                );
+            }
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::ops::Range;
+
+use crop::{IntervalMap, Rope};
+use rand::Rng;
+
+mod common;
+
+use common::SMALL;
+
+/// A naive interval map which every operation is checked against.
+#[derive(Default)]
+struct Model {
+    intervals: Vec<(Range<usize>, usize)>,
+}
+
+impl Model {
+    fn apply_delta(&mut self, replaced: Range<usize>, inserted_len: usize) {
+        let Range { start, end } = replaced;
+
+        let map_start = |offset: usize| {
+            if offset < start {
+                offset
+            } else if offset <= end {
+                start + inserted_len
+            } else {
+                offset - (end - start) + inserted_len
+            }
+        };
+
+        let map_end = |offset: usize| {
+            if offset <= start {
+                offset
+            } else if offset <= end {
+                start
+            } else {
+                offset - (end - start) + inserted_len
+            }
+        };
+
+        for (range, _) in &mut self.intervals {
+            let new_start = map_start(range.start);
+            let new_end = map_end(range.end).max(new_start);
+            *range = new_start..new_end;
+        }
+    }
+
+    fn insert(&mut self, range: Range<usize>, value: usize) {
+        let idx = self
+            .intervals
+            .iter()
+            .position(|(r, _)| r.start > range.start)
+            .unwrap_or(self.intervals.len());
+
+        self.intervals.insert(idx, (range, value));
+    }
+
+    fn overlapping(&self, range: Range<usize>) -> Vec<(Range<usize>, usize)> {
+        self.intervals
+            .iter()
+            .filter(|(r, _)| r.start < range.end && range.start < r.end)
+            .cloned()
+            .collect()
+    }
+}
+
+fn to_vec(map: &IntervalMap<usize>) -> Vec<(Range<usize>, usize)> {
+    map.iter().map(|(range, &value)| (range, value)).collect()
+}
+
+fn random_range(rng: &mut impl Rng, len: usize) -> Range<usize> {
+    let start = rng.gen_range(0..=len);
+    let end = rng.gen_range(start..=(start + 50).min(len));
+    start..end
+}
+
+#[test]
+fn empty() {
+    let map = IntervalMap::<()>::new();
+    map.assert_invariants();
+    assert!(map.is_empty());
+    assert_eq!(map.iter().count(), 0);
+    assert_eq!(map.overlapping(0..10).count(), 0);
+}
+
+#[test]
+#[should_panic]
+fn insert_start_after_end() {
+    let mut map = IntervalMap::new();
+    #[allow(clippy::reversed_empty_ranges)]
+    map.insert(3..2, ());
+}
+
+#[test]
+fn insert_same_start() {
+    let mut map = IntervalMap::new();
+
+    for value in 0..20 {
+        map.insert(5..5 + value, value);
+        map.assert_invariants();
+    }
+
+    assert_eq!(map.len(), 20);
+    assert!(map.iter().map(|(_, &value)| value).eq(0..20));
+}
+
+#[test]
+fn apply_delta_insertion_at_boundaries() {
+    let mut map = IntervalMap::new();
+    map.insert(0..2, 'a');
+    map.insert(2..4, 'b');
+    map.insert(4..4, 'c');
+
+    map.apply_delta(2..2, 3);
+    map.apply_delta(7..7, 1);
+
+    assert_eq!(
+        map.iter().collect::<Vec<_>>(),
+        [(0..2, &'a'), (5..7, &'b'), (8..8, &'c')]
+    );
+}
+
+#[test]
+fn apply_delta_deletion() {
+    let mut map = IntervalMap::new();
+    map.insert(0..10, 'a');
+    map.insert(2..4, 'b');
+    map.insert(3..8, 'c');
+    map.insert(9..12, 'd');
+
+    map.apply_delta(2..9, 0);
+
+    assert_eq!(
+        map.iter().collect::<Vec<_>>(),
+        [(0..3, &'a'), (2..2, &'b'), (2..2, &'c'), (2..5, &'d')]
+    );
+}
+
+#[test]
+fn from_iter() {
+    let map = [(4..6, 'b'), (0..10, 'a'), (4..5, 'c')]
+        .into_iter()
+        .collect::<IntervalMap<_>>();
+
+    map.assert_invariants();
+
+    assert_eq!(
+        map.iter().collect::<Vec<_>>(),
+        [(0..10, &'a'), (4..6, &'b'), (4..5, &'c')]
+    );
+}
+
+/// Tests that a map tracking the words of a `Rope` keeps pointing to the
+/// same words as random edits are made to the text around them.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn follows_rope_edits() {
+    let mut rng = rand::thread_rng();
+
+    let mut rope = Rope::from("foo bar baz qux");
+
+    let mut map = IntervalMap::new();
+    map.insert(0..3, "foo");
+    map.insert(4..7, "bar");
+    map.insert(8..11, "baz");
+    map.insert(12..15, "qux");
+
+    for _ in 0..100 {
+        // Only insert text in the spaces between the words.
+        let spaces =
+            map.iter().map(|(range, _)| range.end).collect::<Vec<_>>();
+
+        let at = spaces[rng.gen_range(0..spaces.len())];
+
+        rope.insert(at, " ");
+        map.apply_delta(at..at, 1);
+        map.assert_invariants();
+
+        for (range, word) in map.iter() {
+            assert_eq!(rope.byte_slice(range), *word);
+        }
+    }
+}
+
+/// Tests that random insertions, edits and queries always match those of a
+/// naive implementation.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_against_model() {
+    let mut rng = rand::thread_rng();
+
+    for _ in 0..20 {
+        let mut map = IntervalMap::new();
+        let mut model = Model::default();
+        let mut len = SMALL.len();
+
+        for value in 0..200 {
+            match rng.gen_range(0..3) {
+                0 => {
+                    let range = random_range(&mut rng, len);
+                    map.insert(range.clone(), value);
+                    model.insert(range, value);
+                },
+
+                1 => {
+                    let replaced = random_range(&mut rng, len);
+                    let inserted_len = rng.gen_range(0..20);
+                    len = len - replaced.len() + inserted_len;
+                    map.apply_delta(replaced.clone(), inserted_len);
+                    model.apply_delta(replaced, inserted_len);
+                },
+
+                _ => {
+                    let range = random_range(&mut rng, len);
+                    let overlapping = map
+                        .overlapping(range.clone())
+                        .map(|(range, &value)| (range, value))
+                        .collect::<Vec<_>>();
+                    assert_eq!(overlapping, model.overlapping(range));
+                },
+            }
+
+            map.assert_invariants();
+            assert_eq!(map.len(), model.intervals.len());
+            assert_eq!(to_vec(&map), model.intervals);
+        }
+
+        map.retain(|range, _| range.len() % 2 == 0);
+        model.intervals.retain(|(range, _)| range.len() % 2 == 0);
+
+        map.assert_invariants();
+        assert_eq!(to_vec(&map), model.intervals);
+    }
+}
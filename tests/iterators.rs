@@ -87,6 +87,7 @@ fn iter_bytes_both_ways() {
 }
 
 #[test]
+#[allow(clippy::needless_as_bytes)]
 fn iter_bytes_cursed() {
     let s = CURSED_LIPSUM;
     let r = Rope::from(s);
@@ -172,6 +173,181 @@ fn iter_bytes_over_slice_backward() {
     }
 }
 
+/// Tests that skipping bytes with `nth()` from the front while also
+/// iterating from the back gives the same bytes as a `str`'s iterator.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_bytes_nth_random() {
+    let mut rng = thread_rng();
+
+    for s in [TINY, CURSED_LIPSUM, MEDIUM, LARGE] {
+        let r = Rope::from(s);
+
+        for _ in 0..20 {
+            let mut rope_bytes = r.bytes();
+            let mut str_bytes = s.bytes();
+
+            loop {
+                assert_eq!(rope_bytes.len(), str_bytes.len());
+
+                let (rope_byte, str_byte) = if rng.gen_bool(0.2) {
+                    (rope_bytes.next_back(), str_bytes.next_back())
+                } else {
+                    let n = rng.gen_range(0..=s.len() / 8);
+                    (rope_bytes.nth(n), str_bytes.nth(n))
+                };
+
+                assert_eq!(rope_byte, str_byte);
+
+                if rope_byte.is_none() {
+                    break;
+                }
+            }
+
+            assert_eq!(rope_bytes.count(), 0);
+        }
+    }
+}
+
+#[test]
+fn iter_bytes_count_last() {
+    let r = Rope::from(LARGE);
+
+    assert_eq!(r.bytes().count(), LARGE.len());
+    assert_eq!(r.bytes().last(), LARGE.bytes().last());
+
+    let mut bytes = r.bytes();
+    bytes.next();
+    bytes.next_back();
+    assert_eq!(bytes.count(), LARGE.len() - 2);
+
+    let slice = r.byte_slice(100..LARGE.len() - 100);
+    assert_eq!(slice.bytes().count(), LARGE.len() - 200);
+    assert_eq!(
+        slice.bytes().last(),
+        Some(LARGE.as_bytes()[LARGE.len() - 101])
+    );
+}
+
+/// Tests that reading bytes into buffers of random lengths, while also
+/// iterating from the back, gives the same bytes as a `str`.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_bytes_read_into_random() {
+    let mut rng = thread_rng();
+
+    for s in [TINY, CURSED_LIPSUM, MEDIUM, LARGE] {
+        let r = Rope::from(s);
+
+        for _ in 0..20 {
+            let mut bytes = r.bytes();
+
+            let mut front = Vec::new();
+            let mut back = Vec::new();
+
+            let mut buf = vec![0; rng.gen_range(1..=s.len().max(1) / 4 + 1)];
+
+            loop {
+                if rng.gen_bool(0.2) {
+                    back.extend(bytes.next_back());
+                    continue;
+                }
+
+                let read = bytes.read_into(&mut buf);
+                front.extend_from_slice(&buf[..read]);
+
+                if read < buf.len() {
+                    break;
+                }
+            }
+
+            assert_eq!(bytes.len(), 0);
+            assert_eq!(bytes.read_into(&mut buf), 0);
+
+            back.reverse();
+            front.extend(back);
+            assert_eq!(front, s.as_bytes());
+        }
+    }
+}
+
+#[test]
+fn to_vec() {
+    for s in ["", TINY, CURSED_LIPSUM, LARGE] {
+        let r = Rope::from(s);
+
+        assert_eq!(r.to_vec(), s.as_bytes());
+        assert_eq!(Vec::from(&r), s.as_bytes());
+        assert_eq!(r.byte_slice(..).to_vec(), s.as_bytes());
+
+        let mut start = s.len() / 3;
+        while !s.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        assert_eq!(r.byte_slice(start..).to_vec(), &s.as_bytes()[start..]);
+    }
+}
+
+#[test]
+fn iter_chunk_segments_empty() {
+    let r = Rope::new();
+    assert_eq!(0, r.chunk_segments().count());
+    assert_eq!(0, r.byte_slice(..).chunk_segments().count());
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_chunk_segments() {
+    let mut rng = thread_rng();
+
+    for text in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut r = Rope::from(text);
+
+        // Move the gaps of a few chunks away from their ends.
+        for _ in 0..10 {
+            let mut offset = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            r.insert(offset, "a");
+        }
+
+        let s = r.to_string();
+
+        let forward = r
+            .chunk_segments()
+            .inspect(|(left, right)| {
+                assert!(!left.is_empty() || !right.is_empty())
+            })
+            .flat_map(|(left, right)| [left, right])
+            .collect::<String>();
+
+        assert_eq!(forward, s);
+
+        let mut backward = r.chunk_segments().rev().collect::<Vec<_>>();
+        backward.reverse();
+        assert_eq!(
+            backward.into_iter().flat_map(|(l, r)| [l, r]).collect::<String>(),
+            s
+        );
+
+        let mut start = rng.gen_range(0..=r.byte_len());
+        while !r.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        let slice = r.byte_slice(start..);
+
+        let sliced = slice
+            .chunk_segments()
+            .flat_map(|(left, right)| [left, right])
+            .collect::<String>();
+
+        assert_eq!(sliced, s[start..]);
+    }
+}
+
 #[test]
 fn iter_chars_empty() {
     let r = Rope::new();
@@ -254,6 +430,63 @@ fn iter_chars_both_ways() {
     assert_eq!(None, rope_chars.next_back());
 }
 
+/// Tests that skipping chars with `nth()` from the front while also
+/// iterating from the back gives the same chars as a `str`'s iterator.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_chars_nth_random() {
+    let mut rng = thread_rng();
+
+    for s in [TINY, CURSED_LIPSUM, MEDIUM, &CURSED_LIPSUM.repeat(50)] {
+        let r = Rope::from(s);
+
+        let total_chars = s.chars().count();
+
+        for _ in 0..20 {
+            let mut rope_chars = r.chars();
+            let mut str_chars = s.chars();
+            let mut chars_left = total_chars;
+
+            loop {
+                #[cfg(feature = "char-metric")]
+                assert_eq!(rope_chars.len(), chars_left);
+
+                let (rope_char, str_char) = if rng.gen_bool(0.2) {
+                    (rope_chars.next_back(), str_chars.next_back())
+                } else {
+                    let n = rng.gen_range(0..=total_chars / 8);
+                    chars_left -= n.min(chars_left);
+                    (rope_chars.nth(n), str_chars.nth(n))
+                };
+
+                assert_eq!(rope_char, str_char);
+
+                if rope_char.is_none() {
+                    break;
+                }
+
+                chars_left -= 1;
+            }
+
+            assert_eq!(rope_chars.count(), 0);
+        }
+    }
+}
+
+#[test]
+fn iter_chars_count_last() {
+    let s = CURSED_LIPSUM.repeat(50);
+    let r = Rope::from(s.as_str());
+
+    assert_eq!(r.chars().count(), s.chars().count());
+    assert_eq!(r.chars().last(), s.chars().last());
+
+    let mut chars = r.chars();
+    chars.next();
+    chars.next_back();
+    assert_eq!(chars.count(), s.chars().count() - 2);
+}
+
 #[test]
 fn iter_chars_cursed() {
     let s = CURSED_LIPSUM;
@@ -271,6 +504,7 @@ fn iter_chars_cursed() {
     }
 }
 
+#[cfg(feature = "lines")]
 #[test]
 fn iter_lines_empty() {
     let r = Rope::new();
@@ -278,6 +512,7 @@ fn iter_lines_empty() {
     assert_eq!(0, r.line_slice(..).lines().count());
 }
 
+#[cfg(feature = "lines")]
 #[test]
 fn iter_lines_0() {
     let r = Rope::from("abc");
@@ -309,6 +544,7 @@ fn iter_lines_0() {
     assert_eq!(4, r.byte_slice(..).lines().count());
 }
 
+#[cfg(feature = "lines")]
 #[test]
 fn iter_lines_1() {
     let s = "\n\n\r\n\r\n\n\r\n\n";
@@ -327,6 +563,7 @@ fn iter_lines_1() {
     }
 }
 
+#[cfg(feature = "lines")]
 #[test]
 fn iter_lines_2() {
     let s = "this is\na line\r\nwith mixed\nline breaks\n";
@@ -345,6 +582,7 @@ fn iter_lines_2() {
     }
 }
 
+#[cfg(feature = "lines")]
 #[test]
 fn iter_lines_3() {
     let s = "This is a piece\nof text that doesn't\nfit in one\n chunk\nand \
@@ -365,6 +603,7 @@ fn iter_lines_3() {
     }
 }
 
+#[cfg(feature = "lines")]
 #[test]
 fn iter_lines_4() {
     let r = Rope::from(
@@ -386,6 +625,7 @@ fn iter_lines_4() {
     assert_eq!(None, lines.next());
 }
 
+#[cfg(feature = "lines")]
 #[cfg_attr(miri, ignore)]
 #[test]
 fn iter_lines_over_test_vectors() {
@@ -407,6 +647,7 @@ fn iter_lines_over_test_vectors() {
     }
 }
 
+#[cfg(feature = "lines")]
 #[test]
 fn iter_lines_forward_backward() {
     let r = Rope::from("\na\nb\nc\n");
@@ -426,6 +667,7 @@ fn iter_lines_forward_backward() {
     assert_eq!(None, backward.next());
 }
 
+#[cfg(feature = "lines")]
 #[cfg_attr(miri, ignore)]
 #[test]
 fn iter_lines_over_random_slices() {
@@ -475,6 +717,27 @@ fn iter_lines_over_random_slices() {
     }
 }
 
+#[cfg(feature = "lines")]
+#[test]
+fn iter_lines_count_last() {
+    let r = Rope::from(LARGE);
+
+    let line_len = LARGE.lines().count();
+
+    assert_eq!(r.lines().count(), line_len);
+    assert_eq!(r.raw_lines().count(), line_len);
+    assert_eq!(r.lines_with_offsets().count(), line_len);
+
+    assert_eq!(r.lines().last().unwrap(), LARGE.lines().last().unwrap());
+    assert_eq!(r.raw_lines().last(), Some(r.line_slice(line_len - 1..)));
+
+    let mut lines = r.lines();
+    lines.next();
+    lines.next_back();
+    assert_eq!(lines.count(), line_len - 2);
+}
+
+#[cfg(feature = "lines")]
 #[test]
 fn iter_raw_lines_0() {
     let r = Rope::from(
@@ -499,6 +762,7 @@ fn iter_raw_lines_0() {
     assert_eq!(None, lines.next());
 }
 
+#[cfg(feature = "lines")]
 #[test]
 fn iter_raw_lines_backward_0() {
     let r = Rope::from(
@@ -523,6 +787,7 @@ fn iter_raw_lines_backward_0() {
     assert_eq!(None, lines.next());
 }
 
+#[cfg(feature = "lines")]
 #[cfg_attr(miri, ignore)]
 #[test]
 fn iter_raw_lines_over_test_vectors() {
@@ -544,6 +809,7 @@ fn iter_raw_lines_over_test_vectors() {
     }
 }
 
+#[cfg(feature = "lines")]
 #[cfg_attr(miri, ignore)]
 #[test]
 fn iter_raw_lines_over_random_slices() {
@@ -618,3 +884,451 @@ fn iter_raw_lines_over_random_slices() {
         }
     }
 }
+
+#[cfg(feature = "lines")]
+#[test]
+fn iter_paragraphs_0() {
+    let r = Rope::from("\n \nfoo\nbar\r\n\r\n\t\n\nbaz\n  qux  \n\n");
+
+    let mut paragraphs = r.paragraphs();
+
+    assert_eq!("foo\nbar", paragraphs.next().unwrap());
+    assert_eq!("baz\n  qux  ", paragraphs.next().unwrap());
+    assert_eq!(None, paragraphs.next());
+
+    assert_eq!(Rope::new().paragraphs().count(), 0);
+    assert_eq!(Rope::from("\n\n  \n").paragraphs().count(), 0);
+    assert_eq!(Rope::from("foo").paragraphs().next().unwrap(), "foo");
+}
+
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_paragraphs_over_random_slices() {
+    /// The paragraphs of a string, computed from its lines.
+    fn paragraphs(s: &str) -> Vec<&str> {
+        let mut paragraphs = Vec::new();
+        let mut start = None;
+        let mut end = 0;
+        let mut offset = 0;
+
+        for raw_line in s.split_inclusive('\n') {
+            let line = raw_line.strip_suffix('\n').unwrap_or(raw_line);
+            let line = line.strip_suffix('\r').unwrap_or(line);
+
+            if line.trim().is_empty() {
+                if let Some(start) = start.take() {
+                    paragraphs.push(&s[start..end]);
+                }
+            } else {
+                start.get_or_insert(offset);
+                end = offset + line.len();
+            }
+
+            offset += raw_line.len();
+        }
+
+        if let Some(start) = start {
+            paragraphs.push(&s[start..end]);
+        }
+
+        paragraphs
+    }
+
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let rope = Rope::from(s);
+
+        for _ in 0..100 {
+            let start = rng.gen_range(0..=rope.byte_len());
+            let end = rng.gen_range(start..=rope.byte_len());
+
+            let rope_slice = rope.byte_slice(start..end);
+            let str_slice = &s[start..end];
+
+            let rope_paragraphs = rope_slice.paragraphs().collect::<Vec<_>>();
+            let str_paragraphs = paragraphs(str_slice);
+
+            assert_eq!(rope_paragraphs.len(), str_paragraphs.len());
+
+            for (rope_paragraph, str_paragraph) in
+                rope_paragraphs.into_iter().zip(str_paragraphs)
+            {
+                rope_paragraph.assert_invariants();
+                assert_eq!(rope_paragraph, str_paragraph);
+            }
+        }
+    }
+}
+
+/// The `\r` of a CRLF cut in half by the end of the range is not part of the
+/// last line.
+#[cfg(feature = "lines")]
+#[test]
+fn iter_lines_in_byte_range_splitting_crlf() {
+    let r = Rope::from("foo\r\nbar\r\n\r\nbaz");
+
+    let lines = |range: core::ops::Range<usize>| {
+        let forward = r
+            .lines_in_byte_range(range.clone())
+            .map(|(idx, line)| (idx, line.to_string()))
+            .collect::<Vec<_>>();
+
+        let mut backward = r
+            .byte_slice(..)
+            .lines_in_byte_range(range)
+            .rev()
+            .map(|(idx, line)| (idx, line.to_string()))
+            .collect::<Vec<_>>();
+        backward.reverse();
+
+        assert_eq!(forward, backward);
+        forward
+    };
+
+    assert_eq!(lines(0..4), [(0, "foo".into())]);
+    assert_eq!(lines(1..9), [(0, "oo".into()), (1, "bar".into())]);
+    assert_eq!(lines(3..4), [(0, "".into())]);
+    assert_eq!(lines(10..11), [(2, "".into())]);
+    assert_eq!(
+        lines(2..11),
+        [(0, "o".into()), (1, "bar".into()), (2, "".into())]
+    );
+
+    // Not split, so the `\r\n` is a line terminator.
+    assert_eq!(lines(0..5), [(0, "foo".into())]);
+}
+
+/// Regression test: the last line used to be trimmed by re-slicing it, which
+/// panicked when the range only contained the `\r` of a split CRLF.
+#[cfg(feature = "lines")]
+#[test]
+fn iter_lines_in_byte_range_only_cr_of_crlf() {
+    let r = Rope::from("🌏\r\nbb\r\na🌏");
+
+    let lines = r.lines_in_byte_range(4..5).collect::<Vec<_>>();
+    assert_eq!(lines, [(0, r.byte_slice(4..4))]);
+
+    let lines = r.byte_slice(..).lines_in_byte_range(4..5).rev();
+    assert_eq!(lines.collect::<Vec<_>>(), [(0, r.byte_slice(4..4))]);
+
+    let lines = r.lines_in_byte_range(9..10).collect::<Vec<_>>();
+    assert_eq!(lines, [(1, r.byte_slice(9..9))]);
+}
+
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_lines_in_byte_range_over_random_slices() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let rope = Rope::from(s);
+
+        for _ in 0..100 {
+            let start = rng.gen_range(0..=rope.byte_len());
+            let end = rng.gen_range(start..=rope.byte_len());
+
+            let first_line = s[..start].matches('\n').count();
+
+            let expected = s[start..end]
+                .lines()
+                .enumerate()
+                .map(|(idx, line)| (first_line + idx, line))
+                .collect::<Vec<_>>();
+
+            let lines = rope.lines_in_byte_range(start..end);
+
+            assert_eq!(lines.len(), expected.len());
+
+            for ((idx, line), (expected_idx, expected_line)) in
+                lines.zip(expected.iter().copied())
+            {
+                assert_eq!(idx, expected_idx);
+                assert_eq!(line, expected_line);
+            }
+
+            for ((idx, line), (expected_idx, expected_line)) in rope
+                .byte_slice(..)
+                .lines_in_byte_range(start..end)
+                .rev()
+                .zip(expected.iter().copied().rev())
+            {
+                assert_eq!(idx, expected_idx);
+                assert_eq!(line, expected_line);
+            }
+        }
+    }
+}
+
+#[cfg(feature = "lines")]
+#[test]
+fn iter_lines_with_offsets_crlf() {
+    let r = Rope::from("a\r\n\nbc\r\nd");
+
+    let lines = r.lines_with_offsets().collect::<Vec<_>>();
+
+    assert_eq!(lines.len(), 4);
+    assert_eq!((lines[0].0, lines[0].1), (0, r.byte_slice(0..1)));
+    assert_eq!((lines[1].0, lines[1].1), (3, r.byte_slice(3..3)));
+    assert_eq!((lines[2].0, lines[2].1), (4, r.byte_slice(4..6)));
+    assert_eq!((lines[3].0, lines[3].1), (8, r.byte_slice(8..9)));
+
+    let rev = r.lines_with_offsets().rev().collect::<Vec<_>>();
+    assert_eq!(rev.into_iter().rev().collect::<Vec<_>>(), lines);
+}
+
+/// Tests that `lines_with_offsets()` yields the same lines as `lines()`,
+/// together with their `byte_of_line()`, when iterated from either end.
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_lines_with_offsets_over_random_slices() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let rope = Rope::from(s);
+
+        for _ in 0..20 {
+            let start = rng.gen_range(0..=rope.byte_len());
+            let end = rng.gen_range(start..=rope.byte_len());
+
+            let slice = rope.byte_slice(start..end);
+
+            let expected = slice
+                .lines()
+                .enumerate()
+                .map(|(idx, line)| (slice.byte_of_line(idx), line))
+                .collect::<Vec<_>>();
+
+            let mut lines = slice.lines_with_offsets();
+
+            assert_eq!(lines.len(), expected.len());
+
+            // Alternate between the two ends.
+            let (mut front, mut back) = (0, expected.len());
+
+            while front < back {
+                if rng.gen_bool(0.5) {
+                    assert_eq!(lines.next(), Some(expected[front]));
+                    front += 1;
+                } else {
+                    back -= 1;
+                    assert_eq!(lines.next_back(), Some(expected[back]));
+                }
+                assert_eq!(lines.len(), back - front);
+            }
+
+            assert_eq!(lines.next(), None);
+            assert_eq!(lines.next_back(), None);
+        }
+    }
+
+    let r = Rope::from(MEDIUM);
+
+    assert!(r.lines_with_offsets().zip(r.lines().enumerate()).all(
+        |((offset, line), (idx, expected))| {
+            offset == r.byte_of_line(idx) && line == expected
+        }
+    ));
+}
+
+/// Tests that the chunks yielded when iterating from both ends at once, in
+/// a random order, add up to the text, and that the `size_hint()` is always
+/// respected.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_chunks_from_both_ends() {
+    let mut rng = rand::thread_rng();
+
+    for s in ["", TINY, SMALL, MEDIUM, LARGE] {
+        let mut rope = Rope::from(s);
+
+        // Edit the rope to move the gaps of some of its gap buffers in the
+        // middle of their chunks, so that those leaves yield two chunks.
+        for _ in 0..10 {
+            if !rope.is_empty() {
+                let offset = rng.gen_range(0..rope.byte_len());
+                rope.insert(offset, "a");
+                rope.delete(offset..offset + 1);
+            }
+        }
+
+        for _ in 0..20 {
+            let start = rng.gen_range(0..=rope.byte_len());
+            let end = rng.gen_range(start..=rope.byte_len());
+
+            let slice = rope.byte_slice(start..end);
+
+            for (mut chunks, expected) in [
+                (rope.chunks(), rope.to_string()),
+                (slice.chunks(), slice.to_string()),
+            ] {
+                let mut front = String::new();
+                let mut back = Vec::new();
+
+                let mut remaining = chunks.clone().count();
+
+                loop {
+                    let (lo, hi) = chunks.size_hint();
+                    assert!(lo <= remaining);
+                    assert!(hi.unwrap() >= remaining);
+
+                    let chunk = if rng.gen() {
+                        chunks.next().map(|chunk| front.push_str(chunk))
+                    } else {
+                        chunks.next_back().map(|chunk| back.push(chunk))
+                    };
+
+                    if chunk.is_none() {
+                        assert_eq!(remaining, 0);
+                        break;
+                    }
+
+                    remaining -= 1;
+                }
+
+                back.reverse();
+                front.push_str(&back.concat());
+
+                assert_eq!(front, expected);
+            }
+        }
+    }
+}
+
+/// Tests that iterating over the lines and raw lines from both ends at once,
+/// in a random order, yields every line exactly once.
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_lines_from_both_ends() {
+    let mut rng = rand::thread_rng();
+
+    for s in ["", "a", "\n", "a\nb\r\nc", TINY, SMALL, MEDIUM, LARGE] {
+        let rope = Rope::from(s);
+
+        for _ in 0..10 {
+            let start = rng.gen_range(0..=rope.byte_len());
+            let end = rng.gen_range(start..=rope.byte_len());
+
+            let slice = rope.byte_slice(start..end);
+
+            let expected = s[start..end].lines().collect::<Vec<_>>();
+
+            let mut lines = slice.lines();
+            let mut front = Vec::new();
+            let mut back = Vec::new();
+
+            loop {
+                assert_eq!(
+                    lines.len(),
+                    expected.len() - front.len() - back.len()
+                );
+
+                let line = if rng.gen() {
+                    lines.next().map(|line| front.push(line))
+                } else {
+                    lines.next_back().map(|line| back.push(line))
+                };
+
+                if line.is_none() {
+                    break;
+                }
+            }
+
+            back.reverse();
+            front.extend(back);
+            assert_eq!(front, expected);
+
+            let expected =
+                s[start..end].split_inclusive('\n').collect::<Vec<_>>();
+
+            let mut raw_lines = slice.raw_lines();
+            let mut front = Vec::new();
+            let mut back = Vec::new();
+
+            loop {
+                let line = if rng.gen() {
+                    raw_lines.next().map(|line| front.push(line))
+                } else {
+                    raw_lines.next_back().map(|line| back.push(line))
+                };
+
+                if line.is_none() {
+                    break;
+                }
+            }
+
+            back.reverse();
+            front.extend(back);
+            assert_eq!(front, expected);
+        }
+    }
+}
+
+/// The rolling hashes of a string, each one computed from scratch.
+fn rolling_hashes(s: &str, window: usize) -> Vec<u64> {
+    s.as_bytes()
+        .windows(window)
+        .map(|bytes| {
+            bytes.iter().fold(0u64, |hash, &byte| {
+                hash.wrapping_mul(crop::iter::RollingHashes::BASE)
+                    .wrapping_add(byte as u64)
+            })
+        })
+        .collect()
+}
+
+#[test]
+fn iter_rolling_hashes_short() {
+    let r = Rope::from("abc");
+
+    assert_eq!(r.rolling_hashes(4).count(), 0);
+    assert_eq!(r.rolling_hashes(4).len(), 0);
+    assert_eq!(
+        r.rolling_hashes(3).collect::<Vec<_>>(),
+        rolling_hashes("abc", 3)
+    );
+    assert_eq!(Rope::new().rolling_hashes(1).next(), None);
+}
+
+#[test]
+#[should_panic]
+fn iter_rolling_hashes_zero_window() {
+    let _ = Rope::from("abc").rolling_hashes(0);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn iter_rolling_hashes_over_random_slices() {
+    let mut rng = thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..20 {
+            let start = rng.gen_range(0..=r.byte_len());
+            let end = rng.gen_range(start..=r.byte_len());
+            let window = rng.gen_range(1..=64);
+
+            if !s.is_char_boundary(start) || !s.is_char_boundary(end) {
+                continue;
+            }
+
+            let expected = rolling_hashes(&s[start..end], window);
+
+            let mut hashes = r.byte_slice(start..end).rolling_hashes(window);
+
+            assert_eq!(hashes.len(), expected.len());
+
+            for (idx, hash) in expected.into_iter().enumerate() {
+                assert_eq!(hashes.next(), Some(hash), "window at {idx}");
+                assert_eq!(hashes.len(), end - start - window - idx);
+            }
+
+            assert_eq!(hashes.next(), None);
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use crop::{LineEnding, Rope};
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE};
+
+fn convert(s: &str, line_ending: LineEnding) -> String {
+    s.replace("\r\n", "\n").replace('\n', line_ending.as_str())
+}
+
+#[test]
+fn write_to_with_line_ending_0() {
+    for s in ["", "\n", "\r", "\r\n", "a\r", "\r\r\n\n", CURSED_LIPSUM, LARGE]
+    {
+        let r = Rope::from(s);
+
+        for line_ending in [LineEnding::Lf, LineEnding::CrLf] {
+            let mut buf = Vec::new();
+            r.write_to_with_line_ending(&mut buf, line_ending).unwrap();
+            assert_eq!(
+                String::from_utf8(buf).unwrap(),
+                convert(s, line_ending)
+            );
+        }
+    }
+}
+
+/// Tests that `\r\n`s split across chunks are converted like any other line
+/// break, both in `Rope`s and in `RopeSlice`s.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn write_to_with_line_ending_random() {
+    let mut rng = rand::thread_rng();
+
+    let pieces = ["\r", "\n", "\r\n", "a", "🦀"];
+
+    for _ in 0..20 {
+        let mut s = String::new();
+        let mut r = Rope::new();
+
+        for _ in 0..2000 {
+            let mut offset = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(offset) {
+                offset -= 1;
+            }
+
+            let text = pieces[rng.gen_range(0..pieces.len())];
+
+            s.insert_str(offset, text);
+            r.insert(offset, text);
+        }
+
+        let mut start = rng.gen_range(0..=s.len());
+        while !s.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        for line_ending in [LineEnding::Lf, LineEnding::CrLf] {
+            let mut buf = Vec::new();
+            r.write_to_with_line_ending(&mut buf, line_ending).unwrap();
+            assert_eq!(
+                String::from_utf8(buf).unwrap(),
+                convert(&s, line_ending)
+            );
+
+            let mut buf = Vec::new();
+            r.byte_slice(start..)
+                .write_to_with_line_ending(&mut buf, line_ending)
+                .unwrap();
+            assert_eq!(
+                String::from_utf8(buf).unwrap(),
+                convert(&s[start..], line_ending)
+            );
+        }
+    }
+}
@@ -0,0 +1,127 @@
+use crop::tree::{LocalCounter, RefCounter};
+use crop::{LocalRope, Rope, RopeBuilder, RopeWith, Tracked, Versioned};
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+/// `Rope`s, and the slices borrowed from them, can still be sent to and
+/// shared with other threads.
+const _: () = {
+    const fn assert_send_sync<T: Send + Sync>() {}
+    assert_send_sync::<Rope>();
+    assert_send_sync::<crop::RopeSlice<'static>>();
+};
+
+/// Tests that random edits on a `LocalRope` give the same text as the same
+/// edits on a `String`, and that they don't affect its clones.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn local_rope_random_edits() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut r = LocalRope::from(s);
+        let mut string = s.to_owned();
+
+        for _ in 0..20 {
+            let snapshot = r.clone();
+            let before = string.clone();
+
+            let mut start = rng.gen_range(0..=string.len());
+            while !string.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=string.len().min(start + 100));
+            while !string.is_char_boundary(end) {
+                end += 1;
+            }
+
+            r.replace(start..end, "🐸 foo\n");
+            string.replace_range(start..end, "🐸 foo\n");
+
+            r.assert_invariants();
+            snapshot.assert_invariants();
+            assert_eq!(r, string);
+            assert_eq!(snapshot, before);
+        }
+    }
+}
+
+#[test]
+fn local_rope_builder() {
+    let mut builder = RopeBuilder::<LocalCounter>::default();
+
+    builder.append("Hello ").append("Earth!\n").append(TINY);
+
+    let r: LocalRope = builder.build();
+
+    r.assert_invariants();
+    assert_eq!(r, format!("Hello Earth!\n{TINY}"));
+}
+
+#[test]
+fn local_rope_slices_and_iterators() {
+    let r = LocalRope::from("foo\nbär\nbaz\n");
+    let s = r.byte_slice(4..);
+
+    assert_eq!(s, "bär\nbaz\n");
+    assert_eq!(s.byte_len(), 9);
+    assert_eq!(s.chars().rev().nth(1), Some('z'));
+    assert_eq!(r.chars().filter(|&ch| ch == 'a').count(), 1);
+    assert_eq!(r.find_all("ba"), [9]);
+}
+
+/// Tests that code generic over the reference counter works with both kinds
+/// of ropes.
+#[test]
+fn rope_with_generic_code() {
+    fn shout<C: RefCounter>(rope: &RopeWith<C>) -> RopeWith<C> {
+        let mut rope = rope.clone();
+        rope.insert(rope.byte_len(), "!");
+        rope
+    }
+
+    assert_eq!(shout(&Rope::from("foo")), "foo!");
+    assert_eq!(shout(&LocalRope::from("foo")), "foo!");
+}
+
+/// Tests that the wrappers and the trait impls of `Rope` also work with
+/// `LocalRope`s.
+#[test]
+fn local_rope_same_api() {
+    let mut r = LocalRope::from("Hello Earth!\n");
+
+    assert!(r.eq_ignore_ascii_case("hello earth!\n"));
+    assert!(r.eq_ignore_ascii_case(&LocalRope::from("HELLO EARTH!\n")));
+
+    let delta = r.diff(&LocalRope::from("Hello Mars!\n"));
+    let inverse = delta.invert(&r);
+    delta.apply(&mut r);
+    assert_eq!(r, "Hello Mars!\n");
+    inverse.apply(&mut r);
+    assert_eq!(r, "Hello Earth!\n");
+
+    let mut t = Tracked::new(r.clone());
+    t.replace(6..11, "Venus");
+    assert_eq!(t.rope(), "Hello Venus!\n");
+
+    let mut v = Versioned::new(r);
+    v.insert(0, "Oh, ");
+    assert_eq!(v.rope(), "Oh, Hello Earth!\n");
+    assert_eq!(v.at_revision(0).unwrap(), "Hello Earth!\n");
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn local_rope_serde() {
+    let r = LocalRope::from("Hello Earth!\n");
+
+    let json = serde_json::to_string(&r).unwrap();
+    assert_eq!(json, r#""Hello Earth!\n""#);
+
+    let de: LocalRope = serde_json::from_str(&json).unwrap();
+    assert_eq!(de, r);
+}
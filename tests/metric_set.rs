@@ -0,0 +1,177 @@
+use crop::tree::AtomicCounter;
+use crop::{
+    AllMetrics,
+    ChunkSummary,
+    LfBreaks,
+    Maintained,
+    MetricSet,
+    Omitted,
+    Rope,
+    RopeWith,
+    ZeroWeight,
+};
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, MEDIUM, SMALL, TINY};
+
+/// Only maintains the line breaks.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct LinesOnly;
+
+impl MetricSet for LinesOnly {
+    type Lines = Maintained;
+    type Chars = Omitted;
+    type Utf16CodeUnits = Omitted;
+    type Width = Omitted;
+}
+
+/// Doesn't maintain any of the optional components of the summaries.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct BytesOnly;
+
+impl MetricSet for BytesOnly {
+    type Lines = Omitted;
+    type Chars = Omitted;
+    type Utf16CodeUnits = Omitted;
+    type Width = Omitted;
+}
+
+/// Small chunks so that even the short texts are split between many
+/// leaves.
+type RopeOf<S> = RopeWith<AtomicCounter, ZeroWeight, LfBreaks, 16, 25, S>;
+
+/// Tests that random edits on ropes which don't maintain some of the
+/// metrics give the same text as the same edits on a `String`, and that the
+/// metrics they do maintain are the same as the ones of a `Rope`.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn metric_set_random_edits() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let mut lines = RopeOf::<LinesOnly>::from(s);
+        let mut bytes = RopeOf::<BytesOnly>::from(s);
+        let mut string = s.to_owned();
+
+        for _ in 0..20 {
+            let mut start = rng.gen_range(0..=string.len());
+            while !string.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=string.len());
+            while !string.is_char_boundary(end) {
+                end += 1;
+            }
+
+            lines.replace(start..end, "foo\r\nbär🐸");
+            bytes.replace(start..end, "foo\r\nbär🐸");
+            string.replace_range(start..end, "foo\r\nbär🐸");
+
+            lines.assert_invariants();
+            bytes.assert_invariants();
+            assert_eq!(lines, string.as_str());
+            assert_eq!(bytes, string.as_str());
+            assert_eq!(bytes.is_ascii(), string.is_ascii());
+
+            #[cfg(feature = "lines")]
+            {
+                let r = Rope::from(string.as_str());
+
+                assert_eq!(lines.line_len(), r.line_len());
+
+                let mut byte_offset = rng.gen_range(0..=string.len());
+                while !string.is_char_boundary(byte_offset) {
+                    byte_offset -= 1;
+                }
+                assert_eq!(
+                    lines.line_of_byte(byte_offset),
+                    r.line_of_byte(byte_offset)
+                );
+
+                let line_index = rng.gen_range(0..r.line_len());
+                assert_eq!(
+                    lines.line(line_index).to_string(),
+                    r.line(line_index).to_string()
+                );
+                assert!(lines
+                    .lines()
+                    .map(|line| line.to_string())
+                    .eq(r.lines().map(|line| line.to_string())));
+            }
+        }
+    }
+}
+
+/// The components of the summaries a rope doesn't maintain are left at
+/// zero.
+#[test]
+fn metric_set_skips_components() {
+    let r = RopeOf::<BytesOnly>::from(MEDIUM);
+    let report = r.check();
+    assert!(report.is_ok(), "{report}");
+    assert_eq!(report.stored_byte_len(), MEDIUM.len());
+
+    #[cfg(feature = "lines")]
+    assert_eq!(report.stored_line_breaks(), 0);
+
+    #[cfg(feature = "char-metric")]
+    assert_eq!(report.stored_char_len(), 0);
+}
+
+/// The methods which don't measure the text in the units of a metric work
+/// on ropes which don't maintain it.
+#[test]
+fn metric_set_iterate_and_format() {
+    let r = RopeOf::<BytesOnly>::from(CURSED_LIPSUM);
+    assert!(r.chars().eq(CURSED_LIPSUM.chars()));
+    assert!(r.chars().rev().eq(CURSED_LIPSUM.chars().rev()));
+
+    // Without the chars in the summaries the size hint is a bound computed
+    // from the bytes left instead of the exact count.
+    let char_len = CURSED_LIPSUM.chars().count();
+    let mut chars = r.chars();
+    let (lo, hi) = chars.size_hint();
+    assert!(lo <= char_len && char_len <= hi.unwrap());
+    chars.next();
+    chars.next_back();
+    let (lo, hi) = chars.size_hint();
+    assert!(lo <= char_len - 2 && char_len - 2 <= hi.unwrap());
+    assert_eq!(chars.count(), char_len - 2);
+
+    assert_eq!(r.to_string(), CURSED_LIPSUM);
+    assert_eq!(format!("{:>2000}", r), format!("{:>2000}", CURSED_LIPSUM));
+    assert_eq!(format!("{r:#?}"), format!("{:?}", Rope::from(CURSED_LIPSUM)));
+}
+
+/// The components a rope doesn't maintain take no room in its summaries.
+#[test]
+fn metric_set_summary_size() {
+    use core::mem::size_of;
+
+    type Summary<S> = ChunkSummary<ZeroWeight, LfBreaks, S>;
+
+    let all = size_of::<Summary<AllMetrics>>();
+    let lines_only = size_of::<Summary<LinesOnly>>();
+    let bytes_only = size_of::<Summary<BytesOnly>>();
+
+    let omits_some = cfg!(any(
+        feature = "char-metric",
+        feature = "utf16-metric",
+        feature = "width-metric"
+    ));
+
+    if omits_some {
+        assert!(lines_only < all);
+    } else {
+        assert_eq!(lines_only, all);
+    }
+
+    if cfg!(feature = "lines") {
+        assert!(bytes_only < lines_only);
+    } else {
+        assert_eq!(bytes_only, lines_only);
+    }
+}
@@ -0,0 +1,79 @@
+mod common;
+
+#[cfg(feature = "unicode-normalization")]
+mod tests {
+    use crop::{NormalizationForm, Rope};
+    use rand::Rng;
+    use unicode_normalization::UnicodeNormalization;
+
+    use crate::common::{CURSED_LIPSUM, MEDIUM, SMALL, TEXT_EMOJI};
+
+    const FORMS: [NormalizationForm; 4] = [
+        NormalizationForm::Nfc,
+        NormalizationForm::Nfd,
+        NormalizationForm::Nfkc,
+        NormalizationForm::Nfkd,
+    ];
+
+    fn normalize(s: &str, form: NormalizationForm) -> String {
+        match form {
+            NormalizationForm::Nfc => s.nfc().collect(),
+            NormalizationForm::Nfd => s.nfd().collect(),
+            NormalizationForm::Nfkc => s.nfkc().collect(),
+            NormalizationForm::Nfkd => s.nfkd().collect(),
+        }
+    }
+
+    #[test]
+    fn normalize_empty() {
+        let r = Rope::new();
+
+        for form in FORMS {
+            assert!(r.is_normalized(form));
+            assert_eq!(r.normalize(form), "");
+        }
+    }
+
+    #[test]
+    fn normalize_matches_str() {
+        for s in [SMALL, MEDIUM, CURSED_LIPSUM, TEXT_EMOJI] {
+            let r = Rope::from(s);
+
+            for form in FORMS {
+                let normalized = r.normalize(form);
+                normalized.assert_invariants();
+                assert_eq!(normalized, normalize(s, form));
+                assert!(normalized.is_normalized(form));
+                assert_eq!(r.is_normalized(form), normalize(s, form) == s);
+            }
+        }
+    }
+
+    /// Tests that combining sequences are normalized correctly when they're
+    /// split across chunks.
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn combining_sequences_random() {
+        let mut rng = rand::thread_rng();
+
+        let pieces =
+            ["e", "\u{301}", "\u{323}", "\u{302}", "A", "\u{30a}", "ﬁ", "한"];
+
+        let mut r = Rope::new();
+        let mut s = String::new();
+
+        for _ in 0..1000 {
+            let piece = pieces[rng.gen_range(0..pieces.len())];
+            r.insert(r.byte_len(), piece);
+            s.push_str(piece);
+        }
+
+        for form in FORMS {
+            assert_eq!(r.normalize(form), normalize(&s, form));
+            assert_eq!(r.is_normalized(form), normalize(&s, form) == s);
+
+            let normalized = r.normalize(form);
+            assert!(normalized.is_normalized(form));
+        }
+    }
+}
@@ -0,0 +1,235 @@
+use std::collections::BTreeSet;
+
+use crop::Rope;
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+#[test]
+fn ord_0() {
+    let a = Rope::from("abc");
+    let b = Rope::from("abd");
+    let c = Rope::from("ab");
+
+    assert!(a < b);
+    assert!(c < a);
+    assert!(a.byte_slice(..2) == c);
+    assert!(a.byte_slice(..2) < a);
+    assert!(b > a.byte_slice(..));
+    assert!(Rope::new() < c);
+
+    // The lazily decompressed bytes of the `compression` feature don't
+    // affect the ordering.
+    #[allow(clippy::mutable_key_type)]
+    let set =
+        [b.clone(), a.clone(), c.clone()].into_iter().collect::<BTreeSet<_>>();
+    assert_eq!(set.into_iter().collect::<Vec<_>>(), [c, a, b]);
+}
+
+/// Tests that comparing a `Rope` with an edited clone of itself, which shares
+/// most of its nodes, only skips the text that's actually shared.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn eq_edited_clone() {
+    let mut rng = rand::thread_rng();
+
+    let r = Rope::from(LARGE);
+
+    assert_eq!(r, r.clone());
+
+    for _ in 0..100 {
+        let mut s = LARGE.to_owned();
+        let mut edited = r.clone();
+
+        let start = rng.gen_range(0..s.len());
+        let end = rng.gen_range(start..=s.len().min(start + 10));
+        let text = ["", "a", "Lorem", "\n"][rng.gen_range(0..4)];
+
+        s.replace_range(start..end, text);
+        edited.replace(start..end, text);
+
+        assert_eq!(edited == r, s == LARGE);
+        assert_eq!(r == edited, s == LARGE);
+
+        // Undoing the edit gives back the same text in different nodes.
+        edited.replace(start..start + text.len(), &LARGE[start..end]);
+
+        assert_eq!(edited, r);
+        assert_eq!(r, edited);
+    }
+}
+
+#[test]
+fn eq_bytes() {
+    let r = Rope::from("Hello Earth 🌎!");
+
+    assert_eq!(r, "Hello Earth 🌎!".as_bytes());
+    assert_eq!(*"Hello Earth 🌎!".as_bytes(), r);
+    assert_eq!(r, b"Hello Earth \xF0\x9F\x8C\x8E!".to_vec());
+    assert_eq!(b"Hello Earth \xF0\x9F\x8C\x8E!".to_vec(), r);
+    assert_eq!(&b"Earth"[..], r.byte_slice(6..11));
+    assert_eq!(r.byte_slice(6..11), b"Earth".to_vec());
+
+    assert_ne!(r, &b"Hello Earth!"[..]);
+    assert_ne!(r.byte_slice(12..16), &b"\xF0\x9F\x8C\x8F"[..]);
+    assert_ne!(Vec::<u8>::new(), r);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn eq_bytes_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        assert_eq!(r, s.as_bytes());
+
+        for _ in 0..10 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=s.len());
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+            let mut bytes = s.as_bytes()[start..end].to_vec();
+
+            assert_eq!(slice, bytes);
+
+            // Flipping any bit makes them different, even if the bytes are
+            // no longer valid UTF-8.
+            if !bytes.is_empty() {
+                let idx = rng.gen_range(0..bytes.len());
+                bytes[idx] ^= 1 << rng.gen_range(0..8);
+                assert_ne!(slice, bytes);
+            }
+        }
+    }
+}
+
+/// Tests that comparing `Rope`s and `RopeSlice`s gives the same result as
+/// comparing the corresponding `&str`s.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn ord_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for _ in 0..100 {
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start += 1;
+            }
+
+            let mut end = rng.gen_range(start..=s.len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let str_slice = &s[start..end];
+            let rope_slice = r.byte_slice(start..end);
+
+            assert_eq!(rope_slice.cmp(&r.byte_slice(..)), str_slice.cmp(s));
+            assert_eq!(rope_slice.partial_cmp(&r), str_slice.partial_cmp(s));
+
+            let other = Rope::from(str_slice);
+            assert_eq!(r.cmp(&other), s.cmp(str_slice));
+            assert_eq!(
+                other.partial_cmp(&rope_slice),
+                Some(core::cmp::Ordering::Equal)
+            );
+        }
+    }
+}
+
+/// Tests that the fingerprint only depends on the text, whatever edits led
+/// to it, so that `Rope`s built separately are still compared correctly.
+#[cfg(feature = "fingerprint")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn fingerprint_after_random_edits() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+        let mut s = s.to_owned();
+
+        for _ in 0..100 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(start) {
+                start -= 1;
+            }
+            let mut end = (start + rng.gen_range(0..300)).min(r.byte_len());
+            while !r.is_char_boundary(end) {
+                end -= 1;
+            }
+            let text = ["", "a", "Lorem", "\n", "🐻‍❄️"][rng.gen_range(0..5)];
+
+            r.replace(start..end, text);
+            s.replace_range(start..end, text);
+
+            let built = Rope::from(s.as_str());
+            assert_eq!(r.fingerprint(), built.fingerprint());
+            assert_eq!(r, built);
+        }
+
+        r.assert_invariants();
+
+        let mut other = r.clone();
+        other.insert(0, "a");
+        other.delete(..1);
+        assert_eq!(r.fingerprint(), other.fingerprint());
+
+        // Same length, different text. The edits could have deleted the
+        // whole text.
+        if let Some(ascii) = r.bytes().position(|b| b.is_ascii() && b != 0) {
+            other.replace(ascii..ascii + 1, "\0");
+            assert_ne!(r.fingerprint(), other.fingerprint());
+            assert_ne!(r, other);
+        }
+    }
+}
+
+/// Tests that a `RopeSlice` has the same fingerprint as a `Rope` with the
+/// same text, wherever it starts and ends in the chunks of its `Rope`.
+#[cfg(feature = "fingerprint")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn fingerprint_of_slices() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        assert_eq!(r.byte_slice(..).fingerprint(), r.fingerprint());
+
+        for _ in 0..100 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(start) {
+                start -= 1;
+            }
+            let mut end = rng.gen_range(start..=r.byte_len());
+            while !r.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+            let built = Rope::from(&s[start..end]);
+
+            assert_eq!(slice.fingerprint(), built.fingerprint());
+            assert_eq!(
+                slice.fingerprint(),
+                built.byte_slice(..).fingerprint()
+            );
+        }
+    }
+}
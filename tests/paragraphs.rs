@@ -0,0 +1,200 @@
+#![cfg(feature = "paragraph-count")]
+
+use core::ops::Range;
+
+use crop::Rope;
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+/// The byte ranges of the paragraphs of a string, computed from its lines.
+fn paragraphs(s: &str) -> Vec<Range<usize>> {
+    let mut paragraphs = Vec::new();
+    let mut start = None;
+    let mut end = 0;
+    let mut offset = 0;
+
+    for raw_line in s.split_inclusive('\n') {
+        let line = match raw_line.strip_suffix('\n') {
+            Some(line) => line.strip_suffix('\r').unwrap_or(line),
+            None => raw_line,
+        };
+
+        if line.trim().is_empty() {
+            if let Some(start) = start.take() {
+                paragraphs.push(start..end);
+            }
+        } else {
+            start.get_or_insert(offset);
+            end = offset + line.len();
+        }
+
+        offset += raw_line.len();
+    }
+
+    if let Some(start) = start {
+        paragraphs.push(start..end);
+    }
+
+    paragraphs
+}
+
+/// Checks the paragraphs of the `Rope` against the ones of the string.
+#[track_caller]
+fn check(r: &Rope, s: &str) {
+    let expected = paragraphs(s);
+
+    assert_eq!(r.paragraph_len(), expected.len());
+    assert_eq!(r.byte_of_paragraph(expected.len()), r.byte_len());
+
+    for (idx, range) in expected.iter().enumerate() {
+        assert_eq!(r.byte_of_paragraph(idx), range.start);
+        assert_eq!(r.paragraph_slice(idx..idx + 1), s[range.clone()]);
+    }
+
+    if let (Some(first), Some(last)) = (expected.first(), expected.last()) {
+        assert_eq!(r.paragraph_slice(..), s[first.start..last.end]);
+    }
+}
+
+#[test]
+fn paragraph_len_empty() {
+    assert_eq!(Rope::new().paragraph_len(), 0);
+    assert_eq!(Rope::from(" \n\t\r\n\n").paragraph_len(), 0);
+
+    let r = Rope::new();
+    assert_eq!(r.byte_of_paragraph(0), 0);
+    assert_eq!(r.paragraph_slice(..), "");
+}
+
+#[test]
+fn paragraphs_0() {
+    let s = "\n  foo\nbar\r\n\r\n \t \nbaz\n  qux  \n\n";
+    let r = Rope::from(s);
+
+    assert_eq!(r.paragraph_len(), 2);
+    assert_eq!(r.byte_of_paragraph(0), 1);
+    assert_eq!(r.byte_of_paragraph(1), "\n  foo\nbar\r\n\r\n \t \n".len());
+    assert_eq!(r.paragraph_slice(0..1), "  foo\nbar");
+    assert_eq!(r.paragraph_slice(1..2), "baz\n  qux  ");
+    assert_eq!(r.paragraph_slice(1..1), "");
+
+    check(&r, s);
+}
+
+/// The last paragraph is followed by a line that's blank but doesn't end
+/// with a line break.
+#[test]
+fn paragraphs_blank_last_line() {
+    for s in ["foo\n  ", "foo\r\n\t", "foo\nbar\n \r"] {
+        check(&Rope::from(s), s);
+    }
+}
+
+#[test]
+#[should_panic(expected = "paragraph offset out of bounds")]
+fn byte_of_paragraph_out_of_bounds() {
+    Rope::from("foo\n\nbar").byte_of_paragraph(3);
+}
+
+#[test]
+#[should_panic(expected = "paragraph start after end")]
+#[allow(clippy::reversed_empty_ranges)]
+fn paragraph_slice_start_after_end() {
+    Rope::from("foo\n\nbar").paragraph_slice(2..1);
+}
+
+/// Tests that the paragraphs are the same as the ones yielded by the
+/// iterator, whose paragraphs can be spread across many chunks.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn paragraphs_same_as_iterator() {
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        assert_eq!(r.paragraph_len(), r.paragraphs().count());
+
+        for (idx, paragraph) in r.paragraphs().enumerate() {
+            assert_eq!(r.paragraph_slice(idx..idx + 1), paragraph);
+        }
+
+        check(&r, s);
+    }
+}
+
+/// Tests that the paragraphs of a `RopeSlice` are the ones of a `Rope`
+/// holding the same text, including when the slice starts or ends in the
+/// middle of a paragraph or of a blank line.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn paragraph_len_of_slices() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        assert_eq!(r.byte_slice(..).paragraph_len(), r.paragraph_len());
+
+        for _ in 0..100 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(start) {
+                start -= 1;
+            }
+            let mut end = rng.gen_range(start..=r.byte_len());
+            while !r.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            let slice = r.byte_slice(start..end);
+            let expected = Rope::from(&s[start..end]).paragraph_len();
+
+            assert_eq!(slice.paragraph_len(), expected);
+            assert_eq!(slice.paragraphs().count(), expected);
+        }
+    }
+}
+
+/// Tests that the paragraphs are kept up to date while editing, including
+/// when paragraphs are joined or split at the edges of the edits.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn paragraphs_after_random_edits() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+        let mut s = s.to_owned();
+
+        for _ in 0..100 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(start) {
+                start -= 1;
+            }
+            let mut end = (start + rng.gen_range(0..300)).min(r.byte_len());
+            while !r.is_char_boundary(end) {
+                end -= 1;
+            }
+            let text = ["\n", "\n\n", " \r\n", "a", "", "b\n \nc"]
+                [rng.gen_range(0..6)];
+
+            r.replace(start..end, text);
+            s.replace_range(start..end, text);
+
+            let expected = paragraphs(&s);
+
+            assert_eq!(r.paragraph_len(), expected.len());
+
+            for _ in 0..expected.len().min(3) {
+                let idx = rng.gen_range(0..expected.len());
+                let range = expected[idx].clone();
+                assert_eq!(r.byte_of_paragraph(idx), range.start);
+                assert_eq!(r.paragraph_slice(idx..idx + 1), s[range]);
+            }
+        }
+
+        check(&r, &s);
+        r.assert_invariants();
+    }
+}
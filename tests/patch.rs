@@ -0,0 +1,285 @@
+#![cfg(feature = "lines")]
+
+use crop::patch::{Patch, PatchError};
+use crop::Rope;
+use rand::Rng;
+
+#[test]
+fn patch_apply_and_unapply() {
+    let patch = "\
+diff --git a/lib.rs b/lib.rs
+index 0123456..789abcd 100644
+--- a/lib.rs
++++ b/lib.rs
+@@ -1,4 +1,4 @@
+ fn main() {
+-    println!(\"Hello Earth\");
++    println!(\"Hello Saturn\");
+ }
+ 
+@@ -7,2 +7,3 @@ fn other() {
+ fn other() {
++    todo!()
+ }
+"
+    .parse::<Patch>()
+    .unwrap();
+
+    let old = "fn main() {\n    println!(\"Hello Earth\");\n}\n\n// \
+               Other.\n\nfn other() {\n}\n";
+
+    let new = "fn main() {\n    println!(\"Hello Saturn\");\n}\n\n// \
+               Other.\n\nfn other() {\n    todo!()\n}\n";
+
+    let mut r = Rope::from(old);
+
+    let delta = patch.apply(&mut r).unwrap();
+    assert_eq!(r, new);
+    assert_eq!(delta.base_len(), old.len());
+    assert_eq!(delta.target_len(), new.len());
+
+    assert_eq!(
+        patch.apply(&mut r),
+        Err(PatchError::ContextMismatch { hunk: 0, line: 1 })
+    );
+    assert_eq!(r, new);
+
+    patch.unapply(&mut r).unwrap();
+    assert_eq!(r, old);
+}
+
+#[test]
+fn patch_no_newline_at_end_of_file() {
+    let patch = "\
+@@ -1,2 +1,2 @@
+ a
+-b
+\\ No newline at end of file
++b
+"
+    .parse::<Patch>()
+    .unwrap();
+
+    let mut r = Rope::from("a\nb");
+    patch.apply(&mut r).unwrap();
+    assert_eq!(r, "a\nb\n");
+
+    patch.unapply(&mut r).unwrap();
+    assert_eq!(r, "a\nb");
+
+    // The line without a newline has to be the last one.
+    let mut r = Rope::from("a\nbc");
+    assert_eq!(
+        patch.apply(&mut r),
+        Err(PatchError::ContextMismatch { hunk: 0, line: 1 })
+    );
+}
+
+/// Tests that a "no newline" marker is rejected unless it follows the last
+/// line of the old or new text of its hunk.
+#[test]
+fn patch_no_newline_marker_misplaced() {
+    for (patch, line) in [
+        ("@@ -1 +1 @@\n\\ No newline at end of file\n a\n", 1),
+        ("@@ -1,2 +1,2 @@\n a\n\\ No newline at end of file\n b\n", 2),
+        ("@@ -2 +2,2 @@\n a\n\\ No newline at end of file\n+b\n", 2),
+        ("@@ -1,2 +1 @@\n-a\n\\ No newline at end of file\n-b\n+c\n", 2),
+        ("@@ -0,0 +1,2 @@\n+a\n\\ No newline at end of file\n+b\n", 2),
+        ("@@ -1 +1 @@\n a\n\\ No newline at end of file\n\\ Again\n", 3),
+    ] {
+        assert_eq!(
+            patch.parse::<Patch>(),
+            Err(PatchError::Malformed { line }),
+            "{patch:?}"
+        );
+    }
+
+    // The marker can follow the last removed or added line even if lines of
+    // the other text come after it.
+    assert!("@@ -1 +1,2 @@\n-a\n\\ No newline at end of file\n+a\n+b\n"
+        .parse::<Patch>()
+        .is_ok());
+
+    assert!("@@ -1 +1 @@\n+b\n\\ No newline at end of file\n-a\n"
+        .parse::<Patch>()
+        .is_ok());
+}
+
+/// Tests that a hunk header is rejected if one of its ranges starts at line
+/// 0 without being empty.
+#[test]
+fn patch_zero_start_not_empty() {
+    for header in [
+        "@@ -0,1 +1 @@\n-a\n+a\n",
+        "@@ -1 +0,1 @@\n-a\n+a\n",
+        "@@ -0 +1 @@\n-a\n+a\n",
+    ] {
+        assert_eq!(
+            header.parse::<Patch>(),
+            Err(PatchError::Malformed { line: 0 }),
+            "{header:?}"
+        );
+    }
+
+    assert!("@@ -0,0 +1 @@\n+a\n".parse::<Patch>().is_ok());
+    assert!("@@ -1 +0,0 @@\n-a\n".parse::<Patch>().is_ok());
+}
+
+#[test]
+fn patch_insert_into_empty() {
+    let patch = "@@ -0,0 +1,2 @@\n+foo\n+bar\n".parse::<Patch>().unwrap();
+
+    let mut r = Rope::new();
+    patch.apply(&mut r).unwrap();
+    assert_eq!(r, "foo\nbar\n");
+
+    patch.unapply(&mut r).unwrap();
+    assert_eq!(r, "");
+}
+
+#[test]
+fn patch_malformed() {
+    assert!("".parse::<Patch>().unwrap().is_empty());
+    assert!("--- a\n+++ b\n".parse::<Patch>().unwrap().is_empty());
+
+    assert_eq!(
+        "@@ -1,x +1 @@\n".parse::<Patch>(),
+        Err(PatchError::Malformed { line: 0 })
+    );
+
+    // Fewer lines than announced.
+    assert_eq!(
+        "--- a\n+++ b\n@@ -1,2 +1,2 @@\n a\n".parse::<Patch>(),
+        Err(PatchError::Malformed { line: 4 })
+    );
+
+    // An unknown marker.
+    assert_eq!(
+        "@@ -1 +1 @@\n*a\n".parse::<Patch>(),
+        Err(PatchError::Malformed { line: 1 })
+    );
+
+    let overlapping = "@@ -1,2 +1,2 @@\n a\n-b\n+c\n@@ -2 +2 @@\n-b\n+d\n"
+        .parse::<Patch>()
+        .unwrap();
+
+    assert_eq!(
+        overlapping.apply(&mut Rope::from("a\nb\n")),
+        Err(PatchError::OverlappingHunks { hunk: 1 })
+    );
+}
+
+/// Returns the unified diff of the hunks which replace the lines of `old` in
+/// each of the ranges with the given lines, using up to 3 lines of context.
+fn unified_diff(
+    old: &[String],
+    edits: &[(std::ops::Range<usize>, Vec<String>)],
+) -> String {
+    let mut diff = String::from("--- a\n+++ b\n");
+
+    let mut shift = 0isize;
+
+    for (range, new_lines) in edits {
+        let before = range.start.saturating_sub(3)..range.start;
+        let after = range.end..(range.end + 3).min(old.len());
+
+        let old_len = before.len() + range.len() + after.len();
+        let new_len = before.len() + new_lines.len() + after.len();
+
+        let old_start =
+            if old_len == 0 { before.start } else { before.start + 1 };
+        let new_start = if new_len == 0 {
+            (before.start as isize + shift) as usize
+        } else {
+            (before.start as isize + shift) as usize + 1
+        };
+
+        diff.push_str(&format!(
+            "@@ -{old_start},{old_len} +{new_start},{new_len} @@\n"
+        ));
+
+        for line in &old[before] {
+            diff.push_str(&format!(" {line}\n"));
+        }
+        for line in &old[range.clone()] {
+            diff.push_str(&format!("-{line}\n"));
+        }
+        for line in new_lines {
+            diff.push_str(&format!("+{line}\n"));
+        }
+        for line in &old[after] {
+            diff.push_str(&format!(" {line}\n"));
+        }
+
+        shift += new_lines.len() as isize - range.len() as isize;
+    }
+
+    diff
+}
+
+/// Tests that applying a patch with random non-overlapping hunks gives the
+/// same text as performing the edits on a `String`, and that unapplying it
+/// gives back the original text.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn patch_random() {
+    let mut rng = rand::thread_rng();
+
+    let words = ["foo", "bär", "🦀", "", "baz qux"];
+
+    for _ in 0..100 {
+        let old = (0..rng.gen_range(0..60))
+            .map(|_| words[rng.gen_range(0..words.len())].to_owned())
+            .collect::<Vec<_>>();
+
+        let mut edits = Vec::new();
+
+        // Keep the hunks at least 7 lines apart so that their contexts
+        // don't overlap.
+        let mut start = 0;
+
+        while start <= old.len() && edits.len() < 5 {
+            let edit_start = rng.gen_range(start..=old.len());
+            let edit_end =
+                rng.gen_range(edit_start..=old.len().min(edit_start + 3));
+
+            let new_lines = (0..rng.gen_range(0..3))
+                .map(|_| words[rng.gen_range(0..words.len())].to_owned())
+                .collect::<Vec<_>>();
+
+            if edit_start == edit_end && new_lines.is_empty() {
+                break;
+            }
+
+            edits.push((edit_start..edit_end, new_lines));
+
+            start = edit_end + 7;
+        }
+
+        let mut new = Vec::new();
+        let mut last = 0;
+        for (range, new_lines) in &edits {
+            new.extend_from_slice(&old[last..range.start]);
+            new.extend_from_slice(new_lines);
+            last = range.end;
+        }
+        new.extend_from_slice(&old[last..]);
+
+        let to_text = |lines: &[String]| {
+            lines.iter().map(|l| format!("{l}\n")).collect::<String>()
+        };
+
+        let patch = unified_diff(&old, &edits).parse::<Patch>().unwrap();
+
+        let mut r = Rope::from(to_text(&old));
+
+        let delta = patch.delta(&r).unwrap();
+        assert_eq!(delta.target_len(), to_text(&new).len());
+
+        patch.apply(&mut r).unwrap();
+        assert_eq!(r, to_text(&new));
+
+        patch.unapply(&mut r).unwrap();
+        assert_eq!(r, to_text(&old));
+    }
+}
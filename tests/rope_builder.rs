@@ -1,7 +1,8 @@
 mod common;
 
-use common::LARGE;
-use crop::{Rope, RopeBuilder};
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL};
+use crop::{LineEnding, Rope, RopeBuilder};
+use rand::Rng;
 
 #[test]
 fn builder_empty() {
@@ -34,6 +35,61 @@ fn builder_0() {
     assert_eq!(s, b.build());
 }
 
+/// Tests that a builder which knows the final length of the text splits it
+/// in the same chunks as `Rope::from()`.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn builder_with_capacity() {
+    // Cut the texts at different lengths to get every size of trailing
+    // chunk.
+    let texts = (0..64)
+        .map(|i| {
+            let mut len = MEDIUM.len() - i * 37;
+            while !MEDIUM.is_char_boundary(len) {
+                len -= 1;
+            }
+            &MEDIUM[..len]
+        })
+        .chain([SMALL, LARGE, CURSED_LIPSUM]);
+
+    for text in texts {
+        let mut b = RopeBuilder::with_capacity(text.len());
+
+        for word in text.split_inclusive(' ') {
+            b.append(word);
+        }
+
+        let r = b.build();
+        r.assert_invariants();
+        assert_eq!(r, text);
+
+        let chunk_lens = |r: &Rope| {
+            r.chunk_segments()
+                .map(|(left, right)| left.len() + right.len())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(chunk_lens(&r), chunk_lens(&Rope::from(text)));
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn builder_wrong_capacity() {
+    for capacity in [0, 1, MEDIUM.len() / 2, MEDIUM.len() * 2] {
+        let mut b = RopeBuilder::with_capacity(capacity);
+
+        for line in MEDIUM.split_inclusive('\n') {
+            b.append(line);
+        }
+
+        b.reserve(10).append("Hello Earth!");
+
+        let r = b.build();
+        r.assert_invariants();
+        assert_eq!(r, format!("{MEDIUM}Hello Earth!"));
+    }
+}
+
 #[test]
 fn builder_crlf_0() {
     let mut b = RopeBuilder::new();
@@ -51,3 +107,119 @@ fn builder_crlf_1() {
     r.assert_invariants();
     assert_eq!(r, "aaa\r\nbbb");
 }
+
+#[test]
+fn builder_normalize_line_endings() {
+    let tests = [
+        ("", "", None),
+        ("aaa", "aaa", None),
+        ("aaa\nbbb", "aaa\nbbb", Some(LineEnding::Lf)),
+        ("aaa\r\nbbb\r\n", "aaa\nbbb\n", Some(LineEnding::CrLf)),
+        ("aaa\rbbb\r", "aaa\nbbb\n", Some(LineEnding::Cr)),
+        ("\r\r\n\n", "\n\n\n", Some(LineEnding::Lf)),
+        ("a\r\nb\rc\r\n", "a\nb\nc\n", Some(LineEnding::CrLf)),
+    ];
+
+    for (text, normalized, line_ending) in tests {
+        let mut b = RopeBuilder::new();
+        b.normalize_line_endings(true).append(text).append("");
+        let r = b.build();
+        r.assert_invariants();
+        assert_eq!(r, normalized);
+
+        // Appending the text one byte at a time splits every `\r\n`.
+        let mut b = RopeBuilder::new();
+        b.normalize_line_endings(true);
+        for byte in text.split_inclusive(|_| true) {
+            b.append(byte).append("");
+        }
+        b.append("x");
+        assert_eq!(b.line_ending(), line_ending, "{text:?}");
+        assert_eq!(b.build(), format!("{normalized}x"));
+    }
+}
+
+#[test]
+fn builder_normalize_line_endings_toggle() {
+    let mut b = RopeBuilder::new();
+    b.normalize_line_endings(true).append("aaa\r");
+    b.normalize_line_endings(false).append("\nbbb\r\n");
+    assert_eq!(b.line_ending(), Some(LineEnding::Cr));
+    assert_eq!(b.build(), "aaa\n\nbbb\r\n");
+}
+
+/// Tests that normalizing the line endings of text appended in random
+/// pieces gives the same result as normalizing the whole text at once.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn builder_normalize_line_endings_random() {
+    let mut rng = rand::thread_rng();
+
+    let pieces = ["\r", "\n", "\r\n", "a", "🦀", CURSED_LIPSUM];
+
+    for _ in 0..20 {
+        let mut s = String::new();
+
+        for _ in 0..2000 {
+            s.push_str(pieces[rng.gen_range(0..pieces.len())]);
+        }
+
+        let mut b = RopeBuilder::new();
+        b.normalize_line_endings(true);
+
+        let mut rest = s.as_str();
+
+        while !rest.is_empty() {
+            let mut len = rng.gen_range(0..=rest.len().min(64));
+            while !rest.is_char_boundary(len) {
+                len -= 1;
+            }
+            b.append(&rest[..len]);
+            rest = &rest[len..];
+        }
+
+        let r = b.build();
+        r.assert_invariants();
+        assert_eq!(r, s.replace("\r\n", "\n").replace('\r', "\n"));
+    }
+}
+
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn from_iter_0() {
+    let r = LARGE.split_inclusive(' ').collect::<Rope>();
+    r.assert_invariants();
+    assert_eq!(r, LARGE);
+
+    let r = LARGE.lines().map(|line| format!("{line}\n")).collect::<Rope>();
+    r.assert_invariants();
+    assert_eq!(r.line_len(), LARGE.lines().count());
+    assert_eq!(
+        r.lines().collect::<Vec<_>>(),
+        LARGE.lines().collect::<Vec<_>>()
+    );
+
+    let r = LARGE.chars().collect::<Rope>();
+    r.assert_invariants();
+    assert_eq!(r, LARGE);
+
+    let r = LARGE.split_inclusive('\n').map(Rope::from).collect::<Rope>();
+    r.assert_invariants();
+    assert_eq!(r, LARGE);
+}
+
+#[test]
+fn from_iter_empty() {
+    let r = core::iter::empty::<&str>().collect::<Rope>();
+    assert!(r.is_empty());
+
+    let r = ["", "", ""].into_iter().collect::<Rope>();
+    assert!(r.is_empty());
+
+    let r = [Rope::new(), Rope::from("a🐸"), Rope::new()]
+        .into_iter()
+        .collect::<Rope>();
+    r.assert_invariants();
+    assert_eq!(r, "a🐸");
+}
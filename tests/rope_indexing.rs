@@ -1,4 +1,5 @@
 use crop::Rope;
+use rand::Rng;
 
 mod common;
 
@@ -35,6 +36,122 @@ fn rope_is_char_boundary() {
     }
 }
 
+#[cfg(feature = "char-metric")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_char() {
+    for s in ["", "Hi", "🐕‍🦺", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM]
+    {
+        let r = Rope::from(s);
+
+        assert_eq!(r.char_len(), s.chars().count());
+
+        for (char_idx, (byte_idx, ch)) in
+            s.char_indices().enumerate().step_by(7)
+        {
+            assert_eq!(r.char(char_idx), ch);
+            assert_eq!(r.char_at_byte(byte_idx), ch);
+        }
+    }
+}
+
+/// Tests `char()` and `char_at_byte()` after random edits, which leave the
+/// gap of the edited chunks in the middle of their text.
+#[cfg(feature = "char-metric")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_char_random_edits() {
+    let mut rng = rand::thread_rng();
+
+    let mut s = String::from(MEDIUM);
+    let mut r = Rope::from(MEDIUM);
+
+    for _ in 0..100 {
+        let mut offset = rng.gen_range(0..=s.len());
+        while !s.is_char_boundary(offset) {
+            offset -= 1;
+        }
+
+        let text = ["a", "é", "\r\n", "🦀"][rng.gen_range(0..4)];
+        s.insert_str(offset, text);
+        r.insert(offset, text);
+    }
+
+    assert_eq!(r.char_len(), s.chars().count());
+
+    for (char_idx, (byte_idx, ch)) in s.char_indices().enumerate().step_by(7) {
+        assert_eq!(r.char(char_idx), ch);
+        assert_eq!(r.char_at_byte(byte_idx), ch);
+    }
+}
+
+#[cfg(feature = "char-metric")]
+#[test]
+#[should_panic]
+fn rope_char_out_of_bounds() {
+    let r = Rope::from("añ");
+    let _ = r.char(2);
+}
+
+#[test]
+#[should_panic]
+fn rope_char_at_byte_not_char_boundary() {
+    let r = Rope::from("añ");
+    let _ = r.char_at_byte(2);
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_is_ascii() {
+    for s in ["", "Hi", "🐕‍🦺", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM]
+    {
+        let r = Rope::from(s);
+        assert_eq!(r.is_ascii(), s.is_ascii());
+        assert_eq!(r.byte_slice(..).is_ascii(), s.is_ascii());
+    }
+}
+
+/// Tests that `is_ascii()` stays in sync with the text of the `Rope` and of
+/// its slices as non-ASCII characters are inserted and deleted.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_is_ascii_random_edits() {
+    let mut rng = rand::thread_rng();
+
+    let mut s = String::from(MEDIUM);
+    let mut r = Rope::from(MEDIUM);
+
+    for _ in 0..200 {
+        let mut start = rng.gen_range(0..=s.len());
+        while !s.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        if rng.gen_bool(0.5) {
+            let text = ["a", "é", "\r\n", "🦀"][rng.gen_range(0..4)];
+            s.insert_str(start, text);
+            r.insert(start, text);
+        } else {
+            let mut end = rng.gen_range(start..=s.len().min(start + 50));
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+            s.replace_range(start..end, "");
+            r.delete(start..end);
+        }
+
+        assert_eq!(r.is_ascii(), s.is_ascii());
+
+        let mut start = rng.gen_range(0..=s.len());
+        while !s.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        assert_eq!(r.byte_slice(start..).is_ascii(), s[start..].is_ascii());
+        assert_eq!(r.byte_slice(..start).is_ascii(), s[..start].is_ascii());
+    }
+}
+
 /// ```
 /// Root
 /// ├───┐
@@ -46,6 +163,7 @@ fn rope_is_char_boundary() {
 ///     ├── "ee\ne"
 ///     └── "fff\n"
 /// ```
+#[cfg(feature = "lines")]
 #[test]
 fn rope_line_0() {
     let r = Rope::from("aaa\nbbb\nccc\nddd\nee\nefff\n");
@@ -64,6 +182,7 @@ fn rope_line_0() {
 ///     ├── "bbbb"
 ///     └── "\nccc"
 /// ```
+#[cfg(feature = "lines")]
 #[test]
 fn rope_line_1() {
     let r = Rope::from("aaaaaaaaaaaaaaa\nbbbb\nccc");
@@ -82,6 +201,7 @@ fn rope_line_1() {
 ///     ├── "\nbbb"
 ///     └── "bbbb"
 /// ```
+#[cfg(feature = "lines")]
 #[test]
 fn rope_line_2() {
     let r = Rope::from("aaaaaaaaaaaaaaa\n\nbbbbbbb");
@@ -93,9 +213,276 @@ fn rope_line_2() {
 /// Root
 /// └── "\n\n\n\n"
 /// ```
+#[cfg(feature = "lines")]
 #[test]
 fn rope_line_3() {
     let r = Rope::from("\n\n\n\n");
     let l = r.line(2);
     assert_eq!("", l);
 }
+
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_raw_line_0() {
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+        let slice = r.byte_slice(..);
+
+        for (line_index, str_line) in s.split_inclusive('\n').enumerate() {
+            let line = r.raw_line(line_index);
+            line.assert_invariants();
+            assert_eq!(line, str_line);
+            assert_eq!(line, r.line_slice(line_index..line_index + 1));
+            assert_eq!(slice.raw_line(line_index), str_line);
+        }
+    }
+}
+
+#[cfg(feature = "lines")]
+#[test]
+#[should_panic]
+fn rope_raw_line_out_of_bounds() {
+    let r = Rope::from("foo\nbar\n");
+    let _ = r.raw_line(2);
+}
+
+/// Tests that the line length stays correct for `Rope`s created from slices
+/// and after edits at the end of the `Rope`.
+#[cfg(feature = "lines")]
+#[test]
+fn rope_line_len_trailing_newline() {
+    let r = Rope::from("foo\nbar\nbaz\n");
+
+    let mut r = Rope::from(r.byte_slice(..8));
+    assert_eq!(r.line_len(), 2);
+
+    r.delete(7..);
+    assert_eq!(r.line_len(), 2);
+
+    r.insert(r.byte_len(), "\r\n");
+    assert_eq!(r.line_len(), 2);
+
+    r.replace(4.., "");
+    assert_eq!(r.line_len(), 1);
+
+    let s = r.byte_slice(..3);
+    assert_eq!(s.line_len(), 1);
+    assert_eq!(Rope::from(s).line_len(), 1);
+
+    r.assert_invariants();
+}
+
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_max_line_len() {
+    for s in ["", "\n\n", "Hi\r\n", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM]
+    {
+        let mut r = Rope::from(s);
+        let expected = s.lines().map(str::len).max().unwrap_or(0);
+        assert_eq!(r.max_line_len(), expected);
+
+        r.insert(0, "x".repeat(5000));
+        let first_line = 5000 + s.lines().next().map_or(0, str::len);
+        assert_eq!(r.max_line_len(), expected.max(first_line));
+    }
+}
+
+/// Tests that the longest line is kept up to date while editing, including
+/// when the `\r` and `\n` of a CRLF pair are inserted separately.
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_max_line_len_after_random_edits() {
+    let mut rng = rand::thread_rng();
+
+    let long_line = "a".repeat(1500);
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+        let mut s = s.to_owned();
+
+        for _ in 0..100 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(start) {
+                start -= 1;
+            }
+            let mut end = (start + rng.gen_range(0..300)).min(r.byte_len());
+            while !r.is_char_boundary(end) {
+                end -= 1;
+            }
+            let text = ["\r", "\n", "\r\n", "", "foo", &long_line]
+                [rng.gen_range(0..6)];
+
+            r.replace(start..end, text);
+            s.replace_range(start..end, text);
+
+            let expected = s.lines().map(str::len).max().unwrap_or(0);
+            assert_eq!(r.max_line_len(), expected);
+        }
+
+        r.assert_invariants();
+    }
+}
+
+/// Tests that `chunk_at_byte()` and `chunk_at_line()` return the same chunks
+/// and offsets as iterating over the chunks.
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_chunk_at_byte_and_line() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+
+        // Move the gaps of some of the gap buffers away from their ends.
+        for _ in 0..10 {
+            let mut offset = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(offset) {
+                offset -= 1;
+            }
+            r.insert(offset, "a\n");
+        }
+
+        let mut chunk_starts = Vec::new();
+        let mut offset = 0;
+        for chunk in r.chunks() {
+            chunk_starts.push((chunk, offset));
+            offset += chunk.len();
+        }
+
+        for _ in 0..100 {
+            let byte_index = rng.gen_range(0..r.byte_len());
+
+            let &(chunk, chunk_byte) = chunk_starts
+                .iter()
+                .rev()
+                .find(|(_, start)| *start <= byte_index)
+                .unwrap();
+
+            let expected = (chunk, chunk_byte, r.line_of_byte(chunk_byte));
+
+            assert_eq!(r.chunk_at_byte(byte_index), expected);
+
+            let line_index = rng.gen_range(0..r.line_len());
+
+            let (chunk, chunk_byte, chunk_line) = r.chunk_at_line(line_index);
+            let line_start = r.byte_of_line(line_index);
+            assert!(chunk_line <= line_index);
+            assert!(chunk_byte <= line_start);
+            assert!(line_start < chunk_byte + chunk.len());
+        }
+    }
+}
+
+#[cfg(feature = "lines")]
+#[test]
+fn rope_line_indent() {
+    let r = Rope::from("\t\tfoo\n  \t bar\n    \n\n \t");
+
+    assert_eq!(r.line_indent(0, 4), (2, 8));
+    assert_eq!(r.line_indent(1, 4), (4, 5));
+    assert_eq!(r.line_indent(1, 2), (4, 5));
+    assert_eq!(r.line_indent(1, 3), (4, 4));
+    assert_eq!(r.line_indent(2, 4), (4, 4));
+    assert_eq!(r.line_indent(3, 4), (0, 0));
+    assert_eq!(r.line_indent(4, 8), (2, 8));
+
+    let s = r.byte_slice(7..);
+    assert_eq!(s.line_indent(0, 4), (3, 5));
+    assert_eq!(s.line_indent(1, 4), (4, 4));
+}
+
+#[cfg(feature = "lines")]
+#[test]
+#[should_panic]
+fn rope_line_indent_zero_tab_width() {
+    let r = Rope::from("\tfoo");
+    let _ = r.line_indent(0, 0);
+}
+
+/// Tests `line_indent()` on lines whose indentation spans several chunks.
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_line_indent_random() {
+    let mut rng = rand::thread_rng();
+
+    let mut s = String::new();
+    let mut expected = Vec::new();
+
+    for _ in 0..200 {
+        let mut bytes = 0;
+        let mut columns = 0;
+
+        for _ in 0..rng.gen_range(0..2000) {
+            if rng.gen_bool(0.5) {
+                s.push(' ');
+                columns += 1;
+            } else {
+                s.push('\t');
+                columns += 4 - columns % 4;
+            }
+            bytes += 1;
+        }
+
+        s.push_str("foo \t\n");
+        expected.push((bytes, columns));
+    }
+
+    let r = Rope::from(s);
+
+    for (line_idx, &expected) in expected.iter().enumerate() {
+        assert_eq!(r.line_indent(line_idx, 4), expected);
+    }
+}
+
+/// Tests `line_boundary_before()` and `line_boundary_after()` at every
+/// offset of a `Rope` and of a slice of it against searching for the
+/// newlines in a `&str`.
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn rope_line_boundaries() {
+    for s in ["", "\n", "a\r\n", "\r\n\r\nb", SMALL, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        for slice_start in [0, s.len() / 3] {
+            let mut slice_start = slice_start;
+            while !s.is_char_boundary(slice_start) {
+                slice_start += 1;
+            }
+
+            let s = &s[slice_start..];
+            let slice = r.byte_slice(slice_start..);
+
+            for offset in 0..=s.len() {
+                if !s.is_char_boundary(offset) {
+                    continue;
+                }
+
+                let start = s[..offset].rfind('\n').map_or(0, |idx| idx + 1);
+
+                let mut end =
+                    s[offset..].find('\n').map_or(s.len(), |idx| offset + idx);
+
+                if end > start
+                    && end < s.len()
+                    && s.as_bytes()[end - 1] == b'\r'
+                {
+                    end -= 1;
+                }
+
+                assert_eq!(slice.line_boundary_before(offset), start);
+                assert_eq!(slice.line_boundary_after(offset), end);
+
+                if slice_start == 0 {
+                    assert_eq!(r.line_boundary_before(offset), start);
+                    assert_eq!(r.line_boundary_after(offset), end);
+                }
+            }
+        }
+    }
+}
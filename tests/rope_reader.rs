@@ -0,0 +1,111 @@
+use std::io::{BufRead, Cursor, Read, Seek, SeekFrom};
+
+use crop::Rope;
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+#[test]
+fn reader_read_to_string() {
+    for s in ["", "Hi", "🐕‍🦺", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM]
+    {
+        let r = Rope::from(s);
+
+        let mut out = String::new();
+        r.reader().read_to_string(&mut out).unwrap();
+        assert_eq!(out, s);
+
+        let mut start = s.len() / 3;
+        while !s.is_char_boundary(start) {
+            start += 1;
+        }
+
+        let s = &s[start..];
+        let slice = r.byte_slice(r.byte_len() - s.len()..);
+
+        let mut out = Vec::new();
+        slice.reader().read_to_end(&mut out).unwrap();
+        assert_eq!(out, s.as_bytes());
+    }
+}
+
+#[test]
+fn reader_lines() {
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        let lines = r.reader().lines().map(Result::unwrap).collect::<Vec<_>>();
+
+        assert_eq!(lines, s.lines().collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn reader_seek_negative() {
+    let r = Rope::from("foo");
+    let mut reader = r.reader();
+    assert!(reader.seek(SeekFrom::Current(-1)).is_err());
+    assert!(reader.seek(SeekFrom::End(-4)).is_err());
+    assert_eq!(reader.seek(SeekFrom::End(2)).unwrap(), 5);
+    assert_eq!(reader.read(&mut [0; 4]).unwrap(), 0);
+}
+
+/// Seeking from the start to an offset past `i64::MAX` moves the reader
+/// there, like a `Cursor`, instead of wrapping to a negative position.
+#[cfg(target_pointer_width = "64")]
+#[test]
+fn reader_seek_past_i64_max() {
+    let r = Rope::from("foo");
+    let mut reader = r.reader();
+    let offset = i64::MAX as u64 + 1;
+    assert_eq!(reader.seek(SeekFrom::Start(offset)).unwrap(), offset);
+    assert_eq!(reader.stream_position().unwrap(), offset);
+    assert_eq!(reader.read(&mut [0; 4]).unwrap(), 0);
+    assert_eq!(reader.seek(SeekFrom::Current(-1)).unwrap(), offset - 1);
+    assert!(reader.seek(SeekFrom::Start(u64::MAX)).is_ok());
+    assert!(reader.seek(SeekFrom::Current(1)).is_err());
+}
+
+/// Tests that random seeks and reads on a `RopeReader` behave the same as on
+/// a `Cursor` wrapping the same bytes.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn reader_random_seeks() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+        let mut reader = r.reader();
+        let mut cursor = Cursor::new(s.as_bytes());
+
+        for _ in 0..100 {
+            let seek = match rng.gen_range(0..3) {
+                0 => SeekFrom::Start(rng.gen_range(0..=s.len() + 1) as u64),
+                1 => SeekFrom::End(-(rng.gen_range(0..=s.len()) as i64)),
+                _ => SeekFrom::Current(rng.gen_range(-10..=10)),
+            };
+
+            assert_eq!(
+                reader.seek(seek).ok(),
+                cursor.seek(seek).ok(),
+                "{seek:?}"
+            );
+
+            let len = rng.gen_range(0..=64);
+            let mut lhs = Vec::new();
+            let mut rhs = Vec::new();
+
+            (&mut reader).take(len).read_to_end(&mut lhs).unwrap();
+            (&mut cursor).take(len).read_to_end(&mut rhs).unwrap();
+
+            assert_eq!(lhs, rhs);
+
+            assert_eq!(
+                reader.stream_position().unwrap(),
+                reader.position() as u64
+            );
+        }
+    }
+}
@@ -1,9 +1,11 @@
-use crop::Rope;
+use std::io::{self, ErrorKind, Read};
+
+use crop::{Gravity, Rope, Tracked};
 use rand::Rng;
 
 mod common;
 
-use common::{LARGE, MEDIUM, SMALL, TEXT, TINY};
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TEXT, TINY};
 
 #[test]
 fn insert_1() {
@@ -352,6 +354,112 @@ fn replace_random() {
     }
 }
 
+#[test]
+fn replace_many_cursors() {
+    let mut r = Rope::from("aaabbbccc");
+
+    let cursors = r.replace_many(
+        [(0..0, "x"), (3..6, "yy"), (6..6, "z")],
+        &[0, 1, 3, 4, 6, 7, 9],
+        Gravity::Left,
+    );
+
+    r.assert_invariants();
+    assert_eq!(r, "xaaayyzccc");
+    assert_eq!(cursors, [0, 2, 4, 4, 4, 8, 10]);
+
+    let mut r = Rope::from("aaabbbccc");
+
+    let cursors = r.replace_many(
+        [(0..0, "x"), (3..6, "yy"), (6..6, "z")],
+        &[0, 1, 3, 4, 6, 7, 9],
+        Gravity::Right,
+    );
+
+    r.assert_invariants();
+    assert_eq!(r, "xaaayyzccc");
+    assert_eq!(cursors, [1, 2, 6, 6, 6, 8, 10]);
+}
+
+#[test]
+fn replace_many_no_edits() {
+    let mut r = Rope::from("Hello");
+    let edits: [(std::ops::Range<usize>, &str); 0] = [];
+    assert_eq!(r.replace_many(edits, &[0, 5], Gravity::Right), [0, 5]);
+    assert_eq!(r, "Hello");
+}
+
+#[test]
+#[should_panic]
+fn replace_many_overlapping() {
+    let mut r = Rope::from("Hello Earth");
+    r.replace_many([(0..5, "a"), (4..6, "b")], &[], Gravity::Left);
+}
+
+#[test]
+#[should_panic]
+fn replace_many_cursor_out_of_bounds() {
+    let mut r = Rope::from("Hello");
+    r.replace_many([(0..1, "a")], &[6], Gravity::Left);
+}
+
+/// Tests that `replace_many()` gives the same text as replacing each range
+/// one at a time, and that its cursors end up where anchors created before
+/// the edits do.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn replace_many_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let mut r = Rope::from(s);
+
+        for _ in 0..20 {
+            let mut edits = Vec::new();
+            let mut start = 0;
+
+            // Separate the edits by at least a byte, where touching edits
+            // would be applied differently by a sequence of replaces.
+            while start < r.byte_len() && edits.len() < 10 {
+                let start_ = rng.gen_range(start..=r.byte_len());
+                let end = rng.gen_range(start_..=r.byte_len().min(start_ + 5));
+                edits.push((
+                    start_..end,
+                    ["", "a", "bcd"][rng.gen_range(0..3)],
+                ));
+                start = end + 1;
+            }
+
+            let cursors = (0..10)
+                .map(|_| rng.gen_range(0..=r.byte_len()))
+                .collect::<Vec<_>>();
+
+            let gravity =
+                if rng.gen() { Gravity::Left } else { Gravity::Right };
+
+            let mut expected = Tracked::new(r.clone());
+
+            let anchors = cursors
+                .iter()
+                .map(|&cursor| expected.anchor(cursor, gravity))
+                .collect::<Vec<_>>();
+
+            for (range, text) in edits.iter().rev() {
+                expected.replace(range.clone(), text);
+            }
+
+            let new_cursors = r.replace_many(edits, &cursors, gravity);
+
+            r.assert_invariants();
+            assert_eq!(r, *expected.rope());
+
+            for (cursor, anchor) in new_cursors.into_iter().zip(anchors) {
+                assert_eq!(Some(cursor), expected.anchor_offset(anchor));
+            }
+        }
+    }
+}
+
 /// ```
 /// Root
 /// ├── "aaa\r"
@@ -403,3 +511,477 @@ fn fix_crlf_4() {
 
     assert_eq!(r, "\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n\r\n");
 }
+
+#[test]
+fn compact_after_deletions() {
+    let mut rng = rand::thread_rng();
+
+    for s in [SMALL, MEDIUM, LARGE] {
+        let mut r = Rope::from(s);
+        let mut string = String::from(s);
+
+        for _ in 0..(s.len() / 8) {
+            let mut start = rng.gen_range(0..=string.len());
+            while !string.is_char_boundary(start) {
+                start += 1;
+            }
+
+            let mut end = (start + rng.gen_range(0..=4)).min(string.len());
+            while !string.is_char_boundary(end) {
+                end += 1;
+            }
+
+            r.delete(start..end);
+            string.replace_range(start..end, "");
+        }
+
+        let chunks_before = r.chunks().count();
+
+        r.compact();
+        r.assert_invariants();
+
+        assert_eq!(r, string);
+        assert!(r.chunks().count() <= chunks_before);
+
+        let chunks_after = r.chunks().count();
+
+        r.compact();
+        assert_eq!(r.chunks().count(), chunks_after);
+    }
+}
+
+#[test]
+fn compact_empty() {
+    let mut r = Rope::from(TINY);
+    r.delete(..);
+    r.compact();
+    r.assert_invariants();
+    assert_eq!(r, "");
+}
+
+#[cfg(feature = "lines")]
+#[test]
+fn append_many_lines() {
+    let mut r = Rope::from(MEDIUM);
+    let mut string = String::from(MEDIUM);
+
+    for i in 0..1000 {
+        let line = format!("line {i}\n");
+        r.insert(r.byte_len(), &line);
+        string.push_str(&line);
+    }
+
+    r.assert_invariants();
+    assert_eq!(r, string);
+    assert_eq!(r.line_len(), string.lines().count());
+}
+
+#[cfg(feature = "lines")]
+#[test]
+fn remove_lines_and_insert_at_line() {
+    let mut r = Rope::from("foo\nbar\r\nbaz");
+
+    r.remove_lines(2..3);
+    assert_eq!(r, "foo\nbar\r\n");
+
+    r.insert_at_line(0, "qux\n");
+    assert_eq!(r, "qux\nfoo\nbar\r\n");
+
+    r.remove_lines(1..1);
+    assert_eq!(r, "qux\nfoo\nbar\r\n");
+
+    r.remove_lines(..);
+    assert_eq!(r, "");
+
+    r.insert_at_line(0, "foo");
+    assert_eq!(r, "foo");
+}
+
+/// Tests that inserting past the last line of a `Rope` which doesn't end with
+/// a line break starts a new line instead of extending the last one.
+#[cfg(feature = "lines")]
+#[test]
+fn insert_at_line_past_last_line() {
+    let mut r = Rope::from("a\nb");
+    r.insert_at_line(2, "c\n");
+    assert_eq!(r, "a\nb\nc\n");
+    assert_eq!(r.line_len(), 3);
+    r.assert_invariants();
+
+    let mut r = Rope::from("a\r\nb");
+    r.insert_at_line(r.line_len(), "c");
+    assert_eq!(r, "a\r\nb\nc");
+    assert_eq!(r.line(2), "c");
+
+    let mut r = Rope::from("a\n");
+    r.insert_at_line(1, "b");
+    assert_eq!(r, "a\nb");
+
+    let mut r = Rope::new();
+    r.insert_at_line(0, "a");
+    assert_eq!(r, "a");
+}
+
+/// Tests that text inserted at the first, a middle or the last line always
+/// ends up on lines of its own, whether or not it ends with a line break.
+#[cfg(feature = "lines")]
+#[test]
+fn insert_at_line_first_middle_last() {
+    for (text, line) in [("x", "x\n"), ("x\n", "x\n"), ("x\r\n", "x\r\n")] {
+        for (s, at, expected) in [
+            ("a\nb\nc\n", 0, format!("{line}a\nb\nc\n")),
+            ("a\nb\nc\n", 1, format!("a\n{line}b\nc\n")),
+            ("a\nb\nc\n", 3, format!("a\nb\nc\n{text}")),
+            ("a\nb\nc", 0, format!("{line}a\nb\nc")),
+            ("a\nb\nc", 2, format!("a\nb\n{line}c")),
+            ("a\nb\nc", 3, format!("a\nb\nc\n{text}")),
+        ] {
+            let mut r = Rope::from(s);
+            r.insert_at_line(at, text);
+            r.assert_invariants();
+            assert_eq!(
+                r, expected,
+                "inserting {text:?} at line {at} of {s:?}"
+            );
+            assert_eq!(r.line(at), "x");
+            assert_eq!(r.line_len(), Rope::from(s).line_len() + 1);
+        }
+    }
+
+    let mut r = Rope::from("a\nb");
+    r.insert_at_line(1, "");
+    r.insert_at_line(2, "");
+    assert_eq!(r, "a\nb");
+}
+
+#[cfg(feature = "lines")]
+#[test]
+#[should_panic]
+fn remove_lines_out_of_bounds() {
+    let mut r = Rope::from("foo\nbar\n");
+    r.remove_lines(1..3);
+}
+
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn remove_lines_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [SMALL, MEDIUM, LARGE] {
+        let mut r = Rope::from(s);
+        let mut lines = s.split_inclusive('\n').collect::<Vec<_>>();
+
+        for _ in 0..20 {
+            let start = rng.gen_range(0..=lines.len());
+            let end = rng.gen_range(start..=lines.len().min(start + 10));
+
+            r.remove_lines(start..end);
+            lines.drain(start..end);
+
+            // The last line may not end with a line break, so we never insert
+            // after it.
+            let at = rng.gen_range(0..lines.len().max(1));
+
+            r.insert_at_line(at, "inserted\n");
+            lines.insert(at, "inserted\n");
+
+            assert_eq!(r, lines.concat());
+        }
+
+        r.assert_invariants();
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn typing_session() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let mut r = Rope::from(s);
+        let mut string = String::from(s);
+
+        let mut cursor = rng.gen_range(0..=string.len());
+        while !string.is_char_boundary(cursor) {
+            cursor += 1;
+        }
+
+        for i in 0..500 {
+            if i % 7 == 6 && cursor > 0 {
+                let mut start = cursor - 1;
+                while !string.is_char_boundary(start) {
+                    start -= 1;
+                }
+                r.delete(start..cursor);
+                string.replace_range(start..cursor, "");
+                cursor = start;
+            } else {
+                let text = if i % 13 == 12 { "\r\n" } else { "ü" };
+                r.insert(cursor, text);
+                string.insert_str(cursor, text);
+                cursor += text.len();
+            }
+
+            assert_eq!(r, string);
+        }
+
+        r.assert_invariants();
+    }
+}
+
+#[cfg(feature = "node-pool")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn node_pool_across_threads() {
+    let mut r = Rope::from(LARGE);
+    let mut string = String::from(LARGE);
+
+    for i in 0..200 {
+        let offset = (i * 97) % string.len();
+        let offset = (offset..).find(|&o| string.is_char_boundary(o)).unwrap();
+        r.insert(offset, "ab");
+        string.insert_str(offset, "ab");
+    }
+
+    // The nodes allocated by this thread are freed by another one, which
+    // recycles them for its own edits.
+    let handle = std::thread::spawn(move || {
+        let mut other = r.clone();
+        drop(r);
+        other.delete(..other.byte_len() / 2);
+        other
+    });
+
+    let other = handle.join().unwrap();
+    string.replace_range(..string.len() / 2, "");
+    other.assert_invariants();
+    assert_eq!(other, string);
+}
+
+/// A reader yielding its bytes in pieces of at most `max_read` bytes, which
+/// can split code points.
+struct PiecewiseReader<'a> {
+    bytes: &'a [u8],
+    max_read: usize,
+}
+
+impl Read for PiecewiseReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let len = self.bytes.len().min(buf.len()).min(self.max_read);
+        buf[..len].copy_from_slice(&self.bytes[..len]);
+        self.bytes = &self.bytes[len..];
+        Ok(len)
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn replace_with_reader_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM] {
+        let mut r = Rope::from(s);
+        let mut string = String::from(s);
+
+        for _ in 0..10 {
+            let start = rng.gen_range(0..=r.byte_len());
+            let end = rng.gen_range(start..=r.byte_len());
+
+            if !string.is_char_boundary(start) || !string.is_char_boundary(end)
+            {
+                continue;
+            }
+
+            let text = &CURSED_LIPSUM[..CURSED_LIPSUM
+                .char_indices()
+                .nth(rng.gen_range(0..200))
+                .map_or(CURSED_LIPSUM.len(), |(idx, _)| idx)];
+
+            let reader = PiecewiseReader {
+                bytes: text.as_bytes(),
+                max_read: rng.gen_range(1..10),
+            };
+
+            let inserted = r.replace_with_reader(start..end, reader).unwrap();
+
+            string.replace_range(start..end, text);
+
+            assert_eq!(inserted, text.len());
+            assert_eq!(r, string);
+            r.assert_invariants();
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn replace_with_reader_large() {
+    let mut r = Rope::from("foo\nbar\n");
+
+    let inserted = r.replace_with_reader(4..7, LARGE.as_bytes()).unwrap();
+
+    assert_eq!(inserted, LARGE.len());
+    assert_eq!(r, format!("foo\n{LARGE}\n"));
+    r.assert_invariants();
+}
+
+#[test]
+fn replace_with_reader_split_code_point() {
+    let mut r = Rope::from("a-c");
+
+    let reader =
+        PiecewiseReader { bytes: "🐸🐕‍🦺".as_bytes(), max_read: 1 };
+
+    r.replace_with_reader(1..2, reader).unwrap();
+
+    assert_eq!(r, "a🐸🐕‍🦺c");
+    r.assert_invariants();
+}
+
+#[test]
+fn replace_with_reader_tracks_edit() {
+    let mut t = Tracked::new(Rope::from("Hello Earth!"));
+
+    t.mark_clean();
+
+    t.edit(|r| r.replace_with_reader(6..11, "Saturn".as_bytes())).unwrap();
+
+    assert_eq!(*t.rope(), "Hello Saturn!");
+    assert_eq!(t.dirty_byte_range(), Some(6..12));
+}
+
+#[test]
+fn replace_with_reader_invalid_utf8() {
+    let mut r = Rope::from("Hello Earth!");
+
+    let err = r.replace_with_reader(6..11, &b"Sat\xFFurn"[..]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(r, "Hello Earth!");
+
+    // The reader ends in the middle of a code point.
+    let err = r.replace_with_reader(6..11, &"🐸".as_bytes()[..3]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+    assert_eq!(r, "Hello Earth!");
+
+    r.assert_invariants();
+}
+
+#[test]
+#[should_panic]
+fn replace_with_reader_not_char_boundary() {
+    let mut r = Rope::from("a🐸b");
+    let _ = r.replace_with_reader(2..5, "c".as_bytes());
+}
+
+#[test]
+fn splice_returns_removed() {
+    let mut r = Rope::from("Hello Earth!");
+
+    let removed = r.splice(6..11, "Mars");
+    assert_eq!(r, "Hello Mars!");
+    assert_eq!(removed, "Earth");
+
+    let removed = r.splice(5..5, ",");
+    assert_eq!(r, "Hello, Mars!");
+    assert_eq!(removed, "");
+
+    let removed = r.splice(.., "");
+    assert_eq!(r, "");
+    assert_eq!(removed, "Hello, Mars!");
+}
+
+#[test]
+fn splice_shares_chunks() {
+    let mut r = Rope::from(LARGE);
+    let before = r.clone();
+
+    let removed = r.splice(10..LARGE.len() - 10, "Hello");
+    removed.assert_invariants();
+    assert_eq!(removed, LARGE[10..LARGE.len() - 10]);
+    assert!(removed.shared_fraction(&before) > 0.9);
+}
+
+#[test]
+#[should_panic]
+fn splice_not_char_boundary() {
+    let mut r = Rope::from("a🐸b");
+    let _ = r.splice(2..5, "c");
+}
+
+/// Tests that splicing random ranges gives back the text that was replaced.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn splice_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let mut r = Rope::from(s);
+        let mut s = s.to_owned();
+
+        for _ in 0..20 {
+            let splice_range = {
+                let start = rng.gen_range(0..=r.byte_len());
+                let end = rng.gen_range(start..=r.byte_len());
+                start..end
+            };
+
+            let splice_with = {
+                let start = rng.gen_range(0..=r.byte_len());
+                let end = rng.gen_range(start..=r.byte_len());
+                s[start..end].to_owned()
+            };
+
+            let removed = r.splice(splice_range.clone(), &splice_with);
+            let expected = s[splice_range.clone()].to_owned();
+            s.replace_range(splice_range, &splice_with);
+
+            r.assert_invariants();
+            removed.assert_invariants();
+            assert_eq!(s, r);
+            assert_eq!(removed, expected);
+        }
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn append_across_leaf_splits() {
+    let mut rng = rand::thread_rng();
+
+    for s in ["", TINY, SMALL, MEDIUM] {
+        let mut r = Rope::from(s);
+        let mut string = String::from(s);
+        let mut clones = Vec::new();
+
+        for i in 0..2000 {
+            let len = rng.gen_range(0..=300);
+            let text = &LARGE[..len];
+            r.insert(r.byte_len(), text);
+            string.push_str(text);
+
+            if i % 97 == 96 {
+                clones.push((r.clone(), string.clone()));
+            }
+
+            if i % 251 == 250 {
+                let mid = string.len() / 2;
+                r.insert(mid, "\n");
+                string.insert(mid, '\n');
+            }
+
+            if i % 50 == 49 {
+                r.assert_invariants();
+                assert_eq!(r, string);
+            }
+        }
+
+        r.assert_invariants();
+        assert_eq!(r, string);
+
+        for (r, string) in clones {
+            r.assert_invariants();
+            assert_eq!(r, string);
+        }
+    }
+}
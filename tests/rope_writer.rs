@@ -0,0 +1,65 @@
+use std::io::{ErrorKind, Write};
+
+use crop::Rope;
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+/// Tests that writing a string in random pieces, which can split code
+/// points, produces the same `Rope` as creating it from the whole string.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn writer_random_pieces() {
+    let mut rng = rand::thread_rng();
+
+    for s in ["", "🐕‍🦺", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let mut r = Rope::from("foo\n");
+
+        {
+            let mut writer = r.writer();
+            let mut bytes = s.as_bytes();
+
+            while !bytes.is_empty() {
+                let len = rng.gen_range(1..=bytes.len().min(100));
+                writer.write_all(&bytes[..len]).unwrap();
+                bytes = &bytes[len..];
+            }
+        }
+
+        r.assert_invariants();
+        assert_eq!(r, format!("foo\n{s}"));
+    }
+}
+
+#[test]
+fn writer_split_code_point() {
+    let mut r = Rope::new();
+    let mut writer = r.writer();
+
+    let bytes = "a🐸".as_bytes();
+    writer.write_all(&bytes[..3]).unwrap();
+    writer.flush().unwrap();
+    writer.write_all(&bytes[3..]).unwrap();
+    drop(writer);
+
+    r.assert_invariants();
+    assert_eq!(r, "a🐸");
+}
+
+#[test]
+fn writer_invalid_utf8() {
+    let mut r = Rope::from("foo");
+    let mut writer = r.writer();
+
+    writer.write_all(b"bar").unwrap();
+
+    let err = writer.write(&[0xF0, 0x28]).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::InvalidData);
+
+    writer.write_all(b"baz").unwrap();
+    drop(writer);
+
+    assert_eq!(r, "foobarbaz");
+}
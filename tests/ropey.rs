@@ -0,0 +1,37 @@
+mod common;
+
+#[cfg(feature = "ropey")]
+mod tests {
+    use crop::Rope;
+
+    use crate::common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn ropey_roundtrip() {
+        for s in ["", "Hi", "🐕‍🦺", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM]
+        {
+            let ropey = ropey::Rope::from_str(s);
+
+            let crop = Rope::from(&ropey);
+            crop.assert_invariants();
+            assert_eq!(crop, s);
+
+            let back = ropey::Rope::from(&crop);
+            assert_eq!(back, s);
+
+            let mut mid = s.len() / 2;
+            while !s.is_char_boundary(mid) {
+                mid += 1;
+            }
+
+            let ropey_slice = ropey.byte_slice(mid..);
+            let crop = Rope::from(ropey_slice);
+            crop.assert_invariants();
+            assert_eq!(crop, &s[mid..]);
+
+            let crop = Rope::from(s);
+            assert_eq!(ropey::Rope::from(crop.byte_slice(..mid)), &s[..mid]);
+        }
+    }
+}
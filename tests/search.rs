@@ -0,0 +1,120 @@
+use crop::Rope;
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE};
+
+fn match_offsets(s: &str, pattern: &str) -> Vec<usize> {
+    s.match_indices(pattern).map(|(offset, _)| offset).collect()
+}
+
+#[test]
+fn find_all_empty_pattern() {
+    for s in ["", "a", "🦀b", CURSED_LIPSUM] {
+        assert_eq!(Rope::from(s).find_all(""), match_offsets(s, ""));
+    }
+}
+
+#[test]
+fn find_all_large() {
+    let r = Rope::from(LARGE);
+
+    for pattern in ["a", "the", "Lorem ipsum", "\n", "foo"] {
+        assert_eq!(r.find_all(pattern), match_offsets(LARGE, pattern));
+    }
+}
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn par_find_all_large() {
+    let r = Rope::from(LARGE);
+
+    for threads in [1, 2, 3, 8] {
+        for pattern in ["", "a", "the", "Lorem ipsum", "\n", "foo"] {
+            assert_eq!(
+                r.par_find_all(pattern, threads),
+                match_offsets(LARGE, pattern)
+            );
+        }
+    }
+}
+
+/// Tests that the occurrences of a pattern which overlap each other across
+/// the boundaries between the parts searched by different threads are
+/// resolved in the same way as a sequential search would.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn par_find_all_overlapping() {
+    let s = "a".repeat(100_003);
+    let r = Rope::from(s.as_str());
+
+    for threads in 1..=7 {
+        for pattern in ["a", "aa", "aaa", "aaaaaaa"] {
+            assert_eq!(
+                r.par_find_all(pattern, threads),
+                match_offsets(&s, pattern)
+            );
+
+            assert_eq!(
+                r.byte_slice(1..).par_find_all(pattern, threads),
+                match_offsets(&s[1..], pattern)
+            );
+        }
+    }
+}
+
+/// Tests that searching a `Rope` whose chunks and gap buffers are split at
+/// random points, both sequentially and in parallel, gives the same result as
+/// searching a `String`.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn find_all_random() {
+    let mut rng = rand::thread_rng();
+
+    let alphabet = ["a", "b", "🦀"];
+
+    for _ in 0..10 {
+        let mut s = String::new();
+        let mut r = Rope::new();
+
+        for _ in 0..4000 {
+            let mut offset = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(offset) {
+                offset -= 1;
+            }
+
+            let text = (0..rng.gen_range(1..10))
+                .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+                .collect::<String>();
+
+            s.insert_str(offset, &text);
+            r.insert(offset, &text);
+        }
+
+        for _ in 0..20 {
+            let pattern = (0..rng.gen_range(1..8))
+                .map(|_| alphabet[rng.gen_range(0..alphabet.len())])
+                .collect::<String>();
+
+            let threads = rng.gen_range(1..16);
+
+            let expected = match_offsets(&s, &pattern);
+
+            assert_eq!(r.find_all(&pattern), expected);
+            assert_eq!(r.par_find_all(&pattern, threads), expected);
+
+            let mut start = rng.gen_range(0..=s.len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let expected = match_offsets(&s[start..], &pattern);
+
+            let slice = r.byte_slice(start..);
+
+            assert_eq!(slice.find_all(&pattern), expected);
+            assert_eq!(slice.par_find_all(&pattern, threads), expected);
+        }
+    }
+}
@@ -0,0 +1,37 @@
+#![cfg(feature = "serde")]
+
+use crop::Rope;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn serde_roundtrip() {
+    for s in ["", "Hi", "🐕‍🦺", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM]
+    {
+        let r = Rope::from(s);
+
+        let json = serde_json::to_string(&r).unwrap();
+        assert_eq!(json, serde_json::to_string(s).unwrap());
+
+        let de = serde_json::from_str::<Rope>(&json).unwrap();
+        de.assert_invariants();
+        assert_eq!(de, s);
+
+        let mut mid = s.len() / 2;
+        while !s.is_char_boundary(mid) {
+            mid += 1;
+        }
+
+        let slice = r.byte_slice(..mid);
+        let json = serde_json::to_string(&slice).unwrap();
+        assert_eq!(json, serde_json::to_string(&s[..mid]).unwrap());
+    }
+}
+
+#[test]
+fn serde_invalid() {
+    assert!(serde_json::from_str::<Rope>("42").is_err());
+}
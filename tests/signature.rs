@@ -0,0 +1,142 @@
+use crop::{DeltaOp, Rope, Signature};
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL};
+
+fn assert_delta_against(old: &Rope, new: &Rope, block_size: usize) {
+    let delta = new.delta_against(&old.signature(block_size));
+
+    assert_eq!(delta.base_len(), old.byte_len());
+    assert_eq!(delta.target_len(), new.byte_len());
+
+    let mut r = old.clone();
+    delta.apply(&mut r);
+    assert_eq!(&r, new);
+}
+
+#[test]
+fn signature_blocks() {
+    let r = Rope::from("abcdefghij");
+
+    let signature = r.signature(3);
+    assert_eq!(signature.block_size(), 3);
+    assert_eq!(signature.base_len(), 10);
+    assert_eq!(signature.blocks().len(), 3);
+
+    let weak = r.rolling_hashes(3).step_by(3).collect::<Vec<_>>();
+
+    for (block, weak) in signature.blocks().iter().zip(weak) {
+        assert_eq!(block.weak, weak);
+    }
+
+    assert!(Rope::from("ab").signature(3).blocks().is_empty());
+    assert!(Rope::new().signature(1).blocks().is_empty());
+}
+
+#[test]
+fn signature_from_parts() {
+    let signature = Rope::from(MEDIUM).signature(64);
+
+    let rebuilt = Signature::from_parts(
+        signature.block_size(),
+        signature.base_len(),
+        signature.blocks().to_vec(),
+    );
+
+    assert_eq!(rebuilt, signature);
+}
+
+#[test]
+#[should_panic]
+fn signature_zero_block_size() {
+    let _ = Rope::from("abc").signature(0);
+}
+
+#[test]
+fn delta_against_unchanged() {
+    let r = Rope::from(LARGE);
+
+    let delta = r.delta_against(&r.signature(512));
+
+    let full_blocks = r.byte_len() / 512 * 512;
+
+    assert_eq!(delta.ops()[0], DeltaOp::Retain(full_blocks));
+    assert_delta_against(&r, &r, 512);
+}
+
+#[test]
+fn delta_against_single_edit() {
+    let old = Rope::from(MEDIUM);
+
+    let mut new = old.clone();
+    new.replace(1000..1010, "Hello");
+
+    let delta = new.delta_against(&old.signature(100));
+
+    let inserted = delta
+        .ops()
+        .iter()
+        .map(|op| match op {
+            DeltaOp::Insert(text) => text.len(),
+            _ => 0,
+        })
+        .sum::<usize>();
+
+    // Only the edited block and the trailing partial block are sent.
+    assert!(inserted <= 100 + 5 + old.byte_len() % 100);
+
+    assert_delta_against(&old, &new, 100);
+}
+
+#[test]
+fn delta_against_multibyte() {
+    // The blocks of 2 bytes cut through the 2-byte code points of the old
+    // text, so they can't be retained in the new one.
+    let old = Rope::from("aéèa");
+    let new = Rope::from("éèa");
+
+    assert_delta_against(&old, &new, 2);
+    assert_delta_against(&new, &old, 2);
+    assert_delta_against(&old, &new, 1);
+}
+
+/// Tests that computing the delta of an edited clone of a rope against the
+/// signature of the rope gives a delta reproducing the edits.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_delta_against() {
+    let mut rng = rand::thread_rng();
+
+    for text in [SMALL, MEDIUM, CURSED_LIPSUM] {
+        let old = Rope::from(text);
+
+        for _ in 0..20 {
+            let mut new = old.clone();
+
+            for _ in 0..rng.gen_range(1..20) {
+                let mut start = rng.gen_range(0..=new.byte_len());
+                while !new.is_char_boundary(start) {
+                    start -= 1;
+                }
+
+                let mut end =
+                    rng.gen_range(start..=new.byte_len().min(start + 30));
+                while !new.is_char_boundary(end) {
+                    end -= 1;
+                }
+
+                let inserted =
+                    ["", "a", "\n", "é\nè", "🐸🐸"][rng.gen_range(0..5)];
+
+                new.replace(start..end, inserted);
+            }
+
+            let block_size = rng.gen_range(1..64);
+
+            assert_delta_against(&old, &new, block_size);
+            assert_delta_against(&new, &old, block_size);
+        }
+    }
+}
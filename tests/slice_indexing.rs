@@ -71,8 +71,46 @@ fn is_char_boundary_random() {
     }
 }
 
+/// Tests `RopeSlice::char()` and `RopeSlice::char_at_byte()` on a bunch of
+/// random RopeSlices over different texts.
+#[cfg(feature = "char-metric")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn char_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [CURSED_LIPSUM, TINY, SMALL, MEDIUM, LARGE] {
+        let r = Rope::from(s);
+
+        for _ in 0..10 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=r.byte_len());
+            while !s.is_char_boundary(end) {
+                end += 1;
+            }
+
+            let str_slice = &s[start..end];
+            let rope_slice = r.byte_slice(start..end);
+
+            assert_eq!(rope_slice.char_len(), str_slice.chars().count());
+
+            for (char_idx, (byte_idx, ch)) in
+                str_slice.char_indices().enumerate().step_by(7)
+            {
+                assert_eq!(rope_slice.char(char_idx), ch);
+                assert_eq!(rope_slice.char_at_byte(byte_idx), ch);
+            }
+        }
+    }
+}
+
 /// Tests `crop::RopeSlice::line_of_byte()` against Ropey's
 /// `ropey::RopeSlice::byte_to_line()`.
+#[cfg(feature = "lines")]
 #[cfg_attr(miri, ignore)]
 #[test]
 fn line_of_byte_random() {
@@ -107,6 +145,7 @@ fn line_of_byte_random() {
 
 /// Tests `crop::RopeSlice::byte_of_line()` against Ropey's
 /// `ropey::RopeSlice::line_to_byte()`.
+#[cfg(feature = "lines")]
 #[cfg_attr(miri, ignore)]
 #[test]
 fn byte_of_line_random() {
@@ -138,3 +177,154 @@ fn byte_of_line_random() {
         }
     }
 }
+
+#[cfg(all(feature = "char-metric", feature = "lines"))]
+fn byte_of_char(s: &str, char_offset: usize) -> usize {
+    s.char_indices().nth(char_offset).map_or(s.len(), |(byte, _)| byte)
+}
+
+/// Tests `crop::{Rope, RopeSlice}::line_of_char()` against Ropey's
+/// `ropey::{Rope, RopeSlice}::char_to_line()`.
+#[cfg(all(feature = "char-metric", feature = "lines"))]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn line_of_char_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let crop = Rope::from(s);
+        let ropey = ropey::Rope::from(s);
+
+        for _ in 0..100 {
+            let char_offset = rng.gen_range(0..=crop.char_len());
+            assert_eq!(
+                crop.line_of_char(char_offset),
+                ropey.char_to_line(char_offset)
+            );
+        }
+
+        for _ in 0..100 {
+            let start = rng.gen_range(0..crop.char_len());
+            let mut end = rng.gen_range(start + 1..=crop.char_len());
+
+            // Ropey counts a lone `\r` at the end of a slice as a line
+            // break while crop doesn't, so don't split `\r\n`s.
+            let end_byte = byte_of_char(s, end);
+            if s[..end_byte].ends_with('\r') && s[end_byte..].starts_with('\n')
+            {
+                end += 1;
+            }
+
+            let range = start..end;
+
+            let crop_slice = crop.byte_slice(
+                byte_of_char(s, range.start)..byte_of_char(s, range.end),
+            );
+            let ropey_slice = ropey.slice(range.clone());
+
+            for _ in 0..10 {
+                let char_offset = rng.gen_range(0..=crop_slice.char_len());
+                let crop_line_offset = crop_slice.line_of_char(char_offset);
+                let ropey_line_offset = ropey_slice.char_to_line(char_offset);
+
+                if crop_line_offset != ropey_line_offset {
+                    println!("char offset: {char_offset}");
+                    println!("char range: {range:?}");
+                    assert_eq!(crop_line_offset, ropey_line_offset)
+                }
+            }
+        }
+    }
+}
+
+/// Tests `crop::{Rope, RopeSlice}::char_of_line()` against Ropey's
+/// `ropey::{Rope, RopeSlice}::line_to_char()`.
+#[cfg(all(feature = "char-metric", feature = "lines"))]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn char_of_line_random() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let crop = Rope::from(s);
+        let ropey = ropey::Rope::from(s);
+
+        for line_offset in 0..=crop.line_len() {
+            assert_eq!(
+                crop.char_of_line(line_offset),
+                ropey.line_to_char(line_offset)
+            );
+        }
+
+        for _ in 0..100 {
+            let start = rng.gen_range(0..crop.char_len());
+            let mut end = rng.gen_range(start + 1..=crop.char_len());
+
+            // Ropey counts a lone `\r` at the end of a slice as a line
+            // break while crop doesn't, so don't split `\r\n`s.
+            let end_byte = byte_of_char(s, end);
+            if s[..end_byte].ends_with('\r') && s[end_byte..].starts_with('\n')
+            {
+                end += 1;
+            }
+
+            let range = start..end;
+
+            let crop_slice = crop.byte_slice(
+                byte_of_char(s, range.start)..byte_of_char(s, range.end),
+            );
+            let ropey_slice = ropey.slice(range.clone());
+
+            for _ in 0..10 {
+                let line_offset = rng.gen_range(0..=crop_slice.line_len());
+                let crop_char_offset = crop_slice.char_of_line(line_offset);
+                let ropey_char_offset = ropey_slice.line_to_char(line_offset);
+
+                if crop_char_offset != ropey_char_offset {
+                    println!("line offset: {line_offset}");
+                    println!("char range: {range:?}");
+                    assert_eq!(crop_char_offset, ropey_char_offset)
+                }
+            }
+        }
+    }
+}
+
+/// Tests that `RopeSlice::chunk_at_byte()` returns the same chunks and
+/// offsets as iterating over the chunks of the slice.
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn slice_chunk_at_byte() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE] {
+        let r = Rope::from(s);
+
+        for _ in 0..100 {
+            let start = rng.gen_range(0..r.byte_len());
+            let end = rng.gen_range(start + 1..=r.byte_len());
+            let slice = r.byte_slice(start..end);
+
+            let byte_index = rng.gen_range(0..slice.byte_len());
+
+            let mut chunk_byte = 0;
+            let chunk = slice
+                .chunks()
+                .find(|chunk| {
+                    if byte_index < chunk_byte + chunk.len() {
+                        true
+                    } else {
+                        chunk_byte += chunk.len();
+                        false
+                    }
+                })
+                .unwrap();
+
+            assert_eq!(
+                slice.chunk_at_byte(byte_index),
+                (chunk, chunk_byte, slice.line_of_byte(chunk_byte))
+            );
+        }
+    }
+}
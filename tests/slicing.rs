@@ -3,7 +3,7 @@ use rand::Rng;
 
 mod common;
 
-use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TEXT_EMOJI, TINY};
 
 #[test]
 fn byte_slice_empty() {
@@ -82,6 +82,7 @@ fn byte_slice_random() {
     }
 }
 
+#[cfg(feature = "lines")]
 #[test]
 fn line_slice_empty() {
     let r = Rope::from("");
@@ -89,6 +90,7 @@ fn line_slice_empty() {
     assert!(s.is_empty());
 }
 
+#[cfg(feature = "lines")]
 #[test]
 fn line_slice_0() {
     let r = Rope::from("aaa\nbbb\nccc\nddd\neee\nfff\n");
@@ -103,6 +105,7 @@ fn line_slice_0() {
     assert_eq!(r, s);
 }
 
+#[cfg(feature = "lines")]
 #[test]
 fn line_slice_1() {
     let r = Rope::from("Hello world");
@@ -124,6 +127,7 @@ fn line_slice_1() {
     assert_eq!("Hello world\nthis is\na test\n", r.line_slice(..));
 }
 
+#[cfg(feature = "lines")]
 #[test]
 fn byte_slice_then_line() {
     let r = Rope::from("foo\nbar\r\nbaz\nfoobar\n");
@@ -136,6 +140,7 @@ fn byte_slice_then_line() {
     assert_eq!(s.line_slice(3..), "foob");
 }
 
+#[cfg(feature = "lines")]
 #[cfg_attr(miri, ignore)]
 #[test]
 fn line_slices_random() {
@@ -220,3 +225,178 @@ fn rope_from_slice() {
         }
     }
 }
+
+#[cfg_attr(miri, ignore)]
+#[test]
+fn string_from_rope_and_slice() {
+    let mut buf = String::new();
+
+    for s in ["", "🐕‍🦺", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        let mut mid = s.len() / 2;
+        while !s.is_char_boundary(mid) {
+            mid += 1;
+        }
+
+        assert_eq!(String::from(&r), s);
+        assert_eq!(String::from(r.byte_slice(mid..)), &s[mid..]);
+
+        buf.clear();
+        r.byte_slice(..mid).collect_into(&mut buf);
+        r.byte_slice(mid..).collect_into(&mut buf);
+        assert_eq!(buf, s);
+
+        assert_eq!(String::from(r), s);
+    }
+}
+
+#[test]
+fn from_str_parse() {
+    fn parse_all<T: core::str::FromStr>(strs: &[&str]) -> Vec<T>
+    where
+        T::Err: core::fmt::Debug,
+    {
+        strs.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    let strs = ["", "Hello", CURSED_LIPSUM, LARGE, TEXT_EMOJI];
+
+    for (r, s) in parse_all::<Rope>(&strs).into_iter().zip(strs) {
+        r.assert_invariants();
+        assert_eq!(r, s);
+    }
+
+    let r: Rope = "Hello\nWorld".parse().unwrap_or_else(|e| match e {});
+    assert_eq!(r, "Hello\nWorld");
+}
+
+#[test]
+fn byte_slice_snapped_chars() {
+    use crop::Snap;
+
+    let r = Rope::from("🗻∈🌏");
+
+    assert_eq!(r.byte_slice_snapped(1..1, Snap::CharOutward), "🗻");
+    assert_eq!(r.byte_slice_snapped(1..2, Snap::CharOutward), "🗻");
+    assert_eq!(r.byte_slice_snapped(1..2, Snap::CharInward), "");
+    assert_eq!(r.byte_slice_snapped(5..6, Snap::CharInward), "");
+    assert_eq!(r.byte_slice_snapped(..100, Snap::CharInward), "🗻∈🌏");
+    assert_eq!(r.byte_slice_snapped(100.., Snap::CharOutward), "");
+}
+
+#[test]
+#[should_panic]
+fn byte_slice_snapped_start_after_end() {
+    let r = Rope::from("foo");
+    #[allow(clippy::reversed_empty_ranges)]
+    let _ = r.byte_slice_snapped(2..1, crop::Snap::CharOutward);
+}
+
+/// Tests that snapping random byte ranges of a `Rope` with gaps in its chunks
+/// to char boundaries matches doing the same on a `&str`.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn byte_slice_snapped_chars_random() {
+    use crop::Snap;
+
+    let mut rng = rand::thread_rng();
+
+    let mut s = String::from(CURSED_LIPSUM);
+    let mut r = Rope::from(CURSED_LIPSUM);
+
+    for _ in 0..100 {
+        let mut offset = rng.gen_range(0..=s.len());
+        while !s.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        s.insert_str(offset, "é🦀");
+        r.insert(offset, "é🦀");
+    }
+
+    for _ in 0..100 {
+        let start = rng.gen_range(0..=s.len());
+        let end = rng.gen_range(start..=s.len() + 3);
+
+        let clamped_end = end.min(s.len());
+
+        let (mut floor_start, mut ceil_start) = (start, start);
+        while !s.is_char_boundary(floor_start) {
+            floor_start -= 1;
+        }
+        while !s.is_char_boundary(ceil_start) {
+            ceil_start += 1;
+        }
+
+        let (mut floor_end, mut ceil_end) = (clamped_end, clamped_end);
+        while !s.is_char_boundary(floor_end) {
+            floor_end -= 1;
+        }
+        while !s.is_char_boundary(ceil_end) {
+            ceil_end += 1;
+        }
+
+        assert_eq!(
+            r.byte_slice_snapped(start..end, Snap::CharOutward),
+            s[floor_start..ceil_end]
+        );
+
+        assert_eq!(
+            r.byte_slice_snapped(start..end, Snap::CharInward),
+            s[ceil_start..floor_end.max(ceil_start)]
+        );
+
+        assert_eq!(
+            r.byte_slice(..).byte_slice_snapped(start..end, Snap::CharInward),
+            s[ceil_start..floor_end.max(ceil_start)]
+        );
+    }
+}
+
+/// Tests that snapping random byte ranges to grapheme boundaries lands on the
+/// boundaries of the graphemes yielded by `Rope::graphemes()`.
+#[cfg(feature = "graphemes")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn byte_slice_snapped_graphemes_random() {
+    use crop::Snap;
+
+    let mut rng = rand::thread_rng();
+
+    let mut r = Rope::from(CURSED_LIPSUM);
+
+    for _ in 0..100 {
+        let mut offset = rng.gen_range(0..=r.byte_len());
+        while !r.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        r.insert(offset, ["🐻‍❄️", "\r\n", "e\u{301}"][rng.gen_range(0..3)]);
+    }
+
+    let mut boundaries = vec![0];
+    for grapheme in r.graphemes() {
+        boundaries.push(boundaries.last().unwrap() + grapheme.len());
+    }
+
+    let floor = |offset: usize| {
+        *boundaries.iter().rev().find(|&&b| b <= offset).unwrap()
+    };
+
+    let ceil =
+        |offset: usize| *boundaries.iter().find(|&&b| b >= offset).unwrap();
+
+    for _ in 0..200 {
+        let start = rng.gen_range(0..=r.byte_len());
+        let end = rng.gen_range(start..=r.byte_len());
+
+        let outward = r.byte_slice_snapped(start..end, Snap::GraphemeOutward);
+        assert_eq!(outward, r.byte_slice(floor(start)..ceil(end)));
+
+        let inward = r.byte_slice_snapped(start..end, Snap::GraphemeInward);
+        let inward_start = ceil(start);
+        assert_eq!(
+            inward,
+            r.byte_slice(inward_start..floor(end).max(inward_start))
+        );
+    }
+}
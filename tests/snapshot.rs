@@ -0,0 +1,327 @@
+use std::sync::Arc;
+
+use crop::tree::AtomicCounter;
+use crop::{LfBreaks, Rope, RopeWith, ZeroWeight};
+#[cfg(feature = "lines")]
+use rand::Rng;
+
+mod common;
+
+use common::SMALL;
+#[cfg(feature = "lines")]
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, TINY};
+
+/// Ropes borrowing their text from a snapshot are still unwind safe, and
+/// can still be sent to and shared with other threads.
+const _: () = {
+    use std::panic::{RefUnwindSafe, UnwindSafe};
+
+    const fn assert_unwind_safe<T: UnwindSafe + RefUnwindSafe>() {}
+    const fn assert_send_sync<T: Send + Sync>() {}
+
+    assert_unwind_safe::<Rope>();
+    assert_unwind_safe::<&'static Rope>();
+    assert_unwind_safe::<crop::RopeSlice<'static>>();
+    assert_send_sync::<Rope>();
+    assert_send_sync::<crop::RopeSlice<'static>>();
+};
+
+type RopeOf<const MAX_BYTES: usize, const MIN_FILL: usize = 25> =
+    RopeWith<AtomicCounter, ZeroWeight, LfBreaks, MAX_BYTES, MIN_FILL>;
+
+/// The length of the header of a snapshot, including its checksum.
+const HEADER_LEN: usize = 64;
+
+fn snapshot<const MAX_BYTES: usize, const MIN_FILL: usize>(
+    rope: &RopeOf<MAX_BYTES, MIN_FILL>,
+) -> Vec<u8> {
+    let mut snapshot = Vec::new();
+    rope.write_snapshot(&mut snapshot).unwrap();
+    snapshot
+}
+
+/// Writes a snapshot in one of the versions of the format before the
+/// current one, whose leaves are the given texts.
+fn legacy_snapshot(version: u32, leaves: &[&str]) -> Vec<u8> {
+    let mut snapshot = b"CROPSNAP".to_vec();
+    snapshot.extend_from_slice(&version.to_le_bytes());
+    // The flags, without the `utf16-metric` one.
+    snapshot.extend_from_slice(&0u32.to_le_bytes());
+    snapshot.extend_from_slice(&2048u32.to_le_bytes());
+    if version >= 3 {
+        snapshot.extend_from_slice(&25u32.to_le_bytes());
+    }
+    snapshot.extend_from_slice(&(leaves.len() as u64).to_le_bytes());
+
+    for leaf in leaves {
+        let line_breaks = leaf.matches('\n').count() as u32;
+        snapshot.extend_from_slice(&(leaf.len() as u32).to_le_bytes());
+        snapshot.extend_from_slice(&line_breaks.to_le_bytes());
+        if version >= 2 {
+            let chars = leaf.chars().count() as u32;
+            snapshot.extend_from_slice(&chars.to_le_bytes());
+        }
+    }
+
+    for leaf in leaves {
+        snapshot.extend_from_slice(leaf.as_bytes());
+    }
+
+    snapshot
+}
+
+#[cfg(feature = "lines")]
+#[cfg_attr(miri, ignore)]
+#[test]
+fn snapshot_roundtrip() {
+    let mut rng = rand::thread_rng();
+
+    for s in ["", "Hi", "🐕‍🦺", TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM]
+    {
+        let mut r = RopeOf::<64>::from(s);
+
+        for _ in 0..10 {
+            let snapshot = snapshot(&r);
+
+            let copied = RopeOf::<64>::from_snapshot(&snapshot).unwrap();
+            copied.assert_invariants();
+            assert_eq!(copied, r);
+            assert_eq!(copied.line_len(), r.line_len());
+
+            let shared =
+                RopeOf::<64>::from_shared_snapshot(Arc::from(&snapshot[..]))
+                    .unwrap();
+            shared.assert_invariants();
+            assert_eq!(shared, r);
+            assert_eq!(shared.line_len(), r.line_len());
+
+            let unchecked = unsafe {
+                RopeOf::<64>::from_shared_snapshot_unchecked(snapshot)
+            }
+            .unwrap();
+            unchecked.assert_invariants();
+            assert_eq!(unchecked, r);
+            assert_eq!(unchecked.line_len(), r.line_len());
+
+            let mut offset = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(offset) {
+                offset += 1;
+            }
+            r.insert(offset, "foo\r\nbar🐸\n");
+        }
+    }
+}
+
+/// The leaves of a rope read from a shared snapshot are copied out of it
+/// when they're edited, and keep the snapshot alive until then.
+#[cfg(feature = "lines")]
+#[test]
+fn snapshot_shared_leaves() {
+    let r = RopeOf::<64>::from(MEDIUM);
+
+    let snapshot: Arc<[u8]> = Arc::from(snapshot(&r));
+
+    let copied = RopeOf::<64>::from_snapshot(&snapshot).unwrap();
+
+    let mut shared =
+        RopeOf::<64>::from_shared_snapshot(Arc::clone(&snapshot)).unwrap();
+
+    assert!(shared.stats().mem_usage() < copied.stats().mem_usage());
+
+    let before = snapshot.to_vec();
+    drop(snapshot);
+
+    shared.insert(10, "foo");
+    shared.delete(MEDIUM.len() - 20..MEDIUM.len() - 10);
+    shared.assert_invariants();
+
+    let mut expected = MEDIUM.to_owned();
+    expected.insert_str(10, "foo");
+    expected.replace_range(MEDIUM.len() - 20..MEDIUM.len() - 10, "");
+    assert_eq!(shared, expected);
+
+    // The snapshot wasn't modified by the edits.
+    assert_eq!(RopeOf::<64>::from_snapshot(&before).unwrap(), MEDIUM);
+}
+
+/// Snapshots written with a different chunk size are rebuilt from their
+/// text.
+#[cfg(feature = "lines")]
+#[test]
+fn snapshot_different_chunk_size() {
+    let r = RopeOf::<64>::from(MEDIUM);
+
+    let loaded = Rope::from_snapshot(&snapshot(&r)).unwrap();
+    loaded.assert_invariants();
+    assert_eq!(loaded, MEDIUM);
+    assert_eq!(loaded.line_len(), r.line_len());
+
+    let loaded = RopeOf::<16>::from_snapshot(&snapshot(&r)).unwrap();
+    loaded.assert_invariants();
+    assert_eq!(loaded, MEDIUM);
+    assert!(loaded.chunks().all(|chunk| chunk.len() <= 16));
+}
+
+/// Snapshots written by a rope maintaining different metrics are rebuilt from
+/// their text.
+#[cfg(feature = "lines")]
+#[test]
+fn snapshot_different_metric_set() {
+    use crop::{MetricSet, Omitted};
+
+    #[derive(Clone, Copy, Debug, Default, PartialEq)]
+    struct BytesOnly;
+
+    impl MetricSet for BytesOnly {
+        type Lines = Omitted;
+        type Chars = Omitted;
+        type Utf16CodeUnits = Omitted;
+        type Width = Omitted;
+    }
+
+    type BytesRope =
+        RopeWith<AtomicCounter, ZeroWeight, LfBreaks, 2048, 25, BytesOnly>;
+
+    let r = BytesRope::from(MEDIUM);
+    let mut snapshot = Vec::new();
+    r.write_snapshot(&mut snapshot).unwrap();
+
+    let loaded = Rope::from_snapshot(&snapshot).unwrap();
+    loaded.assert_invariants();
+    assert_eq!(loaded, MEDIUM);
+    assert_eq!(loaded.line_len(), Rope::from(MEDIUM).line_len());
+
+    let loaded = BytesRope::from_snapshot(&snapshot).unwrap();
+    loaded.assert_invariants();
+    assert_eq!(loaded, MEDIUM);
+}
+
+/// Snapshots written by a rope with a different minimum fill can have leaves
+/// smaller than the ones this rope allows, so they're rebuilt from their text
+/// instead of being rejected.
+#[test]
+fn snapshot_different_min_fill() {
+    let mut r = RopeOf::<64, 1>::from(SMALL);
+
+    // Leave most leaves almost empty.
+    let mut offset = 0;
+    while offset + 60 < r.byte_len() {
+        r.delete(offset..offset + 60);
+        offset += 1;
+    }
+
+    let snapshot = snapshot(&r);
+
+    let loaded = RopeOf::<64, 1>::from_snapshot(&snapshot).unwrap();
+    loaded.assert_invariants();
+    assert_eq!(loaded, r);
+
+    let loaded = RopeOf::<64, 50>::from_snapshot(&snapshot).unwrap();
+    loaded.assert_invariants();
+    assert_eq!(loaded, r.to_string());
+}
+
+/// Version 1 snapshots, which didn't store the number of `char`s of the
+/// leaves, are rebuilt from their text.
+#[cfg(feature = "lines")]
+#[test]
+fn snapshot_version_1() {
+    let leaves = CURSED_LIPSUM.split_inclusive('\n').collect::<Vec<_>>();
+
+    let loaded = Rope::from_snapshot(&legacy_snapshot(1, &leaves)).unwrap();
+    loaded.assert_invariants();
+    assert_eq!(loaded, CURSED_LIPSUM);
+    assert!(!loaded.is_ascii());
+    assert_eq!(loaded.line_len(), Rope::from(CURSED_LIPSUM).line_len());
+}
+
+/// Snapshots written by the other versions of the format which only stored
+/// the summaries of the leaves are rebuilt from their text, however small
+/// their leaves are.
+#[test]
+fn snapshot_legacy_versions() {
+    let tiny_leaves =
+        (0..SMALL.len()).map(|idx| &SMALL[idx..idx + 1]).collect::<Vec<_>>();
+
+    for version in [2, 3] {
+        let loaded =
+            Rope::from_snapshot(&legacy_snapshot(version, &tiny_leaves))
+                .unwrap();
+        loaded.assert_invariants();
+        assert_eq!(loaded, SMALL);
+        assert!(loaded.chunks().count() < SMALL.len());
+    }
+}
+
+#[test]
+fn snapshot_malformed() {
+    let r = RopeOf::<64>::from(SMALL);
+    let snapshot = snapshot(&r);
+
+    let is_invalid_data = |snapshot: &[u8]| {
+        let err = RopeOf::<64>::from_snapshot(snapshot).unwrap_err();
+        err.kind() == std::io::ErrorKind::InvalidData
+    };
+
+    // Truncated.
+    assert!(is_invalid_data(&snapshot[..snapshot.len() - 1]));
+    assert!(is_invalid_data(&snapshot[..10]));
+    assert!(is_invalid_data(&snapshot[..HEADER_LEN + 10]));
+
+    // Trailing bytes.
+    let mut trailing = snapshot.clone();
+    trailing.push(b'a');
+    assert!(is_invalid_data(&trailing));
+
+    // Wrong magic.
+    let mut magic = snapshot.clone();
+    magic[0] = b'X';
+    assert!(is_invalid_data(&magic));
+
+    // Corrupted flags.
+    let mut flags = snapshot.clone();
+    flags[12] ^= 1 << 6;
+    assert!(is_invalid_data(&flags));
+
+    // Corrupted number of children or summaries.
+    for idx in [HEADER_LEN, HEADER_LEN + 4, HEADER_LEN + 12] {
+        let mut nodes = snapshot.clone();
+        nodes[idx] ^= 1;
+        assert!(is_invalid_data(&nodes));
+    }
+
+    // Invalid UTF-8.
+    let mut invalid = snapshot.clone();
+    let last_slot = invalid.len() - 64;
+    invalid[last_slot] = 0xFF;
+    assert!(is_invalid_data(&invalid));
+    assert!(RopeOf::<64>::from_shared_snapshot(Arc::from(invalid)).is_err());
+}
+
+/// The text of the leaves isn't covered by the checksum, so changing it has
+/// to be caught by checking the summaries of the leaves against it.
+#[cfg(feature = "lines")]
+#[test]
+fn snapshot_changed_text() {
+    let r = RopeOf::<64>::from(SMALL);
+    let snapshot = snapshot(&r);
+
+    let chunk = r.chunks().next().unwrap().as_bytes();
+
+    let start = snapshot
+        .windows(chunk.len())
+        .position(|window| window == chunk)
+        .unwrap();
+
+    let offset = chunk.iter().position(|&byte| byte != b'\n').unwrap();
+
+    let mut changed = snapshot.clone();
+    changed[start + offset] = b'\n';
+
+    let err = RopeOf::<64>::from_snapshot(&changed).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+
+    let err =
+        RopeOf::<64>::from_shared_snapshot(Arc::from(changed)).unwrap_err();
+    assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+}
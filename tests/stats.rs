@@ -0,0 +1,116 @@
+use crop::tree::AtomicCounter;
+use crop::{LfBreaks, Rope, RopeWith, ZeroWeight};
+
+mod common;
+
+use common::LARGE;
+
+type RopeOf<const MAX_BYTES: usize> =
+    RopeWith<AtomicCounter, ZeroWeight, LfBreaks, MAX_BYTES>;
+
+#[test]
+fn stats_single_leaf() {
+    // Short enough to fit in a single chunk even with the `small_chunks`
+    // feature.
+    let r = Rope::from("abc");
+    let stats = r.stats();
+    assert_eq!(stats.depth(), 0);
+    assert_eq!(stats.leaf_count(), 1);
+    assert!(stats.mem_usage() > 0);
+}
+
+/// A short string is kept in a leaf that's only as big as it, which grows to
+/// a whole chunk when it's edited.
+#[test]
+fn stats_small_leaf() {
+    let r = Rope::from("abc");
+
+    let mut edited = r.clone();
+    edited.insert(3, "d");
+
+    assert_eq!(r, "abc");
+    assert_eq!(edited, "abcd");
+    assert!(r.stats().mem_usage() < edited.stats().mem_usage());
+}
+
+/// A `Rope` is just a pointer to the root of its tree and one to the path of
+/// its last edit, whatever metrics are enabled.
+#[test]
+fn rope_size() {
+    assert_eq!(
+        core::mem::size_of::<Rope>(),
+        2 * core::mem::size_of::<usize>()
+    );
+}
+
+/// `Rope::with_capacity()` allocates a single chunk up front whatever the
+/// capacity, which isn't grown when text is inserted into it.
+#[test]
+fn stats_with_capacity() {
+    let empty = RopeOf::<64>::new().stats().mem_usage();
+
+    assert_eq!(RopeOf::<64>::with_capacity(0).stats().mem_usage(), empty);
+
+    assert_eq!(
+        RopeOf::<64>::with_capacity(1000).stats().mem_usage(),
+        empty + 64
+    );
+
+    let mut r = RopeOf::<64>::with_capacity(10);
+    assert!(r.is_empty());
+    assert_eq!(r.stats().mem_usage(), empty + 64);
+
+    r.insert(0, "abc");
+    r.assert_invariants();
+    assert_eq!(r, "abc");
+    assert_eq!(r.stats().mem_usage(), empty + 64);
+}
+
+#[test]
+fn stats_large() {
+    let r = Rope::from(LARGE);
+    let stats = r.stats();
+
+    assert!(stats.leaf_count() > 1);
+    assert!(stats.leaf_count() <= r.chunks().count());
+    assert!(stats.depth() > 0);
+    assert!(stats.mem_usage() > LARGE.len());
+    assert!(stats.leaf_occupancy() > 0.0 && stats.leaf_occupancy() <= 1.0);
+
+    // A clone shares the whole tree but reports the same memory usage.
+    assert_eq!(r.clone().stats(), stats);
+}
+
+#[test]
+fn stats_after_slicing() {
+    let r = Rope::from(LARGE);
+    let s = Rope::from(r.byte_slice(LARGE.len() / 3..LARGE.len() / 2));
+    let stats = s.stats();
+    assert!(stats.leaf_count() <= s.chunks().count());
+    assert!(stats.depth() <= r.stats().depth());
+}
+
+#[cfg(feature = "u32-summaries")]
+#[test]
+fn stats_u32_summaries() {
+    let fields = 2 + cfg!(feature = "utf16-metric") as usize;
+
+    // The parts of the summary enabled by these features aren't counts.
+    if !cfg!(any(
+        feature = "max-line-len",
+        feature = "word-count",
+        feature = "grapheme-count",
+        feature = "fingerprint",
+        feature = "paragraph-count"
+    )) {
+        assert_eq!(
+            core::mem::size_of::<crop::ChunkSummary>(),
+            core::mem::size_of::<u32>() * fields
+        );
+    }
+
+    let r = Rope::from(LARGE);
+    assert_eq!(r, LARGE);
+    assert_eq!(r.byte_len(), LARGE.len());
+    assert_eq!(r.line_len(), LARGE.lines().count());
+}
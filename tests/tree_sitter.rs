@@ -0,0 +1,83 @@
+mod common;
+
+#[cfg(feature = "tree-sitter")]
+mod tests {
+    use crop::Rope;
+    use streaming_iterator::StreamingIterator;
+    use tree_sitter::{Parser, Query, QueryCursor, TextProvider};
+
+    use crate::common::LARGE;
+
+    /// Returns a JSON document with many string values to make sure its
+    /// nodes span multiple chunks.
+    fn json_document() -> String {
+        let entries = LARGE
+            .lines()
+            .enumerate()
+            .map(|(idx, line)| format!("  \"{idx}\": {:?}", line))
+            .collect::<Vec<_>>();
+
+        format!("{{\n{}\n}}\n", entries.join(",\n"))
+    }
+
+    fn parser() -> Parser {
+        let mut parser = Parser::new();
+        parser.set_language(&tree_sitter_json::LANGUAGE.into()).unwrap();
+        parser
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn tree_sitter_parse_with_callback() {
+        let json = json_document();
+        let r = Rope::from(json.as_str());
+
+        let expected = parser().parse(&json, None).unwrap();
+
+        let mut callback = r.tree_sitter_callback();
+        let tree =
+            parser().parse_with_options(&mut callback, None, None).unwrap();
+
+        assert!(!tree.root_node().has_error());
+        assert_eq!(tree.root_node().to_sexp(), expected.root_node().to_sexp());
+        assert_eq!(tree.root_node().end_byte(), json.len());
+
+        let s = r.byte_slice(..);
+        let mut callback = s.tree_sitter_callback();
+        let tree =
+            parser().parse_with_options(&mut callback, None, None).unwrap();
+        assert_eq!(tree.root_node().to_sexp(), expected.root_node().to_sexp());
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn tree_sitter_text_provider() {
+        let json = json_document();
+        let r = Rope::from(json.as_str());
+        let tree = parser().parse(&json, None).unwrap();
+
+        let query =
+            Query::new(&tree_sitter_json::LANGUAGE.into(), "(string) @s")
+                .unwrap();
+
+        let mut cursor = QueryCursor::new();
+        let mut captures = cursor.captures(&query, tree.root_node(), &r);
+
+        let mut count = 0;
+
+        while let Some((query_match, idx)) = captures.next() {
+            let node = query_match.captures[*idx].node;
+
+            let text = (&r)
+                .text(node)
+                .flat_map(|chunk| chunk.iter().copied())
+                .collect::<Vec<_>>();
+
+            assert_eq!(text, json.as_bytes()[node.byte_range()]);
+
+            count += 1;
+        }
+
+        assert_eq!(count, 2 * LARGE.lines().count());
+    }
+}
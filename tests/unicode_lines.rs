@@ -0,0 +1,198 @@
+mod common;
+
+#[cfg(feature = "lines")]
+mod tests {
+    use crop::tree::AtomicCounter;
+    use crop::{RopeWith, UnicodeBreaks, ZeroWeight};
+    use rand::Rng;
+
+    use crate::common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+    const BREAKS: [&str; 7] = [
+        "\n", "\r\n", "\u{000B}", "\u{000C}", "\u{0085}", "\u{2028}",
+        "\u{2029}",
+    ];
+
+    type Rope = RopeWith<AtomicCounter, ZeroWeight, UnicodeBreaks>;
+
+    /// Splits a string on the line breaks of `UnicodeBreaks`, not including
+    /// the line terminators.
+    fn str_lines(s: &str) -> Vec<&str> {
+        let mut lines = Vec::new();
+        let mut start = 0;
+
+        for (idx, ch) in s.char_indices() {
+            if matches!(
+                ch,
+                '\n' | '\u{000B}'
+                    | '\u{000C}'
+                    | '\u{0085}'
+                    | '\u{2028}'
+                    | '\u{2029}'
+            ) {
+                let mut line = &s[start..idx];
+                if ch == '\n' {
+                    line = line.strip_suffix('\r').unwrap_or(line);
+                }
+                lines.push(line);
+                start = idx + ch.len_utf8();
+            }
+        }
+
+        if start < s.len() {
+            lines.push(&s[start..]);
+        }
+
+        lines
+    }
+
+    /// Joins the lines of `s` using random line breaks.
+    fn with_random_breaks(s: &str) -> String {
+        let mut rng = rand::thread_rng();
+        let mut out = String::with_capacity(s.len());
+
+        for line in s.lines() {
+            out.push_str(line);
+            out.push_str(BREAKS[rng.gen_range(0..BREAKS.len())]);
+        }
+
+        out
+    }
+
+    #[test]
+    fn unicode_lines_0() {
+        let r =
+            Rope::from("a\u{000B}b\u{000C}c\u{0085}d\u{2028}e\u{2029}f\r\ng");
+
+        assert_eq!(r.line_len(), 7);
+
+        let lines = r.lines().collect::<Vec<_>>();
+        assert_eq!(lines, ["a", "b", "c", "d", "e", "f", "g"]);
+
+        assert_eq!(r.line(3), "d");
+        assert_eq!(r.line_slice(3..4), "d\u{2028}");
+        assert_eq!(
+            r.byte_of_line(4),
+            "a\u{000B}b\u{000C}c\u{0085}d\u{2028}".len()
+        );
+    }
+
+    /// Ropes breaking their lines in different ways can be used side by
+    /// side.
+    #[test]
+    fn unicode_lines_next_to_lf_lines() {
+        let s = "a\u{000B}b\u{2028}c\nd";
+
+        let unicode = Rope::from(s);
+        let lf = crop::Rope::from(s);
+
+        assert_eq!(unicode.line_len(), 4);
+        assert_eq!(lf.line_len(), 2);
+
+        assert_eq!(unicode.line(1), "b");
+        assert_eq!(lf.line(0), "a\u{000B}b\u{2028}c");
+
+        assert_eq!(unicode, s);
+        assert_eq!(lf, s);
+    }
+
+    /// A carriage return is only stripped if it's followed by a line feed.
+    #[test]
+    fn unicode_lines_carriage_return() {
+        let r = Rope::from("a\r\u{000B}b\r\u{2028}c\r\n");
+
+        let lines = r.lines().collect::<Vec<_>>();
+        assert_eq!(lines, ["a\r", "b\r", "c"]);
+
+        let lines = r.lines().rev().collect::<Vec<_>>();
+        assert_eq!(lines, ["c", "b\r", "a\r"]);
+
+        assert_eq!(r.line(0), "a\r");
+        assert_eq!(r.line(2), "c");
+    }
+
+    /// The paragraphs are separated by lines that are blank between any two
+    /// line breaks.
+    #[cfg(feature = "paragraph-count")]
+    #[test]
+    fn unicode_lines_paragraphs() {
+        let s = "foo\u{2028}bar\u{2029}\u{0085}\u{2028}baz\r\n \u{000B}qux";
+        let r = Rope::from(s);
+
+        assert_eq!(r.paragraph_len(), 3);
+        assert_eq!(r.paragraphs().count(), 3);
+
+        assert_eq!(r.paragraph_slice(0..1), "foo\u{2028}bar");
+        assert_eq!(r.paragraph_slice(1..2), "baz");
+        assert_eq!(r.paragraph_slice(2..3), "qux");
+
+        assert_eq!(r.byte_of_paragraph(1), s.find("baz").unwrap());
+        assert_eq!(r.byte_of_paragraph(2), s.find("qux").unwrap());
+    }
+
+    #[test]
+    fn unicode_lines_trailing_break() {
+        let mut r = Rope::from("foo\u{2029}");
+        assert_eq!(r.line_len(), 1);
+
+        r.insert(r.byte_len(), "bar");
+        assert_eq!(r.line_len(), 2);
+
+        r.insert(r.byte_len(), "\u{0085}");
+        assert_eq!(r.line_len(), 2);
+        r.assert_invariants();
+
+        let s = r.byte_slice(..r.byte_len() - 2);
+        assert_eq!(s.line_len(), 2);
+        s.assert_invariants();
+    }
+
+    /// Tests every line break at every offset of a string spanning a few
+    /// machine words, so that some of them straddle two words.
+    #[test]
+    fn unicode_lines_word_boundaries() {
+        for br in BREAKS {
+            for offset in 0..24 {
+                let s = format!(
+                    "{}{br}{}é{br}",
+                    "a".repeat(offset),
+                    "b".repeat(7)
+                );
+                let r = Rope::from(s.as_str());
+                let lines = str_lines(&s);
+
+                assert_eq!(r.line_len(), lines.len());
+                assert_eq!(r.byte_of_line(1), offset + br.len());
+                assert_eq!(r.byte_of_line(2), s.len());
+                assert_eq!(r.lines().collect::<Vec<_>>(), lines);
+            }
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn unicode_lines_random() {
+        for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+            let s = with_random_breaks(s);
+            let r = Rope::from(s.as_str());
+            r.assert_invariants();
+
+            let lines = str_lines(&s);
+
+            assert_eq!(r.line_len(), lines.len());
+
+            for (i, (rope_line, str_line)) in
+                r.lines().zip(lines.iter()).enumerate()
+            {
+                assert_eq!(rope_line, *str_line);
+                assert_eq!(r.line(i), *str_line);
+            }
+
+            for (rope_line, str_line) in
+                r.lines().rev().zip(lines.iter().rev())
+            {
+                assert_eq!(rope_line, *str_line);
+            }
+        }
+    }
+}
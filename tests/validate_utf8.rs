@@ -0,0 +1,98 @@
+mod common;
+
+#[cfg(feature = "validate-utf8")]
+mod tests {
+    use crop::{Rope, RopeBuilder};
+    use rand::Rng;
+
+    use crate::common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL};
+
+    /// Tests that the validation done after every edit doesn't reject any
+    /// valid `Rope`.
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn random_edits_validated() {
+        let mut rng = rand::thread_rng();
+
+        for text in [SMALL, MEDIUM, CURSED_LIPSUM] {
+            let mut r = Rope::from(text);
+            let mut s = String::from(text);
+
+            for _ in 0..100 {
+                let mut start = rng.gen_range(0..=s.len());
+                while !s.is_char_boundary(start) {
+                    start -= 1;
+                }
+
+                let mut end = rng.gen_range(start..=s.len().min(start + 3000));
+                while !s.is_char_boundary(end) {
+                    end -= 1;
+                }
+
+                let inserted = &LARGE[..rng.gen_range(0..5000)];
+
+                match rng.gen_range(0..4) {
+                    0 => {
+                        r.insert(start, inserted);
+                        s.insert_str(start, inserted);
+                    },
+                    1 => {
+                        r.delete(start..end);
+                        s.replace_range(start..end, "");
+                    },
+                    2 => {
+                        let right = r.split_off(end);
+                        r.append(right);
+                    },
+                    _ => {
+                        r.replace(start..end, inserted);
+                        s.replace_range(start..end, inserted);
+                    },
+                }
+
+                assert_eq!(r, s);
+            }
+        }
+    }
+
+    /// Tests that the summary of a `Rope` is still the one of its chunks
+    /// after edits which move an emoji sequence or a flag across the
+    /// boundary between two chunks, and so turn the join in the middle of
+    /// it from one resolved inside a chunk into one that's still unsure
+    /// between two chunks, or viceversa.
+    #[cfg(feature = "grapheme-count")]
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn replace_across_zwj_validated() {
+        let text = "ab 👨\u{200D}👩\u{200D}👧 🇺🇸🇮🇹 ".repeat(300);
+
+        for start in (2000..2500).step_by(5) {
+            let mut start = start;
+            while !text.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = start + 3000;
+            while !text.is_char_boundary(end) {
+                end += 1;
+            }
+
+            for replacement in ["", "\u{200D}"] {
+                let mut r = Rope::from(text.as_str());
+                r.replace(start..end, replacement);
+                assert!(r.validate().is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn builder_validated() {
+        let mut b = RopeBuilder::new();
+
+        for line in MEDIUM.split_inclusive('\n') {
+            b.append(line);
+        }
+
+        assert_eq!(b.build(), MEDIUM);
+    }
+}
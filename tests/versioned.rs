@@ -0,0 +1,167 @@
+use crop::{Gravity, Rope, Tracked, Versioned};
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE};
+
+#[test]
+fn at_revision() {
+    let mut v = Versioned::new(Rope::from("Hello"));
+
+    assert_eq!(v.insert(5, " Earth"), 1);
+    assert_eq!(v.delete(0..1), 2);
+    assert_eq!(v.replace(0..4, "J"), 3);
+
+    assert_eq!(v.at_revision(0).unwrap(), "Hello");
+    assert_eq!(v.at_revision(1).unwrap(), "Hello Earth");
+    assert_eq!(v.at_revision(2).unwrap(), "ello Earth");
+    assert_eq!(v.at_revision(3).unwrap(), "J Earth");
+    assert_eq!(v.at_revision(4), None);
+}
+
+#[test]
+fn edit_without_changes() {
+    let mut v = Versioned::new(Rope::from("Hello"));
+
+    assert_eq!(v.edit(|_| {}), 0);
+    assert_eq!(v.at_revision(0).unwrap(), "Hello");
+
+    assert_eq!(
+        v.edit(|rope| {
+            rope.insert(0, "a");
+            rope.insert(0, "b");
+        }),
+        1
+    );
+
+    assert_eq!(v.at_revision(0).unwrap(), "Hello");
+    assert_eq!(v.at_revision(1).unwrap(), "baHello");
+    assert_eq!(v.at_revision(2), None);
+}
+
+/// Tests that the revisions of a `Versioned` are the ones of the `Tracked` it
+/// wraps, whose anchors and history follow the edits made through it.
+#[test]
+fn revisions_of_tracked() {
+    let mut s = "Hello Earth!\n".repeat(1000);
+
+    let mut t = Tracked::new(Rope::from(s.as_str()));
+    t.insert(0, "Oh, ");
+    s.insert_str(0, "Oh, ");
+    t.track_history();
+
+    let bang = t.anchor(15, Gravity::Right);
+
+    let mut v = Versioned::from_tracked(t, 8);
+    assert_eq!(v.revision(), 1);
+    let first = s.clone();
+
+    assert_eq!(v.replace(10..15, "Mars"), 2);
+    s.replace_range(10..15, "Mars");
+    let second = s.clone();
+
+    // The closure edits two ranges far apart, each of which is a revision.
+    assert_eq!(
+        v.edit(|rope| {
+            rope.replace(0..2, "Ah");
+            rope.delete(6_000..6_006);
+        }),
+        4
+    );
+    s.replace_range(0..2, "Ah");
+    s.replace_range(6_000..6_006, "");
+
+    assert_eq!(v.rope(), s.as_str());
+    assert_eq!(v.at_revision(1).unwrap(), first.as_str());
+    assert_eq!(v.at_revision(2).unwrap(), second.as_str());
+    assert_eq!(v.at_revision(3), None);
+
+    let t = v.tracked();
+    assert_eq!(t.revision(), 4);
+    assert_eq!(t.anchor_offset(bang), Some(14));
+    assert_eq!(t.resolve_offset(1, 16, Gravity::Left), Some(15));
+    assert_eq!(t.resolve_offset(2, 6_010, Gravity::Left), Some(6_004));
+
+    assert_eq!(v.into_tracked().rope(), s.as_str());
+}
+
+#[test]
+fn max_snapshots() {
+    let mut v = Versioned::with_max_snapshots(Rope::new(), 2);
+
+    for _ in 0..5 {
+        v.insert(0, "a");
+    }
+
+    assert_eq!(v.at_revision(2), None);
+    assert_eq!(v.at_revision(3).unwrap(), "aaa");
+    assert_eq!(v.at_revision(4).unwrap(), "aaaa");
+    assert_eq!(v.at_revision(5).unwrap(), "aaaaa");
+
+    let mut v = Versioned::with_max_snapshots(Rope::new(), 0);
+    v.insert(0, "a");
+    assert_eq!(v.at_revision(0), None);
+    assert_eq!(v.into_rope(), "a");
+}
+
+#[test]
+fn changed_bytes() {
+    let mut v = Versioned::new(Rope::from(LARGE));
+
+    v.replace(1000..1010, "Hello");
+    v.insert(2000, "🐸");
+
+    assert!(v.changed_bytes(0, 0).unwrap().is_empty());
+    assert_eq!(v.changed_bytes(0, 1), Some(1000..1005));
+    assert_eq!(v.changed_bytes(1, 2), Some(2000..2004));
+    assert_eq!(v.changed_bytes(0, 2), Some(1000..2004));
+    assert_eq!(v.changed_bytes(2, 0), Some(1000..2005));
+    assert_eq!(v.changed_bytes(0, 3), None);
+}
+
+/// Tests that the changed byte range between any two revisions covers all the
+/// bytes that differ between them.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn random_changed_bytes() {
+    let mut rng = rand::thread_rng();
+
+    let mut v = Versioned::new(Rope::from(CURSED_LIPSUM));
+
+    for _ in 0..30 {
+        let r = v.rope();
+
+        let mut start = rng.gen_range(0..=r.byte_len());
+        while !r.is_char_boundary(start) {
+            start -= 1;
+        }
+
+        let mut end = rng.gen_range(start..=r.byte_len().min(start + 20));
+        while !r.is_char_boundary(end) {
+            end -= 1;
+        }
+
+        v.replace(start..end, ["", "a", "🐸"][rng.gen_range(0..3)]);
+    }
+
+    for from in 0..=v.revision() {
+        for to in 0..=v.revision() {
+            let old = v.at_revision(from).unwrap();
+            let new = v.at_revision(to).unwrap();
+
+            let changed = v.changed_bytes(from, to).unwrap();
+            let unchanged_suffix = new.byte_len() - changed.end;
+
+            assert_eq!(
+                old.byte_slice(..changed.start),
+                new.byte_slice(..changed.start)
+            );
+
+            assert_eq!(
+                old.byte_slice(old.byte_len() - unchanged_suffix..),
+                new.byte_slice(changed.end..)
+            );
+        }
+    }
+}
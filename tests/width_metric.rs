@@ -0,0 +1,143 @@
+mod common;
+
+#[cfg(feature = "width-metric")]
+mod tests {
+    use crop::Rope;
+    use rand::Rng;
+    use unicode_width::UnicodeWidthChar;
+
+    use crate::common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TEXT_EMOJI};
+
+    fn width(s: &str) -> usize {
+        s.chars().map(|ch| ch.width().unwrap_or(0)).sum()
+    }
+
+    /// The first byte offset at which the display width of `s` reaches
+    /// `column`.
+    fn byte_of_column(s: &str, column: usize) -> usize {
+        let mut seen = 0;
+
+        for (idx, ch) in s.char_indices() {
+            if seen >= column {
+                return idx;
+            }
+            seen += ch.width().unwrap_or(0);
+        }
+
+        s.len()
+    }
+
+    #[test]
+    fn width_0() {
+        let r = Rope::new();
+        assert_eq!(r.width(), 0);
+
+        let r = Rope::from(TEXT_EMOJI);
+        assert_eq!(r.width(), width(TEXT_EMOJI));
+
+        let s = r.byte_slice(16..39);
+        assert_eq!(s.width(), width(&TEXT_EMOJI[16..39]));
+    }
+
+    #[test]
+    fn control_chars() {
+        let r = Rope::from("a\tb\r\nc\u{7}");
+        assert_eq!(r.width(), 3);
+        assert_eq!(r.line(0).width(), 2);
+        assert_eq!(r.line(1).width(), 1);
+    }
+
+    #[should_panic]
+    #[test]
+    fn byte_of_column_out_of_bounds() {
+        let r = Rope::from("a中b");
+        let _ = r.byte_of_column(5);
+    }
+
+    #[test]
+    fn line_widths() {
+        for s in [CURSED_LIPSUM, SMALL, MEDIUM] {
+            let r = Rope::from(s);
+
+            for (line, expected) in r.lines().zip(s.lines()) {
+                assert_eq!(line.width(), width(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn conversions_random() {
+        let mut rng = rand::thread_rng();
+
+        for s in [CURSED_LIPSUM, SMALL, MEDIUM, LARGE] {
+            let r = Rope::from(s);
+
+            assert_eq!(r.width(), width(s));
+
+            for _ in 0..100 {
+                let mut byte_offset = rng.gen_range(0..=s.len());
+
+                while !s.is_char_boundary(byte_offset) {
+                    byte_offset -= 1;
+                }
+
+                assert_eq!(
+                    r.column_of_byte(byte_offset),
+                    width(&s[..byte_offset])
+                );
+
+                let column = rng.gen_range(0..=r.width());
+
+                assert_eq!(
+                    r.byte_of_column(column),
+                    byte_of_column(s, column)
+                );
+            }
+        }
+    }
+
+    /// Tests that the width stays in sync with the text as the `Rope` is
+    /// edited.
+    #[test]
+    fn width_after_edits() {
+        let mut rng = rand::thread_rng();
+
+        let mut r = Rope::from(SMALL);
+        let mut s = String::from(SMALL);
+
+        for _ in 0..200 {
+            let mut start = rng.gen_range(0..=s.len());
+
+            while !s.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=(start + 20).min(s.len()));
+
+            while !s.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            let text = CURSED_LIPSUM
+                .chars()
+                .take(rng.gen_range(0..8))
+                .collect::<String>();
+
+            r.replace(start..end, &text);
+            s.replace_range(start..end, &text);
+
+            r.assert_invariants();
+            assert_eq!(r.width(), width(&s));
+        }
+    }
+
+    #[cfg_attr(miri, ignore)]
+    #[test]
+    fn max_line_width() {
+        for s in [CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TEXT_EMOJI] {
+            let r = Rope::from(s);
+            let expected = s.lines().map(width).max().unwrap_or(0);
+            assert_eq!(r.max_line_width(), expected);
+        }
+    }
+}
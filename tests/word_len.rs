@@ -0,0 +1,82 @@
+use crop::Rope;
+use rand::Rng;
+
+mod common;
+
+use common::{CURSED_LIPSUM, LARGE, MEDIUM, SMALL, TINY};
+
+#[test]
+fn word_len_empty() {
+    assert_eq!(Rope::new().word_len(), 0);
+    assert_eq!(Rope::from(" \n\t\r\n").word_len(), 0);
+}
+
+#[test]
+fn word_len_unicode_whitespace() {
+    let r = Rope::from("a\u{3000}b\u{a0}c\u{2028}d");
+    assert_eq!(r.word_len(), 4);
+}
+
+/// Tests that the word count of random slices is the same as the number of
+/// whitespace-separated words in the corresponding string slices.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn word_len_random_slices() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, LARGE, CURSED_LIPSUM] {
+        let r = Rope::from(s);
+
+        assert_eq!(r.word_len(), s.split_whitespace().count());
+
+        for _ in 0..100 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(start) {
+                start -= 1;
+            }
+
+            let mut end = rng.gen_range(start..=r.byte_len());
+            while !r.is_char_boundary(end) {
+                end -= 1;
+            }
+
+            assert_eq!(
+                r.byte_slice(start..end).word_len(),
+                s[start..end].split_whitespace().count()
+            );
+        }
+    }
+}
+
+/// Tests that the word count is kept up to date while editing, including
+/// when words are joined or split at the edges of the edits.
+#[cfg_attr(miri, ignore)]
+#[test]
+fn word_len_after_random_edits() {
+    let mut rng = rand::thread_rng();
+
+    for s in [TINY, SMALL, MEDIUM, CURSED_LIPSUM] {
+        let mut r = Rope::from(s);
+        let mut s = s.to_owned();
+
+        for _ in 0..100 {
+            let mut start = rng.gen_range(0..=r.byte_len());
+            while !r.is_char_boundary(start) {
+                start -= 1;
+            }
+            let mut end = (start + rng.gen_range(0..300)).min(r.byte_len());
+            while !r.is_char_boundary(end) {
+                end -= 1;
+            }
+            let text =
+                [" ", "a", "b c", "", "\u{3000}", "foo "][rng.gen_range(0..6)];
+
+            r.replace(start..end, text);
+            s.replace_range(start..end, text);
+
+            assert_eq!(r.word_len(), s.split_whitespace().count());
+        }
+
+        r.assert_invariants();
+    }
+}